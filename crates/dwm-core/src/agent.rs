@@ -0,0 +1,4107 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::meta::{self, RepoMeta};
+use crate::vcs;
+
+/// How long before a status file is considered stale and ignored.
+const STALE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How long a waiting session can go unheard from before it's presumed
+/// dead. Agents report nothing while they wait for input, so this is much
+/// longer than [`STALE_TIMEOUT`].
+const WAITING_STALE_TIMEOUT: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// How long, in seconds, a session can go unheard from before it's treated
+/// as dead, from the `stale_timeouts` config. `default` replaces the ten
+/// minutes working and idle sessions get; each status can be set on its
+/// own. Waiting sessions get twelve hours unless `waiting` is set.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StaleTimeouts {
+    pub default: Option<u64>,
+    pub working: Option<u64>,
+    pub waiting: Option<u64>,
+    pub idle: Option<u64>,
+}
+
+impl StaleTimeouts {
+    /// The timeouts configured in `dwm_base`'s config, or the defaults if
+    /// it can't be read.
+    pub fn load(dwm_base: &Path) -> Self {
+        // Only this key of the config is read here; the rest is dwm's own.
+        #[derive(Default, Deserialize)]
+        #[serde(default)]
+        struct Config {
+            stale_timeouts: StaleTimeouts,
+        }
+        fs::read_to_string(dwm_base.join("config.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Config>(&json).ok())
+            .map(|config| config.stale_timeouts)
+            .unwrap_or_default()
+    }
+
+    /// The timeouts for the repo whose dwm dir is `repo_dir`.
+    fn for_repo(repo_dir: &Path) -> Self {
+        repo_dir.parent().map(Self::load).unwrap_or_default()
+    }
+
+    /// How long a session in `status` can go unheard from.
+    fn of(&self, status: AgentStatus) -> Duration {
+        let secs = match status.coarse() {
+            AgentStatus::Waiting => self.waiting,
+            AgentStatus::Idle => self.idle.or(self.default),
+            _ => self.working.or(self.default),
+        };
+        match (secs, status.coarse()) {
+            (Some(secs), _) => Duration::from_secs(secs),
+            (None, AgentStatus::Waiting) => WAITING_STALE_TIMEOUT,
+            (None, _) => STALE_TIMEOUT,
+        }
+    }
+}
+
+/// Longest prompt excerpt kept in a status file, in characters.
+const PROMPT_EXCERPT_LEN: usize = 200;
+
+/// Most files a status file remembers a session touching.
+const TOUCHED_LIMIT: usize = 20;
+
+/// Set for agents started by `dwm launch` to the session id it registered,
+/// so their hooks report under that id rather than the agent's own.
+const LAUNCH_SESSION_ENV: &str = "DWM_SESSION_ID";
+
+/// A coding agent that dwm can track. Each reports its state through its
+/// own hook or notification mechanism, which `dwm hook-handler` normalizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum AgentKind {
+    #[default]
+    Claude,
+    Codex,
+    Gemini,
+    Opencode,
+    Aider,
+}
+
+impl AgentKind {
+    pub const ALL: [AgentKind; 5] = [
+        AgentKind::Claude,
+        AgentKind::Codex,
+        AgentKind::Gemini,
+        AgentKind::Opencode,
+        AgentKind::Aider,
+    ];
+
+    /// Short name, as used for `--agent` and in status files.
+    pub fn name(self) -> &'static str {
+        match self {
+            AgentKind::Claude => "claude",
+            AgentKind::Codex => "codex",
+            AgentKind::Gemini => "gemini",
+            AgentKind::Opencode => "opencode",
+            AgentKind::Aider => "aider",
+        }
+    }
+
+    /// Environment variable the agent reads its model from, which its hooks
+    /// inherit.
+    pub fn model_env(self) -> Option<&'static str> {
+        match self {
+            AgentKind::Claude => Some("ANTHROPIC_MODEL"),
+            AgentKind::Gemini => Some("GEMINI_MODEL"),
+            AgentKind::Aider => Some("AIDER_MODEL"),
+            AgentKind::Codex | AgentKind::Opencode => None,
+        }
+    }
+
+    /// Product name, for setup prompts.
+    pub fn label(self) -> &'static str {
+        match self {
+            AgentKind::Claude => "Claude Code",
+            AgentKind::Codex => "Codex",
+            AgentKind::Gemini => "Gemini CLI",
+            AgentKind::Opencode => "opencode",
+            AgentKind::Aider => "Aider",
+        }
+    }
+}
+
+/// Possible states of an agent session. `Thinking` and `RunningTool` are
+/// finer kinds of `Working`, for agents whose hooks tell them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentStatus {
+    Working,
+    Idle,
+    Waiting,
+    /// Working, between tools: the model is generating.
+    Thinking,
+    /// Working, with a tool running.
+    RunningTool,
+}
+
+impl AgentStatus {
+    /// The status with `Thinking` and `RunningTool` folded into `Working`:
+    /// what's counted, colored, logged to history, and hooked.
+    pub fn coarse(self) -> Self {
+        match self {
+            AgentStatus::Thinking | AgentStatus::RunningTool => AgentStatus::Working,
+            status => status,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AgentStatus::Working => "working",
+            AgentStatus::Idle => "idle",
+            AgentStatus::Waiting => "waiting",
+            AgentStatus::Thinking => "thinking",
+            AgentStatus::RunningTool => "running tool",
+        }
+    }
+}
+
+/// On-disk representation of a single agent's status file.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AgentStatusFile {
+    /// Which agent wrote the file; files written before other agents were
+    /// supported are Claude Code's.
+    #[serde(default)]
+    pub agent: AgentKind,
+    /// The model the agent runs, if it said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub workspace: String,
+    pub status: AgentStatus,
+    /// When the session's first event was recorded; missing from files
+    /// written before it was tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    pub updated_at: u64,
+    /// First line of the most recent prompt, truncated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_prompt: Option<String>,
+    /// Name of the most recent tool used since that prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_tool: Option<String>,
+    /// Files the session's tools targeted, most recent first, relative to
+    /// its directory where they're inside it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub touched: Vec<String>,
+    /// When the session started waiting for input, while it still is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_since: Option<u64>,
+}
+
+impl AgentStatusFile {
+    /// Whether the session hasn't been heard from for too long to still be
+    /// alive at `now` (unix seconds).
+    fn is_stale(&self, now: u64, timeouts: &StaleTimeouts) -> bool {
+        now.saturating_sub(self.updated_at) > timeouts.of(self.status).as_secs()
+    }
+
+    /// When the session started waiting, if it is. Files written before this
+    /// was tracked count from their last update.
+    fn waiting_since(&self) -> Option<u64> {
+        (self.status == AgentStatus::Waiting).then(|| self.waiting_since.unwrap_or(self.updated_at))
+    }
+}
+
+/// What a hook event says the agent is doing, recorded alongside its status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentActivity<'a> {
+    /// Keep the previously recorded prompt and tool.
+    Unchanged,
+    /// A new prompt was submitted; replaces the prompt and clears the tool.
+    Prompt(&'a str),
+    /// A tool is about to run, on the file given if it names one; replaces
+    /// the tool and adds the file to those touched.
+    Tool(&'a str, Option<&'a str>),
+}
+
+/// One live agent session, as shown in the picker's agent popup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentSession {
+    pub session_id: String,
+    pub agent: AgentKind,
+    pub model: Option<String>,
+    pub status: AgentStatus,
+    pub started_at: Option<SystemTime>,
+    pub updated_at: SystemTime,
+    pub last_prompt: Option<String>,
+    pub last_tool: Option<String>,
+    /// Files touched, most recent first.
+    pub touched: Vec<String>,
+    /// When it started waiting for input, if it's waiting.
+    pub waiting_since: Option<SystemTime>,
+}
+
+impl AgentSession {
+    /// The agent and its model, e.g. `claude-opus-4-1`; see [`agent_label`].
+    pub fn label(&self) -> String {
+        agent_label(self.agent, self.model.as_deref())
+    }
+}
+
+/// Name an agent session by its agent and, if known, its model, shortened
+/// to tell sessions apart: no provider prefix, release date, or repeat of
+/// the agent's name, so `anthropic/claude-opus-4-1-20250805` under Claude
+/// Code is `claude-opus-4-1`.
+pub fn agent_label(agent: AgentKind, model: Option<&str>) -> String {
+    let Some(model) = model else {
+        return agent.name().to_string();
+    };
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let model = match model.rsplit_once('-') {
+        Some((rest, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => rest,
+        _ => model,
+    };
+    let model = model
+        .strip_prefix(agent.name())
+        .and_then(|m| m.strip_prefix('-'))
+        .unwrap_or(model);
+    if model.is_empty() || model == agent.name() {
+        agent.name().to_string()
+    } else {
+        format!("{}-{}", agent.name(), model)
+    }
+}
+
+/// Aggregated agent counts for a single workspace.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentSummary {
+    pub waiting: u32,
+    pub working: u32,
+    pub idle: u32,
+    /// Each live session's [`agent_label`] and status; when set, these are
+    /// shown instead of the bare counts.
+    pub labels: Vec<(String, AgentStatus)>,
+    /// When the longest-waiting session started waiting.
+    pub waiting_since: Option<SystemTime>,
+}
+
+impl AgentSummary {
+    pub fn is_empty(&self) -> bool {
+        self.waiting == 0 && self.working == 0 && self.idle == 0
+    }
+
+    /// Return the most urgent status present, for color selection.
+    pub fn most_urgent(&self) -> Option<AgentStatus> {
+        if self.waiting > 0 {
+            Some(AgentStatus::Waiting)
+        } else if self.working > 0 {
+            Some(AgentStatus::Working)
+        } else if self.idle > 0 {
+            Some(AgentStatus::Idle)
+        } else {
+            None
+        }
+    }
+
+    /// How long the longest-waiting session has been waiting at `now`.
+    pub fn waiting_for(&self, now: SystemTime) -> Option<Duration> {
+        self.waiting_since
+            .map(|since| now.duration_since(since).unwrap_or(Duration::ZERO))
+    }
+
+    /// ` for 12m` after the first waiting part of the summary.
+    fn waited(&self) -> String {
+        self.waiting_for(SystemTime::now())
+            .map(|time| format!(" for {}", format_duration(time)))
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for AgentSummary {
+    /// `claude-opus waiting for 12m, 2 codex working`, most urgent first;
+    /// just the counts, `1 waiting for 12m, 2 working`, without labels.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        let mut waited = Some(self.waited());
+        if !self.labels.is_empty() {
+            let mut groups: Vec<(AgentStatus, &str, u32)> = Vec::new();
+            for (label, status) in &self.labels {
+                match groups
+                    .iter_mut()
+                    .find(|(s, l, _)| s == status && l == label)
+                {
+                    Some(group) => group.2 += 1,
+                    None => groups.push((*status, label, 1)),
+                }
+            }
+            groups.sort_by_key(|&(status, label, _)| (urgency(status), label));
+            for (status, label, count) in groups {
+                let waited = match status {
+                    AgentStatus::Waiting => waited.take().unwrap_or_default(),
+                    _ => String::new(),
+                };
+                parts.push(match count {
+                    1 => format!("{} {}{}", label, status.label(), waited),
+                    n => format!("{} {} {}{}", n, label, status.label(), waited),
+                });
+            }
+            return write!(f, "{}", parts.join(", "));
+        }
+        if self.waiting > 0 {
+            parts.push(format!(
+                "{} waiting{}",
+                self.waiting,
+                waited.unwrap_or_default()
+            ));
+        }
+        if self.working > 0 {
+            parts.push(format!("{} working", self.working));
+        }
+        if self.idle > 0 {
+            parts.push(format!("{} idle", self.idle));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Return the `.agent-status` directory for a repo.
+pub fn agent_status_dir(repo_dir: &Path) -> PathBuf {
+    repo_dir.join(".agent-status")
+}
+
+/// The `.agent-status` directories of every dwm-managed repo, to watch for
+/// changes.
+pub fn all_agent_status_dirs() -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    agent_status_dirs_at(&home.join(".dwm"))
+}
+
+fn agent_status_dirs_at(dwm_base: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dwm_base) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|repo_dir| meta::is_repo_dir(repo_dir))
+        .map(|repo_dir| agent_status_dir(&repo_dir))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Convert a unix timestamp to a [`SystemTime`].
+pub fn system_time_from_epoch_secs(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// Read all agent status files for a repo and return per-workspace summaries.
+///
+/// Stale entries (see [`StaleTimeouts`]) are silently ignored.
+pub fn read_agent_summaries(repo_dir: &Path) -> HashMap<String, AgentSummary> {
+    read_agent_summaries_at(
+        repo_dir,
+        SystemTime::now(),
+        &StaleTimeouts::for_repo(repo_dir),
+    )
+}
+
+fn read_agent_summaries_at(
+    repo_dir: &Path,
+    now: SystemTime,
+    timeouts: &StaleTimeouts,
+) -> HashMap<String, AgentSummary> {
+    let dir = agent_status_dir(repo_dir);
+    let mut map: HashMap<String, AgentSummary> = HashMap::new();
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return map,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        let status_file: AgentStatusFile = match serde_json::from_str(&content) {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+
+        if status_file.is_stale(epoch_secs(now), timeouts) {
+            continue;
+        }
+
+        let summary = map.entry(status_file.workspace.clone()).or_default();
+        if let Some(since) = status_file.waiting_since().map(system_time_from_epoch_secs) {
+            summary.waiting_since = Some(summary.waiting_since.map_or(since, |s| s.min(since)));
+        }
+        summary.labels.push((
+            agent_label(status_file.agent, status_file.model.as_deref()),
+            status_file.status,
+        ));
+        match status_file.status.coarse() {
+            AgentStatus::Waiting => summary.waiting += 1,
+            AgentStatus::Idle => summary.idle += 1,
+            _ => summary.working += 1,
+        }
+    }
+
+    map
+}
+
+/// Read the live (non-stale) agent sessions in `workspace`, most urgent
+/// first and most recently updated first within a status.
+pub fn read_agent_sessions(repo_dir: &Path, workspace: &str) -> Vec<AgentSession> {
+    read_agent_sessions_at(
+        repo_dir,
+        workspace,
+        SystemTime::now(),
+        &StaleTimeouts::for_repo(repo_dir),
+    )
+}
+
+/// The files agents in `workspace` touched, most recently active session
+/// first, without repeats.
+pub fn workspace_touched(repo_dir: &Path, workspace: &str) -> Vec<String> {
+    let mut sessions = read_agent_sessions(repo_dir, workspace);
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+    let mut touched: Vec<String> = Vec::new();
+    for path in sessions.into_iter().flat_map(|s| s.touched) {
+        if !touched.contains(&path) {
+            touched.push(path);
+        }
+    }
+    touched
+}
+
+fn read_agent_sessions_at(
+    repo_dir: &Path,
+    workspace: &str,
+    now: SystemTime,
+    timeouts: &StaleTimeouts,
+) -> Vec<AgentSession> {
+    let mut sessions: Vec<AgentSession> = read_live_sessions_at(repo_dir, now, timeouts)
+        .into_iter()
+        .filter(|(ws, _)| ws == workspace)
+        .map(|(_, session)| session)
+        .collect();
+    sessions.sort_by(session_order);
+    sessions
+}
+
+/// Read every live agent session in a repo, paired with its workspace name.
+fn read_live_sessions_at(
+    repo_dir: &Path,
+    now: SystemTime,
+    timeouts: &StaleTimeouts,
+) -> Vec<(String, AgentSession)> {
+    let entries = match fs::read_dir(agent_status_dir(repo_dir)) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut sessions = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(session_id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if session_id.starts_with(".tmp-") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(status_file) = serde_json::from_str::<AgentStatusFile>(&content) else {
+            continue;
+        };
+        if status_file.is_stale(epoch_secs(now), timeouts) {
+            continue;
+        }
+        let updated_at = system_time_from_epoch_secs(status_file.updated_at);
+        let waiting_since = status_file.waiting_since().map(system_time_from_epoch_secs);
+        sessions.push((
+            status_file.workspace,
+            AgentSession {
+                session_id: session_id.to_string(),
+                agent: status_file.agent,
+                model: status_file.model,
+                status: status_file.status,
+                started_at: status_file.started_at.map(system_time_from_epoch_secs),
+                updated_at,
+                last_prompt: status_file.last_prompt,
+                last_tool: status_file.last_tool,
+                touched: status_file.touched,
+                waiting_since,
+            },
+        ));
+    }
+    sessions
+}
+
+/// Sort key putting the statuses that most need attention first.
+fn urgency(status: AgentStatus) -> u8 {
+    match status.coarse() {
+        AgentStatus::Waiting => 0,
+        AgentStatus::Idle => 2,
+        _ => 1,
+    }
+}
+
+/// Most urgent status first, longest waiting first among waiting sessions,
+/// then most recently updated.
+fn session_order(a: &AgentSession, b: &AgentSession) -> std::cmp::Ordering {
+    urgency(a.status)
+        .cmp(&urgency(b.status))
+        .then(a.waiting_since.cmp(&b.waiting_since))
+        .then(b.updated_at.cmp(&a.updated_at))
+}
+
+/// A live agent session anywhere under dwm, as listed by `dwm agents`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentListing {
+    /// Name of the repo's main checkout.
+    pub repo: String,
+    pub workspace: String,
+    pub session: AgentSession,
+}
+
+impl AgentListing {
+    /// The session's latest prompt or tool, for a one-line summary.
+    pub fn last_event(&self) -> String {
+        match (&self.session.last_tool, &self.session.last_prompt) {
+            (Some(tool), _) => format!("tool: {}", tool),
+            (None, Some(prompt)) => format!("prompt: {}", prompt),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// List the live agent sessions in every dwm-managed repo, most urgent first.
+pub fn list_all_agent_sessions() -> Result<Vec<AgentListing>> {
+    let home = dirs::home_dir().context("could not determine home directory")?;
+    let dwm_base = home.join(".dwm");
+    Ok(list_all_agent_sessions_at(
+        &dwm_base,
+        SystemTime::now(),
+        &StaleTimeouts::load(&dwm_base),
+    ))
+}
+
+fn list_all_agent_sessions_at(
+    dwm_base: &Path,
+    now: SystemTime,
+    timeouts: &StaleTimeouts,
+) -> Vec<AgentListing> {
+    let Ok(entries) = fs::read_dir(dwm_base) else {
+        return Vec::new();
+    };
+    let mut listings = Vec::new();
+    for entry in entries.flatten() {
+        let repo_dir = entry.path();
+        let Some(repo) = repo_name(&repo_dir) else {
+            continue;
+        };
+        for (workspace, session) in read_live_sessions_at(&repo_dir, now, timeouts) {
+            listings.push(AgentListing {
+                repo: repo.clone(),
+                workspace,
+                session,
+            });
+        }
+    }
+    listings.sort_by(|a, b| {
+        session_order(&a.session, &b.session)
+            .then_with(|| a.repo.cmp(&b.repo))
+            .then_with(|| a.workspace.cmp(&b.workspace))
+    });
+    listings
+}
+
+/// The name of the repo a dwm repo dir belongs to: its main repo's basename,
+/// falling back to the dir's own name. `None` if it isn't a dwm repo dir.
+pub fn repo_name(repo_dir: &Path) -> Option<String> {
+    let meta = RepoMeta::load(repo_dir).ok()??;
+    meta.main_repo
+        .file_name()
+        .or_else(|| repo_dir.file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+}
+
+pub fn epoch_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// The agent summary of the workspace `cwd` is in, if it has any agents.
+pub fn current_summary(dwm_base: &Path, cwd: &Path) -> Option<AgentSummary> {
+    let (repo_dir, workspace) = resolve_workspace_from_cwd(dwm_base, cwd)?;
+    read_agent_summaries(&repo_dir).remove(&workspace)
+}
+
+/// Shorten a prompt to its first line, at most [`PROMPT_EXCERPT_LEN`] chars.
+fn prompt_excerpt(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or("");
+    if line.chars().count() > PROMPT_EXCERPT_LEN {
+        let cut: String = line.chars().take(PROMPT_EXCERPT_LEN - 1).collect();
+        format!("{}…", cut)
+    } else {
+        line.to_string()
+    }
+}
+
+/// Write an agent status file for the given session, recording `activity`
+/// and `model` on top of whatever the session's previous file held. Returns
+/// the status change this logged to the workspace's history, if it was one.
+pub fn write_agent_status(
+    repo_dir: &Path,
+    session_id: &str,
+    agent: AgentKind,
+    workspace: &str,
+    status: AgentStatus,
+    activity: AgentActivity,
+    model: Option<&str>,
+) -> Result<Option<Transition>> {
+    let dir = agent_status_dir(repo_dir);
+    fs::create_dir_all(&dir)?;
+
+    let final_path = dir.join(format!("{}.json", session_id));
+    let previous = fs::read_to_string(&final_path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<AgentStatusFile>(&c).ok());
+    let updated_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // History is best-effort; never fail the hook over it.
+    let transition = record_transition(
+        repo_dir,
+        workspace,
+        session_id,
+        agent,
+        previous.as_ref(),
+        status,
+        updated_at,
+    )
+    .ok()
+    .flatten();
+    // A session still waiting keeps waiting from when it started to.
+    let timeouts = StaleTimeouts::for_repo(repo_dir);
+    let waiting_since = match &previous {
+        Some(p) if status == AgentStatus::Waiting && !p.is_stale(updated_at, &timeouts) => {
+            p.waiting_since()
+        }
+        _ => None,
+    }
+    .or((status == AgentStatus::Waiting).then_some(updated_at));
+    let (started_at, previous_model, mut last_prompt, mut last_tool, mut touched) = match previous {
+        Some(p) => (p.started_at, p.model, p.last_prompt, p.last_tool, p.touched),
+        None => (Some(updated_at), None, None, None, Vec::new()),
+    };
+    match activity {
+        AgentActivity::Unchanged => {}
+        AgentActivity::Prompt(prompt) => {
+            last_prompt = Some(prompt_excerpt(prompt)).filter(|p| !p.is_empty());
+            last_tool = None;
+        }
+        AgentActivity::Tool(tool, path) => {
+            last_tool = Some(tool.to_string());
+            if let Some(path) = path {
+                touched.retain(|p| p != path);
+                touched.insert(0, path.to_string());
+                touched.truncate(TOUCHED_LIMIT);
+            }
+        }
+    }
+
+    let file = AgentStatusFile {
+        agent,
+        model: model.map(str::to_string).or(previous_model),
+        workspace: workspace.to_string(),
+        status,
+        started_at,
+        updated_at,
+        last_prompt,
+        last_tool,
+        touched,
+        waiting_since,
+    };
+    let json = serde_json::to_string(&file)?;
+
+    // Atomic write: write to temp file, then rename
+    let tmp_path = dir.join(format!(".tmp-{}.json", session_id));
+    fs::write(&tmp_path, &json)?;
+    fs::rename(&tmp_path, &final_path)?;
+
+    Ok(transition)
+}
+
+/// Remove the agent status file for the given session. Returns the session's
+/// end as logged to its workspace's history, if it had a status file.
+pub fn remove_agent_status(repo_dir: &Path, session_id: &str) -> Result<Option<Transition>> {
+    let path = agent_status_dir(repo_dir).join(format!("{}.json", session_id));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let ended = fs::read_to_string(&path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<AgentStatusFile>(&c).ok())
+        .map(|previous| {
+            let transition = Transition {
+                session_id: session_id.to_string(),
+                agent: previous.agent,
+                status: None,
+                from: Some(previous.status.coarse()),
+                at: epoch_secs(SystemTime::now()),
+            };
+            let _ = append_history(repo_dir, &previous.workspace, &transition);
+            transition
+        });
+    fs::remove_file(&path)?;
+    Ok(ended)
+}
+
+/// Remove all agent status files for a given workspace name.
+/// Used when a workspace is deleted.
+pub fn remove_agent_statuses_for_workspace(repo_dir: &Path, workspace: &str) {
+    let dir = agent_status_dir(repo_dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        if let Ok(sf) = serde_json::from_str::<AgentStatusFile>(&content)
+            && sf.workspace == workspace
+        {
+            let _ = fs::remove_file(&path);
+        }
+    }
+    let _ = fs::remove_file(history_path(repo_dir, workspace));
+}
+
+/// What [`gc_agent_status`] cleaned up.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Status files of sessions that stopped reporting without ending.
+    pub stale_files: usize,
+    /// Repos whose main repo is gone, with their status and history removed.
+    pub untracked_repos: usize,
+}
+
+impl std::fmt::Display for GcReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        let mut parts = Vec::new();
+        if self.stale_files > 0 {
+            parts.push(format!(
+                "removed {} stale agent status file{}",
+                self.stale_files,
+                plural(self.stale_files)
+            ));
+        }
+        if self.untracked_repos > 0 {
+            parts.push(format!(
+                "cleared agent status for {} untracked repo{}",
+                self.untracked_repos,
+                plural(self.untracked_repos)
+            ));
+        }
+        if parts.is_empty() {
+            write!(f, "nothing to clean up")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// Remove agent status left behind in every dwm-managed repo: status files
+/// of sessions that died without ending, and all agent status and history
+/// of repos whose main repo no longer exists.
+pub fn gc_agent_status() -> Result<GcReport> {
+    let home = dirs::home_dir().context("could not determine home directory")?;
+    let dwm_base = home.join(".dwm");
+    Ok(gc_agent_status_at(
+        &dwm_base,
+        SystemTime::now(),
+        &StaleTimeouts::load(&dwm_base),
+    ))
+}
+
+fn gc_agent_status_at(dwm_base: &Path, now: SystemTime, timeouts: &StaleTimeouts) -> GcReport {
+    let mut report = GcReport::default();
+    let Ok(entries) = fs::read_dir(dwm_base) else {
+        return report;
+    };
+    for entry in entries.flatten() {
+        let repo_dir = entry.path();
+        let Ok(Some(meta)) = RepoMeta::load(&repo_dir) else {
+            continue;
+        };
+        if meta.main_repo.exists() {
+            report.stale_files += remove_stale_statuses(&repo_dir, now, timeouts);
+            continue;
+        }
+        let mut removed = false;
+        for dir in [agent_status_dir(&repo_dir), repo_dir.join(".agent-history")] {
+            removed |= dir.exists() && fs::remove_dir_all(&dir).is_ok();
+        }
+        if removed {
+            report.untracked_repos += 1;
+        }
+    }
+    report
+}
+
+/// Remove the status files in `repo_dir` of sessions gone stale (see
+/// [`StaleTimeouts`]), ending each session in its history where it was last
+/// heard from. Leftover temp files go too. Returns how many were removed.
+fn remove_stale_statuses(repo_dir: &Path, now: SystemTime, timeouts: &StaleTimeouts) -> usize {
+    let Ok(entries) = fs::read_dir(agent_status_dir(repo_dir)) else {
+        return 0;
+    };
+    let now = epoch_secs(now);
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let status_file = fs::read_to_string(&path)
+            .ok()
+            .and_then(|c| serde_json::from_str::<AgentStatusFile>(&c).ok());
+        // Temp and unreadable files are judged by when they were written.
+        let updated_at = match &status_file {
+            Some(file) => file.updated_at,
+            None => match entry.metadata().and_then(|m| m.modified()) {
+                Ok(modified) => epoch_secs(modified),
+                Err(_) => continue,
+            },
+        };
+        let stale = match &status_file {
+            Some(file) => file.is_stale(now, timeouts),
+            None => now.saturating_sub(updated_at) > timeouts.of(AgentStatus::Idle).as_secs(),
+        };
+        if !stale {
+            continue;
+        }
+        if fs::remove_file(&path).is_err() {
+            continue;
+        }
+        removed += 1;
+        let session_id = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        if let Some(file) = status_file
+            && !session_id.starts_with(".tmp-")
+        {
+            let ended = Transition {
+                session_id: session_id.to_string(),
+                agent: file.agent,
+                status: None,
+                from: Some(file.status.coarse()),
+                at: file.updated_at,
+            };
+            let _ = append_history(repo_dir, &file.workspace, &ended);
+        }
+    }
+    removed
+}
+
+// ---------------------------------------------------------------------------
+// Status history
+// ---------------------------------------------------------------------------
+
+/// Most transitions kept in a workspace's history log; older ones are
+/// dropped as new ones arrive.
+const HISTORY_LIMIT: usize = 500;
+
+/// One status change of one session, as a line of a workspace's history log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub session_id: String,
+    #[serde(default)]
+    pub agent: AgentKind,
+    /// The session's new status, or `None` when it ended.
+    pub status: Option<AgentStatus>,
+    /// Its status before, if it had one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<AgentStatus>,
+    /// Unix seconds.
+    pub at: u64,
+}
+
+/// Return the history log for `workspace`, under `.agent-history`.
+fn history_path(repo_dir: &Path, workspace: &str) -> PathBuf {
+    repo_dir
+        .join(".agent-history")
+        .join(format!("{}.jsonl", workspace))
+}
+
+/// Append `transition` to `workspace`'s history log, dropping the oldest
+/// lines once it holds [`HISTORY_LIMIT`].
+fn append_history(repo_dir: &Path, workspace: &str, transition: &Transition) -> Result<()> {
+    let path = history_path(repo_dir, workspace);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(transition)?;
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    let kept = existing.lines().count();
+    if kept < HISTORY_LIMIT {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        writeln!(file, "{}", line)?;
+        return Ok(());
+    }
+
+    let mut content: String = existing
+        .lines()
+        .skip(kept + 1 - HISTORY_LIMIT)
+        .map(|l| format!("{}\n", l))
+        .collect();
+    content.push_str(&line);
+    content.push('\n');
+    let tmp_path = path.with_extension("jsonl.tmp");
+    fs::write(&tmp_path, content)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// Log the session's move to `status` at `now` if it is one: a new session,
+/// a changed status, or a session heard from again after going stale, which
+/// first ends where it was last heard from. Returns the move, if logged.
+fn record_transition(
+    repo_dir: &Path,
+    workspace: &str,
+    session_id: &str,
+    agent: AgentKind,
+    previous: Option<&AgentStatusFile>,
+    status: AgentStatus,
+    now: u64,
+) -> Result<Option<Transition>> {
+    let transition = |status, from, at| Transition {
+        session_id: session_id.to_string(),
+        agent,
+        status,
+        from,
+        at,
+    };
+    // History only tells working, waiting, and idle apart.
+    let status = status.coarse();
+    let mut from = previous.map(|p| p.status.coarse());
+    if let Some(previous) = previous {
+        if previous.is_stale(now, &StaleTimeouts::for_repo(repo_dir)) {
+            let ended = transition(None, from, previous.updated_at);
+            append_history(repo_dir, workspace, &ended)?;
+            from = None;
+        } else if from == Some(status) && previous.workspace == workspace {
+            return Ok(None);
+        }
+    }
+    let moved = transition(Some(status), from, now);
+    append_history(repo_dir, workspace, &moved)?;
+    Ok(Some(moved))
+}
+
+/// Read `workspace`'s history log, oldest first, skipping unreadable lines.
+fn read_history(repo_dir: &Path, workspace: &str) -> Vec<Transition> {
+    fs::read_to_string(history_path(repo_dir, workspace))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect()
+}
+
+/// Time spent in each status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusTimes {
+    pub working: Duration,
+    pub waiting: Duration,
+    pub idle: Duration,
+}
+
+impl StatusTimes {
+    fn add(&mut self, status: AgentStatus, time: Duration) {
+        match status.coarse() {
+            AgentStatus::Waiting => self.waiting += time,
+            AgentStatus::Idle => self.idle += time,
+            _ => self.working += time,
+        }
+    }
+}
+
+impl fmt::Display for StatusTimes {
+    /// E.g. `working 42m, waiting 3m`, leaving out statuses never held.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            ("working", self.working),
+            ("waiting", self.waiting),
+            ("idle", self.idle),
+        ]
+        .into_iter()
+        .filter(|(_, time)| !time.is_zero())
+        .map(|(label, time)| format!("{} {}", label, format_duration(time)))
+        .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Format a duration compactly: `45s`, `42m`, `3h 5m`, or `2d 4h`.
+pub fn format_duration(time: Duration) -> String {
+    let secs = time.as_secs();
+    let (mins, hours, days) = (secs / 60, secs / 3600, secs / 86400);
+    match (days, hours, mins) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, _) if mins % 60 == 0 => format!("{}h", h),
+        (0, h, _) => format!("{}h {}m", h, mins % 60),
+        (d, _, _) if hours % 24 == 0 => format!("{}d", d),
+        (d, _, _) => format!("{}d {}h", d, hours % 24),
+    }
+}
+
+/// One session's share of a workspace's history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionHistory {
+    pub session_id: String,
+    pub agent: AgentKind,
+    /// When its first logged transition happened, in unix seconds.
+    pub first_seen: u64,
+    pub times: StatusTimes,
+}
+
+/// Add up how long each session in `history` spent in each status. A status
+/// lasts until the session's next transition; the latest lasts until `now`
+/// if the session's status file is still live, or until it was last heard
+/// from (per `last_heard`, by session id) if it went stale.
+fn session_histories(
+    history: &[Transition],
+    last_heard: &HashMap<String, u64>,
+    now: u64,
+    timeouts: &StaleTimeouts,
+) -> Vec<SessionHistory> {
+    let mut sessions: Vec<SessionHistory> = Vec::new();
+    for (i, transition) in history.iter().enumerate() {
+        let index = match sessions
+            .iter()
+            .position(|s| s.session_id == transition.session_id)
+        {
+            Some(index) => index,
+            None => {
+                sessions.push(SessionHistory {
+                    session_id: transition.session_id.clone(),
+                    agent: transition.agent,
+                    first_seen: transition.at,
+                    times: StatusTimes::default(),
+                });
+                sessions.len() - 1
+            }
+        };
+        let Some(status) = transition.status else {
+            continue;
+        };
+        let next = history[i + 1..]
+            .iter()
+            .find(|t| t.session_id == transition.session_id);
+        let end = match (next, last_heard.get(&transition.session_id)) {
+            (Some(next), _) => next.at,
+            (None, Some(&heard)) if now.saturating_sub(heard) > timeouts.of(status).as_secs() => {
+                heard
+            }
+            (None, Some(_)) => now,
+            (None, None) => transition.at,
+        };
+        sessions[index].times.add(
+            status,
+            Duration::from_secs(end.saturating_sub(transition.at)),
+        );
+    }
+    sessions
+}
+
+/// When each session in a repo last reported, by session id, stale or not.
+fn last_heard(repo_dir: &Path) -> HashMap<String, u64> {
+    let Ok(entries) = fs::read_dir(agent_status_dir(repo_dir)) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let id = path.file_stem()?.to_str()?.to_string();
+            let content = fs::read_to_string(&path).ok()?;
+            let file = serde_json::from_str::<AgentStatusFile>(&content).ok()?;
+            Some((id, file.updated_at))
+        })
+        .collect()
+}
+
+/// How long each session in `workspace`'s history spent in each status,
+/// in the order they first appeared.
+pub fn workspace_history(repo_dir: &Path, workspace: &str) -> Vec<SessionHistory> {
+    session_histories(
+        &read_history(repo_dir, workspace),
+        &last_heard(repo_dir),
+        epoch_secs(SystemTime::now()),
+        &StaleTimeouts::for_repo(repo_dir),
+    )
+}
+
+/// Total time agents in `workspace` spent in each status.
+pub fn workspace_status_times(repo_dir: &Path, workspace: &str) -> StatusTimes {
+    total_times(&workspace_history(repo_dir, workspace))
+}
+
+pub fn total_times(sessions: &[SessionHistory]) -> StatusTimes {
+    let mut total = StatusTimes::default();
+    for session in sessions {
+        total.working += session.times.working;
+        total.waiting += session.times.waiting;
+        total.idle += session.times.idle;
+    }
+    total
+}
+
+// ---------------------------------------------------------------------------
+// Hook handler
+// ---------------------------------------------------------------------------
+
+/// Resolve a `cwd` path to `(repo_dir, workspace_name)` using only the
+/// filesystem — no VCS subprocess calls.
+///
+/// Returns `None` if the path doesn't correspond to a dwm-managed workspace.
+pub fn resolve_workspace_from_cwd(dwm_base: &Path, cwd: &Path) -> Option<(PathBuf, String)> {
+    // Case 1: cwd is under ~/.dwm/<repo>/<workspace>/...
+    if let Ok(relative) = cwd.strip_prefix(dwm_base) {
+        let mut components = relative.components();
+        let repo_name = components.next()?.as_os_str().to_string_lossy().to_string();
+        let ws_name = components.next()?.as_os_str().to_string_lossy().to_string();
+        let repo_dir = dwm_base.join(&repo_name);
+        return Some((repo_dir, ws_name));
+    }
+
+    // Case 2: cwd is under a main repo tracked by dwm.
+    // Scan the main repos recorded in ~/.dwm/* to find a match.
+    let entries = fs::read_dir(dwm_base).ok()?;
+    for entry in entries.flatten() {
+        let repo_path = entry.path();
+        if !repo_path.is_dir() {
+            continue;
+        }
+        let Ok(Some(meta)) = RepoMeta::load(&repo_path) else {
+            continue;
+        };
+        if cwd.starts_with(&meta.main_repo) {
+            // Determine the main workspace name from the VCS type
+            let ws_name = meta.vcs_type().to_backend().main_workspace_name();
+            return Some((repo_path, ws_name.to_string()));
+        }
+    }
+
+    None
+}
+
+/// What a hook event says happened to a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HookUpdate<'a> {
+    Status(AgentStatus, AgentActivity<'a>),
+    /// The session ended; its status file is removed.
+    End,
+}
+
+/// A hook event from any agent, normalized from that agent's payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HookEvent<'a> {
+    agent: AgentKind,
+    /// Status file name. Ids from agents other than Claude Code are prefixed
+    /// with the agent's name so they can't collide.
+    session_id: String,
+    cwd: PathBuf,
+    update: HookUpdate<'a>,
+    /// The model, from the payload or the agent's environment.
+    model: Option<String>,
+}
+
+/// Gemini CLI hook events that Claude Code doesn't have, used to tell the
+/// two apart when `--agent` isn't given.
+const GEMINI_ONLY_EVENTS: &[&str] = &[
+    "BeforeAgent",
+    "AfterAgent",
+    "BeforeModel",
+    "AfterModel",
+    "BeforeToolSelection",
+    "BeforeTool",
+    "AfterTool",
+    "PreCompress",
+];
+
+fn str_field<'a>(json: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    json.get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// Guess which agent sent `json`, for hooks installed without `--agent`.
+fn detect_agent(json: &serde_json::Value) -> AgentKind {
+    if json.get("thread-id").is_some() || str_field(json, "type") == Some("agent-turn-complete") {
+        AgentKind::Codex
+    } else if str_field(json, "event").is_some() {
+        AgentKind::Opencode
+    } else if str_field(json, "hook_event_name").is_some_and(|e| GEMINI_ONLY_EVENTS.contains(&e)) {
+        AgentKind::Gemini
+    } else {
+        AgentKind::Claude
+    }
+}
+
+/// The model a payload names: a string, or an object with an id.
+fn payload_model(json: &serde_json::Value) -> Option<&str> {
+    let model = json.get("model")?;
+    model
+        .as_str()
+        .filter(|m| !m.is_empty())
+        .or_else(|| str_field(model, "id"))
+        .or_else(|| str_field(model, "modelID"))
+}
+
+/// The model `agent` runs according to `var`, the environment:
+/// `DWM_AGENT_MODEL`, then the agent's own model variable.
+pub fn env_model(agent: AgentKind, var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    std::iter::once("DWM_AGENT_MODEL")
+        .chain(agent.model_env())
+        .find_map(|name| var(name).filter(|m| !m.is_empty()))
+}
+
+/// Working on a new prompt, if the payload carried one.
+fn prompt_activity(prompt: Option<&str>) -> AgentActivity<'_> {
+    prompt.map_or(AgentActivity::Unchanged, AgentActivity::Prompt)
+}
+
+/// Working on a tool, if the payload named one, and on `path` if given.
+fn tool_activity<'a>(tool: Option<&'a str>, path: Option<&'a str>) -> AgentActivity<'a> {
+    tool.map_or(AgentActivity::Unchanged, |tool| {
+        AgentActivity::Tool(tool, path)
+    })
+}
+
+/// The file a Claude Code or Gemini CLI tool call targets, if any.
+fn tool_input_path(json: &serde_json::Value) -> Option<&str> {
+    let input = json.get("tool_input")?;
+    ["file_path", "notebook_path", "absolute_path"]
+        .iter()
+        .find_map(|key| str_field(input, key))
+}
+
+/// Claude Code hooks: JSON on stdin, one hook per event.
+fn parse_claude_hook(json: &serde_json::Value) -> Option<(String, HookUpdate<'_>)> {
+    let session_id = str_field(json, "session_id")?;
+    let update = match str_field(json, "hook_event_name")? {
+        "UserPromptSubmit" => HookUpdate::Status(
+            AgentStatus::Thinking,
+            prompt_activity(str_field(json, "prompt")),
+        ),
+        "PreToolUse" => HookUpdate::Status(
+            AgentStatus::RunningTool,
+            tool_activity(str_field(json, "tool_name"), tool_input_path(json)),
+        ),
+        // A tool or subagent finished and the model carries on.
+        "PostToolUse" | "SubagentStop" => {
+            HookUpdate::Status(AgentStatus::Thinking, AgentActivity::Unchanged)
+        }
+        "Stop" => HookUpdate::Status(AgentStatus::Idle, AgentActivity::Unchanged),
+        "Notification" => match str_field(json, "notification_type")? {
+            "idle_prompt" | "permission_prompt" => {
+                HookUpdate::Status(AgentStatus::Waiting, AgentActivity::Unchanged)
+            }
+            _ => return None,
+        },
+        "SessionEnd" => HookUpdate::End,
+        _ => return None,
+    };
+    Some((session_id.to_string(), update))
+}
+
+/// Gemini CLI hooks: the same shape as Claude Code's, with its own event
+/// names. Its notifications are all permission requests.
+fn parse_gemini_hook(json: &serde_json::Value) -> Option<(String, HookUpdate<'_>)> {
+    let session_id = str_field(json, "session_id")?;
+    let update = match str_field(json, "hook_event_name")? {
+        "BeforeAgent" => HookUpdate::Status(
+            AgentStatus::Thinking,
+            prompt_activity(str_field(json, "prompt")),
+        ),
+        "BeforeTool" => HookUpdate::Status(
+            AgentStatus::RunningTool,
+            tool_activity(str_field(json, "tool_name"), tool_input_path(json)),
+        ),
+        "AfterTool" => HookUpdate::Status(AgentStatus::Thinking, AgentActivity::Unchanged),
+        "AfterAgent" => HookUpdate::Status(AgentStatus::Idle, AgentActivity::Unchanged),
+        "Notification" => HookUpdate::Status(AgentStatus::Waiting, AgentActivity::Unchanged),
+        "SessionEnd" => HookUpdate::End,
+        _ => return None,
+    };
+    Some((session_id.to_string(), update))
+}
+
+/// Codex `notify`: JSON passed as an argument once a turn completes. A
+/// notification without a thread id is keyed by its directory.
+fn parse_codex_notify<'a>(
+    json: &'a serde_json::Value,
+    cwd: &Path,
+) -> Option<(String, HookUpdate<'a>)> {
+    if str_field(json, "type")? != "agent-turn-complete" {
+        return None;
+    }
+    let session_id = str_field(json, "thread-id")
+        .map(str::to_string)
+        .unwrap_or_else(|| vcs::repo_dir_name(cwd));
+    let prompt = json
+        .get("input-messages")
+        .and_then(|m| m.as_array())
+        .and_then(|m| m.last())
+        .and_then(|m| m.as_str());
+    Some((
+        session_id,
+        HookUpdate::Status(AgentStatus::Idle, prompt_activity(prompt)),
+    ))
+}
+
+/// opencode: JSON on stdin from the plugin installed by `dwm agent-setup`,
+/// which forwards opencode's own event names.
+fn parse_opencode_event(json: &serde_json::Value) -> Option<(String, HookUpdate<'_>)> {
+    let session_id = str_field(json, "session_id")?;
+    let update = match str_field(json, "event")? {
+        "chat.message" => HookUpdate::Status(
+            AgentStatus::Thinking,
+            prompt_activity(str_field(json, "prompt")),
+        ),
+        "tool.execute.before" => HookUpdate::Status(
+            AgentStatus::RunningTool,
+            tool_activity(str_field(json, "tool"), str_field(json, "path")),
+        ),
+        "tool.execute.after" => HookUpdate::Status(AgentStatus::Thinking, AgentActivity::Unchanged),
+        "session.status" => match str_field(json, "status")? {
+            "busy" | "retry" => HookUpdate::Status(AgentStatus::Working, AgentActivity::Unchanged),
+            "idle" => HookUpdate::Status(AgentStatus::Idle, AgentActivity::Unchanged),
+            _ => return None,
+        },
+        "session.idle" => HookUpdate::Status(AgentStatus::Idle, AgentActivity::Unchanged),
+        "permission.updated" | "permission.asked" => {
+            HookUpdate::Status(AgentStatus::Waiting, AgentActivity::Unchanged)
+        }
+        "permission.replied" => HookUpdate::Status(AgentStatus::Working, AgentActivity::Unchanged),
+        "session.deleted" => HookUpdate::End,
+        _ => return None,
+    };
+    Some((session_id.to_string(), update))
+}
+
+/// Normalize a hook payload from `agent`. `current_dir` stands in for the
+/// session's directory when the payload doesn't name one. Returns `None` for
+/// events dwm doesn't track or payloads missing what it needs.
+fn parse_hook_event<'a>(
+    agent: AgentKind,
+    json: &'a serde_json::Value,
+    current_dir: &Path,
+) -> Option<HookEvent<'a>> {
+    let cwd = str_field(json, "cwd")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| current_dir.to_path_buf());
+    let (session_id, update) = match agent {
+        AgentKind::Claude => parse_claude_hook(json)?,
+        AgentKind::Gemini => parse_gemini_hook(json)?,
+        AgentKind::Codex => parse_codex_notify(json, &cwd)?,
+        AgentKind::Opencode => parse_opencode_event(json)?,
+        // Aider's notifications command gets no payload and only runs when
+        // Aider is waiting for input; one session per directory.
+        AgentKind::Aider => (
+            vcs::repo_dir_name(&cwd),
+            HookUpdate::Status(AgentStatus::Waiting, AgentActivity::Unchanged),
+        ),
+    };
+    if cwd.as_os_str().is_empty() {
+        return None;
+    }
+    let session_id = match agent {
+        AgentKind::Claude => session_id,
+        _ => format!("{}-{}", agent.name(), session_id),
+    };
+    Some(HookEvent {
+        agent,
+        session_id,
+        cwd,
+        update,
+        model: payload_model(json).map(str::to_string),
+    })
+}
+
+/// Record the hook payload `json` from `agent`, or the agent guessed from
+/// it, in the status files of the workspace it happened in. `current_dir`
+/// stands in for the session's directory when the payload doesn't name one.
+/// `on_change` hears of each status change, with the repo's dwm dir and the
+/// workspace's name. Payloads outside a dwm workspace, and events dwm
+/// doesn't track, are ignored.
+pub fn record_hook(
+    dwm_base: &Path,
+    agent: Option<AgentKind>,
+    json: &serde_json::Value,
+    current_dir: &Path,
+    on_change: &dyn Fn(&Path, &str, &Transition),
+) -> Result<()> {
+    let agent = agent.unwrap_or_else(|| detect_agent(json));
+    let Some(mut event) = parse_hook_event(agent, json, current_dir) else {
+        return Ok(()); // silently ignore untracked events and incomplete data
+    };
+    if event.model.is_none() {
+        event.model = env_model(agent, |name| std::env::var(name).ok());
+    }
+    if let Ok(id) = std::env::var(LAUNCH_SESSION_ENV)
+        && !id.is_empty()
+    {
+        event.session_id = id;
+    }
+    apply_hook_event(dwm_base, &event, on_change)
+}
+
+/// Record `event` in the status files of the workspace it happened in.
+fn apply_hook_event(
+    dwm_base: &Path,
+    event: &HookEvent,
+    on_change: &dyn Fn(&Path, &str, &Transition),
+) -> Result<()> {
+    let Some((repo_dir, ws_name)) = resolve_workspace_from_cwd(dwm_base, &event.cwd) else {
+        return Ok(()); // not a dwm workspace, silently ignore
+    };
+    let relative;
+    let transition = match event.update {
+        HookUpdate::Status(status, mut activity) => {
+            // Files inside the session's directory are kept relative to it.
+            if let AgentActivity::Tool(tool, Some(path)) = activity {
+                relative = Path::new(path)
+                    .strip_prefix(&event.cwd)
+                    .map_or_else(|_| path.to_string(), |p| p.to_string_lossy().into_owned());
+                activity = AgentActivity::Tool(tool, Some(&relative));
+            }
+            write_agent_status(
+                &repo_dir,
+                &event.session_id,
+                event.agent,
+                &ws_name,
+                status,
+                activity,
+                event.model.as_deref(),
+            )?
+        }
+        HookUpdate::End => remove_agent_status(&repo_dir, &event.session_id)?,
+    };
+    if let Some(transition) = transition {
+        on_change(&repo_dir, &ws_name, &transition);
+    }
+    Ok(())
+}
+
+/// Run `command` for `agent` in the workspace at `ws_path` and wait for it.
+/// The session is registered as idle before the agent starts, so it shows
+/// up straight away, and ended when the agent exits; `on_change` hears of
+/// both, as in [`record_hook`].
+///
+/// The agent's stdout is pointed at stderr so it still reaches the terminal
+/// when dwm's stdout is being captured by the shell wrapper.
+pub fn launch_agent_at(
+    dwm_base: &Path,
+    agent: AgentKind,
+    command: &str,
+    ws_path: &Path,
+    model: Option<&str>,
+    on_change: &dyn Fn(&Path, &str, &Transition),
+) -> Result<()> {
+    let (repo_dir, ws_name) = resolve_workspace_from_cwd(dwm_base, ws_path)
+        .with_context(|| format!("{} is not a dwm workspace", ws_path.display()))?;
+    let session_id = format!("{}-launch-{}", agent.name(), std::process::id());
+    let started = write_agent_status(
+        &repo_dir,
+        &session_id,
+        agent,
+        &ws_name,
+        AgentStatus::Idle,
+        AgentActivity::Unchanged,
+        model,
+    )?;
+    if let Some(transition) = started {
+        on_change(&repo_dir, &ws_name, &transition);
+    }
+
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(ws_path)
+        .env(LAUNCH_SESSION_ENV, &session_id)
+        .stdout(std::io::stderr())
+        .status();
+
+    // The agent's own end hook may already have removed the file.
+    if let Some(transition) = remove_agent_status(&repo_dir, &session_id)? {
+        on_change(&repo_dir, &ws_name, &transition);
+    }
+    let status = status.with_context(|| format!("failed to run '{}'", command))?;
+    if !status.success() {
+        bail!("'{}' exited with {}", command, status);
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Agent setup
+// ---------------------------------------------------------------------------
+
+/// The hook configuration that dwm needs in ~/.claude/settings.json.
+fn dwm_hook_config() -> serde_json::Value {
+    serde_json::json!({
+        "PreToolUse": [
+            { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+        ],
+        "PostToolUse": [
+            { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+        ],
+        "SubagentStop": [
+            { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+        ],
+        "Stop": [
+            { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+        ],
+        "Notification": [
+            {
+                "matcher": "idle_prompt|permission_prompt",
+                "hooks": [{ "type": "command", "command": "dwm hook-handler" }]
+            }
+        ],
+        "UserPromptSubmit": [
+            { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+        ],
+        "SessionEnd": [
+            { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+        ]
+    })
+}
+
+/// The hook configuration that dwm needs in ~/.gemini/settings.json.
+fn gemini_hook_config() -> serde_json::Value {
+    let group = serde_json::json!([
+        { "hooks": [{ "type": "command", "command": "dwm hook-handler --agent gemini" }] }
+    ]);
+    serde_json::json!({
+        "BeforeAgent": group,
+        "BeforeTool": group,
+        "AfterTool": group,
+        "AfterAgent": group,
+        "Notification": group,
+        "SessionEnd": group
+    })
+}
+
+/// The `notify` line dwm adds to ~/.codex/config.toml.
+const CODEX_NOTIFY: &str = r#"notify = ["dwm", "hook-handler", "--agent", "codex"]"#;
+
+/// The notifications command dwm sets in ~/.aider.conf.yml.
+const AIDER_NOTIFICATIONS_COMMAND: &str = "dwm hook-handler --agent aider";
+
+/// Plugin dwm installs for opencode. It forwards the events dwm tracks to
+/// `dwm hook-handler`, skipping the rest so streaming output doesn't spawn a
+/// process per message part.
+const OPENCODE_PLUGIN: &str = r#"// Reports opencode session status to dwm. Installed by `dwm agent-setup`.
+const TRACKED = new Set([
+  "session.status",
+  "session.idle",
+  "session.deleted",
+  "permission.updated",
+  "permission.asked",
+  "permission.replied",
+]);
+
+export const DwmPlugin = async ({ directory }) => {
+  const report = (payload) => {
+    try {
+      const proc = Bun.spawn(["dwm", "hook-handler", "--agent", "opencode"], {
+        stdin: "pipe",
+        stdout: "ignore",
+        stderr: "ignore",
+      });
+      proc.stdin.write(JSON.stringify({ cwd: directory, ...payload }));
+      proc.stdin.end();
+    } catch {}
+  };
+  return {
+    event: async ({ event }) => {
+      if (!TRACKED.has(event.type)) return;
+      const props = event.properties ?? {};
+      const session_id = props.sessionID ?? props.info?.id;
+      if (session_id) {
+        report({ event: event.type, session_id, status: props.status?.type });
+      }
+    },
+    "chat.message": async (input, output) => {
+      const prompt = output.parts?.find((part) => part.type === "text")?.text;
+      const model = input.model && `${input.model.providerID}/${input.model.modelID}`;
+      report({ event: "chat.message", session_id: input.sessionID, prompt, model });
+    },
+    "tool.execute.before": async (input, output) => {
+      report({
+        event: "tool.execute.before",
+        session_id: input.sessionID,
+        tool: input.tool,
+        path: output.args?.filePath,
+      });
+    },
+    "tool.execute.after": async (input) => {
+      report({ event: "tool.execute.after", session_id: input.sessionID });
+    },
+  };
+};
+"#;
+
+/// The file dwm edits to hook into `agent`.
+pub fn agent_config_path(agent: AgentKind, home: &Path) -> PathBuf {
+    match agent {
+        AgentKind::Claude => home.join(".claude").join("settings.json"),
+        AgentKind::Codex => home.join(".codex").join("config.toml"),
+        AgentKind::Gemini => home.join(".gemini").join("settings.json"),
+        AgentKind::Opencode => home.join(".config/opencode/plugin/dwm.js"),
+        AgentKind::Aider => home.join(".aider.conf.yml"),
+    }
+}
+
+/// The Claude Code settings file in the repo at `root`: the shared
+/// `.claude/settings.json`, or with `local` the personal, uncommitted
+/// `.claude/settings.local.json`.
+pub fn project_settings_path(root: &Path, local: bool) -> PathBuf {
+    let file = if local {
+        "settings.local.json"
+    } else {
+        "settings.json"
+    };
+    root.join(".claude").join(file)
+}
+
+/// Whether `agent` looks installed: its config exists or its binary is on
+/// `PATH`. Claude Code is always offered, as it was before other agents were.
+pub fn agent_detected(agent: AgentKind, home: &Path) -> bool {
+    let (config, binary) = match agent {
+        AgentKind::Claude => return true,
+        AgentKind::Codex => (home.join(".codex"), "codex"),
+        AgentKind::Gemini => (home.join(".gemini"), "gemini"),
+        AgentKind::Opencode => (home.join(".config/opencode"), "opencode"),
+        AgentKind::Aider => (home.join(".aider.conf.yml"), "aider"),
+    };
+    config.exists()
+        || std::env::var_os("PATH")
+            .is_some_and(|paths| std::env::split_paths(&paths).any(|d| d.join(binary).is_file()))
+}
+
+/// Whether any hook in `groups` runs `command`.
+fn groups_run_command(groups: &[serde_json::Value], command: &str) -> bool {
+    groups.iter().any(|group| {
+        group
+            .get("hooks")
+            .and_then(|h| h.as_array())
+            .is_some_and(|hooks| {
+                hooks.iter().any(|h| {
+                    h.get("command")
+                        .and_then(|c| c.as_str())
+                        .is_some_and(|c| c == command)
+                })
+            })
+    })
+}
+
+/// The command each of `dwm_groups`' hooks runs; they all run the same one.
+fn dwm_command(dwm_groups: &serde_json::Value) -> &str {
+    dwm_groups[0]["hooks"][0]["command"]
+        .as_str()
+        .unwrap_or_default()
+}
+
+/// Check if all of `dwm_hooks` are already installed in the given settings.
+fn hooks_already_installed(settings: &serde_json::Value, dwm_hooks: &serde_json::Value) -> bool {
+    let Some(hooks) = settings.get("hooks").and_then(|h| h.as_object()) else {
+        return false;
+    };
+    dwm_hooks
+        .as_object()
+        .unwrap()
+        .iter()
+        .all(|(event_name, dwm_groups)| {
+            hooks
+                .get(event_name)
+                .and_then(|v| v.as_array())
+                .is_some_and(|arr| groups_run_command(arr, dwm_command(dwm_groups)))
+        })
+}
+
+/// Merge `dwm_hooks` into the given settings object.
+///
+/// This is a pure function that takes existing settings and returns a new
+/// settings object with dwm hooks added, preserving all other settings.
+fn merge_dwm_hooks(
+    mut settings: serde_json::Value,
+    dwm_hooks: &serde_json::Value,
+) -> Result<serde_json::Value> {
+    // Ensure root is an object
+    let settings_obj = settings
+        .as_object_mut()
+        .context("settings.json root must be an object")?;
+
+    // Get or create "hooks" object
+    let hooks_obj = settings_obj
+        .entry("hooks")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .context("hooks must be an object")?;
+
+    for (event_name, dwm_groups) in dwm_hooks.as_object().unwrap() {
+        let arr = hooks_obj
+            .entry(event_name)
+            .or_insert_with(|| serde_json::json!([]))
+            .as_array_mut()
+            .with_context(|| format!("hooks.{} must be an array", event_name))?;
+
+        if !groups_run_command(arr, dwm_command(dwm_groups)) {
+            for group in dwm_groups.as_array().unwrap() {
+                arr.push(group.clone());
+            }
+        }
+    }
+
+    Ok(settings)
+}
+
+/// Settings JSON with `dwm_hooks` merged in, or `None` if they already are.
+fn install_json_hooks(
+    existing: Option<&str>,
+    dwm_hooks: &serde_json::Value,
+) -> Result<Option<String>> {
+    let settings: serde_json::Value = match existing {
+        Some(content) => serde_json::from_str(content)?,
+        None => serde_json::json!({}),
+    };
+    if hooks_already_installed(&settings, dwm_hooks) {
+        return Ok(None);
+    }
+    let merged = merge_dwm_hooks(settings, dwm_hooks)?;
+    Ok(Some(serde_json::to_string_pretty(&merged)?))
+}
+
+/// Whether `line` sets the top-level `key`, with `separator` between key and
+/// value (`=` in TOML, `:` in YAML).
+fn sets_key(line: &str, key: &str, separator: char) -> bool {
+    line.strip_prefix(key)
+        .is_some_and(|rest| rest.trim_start().starts_with(separator))
+}
+
+/// A Codex config.toml with dwm's `notify` command added, or `None` if it's
+/// already there. It goes before the first table so it stays a top-level
+/// key. Codex runs a single notify command, so one set to something else is
+/// left for the user to sort out.
+fn add_codex_notify(config: &str) -> Result<Option<String>> {
+    let table_start = config
+        .lines()
+        .position(|l| l.trim_start().starts_with('['))
+        .unwrap_or(usize::MAX);
+    let mut lines: Vec<&str> = config.lines().collect();
+    if let Some(line) = lines
+        .iter()
+        .take(table_start)
+        .find(|l| sets_key(l, "notify", '='))
+    {
+        if line.contains("hook-handler") {
+            return Ok(None);
+        }
+        anyhow::bail!(
+            "Codex already has a notify command; replace it with `{}`",
+            CODEX_NOTIFY
+        );
+    }
+    lines.insert(table_start.min(lines.len()), CODEX_NOTIFY);
+    Ok(Some(lines.join("\n") + "\n"))
+}
+
+/// An Aider config with notifications turned on and sent to dwm, or `None`
+/// if they already are. A notifications command set to something else is
+/// left for the user to sort out.
+fn add_aider_notifications(config: &str) -> Result<Option<String>> {
+    let command_line = format!("notifications-command: {}", AIDER_NOTIFICATIONS_COMMAND);
+    let mut lines: Vec<&str> = Vec::new();
+    let (mut has_toggle, mut has_command) = (false, false);
+    for line in config.lines() {
+        if sets_key(line, "notifications", ':') {
+            has_toggle = true;
+            lines.push("notifications: true");
+        } else if sets_key(line, "notifications-command", ':') {
+            if !line.contains("hook-handler") {
+                anyhow::bail!(
+                    "Aider already has a notifications command; replace it with `{}`",
+                    command_line
+                );
+            }
+            has_command = true;
+            lines.push(line);
+        } else {
+            lines.push(line);
+        }
+    }
+    if !has_toggle {
+        lines.push("notifications: true");
+    }
+    if !has_command {
+        lines.push(&command_line);
+    }
+    let updated = lines.join("\n") + "\n";
+    Ok((updated != config).then_some(updated))
+}
+
+/// The contents that hook dwm into `agent`, given its config file's current
+/// contents, or `None` if dwm is already hooked in.
+pub fn install_agent_hooks(agent: AgentKind, existing: Option<&str>) -> Result<Option<String>> {
+    match agent {
+        AgentKind::Claude => install_json_hooks(existing, &dwm_hook_config()),
+        AgentKind::Gemini => install_json_hooks(existing, &gemini_hook_config()),
+        AgentKind::Codex => add_codex_notify(existing.unwrap_or("")),
+        AgentKind::Opencode => {
+            Ok((existing != Some(OPENCODE_PLUGIN)).then(|| OPENCODE_PLUGIN.to_string()))
+        }
+        AgentKind::Aider => add_aider_notifications(existing.unwrap_or("")),
+    }
+}
+
+/// Whether `hook` (one entry of a group's `hooks`) runs `dwm hook-handler`.
+fn is_dwm_hook(hook: &serde_json::Value) -> bool {
+    hook.get("command")
+        .and_then(|c| c.as_str())
+        .is_some_and(|c| c.starts_with("dwm hook-handler"))
+}
+
+/// Settings JSON with every `dwm hook-handler` hook taken out, or `None` if
+/// there aren't any. Groups, events, and the `hooks` object are dropped once
+/// they're empty; everything else is left alone.
+fn remove_json_hooks(existing: &str) -> Result<Option<String>> {
+    let mut settings: serde_json::Value = serde_json::from_str(existing)?;
+    let Some(hooks) = settings.get_mut("hooks").and_then(|h| h.as_object_mut()) else {
+        return Ok(None);
+    };
+    let mut removed = false;
+    for groups in hooks.values_mut().filter_map(|g| g.as_array_mut()) {
+        for group in groups.iter_mut() {
+            if let Some(group_hooks) = group.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+                let before = group_hooks.len();
+                group_hooks.retain(|h| !is_dwm_hook(h));
+                removed |= group_hooks.len() != before;
+            }
+        }
+        groups.retain(|g| {
+            g.get("hooks")
+                .and_then(|h| h.as_array())
+                .is_none_or(|h| !h.is_empty())
+        });
+    }
+    if !removed {
+        return Ok(None);
+    }
+    hooks.retain(|_, groups| groups.as_array().is_none_or(|g| !g.is_empty()));
+    if hooks.is_empty()
+        && let Some(settings) = settings.as_object_mut()
+    {
+        settings.remove("hooks");
+    }
+    Ok(Some(serde_json::to_string_pretty(&settings)?))
+}
+
+/// `config` without the lines `is_dwm_line` picks out, or `None` if it has
+/// none.
+fn remove_lines(config: &str, is_dwm_line: impl Fn(&str) -> bool) -> Option<String> {
+    let lines: Vec<&str> = config.lines().filter(|l| !is_dwm_line(l)).collect();
+    if lines.len() == config.lines().count() {
+        return None;
+    }
+    Some(match lines.is_empty() {
+        true => String::new(),
+        false => lines.join("\n") + "\n",
+    })
+}
+
+/// The contents of `agent`'s config file with dwm unhooked, given its
+/// current contents, or `None` if dwm isn't hooked in. Empty when nothing
+/// else is left, as for opencode, whose plugin file is dwm's alone.
+pub fn remove_agent_hooks(agent: AgentKind, existing: &str) -> Result<Option<String>> {
+    let is_handler = |line: &str| line.contains("dwm") && line.contains("hook-handler");
+    match agent {
+        AgentKind::Claude | AgentKind::Gemini => remove_json_hooks(existing),
+        AgentKind::Codex => Ok(remove_lines(existing, |l| {
+            sets_key(l, "notify", '=') && is_handler(l)
+        })),
+        // Notifications were turned on for dwm, so they go with its command.
+        AgentKind::Aider
+            if existing
+                .lines()
+                .any(|l| sets_key(l, "notifications-command", ':') && is_handler(l)) =>
+        {
+            Ok(remove_lines(existing, |l| {
+                sets_key(l, "notifications-command", ':') || sets_key(l, "notifications", ':')
+            }))
+        }
+        AgentKind::Aider => Ok(None),
+        AgentKind::Opencode => Ok(existing
+            .contains("Installed by `dwm agent-setup`")
+            .then(String::new)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Convert a u64 epoch timestamp to SystemTime for test assertions.
+    fn epoch(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    fn write_status_file(
+        dir: &Path,
+        session_id: &str,
+        workspace: &str,
+        status: &str,
+        updated_at: u64,
+    ) {
+        let agent_dir = dir.join(".agent-status");
+        fs::create_dir_all(&agent_dir).unwrap();
+        let content = format!(
+            r#"{{"workspace":"{}","status":"{}","updated_at":{}}}"#,
+            workspace, status, updated_at
+        );
+        fs::write(agent_dir.join(format!("{}.json", session_id)), content).unwrap();
+    }
+
+    #[test]
+    fn read_empty_dir() {
+        let dir = TempDir::new().unwrap();
+        let map = read_agent_summaries(dir.path());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn read_single_status() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        write_status_file(dir.path(), "session1", "my-ws", "working", now);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        let summary = map.get("my-ws").unwrap();
+        assert_eq!(summary.working, 1);
+        assert_eq!(summary.waiting, 0);
+        assert_eq!(summary.idle, 0);
+    }
+
+    #[test]
+    fn read_multiple_agents_same_workspace() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        write_status_file(dir.path(), "s1", "ws", "working", now);
+        write_status_file(dir.path(), "s2", "ws", "waiting", now);
+        write_status_file(dir.path(), "s3", "ws", "waiting", now);
+        write_status_file(dir.path(), "s4", "ws", "idle", now);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        let summary = map.get("ws").unwrap();
+        assert_eq!(summary.working, 1);
+        assert_eq!(summary.waiting, 2);
+        assert_eq!(summary.idle, 1);
+    }
+
+    #[test]
+    fn read_multiple_workspaces() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        write_status_file(dir.path(), "s1", "ws-a", "working", now);
+        write_status_file(dir.path(), "s2", "ws-b", "idle", now);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        assert_eq!(map.get("ws-a").unwrap().working, 1);
+        assert_eq!(map.get("ws-b").unwrap().idle, 1);
+    }
+
+    #[test]
+    fn stale_entries_ignored() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        let old = now - STALE_TIMEOUT.as_secs() - 1;
+        write_status_file(dir.path(), "old-session", "ws", "working", old);
+        write_status_file(dir.path(), "new-session", "ws", "idle", now);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        let summary = map.get("ws").unwrap();
+        assert_eq!(summary.working, 0);
+        assert_eq!(summary.idle, 1);
+    }
+
+    #[test]
+    fn write_and_read_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        write_agent_status(
+            dir.path(),
+            "sess-123",
+            AgentKind::Claude,
+            "my-ws",
+            AgentStatus::Waiting,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+
+        let map = read_agent_summaries(dir.path());
+        let summary = map.get("my-ws").unwrap();
+        assert_eq!(summary.waiting, 1);
+    }
+
+    #[test]
+    fn remove_status() {
+        let dir = TempDir::new().unwrap();
+        write_agent_status(
+            dir.path(),
+            "sess-123",
+            AgentKind::Claude,
+            "my-ws",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        remove_agent_status(dir.path(), "sess-123").unwrap();
+
+        let map = read_agent_summaries(dir.path());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn activity_is_carried_across_status_changes() {
+        let dir = TempDir::new().unwrap();
+        let write = |status, activity| {
+            write_agent_status(
+                dir.path(),
+                "s1",
+                AgentKind::Claude,
+                "ws",
+                status,
+                activity,
+                None,
+            )
+            .unwrap();
+            read_agent_sessions(dir.path(), "ws").remove(0)
+        };
+
+        let s = write(
+            AgentStatus::Working,
+            AgentActivity::Prompt("fix the build\nplease"),
+        );
+        assert_eq!(s.last_prompt.as_deref(), Some("fix the build"));
+        assert_eq!(s.last_tool, None);
+
+        let s = write(AgentStatus::Working, AgentActivity::Tool("Bash", None));
+        assert_eq!(s.last_prompt.as_deref(), Some("fix the build"));
+        assert_eq!(s.last_tool.as_deref(), Some("Bash"));
+
+        let s = write(AgentStatus::Waiting, AgentActivity::Unchanged);
+        assert_eq!(s.status, AgentStatus::Waiting);
+        assert_eq!(s.last_tool.as_deref(), Some("Bash"));
+
+        // A new prompt starts a new turn, so the old tool no longer applies.
+        let s = write(AgentStatus::Working, AgentActivity::Prompt("now add tests"));
+        assert_eq!(s.last_prompt.as_deref(), Some("now add tests"));
+        assert_eq!(s.last_tool, None);
+    }
+
+    #[test]
+    fn prompt_excerpt_truncates_long_prompts() {
+        let long = "x".repeat(PROMPT_EXCERPT_LEN + 50);
+        let excerpt = prompt_excerpt(&long);
+        assert_eq!(excerpt.chars().count(), PROMPT_EXCERPT_LEN);
+        assert!(excerpt.ends_with('…'));
+        assert_eq!(prompt_excerpt("  short  "), "short");
+    }
+
+    #[test]
+    fn sessions_filtered_by_workspace_and_sorted_by_urgency() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000;
+        write_status_file(dir.path(), "idle-1", "ws", "idle", now);
+        write_status_file(dir.path(), "work-old", "ws", "working", now - 60);
+        write_status_file(dir.path(), "work-new", "ws", "working", now - 5);
+        write_status_file(dir.path(), "wait-1", "ws", "waiting", now - 30);
+        write_status_file(dir.path(), "other", "other-ws", "waiting", now);
+        let stale = now - WAITING_STALE_TIMEOUT.as_secs() - 1;
+        write_status_file(dir.path(), "stale", "ws", "waiting", stale);
+
+        let sessions =
+            read_agent_sessions_at(dir.path(), "ws", epoch(now), &StaleTimeouts::default());
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["wait-1", "work-new", "work-old", "idle-1"]);
+    }
+
+    #[test]
+    fn remove_statuses_for_workspace() {
+        let dir = TempDir::new().unwrap();
+        write_agent_status(
+            dir.path(),
+            "s1",
+            AgentKind::Claude,
+            "ws-a",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        write_agent_status(
+            dir.path(),
+            "s2",
+            AgentKind::Claude,
+            "ws-a",
+            AgentStatus::Idle,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        write_agent_status(
+            dir.path(),
+            "s3",
+            AgentKind::Claude,
+            "ws-b",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+
+        remove_agent_statuses_for_workspace(dir.path(), "ws-a");
+
+        let map = read_agent_summaries(dir.path());
+        assert!(!map.contains_key("ws-a"));
+        assert_eq!(map.get("ws-b").unwrap().working, 1);
+    }
+
+    #[test]
+    fn summary_display_all_statuses() {
+        let s = AgentSummary {
+            waiting: 2,
+            working: 1,
+            idle: 1,
+            ..Default::default()
+        };
+        assert_eq!(s.to_string(), "2 waiting, 1 working, 1 idle");
+    }
+
+    #[test]
+    fn summary_display_single_status() {
+        let s = AgentSummary {
+            waiting: 0,
+            working: 1,
+            idle: 0,
+            ..Default::default()
+        };
+        assert_eq!(s.to_string(), "1 working");
+    }
+
+    #[test]
+    fn summary_display_labels_sessions_most_urgent_first() {
+        let s = AgentSummary {
+            labels: vec![
+                ("codex".to_string(), AgentStatus::Working),
+                ("claude-opus".to_string(), AgentStatus::Waiting),
+                ("codex".to_string(), AgentStatus::Working),
+                ("claude-opus".to_string(), AgentStatus::Idle),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            s.to_string(),
+            "claude-opus waiting, 2 codex working, claude-opus idle"
+        );
+    }
+
+    #[test]
+    fn summary_display_says_how_long_its_been_waiting() {
+        let since = Some(SystemTime::now() - Duration::from_secs(12 * 60 + 5));
+        let s = AgentSummary {
+            labels: vec![
+                ("codex".to_string(), AgentStatus::Working),
+                ("claude".to_string(), AgentStatus::Waiting),
+                ("gemini".to_string(), AgentStatus::Waiting),
+            ],
+            waiting_since: since,
+            ..Default::default()
+        };
+        assert_eq!(
+            s.to_string(),
+            "claude waiting for 12m, gemini waiting, codex working"
+        );
+        let s = AgentSummary {
+            waiting: 2,
+            idle: 1,
+            waiting_since: since,
+            ..Default::default()
+        };
+        assert_eq!(s.to_string(), "2 waiting for 12m, 1 idle");
+    }
+
+    #[test]
+    fn summaries_label_sessions_by_agent_and_model() {
+        let dir = TempDir::new().unwrap();
+        for (session, agent, model) in [
+            ("s1", AgentKind::Claude, Some("claude-opus-4-1-20250805")),
+            ("s2", AgentKind::Codex, None),
+        ] {
+            write_agent_status(
+                dir.path(),
+                session,
+                agent,
+                "ws",
+                AgentStatus::Working,
+                AgentActivity::Unchanged,
+                model,
+            )
+            .unwrap();
+        }
+        let map = read_agent_summaries(dir.path());
+        assert_eq!(
+            map["ws"].to_string(),
+            "claude-opus-4-1 working, codex working"
+        );
+    }
+
+    #[test]
+    fn agent_label_shortens_models() {
+        assert_eq!(agent_label(AgentKind::Claude, None), "claude");
+        assert_eq!(
+            agent_label(AgentKind::Claude, Some("claude-opus-4-1-20250805")),
+            "claude-opus-4-1"
+        );
+        assert_eq!(agent_label(AgentKind::Claude, Some("opus")), "claude-opus");
+        assert_eq!(
+            agent_label(
+                AgentKind::Opencode,
+                Some("anthropic/claude-sonnet-4-20250514")
+            ),
+            "opencode-claude-sonnet-4"
+        );
+        assert_eq!(
+            agent_label(AgentKind::Gemini, Some("gemini-2.5-pro")),
+            "gemini-2.5-pro"
+        );
+        assert_eq!(agent_label(AgentKind::Codex, Some("gpt-5")), "codex-gpt-5");
+    }
+
+    #[test]
+    fn model_is_kept_until_reported_again() {
+        let dir = TempDir::new().unwrap();
+        let write = |model| {
+            write_agent_status(
+                dir.path(),
+                "s1",
+                AgentKind::Claude,
+                "ws",
+                AgentStatus::Working,
+                AgentActivity::Unchanged,
+                model,
+            )
+            .unwrap();
+            read_agent_sessions(dir.path(), "ws").remove(0).model
+        };
+        assert_eq!(write(Some("opus")), Some("opus".to_string()));
+        assert_eq!(write(None), Some("opus".to_string()));
+        assert_eq!(write(Some("sonnet")), Some("sonnet".to_string()));
+    }
+
+    #[test]
+    fn model_comes_from_payload_or_environment() {
+        assert_eq!(
+            payload_model(&serde_json::json!({ "model": "claude-opus-4-1" })),
+            Some("claude-opus-4-1")
+        );
+        assert_eq!(
+            payload_model(
+                &serde_json::json!({ "model": { "id": "opus", "display_name": "Opus" } })
+            ),
+            Some("opus")
+        );
+        assert_eq!(payload_model(&serde_json::json!({ "model": "" })), None);
+
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        let vars = &[
+            ("ANTHROPIC_MODEL", "opus"),
+            ("GEMINI_MODEL", "gemini-2.5-pro"),
+        ];
+        assert_eq!(
+            env_model(AgentKind::Claude, env(vars)).as_deref(),
+            Some("opus")
+        );
+        assert_eq!(env_model(AgentKind::Codex, env(vars)), None);
+        let vars = &[("ANTHROPIC_MODEL", "opus"), ("DWM_AGENT_MODEL", "haiku")];
+        assert_eq!(
+            env_model(AgentKind::Claude, env(vars)).as_deref(),
+            Some("haiku")
+        );
+    }
+
+    #[test]
+    fn summary_display_empty() {
+        let s = AgentSummary::default();
+        assert_eq!(s.to_string(), "");
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn summary_most_urgent() {
+        assert_eq!(
+            AgentSummary {
+                waiting: 1,
+                working: 0,
+                idle: 0,
+                ..Default::default()
+            }
+            .most_urgent(),
+            Some(AgentStatus::Waiting)
+        );
+        assert_eq!(
+            AgentSummary {
+                waiting: 0,
+                working: 1,
+                idle: 1,
+                ..Default::default()
+            }
+            .most_urgent(),
+            Some(AgentStatus::Working)
+        );
+        assert_eq!(
+            AgentSummary {
+                waiting: 0,
+                working: 0,
+                idle: 1,
+                ..Default::default()
+            }
+            .most_urgent(),
+            Some(AgentStatus::Idle)
+        );
+        assert_eq!(AgentSummary::default().most_urgent(), None);
+    }
+
+    #[test]
+    fn resolve_cwd_inside_dwm() {
+        let dwm_base = PathBuf::from("/home/user/.dwm");
+        let cwd = PathBuf::from("/home/user/.dwm/myrepo-abc123/my-feature/src");
+
+        let result = resolve_workspace_from_cwd(&dwm_base, &cwd);
+        assert!(result.is_some());
+        let (repo_dir, ws_name) = result.unwrap();
+        assert_eq!(repo_dir, PathBuf::from("/home/user/.dwm/myrepo-abc123"));
+        assert_eq!(ws_name, "my-feature");
+    }
+
+    #[test]
+    fn current_summary_reads_the_cwd_workspace() {
+        let dir = TempDir::new().unwrap();
+        let repo_dir = dir.path().join("myrepo");
+        let now = epoch_secs(SystemTime::now());
+        write_status_file(&repo_dir, "s1", "feat", "waiting", now);
+        write_status_file(&repo_dir, "s2", "feat", "waiting", now);
+        write_status_file(&repo_dir, "s3", "feat", "running_tool", now);
+        write_status_file(&repo_dir, "s4", "other", "idle", now);
+
+        let summary = current_summary(dir.path(), &repo_dir.join("feat").join("src")).unwrap();
+        assert_eq!((summary.waiting, summary.working), (2, 1));
+
+        let idle = current_summary(dir.path(), &repo_dir.join("other")).unwrap();
+        assert_eq!((idle.waiting, idle.working, idle.idle), (0, 0, 1));
+        assert!(current_summary(dir.path(), &repo_dir.join("none")).is_none());
+        assert!(current_summary(dir.path(), Path::new("/elsewhere")).is_none());
+    }
+
+    #[test]
+    fn resolve_cwd_outside_dwm_no_match() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        fs::create_dir_all(&dwm_base).unwrap();
+
+        let cwd = PathBuf::from("/some/random/dir");
+        let result = resolve_workspace_from_cwd(&dwm_base, &cwd);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn resolve_cwd_main_repo() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let repo_dir = dwm_base.join("myrepo-abc123");
+        fs::create_dir_all(&repo_dir).unwrap();
+
+        let main_repo = dir.path().join("repos").join("myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        fs::write(
+            repo_dir.join(".main-repo"),
+            main_repo.to_string_lossy().as_ref(),
+        )
+        .unwrap();
+        fs::write(repo_dir.join(".vcs-type"), "git").unwrap();
+
+        let cwd = main_repo.join("src");
+        fs::create_dir_all(&cwd).unwrap();
+
+        let result = resolve_workspace_from_cwd(&dwm_base, &cwd);
+        assert!(result.is_some());
+        let (resolved_repo, ws_name) = result.unwrap();
+        assert_eq!(resolved_repo, repo_dir);
+        assert_eq!(ws_name, "main-worktree");
+    }
+
+    #[test]
+    fn hook_handler_parse_pre_tool_use() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let repo_dir = dwm_base.join("myrepo-abc123");
+        fs::create_dir_all(&repo_dir).unwrap();
+
+        let ws_dir = repo_dir.join("my-feature");
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (repo, ws) = resolve_workspace_from_cwd(&dwm_base, &ws_dir).unwrap();
+        write_agent_status(
+            &repo,
+            "test-sess",
+            AgentKind::Claude,
+            &ws,
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+
+        let map = read_agent_summaries(&repo);
+        assert_eq!(map.get("my-feature").unwrap().working, 1);
+    }
+
+    #[test]
+    fn malformed_json_files_ignored() {
+        let dir = TempDir::new().unwrap();
+        let agent_dir = dir.path().join(".agent-status");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(agent_dir.join("bad.json"), "not valid json").unwrap();
+
+        let map = read_agent_summaries(dir.path());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn non_json_files_ignored() {
+        let dir = TempDir::new().unwrap();
+        let agent_dir = dir.path().join(".agent-status");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(agent_dir.join("readme.txt"), "hello").unwrap();
+
+        let map = read_agent_summaries(dir.path());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn setup_creates_fresh_settings() {
+        // Test the merge logic directly
+        let mut settings: serde_json::Value = serde_json::json!({});
+        let dwm_hooks = dwm_hook_config();
+
+        let hooks = settings
+            .as_object_mut()
+            .unwrap()
+            .entry("hooks")
+            .or_insert_with(|| serde_json::json!({}));
+        let hooks_obj = hooks.as_object_mut().unwrap();
+
+        for (event_name, dwm_groups) in dwm_hooks.as_object().unwrap() {
+            let existing = hooks_obj
+                .entry(event_name)
+                .or_insert_with(|| serde_json::json!([]));
+            let arr = existing.as_array_mut().unwrap();
+            for group in dwm_groups.as_array().unwrap() {
+                arr.push(group.clone());
+            }
+        }
+
+        assert!(hooks_obj.contains_key("PreToolUse"));
+        assert!(hooks_obj.contains_key("Stop"));
+        assert!(hooks_obj.contains_key("Notification"));
+        assert!(hooks_obj.contains_key("UserPromptSubmit"));
+        assert!(hooks_obj.contains_key("SessionEnd"));
+    }
+
+    #[test]
+    fn setup_preserves_existing_hooks() {
+        let mut settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "hooks": [{ "type": "command", "command": "my-other-tool" }] }
+                ]
+            }
+        });
+
+        let dwm_hooks = dwm_hook_config();
+        let hooks_obj = settings["hooks"].as_object_mut().unwrap();
+
+        for (event_name, dwm_groups) in dwm_hooks.as_object().unwrap() {
+            let existing = hooks_obj
+                .entry(event_name)
+                .or_insert_with(|| serde_json::json!([]));
+            let arr = existing.as_array_mut().unwrap();
+            let already_installed = arr.iter().any(|group| {
+                group
+                    .get("hooks")
+                    .and_then(|h| h.as_array())
+                    .map(|hooks| {
+                        hooks.iter().any(|h| {
+                            h.get("command")
+                                .and_then(|c| c.as_str())
+                                .is_some_and(|c| c == "dwm hook-handler")
+                        })
+                    })
+                    .unwrap_or(false)
+            });
+            if !already_installed {
+                for group in dwm_groups.as_array().unwrap() {
+                    arr.push(group.clone());
+                }
+            }
+        }
+
+        // PreToolUse should have both the existing and dwm hooks
+        let pre_tool = hooks_obj["PreToolUse"].as_array().unwrap();
+        assert_eq!(pre_tool.len(), 2);
+    }
+
+    #[test]
+    fn setup_does_not_duplicate() {
+        let mut settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ]
+            }
+        });
+
+        let dwm_hooks = dwm_hook_config();
+        let hooks_obj = settings["hooks"].as_object_mut().unwrap();
+
+        for (event_name, dwm_groups) in dwm_hooks.as_object().unwrap() {
+            let existing = hooks_obj
+                .entry(event_name)
+                .or_insert_with(|| serde_json::json!([]));
+            let arr = existing.as_array_mut().unwrap();
+            let already_installed = arr.iter().any(|group| {
+                group
+                    .get("hooks")
+                    .and_then(|h| h.as_array())
+                    .map(|hooks| {
+                        hooks.iter().any(|h| {
+                            h.get("command")
+                                .and_then(|c| c.as_str())
+                                .is_some_and(|c| c == "dwm hook-handler")
+                        })
+                    })
+                    .unwrap_or(false)
+            });
+            if !already_installed {
+                for group in dwm_groups.as_array().unwrap() {
+                    arr.push(group.clone());
+                }
+            }
+        }
+
+        // PreToolUse should still have just 1 entry (not duplicated)
+        let pre_tool = hooks_obj["PreToolUse"].as_array().unwrap();
+        assert_eq!(pre_tool.len(), 1);
+    }
+
+    // --- Gap: CLI parse tests for new subcommands ---
+
+    // --- Gap: resolve_workspace_from_cwd with jj VcsType ---
+
+    #[test]
+    fn resolve_cwd_main_repo_jj() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let repo_dir = dwm_base.join("myrepo-abc123");
+        fs::create_dir_all(&repo_dir).unwrap();
+
+        let main_repo = dir.path().join("repos").join("myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        fs::write(
+            repo_dir.join(".main-repo"),
+            main_repo.to_string_lossy().as_ref(),
+        )
+        .unwrap();
+        fs::write(repo_dir.join(".vcs-type"), "jj").unwrap();
+
+        let cwd = main_repo.join("src");
+        fs::create_dir_all(&cwd).unwrap();
+
+        let result = resolve_workspace_from_cwd(&dwm_base, &cwd);
+        let (resolved_repo, ws_name) = result.unwrap();
+        assert_eq!(resolved_repo, repo_dir);
+        assert_eq!(ws_name, "default");
+    }
+
+    #[test]
+    fn resolve_cwd_main_repo_no_vcs_type_defaults_to_jj() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let repo_dir = dwm_base.join("myrepo-abc123");
+        fs::create_dir_all(&repo_dir).unwrap();
+
+        let main_repo = dir.path().join("repos").join("myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        fs::write(
+            repo_dir.join(".main-repo"),
+            main_repo.to_string_lossy().as_ref(),
+        )
+        .unwrap();
+        // No .vcs-type file — should default to jj ("default")
+
+        let result = resolve_workspace_from_cwd(&dwm_base, &main_repo);
+        let (_resolved_repo, ws_name) = result.unwrap();
+        assert_eq!(ws_name, "default");
+    }
+
+    // --- Gap: stale boundary condition (exactly at threshold) ---
+
+    #[test]
+    fn stale_boundary_exactly_at_threshold_is_not_stale() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        let at_boundary = now - STALE_TIMEOUT.as_secs();
+        write_status_file(dir.path(), "sess", "ws", "working", at_boundary);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        // updated_at is exactly at the threshold; check is `>` not `>=`, so NOT stale
+        let summary = map.get("ws").unwrap();
+        assert_eq!(summary.working, 1);
+    }
+
+    // --- Gap: remove_agent_status when file doesn't exist ---
+
+    #[test]
+    fn remove_nonexistent_status_is_ok() {
+        let dir = TempDir::new().unwrap();
+        // No status file exists; should not error
+        let result = remove_agent_status(dir.path(), "nonexistent-session");
+        assert!(result.is_ok());
+    }
+
+    // --- Gap: handle_hook silently ignores missing session_id/cwd ---
+
+    #[test]
+    fn resolve_cwd_dwm_base_only_returns_none() {
+        // cwd is exactly the dwm_base with only one path component (repo name, no workspace)
+        let dwm_base = PathBuf::from("/home/user/.dwm");
+        let cwd = PathBuf::from("/home/user/.dwm/myrepo-abc123");
+
+        let result = resolve_workspace_from_cwd(&dwm_base, &cwd);
+        assert!(
+            result.is_none(),
+            "should need both repo and workspace components"
+        );
+    }
+
+    // --- Gap: AgentStatus serde roundtrip ---
+
+    #[test]
+    fn agent_status_serde_roundtrip() {
+        for status in [
+            AgentStatus::Working,
+            AgentStatus::Idle,
+            AgentStatus::Waiting,
+        ] {
+            let json = serde_json::to_string(&status).unwrap();
+            let back: AgentStatus = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, status);
+        }
+    }
+
+    #[test]
+    fn agent_status_file_serde_roundtrip() {
+        let file = AgentStatusFile {
+            agent: AgentKind::Codex,
+            model: None,
+            workspace: "my-ws".to_string(),
+            status: AgentStatus::Waiting,
+            started_at: None,
+            updated_at: 1234567890,
+            last_prompt: None,
+            last_tool: Some("Edit".to_string()),
+            touched: Vec::new(),
+            waiting_since: None,
+        };
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(!json.contains("last_prompt"));
+        assert!(!json.contains("touched"));
+        let back: AgentStatusFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.agent, AgentKind::Codex);
+        assert_eq!(back.workspace, "my-ws");
+        assert_eq!(back.status, AgentStatus::Waiting);
+        assert_eq!(back.updated_at, 1234567890);
+        assert_eq!(back.last_tool.as_deref(), Some("Edit"));
+    }
+
+    // --- Gap: all stale entries → workspace not in map ---
+
+    #[test]
+    fn all_stale_entries_result_in_empty_map() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        let old = now - STALE_TIMEOUT.as_secs() - 100;
+        write_status_file(dir.path(), "s1", "ws", "working", old);
+        write_status_file(dir.path(), "s2", "ws", "idle", old);
+        let older = now - WAITING_STALE_TIMEOUT.as_secs() - 100;
+        write_status_file(dir.path(), "s3", "ws", "waiting", older);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn waiting_sessions_outlive_the_stale_timeout() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        let waited = now - 3600;
+        write_status_file(dir.path(), "s1", "ws", "waiting", waited);
+        write_status_file(dir.path(), "s2", "ws", "working", waited);
+
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &StaleTimeouts::default());
+        let summary = map.get("ws").unwrap();
+        assert_eq!((summary.waiting, summary.working), (1, 0));
+        // Files from before waiting was tracked count from their last update.
+        assert_eq!(summary.waiting_since, Some(epoch(waited)));
+        assert_eq!(
+            summary.waiting_for(epoch(now)),
+            Some(Duration::from_secs(3600))
+        );
+    }
+
+    #[test]
+    fn stale_timeouts_can_be_overridden_per_status() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        write_status_file(dir.path(), "s1", "ws", "working", now - 120);
+        write_status_file(dir.path(), "s2", "ws", "idle", now - 120);
+        write_status_file(dir.path(), "s3", "ws", "waiting", now - 120);
+
+        let timeouts = StaleTimeouts {
+            default: Some(60),
+            idle: Some(300),
+            waiting: Some(100),
+            ..Default::default()
+        };
+        let map = read_agent_summaries_at(dir.path(), epoch(now), &timeouts);
+        let summary = map.get("ws").unwrap();
+        assert_eq!((summary.working, summary.idle, summary.waiting), (0, 1, 0));
+    }
+
+    #[test]
+    fn stale_timeouts_load_from_config() {
+        let dir = TempDir::new().unwrap();
+        let repo_dir = dir.path().join("repo");
+        fs::create_dir_all(&repo_dir).unwrap();
+        assert_eq!(StaleTimeouts::for_repo(&repo_dir), StaleTimeouts::default());
+
+        fs::write(
+            dir.path().join("config.json"),
+            r#"{"stale_timeouts": {"working": 30}}"#,
+        )
+        .unwrap();
+        let timeouts = StaleTimeouts::for_repo(&repo_dir);
+        assert_eq!(
+            timeouts.of(AgentStatus::RunningTool),
+            Duration::from_secs(30)
+        );
+        assert_eq!(timeouts.of(AgentStatus::Idle), STALE_TIMEOUT);
+        assert_eq!(timeouts.of(AgentStatus::Waiting), WAITING_STALE_TIMEOUT);
+    }
+
+    #[test]
+    fn waiting_since_survives_repeat_waits_and_clears_after() {
+        let dir = TempDir::new().unwrap();
+        let now = epoch_secs(SystemTime::now());
+        let status_dir = agent_status_dir(dir.path());
+        fs::create_dir_all(&status_dir).unwrap();
+        fs::write(
+            status_dir.join("s1.json"),
+            format!(
+                r#"{{"workspace":"ws","status":"waiting","updated_at":{},"waiting_since":{}}}"#,
+                now - 60,
+                now - 720
+            ),
+        )
+        .unwrap();
+        let read = || -> AgentStatusFile {
+            serde_json::from_str(&fs::read_to_string(status_dir.join("s1.json")).unwrap()).unwrap()
+        };
+        let write = |status| {
+            write_agent_status(
+                dir.path(),
+                "s1",
+                AgentKind::Claude,
+                "ws",
+                status,
+                AgentActivity::Unchanged,
+                None,
+            )
+            .unwrap();
+        };
+
+        write(AgentStatus::Waiting);
+        assert_eq!(read().waiting_since, Some(now - 720));
+        write(AgentStatus::Working);
+        assert_eq!(read().waiting_since, None);
+        write(AgentStatus::Waiting);
+        assert!(read().waiting_since.unwrap() >= now);
+    }
+
+    #[test]
+    fn longest_waiting_sessions_come_first() {
+        let dir = TempDir::new().unwrap();
+        let now = 1_000_000u64;
+        write_status_file(dir.path(), "recent", "ws", "waiting", now - 60);
+        write_status_file(dir.path(), "oldest", "ws", "waiting", now - 1800);
+        write_status_file(dir.path(), "busy", "ws", "working", now);
+
+        let mut sessions: Vec<AgentSession> =
+            read_live_sessions_at(dir.path(), epoch(now), &StaleTimeouts::default())
+                .into_iter()
+                .map(|(_, s)| s)
+                .collect();
+        sessions.sort_by(session_order);
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["oldest", "recent", "busy"]);
+    }
+
+    // --- Gap: write_agent_status overwrites existing session file ---
+
+    #[test]
+    fn write_overwrites_previous_status_for_same_session() {
+        let dir = TempDir::new().unwrap();
+        write_agent_status(
+            dir.path(),
+            "sess-1",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        write_agent_status(
+            dir.path(),
+            "sess-1",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Waiting,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+
+        let map = read_agent_summaries(dir.path());
+        let summary = map.get("ws").unwrap();
+        // Should have 1 waiting, NOT 1 working + 1 waiting
+        assert_eq!(summary.waiting, 1);
+        assert_eq!(summary.working, 0);
+    }
+
+    // --- Gap: dwm_hook_config produces expected event keys ---
+
+    #[test]
+    fn hook_config_has_expected_events() {
+        let config = dwm_hook_config();
+        let obj = config.as_object().unwrap();
+        assert!(obj.contains_key("PreToolUse"));
+        assert!(obj.contains_key("Stop"));
+        assert!(obj.contains_key("Notification"));
+        assert!(obj.contains_key("UserPromptSubmit"));
+        assert!(obj.contains_key("SessionEnd"));
+        assert!(obj.contains_key("PostToolUse"));
+        assert!(obj.contains_key("SubagentStop"));
+        assert_eq!(obj.len(), 7);
+    }
+
+    #[test]
+    fn hooks_already_installed_detects_presence() {
+        let settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ],
+                "PostToolUse": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ],
+                "SubagentStop": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ],
+                "Stop": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ],
+                "Notification": [
+                    { "matcher": "idle_prompt|permission_prompt", "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ],
+                "UserPromptSubmit": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ],
+                "SessionEnd": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ]
+            }
+        });
+        assert!(hooks_already_installed(&settings, &dwm_hook_config()));
+
+        // Settings from before PostToolUse and SubagentStop were hooked get
+        // them added on the next setup.
+        let mut older = settings.clone();
+        let hooks = older["hooks"].as_object_mut().unwrap();
+        hooks.remove("PostToolUse");
+        hooks.remove("SubagentStop");
+        assert!(!hooks_already_installed(&older, &dwm_hook_config()));
+    }
+
+    #[test]
+    fn project_settings_live_in_repo_claude_dir() {
+        let root = Path::new("/src/api");
+        assert_eq!(
+            project_settings_path(root, false),
+            PathBuf::from("/src/api/.claude/settings.json")
+        );
+        assert_eq!(
+            project_settings_path(root, true),
+            PathBuf::from("/src/api/.claude/settings.local.json")
+        );
+    }
+
+    #[test]
+    fn hooks_already_installed_false_when_missing() {
+        let settings = serde_json::json!({});
+        assert!(!hooks_already_installed(&settings, &dwm_hook_config()));
+    }
+
+    #[test]
+    fn hooks_already_installed_false_when_partial() {
+        let settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ]
+            }
+        });
+        assert!(!hooks_already_installed(&settings, &dwm_hook_config()));
+    }
+
+    #[test]
+    fn hook_config_notification_has_matcher() {
+        let config = dwm_hook_config();
+        let notif = config["Notification"].as_array().unwrap();
+        assert_eq!(notif.len(), 1);
+        let matcher = notif[0]["matcher"].as_str().unwrap();
+        assert!(matcher.contains("idle_prompt"));
+        assert!(matcher.contains("permission_prompt"));
+    }
+
+    #[test]
+    fn merge_dwm_hooks_creates_fresh_settings() {
+        let settings = serde_json::json!({});
+        let merged = merge_dwm_hooks(settings, &dwm_hook_config()).unwrap();
+
+        let hooks = merged["hooks"].as_object().unwrap();
+        assert!(hooks.contains_key("PreToolUse"));
+        assert!(hooks.contains_key("Stop"));
+        assert!(hooks.contains_key("Notification"));
+        assert!(hooks.contains_key("UserPromptSubmit"));
+        assert!(hooks.contains_key("SessionEnd"));
+
+        // Check one specifically
+        let pre_tool = hooks["PreToolUse"].as_array().unwrap();
+        assert_eq!(pre_tool.len(), 1);
+        assert_eq!(pre_tool[0]["hooks"][0]["command"], "dwm hook-handler");
+    }
+
+    #[test]
+    fn merge_dwm_hooks_preserves_existing_hooks() {
+        let settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "hooks": [{ "type": "command", "command": "my-other-tool" }] }
+                ]
+            },
+            "other_setting": "val"
+        });
+
+        let merged = merge_dwm_hooks(settings, &dwm_hook_config()).unwrap();
+        let pre_tool = merged["hooks"]["PreToolUse"].as_array().unwrap();
+
+        assert_eq!(pre_tool.len(), 2);
+        assert_eq!(pre_tool[0]["hooks"][0]["command"], "my-other-tool");
+        assert_eq!(pre_tool[1]["hooks"][0]["command"], "dwm hook-handler");
+        assert_eq!(merged["other_setting"], "val");
+    }
+
+    #[test]
+    fn merge_dwm_hooks_does_not_duplicate() {
+        let settings = serde_json::json!({
+            "hooks": {
+                "PreToolUse": [
+                    { "hooks": [{ "type": "command", "command": "dwm hook-handler" }] }
+                ]
+            }
+        });
+
+        let merged = merge_dwm_hooks(settings, &dwm_hook_config()).unwrap();
+        let pre_tool = merged["hooks"]["PreToolUse"].as_array().unwrap();
+
+        // Should still be just 1
+        assert_eq!(pre_tool.len(), 1);
+    }
+
+    #[test]
+    fn merge_dwm_hooks_errors_on_invalid_structure() {
+        let settings = serde_json::json!([]); // Not an object
+        assert!(merge_dwm_hooks(settings, &dwm_hook_config()).is_err());
+
+        let settings = serde_json::json!({ "hooks": [] }); // hooks should be an object
+        assert!(merge_dwm_hooks(settings, &dwm_hook_config()).is_err());
+
+        let settings = serde_json::json!({ "hooks": { "PreToolUse": {} } }); // event should be an array
+        assert!(merge_dwm_hooks(settings, &dwm_hook_config()).is_err());
+    }
+
+    // --- Other agents ---
+
+    fn status_update(status: AgentStatus) -> HookUpdate<'static> {
+        HookUpdate::Status(status, AgentActivity::Unchanged)
+    }
+
+    #[test]
+    fn detect_agent_from_payload_shape() {
+        let claude = serde_json::json!({ "hook_event_name": "Stop", "session_id": "s" });
+        let gemini = serde_json::json!({ "hook_event_name": "AfterAgent", "session_id": "s" });
+        let codex = serde_json::json!({ "type": "agent-turn-complete", "thread-id": "t" });
+        let opencode = serde_json::json!({ "event": "session.idle", "session_id": "s" });
+        assert_eq!(detect_agent(&claude), AgentKind::Claude);
+        assert_eq!(detect_agent(&gemini), AgentKind::Gemini);
+        assert_eq!(detect_agent(&codex), AgentKind::Codex);
+        assert_eq!(detect_agent(&opencode), AgentKind::Opencode);
+    }
+
+    #[test]
+    fn claude_hook_events_map_to_statuses() {
+        let cwd = Path::new("/work");
+        let json = serde_json::json!({
+            "hook_event_name": "UserPromptSubmit",
+            "session_id": "abc",
+            "cwd": "/ws",
+            "prompt": "fix it",
+        });
+        let event = parse_hook_event(AgentKind::Claude, &json, cwd).unwrap();
+        assert_eq!(event.session_id, "abc");
+        assert_eq!(event.cwd, PathBuf::from("/ws"));
+        assert_eq!(
+            event.update,
+            HookUpdate::Status(AgentStatus::Thinking, AgentActivity::Prompt("fix it"))
+        );
+
+        for name in ["PostToolUse", "SubagentStop"] {
+            let json = serde_json::json!({ "hook_event_name": name, "session_id": "abc" });
+            let event = parse_hook_event(AgentKind::Claude, &json, cwd).unwrap();
+            assert_eq!(event.update, status_update(AgentStatus::Thinking));
+        }
+
+        let json = serde_json::json!({
+            "hook_event_name": "Notification",
+            "session_id": "abc",
+            "notification_type": "permission_prompt",
+        });
+        let event = parse_hook_event(AgentKind::Claude, &json, cwd).unwrap();
+        assert_eq!(event.update, status_update(AgentStatus::Waiting));
+        // Without a cwd in the payload, the hook's own directory is used.
+        assert_eq!(event.cwd, PathBuf::from("/work"));
+
+        let json = serde_json::json!({
+            "hook_event_name": "Notification",
+            "session_id": "abc",
+            "notification_type": "auth_success",
+        });
+        assert!(parse_hook_event(AgentKind::Claude, &json, cwd).is_none());
+
+        let json = serde_json::json!({ "hook_event_name": "Stop" });
+        assert!(parse_hook_event(AgentKind::Claude, &json, cwd).is_none());
+    }
+
+    #[test]
+    fn gemini_hook_events_map_to_statuses() {
+        let cwd = Path::new("/ws");
+        let event = |name: &str| {
+            serde_json::json!({
+                "hook_event_name": name,
+                "session_id": "g1",
+                "tool_name": "run_shell_command",
+                "prompt": "add tests",
+            })
+        };
+        let json = event("BeforeTool");
+        let parsed = parse_hook_event(AgentKind::Gemini, &json, cwd).unwrap();
+        assert_eq!(parsed.session_id, "gemini-g1");
+        assert_eq!(
+            parsed.update,
+            HookUpdate::Status(
+                AgentStatus::RunningTool,
+                AgentActivity::Tool("run_shell_command", None)
+            )
+        );
+        let json = event("AfterTool");
+        assert_eq!(
+            parse_hook_event(AgentKind::Gemini, &json, cwd)
+                .unwrap()
+                .update,
+            status_update(AgentStatus::Thinking)
+        );
+        let json = event("BeforeAgent");
+        assert_eq!(
+            parse_hook_event(AgentKind::Gemini, &json, cwd)
+                .unwrap()
+                .update,
+            HookUpdate::Status(AgentStatus::Thinking, AgentActivity::Prompt("add tests"))
+        );
+        let json = event("AfterAgent");
+        assert_eq!(
+            parse_hook_event(AgentKind::Gemini, &json, cwd)
+                .unwrap()
+                .update,
+            status_update(AgentStatus::Idle)
+        );
+        let json = event("Notification");
+        assert_eq!(
+            parse_hook_event(AgentKind::Gemini, &json, cwd)
+                .unwrap()
+                .update,
+            status_update(AgentStatus::Waiting)
+        );
+        let json = event("SessionEnd");
+        assert_eq!(
+            parse_hook_event(AgentKind::Gemini, &json, cwd)
+                .unwrap()
+                .update,
+            HookUpdate::End
+        );
+        let json = event("AfterModel");
+        assert!(parse_hook_event(AgentKind::Gemini, &json, cwd).is_none());
+    }
+
+    #[test]
+    fn codex_turn_complete_is_idle_with_last_prompt() {
+        let json = serde_json::json!({
+            "type": "agent-turn-complete",
+            "thread-id": "t-42",
+            "turn-id": "1",
+            "cwd": "/ws",
+            "input-messages": ["first", "second"],
+            "last-assistant-message": "done",
+        });
+        let event = parse_hook_event(AgentKind::Codex, &json, Path::new("/other")).unwrap();
+        assert_eq!(event.agent, AgentKind::Codex);
+        assert_eq!(event.session_id, "codex-t-42");
+        assert_eq!(event.cwd, PathBuf::from("/ws"));
+        assert_eq!(
+            event.update,
+            HookUpdate::Status(AgentStatus::Idle, AgentActivity::Prompt("second"))
+        );
+    }
+
+    #[test]
+    fn codex_without_thread_id_is_keyed_by_directory() {
+        let json = serde_json::json!({ "type": "agent-turn-complete" });
+        let event = parse_hook_event(AgentKind::Codex, &json, Path::new("/ws/app")).unwrap();
+        assert_eq!(
+            event.session_id,
+            format!("codex-{}", vcs::repo_dir_name(Path::new("/ws/app")))
+        );
+        assert_eq!(event.update, status_update(AgentStatus::Idle));
+
+        let json = serde_json::json!({ "type": "something-else" });
+        assert!(parse_hook_event(AgentKind::Codex, &json, Path::new("/ws")).is_none());
+    }
+
+    #[test]
+    fn opencode_events_map_to_statuses() {
+        let cwd = Path::new("/ws");
+        let parse = |json: serde_json::Value| {
+            parse_hook_event(AgentKind::Opencode, &json, cwd).map(|e| {
+                assert_eq!(e.session_id, "opencode-ses_1");
+                match e.update {
+                    HookUpdate::Status(status, _) => Some(status),
+                    HookUpdate::End => None,
+                }
+            })
+        };
+        let event = |name: &str| serde_json::json!({ "event": name, "session_id": "ses_1" });
+        assert_eq!(
+            parse(
+                serde_json::json!({ "event": "session.status", "session_id": "ses_1", "status": "busy" })
+            ),
+            Some(Some(AgentStatus::Working))
+        );
+        assert_eq!(parse(event("session.idle")), Some(Some(AgentStatus::Idle)));
+        assert_eq!(
+            parse(event("permission.updated")),
+            Some(Some(AgentStatus::Waiting))
+        );
+        assert_eq!(parse(event("session.deleted")), Some(None));
+        assert_eq!(parse(event("message.part.updated")), None);
+
+        let json = serde_json::json!({
+            "event": "tool.execute.before",
+            "session_id": "ses_1",
+            "tool": "edit",
+            "path": "/ws/src/app.ts",
+        });
+        assert_eq!(
+            parse_hook_event(AgentKind::Opencode, &json, cwd)
+                .unwrap()
+                .update,
+            HookUpdate::Status(
+                AgentStatus::RunningTool,
+                AgentActivity::Tool("edit", Some("/ws/src/app.ts"))
+            )
+        );
+        assert_eq!(
+            parse(event("tool.execute.after")),
+            Some(Some(AgentStatus::Thinking))
+        );
+    }
+
+    #[test]
+    fn aider_notification_is_waiting_in_current_dir() {
+        let event = parse_hook_event(
+            AgentKind::Aider,
+            &serde_json::Value::Null,
+            Path::new("/ws/app"),
+        )
+        .unwrap();
+        assert_eq!(event.cwd, PathBuf::from("/ws/app"));
+        assert!(event.session_id.starts_with("aider-app-"));
+        assert_eq!(event.update, status_update(AgentStatus::Waiting));
+    }
+
+    #[test]
+    fn apply_hook_event_records_agent_and_ends_session() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let ws = dwm_base.join("repo-abc").join("feature");
+        fs::create_dir_all(&ws).unwrap();
+
+        let json = serde_json::json!({ "type": "agent-turn-complete", "thread-id": "t1" });
+        let event = parse_hook_event(AgentKind::Codex, &json, &ws).unwrap();
+        apply_hook_event(&dwm_base, &event, &|_, _, _| {}).unwrap();
+
+        let repo_dir = dwm_base.join("repo-abc");
+        let sessions = read_agent_sessions(&repo_dir, "feature");
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "codex-t1");
+        assert_eq!(sessions[0].agent, AgentKind::Codex);
+        assert_eq!(sessions[0].status, AgentStatus::Idle);
+
+        let event = HookEvent {
+            update: HookUpdate::End,
+            ..event
+        };
+        apply_hook_event(&dwm_base, &event, &|_, _, _| {}).unwrap();
+        assert!(read_agent_sessions(&repo_dir, "feature").is_empty());
+    }
+
+    #[test]
+    fn launch_registers_the_session_while_the_agent_runs() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let ws = dwm_base.join("repo-abc").join("feature");
+        fs::create_dir_all(&ws).unwrap();
+        let repo_dir = dwm_base.join("repo-abc");
+
+        // The agent sees its session's status file and the id to report as.
+        let command = format!(
+            "cp \"{}/$DWM_SESSION_ID.json\" seen.json",
+            agent_status_dir(&repo_dir).display()
+        );
+        launch_agent_at(
+            &dwm_base,
+            AgentKind::Codex,
+            &command,
+            &ws,
+            Some("o3"),
+            &|_, _, _| {},
+        )
+        .unwrap();
+
+        let seen: AgentStatusFile =
+            serde_json::from_str(&fs::read_to_string(ws.join("seen.json")).unwrap()).unwrap();
+        assert_eq!(seen.agent, AgentKind::Codex);
+        assert_eq!(seen.status, AgentStatus::Idle);
+        assert_eq!(seen.model.as_deref(), Some("o3"));
+
+        assert!(read_agent_sessions(&repo_dir, "feature").is_empty());
+        let history = read_history(&repo_dir, "feature");
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].status, Some(AgentStatus::Idle));
+        assert_eq!(history[1].status, None);
+    }
+
+    #[test]
+    fn launch_reports_a_failed_agent_and_still_ends_it() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let ws = dwm_base.join("repo-abc").join("feature");
+        fs::create_dir_all(&ws).unwrap();
+
+        let err = launch_agent_at(
+            &dwm_base,
+            AgentKind::Claude,
+            "exit 3",
+            &ws,
+            None,
+            &|_, _, _| {},
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("exited"), "error: {}", err);
+        assert!(read_agent_sessions(&dwm_base.join("repo-abc"), "feature").is_empty());
+
+        let outside = dir.path().join("elsewhere");
+        fs::create_dir_all(&outside).unwrap();
+        assert!(
+            launch_agent_at(
+                &dwm_base,
+                AgentKind::Claude,
+                "true",
+                &outside,
+                None,
+                &|_, _, _| {}
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn finer_statuses_count_and_log_as_working() {
+        // Files written before the finer statuses still read as working.
+        let old = TempDir::new().unwrap();
+        write_status_file(old.path(), "old", "ws", "working", 1_000_000);
+        let map = read_agent_summaries_at(old.path(), epoch(1_000_000), &StaleTimeouts::default());
+        assert_eq!(map.get("ws").unwrap().working, 1);
+
+        let dir = TempDir::new().unwrap();
+        let write = |status| {
+            write_agent_status(
+                dir.path(),
+                "s1",
+                AgentKind::Claude,
+                "ws",
+                status,
+                AgentActivity::Unchanged,
+                None,
+            )
+            .unwrap()
+        };
+        assert!(write(AgentStatus::Thinking).is_some());
+        assert!(write(AgentStatus::RunningTool).is_none());
+        assert!(write(AgentStatus::Thinking).is_none());
+        let content = fs::read_to_string(agent_status_dir(dir.path()).join("s1.json")).unwrap();
+        assert!(content.contains(r#""status":"thinking""#), "{}", content);
+        write(AgentStatus::RunningTool);
+        let content = fs::read_to_string(agent_status_dir(dir.path()).join("s1.json")).unwrap();
+        assert!(
+            content.contains(r#""status":"running_tool""#),
+            "{}",
+            content
+        );
+
+        let history = read_history(dir.path(), "ws");
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].status, Some(AgentStatus::Working));
+
+        let summary = read_agent_summaries(dir.path());
+        let summary = summary.get("ws").unwrap();
+        assert_eq!(summary.working, 1);
+        assert_eq!(summary.most_urgent(), Some(AgentStatus::Working));
+        assert_eq!(summary.to_string(), "claude running tool");
+    }
+
+    #[test]
+    fn claude_tool_use_names_its_file() {
+        let cwd = Path::new("/ws");
+        let tool_use = |tool: &str, input: serde_json::Value| {
+            serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "session_id": "s1",
+                "tool_name": tool,
+                "tool_input": input,
+            })
+        };
+        let json = tool_use("Edit", serde_json::json!({ "file_path": "/ws/src/tui.rs" }));
+        assert_eq!(
+            parse_hook_event(AgentKind::Claude, &json, cwd)
+                .unwrap()
+                .update,
+            HookUpdate::Status(
+                AgentStatus::RunningTool,
+                AgentActivity::Tool("Edit", Some("/ws/src/tui.rs"))
+            )
+        );
+        let json = tool_use("Bash", serde_json::json!({ "command": "cargo test" }));
+        assert_eq!(
+            parse_hook_event(AgentKind::Claude, &json, cwd)
+                .unwrap()
+                .update,
+            HookUpdate::Status(AgentStatus::RunningTool, AgentActivity::Tool("Bash", None))
+        );
+    }
+
+    #[test]
+    fn touched_files_are_relative_and_most_recent_first() {
+        let dir = TempDir::new().unwrap();
+        let dwm_base = dir.path().join(".dwm");
+        let ws = dwm_base.join("repo-abc").join("feature");
+        fs::create_dir_all(&ws).unwrap();
+        let repo_dir = dwm_base.join("repo-abc");
+
+        let edit = |path: &Path| {
+            let json = serde_json::json!({
+                "hook_event_name": "PreToolUse",
+                "session_id": "s1",
+                "cwd": ws,
+                "tool_name": "Edit",
+                "tool_input": { "file_path": path },
+            });
+            let event = parse_hook_event(AgentKind::Claude, &json, &ws).unwrap();
+            apply_hook_event(&dwm_base, &event, &|_, _, _| {}).unwrap();
+        };
+        edit(&ws.join("src").join("tui.rs"));
+        edit(Path::new("/etc/hosts"));
+        edit(&ws.join("src").join("tui.rs"));
+
+        let sessions = read_agent_sessions(&repo_dir, "feature");
+        assert_eq!(sessions[0].touched, vec!["src/tui.rs", "/etc/hosts"]);
+
+        for i in 0..TOUCHED_LIMIT {
+            edit(&ws.join(format!("f{}.rs", i)));
+        }
+        let sessions = read_agent_sessions(&repo_dir, "feature");
+        assert_eq!(sessions[0].touched.len(), TOUCHED_LIMIT);
+        assert_eq!(sessions[0].touched[0], format!("f{}.rs", TOUCHED_LIMIT - 1));
+    }
+
+    #[test]
+    fn workspace_touched_merges_sessions_newest_first() {
+        let dir = TempDir::new().unwrap();
+        let now = epoch_secs(SystemTime::now());
+        let write = |session: &str, updated_at: u64, touched: &[&str]| {
+            let file = AgentStatusFile {
+                agent: AgentKind::Claude,
+                model: None,
+                workspace: "ws".to_string(),
+                status: AgentStatus::Working,
+                started_at: None,
+                updated_at,
+                last_prompt: None,
+                last_tool: None,
+                touched: touched.iter().map(|p| p.to_string()).collect(),
+                waiting_since: None,
+            };
+            let status_dir = agent_status_dir(dir.path());
+            fs::create_dir_all(&status_dir).unwrap();
+            fs::write(
+                status_dir.join(format!("{}.json", session)),
+                serde_json::to_string(&file).unwrap(),
+            )
+            .unwrap();
+        };
+        write("old", now - 60, &["a.rs", "b.rs"]);
+        write("new", now, &["c.rs", "a.rs"]);
+        assert_eq!(
+            workspace_touched(dir.path(), "ws"),
+            vec!["c.rs", "a.rs", "b.rs"]
+        );
+    }
+
+    #[test]
+    fn status_file_without_agent_is_claude() {
+        let dir = TempDir::new().unwrap();
+        write_status_file(dir.path(), "sess", "ws", "idle", 1_000_000);
+        let sessions = read_agent_sessions_at(
+            dir.path(),
+            "ws",
+            epoch(1_000_000),
+            &StaleTimeouts::default(),
+        );
+        assert_eq!(sessions[0].agent, AgentKind::Claude);
+    }
+
+    #[test]
+    fn codex_notify_goes_before_first_table() {
+        let config = "model = \"o3\"\n\n[mcp_servers.docs]\ncommand = \"docs\"\n";
+        let updated = add_codex_notify(config).unwrap().unwrap();
+        assert_eq!(
+            updated,
+            format!(
+                "model = \"o3\"\n\n{}\n[mcp_servers.docs]\ncommand = \"docs\"\n",
+                CODEX_NOTIFY
+            )
+        );
+        assert_eq!(add_codex_notify(&updated).unwrap(), None);
+        assert_eq!(
+            add_codex_notify("").unwrap().unwrap(),
+            format!("{}\n", CODEX_NOTIFY)
+        );
+    }
+
+    #[test]
+    fn codex_notify_keeps_someone_elses_command() {
+        let config = "notify = [\"notify-send\", \"done\"]\n";
+        assert!(add_codex_notify(config).is_err());
+        // A `notify` key inside a table isn't the top-level one.
+        let config = "[profiles.quiet]\nnotify = []\n";
+        let updated = add_codex_notify(config).unwrap().unwrap();
+        assert!(updated.starts_with(CODEX_NOTIFY));
+    }
+
+    #[test]
+    fn aider_notifications_are_turned_on_and_sent_to_dwm() {
+        let updated = add_aider_notifications("model: sonnet\nnotifications: false\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            updated,
+            "model: sonnet\nnotifications: true\nnotifications-command: dwm hook-handler --agent aider\n"
+        );
+        assert_eq!(add_aider_notifications(&updated).unwrap(), None);
+        assert!(add_aider_notifications("notifications-command: say done\n").is_err());
+    }
+
+    #[test]
+    fn gemini_hooks_merge_into_settings() {
+        let existing = r#"{ "theme": "Dracula" }"#;
+        let updated = install_agent_hooks(AgentKind::Gemini, Some(existing))
+            .unwrap()
+            .unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&updated).unwrap();
+        assert_eq!(settings["theme"], "Dracula");
+        assert_eq!(
+            settings["hooks"]["BeforeTool"][0]["hooks"][0]["command"],
+            "dwm hook-handler --agent gemini"
+        );
+        assert_eq!(
+            install_agent_hooks(AgentKind::Gemini, Some(&updated)).unwrap(),
+            None
+        );
+        // Claude's hooks in the same shape don't count as Gemini's.
+        let claude = install_agent_hooks(AgentKind::Claude, None)
+            .unwrap()
+            .unwrap();
+        assert!(
+            install_agent_hooks(AgentKind::Gemini, Some(&claude))
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn opencode_plugin_is_written_once() {
+        let plugin = install_agent_hooks(AgentKind::Opencode, None)
+            .unwrap()
+            .unwrap();
+        assert!(plugin.contains("\"hook-handler\", \"--agent\", \"opencode\""));
+        assert_eq!(
+            install_agent_hooks(AgentKind::Opencode, Some(&plugin)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn json_hooks_are_removed_leaving_the_rest() {
+        let existing = r#"{
+  "theme": "dark",
+  "hooks": {
+    "Stop": [
+      { "hooks": [{ "type": "command", "command": "afplay done.aiff" }] }
+    ]
+  }
+}"#;
+        let installed = install_agent_hooks(AgentKind::Claude, Some(existing))
+            .unwrap()
+            .unwrap();
+        let removed = remove_agent_hooks(AgentKind::Claude, &installed)
+            .unwrap()
+            .unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&removed).unwrap();
+        assert_eq!(
+            settings,
+            serde_json::from_str::<serde_json::Value>(existing).unwrap()
+        );
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Claude, &removed).unwrap(),
+            None
+        );
+
+        let gemini = install_agent_hooks(AgentKind::Gemini, Some("{}"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Gemini, &gemini)
+                .unwrap()
+                .as_deref(),
+            Some("{}")
+        );
+    }
+
+    #[test]
+    fn line_hooks_are_removed() {
+        let codex = add_codex_notify("model = \"o3\"\n").unwrap().unwrap();
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Codex, &codex)
+                .unwrap()
+                .as_deref(),
+            Some("model = \"o3\"\n")
+        );
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Codex, "notify = [\"say\"]\n").unwrap(),
+            None
+        );
+
+        let aider = add_aider_notifications("model: sonnet\n").unwrap().unwrap();
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Aider, &aider)
+                .unwrap()
+                .as_deref(),
+            Some("model: sonnet\n")
+        );
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Aider, "notifications: true\n").unwrap(),
+            None
+        );
+
+        // opencode's plugin file is dwm's alone, so it empties.
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Opencode, OPENCODE_PLUGIN)
+                .unwrap()
+                .as_deref(),
+            Some("")
+        );
+        assert_eq!(
+            remove_agent_hooks(AgentKind::Opencode, "export const Other = {};").unwrap(),
+            None
+        );
+    }
+
+    // --- Agent listings across repos ---
+
+    fn write_repo_status(
+        dwm_base: &Path,
+        repo_dir: &str,
+        main_repo: &str,
+        session: &str,
+        workspace: &str,
+        status: &str,
+        updated_at: u64,
+    ) {
+        let dir = dwm_base.join(repo_dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".main-repo"), main_repo).unwrap();
+        write_status_file(&dir, session, workspace, status, updated_at);
+    }
+
+    #[test]
+    fn gc_removes_stale_files_and_ends_their_sessions() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join(".dwm");
+        let main_repo = dir.path().join("api");
+        fs::create_dir_all(&main_repo).unwrap();
+        let main_repo = main_repo.to_string_lossy().into_owned();
+        let now = 1_000_000u64;
+        let stale = now - WAITING_STALE_TIMEOUT.as_secs() - 1;
+        write_repo_status(&base, "api-1", &main_repo, "live", "ws", "working", now - 5);
+        write_repo_status(&base, "api-1", &main_repo, "dead", "ws", "waiting", stale);
+
+        let report = gc_agent_status_at(&base, epoch(now), &StaleTimeouts::default());
+        assert_eq!(
+            report,
+            GcReport {
+                stale_files: 1,
+                untracked_repos: 0
+            }
+        );
+        let repo_dir = base.join("api-1");
+        let status_dir = agent_status_dir(&repo_dir);
+        assert!(status_dir.join("live.json").exists());
+        assert!(!status_dir.join("dead.json").exists());
+        assert_eq!(
+            read_history(&repo_dir, "ws"),
+            vec![Transition {
+                from: Some(AgentStatus::Waiting),
+                ..transition("dead", None, stale)
+            }]
+        );
+
+        // Nothing left to do the second time.
+        assert_eq!(
+            gc_agent_status_at(&base, epoch(now), &StaleTimeouts::default()),
+            GcReport::default()
+        );
+    }
+
+    #[test]
+    fn gc_clears_repos_whose_main_repo_is_gone() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path().join(".dwm");
+        let gone = dir.path().join("deleted").to_string_lossy().into_owned();
+        let now = epoch_secs(SystemTime::now());
+        write_repo_status(&base, "deleted-1", &gone, "s1", "ws", "working", now);
+        let repo_dir = base.join("deleted-1");
+        append_history(&repo_dir, "ws", &transition("s1", None, now)).unwrap();
+
+        let report = gc_agent_status_at(&base, SystemTime::now(), &StaleTimeouts::default());
+        assert_eq!(report.untracked_repos, 1);
+        assert!(!agent_status_dir(&repo_dir).exists());
+        assert!(!repo_dir.join(".agent-history").exists());
+        // The repo dir itself and its metadata are left alone.
+        assert!(meta::is_repo_dir(&repo_dir));
+    }
+
+    #[test]
+    fn gc_report_describes_what_was_removed() {
+        assert_eq!(GcReport::default().to_string(), "nothing to clean up");
+        let report = GcReport {
+            stale_files: 2,
+            untracked_repos: 1,
+        };
+        assert_eq!(
+            report.to_string(),
+            "removed 2 stale agent status files, cleared agent status for 1 untracked repo"
+        );
+    }
+
+    #[test]
+    fn agent_status_dirs_cover_dwm_repos() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        write_repo_status(base, "web-2", "/src/web", "w1", "nav", "idle", 0);
+        fs::create_dir_all(base.join("api-1")).unwrap();
+        fs::write(base.join("api-1").join(".main-repo"), "/src/api").unwrap();
+        fs::create_dir_all(base.join("stray")).unwrap();
+        assert_eq!(
+            agent_status_dirs_at(base),
+            vec![
+                base.join("api-1").join(".agent-status"),
+                base.join("web-2").join(".agent-status"),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_all_agent_sessions_spans_repos_most_urgent_first() {
+        let dir = TempDir::new().unwrap();
+        let base = dir.path();
+        let now = 1_000_000u64;
+        write_repo_status(base, "api-1", "/src/api", "a1", "login", "idle", now - 5);
+        write_repo_status(base, "web-2", "/src/web", "w1", "nav", "waiting", now - 50);
+        write_repo_status(base, "web-2", "/src/web", "w2", "nav", "working", now - 1);
+        let stale = now - WAITING_STALE_TIMEOUT.as_secs() - 1;
+        write_repo_status(base, "web-2", "/src/web", "w3", "old", "waiting", stale);
+        // Directories without a main repo aren't dwm repos.
+        fs::create_dir_all(base.join("stray").join(".agent-status")).unwrap();
+
+        let listings = list_all_agent_sessions_at(base, epoch(now), &StaleTimeouts::default());
+        let summary: Vec<(&str, &str, &str)> = listings
+            .iter()
+            .map(|l| {
+                (
+                    l.repo.as_str(),
+                    l.workspace.as_str(),
+                    l.session.session_id.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("web", "nav", "w1"),
+                ("web", "nav", "w2"),
+                ("api", "login", "a1")
+            ]
+        );
+    }
+
+    #[test]
+    fn write_keeps_session_start_time() {
+        let dir = TempDir::new().unwrap();
+        let agent_dir = dir.path().join(".agent-status");
+        fs::create_dir_all(&agent_dir).unwrap();
+        fs::write(
+            agent_dir.join("sess.json"),
+            r#"{"workspace":"ws","status":"idle","started_at":500,"updated_at":600}"#,
+        )
+        .unwrap();
+        write_agent_status(
+            dir.path(),
+            "sess",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        let sessions = read_agent_sessions(dir.path(), "ws");
+        assert_eq!(sessions[0].started_at, Some(epoch(500)));
+
+        write_agent_status(
+            dir.path(),
+            "new",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        let content = fs::read_to_string(agent_dir.join("new.json")).unwrap();
+        let file: AgentStatusFile = serde_json::from_str(&content).unwrap();
+        assert_eq!(file.started_at, Some(file.updated_at));
+    }
+
+    // --- Status history ---
+
+    fn transition(session: &str, status: Option<AgentStatus>, at: u64) -> Transition {
+        Transition {
+            session_id: session.to_string(),
+            agent: AgentKind::Claude,
+            status,
+            from: None,
+            at,
+        }
+    }
+
+    fn write(dir: &Path, session: &str, workspace: &str, status: AgentStatus) {
+        write_agent_status(
+            dir,
+            session,
+            AgentKind::Claude,
+            workspace,
+            status,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn history_records_only_status_changes() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "s1", "ws", AgentStatus::Working);
+        write(dir.path(), "s1", "ws", AgentStatus::Working);
+        write(dir.path(), "s1", "ws", AgentStatus::Waiting);
+        write(dir.path(), "s2", "other", AgentStatus::Idle);
+        remove_agent_status(dir.path(), "s1").unwrap();
+
+        let statuses: Vec<Option<AgentStatus>> = read_history(dir.path(), "ws")
+            .iter()
+            .map(|t| t.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![Some(AgentStatus::Working), Some(AgentStatus::Waiting), None]
+        );
+        assert_eq!(read_history(dir.path(), "other").len(), 1);
+    }
+
+    #[test]
+    fn history_ends_stale_session_where_last_heard() {
+        let dir = TempDir::new().unwrap();
+        let now = epoch_secs(SystemTime::now());
+        let last_heard = now - STALE_TIMEOUT.as_secs() - 60;
+        write_status_file(dir.path(), "s1", "ws", "working", last_heard);
+        write(dir.path(), "s1", "ws", AgentStatus::Working);
+
+        let history = read_history(dir.path(), "ws");
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0],
+            Transition {
+                from: Some(AgentStatus::Working),
+                ..transition("s1", None, last_heard)
+            }
+        );
+        assert_eq!(history[1].status, Some(AgentStatus::Working));
+        // The session starts over, so the resumed status has nothing before it.
+        assert_eq!(history[1].from, None);
+    }
+
+    #[test]
+    fn transitions_record_the_status_before() {
+        let dir = TempDir::new().unwrap();
+        let first = write_agent_status(
+            dir.path(),
+            "s1",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(first.from, None);
+
+        let repeat = write_agent_status(
+            dir.path(),
+            "s1",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Working,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap();
+        assert_eq!(repeat, None);
+
+        let moved = write_agent_status(
+            dir.path(),
+            "s1",
+            AgentKind::Claude,
+            "ws",
+            AgentStatus::Waiting,
+            AgentActivity::Unchanged,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(moved.from, Some(AgentStatus::Working));
+
+        let ended = remove_agent_status(dir.path(), "s1").unwrap().unwrap();
+        assert_eq!(ended.status, None);
+        assert_eq!(ended.from, Some(AgentStatus::Waiting));
+    }
+
+    #[test]
+    fn history_is_ring_buffered() {
+        let dir = TempDir::new().unwrap();
+        for i in 0..HISTORY_LIMIT as u64 + 3 {
+            append_history(dir.path(), "ws", &transition("s", None, i)).unwrap();
+        }
+        let history = read_history(dir.path(), "ws");
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history[0].at, 3);
+        assert_eq!(history.last().unwrap().at, HISTORY_LIMIT as u64 + 2);
+    }
+
+    #[test]
+    fn deleting_workspace_statuses_removes_history() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "s1", "ws", AgentStatus::Working);
+        assert!(history_path(dir.path(), "ws").exists());
+        remove_agent_statuses_for_workspace(dir.path(), "ws");
+        assert!(!history_path(dir.path(), "ws").exists());
+    }
+
+    #[test]
+    fn session_histories_add_up_time_per_status() {
+        let history = vec![
+            transition("a", Some(AgentStatus::Working), 1000),
+            transition("b", Some(AgentStatus::Working), 1100),
+            transition("a", Some(AgentStatus::Waiting), 1600),
+            transition("b", None, 1400),
+            transition("a", Some(AgentStatus::Working), 1700),
+            transition("c", Some(AgentStatus::Idle), 1800),
+            transition("d", Some(AgentStatus::Idle), 1900),
+        ];
+        let now = 2500;
+        // `a` is live, `c` went stale after last being heard from at 1850,
+        // and `d` has no status file left.
+        let last_heard = HashMap::from([("a".to_string(), 2490), ("c".to_string(), 1850)]);
+        let sessions = session_histories(&history, &last_heard, now, &StaleTimeouts::default());
+
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+        assert_eq!(sessions[0].first_seen, 1000);
+        assert_eq!(
+            sessions[0].times,
+            StatusTimes {
+                working: Duration::from_secs(600 + 800),
+                waiting: Duration::from_secs(100),
+                idle: Duration::ZERO,
+            }
+        );
+        assert_eq!(sessions[1].times.working, Duration::from_secs(300));
+        assert_eq!(sessions[2].times.idle, Duration::from_secs(50));
+        assert_eq!(sessions[3].times, StatusTimes::default());
+    }
+
+    #[test]
+    fn status_times_display_skips_unheld_statuses() {
+        let times = StatusTimes {
+            working: Duration::from_secs(42 * 60 + 10),
+            waiting: Duration::from_secs(3 * 60),
+            idle: Duration::ZERO,
+        };
+        assert_eq!(times.to_string(), "working 42m, waiting 3m");
+        assert_eq!(StatusTimes::default().to_string(), "");
+    }
+
+    #[test]
+    fn format_duration_units() {
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(42 * 60)), "42m");
+        assert_eq!(format_duration(Duration::from_secs(3600)), "1h");
+        assert_eq!(
+            format_duration(Duration::from_secs(3 * 3600 + 300)),
+            "3h 5m"
+        );
+        assert_eq!(format_duration(Duration::from_secs(2 * 86400)), "2d");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86400 + 4 * 3600 + 60)),
+            "2d 4h"
+        );
+    }
+}
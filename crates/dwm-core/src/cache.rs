@@ -0,0 +1,125 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::vcs::DiffStat;
+
+/// Cache file inside `~/.dwm/<repo>/`.
+pub const CACHE_FILE: &str = ".details-cache.json";
+
+/// Details of one workspace as last computed, along with the commits they
+/// were computed from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedDetails {
+    /// [`crate::vcs::WorkspaceInfo::commit_id`] of the workspace.
+    pub commit_id: String,
+    /// [`crate::vcs::VcsBackend::trunk_id`] of the repo.
+    pub trunk_id: String,
+    pub diff_stat: DiffStat,
+    pub merged: bool,
+    pub description: String,
+}
+
+/// Cached details for a repo's workspaces, keyed by workspace name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DetailsCache {
+    workspaces: HashMap<String, CachedDetails>,
+}
+
+impl DetailsCache {
+    /// Read the cache from `repo_dir`, or start empty if it's missing or
+    /// unreadable.
+    pub fn load(repo_dir: &Path) -> Self {
+        fs::read_to_string(repo_dir.join(CACHE_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Cached details for `name`, if they were computed at `commit_id`
+    /// against `trunk_id`. Either side moving invalidates them.
+    pub fn get(&self, name: &str, commit_id: &str, trunk_id: &str) -> Option<&CachedDetails> {
+        if commit_id.is_empty() || trunk_id.is_empty() {
+            return None;
+        }
+        self.workspaces
+            .get(name)
+            .filter(|c| c.commit_id == commit_id && c.trunk_id == trunk_id)
+    }
+
+    /// Record details for `name`. Ignored without both commit ids, since
+    /// there'd be nothing to tell when they went out of date.
+    pub fn insert(&mut self, name: &str, details: CachedDetails) {
+        if details.commit_id.is_empty() || details.trunk_id.is_empty() {
+            return;
+        }
+        self.workspaces.insert(name.to_string(), details);
+    }
+
+    /// Write the cache to `repo_dir`, replacing what was there.
+    pub fn save(&self, repo_dir: &Path) -> Result<()> {
+        let json = serde_json::to_string(self)?;
+        // Atomic write: write to temp file, then rename
+        let path = repo_dir.join(CACHE_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(commit_id: &str, trunk_id: &str) -> CachedDetails {
+        CachedDetails {
+            commit_id: commit_id.to_string(),
+            trunk_id: trunk_id.to_string(),
+            diff_stat: DiffStat {
+                files_changed: 2,
+                insertions: 5,
+                deletions: 1,
+                ..Default::default()
+            },
+            merged: false,
+            description: "feature".to_string(),
+        }
+    }
+
+    #[test]
+    fn get_requires_both_ids_to_match() {
+        let mut cache = DetailsCache::default();
+        cache.insert("feat", details("c1", "t1"));
+        assert_eq!(cache.get("feat", "c1", "t1"), Some(&details("c1", "t1")));
+        assert!(cache.get("feat", "c2", "t1").is_none());
+        assert!(cache.get("feat", "c1", "t2").is_none());
+        assert!(cache.get("other", "c1", "t1").is_none());
+    }
+
+    #[test]
+    fn insert_ignores_missing_ids() {
+        let mut cache = DetailsCache::default();
+        cache.insert("feat", details("", "t1"));
+        cache.insert("hg", details("c1", ""));
+        assert!(cache.workspaces.is_empty());
+        assert!(cache.get("feat", "", "t1").is_none());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(DetailsCache::load(tmp.path()).workspaces.is_empty());
+
+        let mut cache = DetailsCache::default();
+        cache.insert("feat", details("c1", "t1"));
+        cache.save(tmp.path()).unwrap();
+        let loaded = DetailsCache::load(tmp.path());
+        assert_eq!(loaded.get("feat", "c1", "t1"), Some(&details("c1", "t1")));
+
+        fs::write(tmp.path().join(CACHE_FILE), "not json").unwrap();
+        assert!(DetailsCache::load(tmp.path()).workspaces.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+/// Why a command failed, for the exit codes scripts can rely on. Exit code 1
+/// is any other error, and 2 is a usage error from argument parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    /// No workspace, repo, or help topic by that name.
+    NotFound,
+    /// git, jj, hg, or an external backend failed or couldn't be run.
+    Vcs,
+    /// The user declined a confirmation.
+    Cancelled,
+    /// A workspace has uncommitted changes and `--force` wasn't given.
+    Dirty,
+}
+
+impl FailureKind {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::NotFound => 3,
+            Self::Vcs => 4,
+            Self::Cancelled => 5,
+            Self::Dirty => 6,
+        }
+    }
+}
+
+/// An error message tagged with its [`FailureKind`]. Raise one with
+/// `bail!(Failure::not_found(...))`; context added on the way up keeps the
+/// tag, so [`exit_code`] still finds it.
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: FailureKind,
+    message: String,
+}
+
+impl Failure {
+    pub fn new(kind: FailureKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(FailureKind::NotFound, message)
+    }
+
+    pub fn vcs(message: impl Into<String>) -> Self {
+        Self::new(FailureKind::Vcs, message)
+    }
+
+    pub fn cancelled() -> Self {
+        Self::new(FailureKind::Cancelled, "cancelled")
+    }
+
+    pub fn dirty(message: impl Into<String>) -> Self {
+        Self::new(FailureKind::Dirty, message)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// The process exit code for `err`: its [`FailureKind`]'s code if it was
+/// tagged with one, else 1.
+pub fn exit_code(err: &anyhow::Error) -> u8 {
+    err.downcast_ref::<Failure>()
+        .map_or(1, |failure| failure.kind.exit_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{Context, Result, bail};
+
+    #[test]
+    fn exit_code_survives_added_context() {
+        fn missing() -> Result<()> {
+            bail!(Failure::not_found("workspace 'x' not found"))
+        }
+        let err = missing().context("could not switch").unwrap_err();
+        assert_eq!(exit_code(&err), 3);
+        assert_eq!(
+            format!("{:#}", err),
+            "could not switch: workspace 'x' not found"
+        );
+
+        let err: Result<()> =
+            Err(std::io::Error::other("boom")).context(Failure::vcs("git failed"));
+        let err = err.context("outer").unwrap_err();
+        assert_eq!(exit_code(&err), 4);
+
+        assert_eq!(exit_code(&anyhow::anyhow!("plain")), 1);
+    }
+}
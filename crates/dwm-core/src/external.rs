@@ -0,0 +1,400 @@
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::exit::Failure;
+use crate::vcs::{DiffStat, VcsBackend, WorkingCopyStatus, WorkspaceInfo};
+
+/// An external VCS backend from the `external_vcs` config: an executable
+/// that dwm runs once per [`VcsBackend`] call.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExternalVcs {
+    /// Command run through `sh -c` with the method name as its argument.
+    /// It reads the call's parameters as a JSON object on stdin and prints
+    /// the JSON result on stdout, exiting nonzero on failure.
+    pub command: String,
+    /// File or directory marking a repo root (e.g. `".fslckout"`), used to
+    /// detect repos for the backend.
+    #[serde(default)]
+    pub marker: Option<String>,
+}
+
+/// The `external_vcs` setting of the user config at `path`, if it's there
+/// and valid. The rest of the config is left to its readers.
+fn read_setting(path: &Path) -> Option<ExternalVcs> {
+    #[derive(Deserialize)]
+    struct Setting {
+        external_vcs: Option<ExternalVcs>,
+    }
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<Setting>(&content).ok()?.external_vcs
+}
+
+/// One workspace in an external backend's `workspace_list` result.
+#[derive(Debug, Deserialize)]
+struct ExternalWorkspace {
+    name: String,
+    #[serde(default)]
+    change_id: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    bookmarks: Vec<String>,
+    #[serde(default)]
+    commit_id: String,
+}
+
+/// [`VcsBackend`] implementation that delegates every call to a
+/// user-provided executable speaking JSON over stdio; see [`ExternalVcs`].
+pub struct ExternalBackend {
+    /// The configured backend, or `None` if `external_vcs` isn't set, in
+    /// which case every call fails.
+    pub vcs: Option<ExternalVcs>,
+}
+
+impl ExternalBackend {
+    /// The backend configured in the user config, if any.
+    pub fn from_config() -> Self {
+        let vcs = crate::layout::config_path()
+            .ok()
+            .and_then(|path| read_setting(&path));
+        Self { vcs }
+    }
+
+    /// Run `method` with `params` on stdin, returning its output.
+    fn run(&self, method: &str, params: Value) -> Result<Vec<u8>> {
+        let vcs = self
+            .vcs
+            .as_ref()
+            .context("no external_vcs command configured")?;
+        log::debug!("{} {} {}", vcs.command, method, params);
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", vcs.command))
+            .arg("sh")
+            .arg(method)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| Failure::vcs(format!("failed to run {}", vcs.command)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A backend that ignores its input may exit before reading it.
+            let _ = stdin.write_all(params.to_string().as_bytes());
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!(Failure::vcs(format!(
+                "{} {} failed: {}",
+                vcs.command,
+                method,
+                stderr.trim()
+            )));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Run `method` with `params` on stdin and parse its JSON output.
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let stdout = self.run(method, params)?;
+        serde_json::from_slice(&stdout)
+            .with_context(|| format!("could not parse the output of {}", method))
+    }
+
+    /// Run a preview method, which prints a JSON string; empty on failure.
+    fn preview(&self, method: &str, params: Value) -> String {
+        self.call(method, params).unwrap_or_default()
+    }
+}
+
+impl VcsBackend for ExternalBackend {
+    fn root_from(&self, dir: &Path) -> Result<PathBuf> {
+        let root: PathBuf = self.call("root_from", json!({ "dir": dir }))?;
+        Ok(crate::vcs::canonical(&root))
+    }
+
+    fn workspace_list(&self, repo_dir: &Path) -> Result<Vec<(String, WorkspaceInfo)>> {
+        let workspaces: Vec<ExternalWorkspace> =
+            self.call("workspace_list", json!({ "repo_dir": repo_dir }))?;
+        Ok(workspaces
+            .into_iter()
+            .map(|ws| {
+                let info = WorkspaceInfo {
+                    change_id: ws.change_id,
+                    description: ws.description,
+                    bookmarks: ws.bookmarks,
+                    commit_id: ws.commit_id,
+                };
+                (ws.name, info)
+            })
+            .collect())
+    }
+
+    fn workspace_add(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        at: Option<&str>,
+    ) -> Result<()> {
+        let params = json!({ "repo_dir": repo_dir, "ws_path": ws_path, "name": name, "at": at });
+        self.run("workspace_add", params).map(|_| ())
+    }
+
+    fn workspace_add_sparse(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        at: Option<&str>,
+        sparse: &[String],
+    ) -> Result<()> {
+        let params = json!({
+            "repo_dir": repo_dir,
+            "ws_path": ws_path,
+            "name": name,
+            "at": at,
+            "sparse": sparse,
+        });
+        self.run("workspace_add_sparse", params).map(|_| ())
+    }
+
+    fn workspace_remove(&self, repo_dir: &Path, name: &str, ws_path: &Path) -> Result<()> {
+        let params = json!({ "repo_dir": repo_dir, "name": name, "ws_path": ws_path });
+        self.run("workspace_remove", params).map(|_| ())
+    }
+
+    fn workspace_rename(
+        &self,
+        repo_dir: &Path,
+        old_path: &Path,
+        new_path: &Path,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        let params = json!({
+            "repo_dir": repo_dir,
+            "old_path": old_path,
+            "new_path": new_path,
+            "old_name": old_name,
+            "new_name": new_name,
+        });
+        self.run("workspace_rename", params).map(|_| ())
+    }
+
+    fn diff_stat_vs_trunk(
+        &self,
+        repo_dir: &Path,
+        worktree_dir: &Path,
+        ws_name: &str,
+    ) -> Result<DiffStat> {
+        self.call(
+            "diff_stat_vs_trunk",
+            workspace_params(repo_dir, worktree_dir, ws_name),
+        )
+    }
+
+    fn latest_description(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> String {
+        self.call(
+            "latest_description",
+            workspace_params(repo_dir, worktree_dir, ws_name),
+        )
+        .unwrap_or_default()
+    }
+
+    fn is_merged_into_trunk(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> bool {
+        self.call(
+            "is_merged_into_trunk",
+            workspace_params(repo_dir, worktree_dir, ws_name),
+        )
+        .unwrap_or(false)
+    }
+
+    fn working_copy_status(
+        &self,
+        repo_dir: &Path,
+        worktree_dir: &Path,
+        ws_name: &str,
+    ) -> Result<WorkingCopyStatus> {
+        Ok(self
+            .call(
+                "working_copy_status",
+                workspace_params(repo_dir, worktree_dir, ws_name),
+            )
+            .unwrap_or_default())
+    }
+
+    fn trunk_id(&self, repo_dir: &Path) -> Option<String> {
+        self.call("trunk_id", json!({ "repo_dir": repo_dir })).ok()
+    }
+
+    fn fetch(&self, repo_dir: &Path) -> Result<()> {
+        self.run("fetch", json!({ "repo_dir": repo_dir }))
+            .map(|_| ())
+    }
+
+    fn push(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> Result<String> {
+        self.call("push", workspace_params(repo_dir, worktree_dir, ws_name))
+    }
+
+    fn sync(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> Result<String> {
+        self.call("sync", workspace_params(repo_dir, worktree_dir, ws_name))
+    }
+
+    fn vcs_type(&self) -> crate::vcs::VcsType {
+        crate::vcs::VcsType::External
+    }
+
+    fn main_workspace_name(&self) -> &'static str {
+        "default"
+    }
+
+    fn preview_log(
+        &self,
+        repo_dir: &Path,
+        worktree_dir: &Path,
+        ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let mut params = workspace_params(repo_dir, worktree_dir, ws_name);
+        params["limit"] = json!(limit);
+        self.preview("preview_log", params)
+    }
+
+    fn preview_graph(
+        &self,
+        repo_dir: &Path,
+        worktree_dir: &Path,
+        ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let mut params = workspace_params(repo_dir, worktree_dir, ws_name);
+        params["limit"] = json!(limit);
+        self.preview("preview_graph", params)
+    }
+
+    fn preview_diff_stat(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> String {
+        self.preview(
+            "preview_diff_stat",
+            workspace_params(repo_dir, worktree_dir, ws_name),
+        )
+    }
+}
+
+/// Parameters shared by the calls about one workspace.
+fn workspace_params(repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> Value {
+    json!({ "repo_dir": repo_dir, "worktree_dir": worktree_dir, "ws_name": ws_name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A backend running a shell script that answers each method from a
+    /// `case` arm and logs its stdin to `input.json`.
+    fn script_backend(dir: &Path, arms: &str) -> ExternalBackend {
+        let script = dir.join("backend.sh");
+        fs::write(
+            &script,
+            format!(
+                "cat > '{}'\ncase \"$1\" in\n{}\n*) exit 1 ;;\nesac\n",
+                dir.join("input.json").display(),
+                arms
+            ),
+        )
+        .unwrap();
+        ExternalBackend {
+            vcs: Some(ExternalVcs {
+                command: format!("sh '{}'", script.display()),
+                marker: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn calls_methods_with_json_params() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = script_backend(
+            dir.path(),
+            r#"workspace_list) echo '[{"name": "default", "change_id": "abc", "bookmarks": ["main"]}, {"name": "feat"}]' ;;
+diff_stat_vs_trunk) echo '{"files_changed": 2, "insertions": 5}' ;;
+is_merged_into_trunk) echo true ;;
+working_copy_status) echo '{"untracked": true}' ;;
+push) echo '"origin/feat"' ;;
+preview_log) echo '"abc first"' ;;"#,
+        );
+
+        let list = backend.workspace_list(Path::new("/repo")).unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].0, "default");
+        assert_eq!(list[0].1.change_id, "abc");
+        assert_eq!(list[0].1.bookmarks, vec!["main"]);
+        assert_eq!(list[1].0, "feat");
+        let input: Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("input.json")).unwrap())
+                .unwrap();
+        assert_eq!(input, json!({ "repo_dir": "/repo" }));
+
+        let ws = Path::new("/ws");
+        let stat = backend
+            .diff_stat_vs_trunk(Path::new("/repo"), ws, "feat")
+            .unwrap();
+        assert_eq!(
+            (stat.files_changed, stat.insertions, stat.deletions),
+            (2, 5, 0)
+        );
+        assert!(backend.is_merged_into_trunk(Path::new("/repo"), ws, "feat"));
+        let status = backend
+            .working_copy_status(Path::new("/repo"), ws, "feat")
+            .unwrap();
+        assert_eq!(status.describe(), Some("untracked files"));
+        assert_eq!(
+            backend.push(Path::new("/repo"), ws, "feat").unwrap(),
+            "origin/feat"
+        );
+        assert_eq!(
+            backend.preview_log(Path::new("/repo"), ws, "feat", 5),
+            "abc first"
+        );
+        let input: Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("input.json")).unwrap())
+                .unwrap();
+        assert_eq!(input["limit"], 5);
+    }
+
+    #[test]
+    fn failures_surface_stderr_or_fall_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = script_backend(
+            dir.path(),
+            "workspace_add) echo 'no such revision' >&2; exit 1 ;;",
+        );
+        let err = backend
+            .workspace_add(Path::new("/repo"), Path::new("/ws"), "feat", Some("nope"))
+            .unwrap_err();
+        assert!(err.to_string().contains("no such revision"), "{}", err);
+        // Unimplemented optional methods fall back quietly.
+        assert!(!backend.is_merged_into_trunk(Path::new("/repo"), Path::new("/ws"), "feat"));
+        let status = backend
+            .working_copy_status(Path::new("/repo"), Path::new("/ws"), "feat")
+            .unwrap();
+        assert!(!status.is_dirty());
+        assert_eq!(
+            backend.preview_graph(Path::new("/repo"), Path::new("/ws"), "feat", 5),
+            ""
+        );
+    }
+
+    #[test]
+    fn unconfigured_backend_fails() {
+        let backend = ExternalBackend { vcs: None };
+        let err = backend.root_from(Path::new("/repo")).unwrap_err();
+        assert!(err.to_string().contains("external_vcs"), "{}", err);
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::workspace::WorkspaceEntry;
+
+/// Where a pull request's review stands, from GitHub's review decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    Approved,
+    ChangesRequested,
+    ReviewRequired,
+}
+
+/// The combined state of a pull request's CI checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Checks {
+    Passing,
+    Failing,
+    Pending,
+}
+
+/// An open pull request for a workspace's branch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: u64,
+    #[serde(default)]
+    pub url: String,
+    pub draft: bool,
+    /// `None` when the repo doesn't require reviews and none were left.
+    pub review: Option<ReviewDecision>,
+    /// `None` when the branch has no checks.
+    pub checks: Option<Checks>,
+}
+
+impl PullRequest {
+    /// Whether something on the pull request needs fixing: failing checks
+    /// or requested changes.
+    pub fn is_blocked(&self) -> bool {
+        self.checks == Some(Checks::Failing)
+            || self.review == Some(ReviewDecision::ChangesRequested)
+    }
+
+    /// Whether it is approved with nothing failing or still running.
+    pub fn is_ready(&self) -> bool {
+        self.review == Some(ReviewDecision::Approved)
+            && matches!(self.checks, None | Some(Checks::Passing))
+    }
+}
+
+/// Like `#42 ✓ approved`, or `#42 draft ⋯` while checks run on a draft.
+impl fmt::Display for PullRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.number)?;
+        if self.draft {
+            write!(f, " draft")?;
+        }
+        match self.checks {
+            Some(Checks::Passing) => write!(f, " ✓")?,
+            Some(Checks::Failing) => write!(f, " ✗")?,
+            Some(Checks::Pending) => write!(f, " ⋯")?,
+            None => {}
+        }
+        match self.review {
+            Some(ReviewDecision::Approved) => write!(f, " approved"),
+            Some(ReviewDecision::ChangesRequested) => write!(f, " changes requested"),
+            Some(ReviewDecision::ReviewRequired) => write!(f, " review required"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An issue, as linked to the workspace made for it with `dwm new --issue`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+}
+
+/// The CI state of a commit, combined across its checks or pipelines.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CiRun {
+    pub checks: Checks,
+    /// The web page of a failing run if any failed, else of one still
+    /// running, else of any run. Empty if the forge didn't give one.
+    #[serde(default)]
+    pub url: String,
+}
+
+impl CiRun {
+    /// Combine `runs`, each with its state and web page, into one: failing
+    /// if any failed, else running if any are, else passing. Runs with no
+    /// state, like skipped ones, don't count. `None` if none count.
+    pub fn combine(runs: impl IntoIterator<Item = (Option<Checks>, String)>) -> Option<Self> {
+        let runs: Vec<(Checks, String)> = runs
+            .into_iter()
+            .filter_map(|(checks, url)| Some((checks?, url)))
+            .collect();
+        [Checks::Failing, Checks::Pending, Checks::Passing]
+            .into_iter()
+            .find_map(|state| runs.iter().find(|(checks, _)| *checks == state))
+            .map(|(checks, url)| Self {
+                checks: *checks,
+                url: url.clone(),
+            })
+    }
+}
+
+/// Like `✓ passed`, `✗ failed`, or `⋯ running`.
+impl fmt::Display for CiRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.checks {
+            Checks::Passing => "✓ passed",
+            Checks::Failing => "✗ failed",
+            Checks::Pending => "⋯ running",
+        })
+    }
+}
+
+/// Cache file inside `~/.dwm/<repo>/`.
+pub const FORGE_CACHE_FILE: &str = ".forge.json";
+
+/// How long fetched forge state is shown before it's fetched again.
+pub const FORGE_TTL: Duration = Duration::from_secs(2 * 60);
+
+/// A repo's open pull requests and its workspaces' CI state, as last
+/// fetched from its forge.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ForgeCache {
+    /// When the last fetch was attempted, in seconds since the epoch.
+    pub fetched_at: u64,
+    /// Open pull requests keyed by head branch.
+    #[serde(default)]
+    pub branches: HashMap<String, PullRequest>,
+    /// CI state keyed by full commit hash, for the workspaces' heads.
+    #[serde(default)]
+    pub commits: HashMap<String, CiRun>,
+}
+
+impl ForgeCache {
+    /// Read the cache from `repo_dir`, or start empty if it's missing or
+    /// unreadable.
+    pub fn load(repo_dir: &Path) -> Self {
+        fs::read_to_string(repo_dir.join(FORGE_CACHE_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the cache to `repo_dir`, replacing what was there.
+    pub fn save(&self, repo_dir: &Path) -> Result<()> {
+        let json = serde_json::to_string(self)?;
+        // Atomic write: write to temp file, then rename
+        let path = repo_dir.join(FORGE_CACHE_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Whether a fetch was attempted less than [`FORGE_TTL`] before `now`.
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        let fetched_at = SystemTime::UNIX_EPOCH + Duration::from_secs(self.fetched_at);
+        now.duration_since(fetched_at)
+            .is_ok_and(|age| age < FORGE_TTL)
+    }
+
+    /// The open pull request for the first of `branches` that has one.
+    pub fn find(&self, branches: &[String]) -> Option<&PullRequest> {
+        branches.iter().find_map(|b| self.branches.get(b))
+    }
+
+    /// The CI state of the commit whose hash starts with `change_id`, as
+    /// abbreviated in a workspace listing.
+    pub fn ci_for(&self, change_id: &str) -> Option<&CiRun> {
+        if change_id.is_empty() {
+            return None;
+        }
+        self.commits
+            .iter()
+            .find_map(|(sha, ci)| sha.starts_with(change_id).then_some(ci))
+    }
+
+    /// Fill in each entry's pull request and CI state from the cache.
+    pub fn apply(&self, entries: &mut [WorkspaceEntry]) {
+        for entry in entries {
+            entry.pr = self.find(&entry.bookmarks).cloned();
+            entry.ci = self.ci_for(&entry.change_id).cloned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pr(number: u64) -> PullRequest {
+        PullRequest {
+            number,
+            url: format!("https://github.com/me/api/pull/{}", number),
+            draft: false,
+            review: None,
+            checks: None,
+        }
+    }
+
+    #[test]
+    fn display_shows_number_checks_and_review() {
+        let mut approved = pr(12);
+        approved.review = Some(ReviewDecision::Approved);
+        approved.checks = Some(Checks::Passing);
+        assert_eq!(approved.to_string(), "#12 ✓ approved");
+        assert!(approved.is_ready());
+
+        let mut draft = pr(15);
+        draft.draft = true;
+        draft.checks = Some(Checks::Pending);
+        assert_eq!(draft.to_string(), "#15 draft ⋯");
+
+        let mut blocked = pr(17);
+        blocked.review = Some(ReviewDecision::ChangesRequested);
+        blocked.checks = Some(Checks::Failing);
+        assert_eq!(blocked.to_string(), "#17 ✗ changes requested");
+        assert!(blocked.is_blocked());
+
+        let mut waiting = pr(20);
+        waiting.review = Some(ReviewDecision::ReviewRequired);
+        assert_eq!(waiting.to_string(), "#20 review required");
+        assert!(!waiting.is_ready() && !waiting.is_blocked());
+    }
+
+    #[test]
+    fn find_matches_any_branch() {
+        let cache = ForgeCache {
+            fetched_at: 0,
+            branches: HashMap::from([("docs".to_string(), pr(20))]),
+            ..Default::default()
+        };
+        let branches = vec!["wip".to_string(), "docs".to_string()];
+        assert_eq!(cache.find(&branches).map(|pr| pr.number), Some(20));
+        assert!(cache.find(&["main".to_string()]).is_none());
+        assert!(cache.find(&[]).is_none());
+    }
+
+    #[test]
+    fn cache_is_fresh_for_the_ttl() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let mut cache = ForgeCache::default();
+        assert!(!cache.is_fresh(now));
+        cache.fetched_at = 1_000_000 - 30;
+        assert!(cache.is_fresh(now));
+        cache.fetched_at = 1_000_000 - FORGE_TTL.as_secs();
+        assert!(!cache.is_fresh(now));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(ForgeCache::load(tmp.path()).branches.is_empty());
+
+        let cache = ForgeCache {
+            fetched_at: 42,
+            branches: HashMap::from([("docs".to_string(), pr(20))]),
+            ..Default::default()
+        };
+        cache.save(tmp.path()).unwrap();
+        let loaded = ForgeCache::load(tmp.path());
+        assert_eq!(loaded.fetched_at, 42);
+        assert_eq!(loaded.branches, cache.branches);
+
+        fs::write(tmp.path().join(FORGE_CACHE_FILE), "not json").unwrap();
+        assert!(ForgeCache::load(tmp.path()).branches.is_empty());
+    }
+
+    #[test]
+    fn ci_runs_combine_worst_first() {
+        let run = |checks, url: &str| (checks, url.to_string());
+        let ci = CiRun::combine([
+            run(Some(Checks::Passing), "a"),
+            run(Some(Checks::Pending), "b"),
+            run(Some(Checks::Failing), "c"),
+            run(Some(Checks::Failing), "d"),
+        ])
+        .unwrap();
+        assert_eq!((ci.checks, ci.url.as_str()), (Checks::Failing, "c"));
+        assert_eq!(ci.to_string(), "✗ failed");
+        let ci = CiRun::combine([run(None, "a"), run(Some(Checks::Pending), "b")]).unwrap();
+        assert_eq!((ci.checks, ci.url.as_str()), (Checks::Pending, "b"));
+        assert_eq!(ci.to_string(), "⋯ running");
+        assert_eq!(CiRun::combine([run(None, "a")]), None);
+        assert_eq!(CiRun::combine([]), None);
+    }
+
+    #[test]
+    fn ci_is_found_by_abbreviated_hash() {
+        let ci = CiRun {
+            checks: Checks::Passing,
+            url: String::new(),
+        };
+        let cache = ForgeCache {
+            commits: HashMap::from([("0123456789abcdef".to_string(), ci.clone())]),
+            ..Default::default()
+        };
+        assert_eq!(cache.ci_for("01234567"), Some(&ci));
+        assert_eq!(cache.ci_for("76543210"), None);
+        assert_eq!(cache.ci_for(""), None);
+    }
+}
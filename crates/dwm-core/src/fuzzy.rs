@@ -0,0 +1,175 @@
+/// Points for each matched character.
+const SCORE_MATCH: i64 = 16;
+/// Bonus when a match directly follows the previous one.
+const BONUS_CONSECUTIVE: i64 = 12;
+/// Bonus when a match starts a word (after `-`, `_`, `/`, `.`, space, or a
+/// lowercase-to-uppercase transition) or the candidate.
+const BONUS_BOUNDARY: i64 = 10;
+/// Penalty for skipping characters between two matches...
+const PENALTY_GAP_START: i64 = 3;
+/// ...plus this for each skipped character after the first.
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+/// Score `candidate` against `query` fzf-style, case-insensitively: the
+/// query's characters must appear in order, and tighter matches and matches
+/// at word starts score higher. Returns `None` when the query is not a
+/// subsequence of the candidate. An empty query matches with a score of 0.
+pub fn score(candidate: &str, query: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let original: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    if query.len() > lower.len() {
+        return None;
+    }
+
+    let bonus = |j: usize| -> i64 {
+        let boundary = match j.checked_sub(1).map(|p| original[p]) {
+            None => true,
+            Some(prev) => {
+                matches!(prev, '-' | '_' | '/' | '.' | ' ')
+                    || (prev.is_lowercase() && original[j].is_uppercase())
+            }
+        };
+        if boundary { BONUS_BOUNDARY } else { 0 }
+    };
+
+    // best[j]: best score for the query prefix matched so far, ending with a
+    // match at candidate position j.
+    let mut best: Vec<Option<i64>> = lower
+        .iter()
+        .enumerate()
+        .map(|(j, &c)| (c == query[0]).then(|| SCORE_MATCH + bonus(j)))
+        .collect();
+
+    for &qc in &query[1..] {
+        let mut next = vec![None; lower.len()];
+        for j in 0..lower.len() {
+            if lower[j] != qc {
+                continue;
+            }
+            let prev_best = (0..j)
+                .filter_map(|k| {
+                    best[k].map(|s| {
+                        if k + 1 == j {
+                            s + BONUS_CONSECUTIVE
+                        } else {
+                            let gap = (j - k - 1) as i64;
+                            s - PENALTY_GAP_START - PENALTY_GAP_EXTENSION * (gap - 1)
+                        }
+                    })
+                })
+                .max();
+            next[j] = prev_best.map(|s| s + SCORE_MATCH + bonus(j));
+        }
+        best = next;
+    }
+
+    best.into_iter().flatten().max()
+}
+
+/// Levenshtein distance between `a` and `b`: the fewest single-character
+/// insertions, deletions, and substitutions turning one into the other.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// The `candidates` close enough to `query` to be what was meant, closest
+/// first: within one edit per four characters (at least one edit).
+pub fn did_you_mean<'a>(query: &str, candidates: &'a [String]) -> Vec<&'a str> {
+    let limit = (query.chars().count() / 4).max(1);
+    let mut close: Vec<(usize, &str)> = candidates
+        .iter()
+        .map(|c| (edit_distance(query, c), c.as_str()))
+        .filter(|&(distance, _)| distance <= limit)
+        .collect();
+    close.sort();
+    close.into_iter().map(|(_, c)| c).take(3).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_edits() {
+        assert_eq!(edit_distance("hazy-quail", "hazy-quail"), 0);
+        assert_eq!(edit_distance("hazy-qual", "hazy-quail"), 1);
+        assert_eq!(edit_distance("hayz-quail", "hazy-quail"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn did_you_mean_keeps_close_candidates() {
+        let names = ["hazy-quail", "hazy-owl", "calm-fox"].map(String::from);
+        assert_eq!(did_you_mean("hazy-qual", &names), ["hazy-quail"]);
+        assert_eq!(did_you_mean("hazy-ow", &names), ["hazy-owl"]);
+        assert!(did_you_mean("something", &names).is_empty());
+    }
+
+    #[test]
+    fn empty_query_matches() {
+        assert_eq!(score("anything", ""), Some(0));
+    }
+
+    #[test]
+    fn subsequence_matches() {
+        assert!(score("live-view-widget", "lvw").is_some());
+        assert!(score("live-view-widget", "wvl").is_none());
+    }
+
+    #[test]
+    fn case_insensitive() {
+        assert!(score("MyFeature", "myf").is_some());
+        assert!(score("myfeature", "MYF").is_some());
+    }
+
+    #[test]
+    fn query_longer_than_candidate_fails() {
+        assert!(score("ab", "abc").is_none());
+    }
+
+    #[test]
+    fn contiguous_beats_scattered() {
+        let tight = score("view-model", "view").unwrap();
+        let loose = score("v-i-e-w", "view").unwrap();
+        assert!(tight > loose, "{} <= {}", tight, loose);
+    }
+
+    #[test]
+    fn word_boundaries_score_higher() {
+        let boundary = score("live-view-widget", "lvw").unwrap();
+        let inner = score("xlxvxwx", "lvw").unwrap();
+        assert!(boundary > inner, "{} <= {}", boundary, inner);
+    }
+
+    #[test]
+    fn camel_case_counts_as_boundary() {
+        let camel = score("liveViewWidget", "lvw").unwrap();
+        let flat = score("livevwidget", "lvw").unwrap();
+        assert!(camel > flat, "{} <= {}", camel, flat);
+    }
+
+    #[test]
+    fn picks_best_alignment() {
+        // The greedy first 'a' is a worse start than the later word boundary.
+        let s = score("xa-abc", "abc").unwrap();
+        assert_eq!(s, score("abc", "abc").unwrap());
+    }
+}
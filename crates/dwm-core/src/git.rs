@@ -0,0 +1,1051 @@
+use anyhow::{Context, Result, anyhow, bail};
+use git2::{Commit, Oid, Repository};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::exit::Failure;
+use crate::progress;
+use crate::vcs::{
+    self, DiffStat, VcsBackend, VcsError, WorkingCopyStatus, WorkspaceDetails, WorkspaceInfo,
+    canonical,
+};
+
+/// Run `git` with the given arguments inside `dir`.
+fn run_git_in(dir: &Path, args: &[&str]) -> Result<String> {
+    log::debug!("git {} (in {})", args.join(" "), dir.display());
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .context(Failure::vcs("failed to run git - is it installed?"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(Failure::vcs(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Open the git repo `dir` is in, in-process. Listing and comparing
+/// workspaces go through this rather than the `git` binary, which is only
+/// run to change things.
+fn open(dir: &Path) -> Result<Repository> {
+    log::trace!("opening git repo at {}", dir.display());
+    Repository::discover(dir)
+        .with_context(|| Failure::vcs(format!("could not open the git repo at {}", dir.display())))
+}
+
+/// [`open`], but the main repo even when `dir` is one of its linked
+/// worktrees, so every worktree can be listed from it.
+fn open_main(dir: &Path) -> Result<Repository> {
+    let repo = open(dir)?;
+    if !repo.is_worktree() {
+        return Ok(repo);
+    }
+    open(repo.commondir())
+}
+
+/// What workspaces in the repo `dir` is in are compared with and rebased
+/// onto; see [`trunk_rev`].
+fn detect_trunk(dir: &Path) -> String {
+    open(dir).map_or_else(|_| "main".to_string(), |repo| trunk_rev(&repo))
+}
+
+/// `origin/<trunk>` when `repo` has it, so that what a fetch brings in,
+/// such as a branch merged upstream, shows in the listing; else the local
+/// trunk, as in a repo without a remote.
+fn trunk_rev(repo: &Repository) -> String {
+    let trunk = trunk_name(repo);
+    let remote_trunk = format!("origin/{}", trunk);
+    if resolve(repo, &remote_trunk).is_some() {
+        remote_trunk
+    } else {
+        trunk
+    }
+}
+
+/// The commit [`trunk_rev`] names, if there is one yet.
+fn trunk_commit(repo: &Repository) -> Option<Oid> {
+    resolve(repo, &trunk_rev(repo))
+}
+
+/// The trunk/main branch name of `repo`.
+/// Checks: the `dwm.trunk` config set by `dwm init --trunk`, main, master,
+/// then origin/HEAD symbolic ref.
+fn trunk_name(repo: &Repository) -> String {
+    if let Ok(trunk) = repo
+        .config()
+        .and_then(|config| config.get_string("dwm.trunk"))
+        && !trunk.trim().is_empty()
+    {
+        return trunk.trim().to_string();
+    }
+    for branch in ["main", "master"] {
+        if repo
+            .find_reference(&format!("refs/heads/{}", branch))
+            .is_ok()
+        {
+            return branch.to_string();
+        }
+    }
+    if let Ok(head) = repo.find_reference("refs/remotes/origin/HEAD")
+        && let Some(branch) = head
+            .symbolic_target()
+            .and_then(|target| target.strip_prefix("refs/remotes/origin/"))
+    {
+        return branch.to_string();
+    }
+    // Fallback
+    "main".to_string()
+}
+
+/// The commit `rev` names in `repo`, if any.
+fn resolve(repo: &Repository, rev: &str) -> Option<Oid> {
+    let commit = repo.revparse_single(rev).ok()?.peel_to_commit().ok()?;
+    Some(commit.id())
+}
+
+/// The commit checked out in `repo`, or `None` on an unborn branch.
+fn head_commit(repo: &Repository) -> Option<Commit<'_>> {
+    repo.head().ok()?.peel_to_commit().ok()
+}
+
+/// One checkout of a repo: its main working tree or a linked worktree.
+struct WorktreeEntry {
+    path: PathBuf,
+    /// The full hash of the commit checked out, empty on an unborn branch.
+    head: String,
+    /// Branch name (without `refs/heads/` prefix), or `None` for detached HEAD.
+    branch: Option<String>,
+    /// The subject of the commit checked out.
+    description: String,
+}
+
+impl WorktreeEntry {
+    /// The checkout at `path`, read from `repo` opened there.
+    fn read(path: &Path, repo: &Repository) -> Self {
+        let branch = repo.find_reference("HEAD").ok().and_then(|head| {
+            head.symbolic_target()
+                .and_then(|target| target.strip_prefix("refs/heads/"))
+                .map(String::from)
+        });
+        let commit = head_commit(repo);
+        Self {
+            // Trailing slashes would keep paths from comparing equal.
+            path: path.components().collect(),
+            head: commit
+                .as_ref()
+                .map(|c| c.id().to_string())
+                .unwrap_or_default(),
+            branch,
+            description: commit
+                .as_ref()
+                .and_then(|c| c.summary())
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+}
+
+/// The checkouts of `repo`, a main repo: its own working tree first,
+/// unless it's bare, then its linked worktrees by path, as `git worktree
+/// list` orders them. A worktree that can't be opened, such as one whose
+/// directory was deleted, is listed with no commit.
+fn worktrees(repo: &Repository) -> Result<Vec<WorktreeEntry>> {
+    let mut entries = Vec::new();
+    if let Some(workdir) = repo.workdir() {
+        entries.push(WorktreeEntry::read(workdir, repo));
+    }
+    let mut linked = Vec::new();
+    for name in repo.worktrees()?.iter().flatten() {
+        let Ok(worktree) = repo.find_worktree(name) else {
+            continue;
+        };
+        linked.push(match Repository::open_from_worktree(&worktree) {
+            Ok(wt_repo) => WorktreeEntry::read(worktree.path(), &wt_repo),
+            Err(_) => WorktreeEntry {
+                path: worktree.path().components().collect(),
+                head: String::new(),
+                branch: None,
+                description: String::new(),
+            },
+        });
+    }
+    linked.sort_by(|a, b| a.path.cmp(&b.path));
+    entries.extend(linked);
+    Ok(entries)
+}
+
+/// The full commit hashes checked out in `repo_dir`'s worktrees, leaving
+/// out any without a commit yet.
+pub fn worktree_heads(repo_dir: &Path) -> Result<Vec<String>> {
+    Ok(worktrees(&open_main(repo_dir)?)?
+        .into_iter()
+        .map(|wt| wt.head)
+        .filter(|head| !head.is_empty())
+        .collect())
+}
+
+/// The remote to push `branch` to: its configured remote, else `origin`,
+/// else the only remote.
+fn push_remote(dir: &Path, branch: &str) -> Result<String> {
+    let key = format!("branch.{}.remote", branch);
+    if let Ok(remote) = run_git_in(dir, &["config", "--get", &key]) {
+        return Ok(remote.trim().to_string());
+    }
+    let remotes = run_git_in(dir, &["remote"])?;
+    let remotes: Vec<&str> = remotes.lines().collect();
+    if remotes.contains(&"origin") {
+        return Ok("origin".to_string());
+    }
+    match remotes.as_slice() {
+        [only] => Ok(only.to_string()),
+        [] => bail!("no remote to push to"),
+        _ => bail!(
+            "several remotes and no origin; set branch.{}.remote",
+            branch
+        ),
+    }
+}
+
+/// What `head` changes from `trunk`, as `git diff --stat trunk..head`
+/// counts it, with the commits each has that the other doesn't.
+fn diff_stat_between(repo: &Repository, trunk: Oid, head: Oid) -> Result<DiffStat> {
+    let tree = |id| repo.find_commit(id).and_then(|commit| commit.tree());
+    let mut diff = repo.diff_tree_to_tree(Some(&tree(trunk)?), Some(&tree(head)?), None)?;
+    // Count renames as git's `diff --stat` does.
+    diff.find_similar(None)?;
+    let stats = diff.stats()?;
+    let (ahead, behind) = repo.graph_ahead_behind(head, trunk)?;
+    Ok(DiffStat {
+        files_changed: stats.files_changed() as u32,
+        insertions: stats.insertions() as u32,
+        deletions: stats.deletions() as u32,
+        ahead: ahead as u32,
+        behind: behind as u32,
+    })
+}
+
+/// Whether `head` is `trunk` or one of its ancestors (i.e., fully merged).
+fn is_merged(repo: &Repository, trunk: Oid, head: Oid) -> bool {
+    head == trunk || repo.graph_descendant_of(trunk, head).unwrap_or(false)
+}
+
+/// Whether `dir` is a bare repo, which has no working tree of its own.
+fn is_bare(dir: &Path) -> bool {
+    run_git_in(dir, &["rev-parse", "--is-bare-repository"]).is_ok_and(|out| out.trim() == "true")
+}
+
+/// The bare repo that `dir` belongs to, whether `dir` is inside it or in one
+/// of its worktrees, or `None` if the repo isn't bare.
+fn bare_root(dir: &Path) -> Option<PathBuf> {
+    let out = run_git_in(
+        dir,
+        &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+    )
+    .ok()?;
+    let common = PathBuf::from(out.trim());
+    is_bare(&common).then_some(common)
+}
+
+/// [`VcsBackend`] implementation for git worktrees. Listing and comparing
+/// workspaces happens in-process with `libgit2`; changes go through the
+/// `git` CLI.
+pub struct GitBackend;
+
+impl VcsBackend for GitBackend {
+    fn root_from(&self, dir: &Path) -> Result<PathBuf> {
+        // With a bare clone and only worktrees, the bare repo is the main
+        // one, even from inside a worktree.
+        if let Some(root) = bare_root(dir) {
+            return Ok(canonical(&root));
+        }
+        let out = run_git_in(dir, &["rev-parse", "--show-toplevel"])?;
+        Ok(canonical(Path::new(out.trim())))
+    }
+
+    fn workspace_list(&self, repo_dir: &Path) -> Result<Vec<(String, WorkspaceInfo)>> {
+        let repo = open_main(repo_dir)?;
+        Ok(worktrees(&repo)?
+            .into_iter()
+            .map(|wt| {
+                let name = wt
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let short_hash = wt.head.get(..8).unwrap_or(&wt.head).to_string();
+                (
+                    name,
+                    WorkspaceInfo {
+                        change_id: short_hash,
+                        description: wt.description,
+                        bookmarks: wt.branch.into_iter().collect(),
+                        commit_id: wt.head,
+                    },
+                )
+            })
+            .collect())
+    }
+
+    fn workspace_add(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        _at: Option<&str>,
+    ) -> Result<()> {
+        let path_str = ws_path.to_string_lossy();
+        progress::step("adding worktree", || {
+            run_git_in(repo_dir, &["worktree", "add", &path_str, "-b", name])
+        })?;
+        Ok(())
+    }
+
+    fn workspace_add_sparse(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        _at: Option<&str>,
+        sparse: &[String],
+    ) -> Result<()> {
+        let path_str = ws_path.to_string_lossy();
+        progress::step("adding worktree", || {
+            run_git_in(
+                repo_dir,
+                &["worktree", "add", "--no-checkout", &path_str, "-b", name],
+            )
+        })?;
+        // The patterns live in the worktree's own config, so the main
+        // checkout stays whole.
+        let mut args = vec!["sparse-checkout", "set", "--cone", "--"];
+        args.extend(sparse.iter().map(String::as_str));
+        progress::step("setting sparse patterns", || run_git_in(ws_path, &args))?;
+        progress::step("checking out files", || run_git_in(ws_path, &["checkout"]))?;
+        Ok(())
+    }
+
+    fn workspace_remove(&self, repo_dir: &Path, _name: &str, ws_path: &Path) -> Result<()> {
+        let path_str = ws_path.to_string_lossy();
+        run_git_in(repo_dir, &["worktree", "remove", &path_str, "--force"])?;
+        Ok(())
+    }
+
+    fn workspace_rename(
+        &self,
+        repo_dir: &Path,
+        old_path: &Path,
+        new_path: &Path,
+        _old_name: &str,
+        _new_name: &str,
+    ) -> Result<()> {
+        let old_str = old_path.to_string_lossy();
+        let new_str = new_path.to_string_lossy();
+        run_git_in(repo_dir, &["worktree", "move", &old_str, &new_str])?;
+        Ok(())
+    }
+
+    fn diff_stat_vs_trunk(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+    ) -> Result<DiffStat> {
+        let repo = open(worktree_dir)?;
+        // A repo without a trunk branch yet has nothing to compare against;
+        // any other failure is a real problem with the worktree.
+        let Some(trunk) = trunk_commit(&repo) else {
+            return Ok(DiffStat::default());
+        };
+        let head = head_commit(&repo)
+            .with_context(|| format!("{} has no commit checked out", worktree_dir.display()))?;
+        diff_stat_between(&repo, trunk, head.id())
+    }
+
+    fn latest_description(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> String {
+        open(worktree_dir)
+            .ok()
+            .and_then(|repo| Some(head_commit(&repo)?.summary()?.to_string()))
+            .unwrap_or_default()
+    }
+
+    fn is_merged_into_trunk(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> bool {
+        let Ok(repo) = open(worktree_dir) else {
+            return false;
+        };
+        let trunk = trunk_commit(&repo);
+        let head = head_commit(&repo).map(|commit| commit.id());
+        trunk
+            .zip(head)
+            .is_some_and(|(trunk, head)| is_merged(&repo, trunk, head))
+    }
+
+    fn working_copy_status(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+    ) -> Result<WorkingCopyStatus> {
+        match run_git_in(worktree_dir, &["status", "--porcelain"]) {
+            Ok(out) => Ok(vcs::parse_status_porcelain(&out)),
+            // A bare repo has no working tree to be dirty.
+            Err(_) if is_bare(worktree_dir) => Ok(WorkingCopyStatus::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn set_trunk(&self, repo_dir: &Path, trunk: &str) -> Result<()> {
+        run_git_in(repo_dir, &["config", "dwm.trunk", trunk])?;
+        Ok(())
+    }
+
+    fn revision_description(&self, repo_dir: &Path, rev: &str) -> Option<String> {
+        let out = run_git_in(repo_dir, &["log", "-1", "--format=%B", rev, "--"]).ok()?;
+        Some(out.trim().to_string()).filter(|d| !d.is_empty())
+    }
+
+    fn trunk_id(&self, repo_dir: &Path) -> Option<String> {
+        let repo = open(repo_dir).ok()?;
+        trunk_commit(&repo).map(|id| id.to_string())
+    }
+
+    fn workspace_details(
+        &self,
+        repo_dir: &Path,
+        workspaces: &[(&str, &Path)],
+    ) -> Vec<WorkspaceDetails> {
+        let none = || {
+            workspaces
+                .iter()
+                .map(|_| WorkspaceDetails::default())
+                .collect()
+        };
+        // Worktrees share refs and objects, so one handle on the repo
+        // serves all of them.
+        let Ok(repo) = open_main(repo_dir) else {
+            return none();
+        };
+        let Some(trunk) = trunk_commit(&repo) else {
+            return none();
+        };
+        let heads: HashMap<PathBuf, Option<Oid>> = worktrees(&repo)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|wt| (canonical(&wt.path), Oid::from_str(&wt.head).ok()))
+            .collect();
+        // A bare main repo has no working tree, so nothing to compare.
+        let bare = repo.is_bare().then(|| canonical(repo_dir));
+
+        workspaces
+            .iter()
+            .map(|&(_, path)| {
+                let path = canonical(path);
+                if bare.as_ref() == Some(&path) {
+                    return WorkspaceDetails::default();
+                }
+                let head = match heads.get(&path) {
+                    Some(head) => *head,
+                    None => open(&path)
+                        .ok()
+                        .and_then(|repo| head_commit(&repo).map(|commit| commit.id())),
+                };
+                let Some(head) = head else {
+                    let e = anyhow!("{} has no commit checked out", path.display());
+                    return WorkspaceDetails {
+                        error: Some(VcsError::new("diff stat", &e)),
+                        ..Default::default()
+                    };
+                };
+                let (diff_stat, error) = match diff_stat_between(&repo, trunk, head) {
+                    Ok(stat) => (stat, None),
+                    Err(e) => (DiffStat::default(), Some(VcsError::new("diff stat", &e))),
+                };
+                WorkspaceDetails {
+                    diff_stat,
+                    merged: is_merged(&repo, trunk, head),
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    fn update_submodules(&self, ws_path: &Path) -> Result<()> {
+        if !ws_path.join(".gitmodules").exists() {
+            return Ok(());
+        }
+        // Captured rather than streamed, as it would garble the step spinner.
+        run_git_in(ws_path, &["submodule", "update", "--init", "--recursive"])?;
+        Ok(())
+    }
+
+    fn fetch(&self, repo_dir: &Path) -> Result<()> {
+        vcs::run_with_timeout(
+            Command::new("git")
+                .args(["fetch", "--quiet"])
+                .current_dir(repo_dir),
+            vcs::FETCH_TIMEOUT,
+        )
+    }
+
+    fn push(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> Result<String> {
+        let branch = run_git_in(worktree_dir, &["rev-parse", "--abbrev-ref", "HEAD"])?;
+        let branch = branch.trim();
+        if branch == "HEAD" {
+            bail!("{} has no branch checked out", worktree_dir.display());
+        }
+        let remote = push_remote(worktree_dir, branch)?;
+        run_git_in(worktree_dir, &["push", "--quiet", "-u", &remote, branch])?;
+        Ok(format!("{}/{}", remote, branch))
+    }
+
+    fn sync(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> Result<String> {
+        let onto = detect_trunk(worktree_dir);
+        if let Err(e) = run_git_in(worktree_dir, &["rebase", "--quiet", &onto]) {
+            let conflicts = run_git_in(worktree_dir, &["diff", "--name-only", "--diff-filter=U"])
+                .unwrap_or_default();
+            // Don't leave a half-finished rebase behind.
+            let _ = run_git_in(worktree_dir, &["rebase", "--abort"]);
+            let files: Vec<&str> = conflicts.lines().collect();
+            if files.is_empty() {
+                return Err(e);
+            }
+            bail!(
+                "rebasing onto {} conflicts in {}; left the workspace as it was",
+                onto,
+                files.join(", ")
+            );
+        }
+        Ok(onto)
+    }
+
+    fn vcs_type(&self) -> crate::vcs::VcsType {
+        crate::vcs::VcsType::Git
+    }
+
+    fn main_workspace_name(&self) -> &'static str {
+        "main-worktree"
+    }
+
+    fn preview_log(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let limit_str = format!("-{}", limit);
+        run_git_in(
+            worktree_dir,
+            &["log", "--oneline", "--decorate", &limit_str],
+        )
+        .unwrap_or_default()
+    }
+
+    fn preview_graph(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let limit_str = format!("-{}", limit);
+        let trunk = detect_trunk(worktree_dir);
+        // Include trunk so the graph shows where the workspace forked from it.
+        let mut args = vec![
+            "log",
+            "--graph",
+            "--oneline",
+            "--decorate",
+            "--color=always",
+            &limit_str,
+            "HEAD",
+        ];
+        if run_git_in(worktree_dir, &["rev-parse", "--verify", "--quiet", &trunk]).is_ok() {
+            args.push(&trunk);
+        }
+        run_git_in(worktree_dir, &args).unwrap_or_default()
+    }
+
+    fn preview_diff_stat(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> String {
+        let trunk = detect_trunk(worktree_dir);
+        let range = format!("{}..HEAD", trunk);
+        run_git_in(worktree_dir, &["diff", "--stat", &range]).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_backend_vcs_type() {
+        assert_eq!(GitBackend.vcs_type(), crate::vcs::VcsType::Git);
+    }
+
+    #[test]
+    fn git_backend_main_workspace_name() {
+        assert_eq!(GitBackend.main_workspace_name(), "main-worktree");
+    }
+
+    // Integration tests that require a real git repo
+    #[test]
+    fn integration_root_from() {
+        let dir = tempfile::tempdir().unwrap();
+        Command::new("git")
+            .args(["init", dir.path().to_str().unwrap()])
+            .output()
+            .expect("git must be installed to run this test");
+        let backend = GitBackend;
+        let root = backend.root_from(dir.path()).unwrap();
+        // Canonicalize both for comparison (handles /private/tmp on macOS)
+        let expected = dir.path().canonicalize().unwrap();
+        let actual = root.canonicalize().unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn integration_detect_trunk_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        Command::new("git")
+            .args(["init", "-b", "main", dir.path().to_str().unwrap()])
+            .output()
+            .expect("git must be installed to run this test");
+        // Need at least one commit for the branch to exist
+        let _ = Command::new("git")
+            .args([
+                "-C",
+                dir.path().to_str().unwrap(),
+                "commit",
+                "--allow-empty",
+                "-m",
+                "init",
+            ])
+            .output();
+        let trunk = trunk_name(&open(dir.path()).unwrap());
+        assert_eq!(trunk, "main");
+    }
+
+    #[test]
+    fn integration_detect_trunk_master() {
+        let dir = tempfile::tempdir().unwrap();
+        Command::new("git")
+            .args(["init", "-b", "master", dir.path().to_str().unwrap()])
+            .output()
+            .expect("git must be installed to run this test");
+        let _ = Command::new("git")
+            .args([
+                "-C",
+                dir.path().to_str().unwrap(),
+                "commit",
+                "--allow-empty",
+                "-m",
+                "init",
+            ])
+            .output();
+        let trunk = trunk_name(&open(dir.path()).unwrap());
+        assert_eq!(trunk, "master");
+    }
+
+    #[test]
+    fn integration_set_trunk_overrides_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        Command::new("git")
+            .args(["init", "-b", "main", dir.path().to_str().unwrap()])
+            .output()
+            .expect("git must be installed to run this test");
+        GitBackend.set_trunk(dir.path(), "develop").unwrap();
+        assert_eq!(trunk_name(&open(dir.path()).unwrap()), "develop");
+    }
+
+    #[test]
+    fn integration_workspace_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        let git = |cwd: &Path, args: &[&str]| {
+            let out = Command::new("git")
+                .args(["-c", "user.name=dwm", "-c", "user.email=dwm@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .expect("git must be installed to run this test");
+            assert!(out.status.success(), "git {:?} failed", args);
+        };
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-b", "main"]);
+        git(&repo, &["commit", "--allow-empty", "-m", "init"]);
+        let merged_ws = dir.path().join("merged");
+        let feature_ws = dir.path().join("feature");
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "merged",
+                merged_ws.to_str().unwrap(),
+            ],
+        );
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "feature",
+                feature_ws.to_str().unwrap(),
+            ],
+        );
+        std::fs::write(feature_ws.join("new.txt"), "one\ntwo\n").unwrap();
+        git(&feature_ws, &["add", "new.txt"]);
+        git(&feature_ws, &["commit", "-m", "Add new.txt"]);
+
+        let details = GitBackend.workspace_details(
+            &repo,
+            &[
+                ("main-worktree", repo.as_path()),
+                ("merged", merged_ws.as_path()),
+                ("feature", feature_ws.as_path()),
+            ],
+        );
+        assert_eq!(details.len(), 3);
+        assert!(details[0].merged);
+        assert!(details[1].merged);
+        assert_eq!(details[1].diff_stat.insertions, 0);
+        assert!(!details[2].merged);
+        assert_eq!(details[2].diff_stat.files_changed, 1);
+        assert_eq!(details[2].diff_stat.insertions, 2);
+        assert!(details.iter().all(|d| d.error.is_none()));
+
+        // The batched stats match asking about each worktree on its own.
+        std::fs::write(repo.join("trunk.txt"), "three\n").unwrap();
+        git(&repo, &["add", "trunk.txt"]);
+        git(&repo, &["commit", "-m", "Add trunk.txt"]);
+        let details = GitBackend.workspace_details(
+            &repo,
+            &[
+                ("merged", merged_ws.as_path()),
+                ("feature", feature_ws.as_path()),
+            ],
+        );
+        assert_eq!(
+            (details[0].diff_stat.ahead, details[0].diff_stat.behind),
+            (0, 1)
+        );
+        assert_eq!(
+            (details[1].diff_stat.ahead, details[1].diff_stat.behind),
+            (1, 1)
+        );
+        assert_eq!(
+            details[1].diff_stat,
+            GitBackend
+                .diff_stat_vs_trunk(&repo, &feature_ws, "feature")
+                .unwrap()
+        );
+        assert_eq!(
+            details[0].diff_stat,
+            GitBackend
+                .diff_stat_vs_trunk(&repo, &merged_ws, "merged")
+                .unwrap()
+        );
+        assert!(GitBackend.is_merged_into_trunk(&repo, &merged_ws, "merged"));
+        assert!(!GitBackend.is_merged_into_trunk(&repo, &feature_ws, "feature"));
+
+        let list = GitBackend.workspace_list(&repo).unwrap();
+        let feature = list.iter().find(|(name, _)| name == "feature").unwrap();
+        assert_eq!(feature.1.description, "Add new.txt");
+
+        let status = |ws: &Path| {
+            GitBackend
+                .working_copy_status(&repo, ws, "feature")
+                .unwrap()
+        };
+        assert!(!status(&feature_ws).is_dirty());
+        std::fs::write(feature_ws.join("new.txt"), "changed\n").unwrap();
+        std::fs::write(feature_ws.join("scratch.txt"), "").unwrap();
+        assert_eq!(
+            status(&feature_ws).describe(),
+            Some("uncommitted changes and untracked files")
+        );
+    }
+
+    #[test]
+    fn integration_workspace_list_reads_each_worktree() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        let git = |cwd: &Path, args: &[&str]| {
+            let out = Command::new("git")
+                .args(["-c", "user.name=dwm", "-c", "user.email=dwm@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .expect("git must be installed to run this test");
+            assert!(out.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        };
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-b", "main"]);
+        git(&repo, &["commit", "--allow-empty", "-m", "init\n\nbody"]);
+        for name in ["zeta", "alpha"] {
+            let path = dir.path().join(name);
+            git(
+                &repo,
+                &["worktree", "add", "-b", name, path.to_str().unwrap()],
+            );
+        }
+        let detached = dir.path().join("detached");
+        git(
+            &repo,
+            &["worktree", "add", "--detach", detached.to_str().unwrap()],
+        );
+        git(
+            &detached,
+            &["commit", "--allow-empty", "-m", "Try something"],
+        );
+
+        let list = GitBackend.workspace_list(&repo).unwrap();
+        let names: Vec<&str> = list.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["repo", "alpha", "detached", "zeta"]);
+        let (_, main) = &list[0];
+        assert_eq!(main.commit_id, git(&repo, &["rev-parse", "HEAD"]));
+        assert_eq!(main.change_id, &main.commit_id[..8]);
+        assert_eq!(main.bookmarks, vec!["main".to_string()]);
+        let (_, detached_info) = &list[2];
+        assert!(detached_info.bookmarks.is_empty());
+        assert_eq!(detached_info.description, "Try something");
+
+        // Listing from inside a worktree still finds them all.
+        let from_worktree = GitBackend.workspace_list(&detached).unwrap();
+        assert_eq!(from_worktree.len(), 4);
+        assert_eq!(worktree_heads(&repo).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn integration_fetch_shows_upstream_merges() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |cwd: &Path, args: &[&str]| {
+            let out = Command::new("git")
+                .args(["-c", "user.name=dwm", "-c", "user.email=dwm@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .expect("git must be installed to run this test");
+            assert!(out.status.success(), "git {:?} failed", args);
+        };
+        let remote = dir.path().join("remote.git");
+        git(dir.path(), &["init", "--bare", "-b", "main", "remote.git"]);
+        let repo = dir.path().join("repo");
+        git(
+            dir.path(),
+            &["clone", remote.to_str().unwrap(), repo.to_str().unwrap()],
+        );
+        git(&repo, &["commit", "--allow-empty", "-m", "init"]);
+        git(&repo, &["push", "--quiet", "-u", "origin", "main"]);
+        let ws = dir.path().join("feat");
+        git(
+            &repo,
+            &["worktree", "add", "-b", "feat", ws.to_str().unwrap()],
+        );
+        std::fs::write(ws.join("feat.txt"), "feature\n").unwrap();
+        git(&ws, &["add", "."]);
+        git(&ws, &["commit", "-m", "feature"]);
+        git(&ws, &["push", "--quiet", "-u", "origin", "feat"]);
+
+        // Someone else merges the branch upstream.
+        let other = dir.path().join("other");
+        git(
+            dir.path(),
+            &["clone", remote.to_str().unwrap(), other.to_str().unwrap()],
+        );
+        git(&other, &["merge", "--quiet", "--no-ff", "origin/feat"]);
+        git(&other, &["push", "--quiet", "origin", "main"]);
+
+        let details = |repo: &Path| GitBackend.workspace_details(repo, &[("feat", ws.as_path())]);
+        let before = details(&repo);
+        assert!(!before[0].merged);
+        assert_eq!(before[0].diff_stat.ahead, 1);
+
+        GitBackend.fetch(&repo).unwrap();
+        let after = details(&repo);
+        assert!(after[0].merged);
+        assert_eq!(
+            (after[0].diff_stat.ahead, after[0].diff_stat.files_changed),
+            (0, 0)
+        );
+        assert!(GitBackend.is_merged_into_trunk(&repo, &ws, "feat"));
+        assert_eq!(
+            GitBackend.trunk_id(&repo),
+            Some(
+                resolve(&open(&repo).unwrap(), "origin/main")
+                    .unwrap()
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn integration_push_sets_upstream() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |cwd: &Path, args: &[&str]| {
+            let out = Command::new("git")
+                .args(["-c", "user.name=dwm", "-c", "user.email=dwm@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .expect("git must be installed to run this test");
+            assert!(out.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        };
+        let remote = dir.path().join("remote.git");
+        git(dir.path(), &["init", "--bare", "-b", "main", "remote.git"]);
+        let repo = dir.path().join("repo");
+        git(
+            dir.path(),
+            &["clone", remote.to_str().unwrap(), repo.to_str().unwrap()],
+        );
+        git(&repo, &["commit", "--allow-empty", "-m", "init"]);
+        let ws = dir.path().join("feat");
+        git(
+            &repo,
+            &["worktree", "add", "-b", "feat", ws.to_str().unwrap()],
+        );
+        git(&ws, &["commit", "--allow-empty", "-m", "feature"]);
+
+        let pushed = GitBackend.push(&repo, &ws, "feat").unwrap();
+        assert_eq!(pushed, "origin/feat");
+        assert_eq!(
+            git(&ws, &["rev-parse", "--abbrev-ref", "feat@{upstream}"]),
+            "origin/feat"
+        );
+        assert_eq!(
+            git(&remote, &["rev-parse", "feat"]),
+            git(&ws, &["rev-parse", "HEAD"])
+        );
+
+        git(&ws, &["checkout", "--detach"]);
+        let err = GitBackend.push(&repo, &ws, "feat").unwrap_err();
+        assert!(err.to_string().contains("no branch"), "{}", err);
+    }
+
+    #[test]
+    fn integration_sync_rebases_or_reports_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |cwd: &Path, args: &[&str]| {
+            let out = Command::new("git")
+                .args(["-c", "user.name=dwm", "-c", "user.email=dwm@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .expect("git must be installed to run this test");
+            assert!(out.status.success(), "git {:?} failed", args);
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        };
+        let repo = dir.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-b", "main"]);
+        std::fs::write(repo.join("a.txt"), "base\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-m", "init"]);
+        let ws = dir.path().join("feat");
+        git(
+            &repo,
+            &["worktree", "add", "-b", "feat", ws.to_str().unwrap()],
+        );
+        std::fs::write(ws.join("b.txt"), "feature\n").unwrap();
+        git(&ws, &["add", "."]);
+        git(&ws, &["commit", "-m", "feature"]);
+        std::fs::write(repo.join("c.txt"), "trunk\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-m", "trunk moves"]);
+
+        let stat = GitBackend.diff_stat_vs_trunk(&repo, &ws, "feat").unwrap();
+        assert_eq!((stat.ahead, stat.behind), (1, 1));
+
+        assert_eq!(GitBackend.sync(&repo, &ws, "feat").unwrap(), "main");
+        let stat = GitBackend.diff_stat_vs_trunk(&repo, &ws, "feat").unwrap();
+        assert_eq!((stat.ahead, stat.behind), (1, 0));
+        assert_eq!(
+            git(&ws, &["merge-base", "HEAD", "main"]),
+            git(&repo, &["rev-parse", "main"])
+        );
+
+        std::fs::write(ws.join("a.txt"), "feature edit\n").unwrap();
+        git(&ws, &["commit", "-am", "edit a"]);
+        std::fs::write(repo.join("a.txt"), "trunk edit\n").unwrap();
+        git(&repo, &["commit", "-am", "edit a on trunk"]);
+        let before = git(&ws, &["rev-parse", "HEAD"]);
+        let err = GitBackend.sync(&repo, &ws, "feat").unwrap_err();
+        assert!(err.to_string().contains("conflicts in a.txt"), "{}", err);
+        assert_eq!(git(&ws, &["rev-parse", "HEAD"]), before);
+        assert_eq!(git(&ws, &["status", "--porcelain"]), "");
+    }
+
+    #[test]
+    fn integration_bare_repo_with_worktrees() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |cwd: &Path, args: &[&str]| {
+            let out = Command::new("git")
+                .args(["-c", "user.name=dwm", "-c", "user.email=dwm@example.com"])
+                .args(args)
+                .current_dir(cwd)
+                .output()
+                .expect("git must be installed to run this test");
+            assert!(out.status.success(), "git {:?} failed", args);
+        };
+        let src = dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        git(&src, &["init", "-b", "main"]);
+        git(&src, &["commit", "--allow-empty", "-m", "init"]);
+        let bare = dir.path().join("repo.git");
+        git(
+            dir.path(),
+            &["clone", "--bare", "src", bare.to_str().unwrap()],
+        );
+        let feature_ws = dir.path().join("feature");
+        git(
+            &bare,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "feature",
+                feature_ws.to_str().unwrap(),
+            ],
+        );
+
+        assert_eq!(
+            canonical(&GitBackend.root_from(&bare).unwrap()),
+            canonical(&bare)
+        );
+        assert_eq!(
+            canonical(&GitBackend.root_from(&feature_ws).unwrap()),
+            canonical(&bare)
+        );
+        assert!(
+            vcs::detect(&bare).unwrap().vcs_type() == vcs::VcsType::Git,
+            "bare repo should be detected as git"
+        );
+
+        let details = GitBackend.workspace_details(
+            &bare,
+            &[
+                ("main-worktree", bare.as_path()),
+                ("feature", feature_ws.as_path()),
+            ],
+        );
+        assert!(details.iter().all(|d| d.error.is_none()));
+        let status = GitBackend
+            .working_copy_status(&bare, &bare, "main-worktree")
+            .unwrap();
+        assert!(!status.is_dirty());
+
+        let list = GitBackend.workspace_list(&bare).unwrap();
+        let names: Vec<&str> = list.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["feature"]);
+    }
+}
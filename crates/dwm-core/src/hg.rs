@@ -0,0 +1,345 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::exit::Failure;
+use crate::progress;
+use crate::vcs::{self, DiffStat, VcsBackend, WorkingCopyStatus, WorkspaceInfo};
+
+/// Template for [`parse_log_info`]: short node, bookmarks, first line of the
+/// description, one per line.
+const INFO_TEMPLATE: &str = "{node|short}\\n{bookmarks}\\n{desc|firstline}\\n";
+
+/// Run `hg` with the given arguments inside `dir`. The share extension is
+/// enabled for every call, since workspaces are shares.
+fn run_hg_in(dir: &Path, args: &[&str]) -> Result<String> {
+    log::debug!("hg {} (in {})", args.join(" "), dir.display());
+    let output = Command::new("hg")
+        .args(["--config", "extensions.share="])
+        .args(args)
+        .current_dir(dir)
+        .env("HGPLAIN", "1")
+        .output()
+        .context(Failure::vcs("failed to run hg - is it installed?"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(Failure::vcs(format!(
+            "hg {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Try to detect the trunk revision: a `main` or `master` bookmark, falling
+/// back to the tip of the `default` branch.
+fn detect_trunk(dir: &Path) -> String {
+    for bookmark in ["main", "master"] {
+        let revset = format!("bookmark('{}')", bookmark);
+        if run_hg_in(dir, &["log", "-r", &revset, "-T", "x"]).is_ok_and(|out| !out.is_empty()) {
+            return bookmark.to_string();
+        }
+    }
+    "default".to_string()
+}
+
+/// Parse the output of `hg log -r . -T INFO_TEMPLATE` into a
+/// [`WorkspaceInfo`], shortening the node to 8 hex chars.
+fn parse_log_info(output: &str) -> WorkspaceInfo {
+    let mut lines = output.lines();
+    let node = lines.next().unwrap_or("").trim();
+    let bookmarks = lines
+        .next()
+        .unwrap_or("")
+        .split_whitespace()
+        .map(String::from)
+        .collect();
+    let description = lines.next().unwrap_or("").trim().to_string();
+    WorkspaceInfo {
+        change_id: node.chars().take(8).collect(),
+        description,
+        bookmarks,
+        // hg diffs include uncommitted changes, which no node captures, so
+        // hg workspaces aren't cached.
+        commit_id: String::new(),
+    }
+}
+
+/// Resolve a share's source repo from the contents of its `.hg/sharedpath`,
+/// which points at the source's `.hg` directory.
+fn parse_sharedpath(contents: &str) -> Option<PathBuf> {
+    let store = PathBuf::from(contents.trim());
+    store.parent().map(Path::to_path_buf)
+}
+
+/// Shares under `dwm_repo_dir` (directories with a `.hg`), as `(name, path)`.
+fn share_dirs(dwm_repo_dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dwm_repo_dir) else {
+        return Vec::new();
+    };
+    let mut shares: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.join(".hg").is_dir())
+        .filter_map(|p| {
+            let name = p.file_name()?.to_string_lossy().to_string();
+            (!name.starts_with('.')).then_some((name, p))
+        })
+        .collect();
+    shares.sort();
+    shares
+}
+
+/// [`VcsBackend`] implementation that delegates to the `hg` CLI, with each
+/// workspace a share of the main repo carrying a bookmark of the same name.
+pub struct HgBackend;
+
+impl VcsBackend for HgBackend {
+    fn root_from(&self, dir: &Path) -> Result<PathBuf> {
+        let root = PathBuf::from(run_hg_in(dir, &["root"])?.trim());
+        // A share's root is the share itself; report the repo it shares.
+        let root = match fs::read_to_string(root.join(".hg").join("sharedpath")) {
+            Ok(contents) => parse_sharedpath(&contents)
+                .with_context(|| format!("bad sharedpath in {}", root.display()))?,
+            Err(_) => root,
+        };
+        Ok(crate::vcs::canonical(&root))
+    }
+
+    fn workspace_list(&self, repo_dir: &Path) -> Result<Vec<(String, WorkspaceInfo)>> {
+        let info = |dir: &Path| -> Result<WorkspaceInfo> {
+            let out = run_hg_in(dir, &["log", "-r", ".", "-T", INFO_TEMPLATE])?;
+            Ok(parse_log_info(&out))
+        };
+        let mut results = vec![(self.main_workspace_name().to_string(), info(repo_dir)?)];
+        // hg doesn't track shares, so look for them where dwm puts them.
+        if let Some(home) = dirs::home_dir() {
+            let dwm_repo_dir = home.join(".dwm").join(vcs::repo_dir_name(repo_dir));
+            for (name, path) in share_dirs(&dwm_repo_dir) {
+                if let Ok(info) = info(&path) {
+                    results.push((name, info));
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn workspace_add(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        at: Option<&str>,
+    ) -> Result<()> {
+        let repo_str = repo_dir.to_string_lossy();
+        let path_str = ws_path.to_string_lossy();
+        // Share bookmarks too, so trunk and each workspace's bookmark are
+        // visible from every share.
+        progress::step("sharing repo", || {
+            run_hg_in(repo_dir, &["share", "--bookmarks", &repo_str, &path_str])
+        })?;
+        if let Some(rev) = at {
+            progress::step(&format!("updating to {}", rev), || {
+                run_hg_in(ws_path, &["update", "--rev", rev])
+            })?;
+        }
+        progress::step(&format!("adding bookmark '{}'", name), || {
+            run_hg_in(ws_path, &["bookmark", name])
+        })?;
+        Ok(())
+    }
+
+    fn workspace_remove(&self, _repo_dir: &Path, _name: &str, ws_path: &Path) -> Result<()> {
+        // A share is just a directory; its commits and bookmark live on in
+        // the main repo.
+        fs::remove_dir_all(ws_path)
+            .with_context(|| format!("could not remove {}", ws_path.display()))
+    }
+
+    fn workspace_rename(
+        &self,
+        _repo_dir: &Path,
+        old_path: &Path,
+        new_path: &Path,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        fs::rename(old_path, new_path)
+            .with_context(|| format!("could not move {}", old_path.display()))?;
+        // The workspace may have moved off its bookmark; that's fine.
+        let _ = run_hg_in(new_path, &["bookmark", "--rename", old_name, new_name]);
+        Ok(())
+    }
+
+    fn diff_stat_vs_trunk(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+    ) -> Result<DiffStat> {
+        let trunk = detect_trunk(worktree_dir);
+        let base = format!("ancestor({}, .)", trunk);
+        let text = run_hg_in(worktree_dir, &["diff", "--stat", "--rev", &base])?;
+        let mut stat = vcs::parse_diff_stat(&text)?;
+        let count = |revset: String| {
+            run_hg_in(worktree_dir, &["log", "-r", &revset, "-T", "x"])
+                .map_or(0, |out| out.len() as u32)
+        };
+        stat.ahead = count(format!("only(., {})", trunk));
+        stat.behind = count(format!("only({}, .)", trunk));
+        Ok(stat)
+    }
+
+    fn latest_description(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> String {
+        run_hg_in(worktree_dir, &["log", "-r", ".", "-T", "{desc|firstline}"])
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    fn revision_description(&self, repo_dir: &Path, rev: &str) -> Option<String> {
+        let out = run_hg_in(repo_dir, &["log", "-r", rev, "-l", "1", "-T", "{desc}"]).ok()?;
+        Some(out.trim().to_string()).filter(|d| !d.is_empty())
+    }
+
+    fn is_merged_into_trunk(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> bool {
+        let trunk = detect_trunk(worktree_dir);
+        // Merged when no commit of the workspace is missing from trunk.
+        let revset = format!("only(., {})", trunk);
+        run_hg_in(worktree_dir, &["log", "-r", &revset, "-T", "x"]).is_ok_and(|out| out.is_empty())
+    }
+
+    fn working_copy_status(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+    ) -> Result<WorkingCopyStatus> {
+        let out = run_hg_in(worktree_dir, &["status"])?;
+        Ok(vcs::parse_status_porcelain(&out))
+    }
+
+    fn fetch(&self, repo_dir: &Path) -> Result<()> {
+        vcs::run_with_timeout(
+            Command::new("hg")
+                .args(["pull", "--quiet"])
+                .current_dir(repo_dir)
+                .env("HGPLAIN", "1"),
+            vcs::FETCH_TIMEOUT,
+        )
+    }
+
+    fn vcs_type(&self) -> crate::vcs::VcsType {
+        crate::vcs::VcsType::Hg
+    }
+
+    fn main_workspace_name(&self) -> &'static str {
+        "default"
+    }
+
+    fn preview_log(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let limit_str = limit.to_string();
+        run_hg_in(
+            worktree_dir,
+            &[
+                "log",
+                "--limit",
+                &limit_str,
+                "-r",
+                "reverse(::.)",
+                "-T",
+                "{node|short} {if(bookmarks, '({bookmarks}) ')}{desc|firstline}\\n",
+            ],
+        )
+        .unwrap_or_default()
+    }
+
+    fn preview_graph(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let limit_str = limit.to_string();
+        // Include trunk so the graph shows where the workspace forked from it.
+        let revset = format!("reverse(::. or ::{})", detect_trunk(worktree_dir));
+        run_hg_in(
+            worktree_dir,
+            &[
+                "log",
+                "--graph",
+                "--color=always",
+                "--limit",
+                &limit_str,
+                "-r",
+                &revset,
+                "-T",
+                "{label('log.changeset', node|short)} {label('log.bookmark', bookmarks)} {desc|firstline}\\n",
+            ],
+        )
+        .unwrap_or_default()
+    }
+
+    fn preview_diff_stat(&self, _repo_dir: &Path, worktree_dir: &Path, _ws_name: &str) -> String {
+        let base = format!("ancestor({}, .)", detect_trunk(worktree_dir));
+        run_hg_in(worktree_dir, &["diff", "--stat", "--rev", &base]).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_log_info_basic() {
+        let info = parse_log_info("0123456789ab\nfeature main\nAdd the thing\n");
+        assert_eq!(info.change_id, "01234567");
+        assert_eq!(info.bookmarks, vec!["feature", "main"]);
+        assert_eq!(info.description, "Add the thing");
+    }
+
+    #[test]
+    fn parse_log_info_no_bookmarks() {
+        let info = parse_log_info("0123456789ab\n\nfix\n");
+        assert!(info.bookmarks.is_empty());
+        assert_eq!(info.description, "fix");
+        assert!(parse_log_info("").change_id.is_empty());
+    }
+
+    #[test]
+    fn parse_sharedpath_points_at_source_repo() {
+        assert_eq!(
+            parse_sharedpath("/home/user/project/.hg\n"),
+            Some(PathBuf::from("/home/user/project"))
+        );
+    }
+
+    #[test]
+    fn share_dirs_lists_only_shares() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("feat").join(".hg")).unwrap();
+        fs::create_dir_all(dir.path().join("plain")).unwrap();
+        fs::create_dir_all(dir.path().join(".agent-status")).unwrap();
+        let shares = share_dirs(dir.path());
+        assert_eq!(shares, vec![("feat".to_string(), dir.path().join("feat"))]);
+        assert!(share_dirs(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn hg_backend_vcs_type() {
+        assert_eq!(HgBackend.vcs_type(), crate::vcs::VcsType::Hg);
+    }
+
+    #[test]
+    fn hg_backend_main_workspace_name() {
+        assert_eq!(HgBackend.main_workspace_name(), "default");
+    }
+}
@@ -0,0 +1,821 @@
+use anyhow::{Context, Result, bail};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::exit::Failure;
+use crate::progress;
+use crate::vcs::{
+    self, CommitGraph, DiffStat, VcsBackend, VcsError, WorkingCopyStatus, WorkspaceDetails,
+    WorkspaceInfo,
+};
+
+/// Run `jj` with the given arguments in the current working directory.
+fn run_jj(args: &[&str]) -> Result<String> {
+    log::debug!("jj {}", args.join(" "));
+    let output = Command::new("jj")
+        .args(args)
+        .output()
+        .context(Failure::vcs("failed to run jj - is it installed?"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(Failure::vcs(format!(
+            "jj {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Run `jj` with the given arguments inside `dir`. If the workspace there
+/// has a stale working copy (the repo moved on from another workspace), it's
+/// updated with `jj workspace update-stale` and the command retried once.
+fn run_jj_in(dir: &Path, args: &[&str]) -> Result<String> {
+    match run_jj_in_once(dir, args) {
+        Err(e) if is_stale_working_copy_error(&e.to_string()) => {
+            run_jj_in_once(dir, &["workspace", "update-stale"])
+                .context("could not update stale working copy")?;
+            run_jj_in_once(dir, args)
+        }
+        result => result,
+    }
+}
+
+/// Whether jj's error output says the working copy is stale.
+fn is_stale_working_copy_error(stderr: &str) -> bool {
+    stderr.contains("working copy is stale")
+}
+
+/// Run `jj` with the given arguments inside `dir`, without recovering from
+/// a stale working copy.
+fn run_jj_in_once(dir: &Path, args: &[&str]) -> Result<String> {
+    log::debug!("jj {} (in {})", args.join(" "), dir.display());
+    let output = Command::new("jj")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context(Failure::vcs("failed to run jj - is it installed?"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(Failure::vcs(format!(
+            "jj {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Return the jj repository root from the current working directory.
+pub fn root() -> Result<PathBuf> {
+    let out = run_jj(&["root"])?;
+    Ok(PathBuf::from(out.trim()))
+}
+
+/// Return the jj repository root by running `jj root` inside `dir`.
+pub fn root_from(dir: &Path) -> Result<PathBuf> {
+    let out = run_jj_in(dir, &["root"])?;
+    Ok(crate::vcs::canonical(Path::new(out.trim())))
+}
+
+/// Return the basename of the current jj repository root directory.
+pub fn repo_name() -> Result<String> {
+    let root = root()?;
+    let name = root
+        .file_name()
+        .context("repo root has no directory name")?
+        .to_string_lossy()
+        .to_string();
+    Ok(name)
+}
+
+/// Return the jj template string used with `jj workspace list -T`.
+///
+/// Fields are separated by NUL (`\0`) and records by `\0\n` so that
+/// descriptions containing tabs or newlines are parsed correctly.
+fn workspace_list_template() -> &'static str {
+    concat!(
+        r#"name ++ "\0" ++ self.target().change_id().shortest(8) ++ "\0""#,
+        r#" ++ self.target().description() ++ "\0""#,
+        r#" ++ self.target().bookmarks().map(|b| b.name()).join(",") ++ "\0""#,
+        r#" ++ self.target().commit_id() ++ "\0\n""#,
+    )
+}
+
+/// Parse the NUL-delimited output produced by `jj workspace list` with
+/// [`workspace_list_template`] into a list of `(workspace_name, WorkspaceInfo)` pairs.
+fn parse_workspace_info(output: &str) -> Result<Vec<(String, WorkspaceInfo)>> {
+    let mut results = Vec::new();
+    for record in output.split("\0\n") {
+        let record = record.trim_matches('\n');
+        if record.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = record.split('\0').collect();
+        if fields.len() >= 4 {
+            let name = fields[0].to_string();
+            let change_id = fields[1].to_string();
+            let description = fields[2].to_string();
+            let bookmarks: Vec<String> = fields[3]
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+            let commit_id = fields.get(4).unwrap_or(&"").to_string();
+            results.push((
+                name,
+                WorkspaceInfo {
+                    change_id,
+                    description,
+                    bookmarks,
+                    commit_id,
+                },
+            ));
+        }
+    }
+    Ok(results)
+}
+
+/// Format a workspace name as a jj revset operand, quoting it if it contains
+/// characters that are not valid in a bare identifier (e.g. spaces).
+fn revset_ws(name: &str) -> String {
+    if name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        format!("{}@", name)
+    } else {
+        format!("`{}`@", name)
+    }
+}
+
+/// Revset for the commit [`JjBackend::push`] pushes: the workspace's
+/// working-copy commit, or its parent when the working copy is empty (as it
+/// is after `jj commit`), unless that's already in trunk.
+fn push_revset(ws_name: &str) -> String {
+    let ws_at = ws_revset(ws_name);
+    format!("latest(({ws_at} | {ws_at}-) ~ empty() ~ ::trunk())")
+}
+
+/// The workspace's working-copy revset as seen from the main repo, where
+/// the default workspace is just `@`.
+fn ws_revset(ws_name: &str) -> String {
+    if ws_name == "default" {
+        "@".to_string()
+    } else {
+        revset_ws(ws_name)
+    }
+}
+
+/// Local bookmarks on `revset`, one per line in the output of `jj log`.
+fn bookmarks_at(dir: &Path, revset: &str) -> Result<Vec<String>> {
+    let out = run_jj_in(
+        dir,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            revset,
+            "-T",
+            r#"local_bookmarks.map(|b| b.name() ++ "\n").join("")"#,
+        ],
+    )?;
+    Ok(out.lines().map(str::to_string).collect())
+}
+
+/// Walk the ancestor chain of `workspace_name@` and return the description of
+/// the most recent commit that has a non-empty message. Returns an empty string
+/// when no such ancestor exists or jj returns an error.
+fn latest_description(dir: &Path, workspace_name: &str) -> String {
+    let ws_at = revset_ws(workspace_name);
+    let revset = format!(r#"latest(ancestors({ws_at}) & description(glob:"?*"))"#,);
+    let result = run_jj_in(
+        dir,
+        &[
+            "log",
+            "--no-graph",
+            "-r",
+            &revset,
+            "-T",
+            "description",
+            "--limit",
+            "1",
+        ],
+    );
+    match result {
+        Ok(text) => {
+            let trimmed = text.trim().to_string();
+            if trimmed.is_empty() {
+                String::new()
+            } else {
+                trimmed
+            }
+        }
+        Err(_) => String::new(),
+    }
+}
+
+/// Every commit trunk or a working copy has that isn't in the history of
+/// all of them, plus the working copies themselves (one can be that shared
+/// ancestor).
+const HISTORY_REVSET: &str =
+    "fork_point(trunk() | working_copies())..(trunk() | working_copies()) | working_copies()";
+
+/// Template for [`HISTORY_REVSET`]: each commit's id, parents, whether it's
+/// trunk, whether it's empty and undescribed, and the workspaces whose
+/// working copy it is, one name per line since names can contain spaces.
+fn history_template() -> &'static str {
+    concat!(
+        r#"commit_id ++ "\0" ++ parents.map(|c| c.commit_id()).join(",") ++ "\0""#,
+        r#" ++ if(self.contained_in("trunk()"), "trunk") ++ "\0""#,
+        r#" ++ if(empty, if(description, "", "empty")) ++ "\0""#,
+        r#" ++ working_copies.map(|w| w.name()).join("\n") ++ "\0\n""#,
+    )
+}
+
+/// The commits between trunk and the working copies, parsed from `jj log`
+/// with [`history_template`].
+#[derive(Debug, Default)]
+struct History {
+    graph: CommitGraph,
+    trunk: Option<String>,
+    /// Working-copy commit of each workspace, by name.
+    working_copies: HashMap<String, String>,
+    /// Empty, undescribed commits, which aren't work of their own.
+    empty: HashSet<String>,
+}
+
+impl History {
+    fn parse(output: &str) -> Self {
+        let mut history = Self::default();
+        for record in output.split("\0\n") {
+            let fields: Vec<&str> = record.trim_start_matches('\n').split('\0').collect();
+            let [id, parents, trunk, empty, names] = fields[..] else {
+                continue;
+            };
+            history.graph.insert(
+                id,
+                parents
+                    .split(',')
+                    .filter(|p| !p.is_empty())
+                    .map(String::from),
+            );
+            if !trunk.is_empty() {
+                history.trunk = Some(id.to_string());
+            }
+            if !empty.is_empty() {
+                history.empty.insert(id.to_string());
+            }
+            for name in names.lines().filter(|name| !name.is_empty()) {
+                history
+                    .working_copies
+                    .insert(name.to_string(), id.to_string());
+            }
+        }
+        history
+    }
+
+    /// Workspace `name`'s commits ahead of and behind trunk, and whether it
+    /// has been merged, or `None` if it wasn't in the output.
+    fn status(&self, name: &str) -> Option<(u32, u32, bool)> {
+        let trunk = self.trunk.as_deref()?;
+        let head = self.working_copies.get(name)?;
+        let (ahead, behind) = self
+            .graph
+            .ahead_behind(trunk, head, |id| !self.empty.contains(id));
+        let merged = self.graph.ahead_behind(trunk, head, |_| true).0 == 0;
+        Some((ahead, behind, merged))
+    }
+}
+
+/// Run `jj diff --stat --from <from> --to <to>` inside `dir` and parse the
+/// result. Returns a zeroed [`DiffStat`] if jj reports an error.
+fn diff_stat(dir: &Path, from: &str, to: &str) -> Result<DiffStat> {
+    let out = run_jj_in(dir, &["diff", "--stat", "--from", from, "--to", to]);
+    match out {
+        Ok(text) => vcs::parse_diff_stat(&text),
+        Err(_) => Ok(DiffStat::default()),
+    }
+}
+
+/// Number of commits in `revset`, or 0 if jj fails.
+fn count_commits(dir: &Path, revset: &str) -> u32 {
+    run_jj_in(dir, &["log", "--no-graph", "-r", revset, "-T", r#""x""#])
+        .map_or(0, |out| out.trim().len() as u32)
+}
+
+/// [`VcsBackend`] implementation that delegates to the `jj` CLI.
+pub struct JjBackend;
+
+impl VcsBackend for JjBackend {
+    fn root_from(&self, dir: &Path) -> Result<PathBuf> {
+        root_from(dir)
+    }
+
+    fn repo_name_from(&self, dir: &Path) -> Result<String> {
+        let root = self.root_from(dir)?;
+        Ok(crate::vcs::repo_dir_name(&root))
+    }
+
+    fn workspace_list(&self, repo_dir: &Path) -> Result<Vec<(String, WorkspaceInfo)>> {
+        let out = run_jj_in(
+            repo_dir,
+            &["workspace", "list", "-T", workspace_list_template()],
+        )?;
+        parse_workspace_info(&out)
+    }
+
+    fn workspace_add(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        at: Option<&str>,
+    ) -> Result<()> {
+        let path_str = ws_path.to_string_lossy();
+        let mut args = vec!["workspace", "add", "--name", name, &path_str];
+        if let Some(rev) = at {
+            args.push("--revision");
+            args.push(rev);
+        }
+        progress::step("adding workspace", || run_jj_in(repo_dir, &args))?;
+        Ok(())
+    }
+
+    fn workspace_add_sparse(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        at: Option<&str>,
+        sparse: &[String],
+    ) -> Result<()> {
+        let path_str = ws_path.to_string_lossy();
+        let mut args = vec![
+            "workspace",
+            "add",
+            "--name",
+            name,
+            "--sparse-patterns",
+            "empty",
+            &path_str,
+        ];
+        if let Some(rev) = at {
+            args.push("--revision");
+            args.push(rev);
+        }
+        progress::step("adding workspace", || run_jj_in(repo_dir, &args))?;
+        let mut args = vec!["sparse", "set"];
+        for dir in sparse {
+            args.push("--add");
+            args.push(dir);
+        }
+        progress::step("checking out sparse patterns", || run_jj_in(ws_path, &args))?;
+        Ok(())
+    }
+
+    fn workspace_remove(&self, repo_dir: &Path, name: &str, _ws_path: &Path) -> Result<()> {
+        run_jj_in(repo_dir, &["workspace", "forget", name])?;
+        Ok(())
+    }
+
+    fn workspace_rename(
+        &self,
+        _repo_dir: &Path,
+        old_path: &Path,
+        new_path: &Path,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        // Update stale working copy before rename (common when workspace hasn't been used recently)
+        let _ = run_jj_in(old_path, &["workspace", "update-stale"]);
+        // jj workspace rename updates VCS metadata (run inside the workspace dir);
+        // a move that keeps the name only needs the directory moved.
+        if old_name != new_name {
+            run_jj_in(old_path, &["workspace", "rename", new_name])?;
+        }
+        // Then move the directory
+        std::fs::rename(old_path, new_path)?;
+        Ok(())
+    }
+
+    fn diff_stat_vs_trunk(
+        &self,
+        repo_dir: &Path,
+        _worktree_dir: &Path,
+        ws_name: &str,
+    ) -> Result<DiffStat> {
+        let to = ws_revset(ws_name);
+        let mut stat = diff_stat(repo_dir, "trunk()", &to)?;
+        // An empty, undescribed working-copy commit isn't work of its own.
+        let ahead = format!("(trunk()..{}) ~ (empty() & description(exact:\"\"))", to);
+        stat.ahead = count_commits(repo_dir, &ahead);
+        stat.behind = count_commits(repo_dir, &format!("{}..trunk()", to));
+        Ok(stat)
+    }
+
+    fn latest_description(&self, repo_dir: &Path, _worktree_dir: &Path, ws_name: &str) -> String {
+        latest_description(repo_dir, ws_name)
+    }
+
+    fn is_merged_into_trunk(&self, repo_dir: &Path, _worktree_dir: &Path, ws_name: &str) -> bool {
+        let revset = if ws_name == "default" {
+            "trunk()..@".to_string()
+        } else {
+            format!("trunk()..{}", revset_ws(ws_name))
+        };
+        match run_jj_in(
+            repo_dir,
+            &["log", "-r", &revset, "--no-graph", "-T", "commit_id"],
+        ) {
+            Ok(out) => out.trim().is_empty(),
+            Err(_) => false,
+        }
+    }
+
+    fn working_copy_status(
+        &self,
+        _repo_dir: &Path,
+        worktree_dir: &Path,
+        _ws_name: &str,
+    ) -> Result<WorkingCopyStatus> {
+        // jj tracks new files automatically, so nothing is ever untracked;
+        // work not yet committed is whatever the working-copy commit holds.
+        // Running in the workspace snapshots it first.
+        let out = run_jj_in(
+            worktree_dir,
+            &["log", "--no-graph", "-r", "@", "-T", "empty"],
+        )?;
+        Ok(WorkingCopyStatus {
+            uncommitted: out.trim() == "false",
+            untracked: false,
+        })
+    }
+
+    fn set_trunk(&self, repo_dir: &Path, trunk: &str) -> Result<()> {
+        run_jj_in(
+            repo_dir,
+            &[
+                "config",
+                "set",
+                "--repo",
+                "revset-aliases.\"trunk()\"",
+                trunk,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn revision_description(&self, repo_dir: &Path, rev: &str) -> Option<String> {
+        let out = run_jj_in(
+            repo_dir,
+            &[
+                "log",
+                "--no-graph",
+                "-r",
+                rev,
+                "-T",
+                "description",
+                "--limit",
+                "1",
+            ],
+        )
+        .ok()?;
+        Some(out.trim().to_string()).filter(|d| !d.is_empty())
+    }
+
+    fn trunk_id(&self, repo_dir: &Path) -> Option<String> {
+        let out = run_jj_in(
+            repo_dir,
+            &["log", "--no-graph", "-r", "trunk()", "-T", "commit_id"],
+        )
+        .ok()?;
+        Some(out.trim().to_string()).filter(|id| !id.is_empty())
+    }
+
+    fn workspace_details(
+        &self,
+        repo_dir: &Path,
+        workspaces: &[(&str, &Path)],
+    ) -> Vec<WorkspaceDetails> {
+        // Counting commits and checking merges for every workspace takes one
+        // query. jj can't diff a range of commits in a template, though, so
+        // file counts still take a `jj diff` per workspace.
+        let history = run_jj_in(
+            repo_dir,
+            &[
+                "log",
+                "--no-graph",
+                "-r",
+                HISTORY_REVSET,
+                "-T",
+                history_template(),
+            ],
+        )
+        .map(|out| History::parse(&out));
+        vcs::parallel_map(workspaces, |&(name, path)| {
+            let Some((ahead, behind, merged)) = history
+                .as_ref()
+                .ok()
+                .and_then(|history| history.status(name))
+            else {
+                let (diff_stat, error) = match self.diff_stat_vs_trunk(repo_dir, path, name) {
+                    Ok(stat) => (stat, None),
+                    Err(e) => (DiffStat::default(), Some(VcsError::new("diff stat", &e))),
+                };
+                return WorkspaceDetails {
+                    diff_stat,
+                    merged: self.is_merged_into_trunk(repo_dir, path, name),
+                    error,
+                };
+            };
+            let (diff_stat, error) = match diff_stat(repo_dir, "trunk()", &ws_revset(name)) {
+                Ok(stat) => (
+                    DiffStat {
+                        ahead,
+                        behind,
+                        ..stat
+                    },
+                    None,
+                ),
+                Err(e) => (DiffStat::default(), Some(VcsError::new("diff stat", &e))),
+            };
+            WorkspaceDetails {
+                diff_stat,
+                merged,
+                error,
+            }
+        })
+    }
+
+    fn fetch(&self, repo_dir: &Path) -> Result<()> {
+        vcs::run_with_timeout(
+            Command::new("jj")
+                .args(["git", "fetch", "--quiet"])
+                .current_dir(repo_dir),
+            vcs::FETCH_TIMEOUT,
+        )
+    }
+
+    fn push(&self, repo_dir: &Path, _worktree_dir: &Path, ws_name: &str) -> Result<String> {
+        let revset = push_revset(ws_name);
+        if run_jj_in(repo_dir, &["log", "--no-graph", "-r", &revset, "-T", "x"])?.is_empty() {
+            bail!("workspace '{}' has no changes to push", ws_name);
+        }
+        let remote = run_jj_in(repo_dir, &["config", "get", "git.push"])
+            .map(|out| out.trim().to_string())
+            .unwrap_or_else(|_| "origin".to_string());
+        let bookmark = match bookmarks_at(repo_dir, &revset)?.into_iter().next() {
+            Some(bookmark) => {
+                run_jj_in(
+                    repo_dir,
+                    &["git", "push", "--remote", &remote, "--bookmark", &bookmark],
+                )?;
+                bookmark
+            }
+            None => {
+                // No bookmark yet: let jj create a `push-<change id>` one.
+                run_jj_in(
+                    repo_dir,
+                    &["git", "push", "--remote", &remote, "--change", &revset],
+                )?;
+                bookmarks_at(repo_dir, &revset)?
+                    .into_iter()
+                    .next()
+                    .context("jj git push --change created no bookmark")?
+            }
+        };
+        Ok(format!("{}/{}", remote, bookmark))
+    }
+
+    fn sync(&self, repo_dir: &Path, _worktree_dir: &Path, ws_name: &str) -> Result<String> {
+        let ws_at = ws_revset(ws_name);
+        let op = run_jj_in(
+            repo_dir,
+            &["op", "log", "--no-graph", "-n", "1", "-T", "id"],
+        )?;
+        run_jj_in(repo_dir, &["rebase", "-b", &ws_at, "-d", "trunk()"])?;
+        // jj records conflicts in the rebased commits instead of stopping,
+        // so look for them and restore the repo if there are any.
+        let conflicted = format!("(trunk()..{}) & conflicts()", ws_at);
+        let conflicts = run_jj_in(
+            repo_dir,
+            &[
+                "log",
+                "--no-graph",
+                "-r",
+                &conflicted,
+                "-T",
+                r#"change_id.short() ++ "\n""#,
+            ],
+        )?;
+        let commits: Vec<&str> = conflicts.lines().collect();
+        if !commits.is_empty() {
+            run_jj_in(repo_dir, &["op", "restore", op.trim()])?;
+            bail!(
+                "rebasing onto trunk() conflicts in {}; left the workspace as it was",
+                commits.join(", ")
+            );
+        }
+        Ok("trunk()".to_string())
+    }
+
+    fn vcs_type(&self) -> crate::vcs::VcsType {
+        crate::vcs::VcsType::Jj
+    }
+
+    fn main_workspace_name(&self) -> &'static str {
+        "default"
+    }
+
+    fn preview_log(
+        &self,
+        repo_dir: &Path,
+        _worktree_dir: &Path,
+        ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let ancestor_rev = if ws_name == "default" {
+            "ancestors(@)".to_string()
+        } else {
+            format!("ancestors({})", revset_ws(ws_name))
+        };
+        let limit_str = limit.to_string();
+        run_jj_in(
+            repo_dir,
+            &["log", "-r", &ancestor_rev, "--limit", &limit_str],
+        )
+        .unwrap_or_default()
+    }
+
+    fn preview_graph(
+        &self,
+        repo_dir: &Path,
+        _worktree_dir: &Path,
+        ws_name: &str,
+        limit: usize,
+    ) -> String {
+        let ws_rev = if ws_name == "default" {
+            "@".to_string()
+        } else {
+            revset_ws(ws_name)
+        };
+        // Include trunk() so the graph shows where the workspace forked from it.
+        let revset = format!("ancestors({}) | trunk()", ws_rev);
+        let limit_str = limit.to_string();
+        run_jj_in(
+            repo_dir,
+            &[
+                "log",
+                "-r",
+                &revset,
+                "--limit",
+                &limit_str,
+                "--color=always",
+            ],
+        )
+        .unwrap_or_default()
+    }
+
+    fn preview_diff_stat(&self, repo_dir: &Path, _worktree_dir: &Path, ws_name: &str) -> String {
+        let to = if ws_name == "default" {
+            "@".to_string()
+        } else {
+            revset_ws(ws_name)
+        };
+        run_jj_in(
+            repo_dir,
+            &["diff", "--stat", "--from", "trunk()", "--to", &to],
+        )
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_counts_each_workspace() {
+        // base - t1 (trunk)
+        //     \- w1 - w2 (my cool feature), w2 empty and undescribed
+        // feature is at base, so merged; default is at trunk.
+        let output = concat!(
+            "t1\0base\0trunk\0\0default\0\n",
+            "w2\0w1\0\0empty\0my cool feature\0\n",
+            "w1\0base\0\0\0\0\n",
+            "base\0root\0\0\0feature\nother\0\n",
+        );
+        let history = History::parse(output);
+        assert_eq!(history.trunk.as_deref(), Some("t1"));
+        assert_eq!(history.working_copies.len(), 4);
+        assert_eq!(history.status("my cool feature"), Some((1, 1, false)));
+        assert_eq!(history.status("feature"), Some((0, 1, true)));
+        assert_eq!(history.status("other"), Some((0, 1, true)));
+        assert_eq!(history.status("default"), Some((0, 0, true)));
+        assert_eq!(history.status("my"), None);
+        assert_eq!(History::parse("").status("default"), None);
+    }
+
+    #[test]
+    fn parse_workspace_info_basic() {
+        let output =
+            "default\0abc12345\0fix login bug\0main,dev\0\nfeature\0def67890\0add tests\0\0\n";
+        let result = parse_workspace_info(output).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].0, "default");
+        assert_eq!(result[0].1.change_id, "abc12345");
+        assert_eq!(result[0].1.description, "fix login bug");
+        assert_eq!(result[0].1.bookmarks, vec!["main", "dev"]);
+        assert_eq!(result[1].0, "feature");
+        assert_eq!(result[1].1.change_id, "def67890");
+        assert_eq!(result[1].1.description, "add tests");
+        assert!(result[1].1.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn stale_working_copy_error_detected() {
+        let stderr = "jj log failed: Error: The working copy is stale (not updated since operation 1a2b3c).\nHint: Run `jj workspace update-stale` to update it.";
+        assert!(is_stale_working_copy_error(stderr));
+        assert!(!is_stale_working_copy_error(
+            "jj log failed: Error: Revision `nope` doesn't exist"
+        ));
+    }
+
+    #[test]
+    fn parse_workspace_info_commit_id() {
+        let output = "default\0abc12345\0desc\0main\0abcdef0123456789\0\n";
+        let result = parse_workspace_info(output).unwrap();
+        assert_eq!(result[0].1.commit_id, "abcdef0123456789");
+        let result = parse_workspace_info("ws1\0aaa\0desc\0\0\n").unwrap();
+        assert!(result[0].1.commit_id.is_empty());
+    }
+
+    #[test]
+    fn parse_workspace_info_empty_bookmarks() {
+        let output = "ws1\0aaa\0desc\0\0\n";
+        let result = parse_workspace_info(output).unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].1.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn parse_workspace_info_empty_input() {
+        let output = "";
+        let result = parse_workspace_info(output).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn parse_workspace_info_multiline_description() {
+        let output = "default\0abc\0first line\nsecond line\0bookmark1\0\n";
+        let result = parse_workspace_info(output).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].1.description, "first line\nsecond line");
+        assert_eq!(result[0].1.bookmarks, vec!["bookmark1"]);
+    }
+
+    #[test]
+    fn parse_workspace_info_name_with_spaces() {
+        let output = "my feature\0abc12345\0some description\0main\0\n";
+        let result = parse_workspace_info(output).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0, "my feature");
+        assert_eq!(result[0].1.change_id, "abc12345");
+        assert_eq!(result[0].1.description, "some description");
+    }
+
+    #[test]
+    fn revset_ws_simple_name() {
+        assert_eq!(revset_ws("feature"), "feature@");
+        assert_eq!(revset_ws("default"), "default@");
+        assert_eq!(revset_ws("my-branch"), "my-branch@");
+        assert_eq!(revset_ws("with_underscore"), "with_underscore@");
+    }
+
+    #[test]
+    fn revset_ws_name_with_spaces() {
+        assert_eq!(revset_ws("my feature"), "`my feature`@");
+        assert_eq!(revset_ws("work in progress"), "`work in progress`@");
+    }
+
+    #[test]
+    fn push_revset_skips_empty_working_copy() {
+        assert_eq!(
+            push_revset("default"),
+            "latest((@ | @-) ~ empty() ~ ::trunk())"
+        );
+        assert_eq!(
+            push_revset("my feature"),
+            "latest((`my feature`@ | `my feature`@-) ~ empty() ~ ::trunk())"
+        );
+    }
+
+    #[test]
+    fn revset_ws_name_with_special_chars() {
+        assert_eq!(revset_ws("feat/login"), "`feat/login`@");
+        assert_eq!(revset_ws("fix.bug"), "`fix.bug`@");
+    }
+}
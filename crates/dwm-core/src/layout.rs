@@ -0,0 +1,109 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::meta::RepoMeta;
+use crate::vcs;
+
+/// `~/.dwm`, where dwm keeps its config and every repo's workspaces.
+pub fn base_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("could not determine home directory")?;
+    Ok(home.join(".dwm"))
+}
+
+/// `~/.dwm/config.json`, the user config.
+pub fn config_path() -> Result<PathBuf> {
+    Ok(base_dir()?.join("config.json"))
+}
+
+/// Return `~/.dwm/<repo_name>` — the per-repo workspace storage directory.
+pub fn repo_dir(dwm_base: &Path, repo_name: &str) -> PathBuf {
+    dwm_base.join(repo_name)
+}
+
+/// Read the original repository root path from `~/.dwm/<repo_name>/`'s
+/// [`RepoMeta`].
+pub fn main_repo_path(dwm_base: &Path, repo_name: &str) -> Result<PathBuf> {
+    let repo_dir = repo_dir(dwm_base, repo_name);
+    let meta = RepoMeta::load(&repo_dir)?
+        .with_context(|| format!("{} is not a dwm repo directory", repo_dir.display()))?;
+    Ok(meta.main_repo)
+}
+
+/// Create `~/.dwm/<repo_name>/` if it does not yet exist, and record the
+/// main repo and VCS in its [`RepoMeta`] on first use.
+pub fn ensure_repo_dir(
+    dwm_base: &Path,
+    repo_name: &str,
+    main_repo_root: &Path,
+    vcs_type: vcs::VcsType,
+) -> Result<PathBuf> {
+    let dir = repo_dir(dwm_base, repo_name);
+    fs::create_dir_all(&dir)?;
+    match RepoMeta::load(&dir)? {
+        None => RepoMeta::new(main_repo_root, vcs_type).save(&dir)?,
+        Some(mut meta) if meta.vcs.is_none() => {
+            meta.vcs = Some(vcs_type);
+            meta.save(&dir)?;
+        }
+        Some(_) => {}
+    }
+    Ok(dir)
+}
+
+/// Common dependencies threaded through workspace operations, grouped so they
+/// can be injected in tests without touching the real filesystem or VCS.
+pub struct WorkspaceDeps {
+    /// The repo's VCS, usually from [`vcs::detect`].
+    pub backend: Box<dyn vcs::VcsBackend>,
+    /// Directory the operation was asked about, inside the repo or one of
+    /// its workspaces.
+    pub cwd: PathBuf,
+    /// Usually [`base_dir`].
+    pub dwm_base: PathBuf,
+}
+
+impl WorkspaceDeps {
+    /// Dependencies for the repo containing `cwd`, with its detected VCS
+    /// and the real `~/.dwm`.
+    pub fn detect(cwd: &Path) -> Result<Self> {
+        Ok(Self {
+            backend: vcs::detect(cwd)?,
+            cwd: cwd.to_path_buf(),
+            dwm_base: base_dir()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_repo_dir_writes_markers_once() {
+        let base = tempfile::tempdir().unwrap();
+        let dir = ensure_repo_dir(
+            base.path(),
+            "api-1234",
+            Path::new("/src/api"),
+            vcs::VcsType::Git,
+        )
+        .unwrap();
+        assert_eq!(dir, repo_dir(base.path(), "api-1234"));
+        assert_eq!(
+            main_repo_path(base.path(), "api-1234").unwrap(),
+            Path::new("/src/api")
+        );
+        // What's already recorded is left alone.
+        ensure_repo_dir(
+            base.path(),
+            "api-1234",
+            Path::new("/elsewhere"),
+            vcs::VcsType::Jj,
+        )
+        .unwrap();
+        let meta = RepoMeta::load(&dir).unwrap().unwrap();
+        assert_eq!(meta.main_repo, Path::new("/src/api"));
+        assert_eq!(meta.vcs, Some(vcs::VcsType::Git));
+    }
+}
@@ -0,0 +1,43 @@
+//! The VCS backends and workspace layout behind
+//! [dwm](https://dwm.drpz.xyz), for tools that want to work with dwm's
+//! workspaces without running the `dwm` binary, like editor plugins.
+//!
+//! - [`vcs`] defines the [`VcsBackend`](vcs::VcsBackend) trait that every
+//!   operation goes through, and [`vcs::detect`] picks the backend for a
+//!   directory: [`jj`], [`git`], [`hg`], or an [`external`] executable.
+//! - [`layout`] is how dwm keeps repos and their workspaces under
+//!   `~/.dwm/`, and [`WorkspaceDeps`](layout::WorkspaceDeps) bundles a
+//!   backend with the paths an operation needs, so either can be swapped
+//!   out. [`meta`] reads and writes what dwm records about each repo and
+//!   workspace there.
+//! - [`workspace`] creates, lists, renames, and deletes workspaces, and
+//!   keeps track of the repos dwm knows about.
+//! - [`agent`] records what coding agents in each workspace are doing,
+//!   from their hook events, and installs those hooks.
+//! - [`names`] generates and validates workspace names, and [`fuzzy`]
+//!   matches them against a query.
+//! - [`forge`] holds the pull requests and CI runs dwm shows for
+//!   workspaces, cached per repo.
+//! - [`cache`] stores each workspace's computed details between runs.
+//!
+//! Nothing here writes to stdout or draws anything. Progress goes through
+//! the `log` crate as plain text, and the steps of long operations through
+//! whatever [`progress::Reporter`] is installed. Errors are `anyhow` errors;
+//! those with an [`exit::Failure`] attached say why they happened.
+
+pub mod agent;
+pub mod cache;
+pub mod exit;
+pub mod external;
+pub mod forge;
+pub mod fuzzy;
+pub mod git;
+pub mod hg;
+pub mod jj;
+pub mod layout;
+pub mod meta;
+pub mod names;
+pub mod platform;
+pub mod progress;
+pub mod vcs;
+pub mod workspace;
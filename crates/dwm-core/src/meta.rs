@@ -0,0 +1,428 @@
+use anyhow::{Context, Result, bail};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::forge::Issue;
+use crate::names::NamingScheme;
+use crate::vcs::VcsType;
+
+/// Metadata file in each repo's `~/.dwm/` entry; see [`RepoMeta`].
+pub const REPO_FILE: &str = ".repo.json";
+
+/// Directory in a repo's `~/.dwm/` entry with a metadata file for each
+/// workspace, named after it; see [`WorkspaceMeta`]. Kept outside the
+/// workspaces themselves so the VCS never sees them.
+pub const WORKSPACES_DIR: &str = ".workspaces";
+
+/// Files older versions kept a repo's metadata in, one value each. They
+/// are moved into [`REPO_FILE`] the first time it's read.
+const LEGACY_MAIN_REPO: &str = ".main-repo";
+const LEGACY_VCS_TYPE: &str = ".vcs-type";
+const LEGACY_LABEL: &str = ".label";
+
+/// File older versions kept a repo's settings in, just its naming scheme.
+/// Moved into [`REPO_FILE`] the first time that's read.
+const LEGACY_CONFIG: &str = ".config.json";
+
+/// File older versions kept every workspace's linked issue in, by workspace
+/// name. Its entries are moved into each [`WorkspaceMeta`] the first time
+/// any is read.
+const LEGACY_ISSUES: &str = ".issues.json";
+
+/// Layout version of the metadata files this dwm writes. Files with a
+/// newer one are refused rather than misread and overwritten.
+pub const VERSION: u32 = 1;
+
+/// What dwm records about a repo, in [`REPO_FILE`] in its `~/.dwm/` entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoMeta {
+    pub version: u32,
+    /// Root of the repo's main checkout.
+    pub main_repo: PathBuf,
+    /// The VCS driving the repo, from the first workspace or `dwm init`.
+    /// `None` for entries from versions that didn't record it, which were
+    /// all jj; see [`RepoMeta::vcs_type`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vcs: Option<VcsType>,
+    /// Name set with `dwm repos rename`, shown instead of the main repo's
+    /// directory name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// How `dwm new` names workspaces when no name is given, over the
+    /// `names.scheme` setting. Set with `dwm init --naming`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub naming: Option<NamingScheme>,
+}
+
+impl RepoMeta {
+    pub fn new(main_repo: &Path, vcs: VcsType) -> Self {
+        Self {
+            version: VERSION,
+            main_repo: main_repo.to_path_buf(),
+            vcs: Some(vcs),
+            label: None,
+            naming: None,
+        }
+    }
+
+    /// The metadata of the repo whose `~/.dwm/` entry is `repo_dir`, or
+    /// `None` if it isn't one. An entry still in the marker files or
+    /// `.config.json` of older versions is migrated to [`REPO_FILE`] first.
+    pub fn load(repo_dir: &Path) -> Result<Option<Self>> {
+        let path = repo_dir.join(REPO_FILE);
+        let meta = if path.is_file() {
+            read(&path)?
+        } else if repo_dir.join(LEGACY_MAIN_REPO).is_file() {
+            let meta = Self::from_legacy(repo_dir)?;
+            meta.save(repo_dir)?;
+            for file in [LEGACY_MAIN_REPO, LEGACY_VCS_TYPE, LEGACY_LABEL] {
+                // Another dwm migrating at the same time may have got there first.
+                let _ = fs::remove_file(repo_dir.join(file));
+            }
+            log::debug!("migrated {} to {}", repo_dir.display(), REPO_FILE);
+            meta
+        } else {
+            return Ok(None);
+        };
+        Self::migrate_legacy_config(repo_dir, meta).map(Some)
+    }
+
+    /// Move the naming scheme from the [`LEGACY_CONFIG`] of older versions,
+    /// if there is one, into `meta`, unless it already has one.
+    fn migrate_legacy_config(repo_dir: &Path, mut meta: Self) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct LegacyConfig {
+            #[serde(default)]
+            naming: Option<NamingScheme>,
+        }
+        let file = repo_dir.join(LEGACY_CONFIG);
+        if !file.is_file() {
+            return Ok(meta);
+        }
+        let content = fs::read_to_string(&file)
+            .with_context(|| format!("could not read {}", file.display()))?;
+        let legacy: LegacyConfig = serde_json::from_str(&content)
+            .with_context(|| format!("could not parse {}", file.display()))?;
+        if meta.naming.is_none() && legacy.naming.is_some() {
+            meta.naming = legacy.naming;
+            meta.save(repo_dir)?;
+        }
+        // Another dwm migrating at the same time may have got there first.
+        let _ = fs::remove_file(&file);
+        log::debug!("migrated {} to {}", file.display(), REPO_FILE);
+        Ok(meta)
+    }
+
+    /// Read the marker files of older versions.
+    fn from_legacy(repo_dir: &Path) -> Result<Self> {
+        let read_marker = |name: &str| -> Result<Option<String>> {
+            let file = repo_dir.join(name);
+            if !file.exists() {
+                return Ok(None);
+            }
+            let content = fs::read_to_string(&file)
+                .with_context(|| format!("could not read {}", file.display()))?;
+            Ok(Some(content.trim().to_string()).filter(|s| !s.is_empty()))
+        };
+        let main_repo = read_marker(LEGACY_MAIN_REPO)?
+            .with_context(|| format!("{} is empty", repo_dir.join(LEGACY_MAIN_REPO).display()))?;
+        let vcs = read_marker(LEGACY_VCS_TYPE)?
+            .map(|vcs| {
+                vcs.parse::<VcsType>()
+                    .with_context(|| format!("in {}", repo_dir.join(LEGACY_VCS_TYPE).display()))
+            })
+            .transpose()?;
+        Ok(Self {
+            version: VERSION,
+            main_repo: PathBuf::from(main_repo),
+            vcs,
+            label: read_marker(LEGACY_LABEL)?,
+            naming: None,
+        })
+    }
+
+    /// Write this metadata to the repo entry `repo_dir`.
+    pub fn save(&self, repo_dir: &Path) -> Result<()> {
+        write(&repo_dir.join(REPO_FILE), self)
+    }
+
+    /// [`RepoMeta::vcs`], taking unrecorded as jj.
+    pub fn vcs_type(&self) -> VcsType {
+        self.vcs.unwrap_or(VcsType::Jj)
+    }
+}
+
+/// Whether `repo_dir` is a repo's `~/.dwm/` entry, migrated or not, without
+/// reading it.
+pub fn is_repo_dir(repo_dir: &Path) -> bool {
+    repo_dir.join(REPO_FILE).is_file() || repo_dir.join(LEGACY_MAIN_REPO).is_file()
+}
+
+/// What dwm records about one workspace, in a file of its own under the
+/// repo's `~/.dwm/` entry. Workspaces from before this was kept, or made
+/// outside dwm, have none; they load as the default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceMeta {
+    pub version: u32,
+    /// When dwm created the workspace, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// The issue the workspace was made for with `dwm new --issue`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issue: Option<Issue>,
+}
+
+impl Default for WorkspaceMeta {
+    fn default() -> Self {
+        Self {
+            version: VERSION,
+            created_at: None,
+            issue: None,
+        }
+    }
+}
+
+impl WorkspaceMeta {
+    /// The metadata of workspace `name` in the repo entry `repo_dir`. Issue
+    /// links still in the file of older versions are migrated first.
+    pub fn load(repo_dir: &Path, name: &str) -> Result<Self> {
+        migrate_legacy_issues(repo_dir)?;
+        Self::load_migrated(repo_dir, name)
+    }
+
+    fn load_migrated(repo_dir: &Path, name: &str) -> Result<Self> {
+        let path = workspace_file(repo_dir, name);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        read(&path)
+    }
+
+    /// Write this metadata for workspace `name` in the repo entry
+    /// `repo_dir`.
+    pub fn save(&self, repo_dir: &Path, name: &str) -> Result<()> {
+        let path = workspace_file(repo_dir, name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write(&path, self)
+    }
+
+    /// Carry workspace `old`'s metadata over to its new name `new`.
+    pub fn rename(repo_dir: &Path, old: &str, new: &str) -> Result<()> {
+        migrate_legacy_issues(repo_dir)?;
+        let from = workspace_file(repo_dir, old);
+        if !from.exists() {
+            return Ok(());
+        }
+        fs::rename(&from, workspace_file(repo_dir, new))
+            .with_context(|| format!("could not move {}", from.display()))
+    }
+
+    /// Drop workspace `name`'s metadata, once it's deleted.
+    pub fn remove(repo_dir: &Path, name: &str) -> Result<()> {
+        migrate_legacy_issues(repo_dir)?;
+        let path = workspace_file(repo_dir, name);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("could not remove {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Move the links in the repo entry `repo_dir`'s [`LEGACY_ISSUES`] file, if
+/// it has one, into its workspaces' metadata files.
+fn migrate_legacy_issues(repo_dir: &Path) -> Result<()> {
+    let file = repo_dir.join(LEGACY_ISSUES);
+    if !file.is_file() {
+        return Ok(());
+    }
+    let content =
+        fs::read_to_string(&file).with_context(|| format!("could not read {}", file.display()))?;
+    let issues: std::collections::BTreeMap<String, Issue> = serde_json::from_str(&content)
+        .with_context(|| format!("could not parse {}", file.display()))?;
+    for (name, issue) in issues {
+        let mut meta = WorkspaceMeta::load_migrated(repo_dir, &name)?;
+        meta.issue = Some(issue);
+        meta.save(repo_dir, &name)?;
+    }
+    // Another dwm migrating at the same time may have got there first.
+    let _ = fs::remove_file(&file);
+    log::debug!("migrated {} to {}", file.display(), WORKSPACES_DIR);
+    Ok(())
+}
+
+fn workspace_file(repo_dir: &Path, name: &str) -> PathBuf {
+    repo_dir.join(WORKSPACES_DIR).join(format!("{}.json", name))
+}
+
+/// Parse the metadata file at `path`, refusing one from a newer dwm.
+fn read<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    #[derive(Deserialize)]
+    struct Versioned {
+        version: u32,
+    }
+    let content =
+        fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+    let Versioned { version } = serde_json::from_str(&content)
+        .with_context(|| format!("could not parse {}", path.display()))?;
+    if version > VERSION {
+        bail!(
+            "{} was written by a newer dwm (layout version {}); upgrade dwm to use it",
+            path.display(),
+            version
+        );
+    }
+    serde_json::from_str(&content).with_context(|| format!("could not parse {}", path.display()))
+}
+
+/// Write `value` to `path`, replacing it atomically so readers never see
+/// half a file.
+fn write<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)? + "\n";
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, json).with_context(|| format!("could not write {}", path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| format!("could not write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_markers_migrate_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".main-repo"), "/src/api\n").unwrap();
+        fs::write(dir.path().join(".vcs-type"), "git").unwrap();
+        fs::write(dir.path().join(".label"), "backend\n").unwrap();
+        assert!(is_repo_dir(dir.path()));
+
+        let meta = RepoMeta::load(dir.path()).unwrap().unwrap();
+        assert_eq!(meta.main_repo, Path::new("/src/api"));
+        assert_eq!(meta.vcs, Some(VcsType::Git));
+        assert_eq!(meta.label.as_deref(), Some("backend"));
+        for marker in [".main-repo", ".vcs-type", ".label"] {
+            assert!(!dir.path().join(marker).exists(), "{}", marker);
+        }
+        assert!(is_repo_dir(dir.path()));
+        assert_eq!(RepoMeta::load(dir.path()).unwrap(), Some(meta));
+    }
+
+    #[test]
+    fn entries_without_a_vcs_are_jj() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".main-repo"), "/src/api").unwrap();
+        let meta = RepoMeta::load(dir.path()).unwrap().unwrap();
+        assert_eq!(meta.vcs, None);
+        assert_eq!(meta.vcs_type(), VcsType::Jj);
+        assert!(
+            !fs::read_to_string(dir.path().join(REPO_FILE))
+                .unwrap()
+                .contains("vcs")
+        );
+    }
+
+    #[test]
+    fn other_directories_are_not_repos() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(!is_repo_dir(dir.path()));
+        assert_eq!(RepoMeta::load(dir.path()).unwrap(), None);
+        fs::write(dir.path().join(".vcs-type"), "git").unwrap();
+        assert_eq!(RepoMeta::load(dir.path()).unwrap(), None);
+        assert!(dir.path().join(".vcs-type").exists());
+    }
+
+    #[test]
+    fn newer_layouts_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(REPO_FILE),
+            r#"{"version": 99, "main_repo": "/src/api", "owner": "me"}"#,
+        )
+        .unwrap();
+        let err = RepoMeta::load(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("newer dwm"), "{}", err);
+    }
+
+    #[test]
+    fn legacy_issue_links_migrate_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join(".issues.json"),
+            r#"{"42-fix-login": {"number": 42, "title": "Fix login", "url": "https://github.com/me/api/issues/42"}}"#,
+        )
+        .unwrap();
+        let created = WorkspaceMeta {
+            created_at: Some(1_700_000_000),
+            ..Default::default()
+        };
+        created.save(dir.path(), "42-fix-login").unwrap();
+
+        let meta = WorkspaceMeta::load(dir.path(), "other").unwrap();
+        assert_eq!(meta, WorkspaceMeta::default());
+        assert!(!dir.path().join(".issues.json").exists());
+        let linked = WorkspaceMeta::load(dir.path(), "42-fix-login").unwrap();
+        assert_eq!(linked.created_at, created.created_at);
+        assert_eq!(
+            linked.issue,
+            Some(Issue {
+                number: 42,
+                title: "Fix login".to_string(),
+                url: "https://github.com/me/api/issues/42".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn workspace_meta_follows_renames_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            WorkspaceMeta::load(dir.path(), "feat").unwrap(),
+            WorkspaceMeta::default()
+        );
+        let meta = WorkspaceMeta {
+            created_at: Some(1_700_000_000),
+            ..Default::default()
+        };
+        meta.save(dir.path(), "feat").unwrap();
+        assert_eq!(WorkspaceMeta::load(dir.path(), "feat").unwrap(), meta);
+
+        WorkspaceMeta::rename(dir.path(), "feat", "feat-login").unwrap();
+        assert_eq!(
+            WorkspaceMeta::load(dir.path(), "feat").unwrap(),
+            WorkspaceMeta::default()
+        );
+        assert_eq!(WorkspaceMeta::load(dir.path(), "feat-login").unwrap(), meta);
+
+        WorkspaceMeta::remove(dir.path(), "feat-login").unwrap();
+        WorkspaceMeta::remove(dir.path(), "feat-login").unwrap();
+        assert_eq!(
+            WorkspaceMeta::load(dir.path(), "feat-login").unwrap(),
+            WorkspaceMeta::default()
+        );
+    }
+
+    #[test]
+    fn legacy_repo_config_moves_into_repo_meta() {
+        let dir = tempfile::tempdir().unwrap();
+        RepoMeta::new(Path::new("/src/api"), VcsType::Git)
+            .save(dir.path())
+            .unwrap();
+        fs::write(dir.path().join(LEGACY_CONFIG), r#"{"naming": "numbered"}"#).unwrap();
+
+        let meta = RepoMeta::load(dir.path()).unwrap().unwrap();
+        assert_eq!(meta.naming, Some(NamingScheme::Numbered));
+        assert!(!dir.path().join(LEGACY_CONFIG).exists());
+        assert_eq!(RepoMeta::load(dir.path()).unwrap().unwrap(), meta);
+        assert!(
+            fs::read_to_string(dir.path().join(REPO_FILE))
+                .unwrap()
+                .contains("\"naming\": \"numbered\"")
+        );
+    }
+}
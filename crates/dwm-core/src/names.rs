@@ -0,0 +1,520 @@
+use anyhow::{Result, bail};
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::vcs::VcsType;
+
+/// How `dwm new` names a workspace when no name is given.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum NamingScheme {
+    /// A random `adjective-noun` name, like `calm-otter`.
+    #[default]
+    Random,
+    /// `ws-1`, `ws-2`, ..., the lowest number not in use.
+    Numbered,
+    /// A random name after the year and month, like `2024-06-calm-otter`.
+    Dated,
+}
+
+/// Settings for generated workspace names, from the `names` setting.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NameConfig {
+    /// Scheme for repos that `dwm init --naming` wasn't given one for.
+    /// Unset means random.
+    pub scheme: Option<NamingScheme>,
+    /// Put before every generated name, like `"danny-"`. Names are
+    /// directories as well as branches, so a `/` becomes `-`.
+    pub prefix: Option<String>,
+    /// Words for the first half of random names. Empty means the built-in
+    /// ones.
+    pub adjectives: Vec<String>,
+    /// Words for the second half of random names. Empty means the built-in
+    /// ones.
+    pub nouns: Vec<String>,
+}
+
+impl NameConfig {
+    /// Check that generated names would be usable workspace names.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(first @ ('.' | '-')) = self.prefix.as_deref().and_then(|p| p.chars().next()) {
+            bail!("name prefix cannot start with '{}'", first);
+        }
+        let words = self.adjectives.iter().chain(&self.nouns);
+        if let Some(word) = words
+            .into_iter()
+            .find(|w| w.trim().is_empty() || w.contains('/'))
+        {
+            bail!("'{}' can't be part of a workspace name", word);
+        }
+        Ok(())
+    }
+
+    /// A random `adjective-noun` name from the configured words.
+    fn random_name(&self) -> String {
+        let mut rng = rand::rng();
+        let adj = match self.adjectives.choose(&mut rng) {
+            Some(word) => word.as_str(),
+            None => ADJECTIVES.choose(&mut rng).unwrap(),
+        };
+        let noun = match self.nouns.choose(&mut rng) {
+            Some(word) => word.as_str(),
+            None => NOUNS.choose(&mut rng).unwrap(),
+        };
+        format!("{adj}-{noun}")
+    }
+}
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "bold", "calm", "dark", "eager", "fair", "glad", "hazy", "icy", "jade", "keen",
+    "lush", "mild", "neat", "opal", "pale", "quick", "rosy", "soft", "tidy", "vast", "warm",
+    "zany", "aqua", "blue", "crisp", "dusty", "ember", "fresh", "gold", "happy", "ivory", "jolly",
+    "kind", "lazy", "merry", "noble", "olive", "plum", "quiet", "rapid", "sage", "tall", "ultra",
+    "vivid", "wise", "young", "zen", "agile", "brave",
+];
+
+const NOUNS: &[&str] = &[
+    "ant", "bat", "cat", "dog", "elk", "fox", "gnu", "hawk", "ibis", "jay", "koi", "lynx", "mole",
+    "newt", "owl", "puma", "quail", "ram", "seal", "toad", "vole", "wolf", "yak", "crab", "dart",
+    "eel", "frog", "goat", "hare", "inca", "koala", "lamb", "mink", "narwhal", "orca", "panda",
+    "raven", "swan", "tiger", "urchin", "viper", "wren", "zebra", "bear", "crow", "dove", "egret",
+    "finch", "gull", "heron",
+];
+
+impl std::fmt::Display for NamingScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamingScheme::Random => write!(f, "random"),
+            NamingScheme::Numbered => write!(f, "numbered"),
+            NamingScheme::Dated => write!(f, "dated"),
+        }
+    }
+}
+
+/// Random names tried before giving up on finding a free one and
+/// numbering it instead, for word lists small enough to run out.
+const RANDOM_TRIES: usize = 100;
+
+/// Generate a name following `scheme`, with the prefix and words from
+/// `config`, that does not already exist as a subdirectory of `dir`.
+pub fn generate_unique(dir: &Path, scheme: NamingScheme, config: &NameConfig) -> String {
+    let prefix = config
+        .prefix
+        .as_deref()
+        .unwrap_or_default()
+        .replace('/', "-");
+    let free = |name: &String| !dir.join(name).exists();
+    if scheme == NamingScheme::Numbered {
+        return (1..).map(|n| format!("{prefix}ws-{n}")).find(free).unwrap();
+    }
+    let generate = || {
+        let name = config.random_name();
+        match scheme {
+            NamingScheme::Dated => {
+                let (year, month) = year_month(SystemTime::now());
+                format!("{prefix}{year}-{month:02}-{name}")
+            }
+            _ => format!("{prefix}{name}"),
+        }
+    };
+    if let Some(name) = std::iter::repeat_with(generate)
+        .take(RANDOM_TRIES)
+        .find(free)
+    {
+        return name;
+    }
+    numbered_if_taken(dir, generate())
+}
+
+/// `name` if it isn't a subdirectory of `dir` yet, else the first of
+/// `name-2`, `name-3`, ... that isn't.
+pub fn numbered_if_taken(dir: &Path, name: String) -> String {
+    if !dir.join(&name).exists() {
+        return name;
+    }
+    (2..)
+        .map(|n| format!("{name}-{n}"))
+        .find(|name| !dir.join(name).exists())
+        .unwrap()
+}
+
+/// The year and month (in UTC) of `time`.
+fn year_month(time: SystemTime) -> (i64, u32) {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86400) as i64;
+    // Howard Hinnant's `civil_from_days`, counting in 400-year eras that
+    // start on 1 March so leap days fall at the end.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month)
+}
+
+/// Longest workspace name allowed, in bytes.
+const NAME_MAX: usize = 100;
+
+/// Characters no workspace name can contain: path separators, and the rest
+/// of those Windows doesn't allow in file names.
+const FORBIDDEN_CHARS: &[char] = &['/', '\\', '<', '>', ':', '"', '|', '?', '*'];
+
+/// Characters git doesn't allow in branch names, beyond [`FORBIDDEN_CHARS`].
+const GIT_FORBIDDEN_CHARS: &[char] = &[' ', '~', '^', '['];
+
+/// Device names Windows reserves, which can't be file names there even with
+/// an extension.
+const WINDOWS_RESERVED: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Check that `name` can name a workspace of a `vcs_type` repo: as a
+/// directory on any platform, and as the branch or bookmark the VCS makes
+/// for it.
+pub fn validate(name: &str, vcs_type: VcsType) -> Result<()> {
+    if name.is_empty() {
+        bail!("workspace name cannot be empty");
+    }
+    if let Some(first @ ('.' | '-')) = name.chars().next() {
+        bail!("workspace name cannot start with '{}'", first);
+    }
+    if name.len() > NAME_MAX {
+        bail!(
+            "workspace name is {} bytes long; the most allowed is {}",
+            name.len(),
+            NAME_MAX
+        );
+    }
+    if let Some(c) = name.chars().find(|c| c.is_control()) {
+        bail!(
+            "workspace name cannot contain control characters like {:?}",
+            c
+        );
+    }
+    if let Some(c) = name.chars().find(|c| FORBIDDEN_CHARS.contains(c)) {
+        bail!("workspace name cannot contain '{}'", c);
+    }
+    if let Some(last @ ('.' | ' ')) = name.chars().last() {
+        bail!("workspace name cannot end with '{}'", last);
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if WINDOWS_RESERVED
+        .iter()
+        .any(|r| stem.eq_ignore_ascii_case(r))
+    {
+        bail!("'{}' is a reserved file name on Windows", stem);
+    }
+    match vcs_type {
+        VcsType::Git => {
+            if name.contains(' ') {
+                bail!("git branch names cannot contain spaces");
+            }
+            if let Some(c) = name.chars().find(|c| GIT_FORBIDDEN_CHARS.contains(c)) {
+                bail!("git branch names cannot contain '{}'", c);
+            }
+            if let Some(seq) = ["..", "@{"].into_iter().find(|seq| name.contains(seq)) {
+                bail!("git branch names cannot contain '{}'", seq);
+            }
+            if name == "@" {
+                bail!("'@' cannot be a git branch name");
+            }
+            if name.ends_with(".lock") {
+                bail!("git branch names cannot end with '.lock'");
+            }
+        }
+        VcsType::Hg if name.chars().all(|c| c.is_ascii_digit()) => {
+            bail!("Mercurial bookmarks cannot be a number");
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Longest name [`from_issue`] and [`from_description`] make, in bytes,
+/// before cutting off the text at a word.
+const SLUG_MAX: usize = 40;
+
+/// A workspace (and branch) name for issue `number` titled `title`, like
+/// `1234-fix-login-timeout`: its number, then the title's words in lower
+/// case, stopping before the name grows past `SLUG_MAX`.
+pub fn from_issue(number: u64, title: &str) -> String {
+    append_slug(number.to_string(), title)
+}
+
+/// A workspace (and branch) name from a change's `description`, like
+/// `fix-login-timeout` for "Fix login timeout", made from the words of its
+/// first line. `None` if that has no words.
+pub fn from_description(description: &str) -> Option<String> {
+    let first_line = description.lines().find(|line| !line.trim().is_empty())?;
+    Some(append_slug(String::new(), first_line)).filter(|name| !name.is_empty())
+}
+
+/// `name` followed by the words of `text` in lower case, each after a `-`
+/// (the first only if `name` isn't empty), stopping before the name grows
+/// past [`SLUG_MAX`].
+fn append_slug(mut name: String, text: &str) -> String {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty());
+    for word in words {
+        let dash = usize::from(!name.is_empty());
+        if name.len() + dash + word.len() > SLUG_MAX {
+            break;
+        }
+        if dash == 1 {
+            name.push('-');
+        }
+        name.push_str(&word.to_lowercase());
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_has_adjective_dash_noun_format() {
+        let name = NameConfig::default().random_name();
+        let parts: Vec<&str> = name.splitn(2, '-').collect();
+        assert_eq!(parts.len(), 2);
+        assert!(ADJECTIVES.contains(&parts[0]));
+        assert!(NOUNS.contains(&parts[1]));
+    }
+
+    #[test]
+    fn numbered_names_fill_the_lowest_gap() {
+        let dir = tempfile::tempdir().unwrap();
+        let none = NameConfig::default();
+        assert_eq!(
+            generate_unique(dir.path(), NamingScheme::Numbered, &none),
+            "ws-1"
+        );
+        std::fs::create_dir(dir.path().join("ws-1")).unwrap();
+        std::fs::create_dir(dir.path().join("ws-3")).unwrap();
+        assert_eq!(
+            generate_unique(dir.path(), NamingScheme::Numbered, &none),
+            "ws-2"
+        );
+    }
+
+    #[test]
+    fn generate_unique_avoids_collisions() {
+        let dir = tempfile::tempdir().unwrap();
+        // Create a bunch of names and ensure they all get unique ones
+        let mut names = std::collections::HashSet::new();
+        for _ in 0..20 {
+            let name = generate_unique(dir.path(), NamingScheme::Random, &NameConfig::default());
+            // Create a directory with that name so it becomes "taken"
+            std::fs::create_dir(dir.path().join(&name)).unwrap();
+            assert!(names.insert(name));
+        }
+    }
+
+    #[test]
+    fn names_use_configured_prefix_and_words() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = NameConfig {
+            prefix: Some("danny/".to_string()),
+            adjectives: vec!["red".to_string()],
+            nouns: vec!["kite".to_string()],
+            ..Default::default()
+        };
+        let random = |dir: &Path| generate_unique(dir, NamingScheme::Random, &config);
+        assert_eq!(random(dir.path()), "danny-red-kite");
+        // Once the words run out, the name gets a number.
+        std::fs::create_dir(dir.path().join("danny-red-kite")).unwrap();
+        assert_eq!(random(dir.path()), "danny-red-kite-2");
+        assert_eq!(
+            generate_unique(dir.path(), NamingScheme::Numbered, &config),
+            "danny-ws-1"
+        );
+    }
+
+    #[test]
+    fn dated_names_start_with_year_and_month() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = NameConfig {
+            nouns: vec!["foo".to_string()],
+            ..Default::default()
+        };
+        let name = generate_unique(dir.path(), NamingScheme::Dated, &config);
+        let (year, month) = year_month(SystemTime::now());
+        assert!(name.starts_with(&format!("{year}-{month:02}-")), "{name}");
+        assert!(name.ends_with("-foo"), "{name}");
+    }
+
+    #[test]
+    fn year_month_is_the_utc_calendar_month() {
+        let at = |secs| year_month(UNIX_EPOCH + std::time::Duration::from_secs(secs));
+        assert_eq!(at(0), (1970, 1));
+        // 2024-02-29T12:00:00Z, a leap day.
+        assert_eq!(at(1_709_208_000), (2024, 2));
+        // 2024-06-30T23:59:59Z, then a second later.
+        assert_eq!(at(1_719_791_999), (2024, 6));
+        assert_eq!(at(1_719_792_000), (2024, 7));
+        // 2000-12-31T00:00:00Z.
+        assert_eq!(at(978_220_800), (2000, 12));
+    }
+
+    #[test]
+    fn name_config_rejects_unusable_words() {
+        assert!(NameConfig::default().validate().is_ok());
+        let hidden = NameConfig {
+            prefix: Some(".me-".to_string()),
+            ..Default::default()
+        };
+        assert!(hidden.validate().is_err());
+        let slash = NameConfig {
+            nouns: vec!["a/b".to_string()],
+            ..Default::default()
+        };
+        assert!(slash.validate().is_err());
+    }
+
+    #[test]
+    fn validate_accepts_usual_names() {
+        for name in [
+            "calm-otter",
+            "1234-fix-login-timeout",
+            "ws-1",
+            "v2.0-prep",
+            "ünïcode",
+        ] {
+            for vcs in [VcsType::Git, VcsType::Jj, VcsType::Hg] {
+                assert!(validate(name, vcs).is_ok(), "{} ({})", name, vcs);
+            }
+        }
+        // jj workspaces have no branch, so spaces are fine there.
+        assert!(validate("my cool feature", VcsType::Jj).is_ok());
+        assert!(validate(&"a".repeat(NAME_MAX), VcsType::Git).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_unusable_names() {
+        let error = |name: &str, vcs| validate(name, vcs).unwrap_err().to_string();
+        assert_eq!(error("", VcsType::Jj), "workspace name cannot be empty");
+        assert_eq!(
+            error(".hidden", VcsType::Jj),
+            "workspace name cannot start with '.'"
+        );
+        assert_eq!(
+            error("-f", VcsType::Jj),
+            "workspace name cannot start with '-'"
+        );
+        assert_eq!(
+            error("a/b", VcsType::Jj),
+            "workspace name cannot contain '/'"
+        );
+        assert_eq!(
+            error("a\\b", VcsType::Jj),
+            "workspace name cannot contain '\\'"
+        );
+        assert!(error("tab\there", VcsType::Jj).contains("control characters"));
+        assert!(error(&"a".repeat(NAME_MAX + 1), VcsType::Jj).contains("101 bytes"));
+        assert_eq!(
+            error("CON", VcsType::Jj),
+            "'CON' is a reserved file name on Windows"
+        );
+        assert!(error("lpt1.txt", VcsType::Jj).contains("reserved"));
+        assert!(validate("console", VcsType::Jj).is_ok());
+        assert_eq!(
+            error("trailing.", VcsType::Jj),
+            "workspace name cannot end with '.'"
+        );
+        assert_eq!(
+            error("fix: login", VcsType::Jj),
+            "workspace name cannot contain ':'"
+        );
+    }
+
+    #[test]
+    fn validate_applies_branch_rules_per_vcs() {
+        let error = |name: &str, vcs| validate(name, vcs).unwrap_err().to_string();
+        assert_eq!(
+            error("my feature", VcsType::Git),
+            "git branch names cannot contain spaces"
+        );
+        assert_eq!(
+            error("HEAD~1", VcsType::Git),
+            "git branch names cannot contain '~'"
+        );
+        assert_eq!(
+            error("a..b", VcsType::Git),
+            "git branch names cannot contain '..'"
+        );
+        assert_eq!(
+            error("x@{1", VcsType::Git),
+            "git branch names cannot contain '@{'"
+        );
+        assert_eq!(error("@", VcsType::Git), "'@' cannot be a git branch name");
+        assert_eq!(
+            error("ws.lock", VcsType::Git),
+            "git branch names cannot end with '.lock'"
+        );
+        assert!(validate("a..b", VcsType::Jj).is_ok());
+        assert_eq!(
+            error("1234", VcsType::Hg),
+            "Mercurial bookmarks cannot be a number"
+        );
+        assert!(validate("1234", VcsType::Git).is_ok());
+    }
+
+    #[test]
+    fn issue_names_slug_the_title() {
+        assert_eq!(
+            from_issue(1234, "Fix login timeout"),
+            "1234-fix-login-timeout"
+        );
+        assert_eq!(
+            from_issue(7, "  Crash in `dwm new` (macOS)!! "),
+            "7-crash-in-dwm-new-macos"
+        );
+        assert_eq!(from_issue(12, "Ünïcode only ✨"), "12-ünïcode-only");
+        assert_eq!(from_issue(3, ""), "3");
+        let long = from_issue(
+            99,
+            "Workspace picker freezes when a repository has thousands of branches",
+        );
+        assert_eq!(long, "99-workspace-picker-freezes-when-a");
+        assert!(long.len() <= SLUG_MAX);
+    }
+
+    #[test]
+    fn description_names_slug_the_first_line() {
+        assert_eq!(
+            from_description("Fix login timeout\n\nThe session expired early.").as_deref(),
+            Some("fix-login-timeout")
+        );
+        assert_eq!(
+            from_description("\n  feat(auth): Add SSO  ").as_deref(),
+            Some("feat-auth-add-sso")
+        );
+        let long = from_description(
+            "Make the workspace picker stay responsive with thousands of branches",
+        )
+        .unwrap();
+        assert_eq!(long, "make-the-workspace-picker-stay");
+        assert_eq!(from_description(""), None);
+        assert_eq!(from_description("…\n"), None);
+    }
+
+    #[test]
+    fn taken_names_get_a_number() {
+        let dir = tempfile::tempdir().unwrap();
+        let name = |base: &str| numbered_if_taken(dir.path(), base.to_string());
+        assert_eq!(name("fix-login"), "fix-login");
+        std::fs::create_dir(dir.path().join("fix-login")).unwrap();
+        std::fs::create_dir(dir.path().join("fix-login-2")).unwrap();
+        assert_eq!(name("fix-login"), "fix-login-3");
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// The console device prompts read answers from, so they work even with
+/// stdin redirected.
+#[cfg(windows)]
+const TTY: &str = "CONIN$";
+#[cfg(not(windows))]
+const TTY: &str = "/dev/tty";
+
+/// Read a line typed at the terminal, or `None` if there isn't one.
+pub fn read_tty_line() -> io::Result<Option<String>> {
+    let Ok(tty) = File::open(TTY) else {
+        return Ok(None);
+    };
+    let mut line = String::new();
+    BufReader::new(tty).read_line(&mut line)?;
+    Ok(Some(line))
+}
+
+/// `path` for display, with the home directory (`USERPROFILE` on Windows)
+/// shortened to `~`.
+pub fn tilde_path(path: &Path) -> String {
+    if let Some(home) = dirs::home_dir()
+        && let Ok(rest) = path.strip_prefix(&home)
+    {
+        return Path::new("~").join(rest).display().to_string();
+    }
+    path.display().to_string()
+}
+
+/// Drop the `\\?\` prefix `canonicalize` puts on Windows paths, which
+/// `Set-Location` and most tools reject: `\\?\C:\src` becomes `C:\src` and
+/// `\\?\UNC\server\share` becomes `\\server\share`. Other paths pass through.
+pub fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let Some(s) = path.to_str() else {
+        return path;
+    };
+    if let Some(share) = s.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", share));
+    }
+    match s.strip_prefix(r"\\?\") {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => PathBuf::from(rest),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_verbatim_keeps_plain_paths() {
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\C:\Users\dan\src")),
+            PathBuf::from(r"C:\Users\dan\src")
+        );
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\UNC\server\share\repo")),
+            PathBuf::from(r"\\server\share\repo")
+        );
+        // Verbatim paths without a drive letter have no plain spelling.
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\Volume{1234}\repo")),
+            PathBuf::from(r"\\?\Volume{1234}\repo")
+        );
+        assert_eq!(
+            strip_verbatim(PathBuf::from("/home/dan/src")),
+            PathBuf::from("/home/dan/src")
+        );
+    }
+
+    #[test]
+    fn tilde_path_shortens_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            tilde_path(&home.join(".zshrc")),
+            Path::new("~").join(".zshrc").display().to_string()
+        );
+        assert_eq!(tilde_path(Path::new("/elsewhere")), "/elsewhere");
+    }
+}
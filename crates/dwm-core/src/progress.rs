@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Shows the steps of long operations, such as a backend's checkout, to
+/// whoever is waiting on them. dwm draws a spinner on stderr; an embedding
+/// tool can show them its own way, or install nothing to run them silently.
+pub trait Reporter: Sync {
+    /// A step called `label` has started.
+    fn start(&self, label: &str);
+
+    /// The step that last started has ended, successfully if `ok`.
+    fn finish(&self, label: &str, ok: bool);
+}
+
+static REPORTER: OnceLock<&'static dyn Reporter> = OnceLock::new();
+
+/// Set while a step runs, so a step inside another (a backend's, within
+/// a command's) just runs rather than being reported on its own.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Send every later step to `reporter`. Only the first call has any
+/// effect, as with `log::set_logger`.
+pub fn set_reporter(reporter: &'static dyn Reporter) {
+    let _ = REPORTER.set(reporter);
+}
+
+/// Run `f` as one step of a long operation, such as a backend's checkout,
+/// telling the installed [`Reporter`] when it starts and ends.
+pub fn step<T>(label: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    let Some(reporter) = REPORTER.get() else {
+        return f();
+    };
+    if ACTIVE.swap(true, Ordering::SeqCst) {
+        return f();
+    }
+    reporter.start(label);
+    let result = f();
+    reporter.finish(label, result.is_ok());
+    ACTIVE.store(false, Ordering::SeqCst);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_pass_results_through() {
+        assert_eq!(step("adding", || Ok(3)).unwrap(), 3);
+        let err = step("adding", || -> Result<()> { anyhow::bail!("no space") }).unwrap_err();
+        assert_eq!(err.to_string(), "no space");
+    }
+}
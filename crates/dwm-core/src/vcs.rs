@@ -0,0 +1,1008 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::exit::Failure;
+use crate::meta::RepoMeta;
+
+/// How long a fetch from the remote may take before it's abandoned.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VcsType {
+    Jj,
+    Git,
+    Hg,
+    /// A user-provided executable; see [`crate::external`].
+    External,
+}
+
+impl VcsType {
+    pub fn to_backend(self) -> Box<dyn VcsBackend> {
+        match self {
+            VcsType::Jj => Box::new(crate::jj::JjBackend),
+            VcsType::Git => Box::new(crate::git::GitBackend),
+            VcsType::Hg => Box::new(crate::hg::HgBackend),
+            VcsType::External => Box::new(crate::external::ExternalBackend::from_config()),
+        }
+    }
+}
+
+impl fmt::Display for VcsType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VcsType::Jj => write!(f, "jj"),
+            VcsType::Git => write!(f, "git"),
+            VcsType::Hg => write!(f, "hg"),
+            VcsType::External => write!(f, "external"),
+        }
+    }
+}
+
+impl FromStr for VcsType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "jj" => Ok(VcsType::Jj),
+            "git" => Ok(VcsType::Git),
+            "hg" => Ok(VcsType::Hg),
+            "external" => Ok(VcsType::External),
+            other => bail!("unknown VCS type '{}'", other),
+        }
+    }
+}
+
+/// VCS-level metadata for a single workspace/worktree as reported by the
+/// underlying VCS (jj, git, or hg).
+#[derive(Debug, Default, Clone)]
+pub struct WorkspaceInfo {
+    /// Short change/commit id (8 hex chars).
+    pub change_id: String,
+    /// Commit message of the workspace's current revision.
+    pub description: String,
+    /// Branch or bookmark names pointing at this revision.
+    pub bookmarks: Vec<String>,
+    /// Full id of the commit that the workspace's diff stat is computed
+    /// from, used to key cached details. Empty if the backend can't name one.
+    pub commit_id: String,
+}
+
+/// Parsed summary line from `jj diff --stat`, `git diff --stat`, or
+/// `hg diff --stat`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DiffStat {
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+    /// Commits in the workspace that aren't in trunk.
+    pub ahead: u32,
+    /// Commits in trunk that aren't in the workspace.
+    pub behind: u32,
+}
+
+impl DiffStat {
+    /// Commits ahead of and behind trunk, like `↑2 ↓40`, or `None` if the
+    /// workspace is level with trunk.
+    pub fn commit_counts(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.ahead > 0 {
+            parts.push(format!("↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            parts.push(format!("↓{}", self.behind));
+        }
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+}
+
+/// Work in a workspace that hasn't been committed, from
+/// [`VcsBackend::working_copy_status`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorkingCopyStatus {
+    /// Modified, added, or deleted tracked files.
+    pub uncommitted: bool,
+    /// Files the VCS isn't tracking and isn't ignoring.
+    pub untracked: bool,
+}
+
+impl WorkingCopyStatus {
+    pub fn is_dirty(self) -> bool {
+        self.uncommitted || self.untracked
+    }
+
+    /// What there is to lose, e.g. `"uncommitted changes"`, or `None` if
+    /// the workspace is clean.
+    pub fn describe(self) -> Option<&'static str> {
+        match (self.uncommitted, self.untracked) {
+            (true, true) => Some("uncommitted changes and untracked files"),
+            (true, false) => Some("uncommitted changes"),
+            (false, true) => Some("untracked files"),
+            (false, false) => None,
+        }
+    }
+}
+
+/// A backend call that failed while gathering a workspace's details, kept on
+/// the entry so it's shown instead of passing for a clean workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct VcsError {
+    /// What dwm was asking the backend for, e.g. `"workspace list"`.
+    pub operation: String,
+    pub message: String,
+}
+
+impl VcsError {
+    pub fn new(operation: &'static str, err: &anyhow::Error) -> Self {
+        Self {
+            operation: operation.to_string(),
+            message: format!("{:#}", err),
+        }
+    }
+}
+
+impl fmt::Display for VcsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.operation, self.message)
+    }
+}
+
+/// Diff stat and merge status of one workspace, from
+/// [`VcsBackend::workspace_details`].
+#[derive(Debug, Default)]
+pub struct WorkspaceDetails {
+    pub diff_stat: DiffStat,
+    /// Whether the workspace's changes are all in trunk already.
+    pub merged: bool,
+    /// Why the diff stat is missing, if the backend failed to compute it.
+    pub error: Option<VcsError>,
+}
+
+/// Resolve symlinks in `path`, or return it unchanged if it can't be
+/// resolved. Repo roots go through this so that a repo reached through a
+/// symlink maps to the same `~/.dwm/` entry as its real path. On Windows the
+/// result is kept in its plain `C:\...` form.
+pub fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize()
+        .map(crate::platform::strip_verbatim)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Compute a short FNV-1a hex hash of a path string, used to disambiguate
+/// repos that share the same directory basename.
+fn hash_path(path: &Path) -> String {
+    short_hash(&path.to_string_lossy())
+}
+
+/// An 8-hex-digit FNV-1a hash of `s`.
+pub fn short_hash(s: &str) -> String {
+    let mut h: u32 = 2166136261; // FNV-1a offset basis
+    for b in s.bytes() {
+        h ^= b as u32;
+        h = h.wrapping_mul(16777619); // FNV prime
+    }
+    format!("{:08x}", h)
+}
+
+/// Build the `~/.dwm/` sub-directory name for a repo.
+///
+/// The name is `<basename>-<8-char-hash>` so that two repos with the same
+/// directory name but different paths get distinct dwm directories.
+pub fn repo_dir_name(root: &Path) -> String {
+    let root = canonical(root);
+    let name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{}-{}", name, hash_path(&root))
+}
+
+/// Abstraction over jj, git, and hg that workspace operations are delegated to.
+pub trait VcsBackend: Send + Sync {
+    /// Return the repository root given any directory inside the repo.
+    fn root_from(&self, dir: &Path) -> Result<PathBuf>;
+
+    /// Return the dwm directory name for the repo that contains `dir`.
+    fn repo_name_from(&self, dir: &Path) -> Result<String> {
+        let root = self.root_from(dir)?;
+        Ok(repo_dir_name(&root))
+    }
+
+    /// List all workspaces/worktrees known to the VCS, returning `(name, info)` pairs.
+    fn workspace_list(&self, repo_dir: &Path) -> Result<Vec<(String, WorkspaceInfo)>>;
+    /// Create a new workspace/worktree at `ws_path` with the given `name`.
+    /// `at` optionally specifies the starting revision.
+    fn workspace_add(
+        &self,
+        repo_dir: &Path,
+        ws_path: &Path,
+        name: &str,
+        at: Option<&str>,
+    ) -> Result<()>;
+    /// Like [`workspace_add`](Self::workspace_add), but check out only the
+    /// directories in `sparse` (relative to the repo root), without
+    /// materializing the rest first. Unsupported by default.
+    fn workspace_add_sparse(
+        &self,
+        _repo_dir: &Path,
+        _ws_path: &Path,
+        _name: &str,
+        _at: Option<&str>,
+        _sparse: &[String],
+    ) -> Result<()> {
+        bail!(
+            "sparse checkouts aren't supported for {} repos",
+            self.vcs_type()
+        )
+    }
+    /// Remove the workspace/worktree from VCS tracking and delete its directory.
+    fn workspace_remove(&self, repo_dir: &Path, name: &str, ws_path: &Path) -> Result<()>;
+    /// Rename a workspace: update VCS metadata and move the directory.
+    /// `old_path` and `new_path` are the workspace directories on disk.
+    fn workspace_rename(
+        &self,
+        repo_dir: &Path,
+        old_path: &Path,
+        new_path: &Path,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()>;
+
+    /// Return the diff stat between `trunk()` / main branch and the workspace's
+    /// current revision.
+    fn diff_stat_vs_trunk(
+        &self,
+        repo_dir: &Path,
+        worktree_dir: &Path,
+        ws_name: &str,
+    ) -> Result<DiffStat>;
+    /// Return the most recent non-empty commit description reachable from the
+    /// workspace's head. Falls back to an empty string if none is found.
+    fn latest_description(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> String;
+    /// Return `true` if the workspace's changes have already been merged into
+    /// the trunk branch (i.e. no un-merged commits exist).
+    fn is_merged_into_trunk(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> bool;
+    /// Return the diff stat and merge status of each of `workspaces`, given
+    /// as `(name, path)`, in the same order. By default each workspace is
+    /// asked about separately; backends that can answer for many workspaces
+    /// in a few calls override this.
+    fn workspace_details(
+        &self,
+        repo_dir: &Path,
+        workspaces: &[(&str, &Path)],
+    ) -> Vec<WorkspaceDetails> {
+        parallel_map(workspaces, |&(name, path)| {
+            let (diff_stat, error) = match self.diff_stat_vs_trunk(repo_dir, path, name) {
+                Ok(stat) => (stat, None),
+                Err(e) => (DiffStat::default(), Some(VcsError::new("diff stat", &e))),
+            };
+            WorkspaceDetails {
+                diff_stat,
+                merged: self.is_merged_into_trunk(repo_dir, path, name),
+                error,
+            }
+        })
+    }
+
+    /// Whether the workspace has uncommitted changes or untracked files.
+    /// Always clean by default.
+    fn working_copy_status(
+        &self,
+        _repo_dir: &Path,
+        _worktree_dir: &Path,
+        _ws_name: &str,
+    ) -> Result<WorkingCopyStatus> {
+        Ok(WorkingCopyStatus::default())
+    }
+
+    /// The description of revision `rev`, or `None` if it has none or the
+    /// backend can't look one up (the default).
+    fn revision_description(&self, _repo_dir: &Path, _rev: &str) -> Option<String> {
+        None
+    }
+
+    /// Full commit id of trunk, used with each workspace's
+    /// [`WorkspaceInfo::commit_id`] to key cached details. `None`, the
+    /// default, disables the cache.
+    fn trunk_id(&self, _repo_dir: &Path) -> Option<String> {
+        None
+    }
+
+    /// Record `trunk` as the repo's trunk in the VCS's own repo config, so
+    /// every workspace compares against it. Unsupported by default.
+    fn set_trunk(&self, _repo_dir: &Path, _trunk: &str) -> Result<()> {
+        bail!(
+            "setting trunk isn't supported for {} repos",
+            self.vcs_type()
+        )
+    }
+
+    /// Check out the submodules of a freshly added workspace, streaming the
+    /// VCS's progress to stderr. Does nothing by default.
+    fn update_submodules(&self, _ws_path: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    /// Update trunk from the remote so merge checks and diff stats compare
+    /// against a current trunk. Does nothing by default.
+    fn fetch(&self, _repo_dir: &Path) -> Result<()> {
+        Ok(())
+    }
+
+    /// Push the workspace's branch or bookmark to the default remote,
+    /// returning the remote branch it was pushed to (e.g. `origin/feat`).
+    /// Unsupported by default.
+    fn push(&self, _repo_dir: &Path, _worktree_dir: &Path, _ws_name: &str) -> Result<String> {
+        bail!("pushing isn't supported for {} repos", self.vcs_type())
+    }
+
+    /// Rebase the workspace onto the latest trunk, returning what it was
+    /// rebased onto (e.g. `origin/main`). A rebase that would conflict is
+    /// undone and reported as an error naming the conflicts, leaving the
+    /// workspace as it was. Unsupported by default.
+    fn sync(&self, _repo_dir: &Path, _worktree_dir: &Path, _ws_name: &str) -> Result<String> {
+        bail!("syncing isn't supported for {} repos", self.vcs_type())
+    }
+
+    /// VCS type for this backend.
+    fn vcs_type(&self) -> VcsType;
+    /// Name of the primary workspace that lives in the original repo directory
+    /// (e.g. `"default"` for jj, `"main-worktree"` for git).
+    fn main_workspace_name(&self) -> &'static str;
+
+    fn preview_log(
+        &self,
+        _repo_dir: &Path,
+        _worktree_dir: &Path,
+        _ws_name: &str,
+        _limit: usize,
+    ) -> String {
+        String::new()
+    }
+
+    /// Log graph for the preview pane, showing branch structure relative to
+    /// trunk, with ANSI colors preserved. Empty if unsupported, in which case
+    /// the preview falls back to [`preview_log`](Self::preview_log).
+    fn preview_graph(
+        &self,
+        _repo_dir: &Path,
+        _worktree_dir: &Path,
+        _ws_name: &str,
+        _limit: usize,
+    ) -> String {
+        String::new()
+    }
+
+    fn preview_diff_stat(&self, _repo_dir: &Path, _worktree_dir: &Path, _ws_name: &str) -> String {
+        String::new()
+    }
+}
+
+/// Detect the VCS backend for a directory by walking up looking for `.jj/`
+/// (priority), then `.git/` or a bare git repo, then `.hg/`, then the
+/// configured external backend's marker. A colocated jj+git repo uses the
+/// VCS recorded by `dwm init --vcs`.
+pub fn detect(dir: &Path) -> Result<Box<dyn VcsBackend>> {
+    let external = crate::external::ExternalBackend::from_config();
+    let dwm_base = crate::layout::base_dir().ok();
+    detect_with(dir, external, dwm_base.as_deref())
+}
+
+/// Whether `dir` looks like a bare git repo (e.g. `repo.git`), which keeps
+/// the contents of `.git/` at its top level.
+fn is_bare_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+/// Which VCS to drive a colocated jj+git repo with, given its dwm repo
+/// directory: the one recorded in its [`RepoMeta`] if any, otherwise jj, falling
+/// back to git when jj isn't installed.
+fn colocated_vcs(repo_dir: &Path, jj_installed: impl FnOnce() -> bool) -> VcsType {
+    if let Ok(Some(RepoMeta {
+        vcs: Some(vcs_type),
+        ..
+    })) = RepoMeta::load(repo_dir)
+    {
+        return vcs_type;
+    }
+    if jj_installed() {
+        VcsType::Jj
+    } else {
+        VcsType::Git
+    }
+}
+
+/// Whether the `jj` binary can be run.
+fn jj_installed() -> bool {
+    Command::new("jj")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Testable core of [`detect`], with the external backend and dwm base
+/// directory passed in.
+fn detect_with(
+    dir: &Path,
+    external: crate::external::ExternalBackend,
+    dwm_base: Option<&Path>,
+) -> Result<Box<dyn VcsBackend>> {
+    let marker = external.vcs.as_ref().and_then(|vcs| vcs.marker.clone());
+    let mut current = canonical(dir);
+    loop {
+        if current.join(".jj").is_dir() {
+            if current.join(".git").exists()
+                && let Some(dwm_base) = dwm_base
+            {
+                let repo_dir = dwm_base.join(repo_dir_name(&current));
+                return Ok(colocated_vcs(&repo_dir, jj_installed).to_backend());
+            }
+            return Ok(Box::new(crate::jj::JjBackend));
+        }
+        if current.join(".git").exists() || is_bare_git_dir(&current) {
+            return Ok(Box::new(crate::git::GitBackend));
+        }
+        if current.join(".hg").is_dir() {
+            return Ok(Box::new(crate::hg::HgBackend));
+        }
+        if let Some(marker) = &marker
+            && current.join(marker).exists()
+        {
+            return Ok(Box::new(external));
+        }
+        if !current.pop() {
+            break;
+        }
+    }
+    bail!(
+        "no jj, git, hg, or external_vcs repository found in {} or any parent directory",
+        dir.display()
+    )
+}
+
+/// Detect VCS from a dwm repo directory by reading its [`RepoMeta`].
+/// Defaults to jj for backward compatibility if the file doesn't exist.
+pub fn detect_from_dwm_dir(repo_dir: &Path) -> Result<Box<dyn VcsBackend>> {
+    let vcs_type = read_vcs_type(repo_dir)?;
+    Ok(vcs_type.to_backend())
+}
+
+/// Read the VcsType recorded in a dwm repo directory's [`RepoMeta`].
+/// Defaults to Jj for backward compatibility if none is recorded.
+pub fn read_vcs_type(repo_dir: &Path) -> Result<VcsType> {
+    Ok(RepoMeta::load(repo_dir)?.map_or(VcsType::Jj, |meta| meta.vcs_type()))
+}
+
+/// Most threads [`parallel_map`] runs at once, so a repo with many
+/// workspaces doesn't spawn hundreds of subprocesses together.
+const MAX_PARALLEL: usize = 8;
+
+/// Map `f` over `items` on scoped threads, returning results in order. Used
+/// for per-workspace VCS calls, which spend nearly all their time waiting on
+/// subprocesses.
+pub fn parallel_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism()
+        .map_or(4, |n| n.get())
+        .clamp(1, MAX_PARALLEL);
+    if items.len() <= 1 || threads == 1 {
+        return items.iter().map(f).collect();
+    }
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Run `command` to completion, killing it if it takes longer than
+/// `timeout`. Fails with its stderr if it exits nonzero.
+pub fn run_with_timeout(command: &mut Command, timeout: Duration) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| Failure::vcs(format!("failed to run {} - is it installed?", program)))?;
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!(Failure::vcs(format!(
+                "{} timed out after {}s",
+                program,
+                timeout.as_secs()
+            )));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    if !status.success() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = std::io::Read::read_to_string(&mut pipe, &mut stderr);
+        }
+        bail!(Failure::vcs(format!(
+            "{} failed: {}",
+            program,
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Parse `git status --porcelain` or `hg status` output, where each line is a
+/// status code and a path, and untracked files are marked with `?`.
+pub fn parse_status_porcelain(output: &str) -> WorkingCopyStatus {
+    let mut status = WorkingCopyStatus::default();
+    for line in output.lines().filter(|l| !l.trim().is_empty()) {
+        if line.starts_with('?') {
+            status.untracked = true;
+        } else {
+            status.uncommitted = true;
+        }
+    }
+    status
+}
+
+/// Parse the full output of `jj diff --stat` or `git diff --stat`, extracting
+/// the summary line at the end.
+pub fn parse_diff_stat(output: &str) -> Result<DiffStat> {
+    if let Some(last_line) = output.lines().last()
+        && let Some(stat) = parse_diff_stat_line(last_line)
+    {
+        return Ok(stat);
+    }
+    Ok(DiffStat::default())
+}
+
+/// Parse a single diff summary line such as
+/// `"3 files changed, 10 insertions(+), 5 deletions(-)"`.
+/// Returns `None` if the line does not look like a summary line.
+pub fn parse_diff_stat_line(line: &str) -> Option<DiffStat> {
+    let line = line.trim();
+    if !line.contains("file") {
+        return None;
+    }
+    let mut stat = DiffStat::default();
+
+    for part in line.split(',') {
+        let part = part.trim();
+        let tokens: Vec<&str> = part.split_whitespace().collect();
+        if tokens.len() >= 2
+            && let Ok(n) = tokens[0].parse::<u32>()
+        {
+            if tokens[1].starts_with("file") {
+                stat.files_changed = n;
+            } else if tokens[1].starts_with("insertion") {
+                stat.insertions = n;
+            } else if tokens[1].starts_with("deletion") {
+                stat.deletions = n;
+            }
+        }
+    }
+
+    Some(stat)
+}
+
+/// The commits above the newest ancestor shared by trunk and every
+/// workspace, each with its parents, as one `jj log` returns them.
+/// Everything below is in the history of all of them, so ahead/behind
+/// counts taken within it match the full history's.
+#[derive(Debug, Default)]
+pub struct CommitGraph {
+    parents: HashMap<String, Vec<String>>,
+}
+
+impl CommitGraph {
+    pub fn insert(&mut self, id: &str, parents: impl IntoIterator<Item = String>) {
+        self.parents
+            .insert(id.to_string(), parents.into_iter().collect());
+    }
+
+    /// `id` and its ancestors that are in the graph.
+    fn ancestors(&self, id: &str) -> HashSet<&str> {
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&str> = self
+            .parents
+            .get_key_value(id)
+            .map(|(id, _)| id.as_str())
+            .into_iter()
+            .collect();
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            for parent in &self.parents[id] {
+                if let Some((parent, _)) = self.parents.get_key_value(parent) {
+                    stack.push(parent);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Commits `head` has that `trunk` doesn't, counting only those
+    /// `counts` accepts, and commits `trunk` has that `head` doesn't.
+    pub fn ahead_behind(
+        &self,
+        trunk: &str,
+        head: &str,
+        counts: impl Fn(&str) -> bool,
+    ) -> (u32, u32) {
+        let trunk = self.ancestors(trunk);
+        let head = self.ancestors(head);
+        let ahead = head.difference(&trunk).filter(|id| counts(id)).count();
+        (ahead as u32, trunk.difference(&head).count() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_dir_name_resolves_symlinks() {
+        let tmp = tempfile::tempdir().unwrap();
+        let real = tmp.path().join("myrepo");
+        std::fs::create_dir(&real).unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        assert_eq!(repo_dir_name(&link), repo_dir_name(&real));
+        assert!(repo_dir_name(&link).starts_with("myrepo-"));
+    }
+
+    #[test]
+    fn parse_status_porcelain_git() {
+        let status = parse_status_porcelain(" M src/main.rs\n?? notes.txt\n");
+        assert!(status.uncommitted);
+        assert!(status.untracked);
+        let status = parse_status_porcelain("A  new.rs\n");
+        assert_eq!(status.describe(), Some("uncommitted changes"));
+        assert!(!parse_status_porcelain("").is_dirty());
+    }
+
+    #[test]
+    fn parse_status_porcelain_hg() {
+        let status = parse_status_porcelain("? scratch.txt\n");
+        assert_eq!(status.describe(), Some("untracked files"));
+        let status = parse_status_porcelain("M a.rs\n! gone.rs\n? b.rs\n");
+        assert_eq!(
+            status.describe(),
+            Some("uncommitted changes and untracked files")
+        );
+    }
+
+    #[test]
+    fn commit_graph_counts_ahead_and_behind() {
+        // base - t1 - t2 (trunk)
+        //     \- w1 - w2 (workspace), w2 empty
+        let mut graph = CommitGraph::default();
+        graph.insert("t1", ["base".to_string()]);
+        graph.insert("t2", ["t1".to_string()]);
+        graph.insert("w1", ["base".to_string()]);
+        graph.insert("w2", ["w1".to_string()]);
+        assert_eq!(graph.ahead_behind("t2", "w2", |_| true), (2, 2));
+        assert_eq!(graph.ahead_behind("t2", "w2", |id| id != "w2"), (1, 2));
+        assert_eq!(graph.ahead_behind("t2", "t1", |_| true), (0, 1));
+        assert_eq!(graph.ahead_behind("t2", "t2", |_| true), (0, 0));
+        // A workspace at the shared ancestor itself is outside the graph.
+        assert_eq!(graph.ahead_behind("t2", "base", |_| true), (0, 2));
+    }
+
+    #[test]
+    fn parallel_map_keeps_order() {
+        let items: Vec<u32> = (0..50).collect();
+        let doubled = parallel_map(&items, |n| n * 2);
+        assert_eq!(doubled, (0..50).map(|n| n * 2).collect::<Vec<_>>());
+        assert!(parallel_map(&[] as &[u32], |n| *n).is_empty());
+        assert_eq!(parallel_map(&[7], |n| n + 1), vec![8]);
+    }
+
+    #[test]
+    fn vcs_type_from_str_jj() {
+        assert_eq!("jj".parse::<VcsType>().unwrap(), VcsType::Jj);
+    }
+
+    #[test]
+    fn vcs_type_from_str_git() {
+        assert_eq!("git".parse::<VcsType>().unwrap(), VcsType::Git);
+    }
+
+    #[test]
+    fn vcs_type_from_str_hg() {
+        assert_eq!("hg".parse::<VcsType>().unwrap(), VcsType::Hg);
+        assert_eq!(VcsType::Hg.to_string(), "hg");
+        assert_eq!(VcsType::Hg.to_backend().vcs_type(), VcsType::Hg);
+    }
+
+    #[test]
+    fn vcs_type_from_str_unknown() {
+        assert!("svn".parse::<VcsType>().is_err());
+    }
+
+    #[test]
+    fn vcs_type_display_roundtrip() {
+        assert_eq!(
+            VcsType::Jj.to_string().parse::<VcsType>().unwrap(),
+            VcsType::Jj
+        );
+        assert_eq!(
+            VcsType::Git.to_string().parse::<VcsType>().unwrap(),
+            VcsType::Git
+        );
+    }
+
+    #[test]
+    fn vcs_type_to_backend_jj() {
+        let backend = VcsType::Jj.to_backend();
+        assert_eq!(backend.vcs_type(), VcsType::Jj);
+    }
+
+    #[test]
+    fn vcs_type_to_backend_git() {
+        let backend = VcsType::Git.to_backend();
+        assert_eq!(backend.vcs_type(), VcsType::Git);
+    }
+
+    #[test]
+    fn parse_full_stat_line() {
+        let line = "3 files changed, 10 insertions(+), 5 deletions(-)";
+        let stat = parse_diff_stat_line(line).unwrap();
+        assert_eq!(stat.files_changed, 3);
+        assert_eq!(stat.insertions, 10);
+        assert_eq!(stat.deletions, 5);
+    }
+
+    #[test]
+    fn parse_insertions_only() {
+        let line = "1 file changed, 42 insertions(+)";
+        let stat = parse_diff_stat_line(line).unwrap();
+        assert_eq!(stat.files_changed, 1);
+        assert_eq!(stat.insertions, 42);
+        assert_eq!(stat.deletions, 0);
+    }
+
+    #[test]
+    fn parse_deletions_only() {
+        let line = "2 files changed, 7 deletions(-)";
+        let stat = parse_diff_stat_line(line).unwrap();
+        assert_eq!(stat.files_changed, 2);
+        assert_eq!(stat.insertions, 0);
+        assert_eq!(stat.deletions, 7);
+    }
+
+    #[test]
+    fn parse_zero_changes() {
+        let line = "0 files changed";
+        let stat = parse_diff_stat_line(line).unwrap();
+        assert_eq!(stat.files_changed, 0);
+        assert_eq!(stat.insertions, 0);
+        assert_eq!(stat.deletions, 0);
+    }
+
+    #[test]
+    fn parse_non_stat_line_returns_none() {
+        let line = " src/main.rs | 5 ++---";
+        assert!(parse_diff_stat_line(line).is_none());
+    }
+
+    #[test]
+    fn commit_counts_show_only_nonzero_sides() {
+        let stat = |ahead, behind| DiffStat {
+            ahead,
+            behind,
+            ..DiffStat::default()
+        };
+        assert_eq!(stat(0, 0).commit_counts(), None);
+        assert_eq!(stat(2, 0).commit_counts().as_deref(), Some("↑2"));
+        assert_eq!(stat(2, 40).commit_counts().as_deref(), Some("↑2 ↓40"));
+        assert_eq!(stat(0, 3).commit_counts().as_deref(), Some("↓3"));
+    }
+
+    #[test]
+    fn parse_diff_stat_multiline() {
+        let output = " src/main.rs | 5 ++---\n src/lib.rs  | 3 +++\n 2 files changed, 5 insertions(+), 3 deletions(-)";
+        let stat = parse_diff_stat(output).unwrap();
+        assert_eq!(stat.files_changed, 2);
+        assert_eq!(stat.insertions, 5);
+        assert_eq!(stat.deletions, 3);
+    }
+
+    #[test]
+    fn repo_dir_name_same_path_is_stable() {
+        let path = std::path::Path::new("/home/user/projects/myrepo");
+        assert_eq!(repo_dir_name(path), repo_dir_name(path));
+    }
+
+    #[test]
+    fn repo_dir_name_starts_with_basename() {
+        let path = std::path::Path::new("/home/user/myrepo");
+        let dir_name = repo_dir_name(path);
+        assert!(dir_name.starts_with("myrepo-"), "dir_name: {}", dir_name);
+    }
+
+    #[test]
+    fn repo_dir_name_differs_for_same_basename_different_paths() {
+        let path_a = std::path::Path::new("/work/a/myrepo");
+        let path_b = std::path::Path::new("/work/b/myrepo");
+        assert_ne!(repo_dir_name(path_a), repo_dir_name(path_b));
+    }
+
+    #[test]
+    fn detect_jj_priority_over_git() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".jj")).unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let external = crate::external::ExternalBackend { vcs: None };
+        let backend = detect_with(dir.path(), external, None).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Jj);
+    }
+
+    #[test]
+    fn colocated_repo_uses_recorded_vcs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_dir = dir.path().join("repo-abc");
+        assert_eq!(colocated_vcs(&repo_dir, || true), VcsType::Jj);
+        assert_eq!(colocated_vcs(&repo_dir, || false), VcsType::Git);
+        std::fs::create_dir(&repo_dir).unwrap();
+        RepoMeta::new(Path::new("/src/repo"), VcsType::Git)
+            .save(&repo_dir)
+            .unwrap();
+        assert_eq!(colocated_vcs(&repo_dir, || true), VcsType::Git);
+        RepoMeta::new(Path::new("/src/repo"), VcsType::Jj)
+            .save(&repo_dir)
+            .unwrap();
+        assert_eq!(colocated_vcs(&repo_dir, || false), VcsType::Jj);
+
+        // detect looks the record up under the dwm base directory.
+        let repo = dir.path().join("colocated");
+        std::fs::create_dir_all(repo.join(".jj")).unwrap();
+        std::fs::create_dir(repo.join(".git")).unwrap();
+        let dwm_base = dir.path().join("dwm");
+        let record = dwm_base.join(repo_dir_name(&std::fs::canonicalize(&repo).unwrap()));
+        std::fs::create_dir_all(&record).unwrap();
+        RepoMeta::new(&repo, VcsType::Git).save(&record).unwrap();
+        let external = crate::external::ExternalBackend { vcs: None };
+        let backend = detect_with(&repo, external, Some(&dwm_base)).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Git);
+    }
+
+    #[test]
+    fn detect_git_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        let backend = detect(dir.path()).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Git);
+    }
+
+    #[test]
+    fn detect_bare_git() {
+        let dir = tempfile::tempdir().unwrap();
+        let bare = dir.path().join("repo.git");
+        std::fs::create_dir_all(bare.join("objects")).unwrap();
+        std::fs::create_dir(bare.join("refs")).unwrap();
+        std::fs::write(bare.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        let backend = detect(&bare.join("refs")).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Git);
+    }
+
+    #[test]
+    fn detect_jj_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".jj")).unwrap();
+        let backend = detect(dir.path()).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Jj);
+    }
+
+    #[test]
+    fn detect_hg_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".hg")).unwrap();
+        let backend = detect(dir.path()).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Hg);
+    }
+
+    #[test]
+    fn detect_external_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".fslckout"), "").unwrap();
+        let external = || crate::external::ExternalBackend {
+            vcs: Some(crate::external::ExternalVcs {
+                command: "dwm-fossil".to_string(),
+                marker: Some(".fslckout".to_string()),
+            }),
+        };
+        let sub = dir.path().join("src");
+        std::fs::create_dir(&sub).unwrap();
+        let backend = detect_with(&sub, external(), None).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::External);
+
+        let unconfigured = crate::external::ExternalBackend { vcs: None };
+        assert!(detect_with(&sub, unconfigured, None).is_err());
+        assert_eq!(
+            "external".parse::<VcsType>().unwrap().to_string(),
+            "external"
+        );
+    }
+
+    #[test]
+    fn run_with_timeout_reports_failure_and_timeout() {
+        run_with_timeout(Command::new("true").arg("x"), Duration::from_secs(5)).unwrap();
+        let err = run_with_timeout(
+            Command::new("sh").args(["-c", "echo nope >&2; exit 1"]),
+            Duration::from_secs(5),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nope"), "{}", err);
+        let err = run_with_timeout(Command::new("sleep").arg("5"), Duration::from_millis(100))
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+    }
+
+    #[test]
+    fn detect_no_vcs() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(detect(dir.path()).is_err());
+    }
+
+    #[test]
+    fn detect_from_dwm_dir_defaults_to_jj() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = detect_from_dwm_dir(dir.path()).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Jj);
+    }
+
+    /// A repo entry as older versions left it, with its VCS in `.vcs-type`.
+    fn legacy_entry(dir: &Path, vcs: &str) {
+        std::fs::write(dir.join(".main-repo"), "/src/repo").unwrap();
+        std::fs::write(dir.join(".vcs-type"), vcs).unwrap();
+    }
+
+    #[test]
+    fn detect_from_dwm_dir_reads_git() {
+        let dir = tempfile::tempdir().unwrap();
+        legacy_entry(dir.path(), "git");
+        let backend = detect_from_dwm_dir(dir.path()).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Git);
+    }
+
+    #[test]
+    fn detect_from_dwm_dir_reads_jj() {
+        let dir = tempfile::tempdir().unwrap();
+        legacy_entry(dir.path(), "jj");
+        let backend = detect_from_dwm_dir(dir.path()).unwrap();
+        assert_eq!(backend.vcs_type(), VcsType::Jj);
+    }
+
+    #[test]
+    fn detect_from_dwm_dir_unknown_type() {
+        let dir = tempfile::tempdir().unwrap();
+        legacy_entry(dir.path(), "svn");
+        assert!(detect_from_dwm_dir(dir.path()).is_err());
+    }
+}
@@ -0,0 +1,5452 @@
+//! Creating, listing, renaming and deleting workspaces, and the files dwm
+//! keeps about them in each repo's `~/.dwm/` entry. Each operation takes
+//! [`WorkspaceDeps`] for the backend and paths to use; progress goes to
+//! `log` as plain text.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::exit::Failure;
+use crate::layout::{WorkspaceDeps, ensure_repo_dir, main_repo_path, repo_dir};
+use crate::meta::{RepoMeta, WorkspaceMeta};
+use crate::names::NameConfig;
+use crate::{agent, cache, forge, fuzzy, meta, names, progress, vcs};
+
+/// Whether a workspace's changes have been merged into trunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MergeStatus {
+    Merged,
+    Unmerged,
+}
+
+/// Controls whether progress messages are printed to stderr during deletion
+/// or renaming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressOutput {
+    /// Print progress messages to stderr.
+    Verbose,
+    /// Suppress progress messages (used by the TUI which owns the alternate screen).
+    Quiet,
+}
+
+/// Return `true` if `cwd` is equal to or a subdirectory of `ws_path`.
+pub fn is_inside(cwd: &std::path::Path, ws_path: &std::path::Path) -> bool {
+    cwd.starts_with(ws_path)
+}
+
+/// Resolve `repo` to a directory in that repo. A tracked repo can be named
+/// by its `~/.dwm/` entry (`backend-1a2b3c4d`) or just its directory name
+/// (`backend`) or `dwm repos rename` label when that's unambiguous;
+/// anything with a `/`, or that names no tracked repo, is a path.
+pub fn resolve_repo(dwm_base: &Path, repo: &str) -> Result<PathBuf> {
+    let path = Path::new(repo);
+    if repo.contains(std::path::MAIN_SEPARATOR) || repo.starts_with('.') {
+        if !path.is_dir() {
+            bail!("no directory at {}", repo);
+        }
+        return Ok(vcs::canonical(path));
+    }
+    let mut matches = Vec::new();
+    for entry in fs::read_dir(dwm_base).into_iter().flatten().flatten() {
+        let dir_name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(Some(meta)) = RepoMeta::load(&entry.path()) else {
+            continue;
+        };
+        if dir_name == repo {
+            return Ok(meta.main_repo);
+        }
+        if meta.main_repo.file_name().is_some_and(|n| n == repo)
+            || repo_label(&entry.path(), &meta) == repo
+        {
+            matches.push((dir_name, meta.main_repo));
+        }
+    }
+    match matches.len() {
+        1 => Ok(matches.remove(0).1),
+        0 if path.is_dir() => Ok(vcs::canonical(path)),
+        0 => bail!(Failure::not_found(format!(
+            "no tracked repo named '{}'",
+            repo
+        ))),
+        _ => {
+            let mut names: Vec<String> = matches.into_iter().map(|(name, _)| name).collect();
+            names.sort();
+            bail!(
+                "'{}' matches several repos ({}); use one of those names or a path",
+                repo,
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// Choices for `dwm init`. Any left unset are asked for when it runs in a
+/// terminal, and otherwise keep their defaults.
+#[derive(Debug, Default)]
+pub struct InitOptions {
+    /// VCS to drive a colocated jj+git repo with.
+    pub vcs: Option<vcs::VcsType>,
+    /// Branch (git) or revset (jj) to compare workspaces against, instead
+    /// of the detected one.
+    pub trunk: Option<String>,
+    /// How `dwm new` names workspaces when no name is given.
+    pub naming: Option<names::NamingScheme>,
+}
+
+/// Register the repo `deps.cwd` is in with dwm, driving it with
+/// `deps.backend`. The VCS and any naming scheme are recorded in the
+/// repo's [`RepoMeta`], and any trunk in the VCS's own repo config.
+pub fn register(deps: &WorkspaceDeps, options: &InitOptions) -> Result<()> {
+    let vcs_type = deps.backend.vcs_type();
+    let root = deps.backend.root_from(&deps.cwd)?;
+    let repo_name = deps.backend.repo_name_from(&deps.cwd)?;
+    let dir = repo_dir(&deps.dwm_base, &repo_name);
+    let meta = RepoMeta::load(&dir)?;
+    if let Some(previous) = meta.as_ref().and_then(|meta| meta.vcs)
+        && previous != vcs_type
+    {
+        // Workspaces made by one VCS are invisible to the other.
+        let workspaces = fs::read_dir(&dir)?
+            .flatten()
+            .filter(|e| e.path().is_dir() && !e.file_name().to_string_lossy().starts_with('.'))
+            .count();
+        if workspaces > 0 {
+            bail!(
+                "{} has {} workspace(s) made with {}; delete them before switching to {}",
+                root.display(),
+                workspaces,
+                previous,
+                vcs_type
+            );
+        }
+    }
+    match meta {
+        Some(mut meta) => {
+            meta.vcs = Some(vcs_type);
+            meta.save(&dir)?;
+        }
+        None => {
+            ensure_repo_dir(&deps.dwm_base, &repo_name, &root, vcs_type)?;
+        }
+    }
+    log::info!("✓ registered {} ({})", root.display(), vcs_type);
+    if let Some(trunk) = options.trunk.as_deref() {
+        deps.backend.set_trunk(&root, trunk)?;
+        log::info!("✓ trunk set to {}", trunk);
+    }
+    if let Some(naming) = options.naming {
+        let mut meta = RepoMeta::load(&dir)?
+            .with_context(|| format!("{} is not a dwm repo directory", dir.display()))?;
+        meta.naming = Some(naming);
+        meta.save(&dir)?;
+        log::info!("✓ new workspaces get {} names", naming);
+    }
+    Ok(())
+}
+
+/// Merge `dwm_base` entries recorded under a symlinked path to their repo
+/// into the entry for the repo's real path, moving their workspaces (and
+/// agent status and history) along, through the backend `backend_for`
+/// finds for each. Returns the outcome for each entry that needed merging:
+/// the main repo's real path, or why it couldn't be merged. Entries
+/// containing `cwd` are left for a later run, as moving them would strand
+/// a shell in a deleted directory.
+pub fn merge_split_repo_dirs_in(
+    dwm_base: &Path,
+    cwd: &Path,
+    backend_for: impl Fn(&Path) -> Result<Box<dyn vcs::VcsBackend>>,
+) -> Vec<Result<PathBuf>> {
+    let Ok(entries) = fs::read_dir(dwm_base) else {
+        return Vec::new();
+    };
+    let mut outcomes = Vec::new();
+    for entry in entries.flatten() {
+        let old_dir = entry.path();
+        let Ok(Some(RepoMeta {
+            main_repo: stored, ..
+        })) = RepoMeta::load(&old_dir)
+        else {
+            continue;
+        };
+        let root = vcs::canonical(&stored);
+        if root == stored {
+            continue;
+        }
+        let outcome = merge_repo_dir(dwm_base, &old_dir, cwd, &root, &backend_for)
+            .with_context(|| format!("could not merge {}", old_dir.display()));
+        outcomes.push(outcome.map(|()| root));
+    }
+    outcomes
+}
+
+/// Move everything in `old_dir` into the entry for `root`, then remove it.
+/// Nothing is moved if both entries have a workspace of the same name, or
+/// `cwd` is inside `old_dir`. The old label and naming scheme are kept
+/// where the target has none, and other files the target already has are left where they are unless
+/// they're caches or identical. `old_dir` is only removed once everything is
+/// out, so a merge that fails partway is finished by the next one.
+fn merge_repo_dir(
+    dwm_base: &Path,
+    old_dir: &Path,
+    cwd: &Path,
+    root: &Path,
+    backend_for: &impl Fn(&Path) -> Result<Box<dyn vcs::VcsBackend>>,
+) -> Result<()> {
+    let repo_name = vcs::repo_dir_name(root);
+    let target = repo_dir(dwm_base, &repo_name);
+    let workspaces: Vec<String> = fs::read_dir(old_dir)?
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    if target == old_dir {
+        // Only the recorded path was a symlink.
+        let mut meta = RepoMeta::load(old_dir)?
+            .with_context(|| format!("{} is not a dwm repo directory", old_dir.display()))?;
+        meta.main_repo = root.to_path_buf();
+        meta.save(old_dir)?;
+        return Ok(());
+    }
+    if is_inside(cwd, old_dir) {
+        bail!("the current directory is inside it; run `dwm gc` from elsewhere");
+    }
+    if let Some(clash) = workspaces.iter().find(|name| target.join(name).exists()) {
+        bail!(
+            "both {} and {} have a workspace named '{}'",
+            old_dir.display(),
+            target.display(),
+            clash
+        );
+    }
+    let old_meta = RepoMeta::load(old_dir)?;
+    let backend = backend_for(old_dir)?;
+    ensure_repo_dir(dwm_base, &repo_name, root, backend.vcs_type())?;
+    if let Some(old_meta) = old_meta {
+        let mut meta = RepoMeta::load(&target)?
+            .with_context(|| format!("{} is not a dwm repo directory", target.display()))?;
+        if (meta.label.is_none() && old_meta.label.is_some())
+            || (meta.naming.is_none() && old_meta.naming.is_some())
+        {
+            meta.label = meta.label.or(old_meta.label);
+            meta.naming = meta.naming.or(old_meta.naming);
+            meta.save(&target)?;
+        }
+    }
+    for (moved, name) in workspaces.iter().enumerate() {
+        backend
+            .workspace_rename(root, &old_dir.join(name), &target.join(name), name, name)
+            .with_context(|| {
+                format!(
+                    "moved {} of {} workspaces; `dwm gc` will move the rest",
+                    moved,
+                    workspaces.len()
+                )
+            })?;
+    }
+    let mut left = Vec::new();
+    for entry in fs::read_dir(old_dir)?.flatten() {
+        let name = entry.file_name();
+        let (from, to) = (entry.path(), target.join(&name));
+        if name == meta::REPO_FILE {
+            continue;
+        }
+        if from.is_dir() {
+            fs::create_dir_all(&to)?;
+            for file in fs::read_dir(&from)?.flatten() {
+                let dest = to.join(file.file_name());
+                if !dest.exists() {
+                    fs::rename(file.path(), dest)?;
+                }
+            }
+            if fs::remove_dir(&from).is_ok() {
+                continue;
+            }
+        } else if !to.exists() {
+            fs::rename(&from, &to)?;
+            continue;
+        } else if DERIVED_FILES.iter().any(|f| name == *f)
+            || fs::read(&from).ok() == fs::read(&to).ok()
+        {
+            fs::remove_file(&from)?;
+            continue;
+        }
+        left.push(name.to_string_lossy().into_owned());
+    }
+    if !left.is_empty() {
+        left.sort();
+        bail!(
+            "{} already has {}; remove the copies in {} by hand and rerun `dwm gc`",
+            target.display(),
+            left.join(", "),
+            old_dir.display()
+        );
+    }
+    fs::remove_file(old_dir.join(meta::REPO_FILE))?;
+    fs::remove_dir(old_dir)?;
+    Ok(())
+}
+
+/// Files in a repo's `~/.dwm/` entry that are rebuilt on demand, so a merge
+/// drops the old entry's copy rather than keeping both.
+const DERIVED_FILES: [&str; 5] = [
+    cache::CACHE_FILE,
+    forge::FORGE_CACHE_FILE,
+    LISTING_FILE,
+    VISITS_FILE,
+    ".last-fetch",
+];
+
+/// Generate a name for a new workspace in the repo whose `~/.dwm/` entry is
+/// `dir`, following `scheme` if given, else the repo's own scheme, else the
+/// scheme in `config`, using `config`'s prefix and word lists.
+pub fn generate_name(
+    dir: &Path,
+    scheme: Option<names::NamingScheme>,
+    config: &NameConfig,
+) -> Result<String> {
+    let scheme = match scheme {
+        Some(scheme) => scheme,
+        None => RepoMeta::load(dir)?
+            .and_then(|meta| meta.naming)
+            .or(config.scheme)
+            .unwrap_or_default(),
+    };
+    Ok(names::generate_unique(dir, scheme, config))
+}
+
+/// Check that `name` can be given to a new or renamed workspace: valid
+/// (see [`names::validate`]), and not the name listings give the main
+/// workspace, which a workspace called that would hide from `switch`.
+fn check_new_name(deps: &WorkspaceDeps, name: &str) -> Result<()> {
+    names::validate(name, deps.backend.vcs_type())?;
+    let main_ws_name = deps.backend.main_workspace_name();
+    if name == main_ws_name {
+        bail!(
+            "'{}' is reserved for the main workspace in {} repos; choose another name",
+            name,
+            deps.backend.vcs_type()
+        );
+    }
+    Ok(())
+}
+
+/// Create a workspace in the repo `deps.cwd` is in and return its path.
+/// It starts at the revision `at`, or the one workspace `from` is at, and
+/// is called `name`, or else after that revision's description, or else
+/// a name generated following `names`. Submodules are checked out with
+/// `recurse_submodules`, and a non-empty `sparse` checks out only those
+/// directories.
+pub fn create(
+    deps: &WorkspaceDeps,
+    name: Option<String>,
+    at: Option<&str>,
+    from: Option<&str>,
+    recurse_submodules: bool,
+    sparse: &[String],
+    names: &NameConfig,
+) -> Result<PathBuf> {
+    let repo_name = deps.backend.repo_name_from(&deps.cwd)?;
+    let root = deps.backend.root_from(&deps.cwd)?;
+    let dir = ensure_repo_dir(&deps.dwm_base, &repo_name, &root, deps.backend.vcs_type())?;
+
+    // Resolve --from to a change ID by looking up the source workspace.
+    let resolved_at;
+    let mut source_description = None;
+    let at = if let Some(ws_name) = from {
+        let workspaces = deps.backend.workspace_list(&root)?;
+        let (_name, info) = workspaces
+            .iter()
+            .find(|(n, _)| n == ws_name)
+            .with_context(|| Failure::not_found(format!("workspace '{}' not found", ws_name)))?;
+        resolved_at = info.change_id.clone();
+        source_description = Some(info.description.clone());
+        Some(resolved_at.as_str())
+    } else {
+        at
+    };
+
+    let ws_name = match name {
+        Some(n) => n,
+        // Work that starts from a change is most likely about that change,
+        // so name it after its description when there is one.
+        None => match source_description
+            .or_else(|| at.and_then(|rev| deps.backend.revision_description(&root, rev)))
+            .and_then(|description| names::from_description(&description))
+        {
+            Some(described) => names::numbered_if_taken(&dir, described),
+            None => generate_name(&dir, None, names)?,
+        },
+    };
+    // Generated names are checked too, since the prefix and word lists are
+    // the user's.
+    check_new_name(deps, &ws_name)?;
+
+    let ws_path = dir.join(&ws_name);
+    if ws_path.exists() {
+        bail!(
+            "workspace '{}' already exists at {}",
+            ws_name,
+            ws_path.display()
+        );
+    }
+
+    log::info!("creating workspace '{}'...", ws_name);
+    if sparse.is_empty() {
+        deps.backend.workspace_add(&root, &ws_path, &ws_name, at)?;
+    } else {
+        deps.backend
+            .workspace_add_sparse(&root, &ws_path, &ws_name, at, sparse)?;
+    }
+    WorkspaceMeta {
+        created_at: Some(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_secs(),
+        ),
+        ..Default::default()
+    }
+    .save(&dir, &ws_name)?;
+    if recurse_submodules {
+        // The workspace is usable without them, so still hand it back.
+        if let Err(e) = progress::step("updating submodules", || {
+            deps.backend.update_submodules(&ws_path)
+        }) {
+            log::warn!("could not update submodules: {:#}", e);
+        }
+    }
+    log::info!("✓ workspace '{}' created at {}", ws_name, ws_path.display());
+    Ok(ws_path)
+}
+
+/// Delete the workspace called `name`, or else the one `deps.cwd` is in.
+/// Unless `force`, refuses if it has uncommitted changes or untracked
+/// files. Returns the path the shell should cd to if cwd was inside the
+/// deleted workspace.
+pub fn delete(
+    deps: &WorkspaceDeps,
+    name: Option<String>,
+    force: bool,
+    output: ProgressOutput,
+) -> Result<Option<PathBuf>> {
+    let (repo_name_str, ws_name) = match name {
+        Some(name) => {
+            let repo_name_str = if deps.cwd.starts_with(&deps.dwm_base) {
+                let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
+                relative
+                    .components()
+                    .next()
+                    .context("could not determine repo from workspace path")?
+                    .as_os_str()
+                    .to_string_lossy()
+                    .to_string()
+            } else {
+                deps.backend.repo_name_from(&deps.cwd)?
+            };
+            let name = resolve_name(
+                &repo_dir(&deps.dwm_base, &repo_name_str),
+                deps.backend.main_workspace_name(),
+                &name,
+            )?;
+            (repo_name_str, name)
+        }
+        None => {
+            if !deps.cwd.starts_with(&deps.dwm_base) {
+                bail!(
+                    "not inside a dwm workspace (current dir must be under {})",
+                    deps.dwm_base.display()
+                );
+            }
+            let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
+            let components: Vec<&std::ffi::OsStr> =
+                relative.components().map(|c| c.as_os_str()).collect();
+            if components.len() < 2 {
+                bail!("could not determine workspace name from current directory");
+            }
+            (
+                components[0].to_string_lossy().to_string(),
+                components[1].to_string_lossy().to_string(),
+            )
+        }
+    };
+
+    remove_workspace(deps, &repo_name_str, &ws_name, force, output)
+}
+
+/// Which workspaces a bulk `dwm delete` removes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkDelete {
+    /// Every stale workspace: merged into trunk, or untouched for 30 days.
+    Stale,
+    /// Only workspaces merged into trunk.
+    Merged,
+}
+
+impl fmt::Display for BulkDelete {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stale => write!(f, "stale"),
+            Self::Merged => write!(f, "merged"),
+        }
+    }
+}
+
+/// The current repo's workspaces a bulk delete with `filter` would remove.
+/// The main workspace is never one of them.
+pub fn bulk_delete_targets(
+    deps: &WorkspaceDeps,
+    filter: BulkDelete,
+) -> Result<Vec<WorkspaceEntry>> {
+    let mut entries = list(deps)?;
+    // Merged workspaces are always stale, so only stale ones need asking.
+    entries.retain(|e| {
+        !e.is_main
+            && e.is_stale
+            && (filter == BulkDelete::Stale
+                || deps
+                    .backend
+                    .is_merged_into_trunk(&e.main_repo_path, &e.path, &e.name))
+    });
+    Ok(entries)
+}
+
+/// Delete each of `targets`, as found by [`bulk_delete_targets`], going
+/// on past failures. Returns where the shell should cd to if cwd was inside
+/// one of them, and the names that couldn't be deleted.
+pub fn delete_many(
+    deps: &WorkspaceDeps,
+    targets: &[WorkspaceEntry],
+    force: bool,
+) -> Result<(Option<PathBuf>, Vec<String>)> {
+    let mut redirect = None;
+    let mut failed = Vec::new();
+    for entry in targets {
+        let (repo_name_str, ws_name) = split_workspace_path(&deps.dwm_base, &entry.path)?;
+        match remove_workspace(
+            deps,
+            &repo_name_str,
+            &ws_name,
+            force,
+            ProgressOutput::Verbose,
+        ) {
+            Ok(r) => redirect = redirect.or(r),
+            Err(e) => {
+                log::warn!("{:#}", e);
+                failed.push(ws_name);
+            }
+        }
+    }
+    Ok((redirect, failed))
+}
+
+/// Delete the workspace at `ws_path` (`<dwm_base>/<repo dir>/<name>`),
+/// whatever repo `deps.cwd` is in.
+pub fn delete_at(
+    deps: &WorkspaceDeps,
+    ws_path: &Path,
+    force: bool,
+    output: ProgressOutput,
+) -> Result<Option<PathBuf>> {
+    let (repo_name_str, ws_name) = split_workspace_path(&deps.dwm_base, ws_path)?;
+    remove_workspace(deps, &repo_name_str, &ws_name, force, output)
+}
+
+/// Split a workspace path under `dwm_base` into its repo dir name and
+/// workspace name.
+pub fn split_workspace_path(dwm_base: &Path, ws_path: &Path) -> Result<(String, String)> {
+    let relative = ws_path
+        .strip_prefix(dwm_base)
+        .with_context(|| format!("{} is not a dwm workspace", ws_path.display()))?;
+    let components: Vec<&std::ffi::OsStr> = relative.components().map(|c| c.as_os_str()).collect();
+    if components.len() != 2 {
+        bail!("{} is not a dwm workspace", ws_path.display());
+    }
+    Ok((
+        components[0].to_string_lossy().to_string(),
+        components[1].to_string_lossy().to_string(),
+    ))
+}
+
+/// Forget and remove workspace `ws_name` of the repo whose dwm dir is
+/// `repo_name_str`. Unless `force`, refuses if the workspace has uncommitted
+/// changes or untracked files. Returns the path the shell should cd to if cwd
+/// was inside the deleted workspace.
+fn remove_workspace(
+    deps: &WorkspaceDeps,
+    repo_name_str: &str,
+    ws_name: &str,
+    force: bool,
+    output: ProgressOutput,
+) -> Result<Option<PathBuf>> {
+    let verbose = output == ProgressOutput::Verbose;
+    let ws_path = deps.dwm_base.join(repo_name_str).join(ws_name);
+    if !ws_path.exists() {
+        bail!(Failure::not_found(format!(
+            "workspace '{}' not found at {}",
+            ws_name,
+            ws_path.display()
+        )));
+    }
+
+    let main_repo = main_repo_path(&deps.dwm_base, repo_name_str)?;
+
+    if !force {
+        let status = deps
+            .backend
+            .working_copy_status(&main_repo, &ws_path, ws_name)
+            .with_context(|| {
+                format!(
+                    "could not check '{}' for uncommitted changes (use --force to delete anyway)",
+                    ws_name
+                )
+            })?;
+        if let Some(dirty) = status.describe() {
+            bail!(Failure::dirty(format!(
+                "workspace '{}' has {} (use --force to delete anyway)",
+                ws_name, dirty
+            )));
+        }
+    }
+
+    if verbose {
+        log::info!("forgetting workspace '{}'...", ws_name);
+    }
+    deps.backend
+        .workspace_remove(&main_repo, ws_name, &ws_path)?;
+
+    if ws_path.exists() {
+        if verbose {
+            log::info!("removing {}...", ws_path.display());
+        }
+        fs::remove_dir_all(&ws_path)?;
+    }
+
+    // Clean up agent status files for this workspace
+    let rd = repo_dir(&deps.dwm_base, repo_name_str);
+    agent::remove_agent_statuses_for_workspace(&rd, ws_name);
+    let _ = WorkspaceMeta::remove(&rd, ws_name);
+    let _ = fs::remove_file(
+        rd.join(CODE_WORKSPACES_DIR)
+            .join(format!("{}.code-workspace", ws_name)),
+    );
+
+    if verbose {
+        log::info!("✓ workspace '{}' deleted", ws_name);
+    }
+
+    if is_inside(&deps.cwd, &ws_path) {
+        Ok(Some(main_repo))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Resolve the path for the named workspace, which may also be given as a
+/// unique prefix or a `dwm status` row (see [`resolve_name`]).
+pub fn find(deps: &WorkspaceDeps, name: &str) -> Result<PathBuf> {
+    workspace_path(deps, name, true)
+}
+
+/// Resolve the path for the workspace called `name`, or, when `resolve` is
+/// set, whichever one `name` abbreviates.
+pub fn workspace_path(deps: &WorkspaceDeps, name: &str, resolve: bool) -> Result<PathBuf> {
+    let repo_name_str = if deps.cwd.starts_with(&deps.dwm_base) {
+        let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
+        relative
+            .components()
+            .next()
+            .context("could not determine repo from workspace path")?
+            .as_os_str()
+            .to_string_lossy()
+            .to_string()
+    } else {
+        deps.backend.repo_name_from(&deps.cwd)?
+    };
+
+    let main_ws_name = deps.backend.main_workspace_name();
+    let resolved;
+    let name = if resolve {
+        let rd = repo_dir(&deps.dwm_base, &repo_name_str);
+        resolved = resolve_name(&rd, main_ws_name, name)?;
+        &resolved
+    } else {
+        name
+    };
+    if name == main_ws_name {
+        return main_repo_path(&deps.dwm_base, &repo_name_str);
+    }
+
+    let ws_path = deps.dwm_base.join(&repo_name_str).join(name);
+    if !ws_path.exists() {
+        bail!(Failure::not_found(format!(
+            "workspace '{}' not found at {}",
+            name,
+            ws_path.display()
+        )));
+    }
+
+    Ok(ws_path)
+}
+
+/// Run `command` in the named workspace with the caller's stdio, returning
+/// its exit code (or 128 plus the signal that killed it).
+pub fn exec(deps: &WorkspaceDeps, name: &str, command: &[String]) -> Result<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    let path = find(deps, name)?;
+    let (program, args) = command.split_first().context("no command given")?;
+    let status = std::process::Command::new(program)
+        .args(args)
+        .current_dir(&path)
+        .status()
+        .with_context(|| format!("failed to run '{}'", program))?;
+    Ok(status
+        .code()
+        .or_else(|| status.signal().map(|s| 128 + s))
+        .unwrap_or(1))
+}
+
+/// The path of the `nth` most recently visited or modified workspace
+/// (1-based) other than the main one and the one `deps.cwd` is in.
+pub fn recent(deps: &WorkspaceDeps, nth: usize) -> Result<PathBuf> {
+    let mut entries: Vec<WorkspaceEntry> = list_names(deps)?
+        .into_iter()
+        .filter(|e| !e.is_main && !is_inside(&deps.cwd, &e.path))
+        .collect();
+    let (repo_name, _) = listed_repo(deps)?;
+    let visits = read_visits(&repo_dir(&deps.dwm_base, &repo_name));
+    entries.sort_by_key(|e| {
+        let visited = visits.get(&e.name).copied();
+        std::cmp::Reverse(e.last_modified.max(visited))
+    });
+    let count = entries.len();
+    match entries.into_iter().nth(nth.saturating_sub(1)) {
+        Some(entry) => Ok(entry.path),
+        None if count == 0 => bail!("no other workspaces to jump to"),
+        None => bail!(
+            "only {} other workspace{} to jump to",
+            count,
+            if count == 1 { "" } else { "s" }
+        ),
+    }
+}
+
+/// The workspace to start an agent in: the one called `name` if it exists,
+/// otherwise a new one, made as [`create`] does (auto-named when `name` is
+/// `None`).
+pub fn find_or_create(
+    deps: &WorkspaceDeps,
+    name: Option<String>,
+    recurse_submodules: bool,
+    names: &NameConfig,
+) -> Result<PathBuf> {
+    // Only an exact match; `dwm launch fix` shouldn't land in `fix-login`.
+    if let Some(existing) = name.as_deref()
+        && let Ok(path) = workspace_path(deps, existing, false)
+    {
+        return Ok(path);
+    }
+    create(deps, name, None, None, recurse_submodules, &[], names)
+}
+
+/// Push the named workspace (the current one if `name` is `None`) to the
+/// default remote. Returns the workspace's name and the remote branch it
+/// was pushed to.
+pub fn push(deps: &WorkspaceDeps, name: Option<String>) -> Result<(String, String)> {
+    let (_, main_repo) = listed_repo(deps)?;
+    let ws_name = name.unwrap_or_else(|| current_name(deps));
+    let ws_path = find(deps, &ws_name)?;
+    let remote_branch = deps.backend.push(&main_repo, &ws_path, &ws_name)?;
+    Ok((ws_name, remote_branch))
+}
+
+/// Rebase the named workspace (the current one if `name` is `None`) onto
+/// the latest trunk. Returns the workspace's name and what it was rebased
+/// onto.
+pub fn sync(deps: &WorkspaceDeps, name: Option<String>) -> Result<(String, String)> {
+    let (_, main_repo) = listed_repo(deps)?;
+    let ws_name = name.unwrap_or_else(|| current_name(deps));
+    let ws_path = find(deps, &ws_name)?;
+    let onto = deps.backend.sync(&main_repo, &ws_path, &ws_name)?;
+    Ok((ws_name, onto))
+}
+
+/// Name of the workspace `deps.cwd` is in: a dwm workspace, or else the
+/// main workspace.
+pub fn current_name(deps: &WorkspaceDeps) -> String {
+    deps.cwd
+        .strip_prefix(&deps.dwm_base)
+        .ok()
+        .and_then(|relative| relative.components().nth(1))
+        .map(|ws| ws.as_os_str().to_string_lossy().to_string())
+        .unwrap_or_else(|| deps.backend.main_workspace_name().to_string())
+}
+
+/// Directory in a repo's `~/.dwm/` entry holding the `.code-workspace`
+/// files `dwm code` writes, one per workspace. Kept out of the workspaces
+/// themselves so they don't show up as untracked files.
+const CODE_WORKSPACES_DIR: &str = ".code-workspaces";
+
+/// Write the named workspace's `.code-workspace` file, for opening it in
+/// VS Code, and return its path. An existing file keeps its
+/// settings and any folders added to it by hand; the workspace comes first,
+/// then the main repo with `with_main`.
+pub fn write_code_workspace(deps: &WorkspaceDeps, name: &str, with_main: bool) -> Result<PathBuf> {
+    let path = find(deps, name)?;
+    let (dir_name, main_repo) = listed_repo(deps)?;
+    let rd = repo_dir(&deps.dwm_base, &dir_name);
+    let ws_name = match workspace_at(&deps.dwm_base, &path) {
+        Some((_, ws_name)) => ws_name,
+        None => deps.backend.main_workspace_name().to_string(),
+    };
+    let file = rd
+        .join(CODE_WORKSPACES_DIR)
+        .join(format!("{}.code-workspace", ws_name));
+
+    let mut doc = match fs::read_to_string(&file) {
+        Ok(existing) => serde_json::from_str(&existing)
+            .with_context(|| format!("could not parse {}", file.display()))?,
+        Err(_) => serde_json::json!({}),
+    };
+    let doc_obj = doc
+        .as_object_mut()
+        .with_context(|| format!("{} must hold a JSON object", file.display()))?;
+
+    let ours = |folder: &serde_json::Value| {
+        folder["path"]
+            .as_str()
+            .is_some_and(|p| Path::new(p) == path || Path::new(p) == main_repo)
+    };
+    let mut folders = vec![serde_json::json!({ "name": ws_name, "path": path })];
+    if with_main && path != main_repo {
+        folders.push(serde_json::json!({
+            "name": format!(
+                "{} (main)",
+                RepoMeta::load(&rd)?.map_or(dir_name, |meta| repo_label(&rd, &meta))
+            ),
+            "path": main_repo,
+        }));
+    }
+    if let Some(existing) = doc_obj.get("folders").and_then(|f| f.as_array()) {
+        folders.extend(existing.iter().filter(|f| !ours(f)).cloned());
+    }
+    doc_obj.insert("folders".to_string(), serde_json::Value::Array(folders));
+
+    fs::create_dir_all(rd.join(CODE_WORKSPACES_DIR))?;
+    fs::write(&file, serde_json::to_string_pretty(&doc)? + "\n")
+        .with_context(|| format!("could not write {}", file.display()))?;
+    Ok(file)
+}
+
+/// Infer the current workspace name from `deps.cwd`.
+///
+/// Expects `cwd` to be `~/.dwm/<repo>/<workspace>[/…]` and returns the
+/// `<workspace>` component.
+pub fn name_from_cwd(deps: &WorkspaceDeps) -> Result<String> {
+    if !deps.cwd.starts_with(&deps.dwm_base) {
+        bail!(
+            "not inside a dwm workspace (current dir must be under {})",
+            deps.dwm_base.display()
+        );
+    }
+    let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
+    let components: Vec<&std::ffi::OsStr> = relative.components().map(|c| c.as_os_str()).collect();
+    if components.len() < 2 {
+        bail!("could not determine workspace name from current directory");
+    }
+    Ok(components[1].to_string_lossy().to_string())
+}
+
+/// Rename workspace `old_name`, which may be abbreviated as for [`find`],
+/// to `new_name`, carrying what dwm records about it along. Returns the
+/// path the shell should cd to if cwd was inside the renamed workspace.
+pub fn rename(
+    deps: &WorkspaceDeps,
+    old_name: &str,
+    new_name: &str,
+    output: ProgressOutput,
+) -> Result<Option<PathBuf>> {
+    let verbose = output == ProgressOutput::Verbose;
+    let repo_name_str = if deps.cwd.starts_with(&deps.dwm_base) {
+        let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
+        relative
+            .components()
+            .next()
+            .context("could not determine repo from workspace path")?
+            .as_os_str()
+            .to_string_lossy()
+            .to_string()
+    } else {
+        deps.backend.repo_name_from(&deps.cwd)?
+    };
+
+    let main_ws_name = deps.backend.main_workspace_name();
+    let old_name = &resolve_name(
+        &repo_dir(&deps.dwm_base, &repo_name_str),
+        main_ws_name,
+        old_name,
+    )?;
+    if old_name == main_ws_name {
+        bail!("cannot rename the main workspace '{}'", old_name);
+    }
+
+    let old_path = deps.dwm_base.join(&repo_name_str).join(old_name);
+    if !old_path.exists() {
+        bail!(Failure::not_found(format!(
+            "workspace '{}' not found at {}",
+            old_name,
+            old_path.display()
+        )));
+    }
+
+    check_new_name(deps, new_name)?;
+
+    let new_path = deps.dwm_base.join(&repo_name_str).join(new_name);
+    if new_path.exists() {
+        bail!(
+            "workspace '{}' already exists at {}",
+            new_name,
+            new_path.display()
+        );
+    }
+
+    let main_repo = main_repo_path(&deps.dwm_base, &repo_name_str)?;
+
+    if verbose {
+        log::info!("renaming workspace '{}' -> '{}'...", old_name, new_name);
+    }
+    deps.backend
+        .workspace_rename(&main_repo, &old_path, &new_path, old_name, new_name)?;
+    let rd = repo_dir(&deps.dwm_base, &repo_name_str);
+    WorkspaceMeta::rename(&rd, old_name, new_name)?;
+
+    if verbose {
+        log::info!("✓ workspace '{}' renamed to '{}'", old_name, new_name);
+    }
+
+    if is_inside(&deps.cwd, &old_path) {
+        let relative = deps.cwd.strip_prefix(&old_path)?;
+        Ok(Some(new_path.join(relative)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Resolve the repo name and main repo path for `deps.cwd`.
+pub fn listed_repo(deps: &WorkspaceDeps) -> Result<(String, PathBuf)> {
+    if deps.cwd.starts_with(&deps.dwm_base) {
+        let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
+        let repo_name_str = relative
+            .components()
+            .next()
+            .context("could not determine repo from workspace path")?
+            .as_os_str()
+            .to_string_lossy()
+            .to_string();
+        let main_repo = main_repo_path(&deps.dwm_base, &repo_name_str)?;
+        Ok((repo_name_str, main_repo))
+    } else {
+        let repo_name_str = deps.backend.repo_name_from(&deps.cwd)?;
+        let main_repo = deps.backend.root_from(&deps.cwd)?;
+        Ok((repo_name_str, main_repo))
+    }
+}
+
+/// Quickly list the workspaces of the repo `deps.cwd` is in from the
+/// filesystem alone, without asking the VCS: the main workspace plus every
+/// workspace directory, with only names, paths, modification times, and
+/// agent status filled in.
+pub fn list_names(deps: &WorkspaceDeps) -> Result<Vec<WorkspaceEntry>> {
+    let (repo_name_str, main_repo) = listed_repo(deps)?;
+    let rd = repo_dir(&deps.dwm_base, &repo_name_str);
+    if !rd.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut agent_summaries = agent::read_agent_summaries(&rd);
+    let main_ws_name = deps.backend.main_workspace_name();
+    let vcs_type = deps.backend.vcs_type();
+    let entry = |name: String, path: PathBuf, is_main: bool, agent_status| WorkspaceEntry {
+        last_modified: fs::metadata(&path).and_then(|m| m.modified()).ok(),
+        name,
+        path,
+        diff_stat: vcs::DiffStat::default(),
+        is_main,
+        change_id: String::new(),
+        description: String::new(),
+        bookmarks: Vec::new(),
+        working_copy: vcs::WorkingCopyStatus::default(),
+        is_stale: false,
+        repo_name: None,
+        main_repo_path: main_repo.clone(),
+        vcs_type,
+        agent_status,
+        pr: None,
+        ci: None,
+        error: None,
+    };
+
+    let mut entries = vec![entry(
+        main_ws_name.to_string(),
+        main_repo.clone(),
+        true,
+        agent_summaries.remove(main_ws_name),
+    )];
+    for dir_entry in fs::read_dir(&rd)? {
+        let path = dir_entry?.path();
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if !path.is_dir() || name.starts_with('.') {
+            continue;
+        }
+        let agent_status = agent_summaries.remove(&name);
+        entries.push(entry(name, path, false, agent_status));
+    }
+    Ok(entries)
+}
+
+/// Names of the workspaces of the repo `cwd` is in, main one first, for
+/// shell completion. Reads only `dwm_base`, never the VCS, so it's quick
+/// enough to run on every tab. Inside `dwm_base` the path names the repo; elsewhere it's the nearest ancestor of `cwd` with a `~/.dwm/`
+/// entry. Empty outside any tracked repo.
+pub fn completion_names_at(dwm_base: &Path, cwd: &Path) -> Vec<String> {
+    let rd = match cwd.strip_prefix(dwm_base) {
+        Ok(relative) => match relative.components().next() {
+            Some(repo) => dwm_base.join(repo),
+            None => return Vec::new(),
+        },
+        Err(_) => match cwd
+            .ancestors()
+            .map(|dir| repo_dir(dwm_base, &vcs::repo_dir_name(dir)))
+            .find(|rd| meta::is_repo_dir(rd))
+        {
+            Some(rd) => rd,
+            None => return Vec::new(),
+        },
+    };
+    let Ok(dir) = fs::read_dir(&rd) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    if let Ok(vcs_type) = vcs::read_vcs_type(&rd) {
+        names.insert(0, vcs_type.to_backend().main_workspace_name().to_string());
+    }
+    names
+}
+
+/// Collect a [`WorkspaceEntry`] for each workspace of the repo `deps.cwd`
+/// is in, the main one first. Details the VCS can't provide are left empty
+/// with the reason in [`WorkspaceEntry::error`].
+pub fn list(deps: &WorkspaceDeps) -> Result<Vec<WorkspaceEntry>> {
+    let (repo_name_str, main_repo) = listed_repo(deps)?;
+
+    let rd = repo_dir(&deps.dwm_base, &repo_name_str);
+    if !rd.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut agent_summaries = agent::read_agent_summaries(&rd);
+    // Whatever forge state was last fetched; see [`forge::ForgeCache`].
+    let forge_state = forge::ForgeCache::load(&rd);
+
+    let main_ws_name = deps.backend.main_workspace_name();
+    // Without the list no workspace has VCS details, so every entry carries
+    // the error.
+    let (vcs_workspaces, list_error) = match deps.backend.workspace_list(&main_repo) {
+        Ok(workspaces) => (workspaces, None),
+        Err(e) => (Vec::new(), Some(vcs::VcsError::new("workspace list", &e))),
+    };
+
+    // Details cached from an earlier run stand in for the VCS wherever
+    // neither the workspace nor trunk has moved since.
+    let trunk_id = match list_error {
+        None => deps.backend.trunk_id(&main_repo).unwrap_or_default(),
+        Some(_) => String::new(),
+    };
+    let cache = cache::DetailsCache::load(&rd);
+    let cached = |name: &str, info: &vcs::WorkspaceInfo| {
+        cache.get(name, &info.commit_id, &trunk_id).cloned()
+    };
+
+    // Workspace dirs, skipping internal dot-prefixed entries (.repo.json,
+    // .workspaces, .agent-status, etc.), with what the VCS knows about each.
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(&rd)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let info = vcs_workspaces
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, info)| info.clone());
+        let hit = info.as_ref().and_then(|info| cached(&name, info));
+        dirs.push((name, path, info, hit));
+    }
+
+    // Find info for the main workspace
+    let main_info = vcs_workspaces
+        .iter()
+        .find(|(n, _)| n == main_ws_name)
+        .map(|(_, info)| info.clone())
+        .unwrap_or_default();
+    let main_hit = cached(main_ws_name, &main_info);
+
+    // One batch for the main workspace and every uncached workspace the VCS
+    // knows, rather than a few subprocesses per workspace.
+    let mut queries: Vec<(&str, &Path)> = Vec::new();
+    if main_hit.is_none() {
+        queries.push((main_ws_name, main_repo.as_path()));
+    }
+    queries.extend(
+        dirs.iter()
+            .filter(|(_, _, info, hit)| info.is_some() && hit.is_none())
+            .map(|(name, path, _, _)| (name.as_str(), path.as_path())),
+    );
+    let mut details = if queries.is_empty() {
+        Vec::new().into_iter()
+    } else {
+        deps.backend
+            .workspace_details(&main_repo, &queries)
+            .into_iter()
+    };
+    let from_cache = |hit: &cache::CachedDetails| vcs::WorkspaceDetails {
+        diff_stat: hit.diff_stat.clone(),
+        merged: hit.merged,
+        error: None,
+    };
+
+    // Fall back to the VCS for workspaces whose info has no description,
+    // asking about them concurrently.
+    let descriptions = vcs::parallel_map(&dirs, |(name, path, info, hit)| match (info, hit) {
+        (Some(info), _) if !info.description.trim().is_empty() => info.description.clone(),
+        (_, Some(hit)) => hit.description.clone(),
+        _ => deps.backend.latest_description(&main_repo, path, name),
+    });
+
+    // Uncommitted work isn't captured by any commit id, so it's checked on
+    // every run rather than cached.
+    let mut known: Vec<(&str, &Path)> = Vec::new();
+    if list_error.is_none() {
+        known.push((main_ws_name, main_repo.as_path()));
+    }
+    known.extend(
+        dirs.iter()
+            .filter(|(_, _, info, _)| info.is_some())
+            .map(|(name, path, _, _)| (name.as_str(), path.as_path())),
+    );
+    let mut statuses = vcs::parallel_map(&known, |&(name, path)| {
+        deps.backend.working_copy_status(&main_repo, path, name)
+    })
+    .into_iter();
+    let working_copy = |status: Option<Result<vcs::WorkingCopyStatus>>| match status {
+        Some(Ok(status)) => (status, None),
+        Some(Err(e)) => (
+            vcs::WorkingCopyStatus::default(),
+            Some(vcs::VcsError::new("working copy status", &e)),
+        ),
+        None => (vcs::WorkingCopyStatus::default(), None),
+    };
+
+    let mut entries = Vec::new();
+    let mut fresh_cache = cache::DetailsCache::default();
+    let mut remember = |name: &str,
+                        info: &vcs::WorkspaceInfo,
+                        details: &vcs::WorkspaceDetails,
+                        description: &str| {
+        if details.error.is_none() {
+            fresh_cache.insert(
+                name,
+                cache::CachedDetails {
+                    commit_id: info.commit_id.clone(),
+                    trunk_id: trunk_id.clone(),
+                    diff_stat: details.diff_stat.clone(),
+                    merged: details.merged,
+                    description: description.to_string(),
+                },
+            );
+        }
+    };
+
+    let main_details = match &main_hit {
+        Some(hit) => from_cache(hit),
+        None => details.next().unwrap_or_default(),
+    };
+    let main_modified = fs::metadata(&main_repo).and_then(|m| m.modified()).ok();
+    let main_description = if !main_info.description.trim().is_empty() {
+        main_info.description.clone()
+    } else if let Some(hit) = &main_hit {
+        hit.description.clone()
+    } else {
+        deps.backend
+            .latest_description(&main_repo, &main_repo, main_ws_name)
+    };
+    remember(main_ws_name, &main_info, &main_details, &main_description);
+    let (main_working_copy, main_working_copy_error) = working_copy(match list_error {
+        None => statuses.next(),
+        Some(_) => None,
+    });
+    let vcs_type = deps.backend.vcs_type();
+    entries.push(WorkspaceEntry {
+        name: main_ws_name.to_string(),
+        path: main_repo.clone(),
+        last_modified: main_modified,
+        diff_stat: main_details.diff_stat,
+        is_main: true,
+        change_id: main_info.change_id.clone(),
+        description: main_description,
+        bookmarks: main_info.bookmarks.clone(),
+        working_copy: main_working_copy,
+        is_stale: false,
+        repo_name: None,
+        main_repo_path: main_repo.clone(),
+        vcs_type,
+        agent_status: agent_summaries.remove(main_ws_name),
+        pr: forge_state.find(&main_info.bookmarks).cloned(),
+        ci: forge_state.ci_for(&main_info.change_id).cloned(),
+        error: main_details
+            .error
+            .or(main_working_copy_error)
+            .or_else(|| list_error.clone()),
+    });
+
+    for ((name, path, ws_info, hit), description) in dirs.into_iter().zip(descriptions) {
+        // Details come back in query order, which skipped dirs without info
+        // and cached dirs.
+        let ws_details = match (&ws_info, &hit) {
+            (Some(_), Some(hit)) => from_cache(hit),
+            (Some(_), None) => details.next().unwrap_or_default(),
+            (None, _) => vcs::WorkspaceDetails {
+                error: list_error.clone(),
+                ..Default::default()
+            },
+        };
+        let (ws_working_copy, ws_working_copy_error) =
+            working_copy(ws_info.as_ref().and_then(|_| statuses.next()));
+        let info = ws_info.unwrap_or_default();
+        remember(&name, &info, &ws_details, &description);
+
+        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+
+        let merge_status = if ws_details.merged {
+            MergeStatus::Merged
+        } else {
+            MergeStatus::Unmerged
+        };
+
+        let agent_status = agent_summaries.remove(&name);
+        let pr = forge_state.find(&info.bookmarks).cloned();
+        let ci = forge_state.ci_for(&info.change_id).cloned();
+        entries.push(WorkspaceEntry {
+            is_stale: compute_is_stale(merge_status, modified),
+            repo_name: None,
+            name,
+            path,
+            last_modified: modified,
+            diff_stat: ws_details.diff_stat,
+            is_main: false,
+            change_id: info.change_id,
+            description,
+            bookmarks: info.bookmarks,
+            working_copy: ws_working_copy,
+            main_repo_path: main_repo.clone(),
+            vcs_type,
+            agent_status,
+            pr,
+            ci,
+            error: ws_details.error.or(ws_working_copy_error),
+        });
+    }
+
+    // Rewritten from this run's entries, so deleted workspaces drop out. A
+    // failed write only costs the next run its cache hits.
+    let _ = fresh_cache.save(&rd);
+
+    Ok(entries)
+}
+
+/// Number of days of inactivity after which a workspace is considered stale.
+const STALE_DAYS: u64 = 30;
+
+/// All data needed to display a single row in the workspace picker or status
+/// output. Serialized for `dwm daemon`'s listings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub last_modified: Option<std::time::SystemTime>,
+    pub diff_stat: vcs::DiffStat,
+    pub is_main: bool,
+    pub change_id: String,
+    pub description: String,
+    pub bookmarks: Vec<String>,
+    /// Uncommitted changes and untracked files in the workspace.
+    pub working_copy: vcs::WorkingCopyStatus,
+    pub is_stale: bool,
+    pub repo_name: Option<String>,
+    pub main_repo_path: PathBuf,
+    pub vcs_type: vcs::VcsType,
+    pub agent_status: Option<agent::AgentSummary>,
+    /// The open pull request for one of the workspace's branches, as
+    /// last fetched with the `pr_status` setting on.
+    pub pr: Option<forge::PullRequest>,
+    /// The CI state of the workspace's head commit, as last fetched with
+    /// the `ci_status` setting on.
+    pub ci: Option<forge::CiRun>,
+    /// Why the VCS details above are missing or incomplete, if a backend call
+    /// failed.
+    pub error: Option<vcs::VcsError>,
+}
+
+/// An unnamed jj workspace with no details, for building entries with
+/// struct-update syntax.
+impl Default for WorkspaceEntry {
+    fn default() -> Self {
+        WorkspaceEntry {
+            name: String::new(),
+            path: PathBuf::new(),
+            last_modified: None,
+            diff_stat: vcs::DiffStat::default(),
+            is_main: false,
+            change_id: String::new(),
+            description: String::new(),
+            bookmarks: Vec::new(),
+            working_copy: vcs::WorkingCopyStatus::default(),
+            is_stale: false,
+            repo_name: None,
+            main_repo_path: PathBuf::new(),
+            vcs_type: vcs::VcsType::Jj,
+            agent_status: None,
+            pr: None,
+            ci: None,
+            error: None,
+        }
+    }
+}
+
+/// Determine whether a non-main workspace should be shown as stale.
+///
+/// A workspace is stale if it has been merged into trunk, or if its last
+/// modification time is more than [`STALE_DAYS`] days in the past.
+fn compute_is_stale(merged: MergeStatus, last_modified: Option<SystemTime>) -> bool {
+    if merged == MergeStatus::Merged {
+        return true;
+    }
+    if let Some(time) = last_modified
+        && let Ok(duration) = time.elapsed()
+    {
+        return duration.as_secs() > STALE_DAYS * 86400;
+    }
+    false
+}
+
+/// How long a fetch from the remote stays fresh enough to skip another.
+pub const FETCH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Fetch trunk from the remote for `main_repo`, so merge status and diff
+/// stats compare against a current trunk. Unless `force`, skips the fetch
+/// if `rd`'s `.last-fetch` stamp is recent. The stamp marks the attempt,
+/// so an unreachable remote isn't retried on every run. Returns whether
+/// it fetched.
+pub fn fetch_repo(
+    backend: &dyn vcs::VcsBackend,
+    rd: &Path,
+    main_repo: &Path,
+    force: bool,
+    now: SystemTime,
+) -> Result<bool> {
+    let stamp = rd.join(".last-fetch");
+    let fetched_at = fs::metadata(&stamp).and_then(|m| m.modified()).ok();
+    let fresh = fetched_at
+        .and_then(|at| now.duration_since(at).ok())
+        .is_some_and(|age| age < FETCH_INTERVAL);
+    if fresh && !force {
+        return Ok(false);
+    }
+    fs::create_dir_all(rd)?;
+    fs::write(&stamp, "")?;
+    backend.fetch(main_repo)?;
+    // Touched again so `dwm daemon` relists against the fetched trunk.
+    fs::write(&stamp, "")?;
+    Ok(true)
+}
+
+/// A repo tracked under `~/.dwm/`, as shown by `dwm repos list`.
+#[derive(Debug)]
+pub struct TrackedRepo {
+    /// The `~/.dwm/` entry, e.g. `api-1a2b3c4d`.
+    pub dir_name: String,
+    /// The name shown for the repo: its label, else its directory name.
+    pub label: String,
+    pub main_repo: PathBuf,
+    /// The recorded VCS, as [`vcs::VcsType`] displays it.
+    pub vcs: String,
+    pub workspaces: usize,
+    /// Bytes used by the repo's workspaces and dwm's files for it, not
+    /// counting the main repo.
+    pub size: u64,
+}
+
+/// The name shown for the repo whose `~/.dwm/` entry is `rd`: the label
+/// set with `dwm repos rename`, else the main repo's directory name.
+pub fn repo_label(rd: &Path, meta: &RepoMeta) -> String {
+    meta.label
+        .clone()
+        .filter(|label| !label.is_empty())
+        .or_else(|| {
+            meta.main_repo
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| {
+            rd.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+/// Names of the workspace directories in the `~/.dwm/` entry `rd`.
+pub fn workspace_dir_names(rd: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(rd)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Total size of the files under `path`, without following symlinks.
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| disk_usage(&e.path()))
+        .sum()
+}
+
+/// Every repo tracked under `dwm_base`, sorted by label.
+pub fn tracked_repos(dwm_base: &Path) -> Vec<TrackedRepo> {
+    let mut repos: Vec<TrackedRepo> = fs::read_dir(dwm_base)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let rd = entry.path();
+            let meta = RepoMeta::load(&rd).ok()??;
+            Some(TrackedRepo {
+                dir_name: entry.file_name().to_string_lossy().into_owned(),
+                label: repo_label(&rd, &meta),
+                vcs: meta.vcs_type().to_string(),
+                workspaces: workspace_dir_names(&rd).len(),
+                size: disk_usage(&rd),
+                main_repo: meta.main_repo,
+            })
+        })
+        .collect();
+    repos.sort_by(|a, b| a.label.cmp(&b.label).then(a.dir_name.cmp(&b.dir_name)));
+    repos
+}
+
+/// Find the tracked repo called `name`: its `~/.dwm/` entry, or its label
+/// when only one repo has it.
+pub fn find_tracked_repo(dwm_base: &Path, name: &str) -> Result<TrackedRepo> {
+    let mut matches: Vec<TrackedRepo> = tracked_repos(dwm_base)
+        .into_iter()
+        .filter(|r| r.dir_name == name || r.label == name)
+        .collect();
+    if let Some(i) = matches.iter().position(|r| r.dir_name == name) {
+        return Ok(matches.swap_remove(i));
+    }
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => bail!(Failure::not_found(format!(
+            "no tracked repo named '{}' (see dwm repos list)",
+            name
+        ))),
+        _ => {
+            let names: Vec<&str> = matches.iter().map(|r| r.dir_name.as_str()).collect();
+            bail!(
+                "'{}' matches several repos ({}); use one of those names",
+                name,
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// Stop tracking `repo`, deleting its workspaces and its `~/.dwm/` entry;
+/// the main repo itself is left alone. Each workspace is removed through
+/// the VCS first, when its backend can be found, so git and jj forget it too.
+pub fn forget(
+    dwm_base: &Path,
+    repo: &TrackedRepo,
+    backend_for: &impl Fn(&Path) -> Result<Box<dyn vcs::VcsBackend>>,
+) -> Result<()> {
+    let rd = repo_dir(dwm_base, &repo.dir_name);
+    match backend_for(&rd) {
+        Ok(backend) if repo.main_repo.exists() => {
+            for ws_name in workspace_dir_names(&rd) {
+                let ws_path = rd.join(&ws_name);
+                if let Err(e) = backend.workspace_remove(&repo.main_repo, &ws_name, &ws_path) {
+                    log::warn!("could not remove workspace '{}': {:#}", ws_name, e);
+                }
+            }
+        }
+        _ => log::debug!("deleting {} without the VCS", rd.display()),
+    }
+    fs::remove_dir_all(&rd).with_context(|| format!("could not delete {}", rd.display()))
+}
+
+/// Change the name shown for the tracked repo called `name` to `label`,
+/// or back to its directory name when `label` is that name. Its `~/.dwm/`
+/// entry keeps its name, since that's derived from the repo's path.
+pub fn relabel(dwm_base: &Path, name: &str, label: &str) -> Result<()> {
+    let label = label.trim();
+    if label.is_empty() || label.contains(std::path::MAIN_SEPARATOR) || label.starts_with('.') {
+        bail!("'{}' is not a valid repo name", label);
+    }
+    let repo = find_tracked_repo(dwm_base, name)?;
+    if let Some(other) = tracked_repos(dwm_base)
+        .into_iter()
+        .find(|r| r.dir_name != repo.dir_name && (r.label == label || r.dir_name == label))
+    {
+        bail!("'{}' is already the name of {}", label, other.dir_name);
+    }
+    let rd = repo_dir(dwm_base, &repo.dir_name);
+    let mut meta = RepoMeta::load(&rd)?
+        .with_context(|| format!("{} is not a dwm repo directory", rd.display()))?;
+    // Naming it after its directory again just drops the label.
+    meta.label =
+        Some(label.to_string()).filter(|_| repo.main_repo.file_name().is_none_or(|n| n != label));
+    meta.save(&rd)
+}
+
+/// Collect a [`WorkspaceEntry`] for every workspace across all repos
+/// tracked under `dwm_base`, with [`WorkspaceEntry::repo_name`] set.
+pub fn list_all(dwm_base: &Path) -> Result<Vec<WorkspaceEntry>> {
+    if !dwm_base.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut repos = Vec::new();
+    for dir_entry in fs::read_dir(dwm_base)? {
+        if let Some(repo) = tracked_repo(dwm_base, &dir_entry?.path()) {
+            repos.push(repo);
+        }
+    }
+
+    // Repos are independent, so list them concurrently.
+    let listed = vcs::parallel_map(&repos, |(_, deps)| list(deps));
+
+    let mut all_entries = Vec::new();
+    for ((repo_name, _), result) in repos.into_iter().zip(listed) {
+        match result {
+            Ok(entries) => {
+                for mut entry in entries {
+                    entry.repo_name = Some(repo_name.clone());
+                    all_entries.push(entry);
+                }
+            }
+            Err(e) => {
+                log::warn!("skipping repo '{}': {}", repo_name, e);
+            }
+        }
+    }
+
+    Ok(all_entries)
+}
+
+/// Names of the `~/.dwm/` entries that are tracked repos, sorted.
+pub fn tracked_repo_names(dwm_base: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dwm_base)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| meta::is_repo_dir(&e.path()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// The label the repo tracked in the `~/.dwm/` entry `rd` is shown under,
+/// and [`WorkspaceDeps`] for listing it; `None` if `rd` isn't a tracked repo.
+fn tracked_repo(dwm_base: &Path, rd: &Path) -> Option<(String, WorkspaceDeps)> {
+    if !rd.is_dir() {
+        return None;
+    }
+    let meta = RepoMeta::load(rd).ok()??;
+    let repo_name = repo_label(rd, &meta);
+    let backend = vcs::detect_from_dwm_dir(rd).ok()?;
+    let deps = WorkspaceDeps {
+        backend,
+        cwd: rd.to_path_buf(),
+        dwm_base: dwm_base.to_path_buf(),
+    };
+    Some((repo_name, deps))
+}
+
+/// List the workspaces of the repo tracked in `~/.dwm/<dir_name>`, along
+/// with the label it's shown under.
+pub fn list_tracked_repo(dwm_base: &Path, dir_name: &str) -> Result<(String, Vec<WorkspaceEntry>)> {
+    let (repo_name, deps) = tracked_repo(dwm_base, &repo_dir(dwm_base, dir_name))
+        .with_context(|| format!("'{}' is not a repo dwm tracks", dir_name))?;
+    Ok((repo_name, list(&deps)?))
+}
+
+/// The file in a repo's `~/.dwm/` entry holding the workspace names in the
+/// order `dwm status` last numbered them.
+const LISTING_FILE: &str = ".last-listing.json";
+
+/// Remember the order `dwm status` numbered `entries` in, in the `~/.dwm/`
+/// entry `rd`, so [`resolve_name`] can take a row number.
+pub fn record_listing_in(rd: &Path, entries: &[WorkspaceEntry]) -> Result<()> {
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    fs::write(rd.join(LISTING_FILE), serde_json::to_string(&names)?)?;
+    Ok(())
+}
+
+/// Resolve `name` to the name of a workspace in the `~/.dwm/` entry `rd`
+/// (or the main one, `main_ws_name`). An exact name wins; otherwise a number
+/// is a row of the last `dwm status` listing, and anything else may be a
+/// unique prefix of a name. An unknown name comes back as is for the
+/// caller's not-found error, unless it looks like a typo of one, which
+/// fails with suggestions.
+pub fn resolve_name(rd: &Path, main_ws_name: &str, name: &str) -> Result<String> {
+    if name == main_ws_name || rd.join(name).is_dir() {
+        return Ok(name.to_string());
+    }
+    if let Ok(index) = name.parse::<usize>() {
+        return listed_name(rd, index);
+    }
+    let mut names = workspace_dir_names(rd);
+    names.push(main_ws_name.to_string());
+    let prefixed: Vec<&str> = names
+        .iter()
+        .filter(|n| !name.is_empty() && n.starts_with(name))
+        .map(String::as_str)
+        .collect();
+    match prefixed[..] {
+        [only] => return Ok(only.to_string()),
+        [] => {}
+        _ => bail!(
+            "'{}' matches several workspaces: {}",
+            name,
+            prefixed.join(", ")
+        ),
+    }
+    let suggestions = fuzzy::did_you_mean(name, &names);
+    if !suggestions.is_empty() {
+        bail!(Failure::not_found(format!(
+            "workspace '{}' not found; did you mean {}?",
+            name,
+            suggestions
+                .iter()
+                .map(|s| format!("'{}'", s))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// The file in a repo's `~/.dwm/` entry holding when the shell wrapper last
+/// `cd`ed into each workspace, as unix seconds.
+const VISITS_FILE: &str = ".last-visited.json";
+
+/// The `~/.dwm/` entry and name of the workspace `path` is in, if it's in
+/// one under `dwm_base` (the main workspace lives elsewhere, so isn't).
+pub fn workspace_at(dwm_base: &Path, path: &Path) -> Option<(PathBuf, String)> {
+    let mut components = path.strip_prefix(dwm_base).ok()?.components();
+    let repo = components
+        .next()?
+        .as_os_str()
+        .to_string_lossy()
+        .into_owned();
+    let ws_name = components
+        .next()?
+        .as_os_str()
+        .to_string_lossy()
+        .into_owned();
+    if repo.starts_with('.') || ws_name.starts_with('.') {
+        return None;
+    }
+    let rd = repo_dir(dwm_base, &repo);
+    meta::is_repo_dir(&rd).then_some((rd, ws_name))
+}
+
+/// Stamp `ws_name` in the `~/.dwm/` entry `rd` as visited at `at`, dropping
+/// workspaces that no longer exist.
+pub fn record_visit(rd: &Path, ws_name: &str, at: SystemTime) -> Result<()> {
+    let mut visits: std::collections::BTreeMap<String, u64> =
+        fs::read_to_string(rd.join(VISITS_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+    visits.retain(|name, _| rd.join(name).is_dir());
+    let secs = at.duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    visits.insert(ws_name.to_string(), secs);
+    fs::write(rd.join(VISITS_FILE), serde_json::to_string(&visits)?)?;
+    Ok(())
+}
+
+/// When each workspace in the `~/.dwm/` entry `rd` was last visited.
+pub fn read_visits(rd: &Path) -> std::collections::HashMap<String, SystemTime> {
+    let visits: std::collections::HashMap<String, u64> = fs::read_to_string(rd.join(VISITS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    visits
+        .into_iter()
+        .map(|(name, secs)| (name, SystemTime::UNIX_EPOCH + Duration::from_secs(secs)))
+        .collect()
+}
+
+/// The name in row `index` of the last `dwm status` listing for the
+/// `~/.dwm/` entry `rd`.
+fn listed_name(rd: &Path, index: usize) -> Result<String> {
+    let listing: Vec<String> = fs::read_to_string(rd.join(LISTING_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .with_context(|| {
+            Failure::not_found(format!(
+                "no workspace '{}' (run dwm status to number them)",
+                index
+            ))
+        })?;
+    index
+        .checked_sub(1)
+        .and_then(|i| listing.get(i))
+        .cloned()
+        .with_context(|| {
+            Failure::not_found(format!(
+                "no workspace #{} in the last dwm status listing ({} shown)",
+                index,
+                listing.len()
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn is_inside_detects_cwd_within_workspace() {
+        let ws = Path::new("/home/user/.dwm/myrepo/my-workspace");
+        assert!(is_inside(ws, ws));
+        assert!(is_inside(
+            Path::new("/home/user/.dwm/myrepo/my-workspace/src"),
+            ws,
+        ));
+    }
+
+    #[test]
+    fn is_inside_false_for_sibling_workspace() {
+        let ws = Path::new("/home/user/.dwm/myrepo/my-workspace");
+        assert!(!is_inside(
+            Path::new("/home/user/.dwm/myrepo/other-workspace"),
+            ws,
+        ));
+    }
+
+    #[test]
+    fn is_inside_false_for_main_repo() {
+        let ws = Path::new("/home/user/.dwm/myrepo/my-workspace");
+        assert!(!is_inside(Path::new("/home/user/code/myrepo"), ws));
+    }
+
+    // ── MockBackend ──────────────────────────────────────────────────
+
+    #[derive(Debug, Clone)]
+    #[allow(clippy::enum_variant_names)]
+    enum MockCall {
+        WorkspaceAdd {
+            repo_dir: PathBuf,
+            ws_path: PathBuf,
+            name: String,
+            at: Option<String>,
+        },
+        WorkspaceAddSparse {
+            name: String,
+            sparse: Vec<String>,
+        },
+        WorkspaceRemove {
+            repo_dir: PathBuf,
+            name: String,
+            ws_path: PathBuf,
+        },
+        WorkspaceRename {
+            old_name: String,
+            new_name: String,
+        },
+        Fetch {
+            repo_dir: PathBuf,
+        },
+        DiffStat {
+            ws_name: String,
+        },
+        UpdateSubmodules {
+            ws_path: PathBuf,
+        },
+        Push {
+            repo_dir: PathBuf,
+            worktree_dir: PathBuf,
+            ws_name: String,
+        },
+        Sync {
+            worktree_dir: PathBuf,
+            ws_name: String,
+        },
+        SetTrunk {
+            trunk: String,
+        },
+    }
+
+    struct MockBackend {
+        /// The root path returned by root_from / repo_name_from.
+        root: PathBuf,
+        /// Workspaces returned by workspace_list.
+        workspaces: Vec<(String, vcs::WorkspaceInfo)>,
+        /// Records every mutating call for assertions.
+        calls: Arc<Mutex<Vec<MockCall>>>,
+        /// Whether workspace_list fails, as if the VCS were missing.
+        fail_list: bool,
+        /// Returned by trunk_id; `None` disables the details cache.
+        trunk_id: Option<String>,
+        /// Returned by working_copy_status for every workspace.
+        working_copy: vcs::WorkingCopyStatus,
+        /// Workspaces is_merged_into_trunk reports as merged.
+        merged: Vec<String>,
+    }
+
+    impl MockBackend {
+        fn new(
+            root: PathBuf,
+            workspaces: Vec<(String, vcs::WorkspaceInfo)>,
+        ) -> (Self, Arc<Mutex<Vec<MockCall>>>) {
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            (
+                Self {
+                    root,
+                    workspaces,
+                    calls: Arc::clone(&calls),
+                    fail_list: false,
+                    trunk_id: None,
+                    working_copy: vcs::WorkingCopyStatus::default(),
+                    merged: Vec::new(),
+                },
+                calls,
+            )
+        }
+    }
+
+    impl vcs::VcsBackend for MockBackend {
+        fn root_from(&self, _dir: &Path) -> Result<PathBuf> {
+            Ok(self.root.clone())
+        }
+
+        fn workspace_list(&self, _repo_dir: &Path) -> Result<Vec<(String, vcs::WorkspaceInfo)>> {
+            if self.fail_list {
+                bail!("jj not found");
+            }
+            Ok(self.workspaces.clone())
+        }
+
+        fn workspace_add(
+            &self,
+            repo_dir: &Path,
+            ws_path: &Path,
+            name: &str,
+            at: Option<&str>,
+        ) -> Result<()> {
+            self.calls.lock().unwrap().push(MockCall::WorkspaceAdd {
+                repo_dir: repo_dir.to_path_buf(),
+                ws_path: ws_path.to_path_buf(),
+                name: name.to_string(),
+                at: at.map(|s| s.to_string()),
+            });
+            // Create the directory so the workspace "exists" after add
+            fs::create_dir_all(ws_path)?;
+            Ok(())
+        }
+
+        fn workspace_add_sparse(
+            &self,
+            _repo_dir: &Path,
+            ws_path: &Path,
+            name: &str,
+            _at: Option<&str>,
+            sparse: &[String],
+        ) -> Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(MockCall::WorkspaceAddSparse {
+                    name: name.to_string(),
+                    sparse: sparse.to_vec(),
+                });
+            fs::create_dir_all(ws_path)?;
+            Ok(())
+        }
+
+        fn workspace_remove(&self, repo_dir: &Path, name: &str, ws_path: &Path) -> Result<()> {
+            self.calls.lock().unwrap().push(MockCall::WorkspaceRemove {
+                repo_dir: repo_dir.to_path_buf(),
+                name: name.to_string(),
+                ws_path: ws_path.to_path_buf(),
+            });
+            Ok(())
+        }
+
+        fn workspace_rename(
+            &self,
+            _repo_dir: &Path,
+            old_path: &Path,
+            new_path: &Path,
+            old_name: &str,
+            new_name: &str,
+        ) -> Result<()> {
+            self.calls.lock().unwrap().push(MockCall::WorkspaceRename {
+                old_name: old_name.to_string(),
+                new_name: new_name.to_string(),
+            });
+            fs::rename(old_path, new_path)?;
+            Ok(())
+        }
+
+        fn diff_stat_vs_trunk(
+            &self,
+            _repo_dir: &Path,
+            _worktree_dir: &Path,
+            ws_name: &str,
+        ) -> Result<vcs::DiffStat> {
+            self.calls.lock().unwrap().push(MockCall::DiffStat {
+                ws_name: ws_name.to_string(),
+            });
+            Ok(vcs::DiffStat {
+                files_changed: 1,
+                insertions: 10,
+                deletions: 2,
+                ..Default::default()
+            })
+        }
+
+        fn latest_description(
+            &self,
+            _repo_dir: &Path,
+            _worktree_dir: &Path,
+            _ws_name: &str,
+        ) -> String {
+            "mock description".to_string()
+        }
+
+        fn is_merged_into_trunk(
+            &self,
+            _repo_dir: &Path,
+            _worktree_dir: &Path,
+            ws_name: &str,
+        ) -> bool {
+            self.merged.iter().any(|m| m == ws_name)
+        }
+
+        fn revision_description(&self, _repo_dir: &Path, rev: &str) -> Option<String> {
+            self.workspaces
+                .iter()
+                .find(|(_, info)| info.change_id == rev)
+                .map(|(_, info)| info.description.clone())
+        }
+
+        fn trunk_id(&self, _repo_dir: &Path) -> Option<String> {
+            self.trunk_id.clone()
+        }
+
+        fn update_submodules(&self, ws_path: &Path) -> Result<()> {
+            self.calls.lock().unwrap().push(MockCall::UpdateSubmodules {
+                ws_path: ws_path.to_path_buf(),
+            });
+            Ok(())
+        }
+
+        fn push(&self, repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> Result<String> {
+            self.calls.lock().unwrap().push(MockCall::Push {
+                repo_dir: repo_dir.to_path_buf(),
+                worktree_dir: worktree_dir.to_path_buf(),
+                ws_name: ws_name.to_string(),
+            });
+            Ok(format!("origin/{}", ws_name))
+        }
+
+        fn sync(&self, _repo_dir: &Path, worktree_dir: &Path, ws_name: &str) -> Result<String> {
+            self.calls.lock().unwrap().push(MockCall::Sync {
+                worktree_dir: worktree_dir.to_path_buf(),
+                ws_name: ws_name.to_string(),
+            });
+            Ok("trunk".to_string())
+        }
+
+        fn set_trunk(&self, _repo_dir: &Path, trunk: &str) -> Result<()> {
+            self.calls.lock().unwrap().push(MockCall::SetTrunk {
+                trunk: trunk.to_string(),
+            });
+            Ok(())
+        }
+
+        fn working_copy_status(
+            &self,
+            _repo_dir: &Path,
+            _worktree_dir: &Path,
+            _ws_name: &str,
+        ) -> Result<vcs::WorkingCopyStatus> {
+            Ok(self.working_copy)
+        }
+
+        fn fetch(&self, repo_dir: &Path) -> Result<()> {
+            self.calls.lock().unwrap().push(MockCall::Fetch {
+                repo_dir: repo_dir.to_path_buf(),
+            });
+            Ok(())
+        }
+
+        fn vcs_type(&self) -> vcs::VcsType {
+            vcs::VcsType::Jj
+        }
+
+        fn main_workspace_name(&self) -> &'static str {
+            "default"
+        }
+    }
+
+    // ── Helper to set up a dwm repo dir on disk ─────────────────────
+
+    /// Creates a dwm repo dir whose metadata points at `main_repo`.
+    /// Returns the dwm_base path.
+    fn setup_dwm_dir(tmp: &Path, repo_name: &str, main_repo: &Path) -> PathBuf {
+        let dwm_base = tmp.join("dwm");
+        let rd = dwm_base.join(repo_name);
+        fs::create_dir_all(&rd).unwrap();
+        RepoMeta::new(main_repo, vcs::VcsType::Git)
+            .save(&rd)
+            .unwrap();
+        dwm_base
+    }
+
+    // ── repos tests ───────────────────────────────────────────────────
+
+    #[test]
+    fn tracked_repos_counts_workspaces_and_disk_usage() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/api");
+        let dwm_base = setup_dwm_dir(tmp.path(), "api-1234", &main_repo);
+        let rd = dwm_base.join("api-1234");
+        fs::create_dir_all(rd.join("feat-a")).unwrap();
+        fs::create_dir_all(rd.join("feat-b/src")).unwrap();
+        fs::create_dir_all(rd.join(".agent-status")).unwrap();
+        fs::write(rd.join("feat-b/src/lib.rs"), "x".repeat(2000)).unwrap();
+        fs::create_dir_all(dwm_base.join("not-a-repo")).unwrap();
+
+        let repos = tracked_repos(&dwm_base);
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].label, "api");
+        assert_eq!(repos[0].dir_name, "api-1234");
+        assert_eq!(repos[0].vcs, "git");
+        assert_eq!(repos[0].workspaces, 2);
+        assert!(repos[0].size >= 2000);
+    }
+
+    #[test]
+    fn rename_repo_sets_and_clears_the_label() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/api");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = setup_dwm_dir(tmp.path(), "api-1234", &main_repo);
+        let other = tmp.path().join("repos/web");
+        fs::create_dir_all(dwm_base.join("web-5678")).unwrap();
+        RepoMeta::new(&other, vcs::VcsType::Git)
+            .save(&dwm_base.join("web-5678"))
+            .unwrap();
+
+        relabel(&dwm_base, "api", "backend").unwrap();
+        assert_eq!(
+            find_tracked_repo(&dwm_base, "backend").unwrap().dir_name,
+            "api-1234"
+        );
+        assert!(find_tracked_repo(&dwm_base, "api").is_err());
+        assert_eq!(resolve_repo(&dwm_base, "backend").unwrap(), main_repo);
+
+        assert!(relabel(&dwm_base, "backend", "web").is_err());
+        assert!(relabel(&dwm_base, "backend", "a/b").is_err());
+
+        relabel(&dwm_base, "backend", "api").unwrap();
+        let meta = RepoMeta::load(&dwm_base.join("api-1234")).unwrap().unwrap();
+        assert_eq!(meta.label, None);
+        assert_eq!(find_tracked_repo(&dwm_base, "api").unwrap().label, "api");
+    }
+
+    #[test]
+    fn forget_repo_removes_workspaces_and_the_entry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/api");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = setup_dwm_dir(tmp.path(), "api-1234", &main_repo);
+        let rd = dwm_base.join("api-1234");
+        fs::create_dir_all(rd.join("feat-a")).unwrap();
+        fs::create_dir_all(rd.join("feat-b")).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let mock = Mutex::new(Some(mock));
+        let backend_for = |_: &Path| -> Result<Box<dyn vcs::VcsBackend>> {
+            Ok(Box::new(mock.lock().unwrap().take().unwrap()))
+        };
+        let repo = find_tracked_repo(&dwm_base, "api").unwrap();
+        forget(&dwm_base, &repo, &backend_for).unwrap();
+
+        assert!(!rd.exists());
+        assert!(main_repo.exists());
+        let calls = calls.lock().unwrap();
+        let removed: Vec<&str> = calls
+            .iter()
+            .filter_map(|c| match c {
+                MockCall::WorkspaceRemove { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(removed, ["feat-a", "feat-b"]);
+    }
+
+    // ── fetch_repo tests ──────────────────────────────────────────────
+
+    #[test]
+    fn fetch_repo_skips_recent_fetches_unless_forced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let rd = tmp.path().join("dwm").join("repo");
+        let main_repo = tmp.path().join("repo");
+        let (backend, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let now = SystemTime::now();
+
+        assert!(fetch_repo(&backend, &rd, &main_repo, false, now).unwrap());
+        assert!(rd.join(".last-fetch").exists());
+        assert!(!fetch_repo(&backend, &rd, &main_repo, false, now).unwrap());
+        assert!(fetch_repo(&backend, &rd, &main_repo, true, now).unwrap());
+        let later = now + FETCH_INTERVAL + Duration::from_secs(1);
+        assert!(fetch_repo(&backend, &rd, &main_repo, false, later).unwrap());
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert!(matches!(&calls[0], MockCall::Fetch { repo_dir } if *repo_dir == main_repo));
+    }
+
+    // ── list tests ────────────────────────────
+
+    #[test]
+    fn list_names_skips_vcs_details() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        fs::create_dir_all(dwm_base.join(format!("{}/feat-x", dir_name))).unwrap();
+        fs::create_dir_all(dwm_base.join(format!("{}/.agent-status", dir_name))).unwrap();
+
+        let workspaces = vec![(
+            "feat-x".to_string(),
+            vcs::WorkspaceInfo {
+                change_id: "bbb".to_string(),
+                description: "feature".to_string(),
+                bookmarks: vec![],
+                commit_id: String::new(),
+            },
+        )];
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let entries = list_names(&deps).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["default", "feat-x"]);
+        assert!(entries[0].is_main);
+        assert_eq!(entries[0].path, main_repo);
+        let feat = &entries[1];
+        assert!(feat.description.is_empty());
+        assert!(feat.change_id.is_empty());
+        assert_eq!(feat.diff_stat.insertions, 0);
+        assert!(feat.last_modified.is_some());
+    }
+
+    #[test]
+    fn completion_names_read_only_the_dwm_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(main_repo.join("src")).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+        fs::create_dir_all(rd.join("zeta/src")).unwrap();
+        fs::create_dir_all(rd.join("alpha")).unwrap();
+        fs::create_dir_all(rd.join(".agent-status")).unwrap();
+        fs::write(rd.join(LISTING_FILE), "{}").unwrap();
+
+        let expected = vec!["main-worktree", "alpha", "zeta"];
+        // No VCS is involved, so a plain directory stands in for the repo.
+        assert_eq!(completion_names_at(&dwm_base, &main_repo), expected);
+        assert_eq!(
+            completion_names_at(&dwm_base, &main_repo.join("src")),
+            expected
+        );
+        assert_eq!(
+            completion_names_at(&dwm_base, &rd.join("zeta/src")),
+            expected
+        );
+        assert!(completion_names_at(&dwm_base, tmp.path()).is_empty());
+        assert!(completion_names_at(&dwm_base, &dwm_base).is_empty());
+    }
+
+    #[test]
+    fn list_entries_from_inside_dwm() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        // Create a workspace subdir
+        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let workspaces = vec![
+            (
+                "default".to_string(),
+                vcs::WorkspaceInfo {
+                    change_id: "aaa".to_string(),
+                    description: "main desc".to_string(),
+                    bookmarks: vec!["main".to_string()],
+                    commit_id: String::new(),
+                },
+            ),
+            (
+                "feat-x".to_string(),
+                vcs::WorkspaceInfo {
+                    change_id: "bbb".to_string(),
+                    description: "feature".to_string(),
+                    bookmarks: vec![],
+                    commit_id: String::new(),
+                },
+            ),
+        ];
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.clone(),
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        // Should have main + feat-x
+        assert!(entries.len() >= 2);
+
+        let main_entry = entries.iter().find(|e| e.is_main).unwrap();
+        assert_eq!(main_entry.name, "default");
+        assert_eq!(main_entry.change_id, "aaa");
+        assert_eq!(main_entry.description, "main desc");
+        assert_eq!(main_entry.path, main_repo);
+
+        let feat_entry = entries.iter().find(|e| e.name == "feat-x").unwrap();
+        assert_eq!(feat_entry.change_id, "bbb");
+        assert_eq!(feat_entry.description, "feature");
+        assert!(!feat_entry.is_main);
+    }
+
+    #[test]
+    fn list_entries_carry_backend_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mut mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        mock.fail_list = true;
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir,
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert_eq!(entries.len(), 2);
+        for entry in &entries {
+            let error = entry.error.as_ref().unwrap();
+            assert_eq!(error.to_string(), "workspace list: jj not found");
+        }
+    }
+
+    #[test]
+    fn list_entries_skips_dot_prefixed_dirs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        // Create a workspace and an internal dot-prefixed directory
+        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+        let agent_dir = dwm_base.join(format!("{}/.agent-status", dir_name));
+        fs::create_dir_all(&agent_dir).unwrap();
+
+        let workspaces = vec![
+            (
+                "default".to_string(),
+                vcs::WorkspaceInfo {
+                    change_id: "aaa".to_string(),
+                    description: "".to_string(),
+                    bookmarks: vec![],
+                    commit_id: String::new(),
+                },
+            ),
+            (
+                "feat-x".to_string(),
+                vcs::WorkspaceInfo {
+                    change_id: "bbb".to_string(),
+                    description: "".to_string(),
+                    bookmarks: vec![],
+                    commit_id: String::new(),
+                },
+            ),
+        ];
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir,
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert!(
+            !names.contains(&".agent-status"),
+            "dot-prefixed dirs should be excluded, got: {:?}",
+            names
+        );
+        assert!(names.contains(&"feat-x"));
+    }
+
+    #[test]
+    fn list_entries_from_repo_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let workspaces = vec![(
+            "default".to_string(),
+            vcs::WorkspaceInfo {
+                change_id: "abc".to_string(),
+                description: "".to_string(),
+                bookmarks: vec![],
+                commit_id: String::new(),
+            },
+        )];
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
+        // cwd is the repo itself (outside dwm)
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].is_main);
+        // Empty description should fall through to latest_description
+        assert_eq!(entries[0].description, "mock description");
+    }
+
+    #[test]
+    fn list_entries_empty_repo_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        // Don't create dwm dir — repo_dir won't exist
+        let dwm_base = tmp.path().join("dwm");
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn list_entries_reuse_cached_details_until_commits_move() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        fs::create_dir_all(dwm_base.join(format!("{}/feat-x", dir_name))).unwrap();
+
+        let info = |commit_id: &str| vcs::WorkspaceInfo {
+            change_id: "bbb".to_string(),
+            description: String::new(),
+            bookmarks: vec![],
+            commit_id: commit_id.to_string(),
+        };
+        let list = |commit_id: &str, trunk_id: &str| {
+            let workspaces = vec![
+                ("default".to_string(), info("c0")),
+                ("feat-x".to_string(), info(commit_id)),
+            ];
+            let (mut mock, calls) = MockBackend::new(main_repo.clone(), workspaces);
+            mock.trunk_id = Some(trunk_id.to_string());
+            let deps = WorkspaceDeps {
+                backend: Box::new(mock),
+                cwd: main_repo.clone(),
+                dwm_base: dwm_base.clone(),
+            };
+            let entries = list(&deps).unwrap();
+            let queried: Vec<String> = calls
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|c| match c {
+                    MockCall::DiffStat { ws_name } => Some(ws_name.clone()),
+                    _ => None,
+                })
+                .collect();
+            (entries, queried)
+        };
+
+        let (_, queried) = list("c1", "t1");
+        assert_eq!(queried, vec!["default", "feat-x"]);
+
+        let (entries, queried) = list("c1", "t1");
+        assert!(queried.is_empty());
+        let feat = entries.iter().find(|e| e.name == "feat-x").unwrap();
+        assert_eq!(feat.diff_stat.insertions, 10);
+        assert_eq!(feat.description, "mock description");
+
+        let (_, queried) = list("c2", "t1");
+        assert_eq!(queried, vec!["feat-x"]);
+
+        let (_, queried) = list("c2", "t2");
+        assert_eq!(queried, vec!["default", "feat-x"]);
+    }
+
+    // ── create tests ──────────────────────────────────────────────────
+
+    #[test]
+    fn new_workspace_calls_add() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        create(
+            &deps,
+            Some("my-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0] {
+            MockCall::WorkspaceAdd {
+                repo_dir,
+                ws_path,
+                name,
+                at,
+            } => {
+                assert_eq!(repo_dir, &main_repo);
+                assert_eq!(ws_path, &dwm_base.join(format!("{}/my-ws", dir_name)));
+                assert_eq!(name, "my-ws");
+                assert!(at.is_none());
+            }
+            other => panic!("expected WorkspaceAdd, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_workspace_updates_submodules_when_asked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: dwm_base.clone(),
+        };
+
+        create(
+            &deps,
+            Some("my-ws".to_string()),
+            None,
+            None,
+            true,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        match &calls[1] {
+            MockCall::UpdateSubmodules { ws_path } => {
+                assert_eq!(ws_path, &dwm_base.join(format!("{}/my-ws", dir_name)));
+            }
+            other => panic!("expected UpdateSubmodules, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_workspace_with_sparse_profile_adds_sparsely() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: tmp.path().join("dwm"),
+        };
+        let sparse = ["apps/web".to_string()];
+        create(
+            &deps,
+            Some("my-ws".to_string()),
+            None,
+            None,
+            false,
+            &sparse,
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert!(
+            matches!(&calls[..], [MockCall::WorkspaceAddSparse { name, sparse }]
+                if name == "my-ws" && sparse == &["apps/web"]),
+            "{:?}",
+            calls
+        );
+    }
+
+    #[test]
+    fn init_records_vcs_and_refuses_to_strand_workspaces() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        let repo_dir = dwm_base.join(vcs::repo_dir_name(&main_repo));
+        fs::create_dir_all(&repo_dir).unwrap();
+        RepoMeta::new(&main_repo, vcs::VcsType::Git)
+            .save(&repo_dir)
+            .unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        // Switching is fine while no workspaces exist.
+        register(&deps, &InitOptions::default()).unwrap();
+        assert_eq!(vcs::read_vcs_type(&repo_dir).unwrap(), vcs::VcsType::Jj);
+        assert_eq!(
+            RepoMeta::load(&repo_dir).unwrap().unwrap().main_repo,
+            main_repo
+        );
+
+        RepoMeta::new(&main_repo, vcs::VcsType::Git)
+            .save(&repo_dir)
+            .unwrap();
+        fs::create_dir(repo_dir.join("feat")).unwrap();
+        let err = register(&deps, &InitOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("made with git"), "{}", err);
+        assert_eq!(vcs::read_vcs_type(&repo_dir).unwrap(), vcs::VcsType::Git);
+    }
+
+    #[test]
+    fn init_sets_trunk_and_naming_scheme() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        let repo_dir = dwm_base.join(vcs::repo_dir_name(&main_repo));
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+        let options = InitOptions {
+            trunk: Some("develop".to_string()),
+            naming: Some(names::NamingScheme::Numbered),
+            ..Default::default()
+        };
+        register(&deps, &options).unwrap();
+        assert!(matches!(
+            &calls.lock().unwrap()[..],
+            [MockCall::SetTrunk { trunk }] if trunk == "develop"
+        ));
+        assert_eq!(
+            RepoMeta::load(&repo_dir).unwrap().unwrap().naming,
+            Some(names::NamingScheme::Numbered)
+        );
+
+        // New workspaces then follow the scheme.
+        assert_eq!(
+            create(&deps, None, None, None, false, &[], &NameConfig::default()).unwrap(),
+            repo_dir.join("ws-1")
+        );
+    }
+
+    #[test]
+    fn merge_split_repo_dirs_moves_symlinked_entry_into_real_one() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let main_repo = main_repo.canonicalize().unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&main_repo, &link).unwrap();
+
+        // The real entry and one made while cd'd in through the symlink.
+        let real_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &real_name, &main_repo);
+        setup_dwm_dir(tmp.path(), "link-00000000", &link);
+        let split = dwm_base.join("link-00000000");
+        let real = dwm_base.join(&real_name);
+        fs::create_dir_all(real.join("other")).unwrap();
+        fs::create_dir_all(split.join("feat")).unwrap();
+        fs::create_dir_all(split.join(".agent-status")).unwrap();
+        fs::write(split.join(".agent-status/s1.json"), "{}").unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let mock = Mutex::new(Some(mock));
+        let outcomes = merge_split_repo_dirs_in(&dwm_base, tmp.path(), |_| {
+            Ok(Box::new(mock.lock().unwrap().take().unwrap()))
+        });
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].as_ref().unwrap(), &main_repo);
+        assert!(!split.exists());
+        assert!(real.join("feat").is_dir());
+        assert!(real.join("other").is_dir());
+        assert!(real.join(".agent-status/s1.json").exists());
+        let calls = calls.lock().unwrap();
+        assert!(
+            matches!(&calls[..], [MockCall::WorkspaceRename { old_name, new_name }]
+                if old_name == "feat" && new_name == "feat"),
+            "{:?}",
+            calls
+        );
+
+        // Already merged: nothing left to do.
+        assert!(merge_split_repo_dirs_in(&dwm_base, tmp.path(), |_| unreachable!()).is_empty());
+    }
+
+    #[test]
+    fn merge_split_repo_dirs_carries_label_and_naming_over() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let main_repo = main_repo.canonicalize().unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&main_repo, &link).unwrap();
+
+        let real_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &real_name, &main_repo);
+        setup_dwm_dir(tmp.path(), "link-00000000", &link);
+        let split = dwm_base.join("link-00000000");
+        let real = dwm_base.join(&real_name);
+        let mut old_meta = RepoMeta::load(&split).unwrap().unwrap();
+        old_meta.label = Some("mine".to_string());
+        old_meta.save(&split).unwrap();
+        // Where older versions kept the naming scheme.
+        fs::write(split.join(".config.json"), r#"{"naming": "numbered"}"#).unwrap();
+        fs::write(split.join(LISTING_FILE), "[\"old\"]").unwrap();
+        fs::write(real.join(LISTING_FILE), "[\"new\"]").unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let mock = Mutex::new(Some(mock));
+        let outcomes = merge_split_repo_dirs_in(&dwm_base, tmp.path(), |_| {
+            Ok(Box::new(mock.lock().unwrap().take().unwrap()))
+        });
+        assert!(outcomes[0].is_ok(), "{:?}", outcomes[0]);
+        assert!(!split.exists());
+        let meta = RepoMeta::load(&real).unwrap().unwrap();
+        assert_eq!(meta.label.as_deref(), Some("mine"));
+        assert_eq!(meta.main_repo, main_repo);
+        assert_eq!(meta.naming, Some(names::NamingScheme::Numbered));
+        assert!(!real.join(".config.json").exists());
+        assert_eq!(
+            fs::read_to_string(real.join(LISTING_FILE)).unwrap(),
+            "[\"new\"]"
+        );
+    }
+
+    #[test]
+    fn merge_split_repo_dirs_keeps_files_it_could_not_move() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let main_repo = main_repo.canonicalize().unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&main_repo, &link).unwrap();
+
+        let real_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &real_name, &main_repo);
+        setup_dwm_dir(tmp.path(), "link-00000000", &link);
+        let split = dwm_base.join("link-00000000");
+        let real = dwm_base.join(&real_name);
+        fs::write(split.join("notes.txt"), "old").unwrap();
+        fs::write(real.join("notes.txt"), "new").unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let mock = Mutex::new(Some(mock));
+        let outcomes = merge_split_repo_dirs_in(&dwm_base, tmp.path(), |_| {
+            Ok(Box::new(mock.lock().unwrap().take().unwrap()))
+        });
+        let err = outcomes[0].as_ref().unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("already has notes.txt"),
+            "{:#}",
+            err
+        );
+        assert_eq!(fs::read_to_string(split.join("notes.txt")).unwrap(), "old");
+        assert!(split.join(meta::REPO_FILE).exists());
+    }
+
+    #[test]
+    fn merge_split_repo_dirs_leaves_clashing_workspaces_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let main_repo = main_repo.canonicalize().unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&main_repo, &link).unwrap();
+
+        let real_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &real_name, &main_repo);
+        setup_dwm_dir(tmp.path(), "link-00000000", &link);
+        fs::create_dir_all(dwm_base.join(&real_name).join("feat")).unwrap();
+        fs::create_dir_all(dwm_base.join("link-00000000/feat")).unwrap();
+
+        let outcomes = merge_split_repo_dirs_in(&dwm_base, tmp.path(), |_| unreachable!());
+        let err = outcomes[0].as_ref().unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("workspace named 'feat'"),
+            "{:#}",
+            err
+        );
+        assert!(dwm_base.join("link-00000000/feat").is_dir());
+    }
+
+    #[test]
+    fn merge_split_repo_dirs_skips_the_entry_holding_the_cwd() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let main_repo = main_repo.canonicalize().unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&main_repo, &link).unwrap();
+
+        let dwm_base = setup_dwm_dir(tmp.path(), "link-00000000", &link);
+        let ws = dwm_base.join("link-00000000/feat");
+        fs::create_dir_all(ws.join("src")).unwrap();
+
+        let outcomes = merge_split_repo_dirs_in(&dwm_base, &ws.join("src"), |_| unreachable!());
+        let err = outcomes[0].as_ref().unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("current directory is inside it"),
+            "{:#}",
+            err
+        );
+        assert!(ws.is_dir());
+    }
+
+    #[test]
+    fn resolve_repo_by_name_or_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = tmp.path().join("work/backend");
+        let other_backend = tmp.path().join("oss/backend");
+        let web = tmp.path().join("work/web");
+        for dir in [&backend, &other_backend, &web] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let dwm_base = setup_dwm_dir(tmp.path(), "web-11111111", &web);
+        setup_dwm_dir(tmp.path(), "backend-22222222", &backend);
+        setup_dwm_dir(tmp.path(), "backend-33333333", &other_backend);
+
+        assert_eq!(resolve_repo(&dwm_base, "web").unwrap(), web);
+        assert_eq!(
+            resolve_repo(&dwm_base, "backend-22222222").unwrap(),
+            backend
+        );
+        let err = resolve_repo(&dwm_base, "backend").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("several repos (backend-22222222, backend-33333333)"),
+            "{}",
+            err
+        );
+        let path = other_backend.to_string_lossy();
+        assert_eq!(
+            resolve_repo(&dwm_base, &path).unwrap(),
+            other_backend.canonicalize().unwrap()
+        );
+        assert!(resolve_repo(&dwm_base, "mobile").is_err());
+        assert!(resolve_repo(&dwm_base, "./missing").is_err());
+    }
+
+    #[test]
+    fn push_workspace_resolves_current_or_named_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(&dir_name).join("feat");
+        fs::create_dir_all(ws_dir.join("src")).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.join("src"),
+            dwm_base,
+        };
+        let pushed = push(&deps, None).unwrap();
+        assert_eq!(pushed, ("feat".to_string(), "origin/feat".to_string()));
+        let pushed = push(&deps, Some("default".to_string())).unwrap();
+        assert_eq!(pushed.1, "origin/default");
+        assert!(push(&deps, Some("missing".to_string())).is_err());
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 2);
+        match &calls[0] {
+            MockCall::Push {
+                repo_dir,
+                worktree_dir,
+                ws_name,
+            } => {
+                assert_eq!(repo_dir, &main_repo);
+                assert_eq!(worktree_dir, &ws_dir);
+                assert_eq!(ws_name, "feat");
+            }
+            other => panic!("expected Push, got {:?}", other),
+        }
+        match &calls[1] {
+            MockCall::Push { worktree_dir, .. } => assert_eq!(worktree_dir, &main_repo),
+            other => panic!("expected Push, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sync_workspace_rebases_current_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(&dir_name).join("feat");
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.clone(),
+            dwm_base,
+        };
+        let synced = sync(&deps, None).unwrap();
+        assert_eq!(synced, ("feat".to_string(), "trunk".to_string()));
+
+        let calls = calls.lock().unwrap();
+        match &calls[..] {
+            [
+                MockCall::Sync {
+                    worktree_dir,
+                    ws_name,
+                },
+            ] => {
+                assert_eq!(worktree_dir, &ws_dir);
+                assert_eq!(ws_name, "feat");
+            }
+            other => panic!("expected one Sync, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_workspace_auto_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        create(&deps, None, None, None, false, &[], &NameConfig::default()).unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0] {
+            MockCall::WorkspaceAdd { name, .. } => {
+                // Auto-generated name should be non-empty and contain a hyphen (adjective-noun)
+                assert!(!name.is_empty());
+                assert!(
+                    name.contains('-'),
+                    "auto name should be adjective-noun: {}",
+                    name
+                );
+            }
+            other => panic!("expected WorkspaceAdd, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_workspace_duplicate_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace once
+        create(
+            &deps,
+            Some("dup-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        // Second attempt should fail
+        let err = create(
+            &deps,
+            Some("dup-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "error: {}", err);
+    }
+
+    #[test]
+    fn new_workspace_dot_prefix_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: tmp.path().join("dwm"),
+        };
+
+        let err = create(
+            &deps,
+            Some(".agent-status".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("cannot start with '.'"),
+            "error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn new_workspace_rejects_invalid_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: tmp.path().join("dwm"),
+        };
+
+        for name in ["../escape", "nul", "tab\tname", "", "default"] {
+            let err = create(
+                &deps,
+                Some(name.to_string()),
+                None,
+                None,
+                false,
+                &[],
+                &NameConfig::default(),
+            )
+            .unwrap_err();
+            assert!(
+                err.to_string().starts_with("workspace name")
+                    || err.to_string().contains("reserved "),
+                "{}: {}",
+                name,
+                err
+            );
+        }
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn new_workspace_from_existing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let workspaces = vec![(
+            "source-ws".to_string(),
+            vcs::WorkspaceInfo {
+                change_id: "abc12345".to_string(),
+                description: "some work".to_string(),
+                bookmarks: vec![],
+                commit_id: String::new(),
+            },
+        )];
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), workspaces);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        create(
+            &deps,
+            Some("forked".to_string()),
+            None,
+            Some("source-ws"),
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0] {
+            MockCall::WorkspaceAdd {
+                ws_path, name, at, ..
+            } => {
+                assert_eq!(ws_path, &dwm_base.join(format!("{}/forked", dir_name)));
+                assert_eq!(name, "forked");
+                assert_eq!(at.as_deref(), Some("abc12345"));
+            }
+            other => panic!("expected WorkspaceAdd, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn new_workspace_is_named_after_its_source_change() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        let repo_dir = dwm_base.join(vcs::repo_dir_name(&main_repo));
+
+        let info = |change_id: &str, description: &str| vcs::WorkspaceInfo {
+            change_id: change_id.to_string(),
+            description: description.to_string(),
+            bookmarks: vec![],
+            commit_id: String::new(),
+        };
+        let workspaces = vec![
+            (
+                "source-ws".to_string(),
+                info("abc12345", "Fix login timeout\n\nSessions expired early."),
+            ),
+            ("blank".to_string(), info("def67890", "")),
+        ];
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let from = |source| {
+            create(
+                &deps,
+                None,
+                None,
+                Some(source),
+                false,
+                &[],
+                &NameConfig::default(),
+            )
+        };
+        assert_eq!(
+            from("source-ws").unwrap(),
+            repo_dir.join("fix-login-timeout")
+        );
+        assert_eq!(
+            from("source-ws").unwrap(),
+            repo_dir.join("fix-login-timeout-2")
+        );
+        let at = create(
+            &deps,
+            None,
+            Some("abc12345"),
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(at, repo_dir.join("fix-login-timeout-3"));
+
+        // Without a description, the usual generated name.
+        let blank = from("blank").unwrap();
+        let name = blank.file_name().unwrap().to_string_lossy();
+        assert!(!name.starts_with("fix-login-timeout"), "{}", name);
+    }
+
+    #[test]
+    fn new_workspace_from_nonexistent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dwm_base = tmp.path().join("dwm");
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = create(
+            &deps,
+            Some("forked".to_string()),
+            None,
+            Some("no-such-ws"),
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("not found"),
+            "error should mention not found: {}",
+            err
+        );
+    }
+
+    // ── delete tests ─────────────────────────────────
+
+    #[test]
+    fn delete_workspace_by_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        // Create the workspace dir to be deleted
+        let ws_dir = dwm_base.join(format!("{}/my-ws", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        // cwd is outside the workspace being deleted
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        let redirect = delete(
+            &deps,
+            Some("my-ws".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap();
+        assert!(
+            redirect.is_none(),
+            "should not redirect when cwd is outside workspace"
+        );
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0] {
+            MockCall::WorkspaceRemove {
+                repo_dir,
+                name,
+                ws_path,
+            } => {
+                assert_eq!(repo_dir, &main_repo);
+                assert_eq!(name, "my-ws");
+                assert_eq!(ws_path, &ws_dir);
+            }
+            other => panic!("expected WorkspaceRemove, got {:?}", other),
+        }
+
+        // Dir should be removed
+        assert!(!ws_dir.exists());
+    }
+
+    #[test]
+    fn delete_workspace_refuses_dirty_unless_forced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(format!("{}/my-ws", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mut mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        mock.working_copy = vcs::WorkingCopyStatus {
+            uncommitted: true,
+            untracked: false,
+        };
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let err = delete(
+            &deps,
+            Some("my-ws".to_string()),
+            false,
+            ProgressOutput::Quiet,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "workspace 'my-ws' has uncommitted changes (use --force to delete anyway)"
+        );
+        assert!(ws_dir.exists());
+        assert!(calls.lock().unwrap().is_empty());
+
+        delete(
+            &deps,
+            Some("my-ws".to_string()),
+            true,
+            ProgressOutput::Quiet,
+        )
+        .unwrap();
+        assert!(!ws_dir.exists());
+    }
+
+    #[test]
+    fn delete_workspace_redirects_when_inside() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/my-ws", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        // cwd is inside the workspace being deleted
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.join("src"),
+            dwm_base,
+        };
+
+        let redirect = delete(
+            &deps,
+            Some("my-ws".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap();
+        let redirect = redirect.expect("should redirect when cwd is inside workspace");
+        assert_eq!(redirect, main_repo);
+    }
+
+    #[test]
+    fn delete_workspace_infers_from_cwd() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/inferred-ws", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.clone(),
+            dwm_base,
+        };
+
+        // No name given — should infer repo=myrepo, ws=inferred-ws from cwd
+        let _redirected = delete(&deps, None, false, ProgressOutput::Verbose).unwrap();
+
+        let calls = calls.lock().unwrap();
+        match &calls[0] {
+            MockCall::WorkspaceRemove { name, .. } => {
+                assert_eq!(name, "inferred-ws");
+            }
+            other => panic!("expected WorkspaceRemove, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn delete_workspace_at_ignores_cwd_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        let other_repo = tmp.path().join("repos/other");
+        fs::create_dir_all(&main_repo).unwrap();
+        fs::create_dir_all(&other_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/my-ws", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        // cwd is in a different repo entirely
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: other_repo,
+            dwm_base,
+        };
+
+        let redirect = delete_at(&deps, &ws_dir, false, ProgressOutput::Quiet).unwrap();
+        assert!(redirect.is_none());
+        assert!(!ws_dir.exists());
+
+        let calls = calls.lock().unwrap();
+        match &calls[0] {
+            MockCall::WorkspaceRemove { repo_dir, name, .. } => {
+                assert_eq!(repo_dir, &main_repo);
+                assert_eq!(name, "my-ws");
+            }
+            other => panic!("expected WorkspaceRemove, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn delete_workspace_at_rejects_paths_outside_dwm() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let err = delete_at(&deps, &main_repo, false, ProgressOutput::Quiet).unwrap_err();
+        assert!(
+            err.to_string().contains("not a dwm workspace"),
+            "error: {}",
+            err
+        );
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn delete_workspace_not_found_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = delete(
+            &deps,
+            Some("nonexistent".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not found"), "error: {}", err);
+    }
+
+    // ── rename tests ──────────────────────────────
+
+    #[test]
+    fn rename_workspace_success() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        let redirect = rename(&deps, "old-name", "new-name", ProgressOutput::Verbose).unwrap();
+        assert!(
+            redirect.is_none(),
+            "should not redirect when cwd is outside workspace"
+        );
+
+        // Old dir gone, new dir exists
+        assert!(!ws_dir.exists());
+        assert!(dwm_base.join(format!("{}/new-name", dir_name)).exists());
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0] {
+            MockCall::WorkspaceRename {
+                old_name, new_name, ..
+            } => {
+                assert_eq!(old_name, "old-name");
+                assert_eq!(new_name, "new-name");
+            }
+            other => panic!("expected WorkspaceRename, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rename_workspace_carries_its_issue() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+        fs::create_dir_all(rd.join("42-fix-login")).unwrap();
+        let issue = forge::Issue {
+            number: 42,
+            title: "Fix login".to_string(),
+            url: "https://github.com/me/api/issues/42".to_string(),
+        };
+        WorkspaceMeta {
+            issue: Some(issue.clone()),
+            ..Default::default()
+        }
+        .save(&rd, "42-fix-login")
+        .unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        rename(&deps, "42-fix-login", "login", ProgressOutput::Quiet).unwrap();
+        let issue_of = |name| WorkspaceMeta::load(&rd, name).unwrap().issue;
+        assert_eq!(issue_of("42-fix-login"), None);
+        assert_eq!(issue_of("login"), Some(issue));
+
+        delete(
+            &deps,
+            Some("login".to_string()),
+            false,
+            ProgressOutput::Quiet,
+        )
+        .unwrap();
+        assert_eq!(issue_of("login"), None);
+    }
+
+    #[test]
+    fn rename_workspace_redirects_when_inside() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
+        fs::create_dir_all(ws_dir.join("src")).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo, vec![]);
+        // cwd is inside the workspace being renamed
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.join("src"),
+            dwm_base: dwm_base.clone(),
+        };
+
+        let redirect = rename(&deps, "old-name", "new-name", ProgressOutput::Verbose).unwrap();
+        let redirect = redirect.expect("should redirect when cwd is inside workspace");
+        // cwd was old-name/src, so redirect should be new-name/src
+        assert_eq!(
+            redirect,
+            dwm_base.join(format!("{}/new-name/src", dir_name))
+        );
+    }
+
+    #[test]
+    fn rename_workspace_preserves_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
+        fs::create_dir_all(ws_dir.join("src")).unwrap();
+        fs::write(ws_dir.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(ws_dir.join("README.md"), "# hello").unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: dwm_base.clone(),
+        };
+
+        rename(&deps, "old-name", "new-name", ProgressOutput::Verbose).unwrap();
+
+        let new_dir = dwm_base.join(format!("{}/new-name", dir_name));
+        assert!(new_dir.join("src/main.rs").exists());
+        assert_eq!(
+            fs::read_to_string(new_dir.join("src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert_eq!(
+            fs::read_to_string(new_dir.join("README.md")).unwrap(),
+            "# hello"
+        );
+    }
+
+    #[test]
+    fn rename_workspace_old_not_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = rename(&deps, "nonexistent", "new-name", ProgressOutput::Verbose).unwrap_err();
+        assert!(err.to_string().contains("not found"), "error: {}", err);
+    }
+
+    #[test]
+    fn rename_workspace_new_already_exists() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        fs::create_dir_all(dwm_base.join(format!("{}/old-name", dir_name))).unwrap();
+        fs::create_dir_all(dwm_base.join(format!("{}/new-name", dir_name))).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = rename(&deps, "old-name", "new-name", ProgressOutput::Verbose).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "error: {}", err);
+    }
+
+    #[test]
+    fn rename_workspace_refuses_main() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = rename(&deps, "default", "new-name", ProgressOutput::Verbose).unwrap_err();
+        assert!(err.to_string().contains("cannot rename"), "error: {}", err);
+    }
+
+    #[test]
+    fn rename_workspace_dot_prefix_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        fs::create_dir_all(dwm_base.join(format!("{}/old-name", dir_name))).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = rename(&deps, "old-name", ".hidden", ProgressOutput::Verbose).unwrap_err();
+        assert!(
+            err.to_string().contains("cannot start with '.'"),
+            "error: {}",
+            err
+        );
+        let err = rename(&deps, "old-name", "a/b", ProgressOutput::Verbose).unwrap_err();
+        assert_eq!(err.to_string(), "workspace name cannot contain '/'");
+        assert!(deps.dwm_base.join(&dir_name).join("old-name").exists());
+        let err = rename(&deps, "old-name", "default", ProgressOutput::Verbose).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'default' is reserved for the main workspace in jj repos; choose another name"
+        );
+    }
+
+    // ── find tests ──────────────────────────────
+
+    #[test]
+    fn switch_workspace_by_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        // Create a workspace dir
+        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let path = find(&deps, "feat-x").unwrap();
+        assert_eq!(path, ws_dir);
+    }
+
+    #[test]
+    fn switch_workspace_to_main() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        // "default" is the mock's main_workspace_name
+        let path = find(&deps, "default").unwrap();
+        assert_eq!(path, main_repo);
+    }
+
+    #[test]
+    fn code_workspace_files_keep_settings_and_extra_folders() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(&dir_name).join("feat-x");
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        let file = write_code_workspace(&deps, "feat-x", true).unwrap();
+        assert_eq!(
+            file,
+            dwm_base
+                .join(&dir_name)
+                .join(".code-workspaces/feat-x.code-workspace")
+        );
+        let doc: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(doc["folders"][0]["name"], "feat-x");
+        assert_eq!(doc["folders"][0]["path"], ws_dir.to_string_lossy().as_ref());
+        assert_eq!(doc["folders"][1]["name"], "myrepo (main)");
+        assert_eq!(
+            doc["folders"][1]["path"],
+            main_repo.to_string_lossy().as_ref()
+        );
+
+        // Hand edits survive, and the main repo goes without `with_main`.
+        let mut edited = doc.clone();
+        edited["settings"] = serde_json::json!({ "editor.tabSize": 2 });
+        edited["folders"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({ "path": "/srv/docs" }));
+        fs::write(&file, edited.to_string()).unwrap();
+        write_code_workspace(&deps, "feat-x", false).unwrap();
+        let doc: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(doc["settings"]["editor.tabSize"], 2);
+        let paths: Vec<&str> = doc["folders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(paths, vec![ws_dir.to_str().unwrap(), "/srv/docs"]);
+
+        // The main workspace gets a file too, without a duplicate folder.
+        let file = write_code_workspace(&deps, "default", true).unwrap();
+        let doc: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&file).unwrap()).unwrap();
+        assert_eq!(doc["folders"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn switch_workspace_not_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = find(&deps, "nonexistent").unwrap_err();
+        assert!(err.to_string().contains("not found"), "error: {}", err);
+    }
+
+    #[test]
+    fn recent_picks_the_nth_most_recently_modified_other_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let now = SystemTime::now();
+        for (name, mins_ago) in [("old", 60), ("newest", 1), ("middle", 10), ("here", 0)] {
+            let dir = dwm_base.join(&dir_name).join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::File::open(&dir)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(mins_ago * 60))
+                .unwrap();
+        }
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: dwm_base.join(&dir_name).join("here"),
+            dwm_base: dwm_base.clone(),
+        };
+        let ws = |name: &str| dwm_base.join(&dir_name).join(name);
+        assert_eq!(recent(&deps, 1).unwrap(), ws("newest"));
+        assert_eq!(recent(&deps, 2).unwrap(), ws("middle"));
+        assert_eq!(recent(&deps, 3).unwrap(), ws("old"));
+        let err = recent(&deps, 4).unwrap_err();
+        assert_eq!(err.to_string(), "only 3 other workspaces to jump to");
+    }
+
+    #[test]
+    fn visits_recorded_on_cd_rank_first_in_recent() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+        let now = SystemTime::now();
+        for (name, mins_ago) in [("old", 60), ("newest", 1), ("here", 0)] {
+            let dir = rd.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::File::open(&dir)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(mins_ago * 60))
+                .unwrap();
+        }
+
+        assert_eq!(
+            workspace_at(&dwm_base, &rd.join("old").join("src")),
+            Some((rd.clone(), "old".to_string()))
+        );
+        assert_eq!(workspace_at(&dwm_base, &main_repo), None);
+        assert_eq!(workspace_at(&dwm_base, &rd), None);
+        assert_eq!(workspace_at(&dwm_base, &rd.join(".agent-status")), None);
+
+        record_visit(&rd, "gone", now).unwrap();
+        record_visit(&rd, "old", now).unwrap();
+        let visits = read_visits(&rd);
+        assert_eq!(visits.len(), 1, "deleted workspaces are dropped");
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: rd.join("here"),
+            dwm_base: dwm_base.clone(),
+        };
+        assert_eq!(recent(&deps, 1).unwrap(), rd.join("old"));
+        assert_eq!(recent(&deps, 2).unwrap(), rd.join("newest"));
+    }
+
+    #[test]
+    fn exec_runs_in_the_workspace_and_passes_the_exit_code_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(&dir_name).join("feat-x");
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let command = ["sh", "-c", "pwd > out; exit 3"].map(String::from);
+        assert_eq!(exec(&deps, "feat-x", &command).unwrap(), 3);
+        let pwd = fs::read_to_string(ws_dir.join("out")).unwrap();
+        assert_eq!(
+            Path::new(pwd.trim()).canonicalize().unwrap(),
+            ws_dir.canonicalize().unwrap()
+        );
+
+        let missing = ["dwm-no-such-command".to_string()];
+        assert!(exec(&deps, "feat-x", &missing).is_err());
+        assert!(exec(&deps, "nope", &command).is_err());
+    }
+
+    // ── find_or_create tests ──────────────────────────────────────
+
+    #[test]
+    fn launch_reuses_an_existing_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let path = find_or_create(
+            &deps,
+            Some("feat-x".to_string()),
+            false,
+            &NameConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(path, ws_dir);
+        assert!(calls.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn launch_creates_a_missing_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base: dwm_base.clone(),
+        };
+
+        let path = find_or_create(
+            &deps,
+            Some("feat-y".to_string()),
+            false,
+            &NameConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(path, dwm_base.join(format!("{}/feat-y", dir_name)));
+        assert!(path.is_dir());
+        assert_eq!(calls.lock().unwrap().len(), 1);
+
+        let path = find_or_create(&deps, None, false, &NameConfig::default()).unwrap();
+        assert!(path.is_dir());
+        assert_eq!(calls.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn workspace_meta_is_kept_from_create_to_delete() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        find_or_create(
+            &deps,
+            Some("feat-y".to_string()),
+            false,
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let created = WorkspaceMeta::load(&rd, "feat-y").unwrap();
+        assert!(created.created_at.is_some());
+
+        rename(&deps, "feat-y", "feat-z", ProgressOutput::Verbose).unwrap();
+        assert_eq!(
+            WorkspaceMeta::load(&rd, "feat-y").unwrap(),
+            Default::default()
+        );
+        assert_eq!(WorkspaceMeta::load(&rd, "feat-z").unwrap(), created);
+
+        delete(
+            &deps,
+            Some("feat-z".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap();
+        assert!(!rd.join(meta::WORKSPACES_DIR).join("feat-z.json").exists());
+    }
+
+    // ── rename with cwd inference tests ─────────────────────────────
+
+    #[test]
+    fn rename_infers_from_cwd() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
+        fs::create_dir_all(&ws_dir).unwrap();
+
+        let (mock, calls) = MockBackend::new(main_repo, vec![]);
+        // cwd is inside the workspace
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: ws_dir.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Infer old name from cwd
+        let old = name_from_cwd(&deps).unwrap();
+        assert_eq!(old, "old-name");
+
+        // Now do the rename
+        let redirect = rename(&deps, &old, "new-name", ProgressOutput::Verbose).unwrap();
+        let redirect = redirect.expect("should redirect when cwd is inside workspace");
+        assert_eq!(redirect, dwm_base.join(format!("{}/new-name", dir_name)));
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        match &calls[0] {
+            MockCall::WorkspaceRename { old_name, new_name } => {
+                assert_eq!(old_name, "old-name");
+                assert_eq!(new_name, "new-name");
+            }
+            other => panic!("expected WorkspaceRename, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rename_refuses_outside_workspace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        // cwd is outside dwm
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo,
+            dwm_base,
+        };
+
+        let err = name_from_cwd(&deps).unwrap_err();
+        assert!(
+            err.to_string().contains("not inside a dwm workspace"),
+            "error: {}",
+            err
+        );
+    }
+
+    // ── regression: same basename, different paths get distinct dwm dirs ──
+
+    #[test]
+    fn same_basename_different_paths_get_distinct_dir_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_a = tmp.path().join("team-a/myrepo");
+        let repo_b = tmp.path().join("team-b/myrepo");
+        let dir_a = vcs::repo_dir_name(&repo_a);
+        let dir_b = vcs::repo_dir_name(&repo_b);
+        assert_ne!(
+            dir_a, dir_b,
+            "two repos with same basename but different paths must not collide"
+        );
+        assert!(dir_a.starts_with("myrepo-"), "dir_a: {}", dir_a);
+        assert!(dir_b.starts_with("myrepo-"), "dir_b: {}", dir_b);
+    }
+
+    // ── list_all tests ─────────────────────
+
+    #[test]
+    fn list_all_entries_multiple_repos() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dwm_base = tmp.path().join("dwm");
+
+        // Set up two repos
+        let repo1 = tmp.path().join("repos/repo1");
+        let repo2 = tmp.path().join("repos/repo2");
+        fs::create_dir_all(&repo1).unwrap();
+        fs::create_dir_all(&repo2).unwrap();
+
+        // Create dwm dirs with their repo metadata
+        let rd1 = dwm_base.join("repo1");
+        fs::create_dir_all(&rd1).unwrap();
+        RepoMeta::new(&repo1, vcs::VcsType::Git).save(&rd1).unwrap();
+
+        let rd2 = dwm_base.join("repo2");
+        fs::create_dir_all(&rd2).unwrap();
+        RepoMeta::new(&repo2, vcs::VcsType::Git).save(&rd2).unwrap();
+
+        // list_all won't work with MockBackend since it
+        // uses detect_from_dwm_dir internally. The detect_from_dwm_dir will
+        // try to instantiate JjBackend or GitBackend. So we test the scanning
+        // logic by checking it doesn't panic on dirs without repo metadata.
+        let rd3 = dwm_base.join("not-a-repo");
+        fs::create_dir_all(&rd3).unwrap();
+        // No repo metadata — should be skipped
+
+        // We can't fully test this without real VCS backends, but we verify
+        // the function doesn't panic and correctly skips dirs without repo metadata
+        // We need to accept that entries for mock VCS type will fail at workspace_list
+        let result = list_all(&dwm_base);
+        // Should not panic; may return Ok or Err depending on mock backend availability
+        assert!(result.is_ok() || result.is_err());
+    }
+
+    #[test]
+    fn list_all_entries_empty_dwm() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        // Don't even create it
+        let entries = list_all(&dwm_base).unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn list_all_entries_no_repos() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dwm_base = tmp.path().join("dwm");
+        fs::create_dir_all(&dwm_base).unwrap();
+        // Create a file (not a dir)
+        fs::write(dwm_base.join("some-file"), "").unwrap();
+        let entries = list_all(&dwm_base).unwrap();
+        assert!(entries.is_empty());
+    }
+
+    // ── compute_is_stale tests ────────────────────────────────────
+
+    #[test]
+    fn stale_merged_workspace_is_stale() {
+        assert!(compute_is_stale(
+            MergeStatus::Merged,
+            Some(SystemTime::now())
+        ));
+    }
+
+    #[test]
+    fn stale_merged_workspace_without_time_is_stale() {
+        assert!(compute_is_stale(MergeStatus::Merged, None));
+    }
+
+    #[test]
+    fn stale_old_workspace_is_stale() {
+        let old_time = SystemTime::now() - std::time::Duration::from_secs(86400 * 31);
+        assert!(compute_is_stale(MergeStatus::Unmerged, Some(old_time)));
+    }
+
+    #[test]
+    fn stale_recent_workspace_is_not_stale() {
+        let recent = SystemTime::now() - std::time::Duration::from_secs(86400 * 5);
+        assert!(!compute_is_stale(MergeStatus::Unmerged, Some(recent)));
+    }
+
+    #[test]
+    fn stale_unknown_time_not_merged_is_not_stale() {
+        assert!(!compute_is_stale(MergeStatus::Unmerged, None));
+    }
+
+    fn listed_entry(name: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/tmp/{}", name)),
+            main_repo_path: PathBuf::from("/tmp/repo"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn switch_and_delete_accept_status_row_numbers() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+        for name in ["feat-a", "feat-b", "7"] {
+            fs::create_dir_all(rd.join(name)).unwrap();
+        }
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+        let err = find(&deps, "2").unwrap_err();
+        assert!(err.to_string().contains("run dwm status"), "{}", err);
+
+        let entries: Vec<WorkspaceEntry> = ["default", "feat-b", "feat-a", "7"]
+            .iter()
+            .map(|name| listed_entry(name))
+            .collect();
+        record_listing_in(&rd, &entries).unwrap();
+        assert_eq!(find(&deps, "1").unwrap(), main_repo);
+        assert_eq!(find(&deps, "2").unwrap(), rd.join("feat-b"));
+        // A workspace named like a number wins over the row.
+        assert_eq!(find(&deps, "7").unwrap(), rd.join("7"));
+        let err = find(&deps, "9").unwrap_err();
+        assert!(err.to_string().contains("(4 shown)"), "{}", err);
+
+        delete(&deps, Some("3".to_string()), true, ProgressOutput::Quiet).unwrap();
+        assert!(!rd.join("feat-a").exists());
+        assert!(rd.join("feat-b").exists());
+    }
+
+    #[test]
+    fn bulk_delete_picks_stale_or_merged_workspaces() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+        let names = ["old", "landed", "fresh"];
+        for name in names {
+            fs::create_dir_all(rd.join(name)).unwrap();
+        }
+        let long_ago = SystemTime::now() - Duration::from_secs((STALE_DAYS + 1) * 86400);
+        fs::File::open(rd.join("old"))
+            .unwrap()
+            .set_modified(long_ago)
+            .unwrap();
+        let workspaces = ["default"]
+            .into_iter()
+            .chain(names)
+            .map(|n| (n.to_string(), vcs::WorkspaceInfo::default()))
+            .collect();
+
+        let (mut mock, calls) = MockBackend::new(main_repo.clone(), workspaces);
+        mock.merged = vec!["landed".to_string()];
+        mock.working_copy.untracked = true;
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: rd.join("landed"),
+            dwm_base,
+        };
+        let target_names = |filter| -> Vec<String> {
+            let mut names: Vec<String> = bulk_delete_targets(&deps, filter)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(target_names(BulkDelete::Stale), ["landed", "old"]);
+        assert_eq!(target_names(BulkDelete::Merged), ["landed"]);
+
+        // Dirty workspaces are skipped without --force, and the rest go on.
+        let targets = bulk_delete_targets(&deps, BulkDelete::Stale).unwrap();
+        let (redirect, failed) = delete_many(&deps, &targets, false).unwrap();
+        assert_eq!(redirect, None);
+        assert_eq!(failed.len(), 2);
+        assert!(
+            !calls
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|c| matches!(c, MockCall::WorkspaceRemove { .. }))
+        );
+
+        let (redirect, failed) = delete_many(&deps, &targets, true).unwrap();
+        assert_eq!(redirect, Some(main_repo));
+        assert!(failed.is_empty());
+        assert!(!rd.join("old").exists());
+        assert!(!rd.join("landed").exists());
+        assert!(rd.join("fresh").exists());
+    }
+
+    #[test]
+    fn workspace_names_resolve_by_prefix_with_suggestions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let main_repo = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&main_repo).unwrap();
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
+        let rd = dwm_base.join(&dir_name);
+        for name in ["hazy-quail", "hazy-owl", "calm-fox", "calm"] {
+            fs::create_dir_all(rd.join(name)).unwrap();
+        }
+
+        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
+        let deps = WorkspaceDeps {
+            backend: Box::new(mock),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+        assert_eq!(find(&deps, "hazy-q").unwrap(), rd.join("hazy-quail"));
+        assert_eq!(find(&deps, "def").unwrap(), main_repo);
+        // An exact name beats a longer one it prefixes.
+        assert_eq!(find(&deps, "calm").unwrap(), rd.join("calm"));
+
+        let err = find(&deps, "haz").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'haz' matches several workspaces: hazy-owl, hazy-quail"
+        );
+        let err = find(&deps, "hazy-qual").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "workspace 'hazy-qual' not found; did you mean 'hazy-quail'?"
+        );
+        let err = find(&deps, "nothing-like-it").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("workspace 'nothing-like-it' not found at")
+        );
+
+        // Launch only reuses an exact match.
+        find_or_create(
+            &deps,
+            Some("hazy-q".to_string()),
+            false,
+            &NameConfig::default(),
+        )
+        .unwrap();
+        assert!(rd.join("hazy-q").exists());
+
+        rename(&deps, "calm-", "calmer-fox", ProgressOutput::Quiet).unwrap();
+        assert!(rd.join("calmer-fox").exists());
+        delete(
+            &deps,
+            Some("hazy-o".to_string()),
+            true,
+            ProgressOutput::Quiet,
+        )
+        .unwrap();
+        assert!(!rd.join("hazy-owl").exists());
+    }
+
+    // ── E2E tests with real git repos ───────────────────────────────
+
+    fn git_available() -> bool {
+        std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .is_ok()
+    }
+
+    /// Initialize a git repo with an initial commit.
+    /// Returns the canonicalized repo path.
+    fn init_git_repo(dir: &Path) -> PathBuf {
+        let dir_str = dir.to_str().unwrap();
+        std::process::Command::new("git")
+            .args(["init", "-b", "main", dir_str])
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args([
+                "-C",
+                dir_str,
+                "commit",
+                "--allow-empty",
+                "-m",
+                "initial commit",
+            ])
+            .output()
+            .unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    /// Set up a dwm directory for a real git repo.
+    fn setup_dwm_dir_git(tmp: &Path, repo_name: &str, main_repo: &Path) -> PathBuf {
+        let dwm_base = tmp.join("dwm");
+        let rd = dwm_base.join(repo_name);
+        fs::create_dir_all(&rd).unwrap();
+        RepoMeta::new(main_repo, vcs::VcsType::Git)
+            .save(&rd)
+            .unwrap();
+        dwm_base
+    }
+
+    #[test]
+    fn e2e_git_list_entries_main_only() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_git(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert_eq!(entries.len(), 1, "should have main worktree entry");
+        assert!(entries[0].is_main);
+        assert_eq!(entries[0].name, "main-worktree");
+        assert_eq!(entries[0].path, main_repo);
+        assert_eq!(entries[0].description, "initial commit");
+    }
+
+    #[test]
+    fn e2e_git_preview_graph_is_colored() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+
+        let backend: Box<dyn vcs::VcsBackend> = Box::new(crate::git::GitBackend);
+        let graph = backend.preview_graph(&main_repo, &main_repo, "main-worktree", 10);
+        assert!(graph.contains("initial commit"), "got: {:?}", graph);
+        assert!(graph.contains('*'), "expected graph markers: {:?}", graph);
+        assert!(graph.contains("\x1b["), "expected ANSI colors: {:?}", graph);
+    }
+
+    #[test]
+    fn e2e_git_list_entries_with_worktree() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_git(tmp.path(), &dir_name, &main_repo);
+
+        // Create a git worktree in the dwm directory
+        let ws_path = dwm_base.join(format!("{}/feat-branch", dir_name));
+        std::process::Command::new("git")
+            .args([
+                "-C",
+                main_repo.to_str().unwrap(),
+                "worktree",
+                "add",
+                ws_path.to_str().unwrap(),
+                "-b",
+                "feat-branch",
+            ])
+            .output()
+            .unwrap();
+
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert!(
+            entries.len() >= 2,
+            "should have main + worktree, got {}",
+            entries.len()
+        );
+
+        let main_entry = entries.iter().find(|e| e.is_main).unwrap();
+        assert_eq!(main_entry.name, "main-worktree");
+        assert_eq!(main_entry.path, main_repo);
+
+        let feat_entry = entries.iter().find(|e| e.name == "feat-branch").unwrap();
+        assert!(!feat_entry.is_main);
+        assert!(feat_entry.path.ends_with("feat-branch"));
+        assert!(feat_entry.bookmarks.contains(&"feat-branch".to_string()));
+    }
+
+    #[test]
+    fn e2e_git_new_and_delete_workspace() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let dwm_base = tmp.path().join("dwm");
+
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create a workspace
+        create(
+            &deps,
+            Some("test-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/test-ws", dir_name));
+        assert!(ws_dir.exists(), "workspace dir should exist after creation");
+
+        // List and verify it shows up
+        let backend2 = crate::git::GitBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let entries = list(&deps2).unwrap();
+        assert!(
+            entries.iter().any(|e| e.name == "test-ws"),
+            "test-ws should appear in listing"
+        );
+
+        // Delete the workspace
+        let backend3 = crate::git::GitBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        delete(
+            &deps3,
+            Some("test-ws".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap();
+        assert!(
+            !ws_dir.exists(),
+            "workspace dir should be removed after deletion"
+        );
+
+        // Verify it's gone from listing
+        let backend4 = crate::git::GitBackend;
+        let deps4 = WorkspaceDeps {
+            backend: Box::new(backend4),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps4).unwrap();
+        assert!(
+            !entries.iter().any(|e| e.name == "test-ws"),
+            "test-ws should not appear after deletion"
+        );
+    }
+
+    #[test]
+    fn e2e_git_sparse_workspace_checks_out_only_profile_dirs() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(repo_path.join("apps/web")).unwrap();
+        fs::create_dir_all(repo_path.join("apps/api")).unwrap();
+        fs::write(repo_path.join("apps/web/index.js"), "").unwrap();
+        fs::write(repo_path.join("apps/api/main.rs"), "").unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(&main_repo)
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["add", "."]);
+        git(&["commit", "-m", "apps"]);
+
+        let dwm_base = tmp.path().join("dwm");
+        let deps = WorkspaceDeps {
+            backend: Box::new(crate::git::GitBackend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let sparse = ["apps/web".to_string()];
+        create(
+            &deps,
+            Some("web".to_string()),
+            None,
+            None,
+            false,
+            &sparse,
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        let ws_dir = dwm_base.join(vcs::repo_dir_name(&main_repo)).join("web");
+        assert!(ws_dir.join("apps/web/index.js").exists());
+        assert!(!ws_dir.join("apps/api").exists());
+        // The main checkout keeps everything.
+        assert!(main_repo.join("apps/api/main.rs").exists());
+        let entries = list(&deps).unwrap();
+        let web = entries.iter().find(|e| e.name == "web").unwrap();
+        assert!(!web.working_copy.is_dirty(), "{:?}", web.working_copy);
+    }
+
+    #[test]
+    fn e2e_git_worktree_with_changes() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let dwm_base = tmp.path().join("dwm");
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace and make a commit in it
+        create(
+            &deps,
+            Some("feature".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/feature", dir_name));
+
+        // Add a file and commit in the worktree
+        fs::write(ws_dir.join("hello.txt"), "hello world\n").unwrap();
+        let ws_str = ws_dir.to_str().unwrap();
+        std::process::Command::new("git")
+            .args(["-C", ws_str, "add", "hello.txt"])
+            .output()
+            .unwrap();
+        std::process::Command::new("git")
+            .args(["-C", ws_str, "commit", "-m", "add hello"])
+            .output()
+            .unwrap();
+
+        // List and check that the feature workspace has diff stats
+        let backend2 = crate::git::GitBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps2).unwrap();
+        let feat = entries.iter().find(|e| e.name == "feature").unwrap();
+        assert_eq!(feat.description, "add hello");
+        assert!(
+            feat.diff_stat.insertions > 0 || feat.diff_stat.files_changed > 0,
+            "feature workspace should show changes vs trunk"
+        );
+    }
+
+    #[test]
+    fn e2e_git_rename_workspace() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let dwm_base = tmp.path().join("dwm");
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace
+        create(
+            &deps,
+            Some("old-name".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let old_path = dwm_base.join(format!("{}/old-name", dir_name));
+        assert!(old_path.exists());
+
+        // Rename it
+        let backend2 = crate::git::GitBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        rename(&deps2, "old-name", "new-name", ProgressOutput::Verbose).unwrap();
+
+        assert!(!old_path.exists(), "old dir should be gone");
+        assert!(
+            dwm_base.join(format!("{}/new-name", dir_name)).exists(),
+            "new dir should exist"
+        );
+
+        // Verify listing shows the new name
+        let backend3 = crate::git::GitBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps3).unwrap();
+        assert!(entries.iter().any(|e| e.name == "new-name"));
+        assert!(!entries.iter().any(|e| e.name == "old-name"));
+    }
+
+    #[test]
+    fn e2e_git_merge_split_repo_dirs_keeps_worktrees_registered() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&main_repo, &link).unwrap();
+
+        // A worktree made under an entry keyed by the symlinked path.
+        let dwm_base = setup_dwm_dir_git(tmp.path(), "link-00000000", &link);
+        let old_path = dwm_base.join("link-00000000/feat");
+        let out = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&link)
+            .args(["worktree", "add", "-b", "feat"])
+            .arg(&old_path)
+            .output()
+            .unwrap();
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+
+        let outcomes = merge_split_repo_dirs_in(&dwm_base, tmp.path(), vcs::detect_from_dwm_dir);
+        assert!(outcomes[0].is_ok(), "{:?}", outcomes);
+        let new_path = dwm_base.join(vcs::repo_dir_name(&main_repo)).join("feat");
+        assert!(new_path.is_dir());
+        assert!(!old_path.exists());
+
+        let out = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&main_repo)
+            .args(["worktree", "list", "--porcelain"])
+            .output()
+            .unwrap();
+        let worktrees = String::from_utf8_lossy(&out.stdout);
+        let new_path = new_path.canonicalize().unwrap();
+        assert!(
+            worktrees.contains(&format!("worktree {}", new_path.display())),
+            "{}",
+            worktrees
+        );
+    }
+
+    #[test]
+    fn e2e_git_rename_redirects_when_inside() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let dwm_base = tmp.path().join("dwm");
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace with a subdirectory
+        create(
+            &deps,
+            Some("my-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_path = dwm_base.join(format!("{}/my-ws", dir_name));
+        let subdir = ws_path.join("src");
+        fs::create_dir_all(&subdir).unwrap();
+
+        // Rename while cwd is inside the workspace
+        let backend2 = crate::git::GitBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: subdir,
+            dwm_base: dwm_base.clone(),
+        };
+        let redirect = rename(&deps2, "my-ws", "renamed-ws", ProgressOutput::Verbose).unwrap();
+        let redirect = redirect.expect("should redirect when cwd is inside renamed workspace");
+        assert_eq!(
+            redirect,
+            dwm_base.join(format!("{}/renamed-ws/src", dir_name))
+        );
+
+        // The new path should exist and contain the subdirectory
+        let new_ws = dwm_base.join(format!("{}/renamed-ws", dir_name));
+        assert!(new_ws.exists());
+        assert!(new_ws.join("src").exists());
+    }
+
+    fn jj_available() -> bool {
+        std::process::Command::new("jj")
+            .arg("--version")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    fn init_jj_repo(dir: &Path) -> PathBuf {
+        let dir_str = dir.to_str().unwrap();
+        std::process::Command::new("jj")
+            .args(["git", "init", dir_str])
+            .output()
+            .unwrap();
+        // Create a "main" bookmark so trunk() resolves
+        std::process::Command::new("jj")
+            .args([
+                "--repository",
+                dir_str,
+                "bookmark",
+                "create",
+                "main",
+                "-r",
+                "@-",
+            ])
+            .output()
+            .unwrap();
+        dir.canonicalize().unwrap()
+    }
+
+    fn setup_dwm_dir_jj(tmp: &Path, repo_name: &str, main_repo: &Path) -> PathBuf {
+        let dwm_base = tmp.join("dwm");
+        let rd = dwm_base.join(repo_name);
+        fs::create_dir_all(&rd).unwrap();
+        RepoMeta::new(main_repo, vcs::VcsType::Jj)
+            .save(&rd)
+            .unwrap();
+        dwm_base
+    }
+
+    #[test]
+    fn e2e_jj_list_entries_main_only() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert_eq!(entries.len(), 1, "should have default workspace entry");
+        assert!(entries[0].is_main);
+        assert_eq!(entries[0].name, "default");
+        assert_eq!(entries[0].path, main_repo);
+    }
+
+    #[test]
+    fn e2e_jj_list_entries_with_workspace() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        // Create a jj workspace in the dwm directory
+        let ws_path = dwm_base.join(format!("{}/feat-ws", dir_name));
+        std::process::Command::new("jj")
+            .args([
+                "--repository",
+                main_repo.to_str().unwrap(),
+                "workspace",
+                "add",
+                "--name",
+                "feat-ws",
+                ws_path.to_str().unwrap(),
+            ])
+            .output()
+            .unwrap();
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+
+        let entries = list(&deps).unwrap();
+        assert!(
+            entries.len() >= 2,
+            "should have default + workspace, got {}",
+            entries.len()
+        );
+
+        let main_entry = entries.iter().find(|e| e.is_main).unwrap();
+        assert_eq!(main_entry.name, "default");
+        assert_eq!(main_entry.path, main_repo);
+
+        let feat_entry = entries.iter().find(|e| e.name == "feat-ws").unwrap();
+        assert!(!feat_entry.is_main);
+        assert!(feat_entry.path.ends_with("feat-ws"));
+    }
+
+    #[test]
+    fn e2e_jj_new_and_delete_workspace() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create a workspace
+        create(
+            &deps,
+            Some("test-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/test-ws", dir_name));
+        assert!(ws_dir.exists(), "workspace dir should exist after creation");
+
+        // List and verify it shows up
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let entries = list(&deps2).unwrap();
+        assert!(
+            entries.iter().any(|e| e.name == "test-ws"),
+            "test-ws should appear in listing"
+        );
+
+        // Delete the workspace
+        let backend3 = crate::jj::JjBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        delete(
+            &deps3,
+            Some("test-ws".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap();
+        assert!(
+            !ws_dir.exists(),
+            "workspace dir should be removed after deletion"
+        );
+
+        // Verify it's gone from listing
+        let backend4 = crate::jj::JjBackend;
+        let deps4 = WorkspaceDeps {
+            backend: Box::new(backend4),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps4).unwrap();
+        assert!(
+            !entries.iter().any(|e| e.name == "test-ws"),
+            "test-ws should not appear after deletion"
+        );
+    }
+
+    #[test]
+    fn e2e_jj_workspace_with_spaces_in_name() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create a workspace with spaces in its name
+        create(
+            &deps,
+            Some("my cool feature".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/my cool feature", dir_name));
+        assert!(ws_dir.exists(), "workspace dir should exist after creation");
+
+        // List and verify it shows up
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let entries = list(&deps2).unwrap();
+        assert!(
+            entries.iter().any(|e| e.name == "my cool feature"),
+            "workspace with spaces should appear in listing, got: {:?}",
+            entries.iter().map(|e| &e.name).collect::<Vec<_>>()
+        );
+
+        // Switch to the workspace
+        let backend3 = crate::jj::JjBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let switch_path = find(&deps3, "my cool feature").unwrap();
+        assert_eq!(switch_path, ws_dir);
+
+        // Delete the workspace
+        let backend4 = crate::jj::JjBackend;
+        let deps4 = WorkspaceDeps {
+            backend: Box::new(backend4),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        delete(
+            &deps4,
+            Some("my cool feature".to_string()),
+            false,
+            ProgressOutput::Verbose,
+        )
+        .unwrap();
+        assert!(
+            !ws_dir.exists(),
+            "workspace dir should be removed after deletion"
+        );
+
+        // Verify it's gone from listing
+        let backend5 = crate::jj::JjBackend;
+        let deps5 = WorkspaceDeps {
+            backend: Box::new(backend5),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps5).unwrap();
+        assert!(
+            !entries.iter().any(|e| e.name == "my cool feature"),
+            "workspace with spaces should not appear after deletion"
+        );
+    }
+
+    #[test]
+    fn e2e_jj_workspace_with_changes() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace and make changes in it
+        create(
+            &deps,
+            Some("feature".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/feature", dir_name));
+
+        // Add a file (jj auto-tracks new files)
+        fs::write(ws_dir.join("hello.txt"), "hello world\n").unwrap();
+        // Set a description on the workspace's working copy
+        let ws_str = ws_dir.to_str().unwrap();
+        std::process::Command::new("jj")
+            .args(["--repository", ws_str, "describe", "-m", "add hello"])
+            .output()
+            .unwrap();
+
+        // List and check that the feature workspace has diff stats
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps2).unwrap();
+        let feat = entries.iter().find(|e| e.name == "feature").unwrap();
+        assert_eq!(feat.description.trim(), "add hello");
+        assert!(
+            feat.diff_stat.insertions > 0 || feat.diff_stat.files_changed > 0,
+            "feature workspace should show changes vs trunk: {:?}",
+            feat.diff_stat
+        );
+    }
+
+    #[test]
+    fn e2e_jj_rename_workspace() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace
+        create(
+            &deps,
+            Some("old-name".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let old_path = dwm_base.join(format!("{}/old-name", dir_name));
+        assert!(old_path.exists());
+
+        // Rename it
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        rename(&deps2, "old-name", "new-name", ProgressOutput::Verbose).unwrap();
+
+        assert!(!old_path.exists(), "old dir should be gone");
+        assert!(
+            dwm_base.join(format!("{}/new-name", dir_name)).exists(),
+            "new dir should exist"
+        );
+
+        // Verify listing shows the new name
+        let backend3 = crate::jj::JjBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps3).unwrap();
+        assert!(entries.iter().any(|e| e.name == "new-name"));
+        assert!(!entries.iter().any(|e| e.name == "old-name"));
+    }
+
+    #[test]
+    fn e2e_jj_rename_stale_workspace() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace
+        create(
+            &deps,
+            Some("my-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+
+        // Make the workspace stale by committing in the default workspace,
+        // which advances the operation log past what my-ws has seen.
+        let main_str = main_repo.to_str().unwrap();
+        fs::write(main_repo.join("file.txt"), "content\n").unwrap();
+        std::process::Command::new("jj")
+            .args(["--repository", main_str, "describe", "-m", "advance op log"])
+            .output()
+            .unwrap();
+
+        // Rename should succeed despite stale working copy
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        rename(&deps2, "my-ws", "renamed-ws", ProgressOutput::Verbose).unwrap();
+
+        assert!(!dwm_base.join(format!("{}/my-ws", dir_name)).exists());
+        assert!(dwm_base.join(format!("{}/renamed-ws", dir_name)).exists());
+
+        // Verify listing shows the new name
+        let backend3 = crate::jj::JjBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo,
+            dwm_base,
+        };
+        let entries = list(&deps3).unwrap();
+        assert!(entries.iter().any(|e| e.name == "renamed-ws"));
+        assert!(!entries.iter().any(|e| e.name == "my-ws"));
+    }
+
+    #[test]
+    fn e2e_git_switch_workspace() {
+        assert!(git_available(), "git must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_git_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+
+        let dwm_base = tmp.path().join("dwm");
+        let backend = crate::git::GitBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create a workspace
+        create(
+            &deps,
+            Some("switch-target".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/switch-target", dir_name));
+
+        // Switch to it
+        let backend2 = crate::git::GitBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let path = find(&deps2, "switch-target").unwrap();
+        assert_eq!(path, ws_dir);
+
+        // Switch to main
+        let backend3 = crate::git::GitBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+        let path = find(&deps3, "main-worktree").unwrap();
+        assert_eq!(path, main_repo);
+    }
+
+    #[test]
+    fn e2e_jj_switch_workspace() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create a workspace
+        create(
+            &deps,
+            Some("switch-target".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_dir = dwm_base.join(format!("{}/switch-target", dir_name));
+
+        // Switch to it
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+        let path = find(&deps2, "switch-target").unwrap();
+        assert_eq!(path, ws_dir);
+
+        // Switch to main (default)
+        let backend3 = crate::jj::JjBackend;
+        let deps3 = WorkspaceDeps {
+            backend: Box::new(backend3),
+            cwd: main_repo.clone(),
+            dwm_base,
+        };
+        let path = find(&deps3, "default").unwrap();
+        assert_eq!(path, main_repo);
+    }
+
+    #[test]
+    fn e2e_jj_rename_redirects_when_inside() {
+        assert!(jj_available(), "jj must be installed to run this test");
+        let tmp = tempfile::tempdir().unwrap();
+        let repo_path = tmp.path().join("repos/myrepo");
+        fs::create_dir_all(&repo_path).unwrap();
+        let main_repo = init_jj_repo(&repo_path);
+        let dir_name = vcs::repo_dir_name(&main_repo);
+        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
+
+        let backend = crate::jj::JjBackend;
+        let deps = WorkspaceDeps {
+            backend: Box::new(backend),
+            cwd: main_repo.clone(),
+            dwm_base: dwm_base.clone(),
+        };
+
+        // Create workspace with a subdirectory
+        create(
+            &deps,
+            Some("my-ws".to_string()),
+            None,
+            None,
+            false,
+            &[],
+            &NameConfig::default(),
+        )
+        .unwrap();
+        let ws_path = dwm_base.join(format!("{}/my-ws", dir_name));
+        let subdir = ws_path.join("src");
+        fs::create_dir_all(&subdir).unwrap();
+
+        // Rename while cwd is inside the workspace
+        let backend2 = crate::jj::JjBackend;
+        let deps2 = WorkspaceDeps {
+            backend: Box::new(backend2),
+            cwd: subdir,
+            dwm_base: dwm_base.clone(),
+        };
+        let redirect = rename(&deps2, "my-ws", "renamed-ws", ProgressOutput::Verbose).unwrap();
+        let redirect = redirect.expect("should redirect when cwd is inside renamed workspace");
+        assert_eq!(
+            redirect,
+            dwm_base.join(format!("{}/renamed-ws/src", dir_name))
+        );
+
+        // The new path should exist and contain the subdirectory
+        let new_ws = dwm_base.join(format!("{}/renamed-ws", dir_name));
+        assert!(new_ws.exists());
+        assert!(new_ws.join("src").exists());
+    }
+}
@@ -0,0 +1,196 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+
+/// Convert text containing ANSI SGR escape sequences (as produced by
+/// `git log --color=always` or `jj log --color=always`) into styled ratatui
+/// [`Text`]. Styles carry across line breaks like in a terminal; escape
+/// sequences other than SGR are dropped.
+pub fn to_text(input: &str) -> Text<'static> {
+    let mut lines = Vec::new();
+    let mut style = Style::default();
+    for raw_line in input.lines() {
+        let mut spans = Vec::new();
+        let mut buf = String::new();
+        let mut chars = raw_line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\x1b' {
+                buf.push(c);
+                continue;
+            }
+            if chars.peek() != Some(&'[') {
+                continue;
+            }
+            chars.next();
+            // CSI: parameter bytes up to a final byte in '@'..='~'.
+            let mut params = String::new();
+            let mut final_byte = None;
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    final_byte = Some(c);
+                    break;
+                }
+                params.push(c);
+            }
+            if final_byte != Some('m') {
+                continue;
+            }
+            if !buf.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut buf), style));
+            }
+            style = apply_sgr(style, &params);
+        }
+        if !buf.is_empty() {
+            spans.push(Span::styled(buf, style));
+        }
+        lines.push(Line::from(spans));
+    }
+    Text::from(lines)
+}
+
+/// Apply the SGR parameter list `params` (e.g. `"1;38;5;4"`) to `style`.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let codes: Vec<u16> = if params.is_empty() {
+        vec![0]
+    } else {
+        params
+            .split([';', ':'])
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    let mut iter = codes.into_iter();
+    while let Some(code) = iter.next() {
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            7 => style.add_modifier(Modifier::REVERSED),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            30..=37 => style.fg(basic_color(code - 30, false)),
+            90..=97 => style.fg(basic_color(code - 90, true)),
+            40..=47 => style.bg(basic_color(code - 40, false)),
+            100..=107 => style.bg(basic_color(code - 100, true)),
+            39 => style.fg(Color::Reset),
+            49 => style.bg(Color::Reset),
+            38 | 48 => {
+                let color = match iter.next() {
+                    Some(5) => iter.next().map(|n| Color::Indexed(n as u8)),
+                    Some(2) => {
+                        let (r, g, b) = (iter.next(), iter.next(), iter.next());
+                        Some(Color::Rgb(
+                            r.unwrap_or(0) as u8,
+                            g.unwrap_or(0) as u8,
+                            b.unwrap_or(0) as u8,
+                        ))
+                    }
+                    _ => None,
+                };
+                match (code, color) {
+                    (38, Some(c)) => style.fg(c),
+                    (48, Some(c)) => style.bg(c),
+                    _ => style,
+                }
+            }
+            _ => style,
+        };
+    }
+    style
+}
+
+/// Map an ANSI color index (0-7) to a ratatui color.
+fn basic_color(index: u16, bright: bool) -> Color {
+    match (index, bright) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (7, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(text: &Text) -> Vec<(String, Style)> {
+        text.lines
+            .iter()
+            .flat_map(|l| l.spans.iter())
+            .map(|s| (s.content.to_string(), s.style))
+            .collect()
+    }
+
+    #[test]
+    fn plain_text_passes_through() {
+        let text = to_text("hello\nworld");
+        assert_eq!(text.lines.len(), 2);
+        assert_eq!(spans(&text)[0], ("hello".to_string(), Style::default()));
+    }
+
+    #[test]
+    fn basic_foreground_and_reset() {
+        let text = to_text("\x1b[33mabc\x1b[0m def");
+        let s = spans(&text);
+        assert_eq!(
+            s[0],
+            ("abc".to_string(), Style::default().fg(Color::Yellow))
+        );
+        assert_eq!(s[1], (" def".to_string(), Style::default()));
+    }
+
+    #[test]
+    fn bold_and_bright_colors() {
+        let text = to_text("\x1b[1;94m*\x1b[m");
+        let s = spans(&text);
+        assert_eq!(
+            s[0].1,
+            Style::default()
+                .fg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD)
+        );
+    }
+
+    #[test]
+    fn indexed_and_rgb_colors() {
+        let text = to_text("\x1b[38;5;208ma\x1b[48;2;1;2;3mb");
+        let s = spans(&text);
+        assert_eq!(s[0].1, Style::default().fg(Color::Indexed(208)));
+        assert_eq!(
+            s[1].1,
+            Style::default()
+                .fg(Color::Indexed(208))
+                .bg(Color::Rgb(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn style_carries_across_lines() {
+        let text = to_text("\x1b[32mone\ntwo\x1b[0m");
+        assert_eq!(
+            text.lines[1].spans[0].style,
+            Style::default().fg(Color::Green)
+        );
+    }
+
+    #[test]
+    fn non_sgr_sequences_are_dropped() {
+        let text = to_text("a\x1b[2Kb\x1b[1Gc");
+        let content: String = spans(&text).into_iter().map(|(s, _)| s).collect();
+        assert_eq!(content, "abc");
+    }
+}
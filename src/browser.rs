@@ -0,0 +1,37 @@
+use anyhow::{Result, bail};
+use std::process::{Command, Stdio};
+
+/// Commands that open a URL in the default browser, tried in order after
+/// `$BROWSER`. Elsewhere `open` can be something else entirely, like
+/// Debian's `openvt`.
+const BROWSER_COMMANDS: &[&str] = if cfg!(target_os = "macos") {
+    &["open"]
+} else {
+    &["xdg-open", "wslview"]
+};
+
+/// Open `url` in the web browser: `$BROWSER` if set, else the first of
+/// [`BROWSER_COMMANDS`] that succeeds. The browser's own output is
+/// discarded, so it can't draw over the picker.
+pub fn open(url: &str) -> Result<()> {
+    let from_env = std::env::var("BROWSER")
+        .ok()
+        .filter(|b| !b.trim().is_empty());
+    let opened = from_env
+        .iter()
+        .map(String::as_str)
+        .chain(BROWSER_COMMANDS.iter().copied())
+        .any(|browser| {
+            Command::new(browser)
+                .arg(url)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        });
+    if !opened {
+        bail!("no browser found (set $BROWSER, or install xdg-open)");
+    }
+    Ok(())
+}
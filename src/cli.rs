@@ -1,10 +1,39 @@
 use clap::{Parser, Subcommand};
+use clap_complete::ArgValueCompleter;
+
+use crate::agent::AgentKind;
+use crate::completions::workspace_names;
+use crate::names::NamingScheme;
+use crate::shell::Shell;
 
 #[derive(Debug, Parser)]
-#[command(name = "dwm", about = "Dan's Workspace Manager", version)]
+#[command(
+    name = "dwm",
+    about = "Dan's Workspace Manager",
+    version,
+    disable_help_subcommand = true
+)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    /// Act on this tracked repo (by name or path) instead of the one
+    /// containing the current directory
+    #[arg(long, global = true, value_name = "NAME|PATH")]
+    pub repo: Option<String>,
+    /// Only print warnings and errors, not progress
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// Print debug detail, such as each VCS command run (-vv for more)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+}
+
+/// Parse `dwm recent`'s position, written as `2` or `-2`.
+fn parse_recent_index(s: &str) -> Result<usize, String> {
+    match s.trim_start_matches('-').parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("expected a position like -2, got '{}'", s)),
+    }
 }
 
 #[derive(Debug, Subcommand)]
@@ -17,25 +46,184 @@ pub enum Commands {
         #[arg(long, conflicts_with = "from")]
         at: Option<String>,
         /// Fork from an existing workspace's current change
-        #[arg(long, conflicts_with = "at")]
+        #[arg(long, conflicts_with = "at", add = ArgValueCompleter::new(workspace_names))]
         from: Option<String>,
+        /// Check out submodules in the new workspace
+        #[arg(long)]
+        recurse_submodules: bool,
+        /// Check out only the directories of this profile from the
+        /// `sparse_profiles` setting
+        #[arg(long, value_name = "PROFILE")]
+        sparse: Option<String>,
+        /// Name the workspace after this issue on the repo's forge, like
+        /// `1234-fix-login-timeout`, and link it to the issue
+        #[arg(long, value_name = "NUMBER")]
+        issue: Option<u64>,
+        /// Generate the name this way rather than the repo's usual one
+        #[arg(long, value_enum, value_name = "SCHEME", conflicts_with_all = ["name", "issue"])]
+        name_scheme: Option<NamingScheme>,
+    },
+    /// Register the current repo with dwm
+    Init {
+        /// VCS to use for a colocated jj+git repo (defaults to jj)
+        #[arg(long, value_parser = ["jj", "git"])]
+        vcs: Option<String>,
+        /// Branch (git) or revset (jj) to compare workspaces against,
+        /// instead of detecting main or master
+        #[arg(long, value_name = "BRANCH")]
+        trunk: Option<String>,
+        /// How `dwm new` names workspaces when no name is given
+        #[arg(long, value_enum)]
+        naming: Option<NamingScheme>,
     },
     /// List workspaces and pick one interactively
     List {
         /// Show workspaces across all repos
         #[arg(long)]
         all: bool,
+        /// Fetch trunk from the remote first, so merge status is current
+        #[arg(long)]
+        fetch: bool,
+        /// Print one tab-separated line per workspace instead of the picker
+        #[arg(long)]
+        plain: bool,
+        /// Print each workspace with a template like '{name}\t{path}\t{modified}' (implies --plain)
+        #[arg(long, value_name = "TEMPLATE")]
+        format: Option<String>,
+        /// Print every field in a stable tab-separated format for scripts
+        #[arg(long, conflicts_with = "format")]
+        porcelain: bool,
     },
     /// Print a non-interactive workspace summary
-    Status,
+    Status {
+        /// Fetch trunk from the remote first, so merge status is current
+        #[arg(long)]
+        fetch: bool,
+        /// Print every field in a stable tab-separated format for scripts
+        #[arg(long)]
+        porcelain: bool,
+    },
+    /// Show agent sessions across all repos, live when run in a terminal
+    Agents {
+        /// Print sessions as JSON
+        #[arg(long)]
+        json: bool,
+        /// Report how long agents in this workspace spent in each status
+        #[arg(long, value_name = "WORKSPACE", add = ArgValueCompleter::new(workspace_names))]
+        history: Option<String>,
+    },
+    /// Print the agent status of the current workspace
+    #[command(name = "agent-status")]
+    AgentStatus {
+        /// Print a compact badge like `⏳2 ▶1` for shell prompts
+        #[arg(long)]
+        prompt: bool,
+        /// Color the badge
+        #[arg(long, requires = "prompt")]
+        color: bool,
+    },
+    /// Print `repo:workspace` for shell prompts when inside a workspace
+    ///
+    /// Prints nothing outside a dwm workspace. Without --dirty it only reads
+    /// files under ~/.dwm and never the VCS, finishing within 10ms, so it's
+    /// fast enough for every prompt render. `dwm starship-config` prints a
+    /// starship module for it, and `dwm shell-setup --prompt p10k` a
+    /// powerlevel10k segment.
+    Prompt {
+        /// Add the agent badge, like `⏳2 ▶1`
+        #[arg(long)]
+        agents: bool,
+        /// Mark uncommitted changes with `*` (runs the VCS)
+        #[arg(long)]
+        dirty: bool,
+        /// Color the segment
+        #[arg(long)]
+        color: bool,
+    },
+    /// Print a starship custom module that shows `dwm prompt`, ready to paste
+    /// into starship.toml
+    #[command(name = "starship-config")]
+    StarshipConfig,
+    /// Remove agent status left behind by dead sessions and deleted repos, and
+    /// merge repo entries made through a symlinked path
+    Gc,
+    /// Keep every repo's workspace listing up to date in the background and
+    /// serve it over `~/.dwm/.daemon.sock`, so listings are instant
+    Daemon,
+    /// Report workspaces being created and deleted, and changes to their
+    /// agent status and diffs, as they happen
+    Watch {
+        /// Watch every tracked repo
+        #[arg(long)]
+        all: bool,
+        /// Print each event as a line of JSON on stdout
+        #[arg(long)]
+        json: bool,
+    },
+    /// Start an agent in a workspace, creating the workspace if needed
+    Launch {
+        /// Agent to start; its command can be set in `agent_commands`
+        agent: AgentKind,
+        /// Workspace to start it in (a new one if omitted or missing)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        workspace: Option<String>,
+    },
     /// Switch to a workspace by name
     Switch {
         /// Workspace name
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: String,
+    },
+    /// Open a workspace in its own tmux window, or outside tmux attach to
+    /// a session named after it
+    Tmux {
+        /// Workspace name
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
         name: String,
     },
+    /// Jump to the most recently modified workspace, like `cd -`
+    Recent {
+        /// Which one to jump to: 1 (the default) is the most recent, `-2`
+        /// or `2` the one before
+        #[arg(
+            allow_negative_numbers = true,
+            default_value = "1",
+            value_parser = parse_recent_index
+        )]
+        nth: usize,
+    },
+    /// Run a command inside a workspace, e.g. `dwm exec feat-x -- cargo test`
+    Exec {
+        /// Workspace to run it in
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: String,
+        /// Command and its arguments, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Open a workspace in an editor or IDE without changing directory
+    Open {
+        /// Workspace to open (defaults to the current one)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: Option<String>,
+        /// Editor command to use instead of the configured one, e.g. `zed`
+        /// or `idea {path}`
+        #[arg(long, value_name = "COMMAND")]
+        with: Option<String>,
+    },
+    /// Open a workspace in VS Code through a generated .code-workspace file
+    Code {
+        /// Workspace to open (defaults to the current one)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: Option<String>,
+        /// Also add the main repo as a folder, to edit alongside trunk
+        #[arg(long)]
+        main: bool,
+    },
     /// Rename a workspace
     Rename {
         /// New name (or old name if two args given)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
         name: String,
         /// New name when renaming a different workspace
         new_name: Option<String>,
@@ -43,18 +231,106 @@ pub enum Commands {
     /// Delete a workspace (by name, or the current one if omitted)
     Delete {
         /// Workspace name to delete
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
         name: Option<String>,
+        /// Delete even if the workspace has uncommitted changes or untracked
+        /// files
+        #[arg(long)]
+        force: bool,
+        /// Delete every stale workspace: merged into trunk, or untouched for
+        /// 30 days
+        #[arg(long, conflicts_with = "name")]
+        all_stale: bool,
+        /// Delete every workspace merged into trunk
+        #[arg(long, conflicts_with = "name")]
+        merged: bool,
+        /// Don't ask for confirmation before a bulk delete
+        #[arg(short, long)]
+        yes: bool,
     },
-    /// Process a Claude Code hook event (used internally by hooks)
+    /// Push a workspace's branch or bookmark to the default remote
+    Push {
+        /// Workspace to push (the current one if omitted)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: Option<String>,
+    },
+    /// Rebase a workspace onto the latest trunk
+    Sync {
+        /// Workspace to rebase (the current one if omitted)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: Option<String>,
+    },
+    /// Push a workspace and open its pull request, creating one if needed
+    Pr {
+        /// Workspace to open a pull request for (the current one if omitted)
+        #[arg(add = ArgValueCompleter::new(workspace_names))]
+        name: Option<String>,
+        /// Also copy the pull request's URL to the clipboard
+        #[arg(long)]
+        copy: bool,
+    },
+    /// Process an agent hook event (used internally by hooks)
     #[command(name = "hook-handler", hide = true)]
-    HookHandler,
-    /// Set up Claude Code hooks for agent status tracking
+    HookHandler {
+        /// Agent sending the event; guessed from the payload if omitted
+        #[arg(long, value_enum)]
+        agent: Option<AgentKind>,
+        /// Event payload, for agents that pass it as an argument instead of
+        /// on stdin
+        payload: Option<String>,
+    },
+    /// Set up agent hooks for agent status tracking
     #[command(name = "agent-setup", hide = true)]
-    AgentSetup,
+    AgentSetup {
+        /// Set up only this agent
+        #[arg(long, value_enum)]
+        agent: Option<AgentKind>,
+        /// Hook Claude Code into the current repo's .claude/settings.json
+        /// instead of your global settings
+        #[arg(long, conflicts_with = "agent")]
+        project: bool,
+        /// With --project, use the uncommitted .claude/settings.local.json
+        #[arg(long, requires = "project")]
+        local: bool,
+        /// Remove dwm's hooks instead of adding them
+        #[arg(long)]
+        uninstall: bool,
+    },
     /// Run interactive setup for shell integration and agent hooks
     Setup,
     /// Print the current version
     Version,
+    /// Read or change settings in ~/.dwm/config.json
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// List, forget, or rename the repos dwm tracks
+    Repos {
+        #[command(subcommand)]
+        command: ReposCommand,
+    },
+    /// Show help for a command, or a guide to a topic: workflows, shell,
+    /// agents, vcs, or scripting
+    Help {
+        /// Topic, or command (like `repos list`)
+        topic: Vec<String>,
+    },
+    /// Print the man page, or write one per command into a directory
+    Man {
+        /// Write dwm.1 and a page per command here instead of printing
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<std::path::PathBuf>,
+    },
+    /// Print a completion script for bash, zsh, fish, PowerShell, or Elvish
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Record a visit to the workspace at `path` (called by the shell
+    /// wrappers after each cd)
+    #[command(name = "_on-cd", hide = true)]
+    OnCd { path: std::path::PathBuf },
     /// Print shell integration wrapper
     #[command(name = "shell-setup", hide = true)]
     ShellSetup {
@@ -70,6 +346,59 @@ pub enum Commands {
         /// Emit fish wrapper
         #[arg(long, group = "shell_type")]
         fish: bool,
+        /// Emit nushell wrapper
+        #[arg(long, group = "shell_type")]
+        nushell: bool,
+        /// Emit PowerShell wrapper
+        #[arg(long, group = "shell_type")]
+        powershell: bool,
+        /// Emit Elvish wrapper
+        #[arg(long, group = "shell_type")]
+        elvish: bool,
+        /// Emit xonsh wrapper
+        #[arg(long, group = "shell_type")]
+        xonsh: bool,
+        /// Print a prompt config snippet for `dwm prompt` instead
+        #[arg(long, value_name = "THEME", conflicts_with = "shell_type")]
+        prompt: Option<crate::prompt::PromptTheme>,
+        /// Remove the wrapper and completions from your shell config (every
+        /// shell's, unless one is given)
+        #[arg(long, conflicts_with = "prompt")]
+        uninstall: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print every setting and its value
+    List,
+    /// Print one setting, e.g. `editor` or `stale_timeouts.waiting`
+    Get { key: String },
+    /// Change a setting; the value is read as JSON, or else as a string, and
+    /// `null` removes it
+    Set { key: String, value: String },
+    /// Open the config file in $VISUAL or $EDITOR
+    Edit,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ReposCommand {
+    /// Print each tracked repo's path, VCS, workspace count, and disk usage
+    List,
+    /// Stop tracking a repo and delete its workspaces (the repo itself is kept)
+    Forget {
+        /// Repo name, as shown by `dwm repos list`
+        repo: String,
+        /// Don't ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Change the name dwm shows for a repo
+    Rename {
+        /// Repo name, as shown by `dwm repos list`
+        repo: String,
+        /// New name
+        name: String,
     },
 }
 
@@ -86,16 +415,178 @@ mod tests {
         );
     }
 
+    #[test]
+    fn repo_flag_is_global() {
+        let cli = Cli::try_parse_from(["dwm", "new", "--repo", "backend", "fix-auth"]).unwrap();
+        assert_eq!(cli.repo.as_deref(), Some("backend"));
+        assert!(
+            matches!(cli.command, Some(Commands::New { name: Some(n), .. }) if n == "fix-auth")
+        );
+        let cli = Cli::try_parse_from(["dwm", "--repo", "../api"]).unwrap();
+        assert_eq!(cli.repo.as_deref(), Some("../api"));
+        assert!(cli.command.is_none());
+    }
+
     #[test]
     fn explicit_list_subcommand() {
         let cli = Cli::try_parse_from(["dwm", "list"]).unwrap();
-        assert!(matches!(cli.command, Some(Commands::List { all: false })));
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List { all: false, .. })
+        ));
     }
 
     #[test]
     fn list_all_flag() {
         let cli = Cli::try_parse_from(["dwm", "list", "--all"]).unwrap();
-        assert!(matches!(cli.command, Some(Commands::List { all: true })));
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List { all: true, .. })
+        ));
+    }
+
+    #[test]
+    fn fetch_flags() {
+        let cli = Cli::try_parse_from(["dwm", "list", "--all", "--fetch"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List {
+                all: true,
+                fetch: true,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "status", "--fetch"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status {
+                fetch: true,
+                porcelain: false
+            })
+        ));
+    }
+
+    #[test]
+    fn list_plain_and_format_flags() {
+        let cli = Cli::try_parse_from(["dwm", "list", "--plain"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::List {
+                plain: true,
+                format: None,
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "list", "--format", "{name}\\t{path}"]).unwrap();
+        match cli.command {
+            Some(Commands::List { plain, format, .. }) => {
+                assert!(!plain);
+                assert_eq!(format.as_deref(), Some("{name}\\t{path}"));
+            }
+            _ => panic!("expected List"),
+        }
+    }
+
+    #[test]
+    fn push_and_sync_take_optional_name() {
+        let cli = Cli::try_parse_from(["dwm", "push", "feat"]).unwrap();
+        match cli.command {
+            Some(Commands::Push { name }) => assert_eq!(name.as_deref(), Some("feat")),
+            other => panic!("expected Push, got {:?}", other),
+        }
+        let cli = Cli::try_parse_from(["dwm", "sync"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Sync { name: None })));
+        let cli = Cli::try_parse_from(["dwm", "pr", "feat", "--copy"]).unwrap();
+        match cli.command {
+            Some(Commands::Pr { name, copy }) => {
+                assert_eq!(name.as_deref(), Some("feat"));
+                assert!(copy);
+            }
+            other => panic!("expected Pr, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn init_vcs_flag() {
+        let cli = Cli::try_parse_from(["dwm", "init", "--vcs", "git"]).unwrap();
+        match cli.command {
+            Some(Commands::Init { vcs, .. }) => assert_eq!(vcs.as_deref(), Some("git")),
+            other => panic!("expected Init, got {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["dwm", "init", "--vcs", "hg"]).is_err());
+    }
+
+    #[test]
+    fn init_takes_trunk_and_naming() {
+        let cli =
+            Cli::try_parse_from(["dwm", "init", "--trunk", "develop", "--naming", "numbered"])
+                .unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Init { vcs: None, trunk: Some(trunk), naming: Some(NamingScheme::Numbered) })
+                if trunk == "develop"
+        ));
+        assert!(Cli::try_parse_from(["dwm", "init", "--naming", "alphabetical"]).is_err());
+    }
+
+    #[test]
+    fn agent_setup_project_flags() {
+        let cli = Cli::try_parse_from(["dwm", "agent-setup", "--project", "--local"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::AgentSetup {
+                agent: None,
+                project: true,
+                local: true,
+                uninstall: false
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "agent-setup", "--local"]).is_err());
+        assert!(
+            Cli::try_parse_from(["dwm", "agent-setup", "--project", "--agent", "codex"]).is_err()
+        );
+    }
+
+    #[test]
+    fn gc_subcommand_parses() {
+        let cli = Cli::try_parse_from(["dwm", "gc"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Gc)));
+        let cli = Cli::try_parse_from(["dwm", "daemon"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Daemon)));
+        let cli = Cli::try_parse_from(["dwm", "watch", "--json", "--all"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Watch {
+                all: true,
+                json: true
+            })
+        ));
+    }
+
+    #[test]
+    fn agent_status_subcommand_parses() {
+        let cli = Cli::try_parse_from(["dwm", "agent-status", "--prompt", "--color"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::AgentStatus {
+                prompt: true,
+                color: true
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "agent-status", "--color"]).is_err());
+    }
+
+    #[test]
+    fn launch_subcommand_parses() {
+        let cli = Cli::try_parse_from(["dwm", "launch", "codex", "feat-x"]).unwrap();
+        match cli.command {
+            Some(Commands::Launch { agent, workspace }) => {
+                assert_eq!(agent, AgentKind::Codex);
+                assert_eq!(workspace.as_deref(), Some("feat-x"));
+            }
+            other => panic!("expected Launch, got {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["dwm", "launch", "vim"]).is_err());
     }
 
     #[test]
@@ -114,7 +605,7 @@ mod tests {
     fn new_subcommand_parses() {
         let cli = Cli::try_parse_from(["dwm", "new", "my-ws"]).unwrap();
         assert!(
-            matches!(cli.command, Some(Commands::New { name: Some(n), at: None, from: None }) if n == "my-ws")
+            matches!(cli.command, Some(Commands::New { name: Some(n), at: None, from: None, .. }) if n == "my-ws")
         );
     }
 
@@ -122,7 +613,7 @@ mod tests {
     fn new_with_at_flag() {
         let cli = Cli::try_parse_from(["dwm", "new", "--at", "abc123"]).unwrap();
         assert!(
-            matches!(cli.command, Some(Commands::New { name: None, at: Some(r), from: None }) if r == "abc123")
+            matches!(cli.command, Some(Commands::New { name: None, at: Some(r), from: None, .. }) if r == "abc123")
         );
     }
 
@@ -130,7 +621,7 @@ mod tests {
     fn new_with_from_flag() {
         let cli = Cli::try_parse_from(["dwm", "new", "--from", "other-ws"]).unwrap();
         assert!(
-            matches!(cli.command, Some(Commands::New { name: None, at: None, from: Some(f) }) if f == "other-ws")
+            matches!(cli.command, Some(Commands::New { name: None, at: None, from: Some(f), .. }) if f == "other-ws")
         );
     }
 
@@ -138,7 +629,56 @@ mod tests {
     fn new_with_from_and_name() {
         let cli = Cli::try_parse_from(["dwm", "new", "my-ws", "--from", "other-ws"]).unwrap();
         assert!(
-            matches!(cli.command, Some(Commands::New { name: Some(n), at: None, from: Some(f) }) if n == "my-ws" && f == "other-ws")
+            matches!(cli.command, Some(Commands::New { name: Some(n), at: None, from: Some(f), .. }) if n == "my-ws" && f == "other-ws")
+        );
+    }
+
+    #[test]
+    fn new_with_recurse_submodules() {
+        let cli = Cli::try_parse_from(["dwm", "new", "--recurse-submodules"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::New {
+                recurse_submodules: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn new_with_sparse_profile() {
+        let cli = Cli::try_parse_from(["dwm", "new", "ws", "--sparse", "web"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::New { sparse: Some(p), .. }) if p == "web"));
+    }
+
+    #[test]
+    fn new_with_issue() {
+        let cli = Cli::try_parse_from(["dwm", "new", "--issue", "1234"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::New {
+                name: None,
+                issue: Some(1234),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "new", "--issue", "#12"]).is_err());
+    }
+
+    #[test]
+    fn new_with_name_scheme() {
+        let cli = Cli::try_parse_from(["dwm", "new", "--name-scheme", "dated"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::New {
+                name: None,
+                name_scheme: Some(NamingScheme::Dated),
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "new", "ws", "--name-scheme", "numbered"]).is_err());
+        assert!(
+            Cli::try_parse_from(["dwm", "new", "--issue", "3", "--name-scheme", "random"]).is_err()
         );
     }
 
@@ -151,7 +691,25 @@ mod tests {
     #[test]
     fn delete_subcommand_parses() {
         let cli = Cli::try_parse_from(["dwm", "delete", "foo"]).unwrap();
-        assert!(matches!(cli.command, Some(Commands::Delete { name: Some(n) }) if n == "foo"));
+        assert!(
+            matches!(cli.command, Some(Commands::Delete { name: Some(n), force: false, .. }) if n == "foo")
+        );
+    }
+
+    #[test]
+    fn delete_bulk_flags_parse() {
+        let cli = Cli::try_parse_from(["dwm", "delete", "--all-stale", "--merged", "-y"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Delete {
+                name: None,
+                all_stale: true,
+                merged: true,
+                yes: true,
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "delete", "foo", "--merged"]).is_err());
     }
 
     #[test]
@@ -163,7 +721,22 @@ mod tests {
     #[test]
     fn status_subcommand_parses() {
         let cli = Cli::try_parse_from(["dwm", "status"]).unwrap();
-        assert!(matches!(cli.command, Some(Commands::Status)));
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status {
+                fetch: false,
+                porcelain: false
+            })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "status", "--porcelain"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Status {
+                porcelain: true,
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "list", "--porcelain", "--format", "{name}"]).is_err());
     }
 
     #[test]
@@ -174,6 +747,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn config_subcommands_parse() {
+        let cli = Cli::try_parse_from(["dwm", "config", "set", "keys.delete", "x"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config { command: ConfigCommand::Set { key, value } })
+                if key == "keys.delete" && value == "x"
+        ));
+        let cli = Cli::try_parse_from(["dwm", "config", "edit"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config {
+                command: ConfigCommand::Edit
+            })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "config"]).is_err());
+    }
+
+    #[test]
+    fn exec_takes_a_command_after_dashes() {
+        let cli =
+            Cli::try_parse_from(["dwm", "exec", "feat-x", "--", "cargo", "test", "--all"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Exec { name, command }) if name == "feat-x" && command == ["cargo", "test", "--all"]
+        ));
+        assert!(Cli::try_parse_from(["dwm", "exec", "feat-x"]).is_err());
+    }
+
+    #[test]
+    fn open_takes_optional_name_and_editor() {
+        let cli = Cli::try_parse_from(["dwm", "open"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Open {
+                name: None,
+                with: None
+            })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "open", "feat-x", "--with", "idea {path}"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Open { name: Some(name), with: Some(with) })
+                if name == "feat-x" && with == "idea {path}"
+        ));
+    }
+
+    #[test]
+    fn recent_takes_a_position() {
+        let nth = |args: &[&str]| match Cli::try_parse_from(args).unwrap().command {
+            Some(Commands::Recent { nth }) => nth,
+            _ => panic!("expected Recent"),
+        };
+        assert_eq!(nth(&["dwm", "recent"]), 1);
+        assert_eq!(nth(&["dwm", "recent", "-2"]), 2);
+        assert_eq!(nth(&["dwm", "recent", "3"]), 3);
+        assert!(Cli::try_parse_from(["dwm", "recent", "0"]).is_err());
+        assert!(Cli::try_parse_from(["dwm", "recent", "x"]).is_err());
+    }
+
+    #[test]
+    fn repos_subcommands_parse() {
+        let cli = Cli::try_parse_from(["dwm", "repos", "list"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Repos {
+                command: ReposCommand::List
+            })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "repos", "forget", "api", "-y"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Repos { command: ReposCommand::Forget { repo, yes: true } }) if repo == "api"
+        ));
+        let cli = Cli::try_parse_from(["dwm", "repos", "rename", "api", "backend"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Repos { command: ReposCommand::Rename { repo, name } })
+                if repo == "api" && name == "backend"
+        ));
+    }
+
+    #[test]
+    fn completions_take_a_shell() {
+        let cli = Cli::try_parse_from(["dwm", "completions", "zsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Completions { shell: Shell::Zsh })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn help_and_man_subcommands_parse() {
+        let cli = Cli::try_parse_from(["dwm", "help", "repos", "list"]).unwrap();
+        assert!(
+            matches!(cli.command, Some(Commands::Help { topic }) if topic == ["repos", "list"])
+        );
+        let cli = Cli::try_parse_from(["dwm", "help"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Help { topic }) if topic.is_empty()));
+        let cli = Cli::try_parse_from(["dwm", "man", "--out-dir", "man"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Man { out_dir: Some(dir) }) if dir == std::path::Path::new("man")
+        ));
+    }
+
     #[test]
     fn shell_setup_subcommand_parses() {
         let cli = Cli::try_parse_from(["dwm", "shell-setup"]).unwrap();
@@ -183,7 +863,13 @@ mod tests {
                 posix: false,
                 bash: false,
                 zsh: false,
-                fish: false
+                fish: false,
+                nushell: false,
+                powershell: false,
+                elvish: false,
+                xonsh: false,
+                prompt: None,
+                uninstall: false
             })
         ));
     }
@@ -197,6 +883,94 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn shell_setup_nushell_flag() {
+        let cli = Cli::try_parse_from(["dwm", "shell-setup", "--nushell"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ShellSetup { nushell: true, .. })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "shell-setup", "--nushell", "--fish"]).is_err());
+    }
+
+    #[test]
+    fn prompt_flags_parse() {
+        let cli = Cli::try_parse_from(["dwm", "starship-config"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::StarshipConfig)));
+        let cli = Cli::try_parse_from(["dwm", "prompt", "--agents", "--dirty"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Prompt {
+                agents: true,
+                dirty: true,
+                color: false
+            })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "shell-setup", "--prompt", "powerlevel10k"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ShellSetup {
+                prompt: Some(crate::prompt::PromptTheme::P10k),
+                ..
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["dwm", "shell-setup", "--prompt", "starship", "--fish"]).is_err()
+        );
+    }
+
+    #[test]
+    fn shell_setup_powershell_flag() {
+        let cli = Cli::try_parse_from(["dwm", "shell-setup", "--powershell"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ShellSetup {
+                powershell: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn shell_setup_elvish_and_xonsh_flags() {
+        let cli = Cli::try_parse_from(["dwm", "shell-setup", "--elvish"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ShellSetup { elvish: true, .. })
+        ));
+        let cli = Cli::try_parse_from(["dwm", "shell-setup", "--xonsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ShellSetup { xonsh: true, .. })
+        ));
+        assert!(Cli::try_parse_from(["dwm", "shell-setup", "--elvish", "--xonsh"]).is_err());
+    }
+
+    #[test]
+    fn setup_commands_take_uninstall() {
+        let cli = Cli::try_parse_from(["dwm", "shell-setup", "--uninstall", "--zsh"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::ShellSetup {
+                uninstall: true,
+                zsh: true,
+                ..
+            })
+        ));
+        assert!(
+            Cli::try_parse_from(["dwm", "shell-setup", "--uninstall", "--prompt", "starship"])
+                .is_err()
+        );
+        let cli = Cli::try_parse_from(["dwm", "agent-setup", "--uninstall"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::AgentSetup {
+                uninstall: true,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn shell_setup_bash_flag() {
         let cli = Cli::try_parse_from(["dwm", "shell-setup", "--bash"]).unwrap();
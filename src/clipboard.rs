@@ -0,0 +1,92 @@
+use anyhow::{Result, bail};
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Native clipboard commands, tried in order. Each reads the text on stdin.
+const CLIPBOARD_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Copy `text` to the system clipboard.
+///
+/// Sends an OSC 52 escape sequence to the terminal on stderr, which works
+/// over SSH and in tmux with `set-clipboard on`, and also pipes the text to
+/// the first native clipboard command that succeeds, for terminals that
+/// ignore OSC 52. Fails only if neither route is available.
+pub fn copy(text: &str) -> Result<()> {
+    let mut stderr = std::io::stderr();
+    let sent_osc52 =
+        stderr.is_terminal() && write!(stderr, "{}", osc52(text)).is_ok() && stderr.flush().is_ok();
+    if copy_native(text) || sent_osc52 {
+        Ok(())
+    } else {
+        bail!("no clipboard available (install pbcopy, wl-copy, xclip, or xsel)")
+    }
+}
+
+/// Pipe `text` to the first clipboard command that exits successfully.
+fn copy_native(text: &str) -> bool {
+    CLIPBOARD_COMMANDS.iter().any(|argv| {
+        let Ok(mut child) = Command::new(argv[0])
+            .args(&argv[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return false;
+        };
+        let wrote = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        child.wait().is_ok_and(|status| status.success()) && wrote
+    })
+}
+
+/// The OSC 52 sequence asking the terminal to set the clipboard to `text`.
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_pads_partial_chunks() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"/tmp/ws1"), "L3RtcC93czE=");
+    }
+
+    #[test]
+    fn osc52_wraps_encoded_text() {
+        assert_eq!(osc52("foo"), "\x1b]52;c;Zm9v\x07");
+    }
+}
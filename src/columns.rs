@@ -0,0 +1,217 @@
+use ratatui::layout::Constraint;
+use serde::{Deserialize, Serialize};
+
+use crate::forge::Shown;
+
+/// A column in the workspace picker table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    /// Repository name (only shown in the `--all` picker).
+    Repo,
+    Name,
+    Change,
+    Description,
+    Bookmarks,
+    Modified,
+    Changes,
+    /// The branch's open pull request (with the `pr_status` setting).
+    Pr,
+    /// CI state of the head commit (with the `ci_status` setting).
+    Ci,
+    Agent,
+}
+
+impl Column {
+    /// Header text for this column.
+    pub fn title(self) -> &'static str {
+        match self {
+            Column::Repo => "Repo",
+            Column::Name => "Name",
+            Column::Change => "Change",
+            Column::Description => "Description",
+            Column::Bookmarks => "Bookmarks",
+            Column::Modified => "Modified",
+            Column::Changes => "Changes",
+            Column::Pr => "PR",
+            Column::Ci => "CI",
+            Column::Agent => "Agent",
+        }
+    }
+
+    /// Relative share of the free space this column gets when no explicit
+    /// width is configured.
+    fn weight(self) -> u16 {
+        match self {
+            Column::Repo => 10,
+            Column::Name => 14,
+            Column::Change => 8,
+            Column::Description => 27,
+            Column::Bookmarks => 13,
+            Column::Modified => 10,
+            Column::Changes => 12,
+            Column::Pr => 14,
+            Column::Ci => 10,
+            Column::Agent => 16,
+        }
+    }
+}
+
+/// A configured column: either just its name, or its name with a fixed
+/// width in terminal cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ColumnSpec {
+    Auto(Column),
+    Sized { name: Column, width: u16 },
+}
+
+impl ColumnSpec {
+    pub fn column(self) -> Column {
+        match self {
+            ColumnSpec::Auto(c) => c,
+            ColumnSpec::Sized { name, .. } => name,
+        }
+    }
+
+    /// Layout constraint for this column: a fixed length if a width was
+    /// configured, otherwise a weighted share of the remaining space.
+    pub fn constraint(self) -> Constraint {
+        match self {
+            ColumnSpec::Auto(c) => Constraint::Fill(c.weight()),
+            ColumnSpec::Sized { width, .. } => Constraint::Length(width),
+        }
+    }
+}
+
+/// Columns shown by default, in order. `Repo` is dropped in the single-repo
+/// picker.
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Repo,
+    Column::Name,
+    Column::Change,
+    Column::Description,
+    Column::Bookmarks,
+    Column::Modified,
+    Column::Changes,
+    Column::Agent,
+];
+
+/// The columns shown by default with forge state on: [`DEFAULT_COLUMNS`]
+/// with [`Column::Pr`] and [`Column::Ci`] after `Changes`, as `shown`.
+pub fn defaults_with_forge(shown: Shown) -> Vec<ColumnSpec> {
+    DEFAULT_COLUMNS
+        .iter()
+        .flat_map(|&c| match c {
+            Column::Changes => [
+                Some(c),
+                shown.prs.then_some(Column::Pr),
+                shown.ci.then_some(Column::Ci),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            _ => vec![c],
+        })
+        .map(ColumnSpec::Auto)
+        .collect()
+}
+
+/// Resolve the columns for a picker from the configured list (or the
+/// defaults). `Repo` is only kept when `multi_repo` is set. Falls back to the
+/// defaults if nothing would be left to show.
+pub fn resolve(configured: Option<&[ColumnSpec]>, multi_repo: bool) -> Vec<ColumnSpec> {
+    let keep = |spec: &ColumnSpec| multi_repo || spec.column() != Column::Repo;
+    let specs: Vec<ColumnSpec> = configured
+        .unwrap_or_default()
+        .iter()
+        .copied()
+        .filter(keep)
+        .collect();
+    if !specs.is_empty() {
+        return specs;
+    }
+    DEFAULT_COLUMNS
+        .iter()
+        .map(|&c| ColumnSpec::Auto(c))
+        .filter(keep)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_drop_repo_in_single_repo_mode() {
+        let single = resolve(None, false);
+        assert_eq!(single.len(), DEFAULT_COLUMNS.len() - 1);
+        assert!(single.iter().all(|s| s.column() != Column::Repo));
+        let multi = resolve(None, true);
+        assert_eq!(multi[0].column(), Column::Repo);
+    }
+
+    #[test]
+    fn configured_columns_keep_order() {
+        let specs: Vec<ColumnSpec> =
+            serde_json::from_str(r#"["description", {"name": "name", "width": 20}, "agent"]"#)
+                .unwrap();
+        let cols = resolve(Some(&specs), false);
+        let names: Vec<Column> = cols.iter().map(|s| s.column()).collect();
+        assert_eq!(
+            names,
+            vec![Column::Description, Column::Name, Column::Agent]
+        );
+        assert_eq!(cols[1].constraint(), Constraint::Length(20));
+        assert_eq!(cols[0].constraint(), Constraint::Fill(27));
+    }
+
+    #[test]
+    fn repo_only_config_falls_back_to_defaults_in_single_repo_mode() {
+        let specs = vec![ColumnSpec::Auto(Column::Repo)];
+        assert_eq!(
+            resolve(Some(&specs), false).len(),
+            DEFAULT_COLUMNS.len() - 1
+        );
+        assert_eq!(resolve(Some(&specs), true).len(), 1);
+    }
+
+    #[test]
+    fn forge_defaults_add_pr_and_ci_after_changes() {
+        let specs = defaults_with_forge(Shown {
+            prs: true,
+            ci: false,
+        });
+        assert_eq!(specs.len(), DEFAULT_COLUMNS.len() + 1);
+        let names: Vec<Column> = resolve(Some(&specs), false)
+            .iter()
+            .map(|s| s.column())
+            .collect();
+        let changes = names.iter().position(|&c| c == Column::Changes).unwrap();
+        assert_eq!(names[changes + 1], Column::Pr);
+        assert_eq!(
+            serde_json::from_str::<Column>(r#""pr""#).unwrap(),
+            Column::Pr
+        );
+        let both = defaults_with_forge(Shown {
+            prs: true,
+            ci: true,
+        });
+        let changes = both
+            .iter()
+            .position(|s| s.column() == Column::Changes)
+            .unwrap();
+        assert_eq!(both[changes + 1].column(), Column::Pr);
+        assert_eq!(both[changes + 2].column(), Column::Ci);
+        let ci_only = defaults_with_forge(Shown {
+            prs: false,
+            ci: true,
+        });
+        assert_eq!(ci_only[changes + 1].column(), Column::Ci);
+    }
+
+    #[test]
+    fn unknown_column_fails_to_parse() {
+        assert!(serde_json::from_str::<Vec<ColumnSpec>>(r#"["bogus"]"#).is_err());
+    }
+}
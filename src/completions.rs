@@ -0,0 +1,257 @@
+use anyhow::{Result, bail};
+use clap::CommandFactory;
+use clap_complete::CompleteEnv;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::{Bash, Elvish, EnvCompleter, Fish, Powershell, Shells, Zsh};
+use std::ffi::{OsStr, OsString};
+
+use crate::cli::Cli;
+use crate::shell::Shell;
+
+/// Environment variable the completion scripts set, naming the shell, when
+/// they run dwm to complete a command line.
+const COMPLETE_VAR: &str = "DWM_COMPLETE";
+
+/// Shells dwm completes in: clap's, plus nushell for its wrapper.
+const SHELLS: Shells<'static> = Shells(&[&Bash, &Elvish, &Fish, &Powershell, &Zsh, &Nushell]);
+
+/// Answer a completion script's request and exit, if this run is one.
+/// Call first thing in `main`, before anything is printed.
+pub fn complete_from_env() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .shells(SHELLS)
+        .complete();
+}
+
+/// Completes workspace arguments to the names of the current repo's
+/// workspaces starting with `current`, main one first.
+pub fn workspace_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    crate::workspace::completion_names()
+        .into_iter()
+        .filter(|name| name.starts_with(current.as_ref()))
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// The script registering dwm's completions with `shell`, which runs dwm
+/// (by its full path, skipping the wrapper) for each completion.
+fn registration(shell: &dyn EnvCompleter) -> String {
+    let completer = std::env::current_exe()
+        .map(|exe| exe.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "dwm".to_string());
+    let mut script = Vec::new();
+    // Writing to a Vec can't fail.
+    let _ = shell.write_registration(COMPLETE_VAR, "dwm", "dwm", &completer, &mut script);
+    String::from_utf8_lossy(&script).trim_end().to_string()
+}
+
+/// The completion script for `shell`, if dwm has one.
+fn completion_script(shell: Shell) -> Option<String> {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Zsh => &Zsh,
+        Shell::Fish => &Fish,
+        Shell::Nushell => &Nushell,
+        Shell::Pwsh => &Powershell,
+        Shell::Elvish => &Elvish,
+        Shell::Xonsh => return None,
+    };
+    Some(registration(completer))
+}
+
+/// Print the completion script for `shell` to stdout.
+pub fn print_completions(shell: Shell) -> Result<()> {
+    match shell {
+        Shell::Nushell => {
+            bail!("nushell completions come with the wrapper: dwm shell-setup --nushell")
+        }
+        Shell::Xonsh => bail!("xonsh completions aren't supported yet"),
+        _ => {}
+    }
+    if let Some(script) = completion_script(shell) {
+        println!("{}", script);
+    }
+    Ok(())
+}
+
+/// The completion script `shell`'s wrapper ends with, so `dwm shell-setup`
+/// alone sets up completion. zsh's only registers once compinit has run,
+/// so a wrapper loaded before it doesn't error. Nushell's wrapper has its
+/// own completer, and the other shells' wrappers have none yet.
+pub fn wrapper_completions(shell: Shell) -> Option<String> {
+    match shell {
+        Shell::Bash | Shell::Fish => completion_script(shell),
+        Shell::Zsh => {
+            let script = completion_script(shell)?;
+            let script = script.strip_suffix("compdef _clap_dynamic_completer_dwm dwm")?;
+            Some(format!(
+                "{}if (( $+functions[compdef] )); then compdef _clap_dynamic_completer_dwm dwm; fi",
+                script
+            ))
+        }
+        Shell::Nushell | Shell::Pwsh | Shell::Elvish | Shell::Xonsh => None,
+    }
+}
+
+/// The `nu-complete dwm` completer the nushell wrapper's arguments use.
+/// Nushell completes through the wrapper's own signature, so unlike the
+/// other shells there's no separate completion script.
+pub fn nushell_completer() -> String {
+    registration(&Nushell)
+}
+
+/// Nushell completion, which clap doesn't provide: the completer gets the
+/// command line so far and reads back a `value<TAB>description` line per
+/// candidate.
+struct Nushell;
+
+impl EnvCompleter for Nushell {
+    fn name(&self) -> &'static str {
+        "nushell"
+    }
+
+    fn is(&self, name: &str) -> bool {
+        name == "nushell" || name == "nu"
+    }
+
+    fn write_registration(
+        &self,
+        var: &str,
+        _name: &str,
+        bin: &str,
+        completer: &str,
+        buf: &mut dyn std::io::Write,
+    ) -> std::io::Result<()> {
+        let quote = |s: &str| format!("\"{}\"", s.replace('\\', r"\\").replace('"', "\\\""));
+        writeln!(
+            buf,
+            r#"def "nu-complete {bin}" [context: string] {{
+    let words = ($context | split row --regex '\s+')
+    with-env {{ {var}: nushell }} {{ ^{completer} -- ...$words }}
+        | lines
+        | parse "{{value}}\t{{description}}"
+}}"#,
+            completer = quote(completer),
+        )
+    }
+
+    fn write_complete(
+        &self,
+        cmd: &mut clap::Command,
+        args: Vec<OsString>,
+        current_dir: Option<&std::path::Path>,
+        buf: &mut dyn std::io::Write,
+    ) -> std::io::Result<()> {
+        let index = args.len().saturating_sub(1);
+        for candidate in clap_complete::engine::complete(cmd, args, index, current_dir)? {
+            let help = candidate.get_help().map(|h| h.to_string());
+            let help = help.as_deref().and_then(|h| h.lines().next());
+            writeln!(
+                buf,
+                "{}\t{}",
+                candidate.get_value().to_string_lossy(),
+                help.unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What dwm offers for the last word of `line`.
+    fn complete(line: &[&str]) -> Vec<String> {
+        let mut cmd = Cli::command();
+        let args: Vec<OsString> = line.iter().map(OsString::from).collect();
+        let index = args.len() - 1;
+        clap_complete::engine::complete(&mut cmd, args, index, None)
+            .unwrap()
+            .iter()
+            .map(|c| c.get_value().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn completes_subcommands_flags_and_choices() {
+        let subs = complete(&["dwm", "sw"]);
+        assert_eq!(subs, ["switch"]);
+        let subs = complete(&["dwm", ""]);
+        assert!(subs.iter().all(|s| s != "hook-handler" && s != "_on-cd"));
+        assert!(complete(&["dwm", "init", "--vcs", ""]).contains(&"git".to_string()));
+        assert!(complete(&["dwm", "launch", ""]).contains(&"claude".to_string()));
+        assert!(complete(&["dwm", "delete", "--f"]).contains(&"--force".to_string()));
+        assert!(complete(&["dwm", "config", ""]).contains(&"set".to_string()));
+    }
+
+    #[test]
+    fn workspace_arguments_complete_workspace_names() {
+        let mut cmd = Cli::command();
+        cmd.build();
+        let with_completer = |sub: &str, id: &str| {
+            cmd.find_subcommand(sub)
+                .and_then(|c| c.get_arguments().find(|a| a.get_id() == id))
+                .is_some_and(|a| a.get::<clap_complete::ArgValueCompleter>().is_some())
+        };
+        for (sub, id) in [
+            ("new", "from"),
+            ("agents", "history"),
+            ("launch", "workspace"),
+            ("switch", "name"),
+            ("delete", "name"),
+            ("rename", "name"),
+        ] {
+            assert!(with_completer(sub, id), "{} {} doesn't complete", sub, id);
+        }
+        assert!(!with_completer("rename", "new_name"));
+    }
+
+    #[test]
+    fn scripts_call_back_into_dwm() {
+        let bash = completion_script(Shell::Bash).unwrap();
+        assert!(bash.contains("DWM_COMPLETE=\"bash\""));
+        assert!(bash.contains("-F _clap_complete_dwm dwm"));
+        let zsh = completion_script(Shell::Zsh).unwrap();
+        assert!(zsh.starts_with("#compdef dwm"));
+        let fish = completion_script(Shell::Fish).unwrap();
+        assert!(fish.contains("--command dwm") && fish.contains("DWM_COMPLETE=fish"));
+        assert!(completion_script(Shell::Pwsh).is_some());
+        assert!(completion_script(Shell::Xonsh).is_none());
+
+        let nu = nushell_completer();
+        assert!(nu.starts_with("def \"nu-complete dwm\" [context: string]"));
+        assert!(nu.contains("with-env { DWM_COMPLETE: nushell }"));
+    }
+
+    #[test]
+    fn nushell_gets_values_with_descriptions() {
+        let mut cmd = Cli::command();
+        cmd.build();
+        let mut out = Vec::new();
+        let args = ["dwm", "swi"].map(OsString::from).to_vec();
+        Nushell
+            .write_complete(&mut cmd, args, None, &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "switch\tSwitch to a workspace by name\n"
+        );
+    }
+
+    #[test]
+    fn zsh_wrapper_completions_wait_for_compinit() {
+        let zsh = wrapper_completions(Shell::Zsh).unwrap();
+        assert!(zsh.contains("function _clap_dynamic_completer_dwm() {"));
+        assert!(zsh.ends_with(
+            "if (( $+functions[compdef] )); then compdef _clap_dynamic_completer_dwm dwm; fi"
+        ));
+        assert_eq!(
+            wrapper_completions(Shell::Fish),
+            completion_script(Shell::Fish)
+        );
+        assert!(wrapper_completions(Shell::Nushell).is_none());
+    }
+}
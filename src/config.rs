@@ -0,0 +1,690 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::agent::{AgentKind, StaleTimeouts};
+use crate::columns::ColumnSpec;
+use crate::external::ExternalVcs;
+use crate::forge::ForgeConfig;
+use crate::keymap::{KeyMap, KeySpec};
+use crate::names::NameConfig;
+use crate::notify::StatusHook;
+use crate::theme::{Theme, ThemeSpec};
+
+/// User settings read from `~/.dwm/config.json`. Every field is optional so a
+/// missing file (or a partial one) falls back to the built-in defaults.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    /// Command used to open a workspace, e.g. `"code {path}"`. `{path}` is
+    /// replaced with the workspace path; without it the path is appended as
+    /// the last argument. Falls back to `$VISUAL`, then `$EDITOR`.
+    pub editor: Option<String>,
+    /// Per-repo overrides of `editor`, keyed by the repo's directory name
+    /// (or its `~/.dwm/` entry), e.g. `{"android-app": "idea {path}"}`.
+    pub repo_editors: HashMap<String, String>,
+    /// Picker key binding overrides, keyed by action name (e.g. `"delete"`).
+    /// Each value is a key or list of keys; see [`crate::keymap`].
+    pub keys: HashMap<String, KeySpec>,
+    /// Picker table columns in display order, each either a column name or
+    /// `{"name": ..., "width": ...}` with a fixed width. Unset means all.
+    pub columns: Option<Vec<ColumnSpec>>,
+    /// Capture the mouse in the picker: click to select, double-click to
+    /// open, scroll to move, click a header to sort. Off by default.
+    pub mouse: bool,
+    /// Picker color theme: `"dark"`, `"light"`, or a palette object with an
+    /// optional `base` and per-role color overrides. Unset means dark.
+    pub theme: Option<ThemeSpec>,
+    /// Commands or webhooks to run when an agent's status changes; see
+    /// [`crate::notify`].
+    pub on_agent_status: Vec<StatusHook>,
+    /// Commands `dwm launch` runs, keyed by agent name, e.g.
+    /// `{"claude": "claude --model opus"}`. Unlisted agents run as their name.
+    pub agent_commands: HashMap<AgentKind, String>,
+    /// Agent the picker's launch key starts. Unset means Claude Code.
+    pub default_agent: Option<AgentKind>,
+    /// Seconds an agent session can go unheard from before it's treated as
+    /// dead: a `default` plus optional `working`, `waiting` and `idle`
+    /// overrides. See [`crate::agent::StaleTimeouts`].
+    pub stale_timeouts: StaleTimeouts,
+    /// An external VCS backend: a `command` speaking JSON over stdio and an
+    /// optional `marker` file identifying its repos. See
+    /// [`crate::external`].
+    pub external_vcs: Option<ExternalVcs>,
+    /// Fetch trunk from the remote before listing workspaces, at most every
+    /// 15 minutes, as if `--fetch` were passed. Off by default.
+    pub auto_fetch: bool,
+    /// Check out submodules in new git workspaces, as if
+    /// `--recurse-submodules` were passed. Off by default.
+    pub recurse_submodules: bool,
+    /// Named sets of directories for `dwm new --sparse <name>` to check out
+    /// instead of the whole repo, e.g. `{"web": ["apps/web", "libs/ui"]}`.
+    pub sparse_profiles: HashMap<String, Vec<String>>,
+    /// Rename the tmux window to the workspace name whenever the shell
+    /// wrapper `cd`s into one. Off by default.
+    pub tmux_rename_window: bool,
+    /// Inside tmux, open workspaces from `switch`, `new`, and the picker in
+    /// their own windows rather than `cd`ing into them. Off by default.
+    pub tmux_windows: bool,
+    /// Show the open pull request for each workspace's branch, with its
+    /// review and CI state, fetched from the repo's forge at most every
+    /// couple of minutes. Off by default.
+    #[serde(alias = "github_prs")]
+    pub pr_status: bool,
+    /// Show the CI state of each workspace's head commit, fetched from the
+    /// repo's forge along with pull requests. Off by default.
+    pub ci_status: bool,
+    /// Forges by host, for tokens and self-hosted instances, e.g.
+    /// `{"git.example.com": {"type": "forgejo", "token": "…"}}`. See
+    /// [`crate::forge::ForgeConfig`].
+    pub forges: HashMap<String, ForgeConfig>,
+    /// How `dwm new` names workspaces when no name is given: a default
+    /// `scheme`, a `prefix`, and `adjectives` and `nouns` to pick from. See
+    /// [`crate::names::NameConfig`].
+    pub names: NameConfig,
+}
+
+pub use dwm_core::layout::config_path;
+
+impl Config {
+    /// Load the user config, returning defaults when the file does not exist.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&config_path()?)
+    }
+
+    /// Load config from an explicit path. Missing files yield defaults.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("could not read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("could not parse {}", path.display()))
+    }
+
+    /// Resolve the editor command: the configured `editor`, then `$VISUAL`,
+    /// then `$EDITOR`. Returns `None` when none of them is set.
+    pub fn editor_command(&self) -> Option<String> {
+        self.editor
+            .clone()
+            .or_else(|| std::env::var("VISUAL").ok())
+            .or_else(|| std::env::var("EDITOR").ok())
+            .filter(|s| !s.trim().is_empty())
+    }
+
+    /// Resolve the editor command for a workspace of the repo at
+    /// `main_repo`, whose `~/.dwm/` entry is `dir_name`: its
+    /// `repo_editors` entry, else [`Config::editor_command`].
+    pub fn editor_command_for(&self, main_repo: &Path, dir_name: &str) -> Option<String> {
+        let basename = main_repo.file_name().map(|n| n.to_string_lossy());
+        [Some(dir_name), basename.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.repo_editors.get(key))
+            .filter(|s| !s.trim().is_empty())
+            .cloned()
+            .or_else(|| self.editor_command())
+    }
+
+    /// The command that starts `agent`: its `agent_commands` entry, or its
+    /// name.
+    pub fn agent_command(&self, agent: AgentKind) -> String {
+        self.agent_commands
+            .get(&agent)
+            .filter(|c| !c.trim().is_empty())
+            .cloned()
+            .unwrap_or_else(|| agent.name().to_string())
+    }
+
+    /// The directories of the sparse profile `name`.
+    pub fn sparse_profile(&self, name: &str) -> Result<Vec<String>> {
+        match self.sparse_profiles.get(name) {
+            Some(dirs) if !dirs.is_empty() => Ok(dirs.clone()),
+            Some(_) => bail!("sparse profile '{}' lists no directories", name),
+            None => {
+                let mut known: Vec<&str> =
+                    self.sparse_profiles.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                if known.is_empty() {
+                    bail!(
+                        "no sparse profile '{}': \"sparse_profiles\" isn't set in config",
+                        name
+                    )
+                }
+                bail!("no sparse profile '{}' (have: {})", name, known.join(", "))
+            }
+        }
+    }
+
+    /// Check the settings that are only resolved when used.
+    pub fn validate(&self) -> Result<()> {
+        self.keymap()?;
+        self.theme()?;
+        self.names
+            .validate()
+            .context("invalid \"names\" section in config")?;
+        Ok(())
+    }
+
+    /// Build the picker keymap: defaults with the `keys` overrides applied.
+    pub fn keymap(&self) -> Result<KeyMap> {
+        KeyMap::from_config(&self.keys).context("invalid \"keys\" section in config")
+    }
+
+    /// Resolve the picker color theme, defaulting to dark.
+    pub fn theme(&self) -> Result<Theme> {
+        match &self.theme {
+            Some(spec) => spec.resolve().context("invalid \"theme\" in config"),
+            None => Ok(Theme::default()),
+        }
+    }
+}
+
+/// Every top-level setting name, in order.
+fn setting_names() -> Vec<String> {
+    match serde_json::to_value(Config::default()) {
+        Ok(Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Fail unless `key` (dot-separated, like `stale_timeouts.waiting`) starts
+/// with a known setting.
+fn check_setting(key: &str) -> Result<()> {
+    let names = setting_names();
+    let top = key.split('.').next().unwrap_or_default();
+    if !names.iter().any(|n| n == top) {
+        bail!("unknown setting '{}' (settings: {})", top, names.join(", "));
+    }
+    Ok(())
+}
+
+/// Look up the dot-separated `key` in `value`.
+fn lookup<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.').try_fold(value, |v, part| v.get(part))
+}
+
+/// Shown in place of a forge token by `dwm config list` and `get`.
+const MASKED: &str = "********";
+
+/// `config` as JSON with every forge token replaced by [`MASKED`], so
+/// listing settings doesn't print secrets.
+fn masked_value(config: &Config) -> Result<Value> {
+    let mut value = serde_json::to_value(config)?;
+    if let Some(Value::Object(forges)) = value.get_mut("forges") {
+        for forge in forges.values_mut() {
+            if let Some(token) = forge.get_mut("token").filter(|t| !t.is_null()) {
+                *token = Value::String(MASKED.to_string());
+            }
+        }
+    }
+    Ok(value)
+}
+
+/// Whether `config` holds a forge token, so its file should be readable
+/// only by its owner.
+fn holds_token(config: &Config) -> bool {
+    config.forges.values().any(|forge| forge.token.is_some())
+}
+
+/// Make the config file at `path` readable only by its owner if `config`
+/// holds a token.
+fn protect(path: &Path, config: &Config) -> Result<()> {
+    #[cfg(unix)]
+    if holds_token(config) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .with_context(|| format!("could not restrict {}", path.display()))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, config);
+    Ok(())
+}
+
+/// Every setting with its value in the config at `path`, defaults included;
+/// unset optional settings are `null`, and forge tokens are masked.
+pub fn list_settings(path: &Path) -> Result<Vec<(String, Value)>> {
+    let config = masked_value(&Config::load_from(path)?)?;
+    let Value::Object(map) = config else {
+        return Ok(Vec::new());
+    };
+    Ok(map.into_iter().collect())
+}
+
+/// The value of the dot-separated `key` in the config at `path`, or `None`
+/// if it isn't set and has no default. Forge tokens are masked.
+pub fn get_setting(path: &Path, key: &str) -> Result<Option<Value>> {
+    check_setting(key)?;
+    let config = masked_value(&Config::load_from(path)?)?;
+    Ok(lookup(&config, key).filter(|v| !v.is_null()).cloned())
+}
+
+/// Set the dot-separated `key` in the config file at `path` to `raw`, read
+/// as JSON or else as a string; `null` removes it. Other settings in the
+/// file are kept as they are, and nothing is written unless the result is a
+/// valid config.
+pub fn set_setting(path: &Path, key: &str, raw: &str) -> Result<()> {
+    check_setting(key)?;
+    let mut doc = if path.exists() {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("could not read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("could not parse {}", path.display()))?
+    } else {
+        Value::Object(Default::default())
+    };
+    let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()));
+
+    let parts: Vec<&str> = key.split('.').collect();
+    let (last, parents) = parts.split_last().context("empty setting name")?;
+    let mut table = &mut doc;
+    for part in parents {
+        let Value::Object(map) = table else {
+            bail!("'{}' isn't a table of settings", key);
+        };
+        table = map
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Default::default()));
+    }
+    let Value::Object(map) = table else {
+        bail!("'{}' isn't a table of settings", key);
+    };
+    if value.is_null() {
+        map.remove(*last);
+    } else {
+        map.insert(last.to_string(), value);
+    }
+
+    let config: Config = serde_json::from_value(doc.clone())
+        .with_context(|| format!("invalid value for '{}'", key))?;
+    config
+        .validate()
+        .with_context(|| format!("invalid value for '{}'", key))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_config(path, &serde_json::to_string_pretty(&doc)?, &config)
+}
+
+/// Write `content` to the config file at `path`, replacing it whole, and
+/// readable only by its owner if `config`, its parsed form, holds a token.
+fn write_config(path: &Path, content: &str, config: &Config) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if holds_token(config) {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let write = || -> Result<()> {
+        use std::io::Write;
+        // A leftover from a failed write keeps its old mode if reopened.
+        let _ = fs::remove_file(&tmp);
+        let mut file = options.open(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.write_all(b"\n")?;
+        protect(&tmp, config)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    };
+    write().with_context(|| format!("could not write {}", path.display()))
+}
+
+/// Open the config file at `path` in `$VISUAL`, `$EDITOR`, or `vi`, creating
+/// it first if needed, and report whether the result is still valid.
+pub fn edit_config(path: &Path) -> Result<()> {
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, "{}\n")?;
+    }
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("dwm")
+        .arg(path)
+        .status()
+        .with_context(|| format!("failed to run '{}'", editor))?;
+    if !status.success() {
+        bail!("'{}' exited with {}", editor, status);
+    }
+    let config = Config::load_from(path)?;
+    protect(path, &config)?;
+    config.validate()
+}
+
+/// Show a setting's value: strings bare, anything else as JSON.
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names::NamingScheme;
+
+    #[test]
+    fn missing_file_yields_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&dir.path().join("config.json")).unwrap();
+        assert!(config.editor.is_none());
+    }
+
+    #[test]
+    fn parses_editor() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"editor": "code {path}"}"#).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.editor.as_deref(), Some("code {path}"));
+    }
+
+    #[test]
+    fn unknown_fields_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"something_else": 1}"#).unwrap();
+        assert!(Config::load_from(&path).is_ok());
+    }
+
+    #[test]
+    fn invalid_json_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "{not json").unwrap();
+        assert!(Config::load_from(&path).is_err());
+    }
+
+    #[test]
+    fn parses_on_agent_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(
+            &path,
+            r#"{"on_agent_status": [
+                {"command": "say {workspace}", "on": ["waiting"]},
+                {"webhook": "https://example.com/hook"}
+            ]}"#,
+        )
+        .unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.on_agent_status.len(), 2);
+        assert_eq!(
+            config.on_agent_status[0].on,
+            vec![crate::notify::Trigger::Waiting]
+        );
+        assert!(config.on_agent_status[1].on.is_empty());
+    }
+
+    #[test]
+    fn parses_forges_and_old_pr_setting_name() {
+        let config: Config = serde_json::from_str(
+            r#"{"github_prs": true,
+                "forges": {"git.example.com": {"type": "forgejo", "token": "t"}}}"#,
+        )
+        .unwrap();
+        assert!(config.pr_status);
+        let forge = &config.forges["git.example.com"];
+        assert_eq!(forge.kind, Some(crate::forge::ForgeKind::Gitea));
+        assert_eq!(forge.token.as_deref(), Some("t"));
+        assert_eq!(forge.url, None);
+    }
+
+    #[test]
+    fn agent_commands_default_to_the_agent_name() {
+        let config: Config = serde_json::from_str(
+            r#"{"agent_commands": {"claude": "claude --model opus"}, "default_agent": "aider"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.agent_command(AgentKind::Claude),
+            "claude --model opus"
+        );
+        assert_eq!(config.agent_command(AgentKind::Codex), "codex");
+        assert_eq!(config.default_agent, Some(AgentKind::Aider));
+        assert!(serde_json::from_str::<Config>(r#"{"agent_commands": {"vim": "vim"}}"#).is_err());
+    }
+
+    #[test]
+    fn parses_stale_timeouts() {
+        let config: Config =
+            serde_json::from_str(r#"{"stale_timeouts": {"default": 300, "waiting": 3600}}"#)
+                .unwrap();
+        assert_eq!(config.stale_timeouts.default, Some(300));
+        assert_eq!(config.stale_timeouts.waiting, Some(3600));
+        assert_eq!(config.stale_timeouts.idle, None);
+        assert_eq!(Config::default().stale_timeouts, StaleTimeouts::default());
+    }
+
+    #[test]
+    fn sparse_profiles_resolve_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(
+            &path,
+            r#"{"sparse_profiles": {"web": ["apps/web", "libs/ui"], "none": []}}"#,
+        )
+        .unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(
+            config.sparse_profile("web").unwrap(),
+            ["apps/web", "libs/ui"]
+        );
+        let err = config.sparse_profile("api").unwrap_err();
+        assert_eq!(err.to_string(), "no sparse profile 'api' (have: none, web)");
+        assert!(config.sparse_profile("none").is_err());
+        assert!(Config::default().sparse_profile("web").is_err());
+    }
+
+    #[test]
+    fn parses_keys_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"keys": {"delete": "x", "down": ["n", "down"]}}"#).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        let keymap = config.keymap().unwrap();
+        assert_eq!(keymap.label(crate::keymap::Action::Delete), "x");
+    }
+
+    #[test]
+    fn parses_columns() {
+        let config: Config =
+            serde_json::from_str(r#"{"columns": ["name", {"name": "description", "width": 30}]}"#)
+                .unwrap();
+        assert_eq!(config.columns.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn invalid_key_name_errors() {
+        let config: Config = serde_json::from_str(r#"{"keys": {"delete": "hyper"}}"#).unwrap();
+        assert!(config.keymap().is_err());
+    }
+
+    #[test]
+    fn theme_defaults_to_dark_and_parses() {
+        assert_eq!(Config::default().theme().unwrap(), Theme::DARK);
+        let config: Config = serde_json::from_str(r#"{"theme": "light"}"#).unwrap();
+        assert_eq!(config.theme().unwrap(), Theme::LIGHT);
+        let config: Config = serde_json::from_str(r#"{"theme": "neon"}"#).unwrap();
+        assert!(config.theme().is_err());
+    }
+
+    #[test]
+    fn parses_names_section() {
+        let config: Config = serde_json::from_str(
+            r#"{"names": {"scheme": "dated", "prefix": "danny/", "nouns": ["kite"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.names.scheme, Some(NamingScheme::Dated));
+        assert_eq!(config.names.prefix.as_deref(), Some("danny/"));
+        assert_eq!(config.names.nouns, ["kite"]);
+        assert!(config.names.adjectives.is_empty());
+        assert!(config.validate().is_ok());
+        let config: Config = serde_json::from_str(r#"{"names": {"prefix": ".x"}}"#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn get_and_list_include_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(
+            &path,
+            r#"{"editor": "zed", "stale_timeouts": {"waiting": 60}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            get_setting(&path, "editor").unwrap(),
+            Some(Value::String("zed".to_string()))
+        );
+        assert_eq!(
+            get_setting(&path, "stale_timeouts.waiting").unwrap(),
+            Some(Value::from(60))
+        );
+        assert_eq!(
+            get_setting(&path, "mouse").unwrap(),
+            Some(Value::Bool(false))
+        );
+        assert_eq!(get_setting(&path, "theme").unwrap(), None);
+        let err = get_setting(&path, "colour").unwrap_err();
+        assert!(
+            err.to_string().starts_with("unknown setting 'colour'"),
+            "{}",
+            err
+        );
+
+        let settings = list_settings(&path).unwrap();
+        let names: Vec<&str> = settings.iter().map(|(k, _)| k.as_str()).collect();
+        assert!(names.contains(&"auto_fetch") && names.contains(&"sparse_profiles"));
+    }
+
+    #[test]
+    fn set_keeps_other_settings_and_validates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"editor": "zed", "something_else": 1}"#).unwrap();
+
+        set_setting(&path, "mouse", "true").unwrap();
+        set_setting(&path, "keys.delete", "x").unwrap();
+        set_setting(&path, "sparse_profiles.web", r#"["apps/web"]"#).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert!(config.mouse);
+        assert_eq!(config.editor.as_deref(), Some("zed"));
+        assert_eq!(
+            config
+                .keymap()
+                .unwrap()
+                .label(crate::keymap::Action::Delete),
+            "x"
+        );
+        assert_eq!(config.sparse_profile("web").unwrap(), ["apps/web"]);
+        let doc: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(doc["something_else"], 1);
+
+        // Invalid values leave the file alone.
+        let before = fs::read_to_string(&path).unwrap();
+        assert!(set_setting(&path, "keys.delete", "hyper").is_err());
+        assert!(set_setting(&path, "mouse", "sometimes").is_err());
+        assert!(set_setting(&path, "editor.command", "vim").is_err());
+        assert!(set_setting(&path, "colour", "red").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+
+        set_setting(&path, "editor", "null").unwrap();
+        assert!(Config::load_from(&path).unwrap().editor.is_none());
+    }
+
+    #[test]
+    fn forge_tokens_are_masked_and_kept_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"editor": "zed"}"#).unwrap();
+        let forges = r#"{"git.example.com": {"type": "gitea", "token": "s3cret"}}"#;
+        set_setting(&path, "forges", forges).unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(
+            config.forges["git.example.com"].token.as_deref(),
+            Some("s3cret")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let forges = get_setting(&path, "forges").unwrap().unwrap();
+        assert_eq!(forges["git.example.com"]["token"], MASKED);
+        assert_eq!(forges["git.example.com"]["type"], "gitea");
+        let listed = list_settings(&path).unwrap();
+        let (_, forges) = listed.iter().find(|(k, _)| k == "forges").unwrap();
+        assert!(!forges.to_string().contains("s3cret"));
+    }
+
+    #[test]
+    fn set_creates_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dwm/config.json");
+        set_setting(&path, "editor", "code {path}").unwrap();
+        assert_eq!(
+            Config::load_from(&path).unwrap().editor.as_deref(),
+            Some("code {path}")
+        );
+    }
+
+    #[test]
+    fn display_value_shows_strings_bare() {
+        assert_eq!(display_value(&Value::String("vim".to_string())), "vim");
+        assert_eq!(display_value(&serde_json::json!(["a", 1])), r#"["a",1]"#);
+    }
+
+    #[test]
+    fn configured_editor_takes_priority() {
+        temp_env::with_vars([("VISUAL", Some("vim")), ("EDITOR", Some("nano"))], || {
+            let config = Config {
+                editor: Some("zed".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(config.editor_command().as_deref(), Some("zed"));
+        });
+    }
+
+    #[test]
+    fn repo_editor_overrides_editor() {
+        let config = Config {
+            editor: Some("zed".to_string()),
+            repo_editors: HashMap::from([
+                ("android".to_string(), "idea {path}".to_string()),
+                ("web-1a2b3c4d".to_string(), "code".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let editor = |repo: &str, dir: &str| config.editor_command_for(Path::new(repo), dir);
+        assert_eq!(
+            editor("/src/android", "android-9f8e7d6c").as_deref(),
+            Some("idea {path}")
+        );
+        assert_eq!(editor("/src/web", "web-1a2b3c4d").as_deref(), Some("code"));
+        assert_eq!(editor("/src/api", "api-00000000").as_deref(), Some("zed"));
+    }
+
+    #[test]
+    fn editor_falls_back_to_env() {
+        temp_env::with_vars([("VISUAL", None), ("EDITOR", Some("nano"))], || {
+            assert_eq!(Config::default().editor_command().as_deref(), Some("nano"));
+        });
+    }
+}
@@ -0,0 +1,678 @@
+// Only the listing side is built where there are no Unix sockets.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::workspace::{self, WorkspaceEntry};
+use crate::{agent, meta, vcs, watch};
+
+/// Socket `dwm daemon` serves on, inside `~/.dwm/`.
+const SOCKET_FILE: &str = ".daemon.sock";
+
+/// Oldest listing the daemon serves. Commits and edits in a workspace leave
+/// the [`Fingerprint`] alone, so an older one could show diff stats, dirty
+/// state, and merges that have since changed; a request for it relists the
+/// repo first.
+const MAX_AGE: Duration = Duration::from_secs(5);
+
+/// How often the daemon checks for changes where it can't watch for them,
+/// and rereads agent status, whose sessions go stale with time alone.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a listing waits on the daemon before doing without it.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sent with every reply, so entries are never read from a daemon of
+/// another version, which may lay them out differently.
+const VERSION_HEADER: &str = concat!("Dwm-Version: ", env!("CARGO_PKG_VERSION"));
+
+/// Longest request the daemon reads; it only answers short `GET`s.
+const MAX_REQUEST: u64 = 8 * 1024;
+
+/// Files in a repo's `~/.dwm/` entry whose modification times go into its
+/// [`Fingerprint`]. `.last-fetch` is touched again once a fetch finishes.
+const STAMP_FILES: &[&str] = &[meta::REPO_FILE, ".last-fetch", ".forge.json"];
+
+/// What changes in a repo's `~/.dwm/` entry whenever its listing would,
+/// short of the VCS itself: the workspace directories, and when trunk and
+/// forge state were last fetched. A listing made under another fingerprint
+/// is out of date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    workspaces: Vec<String>,
+    stamps: Vec<Option<SystemTime>>,
+}
+
+fn fingerprint(rd: &Path) -> Fingerprint {
+    Fingerprint {
+        workspaces: workspace::workspace_dir_names(rd),
+        stamps: STAMP_FILES
+            .iter()
+            .map(|file| fs::metadata(rd.join(file)).and_then(|m| m.modified()).ok())
+            .collect(),
+    }
+}
+
+/// A repo's workspaces as the daemon last listed them.
+struct Listing {
+    /// What the repo is shown as in listings that span repos; see
+    /// [`workspace::repo_label`].
+    label: String,
+    /// `None` if listing failed; clients then list the repo themselves and
+    /// see why.
+    entries: Option<Vec<WorkspaceEntry>>,
+    fingerprint: Fingerprint,
+    listed_at: Instant,
+}
+
+/// Listings by the name of the repo's `~/.dwm/` entry.
+type Listings = Arc<Mutex<HashMap<String, Listing>>>;
+
+/// Keep `listings` up to date with every repo tracked under `dwm_base`.
+/// Where it can, this watches `~/.dwm/`, each repo's entry, and its agent
+/// status, so a new workspace or agent status shows up right away; it
+/// otherwise looks every [`POLL_INTERVAL`].
+fn keep_listed(dwm_base: &Path, listings: &Listings) {
+    let mut watched = None;
+    let mut watcher = None;
+    loop {
+        let repos = workspace::tracked_repo_names(dwm_base);
+        if watched.as_ref() != Some(&repos) {
+            let mut dirs = vec![dwm_base.to_path_buf()];
+            for name in &repos {
+                let rd = dwm_base.join(name);
+                dirs.push(agent::agent_status_dir(&rd));
+                dirs.push(rd);
+            }
+            watcher = watch::DirWatcher::new(&dirs);
+            watched = Some(repos.clone());
+        }
+        refresh(dwm_base, &repos, listings);
+        match &watcher {
+            Some(watcher) => {
+                watcher.wait(POLL_INTERVAL);
+            }
+            None => std::thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
+
+/// Whether `listing` of the repo in `rd` may still be served: it's under
+/// [`MAX_AGE`] old and nothing has changed its fingerprint.
+fn is_current(listing: &Listing, rd: &Path) -> bool {
+    listing.listed_at.elapsed() < MAX_AGE && listing.fingerprint == fingerprint(rd)
+}
+
+/// Relist each of `repos` whose listing is missing or has another
+/// fingerprint, and reread every repo's agent status. Listings that are
+/// merely old are left for a request to relist; see [`catch_up`].
+fn refresh(dwm_base: &Path, repos: &[String], listings: &Listings) {
+    let due: Vec<(String, Fingerprint)> = {
+        let mut listings = listings.lock().unwrap();
+        listings.retain(|name, _| repos.contains(name));
+        repos
+            .iter()
+            .filter_map(|name| {
+                let current = fingerprint(&dwm_base.join(name));
+                let fresh = listings
+                    .get(name)
+                    .is_some_and(|listing| listing.fingerprint == current);
+                (!fresh).then(|| (name.clone(), current))
+            })
+            .collect()
+    };
+    relist(dwm_base, due, listings);
+
+    let mut listings = listings.lock().unwrap();
+    for (name, listing) in listings.iter_mut() {
+        let summaries = agent::read_agent_summaries(&dwm_base.join(name));
+        for entry in listing.entries.iter_mut().flatten() {
+            entry.agent_status = summaries.get(&entry.name).cloned();
+        }
+    }
+}
+
+/// Relist whichever repos a `GET` of `path` needs that have no current
+/// listing, so the answer can come from the daemon. If this outlasts the
+/// client's [`CLIENT_TIMEOUT`], the client lists without it, and the next
+/// request gets the new listing.
+fn catch_up(dwm_base: &Path, listings: &Listings, path: &str) {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments = path_segments(path);
+    let mut wanted = workspace::tracked_repo_names(dwm_base);
+    match segments.as_slice() {
+        [workspaces] if workspaces == "workspaces" => {}
+        [repos, name, workspaces] if repos == "repos" && workspaces == "workspaces" => {
+            wanted.retain(|tracked| tracked == name);
+        }
+        _ => return,
+    }
+    let due: Vec<(String, Fingerprint)> = {
+        let listings = listings.lock().unwrap();
+        wanted
+            .into_iter()
+            .filter(|name| {
+                !listings
+                    .get(name)
+                    .is_some_and(|listing| is_current(listing, &dwm_base.join(name)))
+            })
+            .map(|name| {
+                let current = fingerprint(&dwm_base.join(&name));
+                (name, current)
+            })
+            .collect()
+    };
+    relist(dwm_base, due, listings);
+}
+
+/// List each repo in `due` under the fingerprint it had beforehand, and
+/// store the listings, each stamped with when it finished.
+fn relist(dwm_base: &Path, due: Vec<(String, Fingerprint)>, listings: &Listings) {
+    if due.is_empty() {
+        return;
+    }
+    // Listing runs the VCS, so it's done outside the lock, a few repos at a
+    // time.
+    let listed = vcs::parallel_map(&due, |(name, _)| {
+        let result = workspace::list_tracked_repo(dwm_base, name);
+        (result, Instant::now())
+    });
+
+    let mut listings = listings.lock().unwrap();
+    for ((name, fingerprint), (result, listed_at)) in due.into_iter().zip(listed) {
+        let (label, mut entries) = match result {
+            Ok((label, entries)) => {
+                log::debug!("listed {} workspaces of {}", entries.len(), label);
+                (label, Some(entries))
+            }
+            Err(e) => {
+                log::warn!("could not list {}: {:#}", name, e);
+                (name.clone(), None)
+            }
+        };
+        let summaries = agent::read_agent_summaries(&dwm_base.join(&name));
+        for entry in entries.iter_mut().flatten() {
+            entry.agent_status = summaries.get(&entry.name).cloned();
+        }
+        let listing = Listing {
+            label,
+            entries,
+            fingerprint,
+            listed_at,
+        };
+        listings.insert(name, listing);
+    }
+}
+
+/// The status line and JSON body answering a `GET` of `path`:
+///
+/// - `/`: the daemon's version and the repos it has listed.
+/// - `/workspaces`: every repo's workspaces, as `dwm list --all` lists them.
+/// - `/repos/<name>/workspaces`: the workspaces of the repo in
+///   `~/.dwm/<name>/`.
+///
+/// Listings that may be out of date, because their fingerprint changed or
+/// they're [`MAX_AGE`] old, get `503`, so the client lists without the
+/// daemon rather than show them; [`catch_up`] relists them first where it
+/// can.
+fn respond(dwm_base: &Path, listings: &Listings, path: &str) -> (&'static str, String) {
+    let path = path.split('?').next().unwrap_or_default();
+    let segments = path_segments(path);
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+    let listings = listings.lock().unwrap();
+    let unavailable = |what: &str| {
+        let body = serde_json::json!({ "error": format!("no up-to-date listing of {}", what) });
+        ("503 Service Unavailable", body.to_string())
+    };
+    // Checked against the disk as each request comes in, so a change the
+    // daemon hasn't caught up with yet is never hidden.
+    let current = |name: &str| {
+        listings
+            .get(name)
+            .filter(|listing| is_current(listing, &dwm_base.join(name)))
+    };
+
+    let body = match segments.as_slice() {
+        [] => {
+            let repos: Vec<_> = listings
+                .iter()
+                .map(|(name, listing)| {
+                    serde_json::json!({
+                        "name": name,
+                        "label": listing.label,
+                        "workspaces": listing.entries.as_ref().map(Vec::len),
+                        "listed_secs_ago": listing.listed_at.elapsed().as_secs(),
+                    })
+                })
+                .collect();
+            serde_json::json!({ "version": env!("CARGO_PKG_VERSION"), "repos": repos }).to_string()
+        }
+        ["workspaces"] => {
+            let mut all = Vec::new();
+            for name in workspace::tracked_repo_names(dwm_base) {
+                let Some(listing) = current(&name) else {
+                    return unavailable("every repo");
+                };
+                // Like `dwm list --all`, leave out repos that fail to list.
+                for entry in listing.entries.iter().flatten() {
+                    let mut entry = entry.clone();
+                    entry.repo_name = Some(listing.label.clone());
+                    all.push(entry);
+                }
+            }
+            serde_json::to_string(&all).unwrap_or_default()
+        }
+        ["repos", name, "workspaces"] => match current(name).and_then(|l| l.entries.as_ref()) {
+            Some(entries) => serde_json::to_string(entries).unwrap_or_default(),
+            None => return unavailable(name),
+        },
+        _ => {
+            let body = serde_json::json!({ "error": format!("nothing at {}", path) });
+            return ("404 Not Found", body.to_string());
+        }
+    };
+    ("200 OK", body)
+}
+
+/// The decoded, non-empty `/`-separated parts of a request path.
+fn path_segments(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect()
+}
+
+/// The body of a daemon's HTTP `response`, if it's a success from a daemon
+/// of this version.
+fn reply_body(response: &str) -> Option<&str> {
+    let (head, body) = response.split_once("\r\n\r\n")?;
+    let mut lines = head.lines();
+    if !lines.next()?.starts_with("HTTP/1.1 200 ") {
+        return None;
+    }
+    lines
+        .any(|line| line.eq_ignore_ascii_case(VERSION_HEADER))
+        .then_some(body)
+}
+
+/// `name` with every byte but letters, digits, and `-_.~` written as `%XX`,
+/// to fit in a request path.
+fn percent_encode(name: &str) -> String {
+    name.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// The workspaces of the repo in `~/.dwm/<repo_dir_name>/` from a running
+/// daemon, or `None` if there isn't one or its listing may be out of date,
+/// so the caller lists them itself.
+pub fn workspaces(dwm_base: &Path, repo_dir_name: &str) -> Option<Vec<WorkspaceEntry>> {
+    let path = format!("/repos/{}/workspaces", percent_encode(repo_dir_name));
+    get(dwm_base, &path)
+}
+
+/// [`workspaces`] for every tracked repo, with repo names filled in.
+pub fn all_workspaces(dwm_base: &Path) -> Option<Vec<WorkspaceEntry>> {
+    get(dwm_base, "/workspaces")
+}
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+/// Run `dwm daemon`: keep every repo's listing up to date in the
+/// background and serve it as JSON over HTTP on `~/.dwm/.daemon.sock`
+/// until killed.
+#[cfg(unix)]
+pub fn run() -> Result<()> {
+    use anyhow::{Context, bail};
+    use std::os::unix::fs::PermissionsExt;
+
+    let dwm_base = workspace::dwm_base_dir()?;
+    fs::create_dir_all(&dwm_base)?;
+    let socket = dwm_base.join(SOCKET_FILE);
+    if UnixStream::connect(&socket).is_ok() {
+        bail!(
+            "dwm daemon is already running on {}",
+            crate::platform::tilde_path(&socket)
+        );
+    }
+    // Left behind by a daemon that was killed.
+    let _ = fs::remove_file(&socket);
+    let listener = UnixListener::bind(&socket)
+        .with_context(|| format!("could not listen on {}", socket.display()))?;
+    fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))?;
+
+    let listings = Listings::default();
+    {
+        let dwm_base = dwm_base.clone();
+        let listings = Arc::clone(&listings);
+        std::thread::spawn(move || keep_listed(&dwm_base, &listings));
+    }
+    log::info!(
+        "serving workspace listings on {}",
+        crate::platform::tilde_path(&socket)
+    );
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let dwm_base = dwm_base.clone();
+        let listings = Arc::clone(&listings);
+        std::thread::spawn(move || serve(&stream, &dwm_base, &listings));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn run() -> Result<()> {
+    anyhow::bail!("dwm daemon needs Unix sockets, which this platform doesn't have")
+}
+
+/// Answer one request on `stream`.
+#[cfg(unix)]
+fn serve(stream: &UnixStream, dwm_base: &Path, listings: &Listings) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST));
+    let mut request = String::new();
+    if reader.read_line(&mut request).is_err() {
+        return;
+    }
+    // Headers don't change the answer, but are read so the client isn't
+    // cut off mid-request.
+    let mut header = String::new();
+    while reader.read_line(&mut header).is_ok_and(|n| n > 0) && !header.trim().is_empty() {
+        header.clear();
+    }
+    let (status, body) = match request.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["GET", path, _] => {
+            catch_up(dwm_base, listings, path);
+            respond(dwm_base, listings, path)
+        }
+        _ => (
+            "400 Bad Request",
+            r#"{"error":"only GET is served"}"#.to_string(),
+        ),
+    };
+    let mut stream = stream;
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        VERSION_HEADER,
+        body
+    );
+}
+
+/// `GET` `path` from the daemon listening under `dwm_base`, if there is one
+/// and it answers in time.
+#[cfg(unix)]
+fn get<T: DeserializeOwned>(dwm_base: &Path, path: &str) -> Option<T> {
+    use std::io::{Read, Write};
+
+    let mut stream = UnixStream::connect(dwm_base.join(SOCKET_FILE)).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    )
+    .ok()?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok()?;
+    let Some(body) = reply_body(&response) else {
+        log::debug!("daemon has no listing at {}; listing directly", path);
+        return None;
+    };
+    serde_json::from_str(body)
+        .map_err(|e| log::debug!("could not read daemon listing: {}", e))
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn get<T: DeserializeOwned>(_dwm_base: &Path, _path: &str) -> Option<T> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(name: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: name.to_string(),
+            path: PathBuf::from("/tmp").join(name),
+            last_modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            diff_stat: vcs::DiffStat {
+                insertions: 3,
+                ..Default::default()
+            },
+            change_id: "abcd1234".to_string(),
+            description: "fix login".to_string(),
+            bookmarks: vec![name.to_string()],
+            main_repo_path: PathBuf::from("/src/api"),
+            vcs_type: vcs::VcsType::Git,
+            ..Default::default()
+        }
+    }
+
+    /// A `~/.dwm/` with one tracked repo, `api-1234`, holding `workspaces`,
+    /// and a listing of it.
+    fn listed(dwm_base: &Path, workspaces: &[&str]) -> Listings {
+        let rd = dwm_base.join("api-1234");
+        fs::create_dir_all(&rd).unwrap();
+        fs::write(rd.join(".main-repo"), "/src/api").unwrap();
+        for name in workspaces {
+            fs::create_dir_all(rd.join(name)).unwrap();
+        }
+        let listing = Listing {
+            label: "api".to_string(),
+            entries: Some(workspaces.iter().map(|name| entry(name)).collect()),
+            fingerprint: fingerprint(&rd),
+            listed_at: Instant::now(),
+        };
+        Arc::new(Mutex::new(HashMap::from([(
+            "api-1234".to_string(),
+            listing,
+        )])))
+    }
+
+    #[test]
+    fn fingerprint_follows_workspaces_and_fetches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let rd = tmp.path();
+        fs::create_dir(rd.join("fix-login")).unwrap();
+        fs::create_dir(rd.join(".agent-status")).unwrap();
+        let before = fingerprint(rd);
+        assert_eq!(before.workspaces, vec!["fix-login"]);
+        // The daemon's own listings rewrite the details cache.
+        fs::write(rd.join(".details-cache.json"), "{}").unwrap();
+        assert_eq!(fingerprint(rd), before);
+
+        fs::create_dir(rd.join("hazy-quail")).unwrap();
+        assert_ne!(fingerprint(rd), before);
+        let before = fingerprint(rd);
+        fs::write(rd.join(".last-fetch"), "").unwrap();
+        assert_ne!(fingerprint(rd), before);
+    }
+
+    #[test]
+    fn serves_a_current_listing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+
+        let (status, body) = respond(tmp.path(), &listings, "/repos/api-1234/workspaces");
+        assert_eq!(status, "200 OK");
+        let entries: Vec<WorkspaceEntry> = serde_json::from_str(&body).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "fix-login");
+        assert_eq!(entries[0].diff_stat.insertions, 3);
+        assert_eq!(entries[0].last_modified, entry("fix-login").last_modified);
+        assert_eq!(entries[0].repo_name, None);
+
+        let (status, body) = respond(tmp.path(), &listings, "/workspaces");
+        assert_eq!(status, "200 OK");
+        let entries: Vec<WorkspaceEntry> = serde_json::from_str(&body).unwrap();
+        assert_eq!(entries[0].repo_name.as_deref(), Some("api"));
+
+        let (status, _) = respond(tmp.path(), &listings, "/repos/other-5678/workspaces");
+        assert_eq!(status, "503 Service Unavailable");
+        let (status, _) = respond(tmp.path(), &listings, "/nope");
+        assert_eq!(status, "404 Not Found");
+    }
+
+    #[test]
+    fn out_of_date_listing_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+        fs::create_dir(tmp.path().join("api-1234").join("hazy-quail")).unwrap();
+        let (status, _) = respond(tmp.path(), &listings, "/repos/api-1234/workspaces");
+        assert_eq!(status, "503 Service Unavailable");
+        let (status, _) = respond(tmp.path(), &listings, "/workspaces");
+        assert_eq!(status, "503 Service Unavailable");
+    }
+
+    #[test]
+    fn old_listing_is_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+        if let Some(listing) = listings.lock().unwrap().get_mut("api-1234") {
+            listing.listed_at = Instant::now() - MAX_AGE;
+        }
+        let (status, _) = respond(tmp.path(), &listings, "/repos/api-1234/workspaces");
+        assert_eq!(status, "503 Service Unavailable");
+    }
+
+    #[test]
+    fn all_workspaces_waits_for_every_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+        let other = tmp.path().join("web-5678");
+        fs::create_dir(&other).unwrap();
+        fs::write(other.join(".main-repo"), "/src/web").unwrap();
+        let (status, _) = respond(tmp.path(), &listings, "/workspaces");
+        assert_eq!(status, "503 Service Unavailable");
+    }
+
+    #[test]
+    fn refresh_drops_repos_no_longer_tracked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+        refresh(tmp.path(), &[], &listings);
+        assert!(listings.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn refresh_leaves_old_listings_for_requests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+        let listed_at = Instant::now() - MAX_AGE;
+        if let Some(listing) = listings.lock().unwrap().get_mut("api-1234") {
+            listing.listed_at = listed_at;
+        }
+        refresh(tmp.path(), &["api-1234".to_string()], &listings);
+        assert_eq!(listings.lock().unwrap()["api-1234"].listed_at, listed_at);
+    }
+
+    #[test]
+    fn request_relists_an_old_listing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let listings = listed(tmp.path(), &["fix-login"]);
+        let listed_at = Instant::now() - MAX_AGE;
+        if let Some(listing) = listings.lock().unwrap().get_mut("api-1234") {
+            listing.listed_at = listed_at;
+        }
+        // Other repos' listings are left alone.
+        catch_up(tmp.path(), &listings, "/repos/web-5678/workspaces");
+        assert_eq!(listings.lock().unwrap()["api-1234"].listed_at, listed_at);
+
+        let before = Instant::now();
+        catch_up(tmp.path(), &listings, "/repos/api-1234/workspaces");
+        // Stamped once the listing finished.
+        assert!(listings.lock().unwrap()["api-1234"].listed_at >= before);
+    }
+
+    #[test]
+    fn reply_needs_success_from_this_version() {
+        let ok = format!("HTTP/1.1 200 OK\r\n{}\r\n\r\n[]", VERSION_HEADER);
+        assert_eq!(reply_body(&ok), Some("[]"));
+        let old = "HTTP/1.1 200 OK\r\nDwm-Version: 0.0.1\r\n\r\n[]";
+        assert_eq!(reply_body(old), None);
+        let refused = format!(
+            "HTTP/1.1 503 Service Unavailable\r\n{}\r\n\r\n{{}}",
+            VERSION_HEADER
+        );
+        assert_eq!(reply_body(&refused), None);
+    }
+
+    #[test]
+    fn repo_names_survive_the_request_path() {
+        let name = "my repo%-1a2b";
+        assert_eq!(percent_encode(name), "my%20repo%25-1a2b");
+        assert_eq!(percent_decode(&percent_encode(name)), name);
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn client_reads_listing_over_the_socket() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dwm_base = tmp.path().to_path_buf();
+        let listings = listed(&dwm_base, &["fix-login"]);
+        // Without a daemon, the caller lists for itself.
+        assert!(workspaces(&dwm_base, "api-1234").is_none());
+
+        let listener = UnixListener::bind(dwm_base.join(SOCKET_FILE)).unwrap();
+        let server = {
+            let dwm_base = dwm_base.clone();
+            std::thread::spawn(move || {
+                for _ in 0..2 {
+                    let (stream, _) = listener.accept().unwrap();
+                    serve(&stream, &dwm_base, &listings);
+                }
+            })
+        };
+        let entries = workspaces(&dwm_base, "api-1234").unwrap();
+        assert_eq!(entries[0].name, "fix-login");
+        assert!(workspaces(&dwm_base, "web-5678").is_none());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,404 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::meta::RepoMeta;
+use crate::workspace::{self, WorkspaceEntry};
+use crate::{agent, watch};
+
+/// How often `dwm watch` relists from the VCS to catch new commits and
+/// edits, which nothing under `~/.dwm/` reflects. The same as the picker's
+/// refresh.
+const RELIST_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long `dwm watch` waits for a change before looking anyway: agent
+/// sessions go stale with time alone, and not every platform can watch.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Something that happened to a workspace, as one line of `dwm watch
+/// --json` output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Event {
+    #[serde(flatten)]
+    pub kind: EventKind,
+    pub workspace: String,
+    pub path: PathBuf,
+    pub main_repo: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum EventKind {
+    Created,
+    Deleted,
+    /// The number of agent sessions in each status changed.
+    AgentStatusChanged {
+        waiting: u32,
+        working: u32,
+        idle: u32,
+    },
+    /// The workspace moved to another change, or its diff against trunk or
+    /// its uncommitted work changed.
+    DiffChanged {
+        change: String,
+        files: u32,
+        insertions: u32,
+        deletions: u32,
+        ahead: u32,
+        behind: u32,
+        dirty: bool,
+    },
+}
+
+impl Event {
+    fn new(kind: EventKind, entry: &WorkspaceEntry) -> Self {
+        Self {
+            kind,
+            workspace: entry.name.clone(),
+            path: entry.path.clone(),
+            main_repo: entry.main_repo_path.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            EventKind::Created => write!(f, "{} created", self.workspace),
+            EventKind::Deleted => write!(f, "{} deleted", self.workspace),
+            EventKind::AgentStatusChanged {
+                waiting,
+                working,
+                idle,
+            } => write!(
+                f,
+                "{} agents: {} waiting, {} working, {} idle",
+                self.workspace, waiting, working, idle
+            ),
+            EventKind::DiffChanged {
+                files,
+                insertions,
+                deletions,
+                dirty,
+                ..
+            } => write!(
+                f,
+                "{} changed: {} files +{} -{}{}",
+                self.workspace,
+                files,
+                insertions,
+                deletions,
+                if *dirty { ", uncommitted" } else { "" }
+            ),
+        }
+    }
+}
+
+fn agent_counts(entry: &WorkspaceEntry) -> EventKind {
+    let summary = entry.agent_status.clone().unwrap_or_default();
+    EventKind::AgentStatusChanged {
+        waiting: summary.waiting,
+        working: summary.working,
+        idle: summary.idle,
+    }
+}
+
+fn diff(entry: &WorkspaceEntry) -> EventKind {
+    let stat = &entry.diff_stat;
+    EventKind::DiffChanged {
+        change: entry.change_id.clone(),
+        files: stat.files_changed,
+        insertions: stat.insertions,
+        deletions: stat.deletions,
+        ahead: stat.ahead,
+        behind: stat.behind,
+        dirty: entry.working_copy.is_dirty(),
+    }
+}
+
+/// The events that take workspaces from `old` to `new`: deletions, then
+/// creations, then changes, each in path order. A new workspace's agents
+/// and diff come with its creation, so only its later changes are events.
+pub fn changes(old: &[WorkspaceEntry], new: &[WorkspaceEntry]) -> Vec<Event> {
+    let old_by_path: HashMap<&Path, &WorkspaceEntry> =
+        old.iter().map(|e| (e.path.as_path(), e)).collect();
+    let new_by_path: HashMap<&Path, &WorkspaceEntry> =
+        new.iter().map(|e| (e.path.as_path(), e)).collect();
+
+    let mut deleted: Vec<&WorkspaceEntry> = old
+        .iter()
+        .filter(|e| !new_by_path.contains_key(e.path.as_path()))
+        .collect();
+    deleted.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut new: Vec<&WorkspaceEntry> = new.iter().collect();
+    new.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut events: Vec<Event> = deleted
+        .into_iter()
+        .map(|e| Event::new(EventKind::Deleted, e))
+        .collect();
+    let mut changed = Vec::new();
+    for entry in new {
+        let Some(before) = old_by_path.get(entry.path.as_path()) else {
+            events.push(Event::new(EventKind::Created, entry));
+            continue;
+        };
+        for (now, was) in [
+            (agent_counts(entry), agent_counts(before)),
+            (diff(entry), diff(before)),
+        ] {
+            if now != was {
+                changed.push(Event::new(now, entry));
+            }
+        }
+    }
+    events.extend(changed);
+    events
+}
+
+/// The `~/.dwm/` entries of the repos being watched: every tracked one
+/// with `all`, else the current repo's.
+fn repo_dirs(all: bool) -> Result<Vec<PathBuf>> {
+    if !all {
+        return Ok(vec![workspace::current_repo_dir()?]);
+    }
+    let dwm_base = workspace::dwm_base_dir()?;
+    Ok(workspace::tracked_repo_names(&dwm_base)
+        .into_iter()
+        .map(|name| dwm_base.join(name))
+        .collect())
+}
+
+fn list(all: bool) -> Result<Vec<WorkspaceEntry>> {
+    if all {
+        workspace::list_all_workspace_entries()
+    } else {
+        workspace::list_workspace_entries()
+    }
+}
+
+/// Watch `repos`' entries and agent status, plus `~/.dwm/` itself with
+/// `all` for repos being added; `None` where that isn't possible.
+fn watch_repos(repos: &[PathBuf], all: bool) -> Option<watch::DirWatcher> {
+    let mut dirs = Vec::new();
+    if all {
+        dirs.push(workspace::dwm_base_dir().ok()?);
+    }
+    for rd in repos {
+        dirs.push(rd.clone());
+        dirs.push(agent::agent_status_dir(rd));
+    }
+    watch::DirWatcher::new(&dirs)
+}
+
+/// Reread the agent status of `repos` into `entries`, which is much cheaper
+/// than relisting.
+fn read_agents(repos: &[PathBuf], entries: &mut [WorkspaceEntry]) {
+    for rd in repos {
+        let Ok(Some(meta)) = RepoMeta::load(rd) else {
+            continue;
+        };
+        let mut summaries = agent::read_agent_summaries(rd);
+        for entry in entries
+            .iter_mut()
+            .filter(|e| e.main_repo_path == meta.main_repo)
+        {
+            entry.agent_status = summaries.remove(&entry.name);
+        }
+    }
+}
+
+/// Run `dwm watch`: report workspaces being created and deleted, and
+/// changes to their agent status and diffs, until interrupted or the reader
+/// goes away. With `json`, each event is a line of JSON on stdout;
+/// otherwise a line of text on stderr.
+pub fn run(all: bool, json: bool) -> Result<()> {
+    let mut repos = repo_dirs(all)?;
+    let mut watcher = watch_repos(&repos, all);
+    let mut layout: Vec<_> = repos
+        .iter()
+        .map(|rd| workspace::workspace_dir_names(rd))
+        .collect();
+    let mut entries = list(all)?;
+    let mut listed_at = Instant::now();
+    let mut out = std::io::stdout().lock();
+    loop {
+        match &watcher {
+            Some(watcher) => {
+                watcher.wait(POLL_INTERVAL);
+            }
+            None => std::thread::sleep(POLL_INTERVAL),
+        }
+        let now_repos = repo_dirs(all)?;
+        if now_repos != repos {
+            watcher = watch_repos(&now_repos, all);
+            repos = now_repos;
+        }
+        // Workspaces coming and going show up under `~/.dwm/` right away;
+        // anything else waits for the next relist.
+        let now_layout: Vec<_> = repos
+            .iter()
+            .map(|rd| workspace::workspace_dir_names(rd))
+            .collect();
+        let fresh = if now_layout != layout || listed_at.elapsed() >= RELIST_INTERVAL {
+            layout = now_layout;
+            listed_at = Instant::now();
+            match list(all) {
+                Ok(fresh) => fresh,
+                Err(e) => {
+                    log::warn!("could not list workspaces: {:#}", e);
+                    continue;
+                }
+            }
+        } else {
+            let mut fresh = entries.clone();
+            read_agents(&repos, &mut fresh);
+            fresh
+        };
+        for event in changes(&entries, &fresh) {
+            if !json {
+                log::info!("{}", event);
+                continue;
+            }
+            let line = serde_json::to_string(&event)?;
+            if writeln!(out, "{}", line).and_then(|_| out.flush()).is_err() {
+                // The reader went away; stop quietly.
+                return Ok(());
+            }
+        }
+        entries = fresh;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentSummary;
+    use crate::vcs;
+    use std::fs;
+
+    fn entry(name: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: name.to_string(),
+            path: PathBuf::from("/home/me/.dwm/api-1234").join(name),
+            change_id: "abcd1234".to_string(),
+            main_repo_path: PathBuf::from("/src/api"),
+            vcs_type: vcs::VcsType::Git,
+            ..Default::default()
+        }
+    }
+
+    fn kinds(events: &[Event]) -> Vec<(&str, &EventKind)> {
+        events
+            .iter()
+            .map(|e| (e.workspace.as_str(), &e.kind))
+            .collect()
+    }
+
+    #[test]
+    fn created_and_deleted_workspaces() {
+        let old = vec![entry("fix-login"), entry("hazy-quail")];
+        let new = vec![entry("hazy-quail"), entry("bold-fox")];
+        let events = changes(&old, &new);
+        assert_eq!(
+            kinds(&events),
+            vec![
+                ("fix-login", &EventKind::Deleted),
+                ("bold-fox", &EventKind::Created)
+            ]
+        );
+        assert!(changes(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn agent_and_diff_changes() {
+        let old = vec![entry("fix-login")];
+        let mut new = vec![entry("fix-login")];
+        new[0].agent_status = Some(AgentSummary {
+            waiting: 1,
+            ..Default::default()
+        });
+        new[0].diff_stat.insertions = 4;
+        new[0].working_copy.uncommitted = true;
+        let events = changes(&old, &new);
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].kind,
+            EventKind::AgentStatusChanged {
+                waiting: 1,
+                working: 0,
+                idle: 0
+            }
+        );
+        assert!(matches!(
+            events[1].kind,
+            EventKind::DiffChanged {
+                insertions: 4,
+                dirty: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn finer_agent_status_is_not_an_event() {
+        let mut old = vec![entry("fix-login")];
+        old[0].agent_status = Some(AgentSummary {
+            working: 1,
+            labels: vec![("claude".to_string(), agent::AgentStatus::Thinking)],
+            ..Default::default()
+        });
+        let mut new = old.clone();
+        new[0].agent_status.as_mut().unwrap().labels[0].1 = agent::AgentStatus::RunningTool;
+        assert!(changes(&old, &new).is_empty());
+    }
+
+    #[test]
+    fn events_serialize_as_one_json_object() {
+        let event = Event::new(EventKind::Created, &entry("fix-login"));
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"created","workspace":"fix-login","path":"/home/me/.dwm/api-1234/fix-login","main_repo":"/src/api"}"#
+        );
+        let event = Event::new(
+            EventKind::AgentStatusChanged {
+                waiting: 1,
+                working: 2,
+                idle: 0,
+            },
+            &entry("fix-login"),
+        );
+        let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "agent-status-changed");
+        assert_eq!(json["working"], 2);
+        assert_eq!(
+            event.to_string(),
+            "fix-login agents: 1 waiting, 2 working, 0 idle"
+        );
+    }
+
+    #[test]
+    fn read_agents_matches_entries_by_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let rd = tmp.path().join("api-1234");
+        fs::create_dir_all(&rd).unwrap();
+        fs::write(rd.join(".main-repo"), "/src/api\n").unwrap();
+        let mut other = entry("fix-login");
+        other.main_repo_path = PathBuf::from("/src/web");
+        let mut entries = vec![entry("fix-login"), other];
+        for entry in &mut entries {
+            entry.agent_status = Some(AgentSummary {
+                idle: 1,
+                ..Default::default()
+            });
+        }
+        // No status files, so this repo's sessions are gone.
+        read_agents(&[rd], &mut entries);
+        assert_eq!(entries[0].agent_status, None);
+        assert!(entries[1].agent_status.is_some());
+    }
+}
@@ -0,0 +1,236 @@
+use crate::fuzzy;
+use crate::workspace::WorkspaceEntry;
+
+/// Description of the filter syntax shown in the help popup, one line each.
+pub const HELP: &[&str] = &[
+    "fuzzy match on name, description, or bookmark; best first",
+    "repo:NAME  bookmark:NAME  stale:yes|no",
+    "agent:waiting|working|idle|any|none",
+    "terms combine, e.g. repo:api agent:waiting login",
+];
+
+/// Which agent state an `agent:` term asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AgentFilter {
+    Waiting,
+    Working,
+    Idle,
+    /// Any live agent at all.
+    Any,
+    /// No live agents.
+    None,
+}
+
+/// One `key:value` term of a filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    /// Repo name contains the value (case-insensitive).
+    Repo(String),
+    /// Some bookmark contains the value (case-insensitive).
+    Bookmark(String),
+    Agent(AgentFilter),
+    Stale(bool),
+}
+
+impl Predicate {
+    /// Parse a `key:value` term. Unknown keys and values return `None` so the
+    /// term is matched as free text instead.
+    fn parse(term: &str) -> Option<Self> {
+        let (key, value) = term.split_once(':')?;
+        if value.is_empty() {
+            return None;
+        }
+        let value = value.to_lowercase();
+        Some(match key.to_lowercase().as_str() {
+            "repo" => Predicate::Repo(value),
+            "bookmark" => Predicate::Bookmark(value),
+            "agent" => Predicate::Agent(match value.as_str() {
+                "waiting" => AgentFilter::Waiting,
+                "working" => AgentFilter::Working,
+                "idle" => AgentFilter::Idle,
+                "any" => AgentFilter::Any,
+                "none" => AgentFilter::None,
+                _ => return None,
+            }),
+            "stale" => Predicate::Stale(match value.as_str() {
+                "yes" | "true" => true,
+                "no" | "false" => false,
+                _ => return None,
+            }),
+            _ => return None,
+        })
+    }
+
+    fn matches(&self, entry: &WorkspaceEntry) -> bool {
+        match self {
+            Predicate::Repo(repo) => repo_name(entry).to_lowercase().contains(repo),
+            Predicate::Bookmark(bookmark) => entry
+                .bookmarks
+                .iter()
+                .any(|b| b.to_lowercase().contains(bookmark)),
+            Predicate::Agent(filter) => {
+                let summary = entry.agent_status.clone().unwrap_or_default();
+                match filter {
+                    AgentFilter::Waiting => summary.waiting > 0,
+                    AgentFilter::Working => summary.working > 0,
+                    AgentFilter::Idle => summary.idle > 0,
+                    AgentFilter::Any => !summary.is_empty(),
+                    AgentFilter::None => summary.is_empty(),
+                }
+            }
+            Predicate::Stale(stale) => entry.is_stale == *stale,
+        }
+    }
+}
+
+/// The repo an entry belongs to: its tracked repo name in the multi-repo
+/// picker, or the main repo's directory name otherwise.
+fn repo_name(entry: &WorkspaceEntry) -> String {
+    match &entry.repo_name {
+        Some(name) => name.clone(),
+        None => entry
+            .main_repo_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// A parsed picker filter: `key:value` predicates, all of which must hold,
+/// plus the remaining free text, fuzzy-matched as before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    predicates: Vec<Predicate>,
+    text: String,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Self {
+        let mut predicates = Vec::new();
+        let mut text = Vec::new();
+        for term in input.split_whitespace() {
+            match Predicate::parse(term) {
+                Some(predicate) => predicates.push(predicate),
+                None => text.push(term),
+            }
+        }
+        Self {
+            predicates,
+            text: text.join(" "),
+        }
+    }
+
+    /// Whether the query keeps every entry.
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty() && self.text.is_empty()
+    }
+
+    /// Score `entry` against the query: `None` if any predicate fails or the
+    /// free text matches none of its name, description, and bookmarks,
+    /// otherwise the best fuzzy score (0 with no free text).
+    pub fn score(&self, entry: &WorkspaceEntry) -> Option<i64> {
+        if !self.predicates.iter().all(|p| p.matches(entry)) {
+            return None;
+        }
+        std::iter::once(entry.name.as_str())
+            .chain(std::iter::once(entry.description.as_str()))
+            .chain(entry.bookmarks.iter().map(String::as_str))
+            .filter_map(|field| fuzzy::score(field, &self.text))
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentSummary;
+    use std::path::PathBuf;
+
+    fn entry(name: &str, repo: Option<&str>) -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/tmp/{}", name)),
+            bookmarks: vec!["release-1.2".to_string()],
+            repo_name: repo.map(str::to_string),
+            main_repo_path: PathBuf::from("/src/backend"),
+            ..Default::default()
+        }
+    }
+
+    fn matches(query: &str, entry: &WorkspaceEntry) -> bool {
+        Query::parse(query).score(entry).is_some()
+    }
+
+    #[test]
+    fn parse_splits_predicates_from_text() {
+        let query = Query::parse("repo:API  login agent:waiting page");
+        assert_eq!(
+            query.predicates,
+            vec![
+                Predicate::Repo("api".to_string()),
+                Predicate::Agent(AgentFilter::Waiting)
+            ]
+        );
+        assert_eq!(query.text, "login page");
+    }
+
+    #[test]
+    fn unknown_keys_and_values_are_free_text() {
+        let query = Query::parse("foo:bar agent:sleepy stale:maybe repo:");
+        assert!(query.predicates.is_empty());
+        assert_eq!(query.text, "foo:bar agent:sleepy stale:maybe repo:");
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert!(Query::parse("  ").is_empty());
+        assert_eq!(Query::parse("").score(&entry("ws", None)), Some(0));
+    }
+
+    #[test]
+    fn repo_predicate_uses_repo_name_or_main_repo_dir() {
+        assert!(matches("repo:front", &entry("ws", Some("frontend"))));
+        assert!(!matches("repo:back", &entry("ws", Some("frontend"))));
+        assert!(matches("repo:backend", &entry("ws", None)));
+    }
+
+    #[test]
+    fn bookmark_predicate_matches_substring() {
+        assert!(matches("bookmark:release", &entry("ws", None)));
+        assert!(!matches("bookmark:hotfix", &entry("ws", None)));
+    }
+
+    #[test]
+    fn agent_predicate_checks_counts() {
+        let mut e = entry("ws", None);
+        assert!(matches("agent:none", &e));
+        assert!(!matches("agent:any", &e));
+        e.agent_status = Some(AgentSummary {
+            waiting: 1,
+            working: 0,
+            idle: 0,
+            ..Default::default()
+        });
+        assert!(matches("agent:waiting", &e));
+        assert!(matches("agent:any", &e));
+        assert!(!matches("agent:working", &e));
+        assert!(!matches("agent:none", &e));
+    }
+
+    #[test]
+    fn stale_predicate() {
+        let mut e = entry("ws", None);
+        assert!(matches("stale:no", &e));
+        e.is_stale = true;
+        assert!(matches("stale:yes", &e));
+        assert!(!matches("stale:false", &e));
+    }
+
+    #[test]
+    fn predicates_and_text_must_all_match() {
+        let e = entry("login-page", Some("api"));
+        assert!(matches("repo:api lp", &e));
+        assert!(!matches("repo:api signup", &e));
+        assert!(!matches("repo:web lp", &e));
+    }
+}
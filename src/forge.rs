@@ -0,0 +1,416 @@
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::gitea::Gitea;
+use crate::github::GitHub;
+use crate::gitlab::GitLab;
+use crate::vcs;
+
+// What's fetched, and the cache it's kept in, are in `dwm-core`, since
+// workspace listings carry them.
+pub use dwm_core::forge::{
+    Checks, CiRun, FORGE_TTL, ForgeCache, Issue, PullRequest, ReviewDecision,
+};
+
+/// A code host dwm shows pull request and CI state for and opens pull
+/// requests on. GitLab's merge requests count as pull requests here.
+pub trait Forge: Send + Sync {
+    /// The forge's name for messages, like `GitHub`.
+    fn name(&self) -> &'static str;
+
+    /// The repo's open pull requests, keyed by head branch.
+    fn open_prs(&self, main_repo: &Path) -> Result<HashMap<String, PullRequest>>;
+
+    /// Open a pull request from `branch` into the repo's default branch.
+    /// Returns its URL.
+    fn create_pr(&self, main_repo: &Path, branch: &str, title: &str, body: &str) -> Result<String>;
+
+    /// The CI state of commit `sha`, or `None` if nothing ran on it.
+    fn commit_ci(&self, main_repo: &Path, sha: &str) -> Result<Option<CiRun>>;
+
+    /// Issue `number` in the repo's tracker.
+    fn issue(&self, main_repo: &Path, number: u64) -> Result<Issue>;
+}
+
+/// The kinds of forge dwm knows, as named in the `forges` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    /// Gitea, and Forgejo, which speaks the same API.
+    #[serde(alias = "forgejo")]
+    Gitea,
+}
+
+/// Settings for the forge at one host, from the `forges` setting.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ForgeConfig {
+    /// What the host runs, for self-hosted forges dwm can't tell from the
+    /// host name.
+    #[serde(rename = "type")]
+    pub kind: Option<ForgeKind>,
+    /// API token, in place of the forge's usual environment variable.
+    pub token: Option<String>,
+    /// Base URL of the web interface, when it isn't `https://<host>`.
+    pub url: Option<String>,
+}
+
+/// A remote's host and repo path, from its URL: `git@host:owner/repo.git`,
+/// `ssh://git@host:22/owner/repo`, and `https://host/owner/repo.git` all
+/// give `host` and `owner/repo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remote {
+    pub host: String,
+    pub path: String,
+}
+
+impl Remote {
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = match url.split_once("://") {
+            Some((_, rest)) => {
+                let (authority, path) = rest.split_once('/')?;
+                let host = authority.rsplit('@').next()?;
+                (host.split(':').next()?, path)
+            }
+            // scp-like syntax: [user@]host:path
+            None => {
+                let (authority, path) = url.split_once(':')?;
+                (authority.rsplit('@').next()?, path)
+            }
+        };
+        let path = path.trim_matches('/').trim_end_matches(".git");
+        if host.is_empty() || path.is_empty() {
+            return None;
+        }
+        Some(Self {
+            host: host.to_lowercase(),
+            path: path.to_string(),
+        })
+    }
+
+    /// The `origin` remote of `main_repo`, else its first.
+    fn of(main_repo: &Path) -> Option<Self> {
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(main_repo)
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+        };
+        let url = git(&["remote", "get-url", "origin"]).or_else(|| {
+            let first = git(&["remote"])?.lines().next()?.to_string();
+            git(&["remote", "get-url", &first])
+        })?;
+        Self::parse(&url)
+    }
+}
+
+/// Which forge `remote` is on: as configured for its host, else guessed
+/// from the host name.
+fn kind_of(remote: &Remote, forges: &HashMap<String, ForgeConfig>) -> Option<ForgeKind> {
+    if let Some(kind) = forges.get(&remote.host).and_then(|f| f.kind) {
+        return Some(kind);
+    }
+    let host = remote.host.as_str();
+    if host == "github.com" {
+        Some(ForgeKind::GitHub)
+    } else if host.contains("gitlab") {
+        Some(ForgeKind::GitLab)
+    } else if host == "codeberg.org" || host.contains("gitea") || host.contains("forgejo") {
+        Some(ForgeKind::Gitea)
+    } else {
+        None
+    }
+}
+
+/// The forge `main_repo`'s remote is on, if it's one dwm knows, set up
+/// with any token and URL from the `forges` setting.
+pub fn detect(main_repo: &Path) -> Option<Box<dyn Forge>> {
+    let remote = Remote::of(main_repo)?;
+    let config = Config::load().unwrap_or_default();
+    let kind = kind_of(&remote, &config.forges)?;
+    let settings = config.forges.get(&remote.host).cloned().unwrap_or_default();
+    Some(match kind {
+        ForgeKind::GitHub => Box::new(GitHub::new(settings.token)),
+        ForgeKind::GitLab => Box::new(GitLab::new(settings.token)),
+        ForgeKind::Gitea => Box::new(Gitea::new(&remote, settings)),
+    })
+}
+
+/// The pull request for `branch` on `forge`: the open one if there is one,
+/// else a new one titled with the first line of `description` and
+/// described by the rest. Returns its URL and whether it was just created.
+pub fn open_pr(
+    forge: &dyn Forge,
+    main_repo: &Path,
+    branch: &str,
+    description: &str,
+) -> Result<(String, bool)> {
+    if let Some(pr) = forge.open_prs(main_repo)?.remove(branch)
+        && !pr.url.is_empty()
+    {
+        return Ok((pr.url, false));
+    }
+    let (title, body) = title_and_body(description, branch);
+    let url = forge
+        .create_pr(main_repo, branch, &title, &body)
+        .with_context(|| format!("could not open a {} pull request", forge.name()))?;
+    Ok((url, true))
+}
+
+/// The pull request title and body for a workspace described by
+/// `description`: its first line and the rest. An undescribed workspace is
+/// titled after `branch`.
+fn title_and_body(description: &str, branch: &str) -> (String, String) {
+    let description = description.trim();
+    match description.split_once('\n') {
+        Some((title, body)) => (title.trim().to_string(), body.trim().to_string()),
+        None if description.is_empty() => (branch.to_string(), String::new()),
+        None => (description.to_string(), String::new()),
+    }
+}
+
+/// The last URL on a line of its own in `output`, where `gh` and `glab`
+/// print the pull request they made.
+pub fn last_url(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .map(str::trim)
+        .rfind(|line| line.starts_with("https://") || line.starts_with("http://"))
+}
+
+/// Run the forge client `cli` with `args` in `main_repo`, returning its
+/// stdout. `token`, if given, is passed in the `token_var` environment
+/// variable the client reads.
+pub fn run_cli(
+    cli: &str,
+    main_repo: &Path,
+    args: &[&str],
+    token: Option<(&str, &str)>,
+) -> Result<String> {
+    log::debug!("{} {} (in {})", cli, args.join(" "), main_repo.display());
+    let mut cmd = Command::new(cli);
+    cmd.args(args).current_dir(main_repo);
+    if let Some((token_var, token)) = token {
+        cmd.env(token_var, token);
+    }
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to run {} - is it installed?", cli))?;
+    if !output.status.success() {
+        bail!(
+            "{} {} failed: {}",
+            cli,
+            args[..2.min(args.len())].join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Which forge state to fetch and show for a repo, from the `pr_status`
+/// and `ci_status` settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Shown {
+    pub prs: bool,
+    pub ci: bool,
+}
+
+impl Shown {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            prs: config.pr_status,
+            ci: config.ci_status,
+        }
+    }
+
+    pub fn any(self) -> bool {
+        self.prs || self.ci
+    }
+}
+
+/// Fetch the `shown` forge state for `main_repo` into `repo_dir`'s cache:
+/// its open pull requests, and the CI state of its worktrees' heads.
+/// Skipped if the cache has a fetch attempt from the last [`FORGE_TTL`], or
+/// the repo isn't on a forge dwm knows. Like `dwm status --fetch`, the
+/// attempt is stamped first, so a missing client or an unreachable forge
+/// isn't retried on every run. Returns the new cache if it fetched.
+pub fn refresh(
+    repo_dir: &Path,
+    main_repo: &Path,
+    shown: Shown,
+    now: SystemTime,
+) -> Result<Option<ForgeCache>> {
+    let mut cache = ForgeCache::load(repo_dir);
+    if !shown.any() || cache.is_fresh(now) {
+        return Ok(None);
+    }
+    let Some(forge) = detect(main_repo) else {
+        return Ok(None);
+    };
+    cache.fetched_at = now.duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    cache.save(repo_dir)?;
+    cache.branches = if shown.prs {
+        forge
+            .open_prs(main_repo)
+            .with_context(|| format!("could not list {} pull requests", forge.name()))?
+    } else {
+        HashMap::new()
+    };
+    cache.commits = if shown.ci {
+        let heads = crate::git::worktree_heads(main_repo)?;
+        let runs = vcs::parallel_map(&heads, |sha| {
+            forge
+                .commit_ci(main_repo, sha)
+                .inspect_err(|e| log::debug!("could not get CI for {}: {:#}", sha, e))
+                .ok()
+                .flatten()
+        });
+        heads
+            .into_iter()
+            .zip(runs)
+            .filter_map(|(sha, ci)| Some((sha, ci?)))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+    cache.save(repo_dir)?;
+    Ok(Some(cache))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dwm_core::forge::FORGE_CACHE_FILE;
+
+    #[test]
+    fn remotes_parse_to_host_and_path() {
+        for url in [
+            "git@github.com:me/api.git",
+            "ssh://git@github.com:22/me/api",
+            "https://github.com/me/api.git\n",
+            "https://token@GitHub.com/me/api/",
+        ] {
+            assert_eq!(
+                Remote::parse(url),
+                Some(Remote {
+                    host: "github.com".to_string(),
+                    path: "me/api".to_string(),
+                }),
+                "{}",
+                url
+            );
+        }
+        assert_eq!(
+            Remote::parse("https://gitlab.example.com/group/sub/api.git")
+                .unwrap()
+                .path,
+            "group/sub/api"
+        );
+        assert_eq!(Remote::parse("/srv/git/api.git"), None);
+    }
+
+    #[test]
+    fn kind_comes_from_config_then_host() {
+        let remote = |host: &str| Remote {
+            host: host.to_string(),
+            path: "me/api".to_string(),
+        };
+        let none = HashMap::new();
+        assert_eq!(
+            kind_of(&remote("github.com"), &none),
+            Some(ForgeKind::GitHub)
+        );
+        assert_eq!(
+            kind_of(&remote("gitlab.example.com"), &none),
+            Some(ForgeKind::GitLab)
+        );
+        assert_eq!(
+            kind_of(&remote("codeberg.org"), &none),
+            Some(ForgeKind::Gitea)
+        );
+        assert_eq!(kind_of(&remote("git.example.com"), &none), None);
+
+        let forges: HashMap<String, ForgeConfig> =
+            serde_json::from_str(r#"{"git.example.com": {"type": "forgejo", "token": "t"}}"#)
+                .unwrap();
+        assert_eq!(
+            kind_of(&remote("git.example.com"), &forges),
+            Some(ForgeKind::Gitea)
+        );
+    }
+
+    #[test]
+    fn title_is_the_first_line_of_the_description() {
+        assert_eq!(
+            title_and_body("Fix login\n\nTokens expired early.\n", "fix-login"),
+            ("Fix login".to_string(), "Tokens expired early.".to_string())
+        );
+        assert_eq!(
+            title_and_body("Fix login", "fix-login"),
+            ("Fix login".to_string(), String::new())
+        );
+        assert_eq!(
+            title_and_body("  \n", "fix-login"),
+            ("fix-login".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn last_url_skips_progress_lines() {
+        let gh = "\nCreating pull request for fix-login into main in me/api\n\nhttps://github.com/me/api/pull/42\n";
+        assert_eq!(last_url(gh), Some("https://github.com/me/api/pull/42"));
+        assert_eq!(last_url("no pull request found\n"), None);
+    }
+
+    #[test]
+    fn refresh_skips_a_fresh_cache() {
+        let tmp = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let cache = ForgeCache {
+            fetched_at: now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            ..Default::default()
+        };
+        cache.save(tmp.path()).unwrap();
+        let shown = Shown {
+            prs: true,
+            ci: true,
+        };
+        // A fresh cache returns before looking at remotes or running a client.
+        assert!(
+            refresh(tmp.path(), Path::new("/nonexistent"), shown, now)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn refresh_skips_when_nothing_is_shown() {
+        let tmp = tempfile::tempdir().unwrap();
+        let shown = Shown::default();
+        assert!(
+            refresh(
+                tmp.path(),
+                Path::new("/nonexistent"),
+                shown,
+                SystemTime::now()
+            )
+            .unwrap()
+            .is_none()
+        );
+        assert!(!tmp.path().join(FORGE_CACHE_FILE).exists());
+    }
+}
@@ -0,0 +1,311 @@
+use anyhow::{Result, bail};
+
+use crate::agent::AgentSummary;
+use crate::workspace::{self, WorkspaceEntry};
+
+/// Fields a `dwm list --format` template can name, each as `{field}`.
+const FIELDS: &[&str] = &[
+    "name",
+    "repo",
+    "path",
+    "main_repo",
+    "description",
+    "change",
+    "bookmarks",
+    "modified",
+    "modified_at",
+    "files",
+    "insertions",
+    "deletions",
+    "ahead",
+    "behind",
+    "dirty",
+    "main",
+    "stale",
+    "agents",
+    "waiting",
+    "working",
+    "idle",
+    "vcs",
+    "error",
+    "pr",
+    "ci",
+];
+
+/// Fields of a `--porcelain` line, tab-separated in this order. This is a
+/// stable contract: fields are only ever added at the end, and existing ones
+/// keep their meaning and formatting, so scripts can index by position.
+pub const PORCELAIN_FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "main_repo",
+    "main",
+    "dirty",
+    "stale",
+    "change",
+    "bookmarks",
+    "modified_at",
+    "files",
+    "insertions",
+    "deletions",
+    "ahead",
+    "behind",
+    "waiting",
+    "working",
+    "idle",
+    "vcs",
+    "error",
+    "description",
+    "pr",
+    "ci",
+];
+
+/// One piece of a parsed template.
+#[derive(Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Field(&'static str),
+}
+
+/// A `dwm list --format` template, like `{name}\t{path}\t{modified}`: text
+/// with `{field}` placeholders, `{{` and `}}` for literal braces, and `\t`,
+/// `\n`, and `\\` escapes so templates can be written in single quotes.
+#[derive(Debug, PartialEq)]
+pub struct ListFormat(Vec<Part>);
+
+impl ListFormat {
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => bail!("unclosed '{{{}' in format", name),
+                        }
+                    }
+                    let Some(field) = FIELDS.iter().find(|f| **f == name.trim()) else {
+                        bail!(
+                            "unknown field '{{{}}}' in format (fields: {})",
+                            name,
+                            FIELDS.join(", ")
+                        );
+                    };
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => bail!("unmatched '}}' in format; write '}}}}' for a literal brace"),
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => {
+                        text.push('\\');
+                        text.push(other);
+                    }
+                    None => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Self(parts))
+    }
+
+    /// The frozen template behind `--porcelain`: every [`PORCELAIN_FIELDS`]
+    /// field, tab-separated.
+    pub fn porcelain() -> Self {
+        let mut parts = Vec::new();
+        for (i, field) in PORCELAIN_FIELDS.iter().enumerate() {
+            if i > 0 {
+                parts.push(Part::Text("\t".to_string()));
+            }
+            parts.push(Part::Field(field));
+        }
+        Self(parts)
+    }
+
+    /// Fill in the template for `entry`.
+    pub fn render(&self, entry: &WorkspaceEntry) -> String {
+        self.0
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Field(field) => field_value(entry, field),
+            })
+            .collect()
+    }
+}
+
+/// The value of `field` for `entry`. Text fields are cut to their first
+/// line with tabs replaced, so one entry stays one line of columns.
+fn field_value(entry: &WorkspaceEntry, field: &str) -> String {
+    let stat = &entry.diff_stat;
+    let value = match field {
+        "name" => entry.name.clone(),
+        "repo" => entry.repo_name.clone().unwrap_or_default(),
+        "path" => entry.path.display().to_string(),
+        "main_repo" => entry.main_repo_path.display().to_string(),
+        "description" => entry.description.lines().next().unwrap_or("").to_string(),
+        "change" => entry.change_id.clone(),
+        "bookmarks" => entry.bookmarks.join(","),
+        "modified" => workspace::format_time_ago(entry.last_modified),
+        "modified_at" => entry
+            .last_modified
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs().to_string())
+            .unwrap_or_default(),
+        "files" => stat.files_changed.to_string(),
+        "insertions" => stat.insertions.to_string(),
+        "deletions" => stat.deletions.to_string(),
+        "ahead" => stat.ahead.to_string(),
+        "behind" => stat.behind.to_string(),
+        "dirty" => entry.working_copy.is_dirty().to_string(),
+        "main" => entry.is_main.to_string(),
+        "stale" => entry.is_stale.to_string(),
+        "agents" => entry
+            .agent_status
+            .as_ref()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+        "waiting" => agent_count(entry, |s| s.waiting),
+        "working" => agent_count(entry, |s| s.working),
+        "idle" => agent_count(entry, |s| s.idle),
+        "vcs" => entry.vcs_type.to_string(),
+        "error" => entry
+            .error
+            .as_ref()
+            .map(|e| e.to_string())
+            .unwrap_or_default(),
+        "pr" => entry
+            .pr
+            .as_ref()
+            .map(|pr| pr.to_string())
+            .unwrap_or_default(),
+        "ci" => entry
+            .ci
+            .as_ref()
+            .map(|ci| ci.to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    value.replace(['\t', '\n'], " ")
+}
+
+/// How many of `entry`'s agent sessions `count` picks out of its summary.
+fn agent_count(entry: &WorkspaceEntry, count: impl Fn(&AgentSummary) -> u32) -> String {
+    entry.agent_status.as_ref().map_or(0, count).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vcs;
+    use std::path::PathBuf;
+
+    fn entry() -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: "feat".to_string(),
+            path: PathBuf::from("/ws/feat"),
+            diff_stat: vcs::DiffStat {
+                files_changed: 2,
+                insertions: 10,
+                deletions: 3,
+                ahead: 1,
+                ..Default::default()
+            },
+            change_id: "abc123".to_string(),
+            description: "Fix\tlogin\nmore detail".to_string(),
+            bookmarks: vec!["feat".to_string(), "wip".to_string()],
+            main_repo_path: PathBuf::from("/repo"),
+            vcs_type: vcs::VcsType::Git,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_fields_and_escapes() {
+        let format = ListFormat::parse(r"{name}\t{path}\t{description}").unwrap();
+        assert_eq!(format.render(&entry()), "feat\t/ws/feat\tFix login");
+        let format =
+            ListFormat::parse("{{{change}}} +{insertions} -{deletions} {bookmarks}").unwrap();
+        assert_eq!(format.render(&entry()), "{abc123} +10 -3 feat,wip");
+        let format = ListFormat::parse("{repo}|{dirty}|{main}|{ahead}|{vcs}").unwrap();
+        assert_eq!(format.render(&entry()), "|false|false|1|git");
+    }
+
+    #[test]
+    fn porcelain_lines_have_every_field_in_order() {
+        let mut e = entry();
+        e.last_modified =
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000));
+        e.agent_status = Some(AgentSummary {
+            waiting: 1,
+            working: 2,
+            ..Default::default()
+        });
+        e.ci = Some(crate::forge::CiRun {
+            checks: crate::forge::Checks::Pending,
+            url: String::new(),
+        });
+        let line = ListFormat::porcelain().render(&e);
+        let fields: Vec<&str> = line.split('\t').collect();
+        assert_eq!(fields.len(), PORCELAIN_FIELDS.len());
+        assert_eq!(
+            fields,
+            [
+                "feat",
+                "/ws/feat",
+                "/repo",
+                "false",
+                "false",
+                "false",
+                "abc123",
+                "feat,wip",
+                "1700000000",
+                "2",
+                "10",
+                "3",
+                "1",
+                "0",
+                "1",
+                "2",
+                "0",
+                "git",
+                "",
+                "Fix login",
+                "",
+                "⋯ running",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_stray_braces() {
+        let err = ListFormat::parse("{nme}").unwrap_err();
+        assert!(
+            err.to_string().starts_with("unknown field '{nme}'"),
+            "{}",
+            err
+        );
+        assert!(ListFormat::parse("{name").is_err());
+        assert!(ListFormat::parse("name}").is_err());
+    }
+}
@@ -0,0 +1,352 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::forge::{Checks, CiRun, Forge, ForgeConfig, Issue, PullRequest, Remote, ReviewDecision};
+use crate::vcs;
+
+/// Gitea or Forgejo, through their REST API with `curl`.
+pub struct Gitea {
+    /// Base of the API, like `https://codeberg.org/api/v1/repos/me/api`.
+    repo_api: String,
+    /// From the `forges` setting, else `GITEA_TOKEN` or `FORGEJO_TOKEN`.
+    /// Reading a public repo works without one.
+    token: Option<String>,
+}
+
+impl Gitea {
+    pub fn new(remote: &Remote, settings: ForgeConfig) -> Self {
+        let base = settings
+            .url
+            .unwrap_or_else(|| format!("https://{}", remote.host));
+        let token = settings.token.or_else(|| {
+            ["GITEA_TOKEN", "FORGEJO_TOKEN"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok().filter(|t| !t.is_empty()))
+        });
+        Self {
+            repo_api: format!(
+                "{}/api/v1/repos/{}",
+                base.trim_end_matches('/'),
+                remote.path
+            ),
+            token,
+        }
+    }
+
+    /// Call `endpoint` under the repo's API, POSTing `body` if given, and
+    /// parse the JSON reply. The token goes to `curl` on stdin rather than
+    /// the command line, where other users could see it.
+    fn api<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<T> {
+        let url = format!("{}{}", self.repo_api, endpoint);
+        log::debug!("curl {}", url);
+        let mut cmd = Command::new("curl");
+        cmd.args(["-fsS", "-m", "10", "-K", "-"])
+            .args(["-H", "Accept: application/json"]);
+        if let Some(body) = body {
+            cmd.args(["-X", "POST", "-H", "Content-Type: application/json"])
+                .arg("--data-binary")
+                .arg(body.to_string());
+        }
+        let mut child = cmd
+            .arg(&url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("failed to run curl - is it installed?")?;
+        // Closed either way, so curl stops reading its config.
+        let mut stdin = child.stdin.take().context("curl has no stdin")?;
+        if let Some(token) = &self.token {
+            writeln!(stdin, "header = \"Authorization: token {}\"", token)?;
+        }
+        drop(stdin);
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "{} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        serde_json::from_slice(&output.stdout).with_context(|| format!("could not parse {}", url))
+    }
+
+    /// The review decision on pull request `number`: the latest approval or
+    /// change request that still stands, else whether reviewers were asked.
+    fn review(&self, pr: &GtPullRequest) -> Option<ReviewDecision> {
+        let reviews: Vec<GtReview> = self
+            .api(&format!("/pulls/{}/reviews", pr.number), None)
+            .ok()?;
+        decide(&reviews, !pr.requested_reviewers.is_empty())
+    }
+
+    /// The combined commit status of `sha`, or `None` without any.
+    fn checks(&self, sha: &str) -> Option<Checks> {
+        let status: GtCombinedStatus = self.api(&format!("/commits/{}/status", sha), None).ok()?;
+        checks_from(&status)
+    }
+}
+
+impl Forge for Gitea {
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn open_prs(&self, _main_repo: &Path) -> Result<HashMap<String, PullRequest>> {
+        let prs: Vec<GtPullRequest> = self.api("/pulls?state=open&limit=50", None)?;
+        // The list has neither reviews nor CI state, so ask about each
+        // pull request, a few at a time.
+        let details = vcs::parallel_map(&prs, |pr| (self.review(pr), self.checks(&pr.head.sha)));
+        Ok(prs
+            .into_iter()
+            .zip(details)
+            .map(|(pr, (review, checks))| {
+                let pull_request = PullRequest {
+                    number: pr.number,
+                    url: pr.html_url,
+                    draft: pr.draft,
+                    review,
+                    checks,
+                };
+                (pr.head.branch, pull_request)
+            })
+            .collect())
+    }
+
+    fn create_pr(
+        &self,
+        _main_repo: &Path,
+        branch: &str,
+        title: &str,
+        body: &str,
+    ) -> Result<String> {
+        if self.token.is_none() {
+            bail!(
+                "opening a pull request needs a token: set GITEA_TOKEN or a token in the forges setting"
+            );
+        }
+        let repo: GtRepo = self.api("", None)?;
+        let created: GtPullRequest = self.api(
+            "/pulls",
+            Some(&serde_json::json!({
+                "head": branch,
+                "base": repo.default_branch,
+                "title": title,
+                "body": body,
+            })),
+        )?;
+        Ok(created.html_url)
+    }
+
+    fn commit_ci(&self, _main_repo: &Path, sha: &str) -> Result<Option<CiRun>> {
+        let status: GtCombinedStatus = self.api(&format!("/commits/{}/status", sha), None)?;
+        Ok(ci_from(status))
+    }
+
+    fn issue(&self, _main_repo: &Path, number: u64) -> Result<Issue> {
+        let issue: GtIssue = self.api(&format!("/issues/{}", number), None)?;
+        Ok(Issue {
+            number: issue.number,
+            title: issue.title,
+            url: issue.html_url,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct GtRepo {
+    default_branch: String,
+}
+
+#[derive(Deserialize)]
+struct GtPullRequest {
+    number: u64,
+    #[serde(default)]
+    html_url: String,
+    /// Only sent by newer versions; older ones mark drafts in the title.
+    #[serde(default)]
+    draft: bool,
+    head: GtBranch,
+    #[serde(default)]
+    requested_reviewers: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct GtIssue {
+    number: u64,
+    title: String,
+    #[serde(default)]
+    html_url: String,
+}
+
+#[derive(Deserialize)]
+struct GtBranch {
+    #[serde(rename = "ref")]
+    branch: String,
+    #[serde(default)]
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct GtReview {
+    state: String,
+    #[serde(default)]
+    dismissed: bool,
+}
+
+#[derive(Deserialize)]
+struct GtCombinedStatus {
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    total_count: u64,
+    #[serde(default)]
+    statuses: Vec<GtStatus>,
+}
+
+/// One status posted on a commit, like a CI run's.
+#[derive(Deserialize)]
+struct GtStatus {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    target_url: String,
+}
+
+/// The review decision from `reviews`, oldest first.
+fn decide(reviews: &[GtReview], reviewers_requested: bool) -> Option<ReviewDecision> {
+    let latest =
+        reviews
+            .iter()
+            .rev()
+            .filter(|r| !r.dismissed)
+            .find_map(|r| match r.state.as_str() {
+                "APPROVED" => Some(ReviewDecision::Approved),
+                "REQUEST_CHANGES" => Some(ReviewDecision::ChangesRequested),
+                _ => None,
+            });
+    latest.or(reviewers_requested.then_some(ReviewDecision::ReviewRequired))
+}
+
+fn checks_from(status: &GtCombinedStatus) -> Option<Checks> {
+    if status.total_count == 0 {
+        return None;
+    }
+    Some(state_checks(&status.state).unwrap_or(Checks::Pending))
+}
+
+/// The CI state of a commit from its statuses.
+fn ci_from(status: GtCombinedStatus) -> Option<CiRun> {
+    CiRun::combine(
+        status
+            .statuses
+            .into_iter()
+            .map(|s| (state_checks(&s.status), s.target_url)),
+    )
+}
+
+/// A commit status state as [`Checks`].
+fn state_checks(state: &str) -> Option<Checks> {
+    match state {
+        "success" | "warning" => Some(Checks::Passing),
+        "failure" | "error" => Some(Checks::Failing),
+        "pending" => Some(Checks::Pending),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(state: &str, dismissed: bool) -> GtReview {
+        GtReview {
+            state: state.to_string(),
+            dismissed,
+        }
+    }
+
+    #[test]
+    fn latest_standing_review_decides() {
+        let reviews = vec![
+            review("REQUEST_CHANGES", false),
+            review("APPROVED", false),
+            review("COMMENT", false),
+        ];
+        assert_eq!(decide(&reviews, false), Some(ReviewDecision::Approved));
+        let reviews = vec![review("APPROVED", false), review("REQUEST_CHANGES", true)];
+        assert_eq!(decide(&reviews, false), Some(ReviewDecision::Approved));
+        assert_eq!(decide(&[], true), Some(ReviewDecision::ReviewRequired));
+        assert_eq!(decide(&[review("COMMENT", false)], false), None);
+    }
+
+    #[test]
+    fn combined_status_maps_to_checks() {
+        let status = |state: &str, total_count| GtCombinedStatus {
+            state: state.to_string(),
+            total_count,
+            statuses: Vec::new(),
+        };
+        assert_eq!(checks_from(&status("success", 2)), Some(Checks::Passing));
+        assert_eq!(checks_from(&status("error", 1)), Some(Checks::Failing));
+        assert_eq!(checks_from(&status("pending", 1)), Some(Checks::Pending));
+        assert_eq!(checks_from(&status("", 0)), None);
+    }
+
+    #[test]
+    fn api_base_comes_from_remote_or_setting() {
+        let remote = Remote::parse("git@codeberg.org:me/api.git").unwrap();
+        let gitea = Gitea::new(&remote, ForgeConfig::default());
+        assert_eq!(gitea.repo_api, "https://codeberg.org/api/v1/repos/me/api");
+        let gitea = Gitea::new(
+            &remote,
+            ForgeConfig {
+                url: Some("https://git.example.com/forge/".to_string()),
+                token: Some("t".to_string()),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            gitea.repo_api,
+            "https://git.example.com/forge/api/v1/repos/me/api"
+        );
+        assert_eq!(gitea.token.as_deref(), Some("t"));
+    }
+
+    #[test]
+    fn pull_requests_parse_without_newer_fields() {
+        let prs: Vec<GtPullRequest> = serde_json::from_str(
+            r#"[{"number": 7, "html_url": "https://codeberg.org/me/api/pulls/7",
+                 "head": {"ref": "fix-login", "sha": "abc"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(prs[0].head.branch, "fix-login");
+        assert!(!prs[0].draft);
+        assert!(prs[0].requested_reviewers.is_empty());
+    }
+
+    #[test]
+    fn statuses_give_ci_state_and_run() {
+        let status: GtCombinedStatus = serde_json::from_str(
+            r#"{"state": "failure", "total_count": 2, "statuses": [
+                {"status": "success", "target_url": "https://ci.example.com/1"},
+                {"status": "failure", "target_url": "https://ci.example.com/2"}
+            ]}"#,
+        )
+        .unwrap();
+        let ci = ci_from(status).unwrap();
+        assert_eq!(ci.checks, Checks::Failing);
+        assert_eq!(ci.url, "https://ci.example.com/2");
+        let none: GtCombinedStatus = serde_json::from_str(r#"{"state": ""}"#).unwrap();
+        assert_eq!(ci_from(none), None);
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::forge::{self, Checks, CiRun, Forge, Issue, PullRequest, ReviewDecision};
+
+/// GitHub, through the `gh` client.
+pub struct GitHub {
+    /// Token from the `forges` setting; otherwise `gh` signs in as it
+    /// usually does, including from `GH_TOKEN` or `GITHUB_TOKEN`.
+    token: Option<String>,
+}
+
+impl GitHub {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn gh(&self, main_repo: &Path, args: &[&str]) -> Result<String> {
+        let token = self.token.as_deref().map(|t| ("GH_TOKEN", t));
+        forge::run_cli("gh", main_repo, args, token)
+    }
+}
+
+impl Forge for GitHub {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn open_prs(&self, main_repo: &Path) -> Result<HashMap<String, PullRequest>> {
+        let json = self.gh(
+            main_repo,
+            &[
+                "pr",
+                "list",
+                "--state",
+                "open",
+                "--limit",
+                "200",
+                "--json",
+                "number,url,headRefName,isDraft,reviewDecision,statusCheckRollup",
+            ],
+        )?;
+        parse_pr_list(&json)
+    }
+
+    fn create_pr(&self, main_repo: &Path, branch: &str, title: &str, body: &str) -> Result<String> {
+        let output = self.gh(
+            main_repo,
+            &[
+                "pr", "create", "--head", branch, "--title", title, "--body", body,
+            ],
+        )?;
+        forge::last_url(&output)
+            .map(str::to_string)
+            .context("gh pr create printed no URL")
+    }
+
+    fn commit_ci(&self, main_repo: &Path, sha: &str) -> Result<Option<CiRun>> {
+        // `gh` fills in `{owner}/{repo}` from the repo's remote.
+        let endpoint = format!(
+            "repos/{{owner}}/{{repo}}/commits/{}/check-runs?per_page=100",
+            sha
+        );
+        let json = self.gh(main_repo, &["api", &endpoint])?;
+        parse_check_runs(&json)
+    }
+
+    fn issue(&self, main_repo: &Path, number: u64) -> Result<Issue> {
+        let number = number.to_string();
+        let json = self.gh(
+            main_repo,
+            &["issue", "view", &number, "--json", "number,title,url"],
+        )?;
+        serde_json::from_str(&json).context("could not parse gh issue view output")
+    }
+}
+
+/// One pull request in `gh pr list --json` output.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhPullRequest {
+    number: u64,
+    #[serde(default)]
+    url: String,
+    head_ref_name: String,
+    #[serde(default)]
+    is_draft: bool,
+    #[serde(default)]
+    review_decision: String,
+    #[serde(default)]
+    status_check_rollup: Vec<GhCheck>,
+}
+
+/// A check run (`status` and `conclusion`) or commit status (`state`) in a
+/// pull request's `statusCheckRollup`.
+#[derive(Deserialize)]
+struct GhCheck {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    conclusion: String,
+    #[serde(default)]
+    state: String,
+}
+
+/// Parse `gh pr list --json` output into pull requests by head branch.
+fn parse_pr_list(json: &str) -> Result<HashMap<String, PullRequest>> {
+    let prs: Vec<GhPullRequest> =
+        serde_json::from_str(json).context("could not parse gh pr list output")?;
+    Ok(prs
+        .into_iter()
+        .map(|pr| {
+            let review = match pr.review_decision.as_str() {
+                "APPROVED" => Some(ReviewDecision::Approved),
+                "CHANGES_REQUESTED" => Some(ReviewDecision::ChangesRequested),
+                "REVIEW_REQUIRED" => Some(ReviewDecision::ReviewRequired),
+                _ => None,
+            };
+            let pull_request = PullRequest {
+                number: pr.number,
+                url: pr.url,
+                draft: pr.is_draft,
+                review,
+                checks: rollup(&pr.status_check_rollup),
+            };
+            (pr.head_ref_name, pull_request)
+        })
+        .collect())
+}
+
+/// The check runs on a commit, from the checks API.
+#[derive(Deserialize)]
+struct GhCheckRuns {
+    check_runs: Vec<GhCheckRun>,
+}
+
+#[derive(Deserialize)]
+struct GhCheckRun {
+    status: String,
+    #[serde(default)]
+    conclusion: Option<String>,
+    #[serde(default)]
+    html_url: String,
+}
+
+/// Parse the checks API's answer for a commit into its CI state.
+fn parse_check_runs(json: &str) -> Result<Option<CiRun>> {
+    let runs: GhCheckRuns = serde_json::from_str(json).context("could not parse check runs")?;
+    Ok(CiRun::combine(runs.check_runs.into_iter().map(|run| {
+        let checks = match (run.status.as_str(), run.conclusion.as_deref()) {
+            ("completed", Some("success")) => Some(Checks::Passing),
+            (
+                "completed",
+                Some("failure" | "timed_out" | "action_required" | "startup_failure"),
+            ) => Some(Checks::Failing),
+            // Neutral, skipped, cancelled, or stale.
+            ("completed", _) => None,
+            _ => Some(Checks::Pending),
+        };
+        (checks, run.html_url)
+    })))
+}
+
+/// Combine `checks` into one state: failing if any failed, else pending if
+/// any are still running, else passing.
+fn rollup(checks: &[GhCheck]) -> Option<Checks> {
+    if checks.is_empty() {
+        return None;
+    }
+    let failed = |c: &GhCheck| {
+        matches!(
+            c.conclusion.as_str(),
+            "FAILURE" | "CANCELLED" | "TIMED_OUT" | "ACTION_REQUIRED" | "STARTUP_FAILURE"
+        ) || matches!(c.state.as_str(), "FAILURE" | "ERROR")
+    };
+    let pending = |c: &GhCheck| {
+        (!c.status.is_empty() && c.status != "COMPLETED")
+            || matches!(c.state.as_str(), "PENDING" | "EXPECTED")
+    };
+    if checks.iter().any(failed) {
+        Some(Checks::Failing)
+    } else if checks.iter().any(pending) {
+        Some(Checks::Pending)
+    } else {
+        Some(Checks::Passing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PR_LIST: &str = r#"[
+        {"number": 12, "url": "https://github.com/me/api/pull/12",
+         "headRefName": "fix-login", "isDraft": false,
+         "reviewDecision": "APPROVED",
+         "statusCheckRollup": [
+            {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+            {"__typename": "StatusContext", "state": "SUCCESS"}
+         ]},
+        {"number": 15, "headRefName": "hazy-quail", "isDraft": true,
+         "reviewDecision": "",
+         "statusCheckRollup": [
+            {"__typename": "CheckRun", "status": "COMPLETED", "conclusion": "SUCCESS"},
+            {"__typename": "CheckRun", "status": "IN_PROGRESS", "conclusion": ""}
+         ]},
+        {"number": 17, "headRefName": "bold-fox", "isDraft": false,
+         "reviewDecision": "CHANGES_REQUESTED",
+         "statusCheckRollup": [
+            {"__typename": "CheckRun", "status": "IN_PROGRESS", "conclusion": ""},
+            {"__typename": "StatusContext", "state": "ERROR"}
+         ]},
+        {"number": 20, "headRefName": "docs", "isDraft": false,
+         "reviewDecision": "REVIEW_REQUIRED", "statusCheckRollup": []}
+    ]"#;
+
+    #[test]
+    fn parses_review_and_checks() {
+        let prs = parse_pr_list(PR_LIST).unwrap();
+        assert_eq!(
+            prs["fix-login"],
+            PullRequest {
+                number: 12,
+                url: "https://github.com/me/api/pull/12".to_string(),
+                draft: false,
+                review: Some(ReviewDecision::Approved),
+                checks: Some(Checks::Passing),
+            }
+        );
+        assert_eq!(prs["hazy-quail"].checks, Some(Checks::Pending));
+        assert_eq!(prs["hazy-quail"].review, None);
+        assert!(prs["hazy-quail"].draft);
+        assert_eq!(prs["bold-fox"].checks, Some(Checks::Failing));
+        assert_eq!(
+            prs["bold-fox"].review,
+            Some(ReviewDecision::ChangesRequested)
+        );
+        assert_eq!(prs["docs"].checks, None);
+        assert!(parse_pr_list("not json").is_err());
+    }
+
+    #[test]
+    fn parses_check_runs() {
+        let json = r#"{"total_count": 3, "check_runs": [
+            {"status": "completed", "conclusion": "success", "html_url": "https://github.com/me/api/runs/1"},
+            {"status": "in_progress", "conclusion": null, "html_url": "https://github.com/me/api/runs/2"},
+            {"status": "completed", "conclusion": "skipped", "html_url": "https://github.com/me/api/runs/3"}
+        ]}"#;
+        let ci = parse_check_runs(json).unwrap().unwrap();
+        assert_eq!(ci.checks, Checks::Pending);
+        assert_eq!(ci.url, "https://github.com/me/api/runs/2");
+        let json = r#"{"check_runs": [
+            {"status": "completed", "conclusion": "success", "html_url": "a"},
+            {"status": "completed", "conclusion": "failure", "html_url": "b"}
+        ]}"#;
+        let ci = parse_check_runs(json).unwrap().unwrap();
+        assert_eq!((ci.checks, ci.url.as_str()), (Checks::Failing, "b"));
+        assert_eq!(parse_check_runs(r#"{"check_runs": []}"#).unwrap(), None);
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::forge::{self, Checks, CiRun, Forge, Issue, PullRequest, ReviewDecision};
+
+/// GitLab, through the `glab` client.
+pub struct GitLab {
+    /// Token from the `forges` setting; otherwise `glab` signs in as it
+    /// usually does, including from `GITLAB_TOKEN`.
+    token: Option<String>,
+}
+
+impl GitLab {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn glab(&self, main_repo: &Path, args: &[&str]) -> Result<String> {
+        let token = self.token.as_deref().map(|t| ("GITLAB_TOKEN", t));
+        forge::run_cli("glab", main_repo, args, token)
+    }
+}
+
+impl Forge for GitLab {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn open_prs(&self, main_repo: &Path) -> Result<HashMap<String, PullRequest>> {
+        // `glab` fills in `:id` from the repo's remote.
+        let json = self.glab(
+            main_repo,
+            &[
+                "api",
+                "projects/:id/merge_requests?state=opened&per_page=100",
+            ],
+        )?;
+        parse_mr_list(&json)
+    }
+
+    fn create_pr(&self, main_repo: &Path, branch: &str, title: &str, body: &str) -> Result<String> {
+        let output = self.glab(
+            main_repo,
+            &[
+                "mr",
+                "create",
+                "--source-branch",
+                branch,
+                "--title",
+                title,
+                "--description",
+                body,
+                "--yes",
+            ],
+        )?;
+        forge::last_url(&output)
+            .map(str::to_string)
+            .context("glab mr create printed no URL")
+    }
+
+    fn commit_ci(&self, main_repo: &Path, sha: &str) -> Result<Option<CiRun>> {
+        let endpoint = format!("projects/:id/pipelines?sha={}&per_page=1", sha);
+        let json = self.glab(main_repo, &["api", &endpoint])?;
+        parse_pipelines(&json)
+    }
+
+    fn issue(&self, main_repo: &Path, number: u64) -> Result<Issue> {
+        let endpoint = format!("projects/:id/issues/{}", number);
+        let json = self.glab(main_repo, &["api", &endpoint])?;
+        let issue: GlIssue = serde_json::from_str(&json).context("could not parse GitLab issue")?;
+        Ok(Issue {
+            number: issue.iid,
+            title: issue.title,
+            url: issue.web_url,
+        })
+    }
+}
+
+/// One merge request from GitLab's merge requests API.
+#[derive(Deserialize)]
+struct GlMergeRequest {
+    iid: u64,
+    #[serde(default)]
+    web_url: String,
+    source_branch: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    detailed_merge_status: String,
+}
+
+/// Parse a merge request list into pull requests by source branch. The list
+/// only says what, if anything, stops each one merging, so review and CI
+/// state are read from that: a merge request held up by something else
+/// shows neither.
+fn parse_mr_list(json: &str) -> Result<HashMap<String, PullRequest>> {
+    let mrs: Vec<GlMergeRequest> =
+        serde_json::from_str(json).context("could not parse GitLab merge requests")?;
+    Ok(mrs
+        .into_iter()
+        .map(|mr| {
+            let (review, checks) = match mr.detailed_merge_status.as_str() {
+                "not_approved" => (Some(ReviewDecision::ReviewRequired), None),
+                "requested_changes" => (Some(ReviewDecision::ChangesRequested), None),
+                "ci_still_running" => (None, Some(Checks::Pending)),
+                "ci_must_pass" => (None, Some(Checks::Failing)),
+                _ => (None, None),
+            };
+            let pull_request = PullRequest {
+                number: mr.iid,
+                url: mr.web_url,
+                draft: mr.draft,
+                review,
+                checks,
+            };
+            (mr.source_branch, pull_request)
+        })
+        .collect())
+}
+
+/// An issue from GitLab's issues API.
+#[derive(Deserialize)]
+struct GlIssue {
+    iid: u64,
+    title: String,
+    #[serde(default)]
+    web_url: String,
+}
+
+/// One pipeline from GitLab's pipelines API.
+#[derive(Deserialize)]
+struct GlPipeline {
+    status: String,
+    #[serde(default)]
+    web_url: String,
+}
+
+/// Parse a pipeline list, newest first, into the newest one's CI state.
+fn parse_pipelines(json: &str) -> Result<Option<CiRun>> {
+    let pipelines: Vec<GlPipeline> =
+        serde_json::from_str(json).context("could not parse GitLab pipelines")?;
+    Ok(CiRun::combine(pipelines.into_iter().take(1).map(|p| {
+        let checks = match p.status.as_str() {
+            "success" => Some(Checks::Passing),
+            "failed" => Some(Checks::Failing),
+            "created"
+            | "waiting_for_resource"
+            | "preparing"
+            | "pending"
+            | "running"
+            | "scheduled" => Some(Checks::Pending),
+            // Canceled, skipped, or waiting on a manual job.
+            _ => None,
+        };
+        (checks, p.web_url)
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_status_maps_to_review_and_checks() {
+        let json = r#"[
+            {"iid": 3, "web_url": "https://gitlab.com/me/api/-/merge_requests/3",
+             "source_branch": "fix-login", "draft": false,
+             "detailed_merge_status": "not_approved"},
+            {"iid": 4, "source_branch": "hazy-quail", "draft": true,
+             "detailed_merge_status": "ci_still_running"},
+            {"iid": 5, "source_branch": "bold-fox",
+             "detailed_merge_status": "mergeable"}
+        ]"#;
+        let prs = parse_mr_list(json).unwrap();
+        assert_eq!(
+            prs["fix-login"],
+            PullRequest {
+                number: 3,
+                url: "https://gitlab.com/me/api/-/merge_requests/3".to_string(),
+                draft: false,
+                review: Some(ReviewDecision::ReviewRequired),
+                checks: None,
+            }
+        );
+        assert_eq!(prs["hazy-quail"].checks, Some(Checks::Pending));
+        assert!(prs["hazy-quail"].draft);
+        assert_eq!(prs["bold-fox"].to_string(), "#5");
+        assert!(parse_mr_list("{}").is_err());
+    }
+
+    #[test]
+    fn newest_pipeline_gives_ci_state() {
+        let json = r#"[
+            {"id": 9, "status": "failed", "web_url": "https://gitlab.com/me/api/-/pipelines/9"},
+            {"id": 8, "status": "success", "web_url": "https://gitlab.com/me/api/-/pipelines/8"}
+        ]"#;
+        let ci = parse_pipelines(json).unwrap().unwrap();
+        assert_eq!(ci.checks, Checks::Failing);
+        assert_eq!(ci.url, "https://gitlab.com/me/api/-/pipelines/9");
+        assert_eq!(parse_pipelines(r#"[{"status": "manual"}]"#).unwrap(), None);
+        assert_eq!(parse_pipelines("[]").unwrap(), None);
+    }
+}
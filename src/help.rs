@@ -0,0 +1,174 @@
+use anyhow::{Result, bail};
+use clap::CommandFactory;
+use owo_colors::OwoColorize;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::cli::Cli;
+use crate::exit::Failure;
+
+/// A long-form guide shown by `dwm help <topic>`.
+pub struct Topic {
+    pub name: &'static str,
+    pub summary: &'static str,
+    /// Plain text; lines starting with `# ` are headings.
+    pub text: &'static str,
+}
+
+pub const TOPICS: &[Topic] = &[
+    Topic {
+        name: "workflows",
+        summary: "Creating, moving between, and cleaning up workspaces",
+        text: include_str!("topics/workflows.txt"),
+    },
+    Topic {
+        name: "shell",
+        summary: "The shell wrapper, completions, and prompt badge",
+        text: include_str!("topics/shell.txt"),
+    },
+    Topic {
+        name: "agents",
+        summary: "Agent status tracking and the hook lifecycle",
+        text: include_str!("topics/agents.txt"),
+    },
+    Topic {
+        name: "vcs",
+        summary: "How jj, git, and Mercurial workspaces differ",
+        text: include_str!("topics/vcs.txt"),
+    },
+    Topic {
+        name: "scripting",
+        summary: "Exit codes and the stable --porcelain format",
+        text: include_str!("topics/scripting.txt"),
+    },
+];
+
+/// Show `dwm help [args]`: the guide for a topic, the help of a command
+/// (`dwm help repos list`), or, with no arguments, the overall help followed
+/// by the list of topics. Topics win over commands of the same name, so
+/// `dwm help agents` is the guide; `dwm agents --help` still has the flags.
+pub fn show(args: &[String]) -> Result<()> {
+    let color = std::io::stdout().is_terminal();
+    let styled = |help: clap::builder::StyledStr| {
+        if color {
+            help.ansi().to_string()
+        } else {
+            help.to_string()
+        }
+    };
+    let mut cmd = Cli::command();
+    cmd.build();
+    let text = if let [name] = args
+        && let Some(topic) = find_topic(name)
+    {
+        render_topic(topic.text, color)
+    } else if args.is_empty() {
+        format!("{}\n{}", styled(cmd.render_long_help()), topic_list())
+    } else {
+        let mut sub = &mut cmd;
+        for name in args {
+            match sub.find_subcommand_mut(name) {
+                Some(next) if !next.is_hide_set() => sub = next,
+                _ => bail!(Failure::not_found(format!(
+                    "no help topic or command '{}'\n\n{}",
+                    args.join(" "),
+                    topic_list()
+                ))),
+            }
+        }
+        styled(sub.render_long_help())
+    };
+    page(&text)
+}
+
+fn find_topic(name: &str) -> Option<&'static Topic> {
+    TOPICS.iter().find(|t| t.name == name)
+}
+
+/// The "Help topics:" section listing each topic with its summary.
+fn topic_list() -> String {
+    let width = TOPICS.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    let mut out = String::from("Help topics (dwm help <topic>):\n");
+    for topic in TOPICS {
+        out.push_str(&format!(
+            "  {:width$}  {}\n",
+            topic.name,
+            topic.summary,
+            width = width
+        ));
+    }
+    out
+}
+
+/// Render a topic's text, with bold headings when `color`.
+fn render_topic(text: &str, color: bool) -> String {
+    text.lines()
+        .map(|line| match line.strip_prefix("# ") {
+            Some(heading) if color => format!("{}\n", heading.bold()),
+            Some(heading) => format!("{}\n", heading.to_uppercase()),
+            None => format!("{}\n", line),
+        })
+        .collect()
+}
+
+/// Print `text` through `$PAGER` (default `less`) when stdout is a terminal,
+/// or straight to stdout otherwise or if the pager can't be started.
+fn page(text: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(&pager)
+            .env(
+                "LESS",
+                std::env::var("LESS").unwrap_or_else(|_| "FRX".to_string()),
+            )
+            .stdin(Stdio::piped())
+            .spawn();
+        if let Ok(mut child) = child {
+            if let Some(mut stdin) = child.stdin.take() {
+                // The pager closing early (say `q` on a long page) isn't an error.
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait()?;
+            return Ok(());
+        }
+    }
+    stdout.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topics_have_headings_and_unique_names() {
+        for topic in TOPICS {
+            assert!(topic.text.starts_with("# "), "{}", topic.name);
+            assert_eq!(TOPICS.iter().filter(|t| t.name == topic.name).count(), 1);
+        }
+        assert!(find_topic("agents").unwrap().text.contains("SessionEnd"));
+        assert!(find_topic("nope").is_none());
+    }
+
+    #[test]
+    fn render_topic_marks_headings() {
+        let text = "# Setting it up\n\nRun dwm setup.\n";
+        assert_eq!(
+            render_topic(text, false),
+            "SETTING IT UP\n\nRun dwm setup.\n"
+        );
+        let colored = render_topic(text, true);
+        assert!(colored.contains("\u{1b}[1mSetting it up"), "{:?}", colored);
+    }
+
+    #[test]
+    fn show_rejects_unknown_topics() {
+        let err = show(&["nope".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("no help topic or command 'nope'"));
+        assert!(err.to_string().contains("agents"));
+        assert!(show(&["repos".to_string(), "nope".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,502 @@
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A picker command that can be bound to one or more keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Down,
+    Up,
+    /// Jump to the first row; letter keys must be pressed twice, as in vim's `gg`.
+    Top,
+    /// Jump to the last workspace.
+    Bottom,
+    HalfPageDown,
+    HalfPageUp,
+    Select,
+    Quit,
+    Filter,
+    Sort,
+    Preview,
+    /// Give the preview pane more room.
+    PreviewGrow,
+    /// Give the preview pane less room.
+    PreviewShrink,
+    /// Switch the preview between beside and below the table.
+    PreviewLayout,
+    Delete,
+    Rename,
+    Open,
+    /// Collapse or expand a repo group in the `--all` picker.
+    Collapse,
+    /// Copy the selected workspace's path to the clipboard.
+    Copy,
+    /// Push the selected workspace's branch or bookmark to the remote.
+    Push,
+    /// Rebase the selected workspace onto the latest trunk.
+    Sync,
+    /// Push the selected workspace and open its pull request.
+    Pr,
+    /// Open the selected workspace's CI run in the browser, the failing one
+    /// if any failed.
+    Ci,
+    /// Open the action menu for the selected workspace.
+    Menu,
+    /// Show the agent sessions in the selected workspace.
+    Agents,
+    /// Start the default agent in the selected workspace.
+    Launch,
+    /// Open the selected workspace in its own tmux window.
+    Tmux,
+    /// Move to the next workspace with an agent waiting for input.
+    NextWaiting,
+    /// Enter cleanup mode: show stale workspaces, marked for bulk deletion.
+    Cleanup,
+    Help,
+}
+
+impl Action {
+    /// Every action, in the order they are listed in help output.
+    pub const ALL: &'static [Action] = &[
+        Action::Down,
+        Action::Up,
+        Action::Top,
+        Action::Bottom,
+        Action::HalfPageDown,
+        Action::HalfPageUp,
+        Action::Select,
+        Action::Filter,
+        Action::Sort,
+        Action::Preview,
+        Action::PreviewGrow,
+        Action::PreviewShrink,
+        Action::PreviewLayout,
+        Action::Delete,
+        Action::Rename,
+        Action::Open,
+        Action::Collapse,
+        Action::Copy,
+        Action::Push,
+        Action::Sync,
+        Action::Pr,
+        Action::Ci,
+        Action::Menu,
+        Action::Agents,
+        Action::Launch,
+        Action::Tmux,
+        Action::NextWaiting,
+        Action::Cleanup,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Name used for this action in the `keys` section of the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::HalfPageDown => "half_page_down",
+            Action::HalfPageUp => "half_page_up",
+            Action::Select => "select",
+            Action::Quit => "quit",
+            Action::Filter => "filter",
+            Action::Sort => "sort",
+            Action::Preview => "preview",
+            Action::PreviewGrow => "preview_grow",
+            Action::PreviewShrink => "preview_shrink",
+            Action::PreviewLayout => "preview_layout",
+            Action::Delete => "delete",
+            Action::Rename => "rename",
+            Action::Open => "open",
+            Action::Collapse => "collapse",
+            Action::Copy => "copy",
+            Action::Push => "push",
+            Action::Sync => "sync",
+            Action::Pr => "pr",
+            Action::Ci => "ci",
+            Action::Menu => "menu",
+            Action::Agents => "agents",
+            Action::Launch => "launch",
+            Action::Tmux => "tmux",
+            Action::NextWaiting => "next_waiting",
+            Action::Cleanup => "cleanup",
+            Action::Help => "help",
+        }
+    }
+
+    /// Human-readable description shown in the help popup.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Down => "move down",
+            Action::Up => "move up",
+            Action::Top => "jump to top (press twice)",
+            Action::Bottom => "jump to last workspace",
+            Action::HalfPageDown => "half a page down",
+            Action::HalfPageUp => "half a page up",
+            Action::Select => "switch to the selected workspace",
+            Action::Quit => "quit",
+            Action::Filter => "filter workspaces",
+            Action::Sort => "cycle sort mode",
+            Action::Preview => "toggle preview pane",
+            Action::PreviewGrow => "grow preview pane",
+            Action::PreviewShrink => "shrink preview pane",
+            Action::PreviewLayout => "preview beside/below table",
+            Action::Delete => "delete workspace",
+            Action::Rename => "rename workspace",
+            Action::Open => "open in editor",
+            Action::Collapse => "collapse/expand repo group",
+            Action::Copy => "copy workspace path",
+            Action::Push => "push to remote",
+            Action::Sync => "rebase onto trunk",
+            Action::Pr => "open pull request",
+            Action::Ci => "open CI run in browser",
+            Action::Menu => "actions for the selected workspace",
+            Action::Agents => "show agent sessions",
+            Action::Launch => "start an agent in workspace",
+            Action::Tmux => "open in a tmux window",
+            Action::NextWaiting => "jump to next waiting agent",
+            Action::Cleanup => "clean up stale workspaces",
+            Action::Help => "show this help",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Down => &["j", "down"],
+            Action::Up => &["k", "up"],
+            Action::Top => &["g", "home"],
+            Action::Bottom => &["G", "end"],
+            Action::HalfPageDown => &["ctrl-d"],
+            Action::HalfPageUp => &["ctrl-u"],
+            Action::Select => &["enter"],
+            Action::Quit => &["q", "esc"],
+            Action::Filter => &["/"],
+            Action::Sort => &["s"],
+            Action::Preview => &["p"],
+            Action::PreviewGrow => &["+", "="],
+            Action::PreviewShrink => &["-"],
+            Action::PreviewLayout => &["L"],
+            Action::Delete => &["d"],
+            Action::Rename => &["r"],
+            Action::Open => &["o"],
+            Action::Collapse => &["tab"],
+            Action::Copy => &["y"],
+            Action::Push => &["P"],
+            Action::Sync => &["S"],
+            Action::Pr => &["R"],
+            Action::Ci => &["C"],
+            Action::Menu => &["a"],
+            Action::Agents => &["A"],
+            Action::Launch => &["l"],
+            Action::Tmux => &["t"],
+            Action::NextWaiting => &["w"],
+            Action::Cleanup => &["c"],
+            Action::Help => &["?"],
+        }
+    }
+}
+
+/// A single key, optionally combined with ctrl or alt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parse a key description such as `"j"`, `"enter"`, `"ctrl-d"` or `"alt-x"`.
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            if let Some(r) = rest.strip_prefix("ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = r;
+            } else if let Some(r) = rest.strip_prefix("alt-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = r;
+            } else {
+                break;
+            }
+        }
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_lowercase().as_str() {
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "delete" => KeyCode::Delete,
+                _ => bail!("unknown key '{}'", s),
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    /// Return `true` if `event` is this key. Shift is ignored so that bindings
+    /// like `"G"` match however the terminal reports the uppercase letter.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        let relevant = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        event.code == self.code && (event.modifiers & relevant) == self.modifiers
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::Delete => write!(f, "Del"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// One key or a list of keys, as written in the config file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn keys(&self) -> Vec<&str> {
+        match self {
+            KeySpec::One(k) => vec![k.as_str()],
+            KeySpec::Many(ks) => ks.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// Effective key bindings for the pickers: the built-in defaults with any
+/// overrides from the config file applied.
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|&action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|k| KeyBinding::parse(k).expect("default key bindings are valid"))
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// Build a keymap from the config file's `keys` section. Each configured
+    /// action replaces that action's default keys entirely.
+    pub fn from_config(overrides: &HashMap<String, KeySpec>) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (name, spec) in overrides {
+            let Some(action) = Action::from_name(name) else {
+                bail!("unknown action '{}' in keys config", name);
+            };
+            let keys = spec
+                .keys()
+                .into_iter()
+                .map(KeyBinding::parse)
+                .collect::<Result<Vec<_>>>()?;
+            keymap.bindings.insert(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    /// Return the action bound to `event`, if any.
+    pub fn action_for(&self, event: &KeyEvent) -> Option<Action> {
+        Action::ALL.iter().copied().find(|action| {
+            self.bindings
+                .get(action)
+                .is_some_and(|keys| keys.iter().any(|k| k.matches(event)))
+        })
+    }
+
+    /// All keys bound to `action`.
+    pub fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Short label for `action` used in the help bar: its first bound key.
+    pub fn label(&self, action: Action) -> String {
+        self.keys(action)
+            .first()
+            .map(|k| k.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn parse_single_char() {
+        let k = KeyBinding::parse("j").unwrap();
+        assert_eq!(k.code, KeyCode::Char('j'));
+        assert_eq!(k.modifiers, KeyModifiers::NONE);
+    }
+
+    #[test]
+    fn parse_named_keys() {
+        assert_eq!(KeyBinding::parse("enter").unwrap().code, KeyCode::Enter);
+        assert_eq!(KeyBinding::parse("Esc").unwrap().code, KeyCode::Esc);
+        assert_eq!(KeyBinding::parse("space").unwrap().code, KeyCode::Char(' '));
+        assert_eq!(
+            KeyBinding::parse("pagedown").unwrap().code,
+            KeyCode::PageDown
+        );
+    }
+
+    #[test]
+    fn parse_modifiers() {
+        let k = KeyBinding::parse("ctrl-d").unwrap();
+        assert_eq!(k.code, KeyCode::Char('d'));
+        assert_eq!(k.modifiers, KeyModifiers::CONTROL);
+        let k = KeyBinding::parse("ctrl-alt-x").unwrap();
+        assert_eq!(k.modifiers, KeyModifiers::CONTROL | KeyModifiers::ALT);
+    }
+
+    #[test]
+    fn parse_unknown_key_errors() {
+        assert!(KeyBinding::parse("hyper").is_err());
+        assert!(KeyBinding::parse("").is_err());
+    }
+
+    #[test]
+    fn display_roundtrips_through_parse() {
+        for s in ["j", "ctrl-d", "Enter", "Esc", "space"] {
+            let k = KeyBinding::parse(s).unwrap();
+            assert_eq!(KeyBinding::parse(&k.to_string()).unwrap(), k);
+        }
+    }
+
+    #[test]
+    fn matches_ignores_shift() {
+        let k = KeyBinding::parse("G").unwrap();
+        assert!(k.matches(&press(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        assert!(k.matches(&press(KeyCode::Char('G'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn matches_requires_ctrl() {
+        let k = KeyBinding::parse("ctrl-d").unwrap();
+        assert!(k.matches(&press(KeyCode::Char('d'), KeyModifiers::CONTROL)));
+        assert!(!k.matches(&press(KeyCode::Char('d'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn default_keymap_has_current_bindings() {
+        let km = KeyMap::default();
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            km.action_for(&press(KeyCode::Char('j'), none)),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            km.action_for(&press(KeyCode::Down, none)),
+            Some(Action::Down)
+        );
+        assert_eq!(
+            km.action_for(&press(KeyCode::Char('k'), none)),
+            Some(Action::Up)
+        );
+        assert_eq!(
+            km.action_for(&press(KeyCode::Esc, none)),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            km.action_for(&press(KeyCode::Char('d'), none)),
+            Some(Action::Delete)
+        );
+        assert_eq!(km.action_for(&press(KeyCode::Char('z'), none)), None);
+    }
+
+    #[test]
+    fn override_replaces_default_keys() {
+        let mut overrides = HashMap::new();
+        overrides.insert("delete".to_string(), KeySpec::One("x".to_string()));
+        overrides.insert(
+            "down".to_string(),
+            KeySpec::Many(vec!["n".to_string(), "ctrl-n".to_string()]),
+        );
+        let km = KeyMap::from_config(&overrides).unwrap();
+        let none = KeyModifiers::NONE;
+        assert_eq!(
+            km.action_for(&press(KeyCode::Char('x'), none)),
+            Some(Action::Delete)
+        );
+        assert_eq!(km.action_for(&press(KeyCode::Char('d'), none)), None);
+        assert_eq!(km.action_for(&press(KeyCode::Char('j'), none)), None);
+        assert_eq!(
+            km.action_for(&press(KeyCode::Char('n'), KeyModifiers::CONTROL)),
+            Some(Action::Down)
+        );
+        assert_eq!(km.label(Action::Delete), "x");
+    }
+
+    #[test]
+    fn override_unknown_action_errors() {
+        let mut overrides = HashMap::new();
+        overrides.insert("explode".to_string(), KeySpec::One("x".to_string()));
+        assert!(KeyMap::from_config(&overrides).is_err());
+    }
+
+    #[test]
+    fn keys_config_parses_from_json() {
+        let overrides: HashMap<String, KeySpec> =
+            serde_json::from_str(r#"{"quit": "x", "up": ["i", "up"]}"#).unwrap();
+        let km = KeyMap::from_config(&overrides).unwrap();
+        assert_eq!(km.keys(Action::Up).len(), 2);
+        assert_eq!(km.label(Action::Quit), "x");
+    }
+}
@@ -0,0 +1,99 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use owo_colors::OwoColorize;
+
+/// Writes `log` records to stderr: progress (`info`) with its leading `✓`
+/// or verb colored, warnings and errors with a colored prefix, and debug
+/// detail dimmed.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // A full-screen view owns stderr; a stray line would corrupt it.
+        if crossterm::terminal::is_raw_mode_enabled().unwrap_or(false) {
+            return;
+        }
+        let _spinner = crate::progress::suspend();
+        match record.level() {
+            Level::Error => eprintln!("{} {}", "error:".red(), record.args()),
+            Level::Warn => eprintln!("{} {}", "warning:".yellow(), record.args()),
+            Level::Info => eprintln!("{}", styled(&record.args().to_string())),
+            Level::Debug | Level::Trace => {
+                eprintln!("{}", format!("debug: {}", record.args()).dimmed())
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// `line` with its leading mark colored: a `✓` green, and the verb of an
+/// in-progress line such as `creating workspace 'x'...` cyan. The library
+/// logs plain text, so the color is added here.
+fn styled(line: &str) -> String {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let rest = line.trim_start();
+    if let Some(after) = rest.strip_prefix('✓') {
+        return format!("{}{}{}", indent, "✓".green(), after);
+    }
+    match rest.split_once(' ') {
+        Some((verb, after)) if verb.ends_with("ing") && rest.ends_with("...") => {
+            format!("{}{} {}", indent, verb.cyan(), after)
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// The most detailed level to print: warnings and errors only with
+/// `--quiet`, progress by default, and debug detail (such as each VCS
+/// command run) with `-v`, or everything with `-vv`.
+pub fn level_filter(quiet: bool, verbose: u8) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::Warn,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+/// Install the stderr logger. Until this runs (as in tests), log macros
+/// print nothing.
+pub fn init(quiet: bool, verbose: u8) {
+    static LOGGER: StderrLogger = StderrLogger;
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level_filter(quiet, verbose));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_pick_the_level() {
+        assert_eq!(level_filter(false, 0), LevelFilter::Info);
+        assert_eq!(level_filter(true, 0), LevelFilter::Warn);
+        assert_eq!(level_filter(false, 1), LevelFilter::Debug);
+        assert_eq!(level_filter(false, 3), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn progress_lines_get_colored_marks() {
+        owo_colors::set_override(true);
+        assert_eq!(
+            styled("  ✓ workspace 'x' deleted"),
+            format!("  {} workspace 'x' deleted", "✓".green())
+        );
+        assert_eq!(
+            styled("creating workspace 'x'..."),
+            format!("{} workspace 'x'...", "creating".cyan())
+        );
+        assert_eq!(styled("nothing to do"), "nothing to do");
+    }
+}
@@ -1,69 +1,323 @@
 mod agent;
+mod ansi;
+mod browser;
 mod cli;
-mod git;
-#[allow(dead_code)]
-mod jj;
-mod names;
+mod clipboard;
+mod columns;
+mod completions;
+mod config;
+mod daemon;
+mod events;
+mod filter;
+mod forge;
+mod format;
+mod gitea;
+mod github;
+mod gitlab;
+mod help;
+mod keymap;
+mod logging;
+mod man;
+mod notify;
+mod progress;
+mod prompt;
 mod shell;
+mod status;
+mod theme;
+mod tmux;
 mod tui;
-mod vcs;
+mod watch;
+mod width;
 mod workspace;
 
+// The VCS layer and workspace logic live in the `dwm-core` library; bring
+// its modules in at the crate root so `crate::vcs` and friends read as
+// before.
+use dwm_core::{exit, external, fuzzy, git, meta, names, platform, vcs};
+
 use anyhow::Result;
 use clap::Parser;
+use std::path::Path;
 
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ConfigCommand, ReposCommand};
 
-fn main() -> Result<()> {
+fn main() -> std::process::ExitCode {
+    completions::complete_from_env();
+    shell::take_cd_file();
     let cli = Cli::parse();
-
-    match cli.command.unwrap_or(Commands::List { all: false }) {
-        Commands::New { name, at, from } => {
-            workspace::new_workspace(name, at.as_deref(), from.as_deref())
+    logging::init(cli.quiet, cli.verbose);
+    progress::init();
+    match run(cli) {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            exit::exit_code(&err).into()
         }
-        Commands::List { all } => {
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    if let Some(repo) = &cli.repo {
+        workspace::set_repo_override(repo)?;
+    }
+
+    match cli.command.unwrap_or(Commands::List {
+        all: false,
+        fetch: false,
+        plain: false,
+        format: None,
+        porcelain: false,
+    }) {
+        Commands::New {
+            name,
+            at,
+            from,
+            recurse_submodules,
+            sparse,
+            issue,
+            name_scheme,
+        } => workspace::new_workspace(
+            name,
+            at.as_deref(),
+            from.as_deref(),
+            recurse_submodules,
+            sparse.as_deref(),
+            issue,
+            name_scheme,
+        ),
+        Commands::Init { vcs, trunk, naming } => workspace::init_repo(workspace::InitOptions {
+            vcs: vcs.map(|v| v.parse()).transpose()?,
+            trunk,
+            naming,
+        }),
+        Commands::List {
+            all,
+            fetch: force,
+            plain,
+            format,
+            porcelain,
+        } => {
+            // Check the template before doing any slow work.
+            let format = match format {
+                Some(template) => Some(format::ListFormat::parse(&template)?),
+                None => porcelain.then(format::ListFormat::porcelain),
+            };
+            // Best-effort; leftovers shouldn't stop the listing.
+            let _ = agent::gc_agent_status();
+            let config = config::Config::load()?;
+            // `--fetch` always fetches; `auto_fetch` only when it's been a while.
+            let fetch = force || config.auto_fetch;
+            if plain || format.is_some() || !tui::is_interactive() {
+                if fetch {
+                    warn_on_fetch_error(fetch_trunks(all, force));
+                }
+                let entries = if all {
+                    workspace::list_all_workspace_entries()?
+                } else {
+                    workspace::list_workspace_entries()?
+                };
+                workspace::print_plain(&entries, format.as_ref());
+                return Ok(());
+            }
+            let settings = tui::PickerSettings::from_config(&config)?;
+            // The picker's periodic refresh picks up the fetched trunk.
+            if fetch {
+                std::thread::spawn(move || fetch_trunks(all, force));
+            }
             if all {
                 let entries = workspace::list_all_workspace_entries()?;
-                if let Some(tui::PickerResult::Selected(path)) =
-                    tui::run_picker_multi_repo(entries)?
-                {
-                    println!("{}", path);
+                match tui::run_picker_multi_repo(
+                    entries,
+                    settings,
+                    |path, force| {
+                        workspace::delete_workspace_at(
+                            path,
+                            force,
+                            workspace::ProgressOutput::Quiet,
+                        )
+                    },
+                    workspace::open_in_editor,
+                    workspace::list_all_workspace_entries,
+                )? {
+                    Some(tui::PickerResult::Selected(path)) => workspace::go_to(Path::new(&path))?,
+                    Some(tui::PickerResult::CreateNewIn { repo, name }) => {
+                        workspace::new_workspace_in(&repo, name)?;
+                    }
+                    Some(tui::PickerResult::CreateNew(name)) => {
+                        workspace::new_workspace(name, None, None, false, None, None, None)?;
+                    }
+                    Some(tui::PickerResult::Launch(path)) => launch(None, Path::new(&path))?,
+                    Some(tui::PickerResult::Tmux(path)) => tmux::open(Path::new(&path))?,
+                    Some(tui::PickerResult::Pr(path)) => {
+                        workspace::pr_workspace_at(Path::new(&path))?
+                    }
+                    None => {}
                 }
                 return Ok(());
             }
             let repo_dir = workspace::current_repo_dir()?;
-            let entries = workspace::list_workspace_entries()?;
+            let entries = workspace::list_workspace_names()?;
             match tui::run_picker(
                 entries,
                 repo_dir,
-                |name| {
+                settings,
+                |name, force| {
                     workspace::delete_workspace(
                         Some(name.to_string()),
-                        workspace::DeleteOutput::Quiet,
+                        force,
+                        workspace::ProgressOutput::Quiet,
+                    )
+                },
+                |old, new| {
+                    workspace::rename_workspace(
+                        old.to_string(),
+                        Some(new.to_string()),
+                        workspace::ProgressOutput::Quiet,
                     )
                 },
+                workspace::open_in_editor,
                 workspace::list_workspace_entries,
             )? {
-                Some(tui::PickerResult::Selected(path)) => println!("{}", path),
+                Some(tui::PickerResult::Selected(path)) => workspace::go_to(Path::new(&path))?,
                 Some(tui::PickerResult::CreateNew(name)) => {
-                    workspace::new_workspace(name, None, None)?;
+                    workspace::new_workspace(name, None, None, false, None, None, None)?;
+                }
+                Some(tui::PickerResult::CreateNewIn { repo, name }) => {
+                    workspace::new_workspace_in(&repo, name)?;
                 }
+                Some(tui::PickerResult::Launch(path)) => launch(None, Path::new(&path))?,
+                Some(tui::PickerResult::Tmux(path)) => tmux::open(Path::new(&path))?,
+                Some(tui::PickerResult::Pr(path)) => workspace::pr_workspace_at(Path::new(&path))?,
                 None => {}
             }
             Ok(())
         }
-        Commands::Status => {
-            let entries = workspace::list_workspace_entries()?;
-            workspace::print_status(&entries);
+        Commands::Status { fetch, porcelain } => {
+            let _ = agent::gc_agent_status();
+            if fetch || config::Config::load()?.auto_fetch {
+                warn_on_fetch_error(workspace::fetch_trunk(fetch));
+            }
+            // PR and CI state come from the forge, so fetch them while the
+            // VCS is busy.
+            let forge_state = std::thread::spawn(workspace::refresh_forge);
+            let mut entries = workspace::list_workspace_entries()?;
+            if let Ok(Some(forge_state)) = forge_state.join() {
+                forge_state.apply(&mut entries);
+            }
+            if porcelain {
+                workspace::print_plain(&entries, Some(&format::ListFormat::porcelain()));
+            } else {
+                workspace::print_status(&entries);
+            }
+            // Best-effort; the numbers are a shortcut, not the listing.
+            let _ = workspace::record_listing(&entries);
             Ok(())
         }
+        Commands::Agents { json, history } => {
+            if let Some(ws) = history {
+                let repo_dir = workspace::current_repo_dir()?;
+                let sessions = agent::workspace_history(&repo_dir, &ws);
+                if json {
+                    return agent::print_history_json(&sessions);
+                }
+                agent::print_history(&sessions);
+                return Ok(());
+            }
+            let listings = agent::list_all_agent_sessions()?;
+            if json {
+                return agent::print_agents_json(&listings);
+            }
+            if !tui::is_interactive() {
+                agent::print_agents(&listings);
+                return Ok(());
+            }
+            let settings = tui::PickerSettings::from_config(&config::Config::load()?)?;
+            tui::run_agent_dashboard(
+                listings,
+                settings,
+                agent::all_agent_status_dirs(),
+                agent::list_all_agent_sessions,
+            )
+        }
+        Commands::AgentStatus { prompt, color } => agent::print_agent_status(prompt, color),
+        Commands::Prompt {
+            agents,
+            dirty,
+            color,
+        } => prompt::print_prompt(agents, dirty, color),
+        Commands::StarshipConfig => {
+            prompt::print_prompt_snippet(prompt::PromptTheme::Starship);
+            Ok(())
+        }
+        Commands::Gc => {
+            use owo_colors::OwoColorize;
+            for outcome in workspace::merge_split_repo_dirs()? {
+                match outcome {
+                    Ok(root) => log::info!(
+                        "{} merged split entries for {}",
+                        "✓".green(),
+                        root.display()
+                    ),
+                    Err(e) => log::warn!("{:#}", e),
+                }
+            }
+            let report = agent::gc_agent_status()?;
+            log::info!("{} {}", "✓".green(), report);
+            Ok(())
+        }
+        Commands::Daemon => daemon::run(),
+        Commands::Watch { all, json } => events::run(all, json),
+        Commands::Launch { agent, workspace } => {
+            let path = workspace::launch_workspace(workspace)?;
+            launch(Some(agent), &path)
+        }
         Commands::Switch { name } => workspace::switch_workspace(&name),
-        Commands::Rename { name, new_name } => workspace::rename_workspace(name, new_name),
-        Commands::Delete { name } => {
-            workspace::delete_workspace(name, workspace::DeleteOutput::Verbose).map(|_| ())
+        Commands::Tmux { name } => workspace::tmux_workspace(&name),
+        Commands::Recent { nth } => workspace::recent_workspace(nth),
+        Commands::Exec { name, command } => {
+            let code = workspace::exec_in_workspace(&name, &command)?;
+            std::process::exit(code)
         }
-        Commands::HookHandler => agent::handle_hook(),
-        Commands::AgentSetup => agent::setup_agent_hooks(),
+        Commands::Open { name, with } => workspace::open_workspace(name, with.as_deref()),
+        Commands::Code { name, main } => workspace::code_workspace(name, main),
+        Commands::Rename { name, new_name } => {
+            workspace::rename_workspace(name, new_name, workspace::ProgressOutput::Verbose)
+                .map(|_| ())
+        }
+        Commands::Delete {
+            force,
+            all_stale: true,
+            yes,
+            ..
+        } => workspace::delete_workspaces(workspace::BulkDelete::Stale, force, yes).map(|_| ()),
+        Commands::Delete {
+            force,
+            merged: true,
+            yes,
+            ..
+        } => workspace::delete_workspaces(workspace::BulkDelete::Merged, force, yes).map(|_| ()),
+        Commands::Delete { name, force, .. } => {
+            workspace::delete_workspace(name, force, workspace::ProgressOutput::Verbose).map(|_| ())
+        }
+        Commands::Push { name } => workspace::push_workspace(name),
+        Commands::Sync { name } => workspace::sync_workspace(name),
+        Commands::Pr { name, copy } => workspace::pr_workspace(name, copy),
+        Commands::HookHandler { agent, payload } => agent::handle_hook(agent, payload),
+        Commands::AgentSetup {
+            project: true,
+            local,
+            uninstall,
+            ..
+        } => match uninstall {
+            true => agent::uninstall_project_hooks(local),
+            false => agent::setup_project_hooks(local),
+        },
+        Commands::AgentSetup {
+            agent, uninstall, ..
+        } => match uninstall {
+            true => agent::uninstall_agent_hooks(agent),
+            false => agent::setup_agent_hooks(agent),
+        },
         Commands::Setup => {
             use owo_colors::OwoColorize;
             eprintln!("{}", "dwm setup".bold().cyan());
@@ -72,7 +326,7 @@ fn main() -> Result<()> {
             shell::setup_shell_interactive()?;
             eprintln!();
             eprintln!("{}", "Agent status tracking:".bold().yellow());
-            agent::setup_agent_hooks()?;
+            agent::setup_agent_hooks(None)?;
             Ok(())
         }
         Commands::Version => {
@@ -84,13 +338,61 @@ fn main() -> Result<()> {
             );
             Ok(())
         }
+        Commands::Config { command } => {
+            let path = config::config_path()?;
+            match command {
+                ConfigCommand::List => {
+                    for (key, value) in config::list_settings(&path)? {
+                        println!("{} = {}", key, value);
+                    }
+                }
+                ConfigCommand::Get { key } => {
+                    if let Some(value) = config::get_setting(&path, &key)? {
+                        println!("{}", config::display_value(&value));
+                    }
+                }
+                ConfigCommand::Set { key, value } => config::set_setting(&path, &key, &value)?,
+                ConfigCommand::Edit => config::edit_config(&path)?,
+            }
+            Ok(())
+        }
+        Commands::Repos { command } => match command {
+            ReposCommand::List => workspace::list_repos(),
+            ReposCommand::Forget { repo, yes } => workspace::forget_repo(&repo, yes),
+            ReposCommand::Rename { repo, name } => workspace::rename_repo(&repo, &name),
+        },
+        Commands::Help { topic } => help::show(&topic),
+        Commands::Man { out_dir } => man::print_man_pages(out_dir.as_deref()),
+        Commands::Completions { shell } => completions::print_completions(shell),
+        Commands::OnCd { path } => {
+            workspace::on_cd(&path);
+            Ok(())
+        }
         Commands::ShellSetup {
             posix,
             bash,
             zsh,
             fish,
+            nushell,
+            powershell,
+            elvish,
+            xonsh,
+            prompt,
+            uninstall,
         } => {
-            let shell = if fish {
+            if let Some(theme) = prompt {
+                prompt::print_prompt_snippet(theme);
+                return Ok(());
+            }
+            let shell = if xonsh {
+                Some(shell::Shell::Xonsh)
+            } else if elvish {
+                Some(shell::Shell::Elvish)
+            } else if powershell {
+                Some(shell::Shell::Pwsh)
+            } else if nushell {
+                Some(shell::Shell::Nushell)
+            } else if fish {
                 Some(shell::Shell::Fish)
             } else if zsh {
                 Some(shell::Shell::Zsh)
@@ -99,7 +401,37 @@ fn main() -> Result<()> {
             } else {
                 None
             };
-            shell::print_shell_setup(shell)
+            match uninstall {
+                true => shell::uninstall_shell(shell),
+                false => shell::print_shell_setup(shell),
+            }
         }
     }
 }
+
+/// Fetch trunk for the current repo, or every repo if `all`; see
+/// [`workspace::fetch_trunk`].
+fn fetch_trunks(all: bool, force: bool) -> Result<()> {
+    if all {
+        workspace::fetch_all_trunks(force)
+    } else {
+        workspace::fetch_trunk(force)
+    }
+}
+
+/// Warn about a failed fetch; listing carries on with the local trunk.
+fn warn_on_fetch_error(result: Result<()>) {
+    if let Err(e) = result {
+        log::warn!("could not fetch trunk: {}", e);
+    }
+}
+
+/// Start `agent` (the configured default when `None`) in the workspace at
+/// `path`, then print the path for the shell wrapper to `cd` into.
+fn launch(agent: Option<agent::AgentKind>, path: &Path) -> Result<()> {
+    let config = config::Config::load()?;
+    let agent = agent.or(config.default_agent).unwrap_or_default();
+    agent::launch_agent(agent, &config.agent_command(agent), path)?;
+    shell::print_cd(path)?;
+    Ok(())
+}
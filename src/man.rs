@@ -0,0 +1,114 @@
+use anyhow::{Context, Result};
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+use clap_mangen::roff::{Roff, bold, roman};
+use std::fs;
+use std::path::Path;
+
+use crate::cli::Cli;
+use crate::help::TOPICS;
+
+/// Print the `dwm(1)` man page to stdout, or with `out_dir`, write it and a
+/// `dwm-<command>(1)` page for every visible subcommand (nested ones too,
+/// like `dwm-repos-list.1`) into that directory.
+pub fn print_man_pages(out_dir: Option<&Path>) -> Result<()> {
+    let mut cmd = Cli::command();
+    cmd.build();
+    let Some(dir) = out_dir else {
+        print!("{}", render_page(&cmd)?);
+        return Ok(());
+    };
+    fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    let mut pages = Vec::new();
+    collect_pages(&cmd, &mut pages)?;
+    for (name, page) in &pages {
+        let path = dir.join(format!("{}.1", name));
+        fs::write(&path, page).with_context(|| format!("could not write {}", path.display()))?;
+    }
+    log::info!("wrote {} man pages to {}", pages.len(), dir.display());
+    Ok(())
+}
+
+/// Render the page for `cmd` followed by the pages of its visible
+/// subcommands, each with its file name.
+fn collect_pages(cmd: &Command, pages: &mut Vec<(String, String)>) -> Result<()> {
+    pages.push((page_name(cmd).to_string(), render_page(cmd)?));
+    for sub in cmd.get_subcommands().filter(|c| !c.is_hide_set()) {
+        collect_pages(sub, pages)?;
+    }
+    Ok(())
+}
+
+/// The man page name of a built command: `dwm repos list` is `dwm-repos-list`.
+fn page_name(cmd: &Command) -> &str {
+    cmd.get_display_name().unwrap_or_else(|| cmd.get_name())
+}
+
+/// The roff source of the man page for the built command `cmd`. The main
+/// page ends with the built-in guides; the others point back to it.
+fn render_page(cmd: &Command) -> Result<String> {
+    let name = page_name(cmd);
+    let version = Cli::command().get_version().unwrap_or_default().to_string();
+    let mut page = Vec::new();
+    Man::new(cmd.clone())
+        .title(name.to_uppercase())
+        .source(format!("dwm {}", version))
+        .manual("dwm manual")
+        .render(&mut page)?;
+
+    let mut extra = Roff::default();
+    if name == "dwm" {
+        extra.control("SH", ["GUIDES"]);
+        extra.text([
+            roman("Longer guides are built in; run "),
+            bold("dwm help"),
+            roman(" topic to read one."),
+        ]);
+        for topic in TOPICS {
+            extra.control("TP", []);
+            extra.text([bold(topic.name)]);
+            extra.text([roman(topic.summary)]);
+        }
+    } else {
+        extra.control("SH", ["SEE ALSO"]);
+        extra.text([bold("dwm"), roman("(1)")]);
+    }
+    extra.to_writer(&mut page)?;
+    Ok(String::from_utf8(page)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_page_lists_commands_options_and_guides() {
+        let mut cmd = Cli::command();
+        cmd.build();
+        let page = render_page(&cmd).unwrap();
+        assert!(page.contains(".TH DWM 1 "), "{}", page);
+        assert!(page.contains("Dan\\*(Aqs Workspace Manager"), "{}", page);
+        assert!(page.contains("\\-\\-quiet"));
+        assert!(page.contains("dwm\\-delete(1)"), "{}", page);
+        assert!(page.contains(".SH GUIDES\n"));
+        assert!(page.contains("\\fBagents\\fR"));
+        // Hidden commands stay out.
+        assert!(!page.contains("hook\\-handler"));
+    }
+
+    #[test]
+    fn man_pages_are_written_per_command() {
+        let tmp = tempfile::tempdir().unwrap();
+        print_man_pages(Some(tmp.path())).unwrap();
+        let delete = fs::read_to_string(tmp.path().join("dwm-delete.1")).unwrap();
+        assert!(delete.contains(".TH DWM-DELETE 1 "), "{}", delete);
+        assert!(delete.contains("\\-\\-all\\-stale"), "{}", delete);
+        assert!(
+            delete.contains(".SH \"SEE ALSO\"\n\\fBdwm\\fR(1)"),
+            "{}",
+            delete
+        );
+        assert!(tmp.path().join("dwm-repos-list.1").exists());
+        assert!(!tmp.path().join("dwm-hook-handler.1").exists());
+    }
+}
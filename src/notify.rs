@@ -0,0 +1,320 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+
+use crate::agent::{AgentStatus, Transition};
+use crate::config::Config;
+
+/// A status an `on_agent_status` hook can fire on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Trigger {
+    Working,
+    Waiting,
+    Idle,
+    /// The session ended or went stale.
+    Ended,
+}
+
+impl Trigger {
+    fn of(status: Option<AgentStatus>) -> Self {
+        match status.map(AgentStatus::coarse) {
+            Some(AgentStatus::Waiting) => Trigger::Waiting,
+            Some(AgentStatus::Idle) => Trigger::Idle,
+            Some(_) => Trigger::Working,
+            None => Trigger::Ended,
+        }
+    }
+}
+
+/// Something to run when an agent's status changes: a shell `command`, or
+/// a POST of `body` to a `webhook` URL. Exactly one of the two is set.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatusHook {
+    /// Run with `sh -c`. Placeholders become quoted `$DWM_*` variables, which
+    /// are also set in its environment.
+    pub command: Option<String>,
+    /// URL to POST JSON to, with `curl`.
+    pub webhook: Option<String>,
+    /// Webhook body template; placeholders are JSON-escaped. Unset sends
+    /// the event itself.
+    pub body: Option<String>,
+    /// Statuses to fire on. Empty means every change.
+    pub on: Vec<Trigger>,
+}
+
+/// A status change, as sent to hooks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusEvent {
+    pub repo: String,
+    pub workspace: String,
+    pub session_id: String,
+    pub agent: &'static str,
+    /// The new status, or `"ended"`.
+    pub status: &'static str,
+    pub previous: Option<&'static str>,
+    /// Unix seconds.
+    pub at: u64,
+}
+
+impl StatusEvent {
+    pub fn new(repo: &str, workspace: &str, transition: &Transition) -> Self {
+        Self {
+            repo: repo.to_string(),
+            workspace: workspace.to_string(),
+            session_id: transition.session_id.clone(),
+            agent: transition.agent.name(),
+            status: transition.status.map_or("ended", AgentStatus::label),
+            previous: transition.from.map(AgentStatus::label),
+            at: transition.at,
+        }
+    }
+
+    /// Each placeholder with the environment variable carrying its value.
+    fn fields(&self) -> [(&'static str, &'static str, &str); 6] {
+        [
+            ("{repo}", "DWM_REPO", &self.repo),
+            ("{workspace}", "DWM_WORKSPACE", &self.workspace),
+            ("{status}", "DWM_STATUS", self.status),
+            (
+                "{previous}",
+                "DWM_PREVIOUS_STATUS",
+                self.previous.unwrap_or(""),
+            ),
+            ("{agent}", "DWM_AGENT", self.agent),
+            ("{session}", "DWM_SESSION", &self.session_id),
+        ]
+    }
+}
+
+impl StatusHook {
+    /// Whether the hook fires on a change to `status`.
+    fn fires_on(&self, status: Option<AgentStatus>) -> bool {
+        self.on.is_empty() || self.on.contains(&Trigger::of(status))
+    }
+
+    /// Build the process that delivers `event`.
+    fn command_for(&self, event: &StatusEvent) -> Result<Command> {
+        match (&self.command, &self.webhook) {
+            (Some(script), None) => {
+                let mut script = script.clone();
+                let mut cmd = Command::new("sh");
+                for (placeholder, var, value) in event.fields() {
+                    script = script.replace(placeholder, &format!("\"${}\"", var));
+                    cmd.env(var, value);
+                }
+                cmd.arg("-c").arg(script);
+                Ok(cmd)
+            }
+            (None, Some(url)) => {
+                let body = match &self.body {
+                    Some(template) => event.fields().iter().fold(
+                        template.clone(),
+                        |body, (placeholder, _, value)| {
+                            body.replace(placeholder, &json_escape(value))
+                        },
+                    ),
+                    None => serde_json::to_string(event)?,
+                };
+                let mut cmd = Command::new("curl");
+                cmd.args(["-fsS", "-m", "10", "-X", "POST"])
+                    .args(["-H", "Content-Type: application/json"])
+                    .arg("--data-binary")
+                    .arg(body)
+                    .arg(url);
+                Ok(cmd)
+            }
+            (Some(_), Some(_)) => {
+                bail!("an on_agent_status hook can't set both command and webhook")
+            }
+            (None, None) => bail!("an on_agent_status hook needs a command or a webhook"),
+        }
+    }
+
+    /// Start delivering `event` in the background, detached from the agent's
+    /// hook so a slow command or webhook never holds the agent up.
+    fn fire(&self, event: &StatusEvent) -> Result<Child> {
+        let mut cmd = self.command_for(event)?;
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        Ok(cmd.spawn()?)
+    }
+}
+
+/// `value` as the inside of a JSON string.
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::Value::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// Fire the `on_agent_status` hooks in `dwm_base`'s config that match
+/// `transition`, logged in `workspace` of `repo_dir`. Failures are ignored:
+/// a broken hook must not break the agent's.
+pub fn status_changed(dwm_base: &Path, repo_dir: &Path, workspace: &str, transition: &Transition) {
+    let Ok(config) = Config::load_from(&dwm_base.join("config.json")) else {
+        return;
+    };
+    if config.on_agent_status.is_empty() {
+        return;
+    }
+    let repo = crate::agent::repo_name(repo_dir).unwrap_or_default();
+    let event = StatusEvent::new(&repo, workspace, transition);
+    for hook in config
+        .on_agent_status
+        .iter()
+        .filter(|hook| hook.fires_on(transition.status))
+    {
+        let _ = hook.fire(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::AgentKind;
+    use std::fs;
+
+    fn event() -> StatusEvent {
+        StatusEvent::new(
+            "myrepo",
+            "feat \"x\"",
+            &Transition {
+                session_id: "codex-1".to_string(),
+                agent: AgentKind::Codex,
+                status: Some(AgentStatus::Waiting),
+                from: Some(AgentStatus::Working),
+                at: 1_700_000_000,
+            },
+        )
+    }
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn event_names_statuses() {
+        let event = event();
+        assert_eq!(event.agent, "codex");
+        assert_eq!(event.status, "waiting");
+        assert_eq!(event.previous, Some("working"));
+
+        let ended = StatusEvent::new(
+            "r",
+            "w",
+            &Transition {
+                session_id: "s".to_string(),
+                agent: AgentKind::Claude,
+                status: None,
+                from: Some(AgentStatus::Idle),
+                at: 0,
+            },
+        );
+        assert_eq!(ended.status, "ended");
+    }
+
+    #[test]
+    fn empty_on_fires_on_everything() {
+        let hook = StatusHook::default();
+        assert!(hook.fires_on(Some(AgentStatus::Working)));
+        assert!(hook.fires_on(None));
+
+        let hook = StatusHook {
+            on: vec![Trigger::Waiting, Trigger::Ended],
+            ..Default::default()
+        };
+        assert!(hook.fires_on(Some(AgentStatus::Waiting)));
+        assert!(hook.fires_on(None));
+        assert!(!hook.fires_on(Some(AgentStatus::Working)));
+    }
+
+    #[test]
+    fn command_placeholders_become_quoted_variables() {
+        let hook = StatusHook {
+            command: Some("notify-send {workspace} {status}".to_string()),
+            ..Default::default()
+        };
+        let cmd = hook.command_for(&event()).unwrap();
+        assert_eq!(cmd.get_program(), "sh");
+        assert_eq!(
+            args(&cmd),
+            vec!["-c", "notify-send \"$DWM_WORKSPACE\" \"$DWM_STATUS\""]
+        );
+        let workspace = cmd
+            .get_envs()
+            .find(|(k, _)| *k == "DWM_WORKSPACE")
+            .and_then(|(_, v)| v);
+        assert_eq!(workspace.unwrap(), "feat \"x\"");
+    }
+
+    #[test]
+    fn webhook_posts_event_json_by_default() {
+        let hook = StatusHook {
+            webhook: Some("https://example.com/hook".to_string()),
+            ..Default::default()
+        };
+        let cmd = hook.command_for(&event()).unwrap();
+        assert_eq!(cmd.get_program(), "curl");
+        let args = args(&cmd);
+        assert_eq!(args.last().unwrap(), "https://example.com/hook");
+        let body: serde_json::Value = serde_json::from_str(&args[args.len() - 2]).unwrap();
+        assert_eq!(body["repo"], "myrepo");
+        assert_eq!(body["workspace"], "feat \"x\"");
+        assert_eq!(body["status"], "waiting");
+        assert_eq!(body["previous"], "working");
+        assert_eq!(body["at"], 1_700_000_000);
+    }
+
+    #[test]
+    fn webhook_body_template_escapes_values() {
+        let hook = StatusHook {
+            webhook: Some("https://example.com/hook".to_string()),
+            body: Some(r#"{"text": "{repo}/{workspace} is {status}"}"#.to_string()),
+            ..Default::default()
+        };
+        let cmd = hook.command_for(&event()).unwrap();
+        let args = args(&cmd);
+        let body: serde_json::Value = serde_json::from_str(&args[args.len() - 2]).unwrap();
+        assert_eq!(body["text"], "myrepo/feat \"x\" is waiting");
+    }
+
+    #[test]
+    fn hook_needs_exactly_one_target() {
+        assert!(StatusHook::default().command_for(&event()).is_err());
+        let both = StatusHook {
+            command: Some("true".to_string()),
+            webhook: Some("https://example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(both.command_for(&event()).is_err());
+    }
+
+    #[test]
+    fn fired_command_sees_the_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let hook = StatusHook {
+            command: Some(format!(
+                "printf '%s %s %s' {{workspace}} {{previous}} {{agent}} > '{}'",
+                out.display()
+            )),
+            ..Default::default()
+        };
+        let status = hook.fire(&event()).unwrap().wait().unwrap();
+        assert!(status.success());
+        assert_eq!(
+            fs::read_to_string(&out).unwrap(),
+            "feat \"x\" working codex"
+        );
+    }
+}
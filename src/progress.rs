@@ -0,0 +1,159 @@
+use owo_colors::OwoColorize;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Braille spinner frames, shared with the picker's loading indicator.
+pub const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// How often the spinner line is redrawn.
+const TICK: Duration = Duration::from_millis(80);
+
+/// Set while a spinner line is on screen, for [`suspend`].
+static SHOWING: AtomicBool = AtomicBool::new(false);
+
+/// Held while writing to stderr, so the spinner thread and other output
+/// don't interleave mid-line.
+static DRAW: Mutex<()> = Mutex::new(());
+
+/// Draws the steps reported through [`dwm_core::progress`] on stderr: a
+/// spinner with the step's label and the time so far stays on the last
+/// line while it runs, replaced by a `✓` or `✗` line when it ends.
+struct Spinner {
+    running: Mutex<Option<Running>>,
+}
+
+/// The step whose spinner is on screen.
+struct Running {
+    started: Instant,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl dwm_core::progress::Reporter for Spinner {
+    fn start(&self, label: &str) {
+        if !enabled() {
+            return;
+        }
+        let started = Instant::now();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            let label = label.to_string();
+            thread::spawn(move || {
+                let mut tick = 0;
+                while !stop.load(Ordering::SeqCst) {
+                    let frame = SPINNER_FRAMES[tick % SPINNER_FRAMES.len()];
+                    draw(&format!(
+                        "  {} {} {}",
+                        frame.cyan(),
+                        label,
+                        elapsed(started.elapsed()).dimmed()
+                    ));
+                    tick += 1;
+                    thread::park_timeout(TICK);
+                }
+            })
+        };
+        *self.running.lock().unwrap_or_else(|e| e.into_inner()) = Some(Running {
+            started,
+            stop,
+            thread,
+        });
+    }
+
+    fn finish(&self, label: &str, ok: bool) {
+        let Some(running) = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        else {
+            return;
+        };
+        running.stop.store(true, Ordering::SeqCst);
+        running.thread.thread().unpark();
+        let _ = running.thread.join();
+
+        let _guard = DRAW.lock();
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K");
+        SHOWING.store(false, Ordering::SeqCst);
+        let _ = if ok {
+            writeln!(
+                stderr,
+                "  {} {} {}",
+                "✓".green(),
+                label,
+                elapsed(running.started.elapsed()).dimmed()
+            )
+        } else {
+            writeln!(stderr, "  {} {}", "✗".red(), label)
+        };
+    }
+}
+
+/// Draw steps with the spinner from now on.
+pub fn init() {
+    static SPINNER: Spinner = Spinner {
+        running: Mutex::new(None),
+    };
+    dwm_core::progress::set_reporter(&SPINNER);
+}
+
+/// Whether steps are shown: only with progress messages on (not
+/// `--quiet`), on a terminal, and outside a full-screen view. Piped, steps
+/// run silently and only the surrounding `log` lines are written.
+fn enabled() -> bool {
+    log::log_enabled!(log::Level::Info)
+        && std::io::stderr().is_terminal()
+        && !crossterm::terminal::is_raw_mode_enabled().unwrap_or(false)
+}
+
+/// Replace the spinner line with `line`.
+fn draw(line: &str) {
+    let _guard = DRAW.lock();
+    let mut stderr = std::io::stderr();
+    let _ = write!(stderr, "\r\x1b[2K{}", line);
+    let _ = stderr.flush();
+    SHOWING.store(true, Ordering::SeqCst);
+}
+
+/// Erase the spinner line, if one is showing, and keep it from being
+/// redrawn until the returned guard is dropped, so other output can go to
+/// stderr in between. The spinner reappears below it on its next tick.
+pub fn suspend() -> impl Drop {
+    let guard = DRAW.lock().unwrap_or_else(|e| e.into_inner());
+    if SHOWING.swap(false, Ordering::SeqCst) {
+        let _ = write!(std::io::stderr(), "\r\x1b[2K");
+    }
+    guard
+}
+
+/// `d` for a person: tenths of a second under ten seconds, then whole
+/// seconds, then minutes and seconds.
+fn elapsed(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 10 {
+        format!("{:.1}s", d.as_secs_f64())
+    } else if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elapsed_reads_naturally() {
+        assert_eq!(elapsed(Duration::from_millis(430)), "0.4s");
+        assert_eq!(elapsed(Duration::from_secs(12)), "12s");
+        assert_eq!(elapsed(Duration::from_secs(62)), "1m02s");
+        assert_eq!(elapsed(Duration::from_secs(600)), "10m00s");
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::meta::RepoMeta;
+use crate::{agent, workspace};
+
+/// How long `dwm prompt` may take without `--dirty`, so it never holds up
+/// a prompt. It stays well inside this by reading only small files under
+/// `~/.dwm/`: never the VCS, the daemon, or the network.
+const LATENCY_BUDGET: Duration = Duration::from_millis(10);
+
+/// A prompt framework `dwm shell-setup --prompt` prints a config snippet for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PromptTheme {
+    Starship,
+    #[value(name = "p10k", alias = "powerlevel10k")]
+    P10k,
+}
+
+/// Print the prompt segment for the workspace the current directory is in,
+/// like `myrepo:hazy-quail`, or nothing outside a workspace. `agents` adds
+/// the agent badge and `dirty` a `*` for uncommitted work. Only `dirty` runs
+/// the VCS; without it this reads a couple of small files and stays within
+/// [`LATENCY_BUDGET`].
+pub fn print_prompt(agents: bool, dirty: bool, color: bool) -> Result<()> {
+    let start = Instant::now();
+    let home = dirs::home_dir().context("could not determine home directory")?;
+    let cwd = workspace::working_dir()?;
+    if let Some(segment) = prompt_segment(&home.join(".dwm"), &cwd, agents, dirty, color) {
+        println!("{}", segment);
+    }
+    let took = start.elapsed();
+    if !dirty && took > LATENCY_BUDGET {
+        log::debug!(
+            "prompt took {:?}, over its {:?} budget",
+            took,
+            LATENCY_BUDGET
+        );
+    }
+    Ok(())
+}
+
+/// The segment [`print_prompt`] prints for `cwd`.
+fn prompt_segment(
+    dwm_base: &Path,
+    cwd: &Path,
+    agents: bool,
+    dirty: bool,
+    color: bool,
+) -> Option<String> {
+    let (repo_dir, ws_name) = agent::resolve_workspace_from_cwd(dwm_base, cwd)?;
+    if ws_name.starts_with('.') {
+        return None;
+    }
+    let meta = RepoMeta::load(&repo_dir).ok()??;
+    let repo = workspace::repo_label(&repo_dir, &meta);
+
+    let mut segment = match color {
+        true => format!("{}:{}", repo.dimmed(), ws_name.bold()),
+        false => format!("{}:{}", repo, ws_name),
+    };
+    if dirty && is_dirty(&repo_dir, &meta, &ws_name) {
+        segment.push_str(&match color {
+            true => "*".yellow().to_string(),
+            false => "*".to_string(),
+        });
+    }
+    if agents {
+        let badge = agent::current_badge(dwm_base, cwd, color);
+        if !badge.is_empty() {
+            segment.push(' ');
+            segment.push_str(&badge);
+        }
+    }
+    Some(segment)
+}
+
+/// Whether `ws_name` has uncommitted changes or untracked files. A VCS
+/// failure counts as clean, so the prompt never shows an error.
+fn is_dirty(repo_dir: &Path, meta: &RepoMeta, ws_name: &str) -> bool {
+    let main_repo = &meta.main_repo;
+    let backend = meta.vcs_type().to_backend();
+    let ws_path = if ws_name == backend.main_workspace_name() {
+        main_repo.to_path_buf()
+    } else {
+        repo_dir.join(ws_name)
+    };
+    backend
+        .working_copy_status(main_repo, &ws_path, ws_name)
+        .is_ok_and(|status| status.is_dirty())
+}
+
+/// Print the config snippet that adds `dwm prompt` to `theme`.
+pub fn print_prompt_snippet(theme: PromptTheme) {
+    print!("{}", prompt_snippet(theme));
+}
+
+fn prompt_snippet(theme: PromptTheme) -> &'static str {
+    match theme {
+        PromptTheme::Starship => STARSHIP_SNIPPET,
+        PromptTheme::P10k => P10K_SNIPPET,
+    }
+}
+
+const STARSHIP_SNIPPET: &str = r#"# Add to ~/.config/starship.toml. Starship colors the segment itself, and
+# hides it outside dwm workspaces. `dwm prompt` only reads files under
+# ~/.dwm, taking a few milliseconds; --dirty marks uncommitted work, but
+# runs the VCS on every prompt.
+[custom.dwm]
+command = "dwm prompt --agents"
+when = true
+# sh starts faster than a shell that loads your config first.
+shell = ["sh"]
+format = "([$output]($style) )"
+style = "bold purple"
+description = "The dwm workspace the current directory is in"
+"#;
+
+const P10K_SNIPPET: &str = r#"# Add to ~/.p10k.zsh, then add `dwm` to POWERLEVEL9K_LEFT_PROMPT_ELEMENTS
+# (or the right ones). Add --dirty to mark uncommitted work.
+function prompt_dwm() {
+  local segment
+  segment=$(command dwm prompt --agents 2>/dev/null)
+  [[ -n $segment ]] && p10k segment -f 141 -t "${segment//\%/%%}"
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn setup_repo(dwm_base: &Path, main_repo: &Path) -> PathBuf {
+        let repo_dir = dwm_base.join("myrepo-1a2b3c4d");
+        fs::create_dir_all(repo_dir.join("hazy-quail").join("src")).unwrap();
+        fs::write(
+            repo_dir.join(".main-repo"),
+            main_repo.to_string_lossy().as_ref(),
+        )
+        .unwrap();
+        fs::write(repo_dir.join(".vcs-type"), "jj").unwrap();
+        repo_dir
+    }
+
+    #[test]
+    fn segment_names_repo_and_workspace() {
+        let tmp = TempDir::new().unwrap();
+        let dwm_base = tmp.path().join(".dwm");
+        let main_repo = tmp.path().join("code").join("myrepo");
+        let repo_dir = setup_repo(&dwm_base, &main_repo);
+
+        let cwd = repo_dir.join("hazy-quail").join("src");
+        assert_eq!(
+            prompt_segment(&dwm_base, &cwd, false, false, false).as_deref(),
+            Some("myrepo:hazy-quail")
+        );
+        assert_eq!(
+            prompt_segment(&dwm_base, &main_repo, false, false, false).as_deref(),
+            Some("myrepo:default")
+        );
+
+        let mut meta = RepoMeta::load(&repo_dir).unwrap().unwrap();
+        meta.label = Some("web".to_string());
+        meta.save(&repo_dir).unwrap();
+        assert_eq!(
+            prompt_segment(&dwm_base, &cwd, false, false, false).as_deref(),
+            Some("web:hazy-quail")
+        );
+
+        assert!(prompt_segment(&dwm_base, &repo_dir, false, false, false).is_none());
+        assert!(
+            prompt_segment(
+                &dwm_base,
+                &repo_dir.join(".agent-status"),
+                false,
+                false,
+                false
+            )
+            .is_none()
+        );
+        assert!(prompt_segment(&dwm_base, tmp.path(), false, false, false).is_none());
+    }
+
+    #[test]
+    fn segment_adds_agent_badge() {
+        let tmp = TempDir::new().unwrap();
+        let dwm_base = tmp.path().join(".dwm");
+        let repo_dir = setup_repo(&dwm_base, &tmp.path().join("myrepo"));
+        let status_dir = repo_dir.join(".agent-status");
+        fs::create_dir_all(&status_dir).unwrap();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        fs::write(
+            status_dir.join("s1.json"),
+            format!(r#"{{"workspace":"hazy-quail","status":"waiting","updated_at":{now}}}"#),
+        )
+        .unwrap();
+
+        let cwd = repo_dir.join("hazy-quail");
+        assert_eq!(
+            prompt_segment(&dwm_base, &cwd, true, false, false).as_deref(),
+            Some("myrepo:hazy-quail ⏳1")
+        );
+        assert_eq!(
+            prompt_segment(&dwm_base, &cwd, false, false, false).as_deref(),
+            Some("myrepo:hazy-quail")
+        );
+    }
+
+    #[test]
+    fn segment_is_within_latency_budget() {
+        let tmp = TempDir::new().unwrap();
+        let dwm_base = tmp.path().join(".dwm");
+        // Other repos are scanned when resolving a main checkout.
+        for i in 0..20 {
+            let other = dwm_base.join(format!("other-{}", i));
+            fs::create_dir_all(&other).unwrap();
+            fs::write(other.join(".main-repo"), format!("/src/other-{}", i)).unwrap();
+        }
+        let main_repo = tmp.path().join("code").join("myrepo");
+        let repo_dir = setup_repo(&dwm_base, &main_repo);
+        let status_dir = repo_dir.join(".agent-status");
+        fs::create_dir_all(&status_dir).unwrap();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        for i in 0..20 {
+            fs::write(
+                status_dir.join(format!("s{}.json", i)),
+                format!(r#"{{"workspace":"hazy-quail","status":"working","updated_at":{now}}}"#),
+            )
+            .unwrap();
+        }
+
+        // The quickest of a few runs, so a busy machine doesn't fail it.
+        let fastest = (0..5)
+            .map(|_| {
+                let start = Instant::now();
+                prompt_segment(&dwm_base, &main_repo, true, false, true).unwrap();
+                prompt_segment(&dwm_base, &repo_dir.join("hazy-quail"), true, false, true).unwrap();
+                start.elapsed() / 2
+            })
+            .min()
+            .unwrap();
+        assert!(fastest < LATENCY_BUDGET, "took {:?}", fastest);
+    }
+
+    #[test]
+    fn snippets_call_dwm_prompt() {
+        assert!(prompt_snippet(PromptTheme::Starship).contains("[custom.dwm]"));
+        assert!(prompt_snippet(PromptTheme::Starship).contains(r#"shell = ["sh"]"#));
+        assert!(prompt_snippet(PromptTheme::P10k).contains("function prompt_dwm()"));
+        for theme in [PromptTheme::Starship, PromptTheme::P10k] {
+            assert!(prompt_snippet(theme).contains("dwm prompt --agents"));
+        }
+    }
+}
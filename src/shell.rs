@@ -1,18 +1,59 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::platform::{read_tty_line, tilde_path};
+
+/// Environment variable naming the file the wrappers read a directory to
+/// `cd` into from once dwm exits; see [`print_cd`]. Every command runs with
+/// it, so a new command that changes directory needs no wrapper changes.
+pub const CD_FILE_ENV: &str = "DWM_CD_FILE";
+
+/// Environment variable that wrappers from before [`CD_FILE_ENV`] set while
+/// capturing stdout to `cd`, so the picker can tell such a capture apart
+/// from a pipe like `dwm list | fzf`.
+pub const WRAPPER_ENV: &str = "DWM_SHELL_WRAPPER";
+
+/// The wrapper's cd file, taken out of the environment by [`take_cd_file`].
+static CD_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Take the wrapper's cd file out of the environment, so the commands dwm
+/// runs (agents, editors, `dwm exec`) can't write to it. Call first thing
+/// in `main`.
+pub fn take_cd_file() {
+    let Some(file) = std::env::var_os(CD_FILE_ENV).filter(|f| !f.is_empty()) else {
+        return;
+    };
+    // SAFETY: called at the start of `main`, before any other thread exists.
+    unsafe { std::env::remove_var(CD_FILE_ENV) };
+    let _ = CD_FILE.set(PathBuf::from(file));
+}
 
-/// Subcommands whose stdout may be a workspace path that the shell wrapper
-/// should `cd` into. This is the single source of truth — both the POSIX and
-/// fish wrapper generators read from this list.
-pub const CD_SUBCOMMANDS: &[&str] = &["new", "list", "switch", "delete", "rename"];
+/// Hand `path` to the shell wrapper to `cd` into: written to its cd file,
+/// or without one printed to stdout, for older wrappers that capture it and
+/// for `cd "$(command dwm switch x)"`.
+pub fn print_cd(path: &Path) -> Result<()> {
+    match CD_FILE.get() {
+        Some(file) => std::fs::write(file, format!("{}\n", path.display()))
+            .with_context(|| format!("could not write {}", file.display()))?,
+        None => println!("{}", path.display()),
+    }
+    Ok(())
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum Shell {
     Bash,
     Zsh,
     Fish,
+    #[value(alias = "nu")]
+    Nushell,
+    #[value(name = "powershell", alias = "pwsh")]
+    Pwsh,
+    Elvish,
+    Xonsh,
 }
 
 impl Shell {
@@ -29,6 +70,13 @@ impl Shell {
             }
             Shell::Zsh => home.join(".zshrc"),
             Shell::Bash => home.join(".bashrc"),
+            Shell::Nushell => nushell_config_dir().join("config.nu"),
+            Shell::Pwsh => powershell_profile_dir().join("Microsoft.PowerShell_profile.ps1"),
+            Shell::Elvish => match std::env::var("XDG_CONFIG_HOME") {
+                Ok(xdg) if !xdg.is_empty() => PathBuf::from(xdg).join("elvish/rc.elv"),
+                _ => home.join(".config/elvish/rc.elv"),
+            },
+            Shell::Xonsh => home.join(".xonshrc"),
         }
     }
 
@@ -37,57 +85,324 @@ impl Shell {
         match self {
             Shell::Fish => "dwm shell-setup --fish | source",
             Shell::Bash | Shell::Zsh => r#"eval "$(dwm shell-setup)""#,
+            Shell::Nushell => NUSHELL_SOURCE_LINE,
+            Shell::Pwsh => "Invoke-Expression (& dwm shell-setup --powershell | Out-String)",
+            Shell::Elvish => "eval (dwm shell-setup --elvish | slurp)",
+            Shell::Xonsh => {
+                "execx($(dwm shell-setup --xonsh), 'exec', __xonsh__.ctx, filename='dwm')"
+            }
+        }
+    }
+
+    /// Every config file the setup touches, with the line each one needs.
+    /// Nushell can't source a command's output directly, so `env.nu` saves
+    /// the wrapper to a file that `config.nu` then sources.
+    fn setup_lines(&self) -> Vec<(PathBuf, &'static str)> {
+        let mut lines = Vec::new();
+        if *self == Shell::Nushell {
+            lines.push((nushell_config_dir().join("env.nu"), NUSHELL_SAVE_LINE));
         }
+        lines.push((self.config_path(), self.setup_line()));
+        lines
     }
 
+    /// Returns the wrapper, between version markers so a copy pasted into a
+    /// config file can be checked and refreshed later.
     fn function_output(&self) -> String {
-        match self {
+        marked(&self.wrapper_body())
+    }
+
+    /// The wrapper function, followed by completions where the shell has
+    /// them.
+    fn wrapper_body(&self) -> String {
+        let function = match self {
             Shell::Fish => fish_function(),
             Shell::Bash | Shell::Zsh => posix_function(),
+            Shell::Nushell => nushell_function(),
+            Shell::Pwsh => powershell_function(),
+            Shell::Elvish => elvish_function(),
+            Shell::Xonsh => xonsh_function(),
+        };
+        match crate::completions::wrapper_completions(*self) {
+            Some(completions) => format!("{}\n\n{}", function, completions),
+            None => function,
+        }
+    }
+
+    /// The first line of a wrapper pasted before the version markers existed.
+    fn legacy_wrapper_start(&self) -> Option<&'static str> {
+        match self {
+            Shell::Fish => Some("function dwm"),
+            Shell::Bash | Shell::Zsh => Some("dwm() {"),
+            Shell::Pwsh => Some("function dwm {"),
+            // The nushell wrapper is always saved fresh by env.nu, and the
+            // others have only ever been pasted with markers.
+            Shell::Nushell | Shell::Elvish | Shell::Xonsh => None,
         }
     }
+
+    /// The line that closes an unmarked wrapper's function.
+    fn legacy_wrapper_end(&self) -> &'static str {
+        match self {
+            Shell::Fish => "end",
+            _ => "}",
+        }
+    }
+}
+
+/// Opens a wrapper, followed by the [`crate::vcs::short_hash`] of its body.
+const MARKER_START: &str = "# >>> dwm wrapper";
+
+/// Closes a wrapper opened by [`MARKER_START`].
+const MARKER_END: &str = "# <<< dwm wrapper <<<";
+
+/// `body` between the version markers. Every shell dwm supports reads `#`
+/// lines as comments.
+fn marked(body: &str) -> String {
+    format!(
+        "{MARKER_START} {} >>>\n{body}\n{MARKER_END}",
+        crate::vcs::short_hash(body)
+    )
+}
+
+/// A copy of the wrapper pasted into a config file.
+#[derive(Debug, PartialEq, Eq)]
+struct PastedWrapper {
+    /// The lines it spans, markers included.
+    lines: std::ops::Range<usize>,
+    /// Whether it's what this dwm would emit.
+    current: bool,
+}
+
+/// Find a wrapper pasted into `contents`: one between version markers, or
+/// failing that, an unmarked one from before they existed, which is always
+/// out of date.
+fn find_pasted_wrapper(shell: Shell, contents: &str) -> Option<PastedWrapper> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let closing = |start: usize, end_line: &str| {
+        (start..lines.len())
+            .find(|&i| lines[i].trim_end() == end_line)
+            .map(|end| start..end + 1)
+    };
+    if let Some(start) = lines.iter().position(|l| l.starts_with(MARKER_START)) {
+        let version = lines[start][MARKER_START.len()..]
+            .trim()
+            .trim_end_matches(">>>")
+            .trim();
+        return Some(PastedWrapper {
+            lines: closing(start, MARKER_END)?,
+            current: version == crate::vcs::short_hash(&shell.wrapper_body()),
+        });
+    }
+    let legacy = shell.legacy_wrapper_start()?;
+    let start = lines.iter().position(|l| l.trim_end() == legacy)?;
+    Some(PastedWrapper {
+        lines: closing(start, shell.legacy_wrapper_end())?,
+        current: false,
+    })
+}
+
+/// `contents` with the wrapper at `lines` replaced by the current one.
+fn refresh_wrapper(shell: Shell, contents: &str, lines: std::ops::Range<usize>) -> String {
+    let mut out: Vec<String> = contents.lines().map(str::to_string).collect();
+    out.splice(lines, [shell.function_output()]);
+    let mut refreshed = out.join("\n");
+    if contents.ends_with('\n') {
+        refreshed.push('\n');
+    }
+    refreshed
+}
+
+/// The `env.nu` line that writes the nushell wrapper to a file each startup.
+const NUSHELL_SAVE_LINE: &str =
+    r#"dwm shell-setup --nushell | save --force ($nu.default-config-dir | path join "dwm.nu")"#;
+
+/// The `config.nu` line that loads the file written by [`NUSHELL_SAVE_LINE`].
+const NUSHELL_SOURCE_LINE: &str = r#"source ($nu.default-config-dir | path join "dwm.nu")"#;
+
+/// Nushell's config directory, as `$nu.default-config-dir` finds it.
+fn nushell_config_dir() -> PathBuf {
+    match std::env::var("XDG_CONFIG_HOME") {
+        Ok(xdg) if !xdg.is_empty() => PathBuf::from(xdg).join("nushell"),
+        _ => dirs::config_dir()
+            .expect("could not determine config directory")
+            .join("nushell"),
+    }
 }
 
 /// Returns the POSIX shell function definition that wraps the `dwm` binary.
-/// Subcommands listed in [`CD_SUBCOMMANDS`] (plus the bare invocation) capture
-/// stdout and `cd` into the result. All other subcommands run directly.
+/// It runs every command with a fresh [`CD_FILE_ENV`] and `cd`s into
+/// whatever dwm wrote there, if the command succeeded.
 fn posix_function() -> String {
-    let cases = CD_SUBCOMMANDS.join("|");
     format!(
         r#"dwm() {{
-    case "$1" in
-        {cases}|"")
-            local dir
-            dir="$(command dwm "$@")" || return $?
-            [ -n "$dir" ] && cd "$dir"
-            ;;
-        *)
-            command dwm "$@"
-            ;;
-    esac
+    local cd_file dir ret
+    cd_file="$(mktemp "${{TMPDIR:-/tmp}}/dwm-cd.XXXXXX")" || {{
+        command dwm "$@"
+        return
+    }}
+    {CD_FILE_ENV}="$cd_file" command dwm "$@"
+    ret=$?
+    dir="$(cat "$cd_file")"
+    rm -f "$cd_file"
+    if [ "$ret" -eq 0 ] && [ -n "$dir" ]; then
+        cd "$dir" || return
+        command dwm _on-cd "$dir"
+    fi
+    return "$ret"
 }}"#
     )
 }
 
 /// Returns the fish shell function definition that wraps the `dwm` binary.
 fn fish_function() -> String {
-    let cases = CD_SUBCOMMANDS.join(" ");
     format!(
         r#"function dwm
-    switch "$argv[1]"
-        case {cases} ""
-            set -l dir (command dwm $argv)
-            or return $status
-            if test -n "$dir"
-                cd "$dir"; or return 1
-            end
-        case '*'
-            command dwm $argv
+    set -l cd_file (mktemp); or begin
+        command dwm $argv
+        return
     end
+    {CD_FILE_ENV}=$cd_file command dwm $argv
+    set -l ret $status
+    set -l dir (cat $cd_file)
+    rm -f $cd_file
+    if test $ret -eq 0; and test -n "$dir"
+        cd "$dir"; or return 1
+        command dwm _on-cd "$dir"
+    end
+    return $ret
 end"#
     )
 }
 
+/// Returns the nushell wrapper: a `def --env` command, so its `cd` sticks,
+/// whose arguments complete through [`crate::completions::nushell_completer`].
+fn nushell_function() -> String {
+    format!(
+        r#"{completer}
+
+def --env --wrapped dwm [...args: string@"nu-complete dwm"] {{
+    let cd_file = (mktemp --tmpdir dwm-cd.XXXXXX)
+    let ok = try {{
+        with-env {{ {CD_FILE_ENV}: $cd_file }} {{ ^dwm ...$args }}
+        true
+    }} catch {{ false }}
+    let dir = (open --raw $cd_file | into string | str trim)
+    rm --force $cd_file
+    if $ok and ($dir | is-not-empty) {{
+        cd $dir
+        ^dwm _on-cd $dir
+    }}
+}}"#,
+        completer = crate::completions::nushell_completer(),
+    )
+}
+
+/// The directory holding PowerShell's `$PROFILE`: `Documents\PowerShell` on
+/// Windows, `~/.config/powershell` elsewhere.
+fn powershell_profile_dir() -> PathBuf {
+    if cfg!(windows) {
+        return dirs::document_dir()
+            .expect("could not determine documents directory")
+            .join("PowerShell");
+    }
+    match std::env::var("XDG_CONFIG_HOME") {
+        Ok(xdg) if !xdg.is_empty() => PathBuf::from(xdg).join("powershell"),
+        _ => dirs::home_dir()
+            .expect("could not determine home directory")
+            .join(".config/powershell"),
+    }
+}
+
+/// Returns the PowerShell wrapper. It calls the `dwm` application found on
+/// `PATH` explicitly, since a bare `dwm` inside the function would be itself.
+fn powershell_function() -> String {
+    format!(
+        r#"function dwm {{
+    $dwmBin = Get-Command -Name dwm -CommandType Application | Select-Object -First 1
+    $cdFile = (New-TemporaryFile).FullName
+    $previous = $env:{CD_FILE_ENV}
+    $env:{CD_FILE_ENV} = $cdFile
+    try {{
+        & $dwmBin @args
+    }} finally {{
+        $env:{CD_FILE_ENV} = $previous
+    }}
+    $ret = $LASTEXITCODE
+    $dir = Get-Content -LiteralPath $cdFile -Raw
+    Remove-Item -LiteralPath $cdFile -ErrorAction SilentlyContinue
+    if ($ret -eq 0 -and $dir) {{
+        $dir = $dir.Trim()
+        Set-Location -LiteralPath $dir
+        & $dwmBin _on-cd $dir
+    }}
+    $global:LASTEXITCODE = $ret
+}}"#
+    )
+}
+
+/// Returns the Elvish wrapper. `eval` runs it in a throwaway namespace, so
+/// it adds `dwm` to the REPL's with `edit:add-var`; inside, `e:dwm` is the
+/// binary. A failing dwm is rethrown after the cd file is cleaned up.
+fn elvish_function() -> String {
+    format!(
+        r#"use os
+use file
+use str
+fn dwm {{|@args|
+    var f = (os:temp-file 'dwm-cd.*')
+    file:close $f
+    var cd-file = $f[name]
+    var err = $nil
+    try {{
+        tmp E:{CD_FILE_ENV} = $cd-file
+        e:dwm $@args
+    }} catch e {{
+        set err = $e
+    }}
+    var dir = (str:trim-space (slurp < $cd-file))
+    os:remove $cd-file
+    if (not-eq $err $nil) {{
+        fail $err
+    }}
+    if (not-eq $dir '') {{
+        cd $dir
+        e:dwm _on-cd $dir
+    }}
+}}
+edit:add-var dwm~ $dwm~"#
+    )
+}
+
+/// Returns the xonsh wrapper: a callable alias that runs the `dwm` binary
+/// found on `$PATH` in the foreground and returns its exit code.
+fn xonsh_function() -> String {
+    format!(
+        r#"from xonsh.tools import unthreadable
+
+@unthreadable
+def _dwm(args):
+    import os, shutil, subprocess, tempfile
+    env = ${{...}}.detype()
+    dwm_bin = shutil.which("dwm", path=env.get("PATH"))
+    fd, cd_file = tempfile.mkstemp(prefix="dwm-cd.")
+    os.close(fd)
+    try:
+        env["{CD_FILE_ENV}"] = cd_file
+        ret = subprocess.run([dwm_bin, *args], env=env).returncode
+        with open(cd_file) as f:
+            target = f.read().strip()
+    finally:
+        os.remove(cd_file)
+    if ret == 0 and target:
+        cd @(target)
+        subprocess.run([dwm_bin, "_on-cd", target])
+    return ret
+
+aliases["dwm"] = _dwm"#
+    )
+}
+
 /// Detect the parent shell from environment variables.
 fn detect_shell() -> Option<Shell> {
     // Check shell-specific version env vars first (most reliable).
@@ -100,8 +415,17 @@ fn detect_shell() -> Option<Shell> {
     if std::env::var("BASH_VERSION").is_ok() {
         return Some(Shell::Bash);
     }
+    if std::env::var("NU_VERSION").is_ok() {
+        return Some(Shell::Nushell);
+    }
+    if std::env::var("XONSH_VERSION").is_ok() {
+        return Some(Shell::Xonsh);
+    }
     // Fall back to $SHELL (login shell).
     if let Ok(shell) = std::env::var("SHELL") {
+        if shell.ends_with("/nu") {
+            return Some(Shell::Nushell);
+        }
         if shell.ends_with("/fish") {
             return Some(Shell::Fish);
         }
@@ -111,17 +435,21 @@ fn detect_shell() -> Option<Shell> {
         if shell.ends_with("/bash") {
             return Some(Shell::Bash);
         }
+        if shell.ends_with("/pwsh") {
+            return Some(Shell::Pwsh);
+        }
+        if shell.ends_with("/elvish") {
+            return Some(Shell::Elvish);
+        }
+        if shell.ends_with("/xonsh") {
+            return Some(Shell::Xonsh);
+        }
     }
-    None
-}
-
-fn display_config_path(path: &std::path::Path) -> String {
-    if let Ok(home) = std::env::var("HOME")
-        && let Ok(rest) = path.strip_prefix(&home)
-    {
-        return format!("~/{}", rest.display());
+    // Windows has no $SHELL; PowerShell always sets PSModulePath.
+    if std::env::var("PSModulePath").is_ok() {
+        return Some(Shell::Pwsh);
     }
-    path.display().to_string()
+    None
 }
 
 /// Run interactive shell setup: detect the shell and offer to install.
@@ -131,8 +459,13 @@ pub fn setup_shell_interactive() -> Result<()> {
         Some(s) => {
             let installed = offer_install(s)?;
             if !installed {
-                eprintln!("{}", "  Add this to your shell config manually:".dimmed());
-                eprintln!("    {}", s.setup_line().bold());
+                for (config, line) in s.setup_lines() {
+                    eprintln!(
+                        "{}",
+                        format!("  Add this to {} manually:", tilde_path(&config)).dimmed()
+                    );
+                    eprintln!("    {}", line.bold());
+                }
             }
         }
         None => {
@@ -144,16 +477,65 @@ pub fn setup_shell_interactive() -> Result<()> {
     Ok(())
 }
 
-/// Offer to append the setup line to the user's shell config file.
+/// Offer to append the setup lines to the user's shell config files.
 /// Returns `true` if the hint should be suppressed (already installed or just installed).
 fn offer_install(shell: Shell) -> Result<bool> {
+    if let Some(handled) = offer_refresh(shell)? {
+        return Ok(handled);
+    }
+    let mut installed = true;
+    for (config, setup_line) in shell.setup_lines() {
+        installed &= offer_install_line(&config, setup_line)?;
+    }
+    Ok(installed)
+}
+
+/// If the shell config has the wrapper pasted in rather than the setup line,
+/// check it's current and offer to refresh it if not. Returns `None` when
+/// there's no pasted wrapper, else whether the hint should be suppressed.
+fn offer_refresh(shell: Shell) -> Result<Option<bool>> {
     let config = shell.config_path();
-    let setup_line = shell.setup_line();
-    let display = display_config_path(&config);
+    let Ok(contents) = std::fs::read_to_string(&config) else {
+        return Ok(None);
+    };
+    let Some(pasted) = find_pasted_wrapper(shell, &contents) else {
+        return Ok(None);
+    };
+    let display = tilde_path(&config);
+    if pasted.current {
+        eprintln!(
+            "  {} Wrapper in {} is up to date",
+            "✓".green(),
+            display.dimmed()
+        );
+        return Ok(Some(true));
+    }
+    eprintln!(
+        "  {} The dwm wrapper in {} is out of date",
+        "!".yellow(),
+        display.bold()
+    );
+    eprint!("  {} Refresh it? [y/N] ", "?".bold().cyan());
+    let response = read_tty_line()?.unwrap_or_default();
+    if !response.trim().eq_ignore_ascii_case("y") {
+        return Ok(Some(false));
+    }
+    std::fs::write(&config, refresh_wrapper(shell, &contents, pasted.lines))?;
+    eprintln!(
+        "  {} Refreshed wrapper in {}",
+        "✓".green(),
+        display.dimmed()
+    );
+    Ok(Some(true))
+}
+
+/// Offer to append `setup_line` to `config`, unless it's already there.
+fn offer_install_line(config: &Path, setup_line: &str) -> Result<bool> {
+    let display = tilde_path(config);
 
     // Check if already present.
     if config.exists() {
-        let contents = std::fs::read_to_string(&config)?;
+        let contents = std::fs::read_to_string(config)?;
         if contents.contains(setup_line) {
             eprintln!(
                 "  {} Already installed in {}",
@@ -164,20 +546,12 @@ fn offer_install(shell: Shell) -> Result<bool> {
         }
     }
 
-    // Prompt the user. Read from /dev/tty so this works even if stdin is redirected.
+    // Prompt the user. Read from the terminal so this works even if stdin is redirected.
     eprint!("  {} Add to {}? [y/N] ", "?".bold().cyan(), display.bold());
-    let tty = std::fs::File::open("/dev/tty");
-    let response = match tty {
-        Ok(f) => {
-            let mut line = String::new();
-            std::io::BufRead::read_line(&mut std::io::BufReader::new(f), &mut line)?;
-            line
-        }
-        Err(_) => String::new(),
-    };
+    let response = read_tty_line()?.unwrap_or_default();
 
     if response.trim().eq_ignore_ascii_case("y") {
-        // Ensure parent directory exists (relevant for fish config).
+        // Ensure parent directory exists (relevant for fish and nushell config).
         if let Some(parent) = config.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -185,10 +559,10 @@ fn offer_install(shell: Shell) -> Result<bool> {
         let mut file = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
-            .open(&config)?;
+            .open(config)?;
         // Add a newline before the setup line if the file doesn't end with one.
         let needs_newline = config.exists() && {
-            let contents = std::fs::read_to_string(&config)?;
+            let contents = std::fs::read_to_string(config)?;
             !contents.is_empty() && !contents.ends_with('\n')
         };
         if needs_newline {
@@ -202,6 +576,79 @@ fn offer_install(shell: Shell) -> Result<bool> {
     }
 }
 
+/// Whether `line` loads dwm's wrapper or completions.
+fn loads_dwm(line: &str) -> bool {
+    let line = line.trim();
+    !line.starts_with('#')
+        && (line.contains("dwm shell-setup")
+            || line.contains("dwm completions")
+            || line == NUSHELL_SOURCE_LINE)
+}
+
+/// `contents` of one of `shell`'s config files with dwm taken out: every
+/// pasted wrapper and every line that loads the wrapper or completions.
+/// `None` if there's no dwm in it.
+fn remove_setup(shell: Shell, contents: &str) -> Option<String> {
+    let mut lines: Vec<&str> = contents.lines().collect();
+    let before = lines.len();
+    while let Some(pasted) = find_pasted_wrapper(shell, &lines.join("\n")) {
+        lines.drain(pasted.lines);
+    }
+    lines.retain(|l| !loads_dwm(l));
+    if lines.len() == before {
+        return None;
+    }
+    let mut removed = lines.join("\n");
+    if contents.ends_with('\n') && !removed.is_empty() {
+        removed.push('\n');
+    }
+    Some(removed)
+}
+
+/// Take dwm out of `shell`'s config files, or with `None` out of every
+/// supported shell's, asking before changing each one.
+pub fn uninstall_shell(shell: Option<Shell>) -> Result<()> {
+    let shells = match shell {
+        Some(s) => vec![s],
+        None => <Shell as clap::ValueEnum>::value_variants().to_vec(),
+    };
+    let mut found = false;
+    for shell in shells {
+        for (config, _) in shell.setup_lines() {
+            let Ok(contents) = std::fs::read_to_string(&config) else {
+                continue;
+            };
+            let Some(removed) = remove_setup(shell, &contents) else {
+                continue;
+            };
+            found = true;
+            let display = tilde_path(&config);
+            eprint!(
+                "  {} Remove dwm from {}? [y/N] ",
+                "?".bold().cyan(),
+                display.bold()
+            );
+            let response = read_tty_line()?.unwrap_or_default();
+            if !response.trim().eq_ignore_ascii_case("y") {
+                continue;
+            }
+            std::fs::write(&config, removed)?;
+            eprintln!("  {} Removed from {}", "✓".green(), display.dimmed());
+        }
+        // The wrapper env.nu saved goes once config.nu no longer sources it.
+        if shell == Shell::Nushell
+            && std::fs::read_to_string(shell.config_path())
+                .is_ok_and(|config| !config.contains(NUSHELL_SOURCE_LINE))
+        {
+            let _ = std::fs::remove_file(nushell_config_dir().join("dwm.nu"));
+        }
+    }
+    if !found {
+        eprintln!("  {} No dwm shell setup to remove", "✓".green());
+    }
+    Ok(())
+}
+
 /// Print the shell integration wrapper to stdout.
 ///
 /// When stdout is a terminal and we can detect the shell, offer to auto-install
@@ -225,13 +672,33 @@ pub fn print_shell_setup(shell: Option<Shell>) -> Result<()> {
                             eprintln!("{}", "# Add this to your shell rc file:".dimmed());
                             eprintln!("{} {}", "# ".dimmed(), s.setup_line().bold());
                         }
+                        Shell::Pwsh => {
+                            eprintln!("{}", "# Add this to your PowerShell $PROFILE:".dimmed());
+                            eprintln!("{} {}", "# ".dimmed(), s.setup_line().bold());
+                        }
+                        Shell::Elvish | Shell::Xonsh => {
+                            eprintln!(
+                                "{}",
+                                format!("# Add this to {}:", tilde_path(&s.config_path())).dimmed()
+                            );
+                            eprintln!("{} {}", "# ".dimmed(), s.setup_line().bold());
+                        }
+                        Shell::Nushell => {
+                            for (config, line) in s.setup_lines() {
+                                eprintln!(
+                                    "{}",
+                                    format!("# Add this to {}:", tilde_path(&config)).dimmed()
+                                );
+                                eprintln!("{} {}", "# ".dimmed(), line.bold());
+                            }
+                        }
                     }
                 }
             }
         }
         None => {
             // Can't detect shell, emit posix and show generic hint.
-            println!("{}", posix_function());
+            println!("{}", marked(&posix_function()));
             if std::io::stdout().is_terminal() {
                 eprintln!("{}", "# Add this to your shell rc file:".dimmed());
                 eprintln!("{} {}", "# ".dimmed(), "eval \"$(dwm shell-setup)\"".bold());
@@ -266,31 +733,12 @@ mod tests {
     }
 
     #[test]
-    fn posix_function_includes_all_cd_subcommands() {
-        let fn_str = posix_function();
-        for sub in CD_SUBCOMMANDS {
-            assert!(
-                fn_str.contains(sub),
-                "posix wrapper must include cd subcommand '{sub}'"
-            );
-        }
-    }
-
-    #[test]
-    fn posix_function_passes_other_subcommands_through() {
+    fn posix_function_passes_a_cd_file() {
         let fn_str = posix_function();
-        assert!(
-            fn_str.contains("*)\n            command dwm \"$@\""),
-            "non-cd subcommands must pass through directly"
-        );
-    }
-
-    #[test]
-    fn posix_function_propagates_exit_code() {
-        assert!(
-            posix_function().contains("|| return $?"),
-            "must propagate exit code on failure"
-        );
+        assert!(fn_str.contains("DWM_CD_FILE=\"$cd_file\" command dwm \"$@\""));
+        assert!(fn_str.contains("rm -f \"$cd_file\""));
+        // zsh reserves $status, so the exit code is kept in $ret.
+        assert!(fn_str.contains("ret=$?") && fn_str.contains("return \"$ret\""));
     }
 
     #[test]
@@ -299,7 +747,7 @@ mod tests {
         let open = fn_str.matches('{').count();
         let close = fn_str.matches('}').count();
         assert_eq!(open, close, "braces must be balanced");
-        assert!(fn_str.contains("local dir"));
+        assert!(fn_str.contains("local cd_file dir ret"));
     }
 
     // --- Fish wrapper structure tests ---
@@ -323,40 +771,11 @@ mod tests {
     }
 
     #[test]
-    fn fish_function_includes_all_cd_subcommands() {
+    fn fish_function_passes_a_cd_file() {
         let fn_str = fish_function();
-        for sub in CD_SUBCOMMANDS {
-            assert!(
-                fn_str.contains(sub),
-                "fish wrapper must include cd subcommand '{sub}'"
-            );
-        }
-    }
-
-    #[test]
-    fn fish_function_passes_other_subcommands_through() {
-        let fn_str = fish_function();
-        assert!(fn_str.contains("case '*'"), "must have a catch-all case");
-        assert!(
-            fn_str.contains("command dwm $argv"),
-            "non-cd subcommands must pass through directly"
-        );
-    }
-
-    #[test]
-    fn fish_function_propagates_exit_code() {
-        assert!(
-            fish_function().contains("or return $status"),
-            "must propagate exit code on failure"
-        );
-    }
-
-    #[test]
-    fn fish_function_uses_set_for_variables() {
-        assert!(
-            fish_function().contains("set -l dir"),
-            "must use set -l for local variables"
-        );
+        assert!(fn_str.contains("DWM_CD_FILE=$cd_file command dwm $argv"));
+        assert!(fn_str.contains("set -l ret $status"));
+        assert!(fn_str.contains("return $ret"));
     }
 
     // --- POSIX wrapper integration tests (require bash) ---
@@ -369,17 +788,20 @@ mod tests {
             .unwrap_or(false)
     }
 
-    /// Eval the POSIX wrapper in bash with a fake `dwm` binary that prints
-    /// `target_dir` to stdout. Runs `dwm <args>` then `pwd`, returning the
-    /// final working directory.
-    fn run_posix_wrapper(args: &str, target_dir: &std::path::Path) -> String {
-        let tmp = tempfile::tempdir().unwrap();
-
-        // Create a fake dwm binary that prints the target directory.
-        let fake_bin = tmp.path().join("dwm");
+    /// Eval the POSIX wrapper in bash with a fake `dwm` binary running
+    /// `fake_body`, which logs `_on-cd` calls to `on-cd.log` in `tmp`. Runs
+    /// `script` and returns its stdout.
+    fn run_posix_wrapper(tmp: &Path, fake_body: &str, script: &str) -> String {
+        let bin_dir = tmp.join("bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        let fake_bin = bin_dir.join("dwm");
         std::fs::write(
             &fake_bin,
-            format!("#!/bin/sh\necho '{}'", target_dir.display()),
+            format!(
+                "#!/bin/sh\ncase \"$1\" in\n_on-cd) echo \"$2\" >> '{}' ;;\n*) {} ;;\nesac",
+                tmp.join("on-cd.log").display(),
+                fake_body
+            ),
         )
         .unwrap();
         #[cfg(unix)]
@@ -388,30 +810,27 @@ mod tests {
             std::fs::set_permissions(&fake_bin, std::fs::Permissions::from_mode(0o755)).unwrap();
         }
 
-        let wrapper = posix_function();
         let script = format!(
-            "export PATH=\"{bin_dir}:$PATH\"\n{wrapper}\ndwm {args}\npwd",
-            bin_dir = tmp.path().display(),
+            "export PATH=\"{}:$PATH\"\n{}\n{}",
+            bin_dir.display(),
+            posix_function(),
+            script
         );
-
         let output = std::process::Command::new("bash")
             .arg("-c")
             .arg(&script)
             .output()
             .unwrap();
-
         assert!(
             output.status.success(),
             "bash wrapper failed: {}",
             String::from_utf8_lossy(&output.stderr)
         );
-        // The last line of stdout is `pwd` output (the current directory).
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        stdout.lines().last().unwrap_or("").to_string()
+        String::from_utf8_lossy(&output.stdout).into_owned()
     }
 
     #[test]
-    fn posix_wrapper_cds_for_each_cd_subcommand() {
+    fn posix_wrapper_cds_into_the_cd_file_path() {
         if !bash_available() {
             return;
         }
@@ -419,50 +838,41 @@ mod tests {
         let target = tmp.path().join("workspace");
         std::fs::create_dir(&target).unwrap();
 
-        for sub in CD_SUBCOMMANDS {
-            let pwd = run_posix_wrapper(&format!("{sub} some-arg"), &target);
-            assert_eq!(
-                pwd,
-                target.to_str().unwrap(),
-                "wrapper must cd after `dwm {sub}`"
-            );
+        // Any subcommand can ask for a cd, including ones the wrapper has
+        // never heard of.
+        let fake = format!("echo '{}' > \"$DWM_CD_FILE\"", target.display());
+        for args in ["switch x", "", "brand-new-command"] {
+            let out = run_posix_wrapper(tmp.path(), &fake, &format!("dwm {args}\npwd"));
+            assert_eq!(out.trim(), target.to_str().unwrap(), "after `dwm {args}`");
         }
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("on-cd.log")).unwrap(),
+            format!("{}\n", target.display()).repeat(3)
+        );
     }
 
     #[test]
-    fn posix_wrapper_cds_on_bare_invocation() {
+    fn posix_wrapper_leaves_stdout_alone() {
         if !bash_available() {
             return;
         }
         let tmp = tempfile::tempdir().unwrap();
-        let target = tmp.path().join("workspace");
-        std::fs::create_dir(&target).unwrap();
-
-        let pwd = run_posix_wrapper("", &target);
-        assert_eq!(
-            pwd,
-            target.to_str().unwrap(),
-            "wrapper must cd on bare (no subcommand) invocation"
-        );
+        let out = run_posix_wrapper(tmp.path(), "echo 'name  path'", "dwm list --plain\npwd");
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(out, format!("name  path\n{}\n", cwd.display()));
+        assert!(!tmp.path().join("on-cd.log").exists());
     }
 
     #[test]
-    fn posix_wrapper_does_not_cd_for_other_subcommands() {
+    fn posix_wrapper_keeps_exit_code_and_skips_cd_on_failure() {
         if !bash_available() {
             return;
         }
         let tmp = tempfile::tempdir().unwrap();
-        let target = tmp.path().join("workspace");
-        std::fs::create_dir(&target).unwrap();
-
-        for sub in ["version", "status", "shell-setup"] {
-            let pwd = run_posix_wrapper(sub, &target);
-            assert_ne!(
-                pwd,
-                target.to_str().unwrap(),
-                "wrapper must NOT cd after `dwm {sub}`"
-            );
-        }
+        let fake = format!("echo '{}' > \"$DWM_CD_FILE\"; exit 3", tmp.path().display());
+        let out = run_posix_wrapper(tmp.path(), &fake, "dwm switch x; echo \"$?\"\npwd");
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(out, format!("3\n{}\n", cwd.display()));
     }
 
     // --- Shell enum method tests ---
@@ -470,7 +880,7 @@ mod tests {
     #[test]
     fn config_path_fish_default() {
         // Clear XDG_CONFIG_HOME to test default path.
-        let _guard = temp_env::with_var("XDG_CONFIG_HOME", None::<&str>, || {
+        temp_env::with_var("XDG_CONFIG_HOME", None::<&str>, || {
             let path = Shell::Fish.config_path();
             assert!(path.ends_with(".config/fish/config.fish"));
         });
@@ -496,6 +906,28 @@ mod tests {
         assert!(path.ends_with(".bashrc"));
     }
 
+    #[test]
+    fn nushell_setup_saves_in_env_and_sources_in_config() {
+        temp_env::with_var("XDG_CONFIG_HOME", Some("/tmp/xdg-test"), || {
+            let lines = Shell::Nushell.setup_lines();
+            assert_eq!(
+                lines,
+                vec![
+                    (
+                        PathBuf::from("/tmp/xdg-test/nushell/env.nu"),
+                        NUSHELL_SAVE_LINE
+                    ),
+                    (
+                        PathBuf::from("/tmp/xdg-test/nushell/config.nu"),
+                        NUSHELL_SOURCE_LINE
+                    ),
+                ]
+            );
+        });
+        assert!(NUSHELL_SAVE_LINE.starts_with("dwm shell-setup --nushell | save --force"));
+        assert_eq!(Shell::Fish.setup_lines().len(), 1);
+    }
+
     #[test]
     fn setup_line_fish() {
         assert_eq!(Shell::Fish.setup_line(), "dwm shell-setup --fish | source");
@@ -562,6 +994,7 @@ mod tests {
                 ("FISH_VERSION", None),
                 ("ZSH_VERSION", None),
                 ("BASH_VERSION", None),
+                ("NU_VERSION", None),
                 ("SHELL", Some("/usr/bin/zsh")),
             ],
             || {
@@ -570,6 +1003,99 @@ mod tests {
         );
     }
 
+    #[test]
+    fn detect_shell_powershell() {
+        temp_env::with_vars(
+            [
+                ("FISH_VERSION", None),
+                ("ZSH_VERSION", None),
+                ("BASH_VERSION", None),
+                ("NU_VERSION", None),
+                ("XONSH_VERSION", None),
+                ("SHELL", None),
+                ("PSModulePath", Some(r"C:\Program Files\PowerShell\Modules")),
+            ],
+            || {
+                assert_eq!(detect_shell(), Some(Shell::Pwsh));
+            },
+        );
+        temp_env::with_vars(
+            [
+                ("FISH_VERSION", None::<&str>),
+                ("ZSH_VERSION", None),
+                ("BASH_VERSION", None),
+                ("NU_VERSION", None),
+                ("XONSH_VERSION", None),
+                ("SHELL", None),
+                ("PSModulePath", None),
+            ],
+            || {
+                assert_eq!(detect_shell(), None);
+            },
+        );
+    }
+
+    #[test]
+    fn detect_shell_nushell() {
+        temp_env::with_vars(
+            [
+                ("FISH_VERSION", None),
+                ("ZSH_VERSION", None),
+                ("BASH_VERSION", None),
+                ("NU_VERSION", Some("0.98.0")),
+            ],
+            || {
+                assert_eq!(detect_shell(), Some(Shell::Nushell));
+            },
+        );
+        temp_env::with_vars(
+            [
+                ("FISH_VERSION", None),
+                ("ZSH_VERSION", None),
+                ("BASH_VERSION", None),
+                ("NU_VERSION", None),
+                ("SHELL", Some("/opt/homebrew/bin/nu")),
+            ],
+            || {
+                assert_eq!(detect_shell(), Some(Shell::Nushell));
+            },
+        );
+    }
+
+    #[test]
+    fn detect_shell_elvish_and_xonsh() {
+        temp_env::with_vars(
+            [
+                ("FISH_VERSION", None),
+                ("ZSH_VERSION", None),
+                ("BASH_VERSION", None),
+                ("NU_VERSION", None),
+                ("XONSH_VERSION", Some("0.18.3")),
+            ],
+            || {
+                assert_eq!(detect_shell(), Some(Shell::Xonsh));
+            },
+        );
+        for (shell, expected) in [
+            ("/usr/local/bin/elvish", Shell::Elvish),
+            ("/usr/bin/xonsh", Shell::Xonsh),
+        ] {
+            temp_env::with_vars(
+                [
+                    ("FISH_VERSION", None),
+                    ("ZSH_VERSION", None),
+                    ("BASH_VERSION", None),
+                    ("NU_VERSION", None),
+                    ("XONSH_VERSION", None),
+                    ("SHELL", Some(shell)),
+                ],
+                || {
+                    assert_eq!(detect_shell(), Some(expected));
+                },
+            );
+        }
+    }
+
     // --- print_shell_setup tests ---
 
     #[test]
@@ -608,4 +1134,151 @@ mod tests {
     fn function_output_zsh_returns_posix() {
         assert!(Shell::Zsh.function_output().contains("dwm() {"));
     }
+
+    #[test]
+    fn function_output_includes_completions() {
+        assert!(
+            Shell::Bash
+                .function_output()
+                .contains("-F _clap_complete_dwm dwm")
+        );
+        assert!(
+            Shell::Zsh
+                .function_output()
+                .contains("compdef _clap_dynamic_completer_dwm dwm")
+        );
+        assert!(Shell::Fish.function_output().contains("--command dwm"));
+        assert!(!Shell::Pwsh.function_output().contains("DWM_COMPLETE"));
+    }
+
+    #[test]
+    fn remove_setup_takes_out_setup_lines_and_pasted_wrappers() {
+        let rc = format!(
+            "export EDITOR=vim\n{}\nsource <(dwm completions zsh)\nalias g=git\n# dwm shell-setup notes\n",
+            Shell::Zsh.setup_line()
+        );
+        assert_eq!(
+            remove_setup(Shell::Zsh, &rc).as_deref(),
+            Some("export EDITOR=vim\nalias g=git\n# dwm shell-setup notes\n")
+        );
+
+        let pasted = format!(
+            "set -x EDITOR vim\n{}\nfunction dwm\n    command dwm $argv\nend\n",
+            Shell::Fish.function_output()
+        );
+        assert_eq!(
+            remove_setup(Shell::Fish, &pasted).as_deref(),
+            Some("set -x EDITOR vim\n")
+        );
+
+        assert_eq!(remove_setup(Shell::Bash, "alias g=git\n"), None);
+        assert_eq!(
+            remove_setup(Shell::Nushell, &format!("{}\n", NUSHELL_SOURCE_LINE)).as_deref(),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn pasted_wrappers_are_checked_against_the_current_one() {
+        let current = format!(
+            "export EDITOR=vim\n{}\nalias g=git\n",
+            Shell::Bash.function_output()
+        );
+        assert_eq!(
+            find_pasted_wrapper(Shell::Bash, &current),
+            Some(PastedWrapper {
+                lines: 1..current.lines().count() - 1,
+                current: true
+            })
+        );
+
+        let stale = "export EDITOR=vim\n# >>> dwm wrapper 00000000 >>>\ndwm() {\n    command dwm \"$@\"\n}\n# <<< dwm wrapper <<<\nalias g=git\n";
+        let pasted = find_pasted_wrapper(Shell::Bash, stale).unwrap();
+        assert_eq!(pasted.lines, 1..6);
+        assert!(!pasted.current);
+        let refreshed = refresh_wrapper(Shell::Bash, stale, pasted.lines);
+        assert_eq!(refreshed, current);
+
+        // Wrappers pasted before the markers existed are always stale.
+        let legacy = "function dwm\n    command dwm $argv\nend\nset -x EDITOR vim\n";
+        let pasted = find_pasted_wrapper(Shell::Fish, legacy).unwrap();
+        assert_eq!(pasted.lines, 0..3);
+        assert!(!pasted.current);
+        assert_eq!(
+            refresh_wrapper(Shell::Fish, legacy, pasted.lines),
+            format!("{}\nset -x EDITOR vim\n", Shell::Fish.function_output())
+        );
+
+        assert_eq!(
+            find_pasted_wrapper(Shell::Bash, r#"eval "$(dwm shell-setup)""#),
+            None
+        );
+    }
+
+    #[test]
+    fn powershell_function_passes_a_cd_file() {
+        let out = Shell::Pwsh.function_output();
+        assert!(out.contains("\nfunction dwm {\n"));
+        assert!(out.contains("Get-Command -Name dwm -CommandType Application"));
+        assert!(out.contains("$env:DWM_CD_FILE = $cdFile"));
+        assert!(out.contains("Set-Location -LiteralPath $dir"));
+    }
+
+    #[test]
+    fn elvish_function_passes_a_cd_file() {
+        let out = Shell::Elvish.function_output();
+        assert!(out.contains("\nfn dwm {|@args|\n"));
+        assert!(out.contains("tmp E:DWM_CD_FILE = $cd-file"));
+        assert!(out.contains("e:dwm $@args"));
+        assert!(out.contains("e:dwm _on-cd $dir"));
+        assert!(out.contains("edit:add-var dwm~ $dwm~"));
+        assert!(Shell::Elvish.setup_line().contains("shell-setup --elvish"));
+    }
+
+    #[test]
+    fn xonsh_function_passes_a_cd_file() {
+        let out = Shell::Xonsh.function_output();
+        assert!(out.contains("\ndef _dwm(args):\n"));
+        assert!(out.contains("env[\"DWM_CD_FILE\"] = cd_file"));
+        assert!(out.contains("cd @(target)"));
+        assert!(out.contains("aliases[\"dwm\"] = _dwm"));
+        assert!(Shell::Xonsh.setup_line().contains("shell-setup --xonsh"));
+    }
+
+    #[test]
+    fn config_path_elvish_and_xonsh() {
+        temp_env::with_var("XDG_CONFIG_HOME", Some("/tmp/xdg-test"), || {
+            assert_eq!(
+                Shell::Elvish.config_path(),
+                PathBuf::from("/tmp/xdg-test/elvish/rc.elv")
+            );
+        });
+        assert!(Shell::Xonsh.config_path().ends_with(".xonshrc"));
+    }
+
+    #[test]
+    fn config_path_powershell_profile() {
+        if cfg!(windows) {
+            return;
+        }
+        temp_env::with_var("XDG_CONFIG_HOME", Some("/tmp/xdg-test"), || {
+            assert_eq!(
+                Shell::Pwsh.config_path(),
+                PathBuf::from("/tmp/xdg-test/powershell/Microsoft.PowerShell_profile.ps1")
+            );
+        });
+        assert!(
+            Shell::Pwsh
+                .setup_line()
+                .contains("shell-setup --powershell")
+        );
+    }
+
+    #[test]
+    fn nushell_function_passes_a_cd_file() {
+        let out = Shell::Nushell.function_output();
+        assert!(out.contains("def --env --wrapped dwm [...args: string@\"nu-complete dwm\"]"));
+        assert!(out.contains("def \"nu-complete dwm\""));
+        assert!(out.contains("with-env { DWM_CD_FILE: $cd_file } { ^dwm ...$args }"));
+    }
 }
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How serious a status message is, which sets its color and how long it
+/// stays up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    /// How long a message at this level is shown.
+    fn ttl(self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(4),
+            Level::Warn => Duration::from_secs(6),
+            Level::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub level: Level,
+    pub text: String,
+}
+
+/// Most messages kept waiting; older ones are dropped past this.
+const MAX_QUEUED: usize = 4;
+
+/// Status messages for the picker's help bar. The oldest is shown until its
+/// level's time is up, then the next takes its place, so a message isn't lost
+/// to the next keypress or to one posted right after it.
+#[derive(Debug, Default)]
+pub struct StatusQueue {
+    messages: VecDeque<Message>,
+    /// When the front message was first shown, once it has been.
+    shown_at: Option<Instant>,
+}
+
+impl StatusQueue {
+    /// Queue `text` at `level`. Posting a message that is already queued,
+    /// such as a background refresh failing every cycle, only restarts its
+    /// time if it is being shown.
+    pub fn push(&mut self, level: Level, text: impl Into<String>) {
+        let text = text.into();
+        if let Some(pos) = self.messages.iter().position(|m| m.text == text) {
+            if pos == 0 {
+                self.shown_at = None;
+            }
+            return;
+        }
+        self.messages.push_back(Message { level, text });
+        if self.messages.len() > MAX_QUEUED {
+            self.messages.pop_front();
+            self.shown_at = None;
+        }
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(Level::Info, text);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(Level::Error, text);
+    }
+
+    /// Take back a queued message, such as a progress note once the work
+    /// it announced is done.
+    pub fn dismiss(&mut self, text: &str) {
+        if let Some(pos) = self.messages.iter().position(|m| m.text == text) {
+            self.messages.remove(pos);
+            if pos == 0 {
+                self.shown_at = None;
+            }
+        }
+    }
+
+    /// The message to show, if any.
+    pub fn current(&self) -> Option<&Message> {
+        self.messages.front()
+    }
+
+    /// Advance to `now`: drop the shown message once its time is up and
+    /// start the clock on the next one.
+    pub fn expire(&mut self, now: Instant) {
+        let Some(front) = self.messages.front() else {
+            return;
+        };
+        let shown_at = *self.shown_at.get_or_insert(now);
+        if now.duration_since(shown_at) >= front.level.ttl() {
+            self.messages.pop_front();
+            self.shown_at = self.messages.front().map(|_| now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(queue: &StatusQueue) -> Vec<&str> {
+        queue.messages.iter().map(|m| m.text.as_str()).collect()
+    }
+
+    #[test]
+    fn message_stays_until_its_time_is_up() {
+        let start = Instant::now();
+        let mut queue = StatusQueue::default();
+        queue.info("copied");
+        queue.expire(start);
+        queue.expire(start + Duration::from_secs(3));
+        assert_eq!(queue.current().unwrap().text, "copied");
+        queue.expire(start + Duration::from_secs(4));
+        assert_eq!(queue.current(), None);
+    }
+
+    #[test]
+    fn errors_stay_longer_than_info() {
+        let start = Instant::now();
+        let mut queue = StatusQueue::default();
+        queue.error("refresh failed");
+        queue.expire(start);
+        queue.expire(start + Duration::from_secs(6));
+        assert_eq!(queue.current().unwrap().level, Level::Error);
+        queue.expire(start + Duration::from_secs(8));
+        assert_eq!(queue.current(), None);
+    }
+
+    #[test]
+    fn dismissed_message_makes_way_for_the_next() {
+        let start = Instant::now();
+        let mut queue = StatusQueue::default();
+        queue.info("pushing feat…");
+        queue.expire(start);
+        queue.info("pushed feat");
+        queue.dismiss("pushing feat…");
+        assert_eq!(queue.current().unwrap().text, "pushed feat");
+        queue.expire(start + Duration::from_secs(3));
+        assert!(queue.current().is_some());
+    }
+
+    #[test]
+    fn messages_are_shown_in_turn() {
+        let start = Instant::now();
+        let mut queue = StatusQueue::default();
+        queue.info("first");
+        queue.expire(start);
+        queue.push(Level::Warn, "second");
+        assert_eq!(queue.current().unwrap().text, "first");
+        queue.expire(start + Duration::from_secs(4));
+        assert_eq!(queue.current().unwrap().text, "second");
+        // The second message gets its own full time from when it appeared.
+        queue.expire(start + Duration::from_secs(9));
+        assert_eq!(queue.current().unwrap().text, "second");
+        queue.expire(start + Duration::from_secs(10));
+        assert_eq!(queue.current(), None);
+    }
+
+    #[test]
+    fn repeated_message_is_not_queued_twice() {
+        let start = Instant::now();
+        let mut queue = StatusQueue::default();
+        queue.error("refresh failed");
+        queue.expire(start);
+        queue.expire(start + Duration::from_secs(7));
+        queue.error("refresh failed");
+        assert_eq!(texts(&queue), vec!["refresh failed"]);
+        // Re-posting restarted the clock.
+        queue.expire(start + Duration::from_secs(10));
+        queue.expire(start + Duration::from_secs(17));
+        assert_eq!(queue.current().unwrap().text, "refresh failed");
+    }
+
+    #[test]
+    fn oldest_messages_are_dropped_past_the_limit() {
+        let mut queue = StatusQueue::default();
+        for i in 0..6 {
+            queue.info(format!("m{}", i));
+        }
+        assert_eq!(texts(&queue), vec!["m2", "m3", "m4", "m5"]);
+    }
+}
@@ -0,0 +1,192 @@
+use anyhow::{Result, bail};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Colors used by the picker UI. Each field names a role rather than a hue so
+/// palettes can be swapped wholesale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Regular text: descriptions, preview contents, popup headings.
+    pub text: Color,
+    /// De-emphasized text: help bar, stale workspaces, idle agents.
+    pub muted: Color,
+    /// Background of the selected row.
+    pub highlight: Color,
+    /// Background of the table header row.
+    pub header: Color,
+    /// Background of rows whose agents are waiting for input.
+    pub attention: Color,
+    /// Repo names, the create row, status messages, insertions, busy agents.
+    pub accent: Color,
+    /// Workspace names and key labels in the help popup.
+    pub name: Color,
+    /// Change IDs.
+    pub change: Color,
+    /// Bookmark names.
+    pub bookmark: Color,
+    /// Last-modified times.
+    pub modified: Color,
+    /// Agents waiting for input.
+    pub warning: Color,
+    /// Diffs that remove more than they add.
+    pub danger: Color,
+}
+
+impl Theme {
+    /// The default palette, for dark terminal backgrounds.
+    pub const DARK: Theme = Theme {
+        text: Color::White,
+        muted: Color::DarkGray,
+        highlight: Color::Rgb(40, 40, 60),
+        header: Color::DarkGray,
+        attention: Color::Rgb(60, 45, 20),
+        accent: Color::Green,
+        name: Color::Cyan,
+        change: Color::Magenta,
+        bookmark: Color::Blue,
+        modified: Color::Yellow,
+        warning: Color::Yellow,
+        danger: Color::Red,
+    };
+
+    /// A palette for light terminal backgrounds.
+    pub const LIGHT: Theme = Theme {
+        text: Color::Black,
+        muted: Color::Rgb(120, 120, 120),
+        highlight: Color::Rgb(210, 220, 240),
+        header: Color::Rgb(200, 200, 200),
+        attention: Color::Rgb(250, 235, 200),
+        accent: Color::Rgb(0, 120, 0),
+        name: Color::Rgb(0, 100, 160),
+        change: Color::Rgb(140, 0, 140),
+        bookmark: Color::Blue,
+        modified: Color::Rgb(140, 100, 0),
+        warning: Color::Rgb(180, 100, 0),
+        danger: Color::Rgb(180, 0, 0),
+    };
+
+    /// Look up a built-in theme by name (`"dark"` or `"light"`).
+    pub fn named(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Self::DARK),
+            "light" => Some(Self::LIGHT),
+            _ => None,
+        }
+    }
+
+    /// The field for the role called `role` in the config file.
+    fn slot(&mut self, role: &str) -> Option<&mut Color> {
+        Some(match role {
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "highlight" => &mut self.highlight,
+            "header" => &mut self.header,
+            "attention" => &mut self.attention,
+            "accent" => &mut self.accent,
+            "name" => &mut self.name,
+            "change" => &mut self.change,
+            "bookmark" => &mut self.bookmark,
+            "modified" => &mut self.modified,
+            "warning" => &mut self.warning,
+            "danger" => &mut self.danger,
+            _ => return None,
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::DARK
+    }
+}
+
+/// The `theme` config value: a built-in theme name, or a palette that starts
+/// from a built-in `base` (dark by default) and overrides individual roles.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ThemeSpec {
+    Named(String),
+    Custom {
+        #[serde(default)]
+        base: Option<String>,
+        #[serde(flatten)]
+        colors: BTreeMap<String, String>,
+    },
+}
+
+impl ThemeSpec {
+    /// Resolve to a concrete [`Theme`]. Colors are names (`"red"`,
+    /// `"light-blue"`), 256-color indices (`"208"`), or hex (`"#rrggbb"`).
+    pub fn resolve(&self) -> Result<Theme> {
+        let (base, colors) = match self {
+            ThemeSpec::Named(name) => (Some(name), None),
+            ThemeSpec::Custom { base, colors } => (base.as_ref(), Some(colors)),
+        };
+        let mut theme = match base {
+            Some(name) => match Theme::named(name) {
+                Some(theme) => theme,
+                None => bail!(
+                    "unknown theme \"{}\" (expected \"dark\" or \"light\")",
+                    name
+                ),
+            },
+            None => Theme::default(),
+        };
+        for (role, value) in colors.into_iter().flatten() {
+            let Some(slot) = theme.slot(role) else {
+                bail!("unknown theme color \"{}\"", role);
+            };
+            *slot = match Color::from_str(value) {
+                Ok(color) => color,
+                Err(_) => bail!("invalid color \"{}\" for \"{}\"", value, role),
+            };
+        }
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ThemeSpec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn named_themes_resolve() {
+        assert_eq!(parse(r#""dark""#).resolve().unwrap(), Theme::DARK);
+        assert_eq!(parse(r#""light""#).resolve().unwrap(), Theme::LIGHT);
+    }
+
+    #[test]
+    fn unknown_theme_name_fails() {
+        let err = parse(r#""solarized""#).resolve().unwrap_err();
+        assert!(err.to_string().contains("solarized"));
+    }
+
+    #[test]
+    fn custom_palette_overrides_base() {
+        let theme = parse(r##"{"base": "light", "highlight": "#102030", "name": "208"}"##)
+            .resolve()
+            .unwrap();
+        assert_eq!(theme.highlight, Color::Rgb(16, 32, 48));
+        assert_eq!(theme.name, Color::Indexed(208));
+        assert_eq!(theme.text, Theme::LIGHT.text);
+    }
+
+    #[test]
+    fn custom_palette_defaults_to_dark() {
+        let theme = parse(r#"{"accent": "light-blue"}"#).resolve().unwrap();
+        assert_eq!(theme.accent, Color::LightBlue);
+        assert_eq!(theme.muted, Theme::DARK.muted);
+    }
+
+    #[test]
+    fn unknown_role_and_bad_color_fail() {
+        assert!(parse(r#"{"sparkle": "red"}"#).resolve().is_err());
+        assert!(parse(r#"{"accent": "not-a-color"}"#).resolve().is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+use std::process::Command;
+
+/// Whether dwm is running inside a tmux client.
+pub fn inside() -> bool {
+    std::env::var_os("TMUX").is_some_and(|v| !v.is_empty())
+}
+
+/// Whether `switch`, `new`, and the picker should open workspaces in tmux
+/// windows rather than `cd`: inside tmux with the `tmux_windows` setting on.
+pub fn auto() -> bool {
+    inside() && crate::config::Config::load().is_ok_and(|c| c.tmux_windows)
+}
+
+/// The window or session name for the workspace at `path`: its directory
+/// name, with the `.` and `:` tmux reads in targets swapped for `_`.
+fn window_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+        .replace(['.', ':'], "_")
+}
+
+/// The id of the window called `name` in `list-windows` output of
+/// `#{window_id}\t#{window_name}` lines.
+fn find_window<'a>(listing: &'a str, name: &str) -> Option<&'a str> {
+    listing
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .find(|(_, window)| *window == name)
+        .map(|(id, _)| id)
+}
+
+/// Open the workspace at `path` in tmux. Inside tmux, focus the current
+/// session's window named after the workspace, or create one rooted at
+/// `path`. Outside, attach to the session of that name, creating it first.
+pub fn open(path: &Path) -> Result<()> {
+    let name = window_name(path);
+    let dir = path.to_string_lossy();
+    if !inside() {
+        return tmux(&["new-session", "-A", "-s", &name, "-c", &dir]);
+    }
+    let output = Command::new("tmux")
+        .args(["list-windows", "-F", "#{window_id}\t#{window_name}"])
+        .output()
+        .context("could not run tmux")?;
+    let listing = String::from_utf8_lossy(&output.stdout);
+    match find_window(&listing, &name) {
+        Some(id) => tmux(&["select-window", "-t", id]),
+        None => tmux(&["new-window", "-n", &name, "-c", &dir]),
+    }
+}
+
+/// Run `tmux args`, failing if it does.
+fn tmux(args: &[&str]) -> Result<()> {
+    let status = Command::new("tmux")
+        .args(args)
+        .status()
+        .context("could not run tmux")?;
+    if !status.success() {
+        bail!("tmux {} failed", args[0]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_are_named_after_the_workspace_directory() {
+        assert_eq!(
+            window_name(Path::new("/home/me/.dwm/api-1a2b3c4d/fix-login")),
+            "fix-login"
+        );
+        assert_eq!(window_name(Path::new("/src/v1.2:rc")), "v1_2_rc");
+    }
+
+    #[test]
+    fn find_window_matches_whole_names() {
+        let listing = "@1\tzsh\n@4\tfix-login\n@7\tfix\n";
+        assert_eq!(find_window(listing, "fix"), Some("@7"));
+        assert_eq!(find_window(listing, "fix-login"), Some("@4"));
+        assert_eq!(find_window(listing, "fix-log"), None);
+    }
+}
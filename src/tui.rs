@@ -1,23 +1,42 @@
 use anyhow::Result;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{Frame, prelude::*, widgets::*};
-use std::collections::HashMap;
-use std::io;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
-
-use crate::agent::AgentSummary;
-use crate::workspace::{WorkspaceEntry, format_time_ago};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{self, AgentListing, AgentSession, AgentStatus, AgentSummary};
+use crate::ansi;
+use crate::columns::{self, Column, ColumnSpec};
+use crate::config::Config;
+use crate::filter::{self, Query};
+use crate::forge::{Checks, Shown};
+use crate::keymap::{Action, KeyMap};
+use crate::names;
+use crate::status::{Level, Message, StatusQueue};
+use crate::theme::Theme;
+use crate::watch::{DirWatcher, Waker};
+use crate::workspace::{WorkspaceEntry, dirty_marker, format_time_ago};
+use crate::{progress, width};
 
 /// Shared stop signal that can wake sleeping threads immediately.
 struct StopSignal {
     flag: AtomicBool,
     condvar: Condvar,
     mutex: Mutex<()>,
+    /// Watchers to wake when stopped, so threads blocked on them exit.
+    wakers: Mutex<Vec<Waker>>,
 }
 
 impl StopSignal {
@@ -26,12 +45,21 @@ impl StopSignal {
             flag: AtomicBool::new(false),
             condvar: Condvar::new(),
             mutex: Mutex::new(()),
+            wakers: Mutex::new(Vec::new()),
         }
     }
 
     fn stop(&self) {
         self.flag.store(true, Ordering::Relaxed);
         self.condvar.notify_all();
+        for waker in self.wakers.lock().unwrap().iter() {
+            waker.wake();
+        }
+    }
+
+    /// Wake `waker` too when stopped.
+    fn add_waker(&self, waker: Waker) {
+        self.wakers.lock().unwrap().push(waker);
     }
 
     fn is_stopped(&self) -> bool {
@@ -67,6 +95,53 @@ fn spawn_refresh_thread<T: Send + 'static>(
     })
 }
 
+/// How often a refresh thread watching for changes polls anyway, to notice
+/// sessions going stale and repos it isn't watching.
+const WATCHED_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Like [`spawn_refresh_thread`], but calls `produce` again as soon as a
+/// file in one of `dirs` changes, and otherwise only every
+/// [`WATCHED_POLL_INTERVAL`]. Polls every `interval` instead where the
+/// directories can't be watched.
+fn spawn_watched_refresh_thread<T: Send + 'static>(
+    dirs: &[PathBuf],
+    interval: std::time::Duration,
+    stop: Arc<StopSignal>,
+    sender: Arc<Mutex<Option<T>>>,
+    mut produce: impl FnMut() -> Option<T> + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    let Some(watcher) = DirWatcher::new(dirs) else {
+        return spawn_refresh_thread(interval, stop, sender, produce);
+    };
+    stop.add_waker(watcher.waker());
+    std::thread::spawn(move || {
+        loop {
+            if stop.is_stopped() {
+                break;
+            }
+            if let Some(value) = produce() {
+                let _ = sender.lock().map(|mut m| *m = Some(value));
+            }
+            watcher.wait(WATCHED_POLL_INTERVAL);
+        }
+    })
+}
+
+/// Adapt a fallible `produce` for [`spawn_refresh_thread`], posting each
+/// error to `errors` rather than dropping it.
+fn posting_errors<T>(
+    errors: Arc<Mutex<Option<String>>>,
+    mut produce: impl FnMut() -> Result<T>,
+) -> impl FnMut() -> Option<T> {
+    move || match produce() {
+        Ok(value) => Some(value),
+        Err(e) => {
+            let _ = errors.lock().map(|mut m| *m = Some(format!("{:#}", e)));
+            None
+        }
+    }
+}
+
 /// Thread-safe single-slot mailbox for passing data from background threads.
 struct Mailbox<T>(Arc<Mutex<Option<T>>>);
 
@@ -88,28 +163,84 @@ impl<T> Mailbox<T> {
 enum PreviewState {
     Hidden,
     Loading,
-    Ready { log: String, diff_stat: String },
+    /// `graph` holds the backend's colored log graph (ANSI escapes intact);
+    /// when it is empty the plain `log` is shown instead. `agents` sums up
+    /// the time agents spent in each status, empty without any history, and
+    /// `touched` lists the files they touched last. `issue` is the issue the
+    /// workspace was made for, if any.
+    Ready {
+        log: String,
+        graph: String,
+        diff_stat: String,
+        agents: String,
+        touched: String,
+        issue: Option<crate::forge::Issue>,
+    },
 }
 
+/// Most touched files the preview pane lists.
+const PREVIEW_TOUCHED: usize = 3;
+
 fn fetch_preview(
     main_repo_path: PathBuf,
     worktree_dir: PathBuf,
     ws_name: String,
     vcs_type: crate::vcs::VcsType,
+    agent_repo_dir: Option<PathBuf>,
     mailbox: Arc<Mutex<Option<PreviewState>>>,
 ) {
     std::thread::spawn(move || {
         let backend = vcs_type.to_backend();
 
-        let log = backend.preview_log(&main_repo_path, &worktree_dir, &ws_name, 10);
+        let graph = backend.preview_graph(&main_repo_path, &worktree_dir, &ws_name, 10);
+        let log = if graph.is_empty() {
+            backend.preview_log(&main_repo_path, &worktree_dir, &ws_name, 10)
+        } else {
+            String::new()
+        };
         let diff_stat = backend.preview_diff_stat(&main_repo_path, &worktree_dir, &ws_name);
-
-        let _ = mailbox
-            .lock()
-            .map(|mut m| *m = Some(PreviewState::Ready { log, diff_stat }));
+        let (agents, touched) = agent_repo_dir
+            .as_ref()
+            .map(|dir| {
+                let times = agent::workspace_status_times(dir, &ws_name).to_string();
+                let mut touched = agent::workspace_touched(dir, &ws_name);
+                touched.truncate(PREVIEW_TOUCHED);
+                (times, touched.join(", "))
+            })
+            .unwrap_or_default();
+        let issue = agent_repo_dir
+            .as_ref()
+            .and_then(|dir| crate::meta::WorkspaceMeta::load(dir, &ws_name).ok())
+            .and_then(|meta| meta.issue);
+
+        let _ = mailbox.lock().map(|mut m| {
+            *m = Some(PreviewState::Ready {
+                log,
+                graph,
+                diff_stat,
+                agents,
+                touched,
+                issue,
+            })
+        });
     });
 }
 
+/// Leave raw mode and the alternate screen, run `f` with a normal terminal,
+/// then restore the TUI. Callers must clear the [`Terminal`] afterwards so the
+/// next draw repaints everything.
+fn with_suspended_terminal<T>(mouse: bool, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    disable_raw_mode()?;
+    crossterm::execute!(io::stderr(), DisableMouseCapture, LeaveAlternateScreen)?;
+    let result = f();
+    crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
+    if mouse {
+        crossterm::execute!(io::stderr(), EnableMouseCapture)?;
+    }
+    enable_raw_mode()?;
+    result
+}
+
 /// The action chosen by the user in the interactive workspace picker.
 #[derive(Debug)]
 pub enum PickerResult {
@@ -117,23 +248,253 @@ pub enum PickerResult {
     Selected(String),
     /// User wants to create a new workspace with an optional explicit name.
     CreateNew(Option<String>),
+    /// User wants to create a new workspace in the repo at `repo` (its main
+    /// workspace path), with an optional explicit name. Only produced by the
+    /// `--all` picker.
+    CreateNewIn { repo: PathBuf, name: Option<String> },
+    /// User wants to start the default agent in the workspace at this path.
+    Launch(String),
+    /// User wants the workspace at this path in its own tmux window.
+    Tmux(String),
+    /// User wants the workspace at this path pushed and its pull request
+    /// opened.
+    Pr(String),
+}
+
+/// User-configurable picker settings, derived from [`Config`].
+#[derive(Debug, Clone, Default)]
+pub struct PickerSettings {
+    pub keymap: KeyMap,
+    /// Configured table columns; `None` shows the defaults.
+    pub columns: Option<Vec<ColumnSpec>>,
+    /// Whether to capture the mouse for clicking and scrolling.
+    pub mouse: bool,
+    pub theme: Theme,
+}
+
+impl PickerSettings {
+    /// Build picker settings from the user config, validating key bindings
+    /// and the theme.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        // PR and CI state are only fetched with their settings on, so only
+        // then do the default columns make room for them.
+        let shown = Shown::from_config(config);
+        Ok(Self {
+            keymap: config.keymap()?,
+            columns: match &config.columns {
+                None if shown.any() => Some(columns::defaults_with_forge(shown)),
+                columns => columns.clone(),
+            },
+            mouse: config.mouse,
+            theme: config.theme()?,
+        })
+    }
+}
+
+/// File in `~/.dwm/<repo>/` holding the repo's remembered [`UiState`].
+const UI_STATE_FILE: &str = ".ui-state.json";
+
+/// Picker state remembered per repo between launches of the single-repo
+/// picker.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+struct UiState {
+    sort: Option<SortMode>,
+    filter: String,
+    preview: bool,
+    preview_layout: PreviewLayout,
+}
+
+impl UiState {
+    /// Load the state saved in `repo_dir`. A missing or unreadable file
+    /// yields the defaults.
+    fn load(repo_dir: &Path) -> Self {
+        std::fs::read_to_string(repo_dir.join(UI_STATE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the state to `repo_dir`, replacing the file atomically.
+    fn save(&self, repo_dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(repo_dir)?;
+        let json = serde_json::to_string(self)?;
+        let tmp_path = repo_dir.join(format!(".tmp{}", UI_STATE_FILE));
+        std::fs::write(&tmp_path, json)?;
+        std::fs::rename(&tmp_path, repo_dir.join(UI_STATE_FILE))?;
+        Ok(())
+    }
+}
+
+/// Where the preview pane sits relative to the table and how much room it
+/// takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+struct PreviewLayout {
+    /// Share of the picker given to the preview, in percent.
+    percent: u16,
+    /// Whether the preview sits below the table rather than beside it.
+    stacked: bool,
+}
+
+impl Default for PreviewLayout {
+    fn default() -> Self {
+        Self {
+            percent: 45,
+            stacked: false,
+        }
+    }
+}
+
+impl PreviewLayout {
+    /// Percentage points added or removed per grow or shrink.
+    const STEP: u16 = 5;
+    const MIN_PERCENT: u16 = 20;
+    const MAX_PERCENT: u16 = 80;
+
+    /// `percent` kept within bounds, since the state file may be edited.
+    fn clamped_percent(self) -> u16 {
+        self.percent.clamp(Self::MIN_PERCENT, Self::MAX_PERCENT)
+    }
+
+    fn grow(&mut self) {
+        self.percent = (self.clamped_percent() + Self::STEP).min(Self::MAX_PERCENT);
+    }
+
+    fn shrink(&mut self) {
+        self.percent = (self.clamped_percent() - Self::STEP).max(Self::MIN_PERCENT);
+    }
+
+    /// Split `area` into the table and preview areas.
+    fn split(self, area: Rect) -> (Rect, Rect) {
+        let percent = self.clamped_percent();
+        let constraints = [
+            Constraint::Percentage(100 - percent),
+            Constraint::Percentage(percent),
+        ];
+        let layout = if self.stacked {
+            Layout::vertical(constraints)
+        } else {
+            Layout::horizontal(constraints)
+        };
+        let chunks = layout.split(area);
+        (chunks[0], chunks[1])
+    }
+}
+
+/// Maximum gap between two clicks on the same row for a double-click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// What a mouse event landed on in the workspace table.
+#[derive(Debug, PartialEq)]
+enum MouseTarget {
+    /// A table row, as an index into the visible rows.
+    Row(usize),
+    /// A column header.
+    Header(Column),
+    ScrollUp,
+    ScrollDown,
+}
+
+/// Mouse hit-testing state, shared by both pickers.
+#[derive(Debug, Default)]
+struct MouseState {
+    /// Area the table was last rendered into.
+    table_area: Rect,
+    /// Time and row of the last left click, for double-click detection.
+    last_click: Option<(Instant, usize)>,
+}
+
+impl MouseState {
+    /// Work out what `event` refers to, given the table's `columns` and
+    /// current scroll `offset`. Returns `None` for events outside the table
+    /// and for anything other than left clicks and scrolling.
+    fn target(
+        &self,
+        event: &MouseEvent,
+        columns: &[ColumnSpec],
+        offset: usize,
+    ) -> Option<MouseTarget> {
+        match event.kind {
+            MouseEventKind::ScrollDown => return Some(MouseTarget::ScrollDown),
+            MouseEventKind::ScrollUp => return Some(MouseTarget::ScrollUp),
+            MouseEventKind::Down(MouseButton::Left) => {}
+            _ => return None,
+        }
+        let inner = self.table_area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(event.column, event.row)) {
+            return None;
+        }
+        if event.row == inner.y {
+            let widths: Vec<Constraint> = columns.iter().map(|c| c.constraint()).collect();
+            let cells = Layout::horizontal(widths).spacing(1).split(inner);
+            return columns
+                .iter()
+                .zip(cells.iter())
+                .find(|(_, cell)| event.column >= cell.x && event.column < cell.right())
+                .map(|(spec, _)| MouseTarget::Header(spec.column()));
+        }
+        Some(MouseTarget::Row(
+            offset + (event.row - inner.y - 1) as usize,
+        ))
+    }
+
+    /// Half the number of rows visible in the table, for half-page motions.
+    fn half_page(&self) -> usize {
+        // Borders and the header row take three lines.
+        (self.table_area.height.saturating_sub(3) / 2) as usize
+    }
+
+    /// Record a left click on `row`. Returns `true` if it completes a
+    /// double-click on that row.
+    fn register_click(&mut self, row: usize) -> bool {
+        let now = Instant::now();
+        let double = matches!(
+            self.last_click,
+            Some((at, prev)) if prev == row && now.duration_since(at) < DOUBLE_CLICK_INTERVAL
+        );
+        self.last_click = if double { None } else { Some((now, row)) };
+        double
+    }
 }
 
 /// Column by which the workspace table is sorted.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
 enum SortMode {
     Recency,
     Name,
     DiffSize,
+    /// Workspaces whose agents have waited longest for input first.
+    Waiting,
 }
 
 impl SortMode {
+    /// Every sort mode, in cycle order.
+    const ALL: [SortMode; 4] = [
+        SortMode::Recency,
+        SortMode::Name,
+        SortMode::DiffSize,
+        SortMode::Waiting,
+    ];
+
     /// Cycle to the next sort mode.
     fn next(self) -> Self {
         match self {
             SortMode::Recency => SortMode::Name,
             SortMode::Name => SortMode::DiffSize,
-            SortMode::DiffSize => SortMode::Recency,
+            SortMode::DiffSize => SortMode::Waiting,
+            SortMode::Waiting => SortMode::Recency,
+        }
+    }
+
+    /// Sort mode selected by clicking `column`'s header, if it has one.
+    fn for_column(column: Column) -> Option<Self> {
+        match column {
+            Column::Name => Some(SortMode::Name),
+            Column::Modified => Some(SortMode::Recency),
+            Column::Changes => Some(SortMode::DiffSize),
+            _ => None,
         }
     }
 
@@ -143,27 +504,33 @@ impl SortMode {
             SortMode::Recency => "recency",
             SortMode::Name => "name",
             SortMode::DiffSize => "diff size",
+            SortMode::Waiting => "waiting",
         }
     }
 }
 
-/// Return `true` if `entry` matches the filter `query` (case-insensitive).
-/// Matches against workspace name, description, and bookmark names.
-fn matches_filter(entry: &WorkspaceEntry, query: &str) -> bool {
-    let query = query.to_lowercase();
-    entry.name.to_lowercase().contains(&query)
-        || entry.description.to_lowercase().contains(&query)
-        || entry
-            .bookmarks
-            .iter()
-            .any(|b| b.to_lowercase().contains(&query))
+/// Indices of `entries` matching the filter `input` (see [`Query`]), best
+/// match first. Ties keep the current sort order; an empty filter keeps every
+/// entry in order.
+fn filtered_indices(entries: &[WorkspaceEntry], input: &str) -> Vec<usize> {
+    let query = Query::parse(input);
+    if query.is_empty() {
+        return (0..entries.len()).collect();
+    }
+    let mut scored: Vec<(usize, i64)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| query.score(e).map(|score| (i, score)))
+        .collect();
+    scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(i, _)| i).collect()
 }
 
 /// Sort `entries` in-place according to `mode`.
 fn sort_entries(entries: &mut [WorkspaceEntry], mode: SortMode) {
     match mode {
         SortMode::Name => {
-            entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+            entries.sort_by_key(|e| e.name.to_lowercase());
         }
         SortMode::Recency => {
             entries.sort_by(|a, b| {
@@ -183,9 +550,38 @@ fn sort_entries(entries: &mut [WorkspaceEntry], mode: SortMode) {
                 b_total.cmp(&a_total)
             });
         }
+        SortMode::Waiting => {
+            // Longest waiting first; the rest stay in recency order.
+            sort_entries(entries, SortMode::Recency);
+            entries.sort_by_key(
+                |e| match e.agent_status.as_ref().and_then(|s| s.waiting_since) {
+                    Some(since) => (0, Some(since)),
+                    None => (1, None),
+                },
+            );
+        }
     }
 }
 
+/// Why `name` can't name a new workspace of the repo whose workspaces are
+/// `entries`, if it can't: see [`names::validate`], and it can't be the
+/// main workspace's name.
+fn invalid_name(entries: &[WorkspaceEntry], name: &str) -> Option<String> {
+    let vcs_type = entries.first()?.vcs_type;
+    if let Err(e) = names::validate(name, vcs_type) {
+        return Some(e.to_string());
+    }
+    entries
+        .iter()
+        .any(|e| e.is_main && e.name == name)
+        .then(|| {
+            format!(
+                "'{}' is reserved for the main workspace in {} repos; choose another name",
+                name, vcs_type
+            )
+        })
+}
+
 /// Current interaction mode of the single-repo picker.
 #[derive(Debug, PartialEq)]
 enum Mode {
@@ -197,6 +593,10 @@ enum Mode {
     Filter,
     /// Waiting for y/n confirmation before deleting the named workspace.
     ConfirmDelete(String),
+    /// User is typing a new name for the named workspace.
+    Rename(String),
+    /// Reviewing stale workspaces to delete in bulk.
+    Cleanup(Cleanup),
 }
 
 /// State for the single-repo interactive picker.
@@ -205,7 +605,7 @@ struct App {
     /// Index into [`filtered_indices`] (not into `entries` directly).
     selected: usize,
     mode: Mode,
-    /// Buffer for the new-workspace name being typed.
+    /// Buffer for the new-workspace name (or rename target) being typed.
     input_buf: String,
     sort_mode: SortMode,
     /// Live filter string.
@@ -213,13 +613,39 @@ struct App {
     /// Indices into `entries` that survive the current filter.
     filtered_indices: Vec<usize>,
     show_preview: bool,
+    preview_layout: PreviewLayout,
     preview: PreviewState,
     preview_mailbox: Arc<Mutex<Option<PreviewState>>>,
+    /// Whether a fetch into `preview_mailbox` has not yet been drained, so
+    /// periodic refreshes don't pile up behind a slow backend.
+    preview_pending: bool,
     table_state: TableState,
-    /// Transient status message shown in the help bar (e.g. after deletion).
-    status_message: Option<String>,
+    /// Status messages shown in the help bar (e.g. after deletion).
+    status: StatusQueue,
+    /// Effective key bindings for browse mode.
+    keymap: KeyMap,
+    /// The repo's `~/.dwm/<repo>` directory, where agent status files live.
+    repo_dir: Option<PathBuf>,
+    /// Whether the `?` help popup is open.
+    show_help: bool,
+    /// The open action menu, if any.
+    menu: Option<ActionMenu>,
+    /// The open agent session popup, if any.
+    agents: Option<AgentPopup>,
+    motion: Motion,
+    /// Table columns in display order.
+    columns: Vec<ColumnSpec>,
+    theme: Theme,
+    mouse: MouseState,
+    /// Whether `entries` is still the quick name-only listing, before the
+    /// first full refresh has arrived.
+    loading: bool,
+    /// Advances every frame while `loading` to animate the spinner.
+    spinner_tick: usize,
     /// Receives full workspace entry refreshes from background thread.
     refresh_mailbox: Mailbox<Vec<WorkspaceEntry>>,
+    /// Receives the error from a failed background refresh.
+    refresh_error_mailbox: Mailbox<String>,
     /// Receives agent status updates from background thread.
     agent_refresh_mailbox: Mailbox<HashMap<String, AgentSummary>>,
 }
@@ -240,11 +666,25 @@ impl App {
             filter_buf: String::new(),
             filtered_indices,
             show_preview: false,
+            preview_layout: PreviewLayout::default(),
             preview: PreviewState::Hidden,
             preview_mailbox: Arc::new(Mutex::new(None)),
+            preview_pending: false,
             table_state: TableState::default().with_selected(0),
-            status_message: None,
+            status: StatusQueue::default(),
+            keymap: KeyMap::default(),
+            repo_dir: None,
+            show_help: false,
+            menu: None,
+            agents: None,
+            motion: Motion::default(),
+            columns: columns::resolve(None, false),
+            theme: Theme::default(),
+            mouse: MouseState::default(),
+            loading: false,
+            spinner_tick: 0,
             refresh_mailbox: Mailbox::new(),
+            refresh_error_mailbox: Mailbox::new(),
             agent_refresh_mailbox: Mailbox::new(),
         }
     }
@@ -262,6 +702,11 @@ impl App {
         self.filtered_indices.len() + 1 // +1 for "Create new" row
     }
 
+    /// Why `name` can't name a workspace of this repo, if it can't.
+    fn invalid_name(&self, name: &str) -> Option<String> {
+        invalid_name(&self.entries, name)
+    }
+
     /// Return `true` when the cursor is on the "+ Create new" row.
     fn on_create_row(&self) -> bool {
         self.selected == self.filtered_indices.len()
@@ -291,37 +736,128 @@ impl App {
         self.sync_table_state();
     }
 
+    /// Move the cursor for a navigation `action`, repeated `count` times.
+    fn apply_motion(&mut self, action: Action, count: Option<usize>) {
+        let half_page = self.mouse.half_page();
+        self.selected = motion_target(action, count, self.selected, self.total_rows(), half_page);
+        self.sync_table_state();
+    }
+
+    /// Move the cursor to the next visible workspace, after the current row
+    /// and wrapping around, whose agents are waiting for input. Returns
+    /// `false` if there is none.
+    fn next_waiting(&mut self) -> bool {
+        let total = self.total_rows();
+        let found = (1..=total)
+            .map(|step| (self.selected + step) % total)
+            .find(|&row| {
+                self.filtered_indices
+                    .get(row)
+                    .is_some_and(|&i| has_waiting_agent(&self.entries[i]))
+            });
+        if let Some(row) = found {
+            self.selected = row;
+            self.sync_table_state();
+        }
+        found.is_some()
+    }
+
     /// Keep `table_state` selection in sync with `selected`.
     fn sync_table_state(&mut self) {
         self.table_state.select(Some(self.selected));
     }
 
+    /// Switch to `mode`, re-sort, and move the cursor to the top.
+    fn set_sort_mode(&mut self, mode: SortMode) {
+        self.sort_mode = mode;
+        sort_entries(&mut self.entries, self.sort_mode);
+        self.recompute_filter();
+        self.selected = 0;
+        self.sync_table_state();
+    }
+
+    /// The result of activating the current row: create on the sentinel row,
+    /// otherwise select the workspace.
+    fn activate(&self) -> Option<PickerResult> {
+        if self.on_create_row() {
+            return Some(PickerResult::CreateNew(None));
+        }
+        self.selected_entry_index()
+            .map(|idx| PickerResult::Selected(self.entries[idx].path.to_string_lossy().to_string()))
+    }
+
+    /// Handle a mouse event in browse mode. Returns a result when a
+    /// double-click activates a row.
+    fn handle_mouse(&mut self, event: &MouseEvent) -> Option<PickerResult> {
+        match self
+            .mouse
+            .target(event, &self.columns, self.table_state.offset())?
+        {
+            MouseTarget::ScrollDown if self.selected + 1 < self.total_rows() => self.next(),
+            MouseTarget::ScrollUp if self.selected > 0 => self.previous(),
+            MouseTarget::Header(column) => {
+                if let Some(mode) = SortMode::for_column(column) {
+                    self.set_sort_mode(mode);
+                }
+            }
+            MouseTarget::Row(row) if row < self.total_rows() => {
+                self.selected = row;
+                self.sync_table_state();
+                if self.mouse.register_click(row) {
+                    return self.activate();
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
     fn trigger_preview_fetch(&mut self) {
         if !self.show_preview {
             return;
         }
         if let Some(idx) = self.selected_entry_index() {
-            let entry = &self.entries[idx];
             self.preview = PreviewState::Loading;
-            let mailbox = Arc::new(Mutex::new(None));
-            self.preview_mailbox = Arc::clone(&mailbox);
-            fetch_preview(
-                entry.main_repo_path.clone(),
-                entry.path.clone(),
-                entry.name.clone(),
-                entry.vcs_type,
-                mailbox,
-            );
+            self.start_preview_fetch(idx);
         } else {
             self.preview = PreviewState::Hidden;
         }
     }
 
+    /// Re-fetch the visible preview in place, keeping the current contents
+    /// on screen until the new ones arrive. Skipped while a fetch is pending.
+    fn refresh_preview(&mut self) {
+        if !self.show_preview || self.preview_pending {
+            return;
+        }
+        if let Some(idx) = self.selected_entry_index() {
+            self.start_preview_fetch(idx);
+        }
+    }
+
+    /// Fetch the preview for `entries[idx]` in the background, replacing the
+    /// mailbox so results for a previous selection are discarded.
+    fn start_preview_fetch(&mut self, idx: usize) {
+        let entry = &self.entries[idx];
+        let mailbox = Arc::new(Mutex::new(None));
+        self.preview_mailbox = Arc::clone(&mailbox);
+        self.preview_pending = true;
+        fetch_preview(
+            entry.main_repo_path.clone(),
+            entry.path.clone(),
+            entry.name.clone(),
+            entry.vcs_type,
+            self.repo_dir.clone(),
+            mailbox,
+        );
+    }
+
     fn drain_preview_mailbox(&mut self) {
         if let Ok(mut guard) = self.preview_mailbox.try_lock()
             && let Some(state) = guard.take()
         {
             self.preview = state;
+            self.preview_pending = false;
         }
     }
 
@@ -335,11 +871,22 @@ impl App {
             for entry in &mut self.entries {
                 entry.agent_status = summaries.get(&entry.name).cloned();
             }
+            // Only agent updates move workspaces in this mode.
+            if self.sort_mode == SortMode::Waiting {
+                let entries = std::mem::take(&mut self.entries);
+                self.merge_entries(entries);
+            }
         }
 
-        // Check full entry refresh (~10s interval)
+        // Check full entry refresh (~10s interval); re-fetch the preview on
+        // the same cycle so it picks up new commits.
         if let Some(new_entries) = self.refresh_mailbox.take() {
             self.merge_entries(new_entries);
+            self.loading = false;
+            self.refresh_preview();
+        }
+        if let Some(error) = self.refresh_error_mailbox.take() {
+            self.status.error(format!("refresh failed: {}", error));
         }
     }
 
@@ -371,46 +918,159 @@ impl App {
         self.sync_table_state();
     }
 
-    /// Recompute `filtered_indices` after `filter_buf` has changed.
+    /// Recompute `filtered_indices` after `filter_buf` has changed. Cleanup
+    /// mode further narrows them to stale workspaces.
     fn recompute_filter(&mut self) {
-        if self.filter_buf.is_empty() {
-            self.filtered_indices = (0..self.entries.len()).collect();
-        } else {
-            self.filtered_indices = self
-                .entries
-                .iter()
-                .enumerate()
-                .filter(|(_, e)| matches_filter(e, &self.filter_buf))
-                .map(|(i, _)| i)
-                .collect();
+        self.filtered_indices = filtered_indices(&self.entries, &self.filter_buf);
+        if matches!(self.mode, Mode::Cleanup(_)) {
+            let entries = &self.entries;
+            self.filtered_indices
+                .retain(|&i| is_cleanup_candidate(&entries[i]));
         }
         if self.selected >= self.total_rows() {
             self.selected = self.total_rows().saturating_sub(1);
         }
         self.sync_table_state();
     }
+
+    /// Whether the help bar is prompting for input, which takes precedence
+    /// over status messages.
+    fn awaiting_input(&self) -> bool {
+        self.menu.is_some()
+            || match self.mode {
+                Mode::Browse => false,
+                Mode::Cleanup(ref cleanup) => cleanup.confirming,
+                _ => true,
+            }
+    }
+
+    /// Enter cleanup mode with every stale workspace marked, or report that
+    /// there is nothing to clean up.
+    fn start_cleanup(&mut self) {
+        if self.loading {
+            self.status.info("still loading workspace details");
+            return;
+        }
+        match Cleanup::start(&self.entries) {
+            Some(cleanup) => {
+                self.mode = Mode::Cleanup(cleanup);
+                self.recompute_filter();
+                self.selected = 0;
+                self.sync_table_state();
+            }
+            None => self.status.info("no stale workspaces"),
+        }
+    }
+
+    /// Leave cleanup mode, showing every workspace that passes the filter.
+    fn end_cleanup(&mut self) {
+        self.mode = Mode::Browse;
+        self.recompute_filter();
+    }
+
+    /// Mark or unmark the selected workspace in cleanup mode.
+    fn toggle_cleanup_mark(&mut self) {
+        let Some(idx) = self.selected_entry_index() else {
+            return;
+        };
+        if let Mode::Cleanup(ref mut cleanup) = self.mode {
+            cleanup.toggle(&self.entries[idx].path);
+        }
+    }
+
+    /// The visible workspaces marked in cleanup mode, in display order.
+    fn cleanup_targets(&self) -> Vec<&WorkspaceEntry> {
+        let Mode::Cleanup(ref cleanup) = self.mode else {
+            return Vec::new();
+        };
+        self.visible_entries()
+            .into_iter()
+            .filter(|e| cleanup.marked.contains(&e.path))
+            .collect()
+    }
+
+    /// Restore a remembered sort mode, filter, and preview visibility and
+    /// layout.
+    fn apply_ui_state(&mut self, state: &UiState) {
+        if let Some(mode) = state.sort {
+            self.set_sort_mode(mode);
+        }
+        self.filter_buf = state.filter.clone();
+        self.recompute_filter();
+        self.show_preview = state.preview;
+        self.preview_layout = state.preview_layout;
+        self.trigger_preview_fetch();
+    }
+
+    /// The sort mode, filter, and preview visibility and layout to remember.
+    fn ui_state(&self) -> UiState {
+        UiState {
+            sort: Some(self.sort_mode),
+            filter: self.filter_buf.clone(),
+            preview: self.show_preview,
+            preview_layout: self.preview_layout,
+        }
+    }
+
+    /// Move the cursor to the visible row for the workspace called `name`, if
+    /// it passes the current filter.
+    fn select_by_name(&mut self, name: &str) {
+        if let Some(pos) = self
+            .filtered_indices
+            .iter()
+            .position(|&i| self.entries[i].name == name)
+        {
+            self.selected = pos;
+            self.sync_table_state();
+        }
+    }
 }
 
-fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState) {
+fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState, theme: &Theme) {
     let content = match preview {
-        PreviewState::Hidden => String::new(),
-        PreviewState::Loading => "Loading...".to_string(),
-        PreviewState::Ready { log, diff_stat } => {
-            let mut text = String::new();
-            if !diff_stat.is_empty() {
-                text.push_str("--- diff stat vs trunk ---\n");
-                text.push_str(diff_stat);
-                if !diff_stat.ends_with('\n') {
-                    text.push('\n');
+        PreviewState::Hidden => Text::default(),
+        PreviewState::Loading => Text::raw("Loading..."),
+        PreviewState::Ready {
+            log,
+            graph,
+            diff_stat,
+            agents,
+            touched,
+            issue,
+        } => {
+            let mut text = Text::default();
+            if let Some(issue) = issue {
+                text.push_line("--- issue ---");
+                text.push_line(format!("#{} {}", issue.number, issue.title));
+                if !issue.url.is_empty() {
+                    text.push_line(issue.url.clone());
+                }
+                text.push_line("");
+            }
+            if !agents.is_empty() || !touched.is_empty() {
+                text.push_line("--- agents ---");
+                if !agents.is_empty() {
+                    text.push_line(agents.clone());
+                }
+                if !touched.is_empty() {
+                    text.push_line(format!("last touched: {}", touched));
                 }
-                text.push('\n');
+                text.push_line("");
             }
-            if !log.is_empty() {
-                text.push_str("--- log ---\n");
-                text.push_str(log);
+            if !diff_stat.is_empty() {
+                text.push_line("--- diff stat vs trunk ---");
+                text.extend(Text::raw(diff_stat.clone()));
+                text.push_line("");
+            }
+            if !graph.is_empty() {
+                text.push_line("--- log ---");
+                text.extend(ansi::to_text(graph));
+            } else if !log.is_empty() {
+                text.push_line("--- log ---");
+                text.extend(Text::raw(log.clone()));
             }
-            if text.is_empty() {
-                "No changes".to_string()
+            if text.lines.is_empty() {
+                Text::raw("No changes")
             } else {
                 text
             }
@@ -425,135 +1085,876 @@ fn render_preview(frame: &mut Frame, area: Rect, preview: &PreviewState) {
                 .title_alignment(Alignment::Center),
         )
         .wrap(Wrap { trim: false })
-        .style(Style::default().fg(Color::White));
+        .style(Style::default().fg(theme.text));
 
     frame.render_widget(paragraph, area);
 }
 
-/// Render the single-repo workspace table and help bar into `frame`.
-fn render(frame: &mut Frame, app: &mut App) {
-    let full_area = frame.area();
+/// Actions available in the single-repo picker, in help display order.
+const SINGLE_REPO_ACTIONS: &[Action] = &[
+    Action::Down,
+    Action::Up,
+    Action::Top,
+    Action::Bottom,
+    Action::HalfPageDown,
+    Action::HalfPageUp,
+    Action::Help,
+    Action::Filter,
+    Action::Sort,
+    Action::Preview,
+    Action::PreviewGrow,
+    Action::PreviewShrink,
+    Action::PreviewLayout,
+    Action::Delete,
+    Action::Rename,
+    Action::Open,
+    Action::Copy,
+    Action::Push,
+    Action::Sync,
+    Action::Pr,
+    Action::Ci,
+    Action::Menu,
+    Action::Agents,
+    Action::Launch,
+    Action::Tmux,
+    Action::NextWaiting,
+    Action::Cleanup,
+    Action::Select,
+    Action::Quit,
+];
+
+/// Actions available in the multi-repo picker, in help display order.
+const MULTI_REPO_ACTIONS: &[Action] = &[
+    Action::Down,
+    Action::Up,
+    Action::Top,
+    Action::Bottom,
+    Action::HalfPageDown,
+    Action::HalfPageUp,
+    Action::Help,
+    Action::Filter,
+    Action::Sort,
+    Action::Preview,
+    Action::PreviewGrow,
+    Action::PreviewShrink,
+    Action::PreviewLayout,
+    Action::Delete,
+    Action::Open,
+    Action::Copy,
+    Action::Push,
+    Action::Sync,
+    Action::Pr,
+    Action::Ci,
+    Action::Menu,
+    Action::Agents,
+    Action::Launch,
+    Action::Tmux,
+    Action::NextWaiting,
+    Action::Cleanup,
+    Action::Collapse,
+    Action::Select,
+    Action::Quit,
+];
+
+/// Build the browse-mode help bar text from the effective key bindings:
+/// up/down first, then the rest of `actions` in order.
+fn browse_help(keymap: &KeyMap, sort_mode: SortMode, actions: &[Action]) -> String {
+    let mut text = format!(
+        " {}/{}: navigate",
+        keymap.label(Action::Down),
+        keymap.label(Action::Up)
+    );
+    for &action in actions {
+        let label = match action {
+            // The other motions and the preview sizing keys are listed
+            // only in the `?` popup.
+            Action::Down
+            | Action::Up
+            | Action::Top
+            | Action::Bottom
+            | Action::HalfPageDown
+            | Action::HalfPageUp
+            | Action::PreviewGrow
+            | Action::PreviewShrink
+            | Action::PreviewLayout => continue,
+            Action::Sort => format!("sort ({})", sort_mode.label()),
+            other => other.name().to_string(),
+        };
+        text.push_str(&format!("  {}: {}", keymap.label(action), label));
+    }
+    text
+}
 
-    // Reserve 1 line at the bottom for help bar
-    let (main_area, help_area) = if full_area.height > 3 {
-        let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(full_area);
-        (chunks[0], Some(chunks[1]))
-    } else {
-        (full_area, None)
-    };
+/// Return a rect of at most `width` x `height` centered within `area`.
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
 
-    // Split horizontally if preview is visible
-    let (table_area, preview_area) = if app.show_preview {
-        let chunks = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
-            .split(main_area);
-        (chunks[0], Some(chunks[1]))
-    } else {
-        (main_area, None)
+/// Vim-style count prefix and pending `g` press for browse-mode motions.
+#[derive(Debug, Default)]
+struct Motion {
+    /// Count typed so far, e.g. `12` before `j`.
+    count: Option<usize>,
+    /// Whether a letter bound to [`Action::Top`] was pressed once and the
+    /// second press is awaited.
+    pending_top: bool,
+}
+
+impl Motion {
+    /// Digest `key`, which the keymap resolved to `action`. Unbound digits
+    /// build up the count, and the first press of a letter bound to
+    /// [`Action::Top`] waits for the second; both yield no action. Otherwise
+    /// returns `action` along with the count typed before it.
+    fn feed(&mut self, key: &KeyEvent, action: Option<Action>) -> (Option<Action>, Option<usize>) {
+        let pending_top = std::mem::take(&mut self.pending_top);
+        if action.is_none()
+            && !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && let KeyCode::Char(c) = key.code
+            && let Some(digit) = c.to_digit(10)
+            && (digit != 0 || self.count.is_some())
+        {
+            let count = self.count.unwrap_or(0);
+            self.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+            return (None, None);
+        }
+        if action == Some(Action::Top) && matches!(key.code, KeyCode::Char(_)) && !pending_top {
+            self.pending_top = true;
+            return (None, None);
+        }
+        (action, self.count.take())
+    }
+}
+
+/// Row the cursor lands on after `action` from row `selected` of `total`,
+/// where half a page is `half_page` rows. Counted moves stop at the ends
+/// instead of wrapping, and a count on top/bottom picks that row number.
+fn motion_target(
+    action: Action,
+    count: Option<usize>,
+    selected: usize,
+    total: usize,
+    half_page: usize,
+) -> usize {
+    let Some(last) = total.checked_sub(1) else {
+        return 0;
+    };
+    let steps = count.unwrap_or(1);
+    let target = match (action, count) {
+        (Action::Down, None) if selected == last => 0,
+        (Action::Up, None) if selected == 0 => last,
+        (Action::Down, _) => selected.saturating_add(steps),
+        (Action::Up, _) => selected.saturating_sub(steps),
+        (Action::HalfPageDown, _) => selected.saturating_add(half_page.max(1) * steps),
+        (Action::HalfPageUp, _) => selected.saturating_sub(half_page.max(1) * steps),
+        (Action::Top | Action::Bottom, Some(n)) => n.saturating_sub(1),
+        (Action::Top, None) => 0,
+        // The last row is "+ Create new"; land on the workspace above it.
+        (Action::Bottom, None) => last.saturating_sub(1),
+        _ => selected,
     };
+    target.min(last)
+}
 
-    let header_cells = [
-        "Name",
-        "Change",
-        "Description",
-        "Bookmarks",
-        "Modified",
-        "Changes",
-        "Agent",
-    ]
-    .iter()
-    .map(|h| Cell::from(*h).style(Style::default().fg(Color::White).bold()));
-    let header = Row::new(header_cells)
-        .style(Style::default().bg(Color::DarkGray))
-        .height(1);
+/// Popup listing the actions available for the highlighted workspace, so
+/// features can be reached without a dedicated key each.
+#[derive(Debug, Clone, PartialEq)]
+struct ActionMenu {
+    /// Workspace name shown in the popup title.
+    title: String,
+    items: Vec<Action>,
+    selected: usize,
+}
 
-    let visible = app.visible_entries();
-    let mut rows: Vec<Row> = visible
+impl ActionMenu {
+    /// Build the menu for `entry` from `items`, leaving out actions that are
+    /// refused on the main workspace.
+    fn for_entry(entry: &WorkspaceEntry, items: &[Action]) -> Self {
+        let items = items
+            .iter()
+            .copied()
+            .filter(|a| !(entry.is_main && matches!(a, Action::Delete | Action::Rename)))
+            .collect();
+        Self {
+            title: entry.name.clone(),
+            items,
+            selected: 0,
+        }
+    }
+}
+
+/// Actions offered by the single-repo action menu, in display order.
+const SINGLE_REPO_MENU: &[Action] = &[
+    Action::Select,
+    Action::Open,
+    Action::Copy,
+    Action::Rename,
+    Action::Delete,
+    Action::Agents,
+    Action::Launch,
+    Action::Tmux,
+    Action::Push,
+    Action::Sync,
+    Action::Pr,
+    Action::Ci,
+];
+
+/// Actions offered by the multi-repo action menu, in display order.
+const MULTI_REPO_MENU: &[Action] = &[
+    Action::Select,
+    Action::Open,
+    Action::Copy,
+    Action::Delete,
+    Action::Agents,
+    Action::Launch,
+    Action::Tmux,
+    Action::Push,
+    Action::Sync,
+    Action::Pr,
+    Action::Ci,
+];
+
+/// Handle `key` while the action menu in `menu` is open. Returns the chosen
+/// action, to be dispatched as if its key had been pressed; the menu closes
+/// on a choice or on Esc / quit. An item's own key also chooses it.
+fn menu_key(menu: &mut Option<ActionMenu>, keymap: &KeyMap, key: &KeyEvent) -> Option<Action> {
+    let m = menu.as_mut()?;
+    if key.code == KeyCode::Enter {
+        let chosen = m.items.get(m.selected).copied();
+        *menu = None;
+        return chosen;
+    }
+    match keymap.action_for(key) {
+        Some(Action::Down) if !m.items.is_empty() => {
+            m.selected = (m.selected + 1) % m.items.len();
+            None
+        }
+        Some(Action::Up) if !m.items.is_empty() => {
+            m.selected = m.selected.checked_sub(1).unwrap_or(m.items.len() - 1);
+            None
+        }
+        Some(Action::Quit | Action::Menu) => {
+            *menu = None;
+            None
+        }
+        Some(action) if m.items.contains(&action) => {
+            *menu = None;
+            Some(action)
+        }
+        _ => None,
+    }
+}
+
+/// Render the action menu popup, listing each item with its key.
+fn render_action_menu(frame: &mut Frame, menu: &ActionMenu, keymap: &KeyMap, theme: &Theme) {
+    let lines: Vec<Line> = menu
+        .items
         .iter()
-        .map(|entry| {
-            let name_text = if entry.is_main {
-                format!("{} (main)", entry.name)
-            } else if entry.is_stale {
-                format!("{} [stale]", entry.name)
+        .enumerate()
+        .map(|(i, &action)| {
+            let style = if i == menu.selected {
+                Style::default().bg(theme.highlight)
             } else {
-                entry.name.clone()
+                Style::default()
             };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {:<7}", keymap.label(action)),
+                    Style::default().fg(theme.name).bold(),
+                ),
+                Span::styled(
+                    format!("{} ", action.description()),
+                    Style::default().fg(theme.text),
+                ),
+            ])
+            .style(style)
+        })
+        .collect();
+    let width = lines
+        .iter()
+        .map(|l| l.width() as u16)
+        .max()
+        .unwrap_or(0)
+        .max(24)
+        + 2;
+    let area = centered_rect(frame.area(), width, lines.len() as u16 + 2);
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", menu.title))
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup, area);
+}
 
-            let change_text = entry.change_id.clone();
-
-            let desc_text = entry.description.lines().next().unwrap_or("").to_string();
-
-            let bookmarks_text = entry.bookmarks.join(", ");
-
-            let time_text = format_time_ago(entry.last_modified);
+/// Popup listing each agent session in one workspace.
+#[derive(Debug, Clone, PartialEq)]
+struct AgentPopup {
+    /// Workspace name shown in the popup title.
+    title: String,
+    sessions: Vec<AgentSession>,
+}
 
-            let stat = &entry.diff_stat;
-            let changes_text =
-                if stat.files_changed == 0 && stat.insertions == 0 && stat.deletions == 0 {
-                    "clean".to_string()
-                } else {
-                    let mut parts = Vec::new();
-                    if stat.insertions > 0 {
-                        parts.push(format!("+{}", stat.insertions));
-                    }
-                    if stat.deletions > 0 {
-                        parts.push(format!("-{}", stat.deletions));
-                    }
-                    if parts.is_empty() {
-                        format!("{} files", stat.files_changed)
-                    } else {
-                        parts.join(" ")
-                    }
-                };
+/// Render the agent popup: one line per session with its status, agent,
+/// short id, and last update, followed by its last prompt and tool when known.
+fn render_agent_popup(frame: &mut Frame, popup: &AgentPopup, theme: &Theme) {
+    let mut lines: Vec<Line> = Vec::new();
+    if popup.sessions.is_empty() {
+        lines.push(Line::styled(
+            " no active agent sessions ",
+            Style::default().fg(theme.muted),
+        ));
+    }
+    let label_w = popup
+        .sessions
+        .iter()
+        .map(|s| width::of(&s.label()) + 1)
+        .max()
+        .unwrap_or(0)
+        .max(9);
+    for session in &popup.sessions {
+        let label = session.status.label();
+        let color = match session.status.coarse() {
+            AgentStatus::Waiting => theme.warning,
+            AgentStatus::Idle => theme.muted,
+            _ => theme.accent,
+        };
+        let id = session
+            .session_id
+            .strip_prefix(&format!("{}-", session.agent.name()))
+            .unwrap_or(&session.session_id);
+        let short_id: String = id.chars().take(8).collect();
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:<8}", label), Style::default().fg(color).bold()),
+            Span::styled(
+                width::pad(&session.label(), label_w),
+                Style::default().fg(theme.muted),
+            ),
+            Span::styled(
+                format!("{:<9}", short_id),
+                Style::default().fg(theme.change),
+            ),
+            Span::styled(
+                format!("{} ", format_time_ago(Some(session.updated_at))),
+                Style::default().fg(theme.modified),
+            ),
+        ]));
+        if let Some(ref prompt) = session.last_prompt {
+            lines.push(Line::styled(
+                format!("   prompt: {} ", prompt),
+                Style::default().fg(theme.text),
+            ));
+        }
+        if let Some(ref tool) = session.last_tool {
+            lines.push(Line::styled(
+                format!("   tool: {} ", tool),
+                Style::default().fg(theme.text),
+            ));
+        }
+        if let Some(path) = session.touched.first() {
+            lines.push(Line::styled(
+                format!("   last touched: {} ", path),
+                Style::default().fg(theme.text),
+            ));
+        }
+    }
+    let area = frame.area();
+    let width = lines
+        .iter()
+        .map(|l| l.width() as u16)
+        .max()
+        .unwrap_or(0)
+        .max(32)
+        .min(area.width.saturating_sub(4))
+        + 2;
+    let rect = centered_rect(area, width, lines.len() as u16 + 2);
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Agents in {} ", popup.title))
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(Clear, rect);
+    frame.render_widget(widget, rect);
+}
 
-            // Use dim styling for stale workspaces
-            let dim = entry.is_stale;
-            let name_fg = if dim { Color::DarkGray } else { Color::Cyan };
-            let change_fg = if dim { Color::DarkGray } else { Color::Magenta };
-            let desc_fg = if dim { Color::DarkGray } else { Color::White };
-            let bookmark_fg = if dim { Color::DarkGray } else { Color::Blue };
-            let time_fg = if dim { Color::DarkGray } else { Color::Yellow };
-            let changes_fg = if dim {
-                Color::DarkGray
-            } else if stat.deletions > stat.insertions {
-                Color::Red
-            } else if stat.insertions > 0 {
-                Color::Green
+/// Whether cleanup mode offers `entry`: stale, and not the main workspace,
+/// which can't be deleted.
+fn is_cleanup_candidate(entry: &WorkspaceEntry) -> bool {
+    entry.is_stale && !entry.is_main
+}
+
+/// Cleanup mode: the picker shows only stale workspaces, all marked for
+/// deletion to begin with, and deletes the marked ones after a single
+/// confirmation.
+#[derive(Debug, Clone, PartialEq)]
+struct Cleanup {
+    /// Paths of the workspaces marked for deletion.
+    marked: HashSet<PathBuf>,
+    /// Whether the bulk-delete confirmation is open.
+    confirming: bool,
+}
+
+impl Cleanup {
+    /// Start with every cleanup candidate in `entries` marked, or return
+    /// `None` if there are none.
+    fn start(entries: &[WorkspaceEntry]) -> Option<Self> {
+        let marked: HashSet<PathBuf> = entries
+            .iter()
+            .filter(|e| is_cleanup_candidate(e))
+            .map(|e| e.path.clone())
+            .collect();
+        (!marked.is_empty()).then_some(Self {
+            marked,
+            confirming: false,
+        })
+    }
+
+    /// Mark the workspace at `path` if unmarked, or unmark it.
+    fn toggle(&mut self, path: &Path) {
+        if !self.marked.remove(path) {
+            self.marked.insert(path.to_path_buf());
+        }
+    }
+}
+
+/// Build the cleanup-mode row for `entry`: like [`entry_row`], but the name
+/// is prefixed with `[x]` when the workspace is marked for deletion.
+fn cleanup_row(
+    entry: &WorkspaceEntry,
+    marked: bool,
+    columns: &[ColumnSpec],
+    theme: &Theme,
+) -> Row<'static> {
+    Row::new(columns.iter().map(|c| match c.column() {
+        Column::Name => {
+            let (mark, color) = if marked {
+                ("[x]", theme.danger)
             } else {
-                Color::DarkGray
+                ("[ ]", theme.muted)
             };
+            Cell::from(Line::from(vec![
+                Span::styled(format!("{} ", mark), Style::default().fg(color)),
+                Span::styled(
+                    format!("{}{}", entry.name, dirty_marker(entry)),
+                    Style::default().fg(theme.name),
+                ),
+            ]))
+        }
+        column => entry_cell(entry, column, theme),
+    }))
+    .style(row_style(entry, theme))
+}
 
-            let (agent_text, agent_fg) = match &entry.agent_status {
-                Some(summary) if !summary.is_empty() => {
-                    let color = if dim {
-                        Color::DarkGray
-                    } else {
-                        match summary.most_urgent() {
-                            Some(crate::agent::AgentStatus::Waiting) => Color::Yellow,
-                            Some(crate::agent::AgentStatus::Working) => Color::Green,
-                            _ => Color::DarkGray,
-                        }
-                    };
-                    (summary.to_string(), color)
+/// Describe the work on a workspace that isn't in trunk, from its diff stat
+/// against trunk, or `None` if there is none.
+fn unmerged_work(stat: &crate::vcs::DiffStat) -> Option<String> {
+    if stat.files_changed == 0 && stat.insertions == 0 && stat.deletions == 0 {
+        return None;
+    }
+    let plural = if stat.files_changed == 1 { "" } else { "s" };
+    Some(format!(
+        "{} file{} unmerged (+{} -{})",
+        stat.files_changed, plural, stat.insertions, stat.deletions
+    ))
+}
+
+/// Help bar text while browsing in cleanup mode, with `marked` of `total`
+/// visible workspaces marked.
+fn cleanup_help(marked: usize, total: usize) -> String {
+    format!(
+        " cleanup: {} of {} marked  space: toggle  Enter: delete marked  Esc: done",
+        marked, total
+    )
+}
+
+/// Render the cleanup confirmation popup: each `(label, entry)` about to be
+/// deleted, with the unmerged work that would be lost.
+fn render_cleanup_confirm(frame: &mut Frame, targets: &[(String, &WorkspaceEntry)], theme: &Theme) {
+    let label_width = targets
+        .iter()
+        .map(|(label, _)| width::of(label))
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = targets
+        .iter()
+        .map(|(label, entry)| {
+            let (work, color) = match (
+                unmerged_work(&entry.diff_stat),
+                entry.working_copy.describe(),
+            ) {
+                (Some(work), Some(dirty)) => (format!("{}, {}", work, dirty), theme.warning),
+                (Some(work), None) => (work, theme.warning),
+                (None, Some(dirty)) => (dirty.to_string(), theme.warning),
+                (None, None) => ("nothing unmerged".to_string(), theme.muted),
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}  ", width::pad(label, label_width)),
+                    Style::default().fg(theme.name),
+                ),
+                Span::styled(format!("{} ", work), Style::default().fg(color)),
+            ])
+        })
+        .collect();
+    let plural = if targets.len() == 1 { "" } else { "s" };
+    let title = format!(" Delete {} workspace{}? ", targets.len(), plural);
+    let area = frame.area();
+    let width = lines
+        .iter()
+        .map(|l| l.width() as u16)
+        .max()
+        .unwrap_or(0)
+        .max(width::of(&title) as u16 + 2)
+        .min(area.width.saturating_sub(4))
+        + 2;
+    let rect = centered_rect(area, width, lines.len() as u16 + 2);
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(Clear, rect);
+    frame.render_widget(widget, rect);
+}
+
+/// `" (has uncommitted changes)"` or similar for the delete prompt of a
+/// dirty workspace, else empty.
+fn dirty_warning(entry: Option<&WorkspaceEntry>) -> String {
+    entry
+        .and_then(|e| e.working_copy.describe())
+        .map(|dirty| format!(" (has {})", dirty))
+        .unwrap_or_default()
+}
+
+/// Delete each `(label, path)` in `targets` with `delete`, carrying on past
+/// failures. Returns whether any deletion printed a redirect path, and a
+/// status message summarizing the outcome, at warning level if any failed.
+fn delete_all(
+    targets: &[(String, PathBuf)],
+    mut delete: impl FnMut(&str, &Path) -> Result<bool>,
+) -> (bool, Level, String) {
+    let mut redirected = false;
+    let mut failures = Vec::new();
+    for (label, path) in targets {
+        match delete(label, path) {
+            Ok(r) => redirected |= r,
+            Err(e) => failures.push(format!("{}: {}", label, e)),
+        }
+    }
+    let deleted = targets.len() - failures.len();
+    let plural = if deleted == 1 { "" } else { "s" };
+    let mut message = format!("deleted {} workspace{}", deleted, plural);
+    if failures.is_empty() {
+        return (redirected, Level::Info, message);
+    }
+    message.push_str(&format!("; failed: {}", failures.join(", ")));
+    (redirected, Level::Warn, message)
+}
+
+/// Help bar text and style for a status `message`, colored by its level.
+fn status_line(message: &Message, theme: &Theme) -> (String, Style) {
+    let color = match message.level {
+        Level::Info => theme.accent,
+        Level::Warn => theme.warning,
+        Level::Error => theme.danger,
+    };
+    (format!(" {}", message.text), Style::default().fg(color))
+}
+
+/// Help bar line for the selected entry's backend error, if it has one, so
+/// the reason behind its "⚠ error" cell can be read.
+fn selected_error(
+    entries: &[WorkspaceEntry],
+    selected: Option<usize>,
+    theme: &Theme,
+) -> Option<(String, Style)> {
+    let error = entries.get(selected?)?.error.as_ref()?;
+    Some((format!(" ⚠ {}", error), Style::default().fg(theme.danger)))
+}
+
+/// Help bar text while the action menu is open.
+const MENU_HELP: &str = " j/k: move  Enter: choose  Esc: close";
+
+/// Render the `?` help popup listing every binding in `actions`, the sort
+/// modes, and the filter syntax, centered over the rest of the UI.
+fn render_help_popup(frame: &mut Frame, keymap: &KeyMap, actions: &[Action], theme: &Theme) {
+    let key_style = Style::default().fg(theme.name).bold();
+    let heading_style = Style::default().fg(theme.text).bold();
+
+    let bindings: Vec<(String, &str)> = actions
+        .iter()
+        .map(|&action| {
+            let keys = keymap
+                .keys(action)
+                .iter()
+                .map(|k| k.to_string())
+                .collect::<Vec<_>>()
+                .join(" / ");
+            (keys, action.description())
+        })
+        .collect();
+    let key_width = bindings
+        .iter()
+        .map(|(k, _)| width::of(k) + 2)
+        .max()
+        .unwrap_or(0)
+        .max(14);
+    let desc_width = bindings
+        .iter()
+        .map(|(_, d)| width::of(d))
+        .max()
+        .unwrap_or(0);
+    let column_width = 2 + key_width + desc_width;
+    // Split the bindings into two columns when they fit side by side.
+    let two_columns = frame.area().width as usize >= 2 * column_width + 6;
+    let rows = if two_columns {
+        bindings.len().div_ceil(2)
+    } else {
+        bindings.len()
+    };
+
+    let mut lines = vec![Line::styled("Keys", heading_style)];
+    for row in 0..rows {
+        let mut spans = Vec::new();
+        for (keys, desc) in [Some(row), two_columns.then_some(row + rows)]
+            .into_iter()
+            .flatten()
+            .filter_map(|i| bindings.get(i))
+        {
+            spans.push(Span::styled(
+                format!("  {}", width::pad(keys, key_width)),
+                key_style,
+            ));
+            spans.push(Span::raw(width::pad(desc, desc_width)));
+        }
+        lines.push(Line::from(spans));
+    }
+    let sort_modes = SortMode::ALL
+        .iter()
+        .map(|m| m.label())
+        .collect::<Vec<_>>()
+        .join(" → ");
+    lines.push(Line::raw(""));
+    lines.push(Line::styled("Sort modes", heading_style));
+    lines.push(Line::raw(format!("  {}", sort_modes)));
+    lines.push(Line::raw(""));
+    lines.push(Line::styled("Filter", heading_style));
+    for help in filter::HELP {
+        lines.push(Line::raw(format!("  {}", help)));
+    }
+
+    let width = if two_columns {
+        2 * column_width + 4
+    } else {
+        70
+    };
+    let area = centered_rect(frame.area(), width as u16, lines.len() as u16 + 2);
+    let popup = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Help (any key to close) ")
+                .title_alignment(Alignment::Center),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup, area);
+}
+
+/// Build the table header row for `columns`.
+fn table_header(columns: &[ColumnSpec], theme: &Theme) -> Row<'static> {
+    let cells = columns
+        .iter()
+        .map(|c| Cell::from(c.column().title()).style(Style::default().fg(theme.text).bold()));
+    Row::new(cells)
+        .style(Style::default().bg(theme.header))
+        .height(1)
+}
+
+/// Build the table row for `entry` with one cell per entry in `columns`.
+fn entry_row(entry: &WorkspaceEntry, columns: &[ColumnSpec], theme: &Theme) -> Row<'static> {
+    Row::new(columns.iter().map(|c| entry_cell(entry, c.column(), theme)))
+        .style(row_style(entry, theme))
+}
+
+/// Whether any of `entry`'s agents is waiting for input.
+fn has_waiting_agent(entry: &WorkspaceEntry) -> bool {
+    entry.agent_status.as_ref().is_some_and(|s| s.waiting > 0)
+}
+
+/// Row background for `entry`: tinted when an agent is waiting for input,
+/// so those rows stand out for triage.
+fn row_style(entry: &WorkspaceEntry, theme: &Theme) -> Style {
+    if has_waiting_agent(entry) {
+        Style::default().bg(theme.attention)
+    } else {
+        Style::default()
+    }
+}
+
+/// Build the row for `entry` while details are still loading: only the
+/// columns known from the filesystem are filled in.
+fn loading_row(entry: &WorkspaceEntry, columns: &[ColumnSpec], theme: &Theme) -> Row<'static> {
+    Row::new(columns.iter().map(|c| match c.column() {
+        Column::Repo | Column::Name | Column::Modified | Column::Agent => {
+            entry_cell(entry, c.column(), theme)
+        }
+        _ => Cell::from(""),
+    }))
+    .style(row_style(entry, theme))
+}
+
+/// Render a single table cell for `entry`. Stale workspaces are dimmed.
+fn entry_cell(entry: &WorkspaceEntry, column: Column, theme: &Theme) -> Cell<'static> {
+    let dim = entry.is_stale;
+    let fg = |color: Color| if dim { theme.muted } else { color };
+
+    let (text, color) = match column {
+        Column::Repo => (
+            entry.repo_name.as_deref().unwrap_or("").to_string(),
+            theme.accent,
+        ),
+        Column::Name => {
+            let marker = dirty_marker(entry);
+            let text = if entry.is_main {
+                format!("{}{} (main)", entry.name, marker)
+            } else if entry.is_stale {
+                format!("{}{} [stale]", entry.name, marker)
+            } else {
+                format!("{}{}", entry.name, marker)
+            };
+            (text, fg(theme.name))
+        }
+        Column::Change => (entry.change_id.clone(), fg(theme.change)),
+        Column::Description => (
+            entry.description.lines().next().unwrap_or("").to_string(),
+            fg(theme.text),
+        ),
+        Column::Bookmarks => (entry.bookmarks.join(", "), fg(theme.bookmark)),
+        Column::Modified => (format_time_ago(entry.last_modified), fg(theme.modified)),
+        // A failed backend call leaves the stat empty; don't call that clean.
+        Column::Changes if entry.error.is_some() => ("⚠ error".to_string(), theme.danger),
+        Column::Changes => {
+            let stat = &entry.diff_stat;
+            let text = if stat.files_changed == 0 && stat.insertions == 0 && stat.deletions == 0 {
+                "clean".to_string()
+            } else {
+                let mut parts = Vec::new();
+                if stat.insertions > 0 {
+                    parts.push(format!("+{}", stat.insertions));
+                }
+                if stat.deletions > 0 {
+                    parts.push(format!("-{}", stat.deletions));
+                }
+                if parts.is_empty() {
+                    format!("{} files", stat.files_changed)
+                } else {
+                    parts.join(" ")
                 }
-                _ => (String::new(), Color::DarkGray),
             };
+            let text = match stat.commit_counts() {
+                Some(counts) => format!("{} {}", text, counts),
+                None => text,
+            };
+            let color = if stat.deletions > stat.insertions {
+                theme.danger
+            } else if stat.insertions > 0 {
+                theme.accent
+            } else {
+                theme.muted
+            };
+            (text, fg(color))
+        }
+        Column::Pr => match &entry.pr {
+            Some(pr) => {
+                let color = if pr.is_blocked() {
+                    theme.danger
+                } else if pr.is_ready() {
+                    theme.accent
+                } else {
+                    theme.muted
+                };
+                (pr.to_string(), fg(color))
+            }
+            None => (String::new(), theme.muted),
+        },
+        Column::Ci => match &entry.ci {
+            Some(ci) => {
+                let color = match ci.checks {
+                    Checks::Passing => theme.accent,
+                    Checks::Failing => theme.danger,
+                    Checks::Pending => theme.warning,
+                };
+                (ci.to_string(), fg(color))
+            }
+            None => (String::new(), theme.muted),
+        },
+        Column::Agent => match &entry.agent_status {
+            Some(summary) if !summary.is_empty() => {
+                let color = match summary.most_urgent() {
+                    Some(crate::agent::AgentStatus::Waiting) => theme.warning,
+                    Some(crate::agent::AgentStatus::Working) => theme.accent,
+                    _ => theme.muted,
+                };
+                (summary.to_string(), fg(color))
+            }
+            _ => (String::new(), theme.muted),
+        },
+    };
+    Cell::from(text).style(Style::default().fg(color))
+}
 
-            Row::new(vec![
-                Cell::from(name_text).style(Style::default().fg(name_fg)),
-                Cell::from(change_text).style(Style::default().fg(change_fg)),
-                Cell::from(desc_text).style(Style::default().fg(desc_fg)),
-                Cell::from(bookmarks_text).style(Style::default().fg(bookmark_fg)),
-                Cell::from(time_text).style(Style::default().fg(time_fg)),
-                Cell::from(changes_text).style(Style::default().fg(changes_fg)),
-                Cell::from(agent_text).style(Style::default().fg(agent_fg)),
-            ])
+/// Render the single-repo workspace table and help bar into `frame`.
+fn render(frame: &mut Frame, app: &mut App) {
+    let full_area = frame.area();
+
+    // Reserve 1 line at the bottom for help bar
+    let (main_area, help_area) = if full_area.height > 3 {
+        let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(full_area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (full_area, None)
+    };
+
+    // Split off the preview pane if visible
+    let (table_area, preview_area) = if app.show_preview {
+        let (table_area, preview_area) = app.preview_layout.split(main_area);
+        (table_area, Some(preview_area))
+    } else {
+        (main_area, None)
+    };
+
+    let header = table_header(&app.columns, &app.theme);
+    let visible = app.visible_entries();
+    let mut rows: Vec<Row> = visible
+        .iter()
+        .map(|entry| match app.mode {
+            Mode::Cleanup(ref cleanup) => cleanup_row(
+                entry,
+                cleanup.marked.contains(&entry.path),
+                &app.columns,
+                &app.theme,
+            ),
+            _ if app.loading => loading_row(entry, &app.columns, &app.theme),
+            _ => entry_row(entry, &app.columns, &app.theme),
         })
         .collect();
 
     // Append "+ Create new" row
     let create_row_selected = app.on_create_row();
     let create_style = if create_row_selected {
-        Style::default().bg(Color::Rgb(40, 40, 60))
+        Style::default().bg(app.theme.highlight)
     } else {
         Style::default()
     };
@@ -567,40 +1968,31 @@ fn render(frame: &mut Frame, app: &mut App) {
     } else {
         "+ Create new".to_string()
     };
-    rows.push(
-        Row::new(vec![
-            Cell::from(create_name).style(Style::default().fg(Color::Green)),
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(""),
-            Cell::from(""),
-        ])
-        .style(create_style),
-    );
+    let mut create_cells =
+        vec![Cell::from(create_name).style(Style::default().fg(app.theme.accent))];
+    create_cells.resize(app.columns.len().max(1), Cell::from(""));
+    rows.push(Row::new(create_cells).style(create_style));
 
-    let widths = [
-        Constraint::Percentage(14),
-        Constraint::Percentage(8),
-        Constraint::Percentage(27),
-        Constraint::Percentage(13),
-        Constraint::Percentage(10),
-        Constraint::Percentage(12),
-        Constraint::Percentage(16),
-    ];
+    let widths: Vec<Constraint> = app.columns.iter().map(|c| c.constraint()).collect();
 
+    let title = if app.loading {
+        let frame = progress::SPINNER_FRAMES[app.spinner_tick % progress::SPINNER_FRAMES.len()];
+        format!(" dwm workspaces {} loading ", frame)
+    } else {
+        " dwm workspaces ".to_string()
+    };
     let table = Table::new(rows, widths)
         .header(header)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" dwm workspaces ")
+                .title(title)
                 .title_alignment(Alignment::Center),
         )
-        .row_highlight_style(Style::default().bg(Color::Rgb(40, 40, 60)));
+        .row_highlight_style(Style::default().bg(app.theme.highlight));
 
     frame.render_stateful_widget(table, table_area, &mut app.table_state);
+    app.mouse.table_area = table_area;
 
     // Overlay a full-width input line on top of the create row
     if input_active {
@@ -616,21 +2008,31 @@ fn render(frame: &mut Frame, app: &mut App) {
                 1,
             );
             let input_text = format!("Name: {}_", app.input_buf);
-            let input_line = Paragraph::new(input_text)
-                .style(Style::default().fg(Color::Green).bg(Color::Rgb(40, 40, 60)));
+            let input_line = Paragraph::new(input_text).style(
+                Style::default()
+                    .fg(app.theme.accent)
+                    .bg(app.theme.highlight),
+            );
             frame.render_widget(input_line, input_area);
         }
     }
 
     // Render preview pane if visible
     if let Some(preview_area) = preview_area {
-        render_preview(frame, preview_area, &app.preview);
+        render_preview(frame, preview_area, &app.preview, &app.theme);
     }
 
     // Render help bar at bottom
     if let Some(help_area) = help_area {
-        let (help_text, help_style) = if let Some(ref msg) = app.status_message {
-            (format!(" {}", msg), Style::default().fg(Color::Green))
+        let status = app.status.current().filter(|_| !app.awaiting_input());
+        let (help_text, help_style) = if let Some(msg) = status {
+            status_line(msg, &app.theme)
+        } else if let Mode::Browse = app.mode
+            && app.menu.is_none()
+            && let Some(error) =
+                selected_error(&app.entries, app.selected_entry_index(), &app.theme)
+        {
+            error
         } else {
             let text = match app.mode {
                 Mode::InputName => " Enter: create  Esc: cancel".to_string(),
@@ -638,8 +2040,31 @@ fn render(frame: &mut Frame, app: &mut App) {
                     format!(" filter: {}▏  Enter: apply  Esc: clear", app.filter_buf)
                 }
                 Mode::ConfirmDelete(ref name) => {
-                    format!(" Delete '{}'? y: confirm  n: cancel", name)
+                    let entry = app.entries.iter().find(|e| e.name == *name);
+                    format!(
+                        " Delete '{}'{}? y: confirm  n: cancel",
+                        name,
+                        dirty_warning(entry)
+                    )
+                }
+                Mode::Rename(ref name) => {
+                    format!(
+                        " Rename '{}' to: {}▏  Enter: confirm  Esc: cancel",
+                        name, app.input_buf
+                    )
+                }
+                Mode::Cleanup(ref cleanup) if cleanup.confirming => {
+                    let count = app.cleanup_targets().len();
+                    let plural = if count == 1 { "" } else { "s" };
+                    format!(
+                        " Delete {} workspace{}? y: confirm  n: cancel",
+                        count, plural
+                    )
                 }
+                Mode::Cleanup(_) => {
+                    cleanup_help(app.cleanup_targets().len(), app.filtered_indices.len())
+                }
+                Mode::Browse if app.menu.is_some() => MENU_HELP.to_string(),
                 Mode::Browse if app.on_create_row() => {
                     " Enter: create (auto-name)  type: name it  q: quit".to_string()
                 }
@@ -650,17 +2075,49 @@ fn render(frame: &mut Frame, app: &mut App) {
                         String::new()
                     };
                     format!(
-                        " j/k: navigate  /: filter  s: sort ({})  p: preview  d: delete  Enter: select  q: quit{}",
-                        app.sort_mode.label(),
+                        "{}{}",
+                        browse_help(&app.keymap, app.sort_mode, SINGLE_REPO_ACTIONS),
                         filter_info
                     )
                 }
             };
-            (text, Style::default().fg(Color::DarkGray))
+            (text, Style::default().fg(app.theme.muted))
         };
         let help = Paragraph::new(help_text).style(help_style);
         frame.render_widget(help, help_area);
     }
+
+    if let Mode::Cleanup(ref cleanup) = app.mode
+        && cleanup.confirming
+    {
+        let targets: Vec<(String, &WorkspaceEntry)> = app
+            .cleanup_targets()
+            .into_iter()
+            .map(|e| (e.name.clone(), e))
+            .collect();
+        render_cleanup_confirm(frame, &targets, &app.theme);
+    }
+
+    if let Some(ref menu) = app.menu {
+        render_action_menu(frame, menu, &app.keymap, &app.theme);
+    }
+
+    if let Some(ref agents) = app.agents {
+        render_agent_popup(frame, agents, &app.theme);
+    }
+
+    if app.show_help {
+        render_help_popup(frame, &app.keymap, SINGLE_REPO_ACTIONS, &app.theme);
+    }
+}
+
+/// Whether the interactive picker can run: it draws on stderr and reads keys
+/// from stdin, and its stdout must be a terminal or captured by an older
+/// shell wrapper rather than piped into another program.
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+        && io::stderr().is_terminal()
+        && (io::stdout().is_terminal() || std::env::var_os(crate::shell::WRAPPER_ENV).is_some())
 }
 
 /// Event loop for the single-repo picker. `next_event` is injectable for
@@ -668,88 +2125,283 @@ fn render(frame: &mut Frame, app: &mut App) {
 ///
 /// `on_delete` performs the workspace deletion — returns `Ok(true)` if the
 /// caller already printed a redirect path (picker should exit), `Ok(false)`
-/// if the picker should refresh and continue.
+/// if the picker should refresh and continue. Its flag forces the deletion
+/// of a workspace with uncommitted work, and is only set when the prompt
+/// warned about that work.
+///
+/// `on_rename` performs a workspace rename from the old name to the new one,
+/// with the same return convention as `on_delete`.
+///
+/// `on_open` opens the given workspace path in the user's editor, blocking
+/// until it exits.
 ///
-/// `list_entries` is called after a successful non-redirect deletion to
-/// refresh the entry list.
+/// `on_copy` copies the given workspace path to the clipboard.
+///
+/// `on_push` pushes the workspace at the given path, returning the remote
+/// branch it went to.
+///
+/// `on_sync` rebases the workspace at the given path onto trunk, returning
+/// what it was rebased onto.
+///
+/// `on_browse` opens the given URL in the web browser.
+///
+/// `list_entries` is called after a successful non-redirect deletion or
+/// rename to refresh the entry list.
+#[allow(clippy::too_many_arguments)]
 fn run_picker_inner<B: Backend>(
     terminal: &mut Terminal<B>,
-    app: App,
+    app: &mut App,
     next_event: &mut dyn FnMut() -> Result<Option<Event>>,
-    on_delete: &mut dyn FnMut(&str) -> Result<bool>,
+    on_delete: &mut dyn FnMut(&str, bool) -> Result<bool>,
+    on_rename: &mut dyn FnMut(&str, &str) -> Result<bool>,
+    on_open: &mut dyn FnMut(&Path) -> Result<()>,
+    on_copy: &mut dyn FnMut(&str) -> Result<()>,
+    on_push: &mut dyn FnMut(&Path) -> Result<String>,
+    on_sync: &mut dyn FnMut(&Path) -> Result<String>,
+    on_browse: &mut dyn FnMut(&str) -> Result<()>,
     list_entries: &mut dyn FnMut() -> Result<Vec<WorkspaceEntry>>,
 ) -> Result<Option<PickerResult>> {
-    let mut app = app;
-
     loop {
-        // Drain mailboxes before drawing
+        // Drain mailboxes and expire status messages before drawing
         app.drain_preview_mailbox();
         app.drain_refresh_mailbox();
+        app.status.expire(Instant::now());
 
-        terminal.draw(|f| render(f, &mut app))?;
+        if app.loading {
+            app.spinner_tick = app.spinner_tick.wrapping_add(1);
+        }
+        terminal.draw(|f| render(f, app))?;
 
         let event = next_event()?;
         let Some(event) = event else {
             continue;
         };
 
+        if let Event::Mouse(mouse) = event {
+            if app.mode == Mode::Browse
+                && app.menu.is_none()
+                && app.agents.is_none()
+                && !app.show_help
+            {
+                let prev_selected = app.selected;
+                if let Some(result) = app.handle_mouse(&mouse) {
+                    return Ok(Some(result));
+                }
+                if app.selected != prev_selected {
+                    app.trigger_preview_fetch();
+                }
+            }
+            continue;
+        }
+
         if let Event::Key(key) = event {
             if key.kind != KeyEventKind::Press {
                 continue;
             }
 
             let prev_selected = app.selected;
-            app.status_message = None;
+
+            // Any key dismisses the help and agent popups.
+            if app.show_help || app.agents.is_some() {
+                app.show_help = false;
+                app.agents = None;
+                continue;
+            }
 
             match app.mode {
-                Mode::Browse => match key.code {
-                    KeyCode::Esc => return Ok(None),
-                    KeyCode::Down => app.next(),
-                    KeyCode::Up => app.previous(),
-                    KeyCode::Enter => {
-                        if app.on_create_row() {
-                            return Ok(Some(PickerResult::CreateNew(None)));
-                        } else if let Some(idx) = app.selected_entry_index() {
-                            let path = app.entries[idx].path.to_string_lossy().to_string();
-                            return Ok(Some(PickerResult::Selected(path)));
-                        }
-                    }
-                    KeyCode::Char(c) if app.on_create_row() => {
+                Mode::Browse => {
+                    let (action, count) = if app.menu.is_some() {
+                        (menu_key(&mut app.menu, &app.keymap, &key), None)
+                    } else if let KeyCode::Char(c) = key.code
+                        && app.on_create_row()
+                        && !key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        // Typing on the "+ Create new" row starts naming the workspace.
                         app.mode = Mode::InputName;
                         app.input_buf.clear();
                         app.input_buf.push(c);
-                    }
-                    KeyCode::Char('q') => return Ok(None),
-                    KeyCode::Char('j') => app.next(),
-                    KeyCode::Char('k') => app.previous(),
-                    KeyCode::Char('s') => {
-                        app.sort_mode = app.sort_mode.next();
-                        sort_entries(&mut app.entries, app.sort_mode);
-                        app.recompute_filter();
-                        app.selected = 0;
-                        app.sync_table_state();
-                    }
-                    KeyCode::Char('/') => {
-                        app.mode = Mode::Filter;
-                    }
-                    KeyCode::Char('p') => {
-                        app.show_preview = !app.show_preview;
-                        if app.show_preview {
-                            app.trigger_preview_fetch();
-                        } else {
-                            app.preview = PreviewState::Hidden;
+                        (None, None)
+                    } else {
+                        app.motion.feed(&key, app.keymap.action_for(&key))
+                    };
+                    match action {
+                        Some(Action::Quit) => return Ok(None),
+                        Some(Action::Help) => app.show_help = true,
+                        Some(
+                            action @ (Action::Down
+                            | Action::Up
+                            | Action::Top
+                            | Action::Bottom
+                            | Action::HalfPageDown
+                            | Action::HalfPageUp),
+                        ) => app.apply_motion(action, count),
+                        Some(Action::Select) => {
+                            if let Some(result) = app.activate() {
+                                return Ok(Some(result));
+                            }
                         }
-                    }
-                    KeyCode::Char('d') => {
-                        if let Some(idx) = app.selected_entry_index() {
-                            let entry = &app.entries[idx];
-                            if !entry.is_main {
-                                app.mode = Mode::ConfirmDelete(entry.name.clone());
+                        Some(Action::Sort) => app.set_sort_mode(app.sort_mode.next()),
+                        Some(Action::Filter) => {
+                            app.mode = Mode::Filter;
+                        }
+                        Some(Action::Preview) => {
+                            app.show_preview = !app.show_preview;
+                            if app.show_preview {
+                                app.trigger_preview_fetch();
+                            } else {
+                                app.preview = PreviewState::Hidden;
+                            }
+                        }
+                        Some(Action::PreviewGrow) => app.preview_layout.grow(),
+                        Some(Action::PreviewShrink) => app.preview_layout.shrink(),
+                        Some(Action::PreviewLayout) => {
+                            app.preview_layout.stacked = !app.preview_layout.stacked;
+                        }
+                        Some(Action::Delete) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let entry = &app.entries[idx];
+                                if !entry.is_main {
+                                    app.mode = Mode::ConfirmDelete(entry.name.clone());
+                                }
+                            }
+                        }
+                        Some(Action::Rename) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let entry = &app.entries[idx];
+                                if !entry.is_main {
+                                    app.input_buf = entry.name.clone();
+                                    app.mode = Mode::Rename(entry.name.clone());
+                                }
+                            }
+                        }
+                        Some(Action::Open) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let path = app.entries[idx].path.clone();
+                                if let Err(e) = on_open(&path) {
+                                    app.status.error(format!("open failed: {}", e));
+                                }
+                                terminal.clear()?;
+                            }
+                        }
+                        Some(Action::Copy) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let path = app.entries[idx].path.display().to_string();
+                                match on_copy(&path) {
+                                    Ok(()) => app.status.info(format!("copied {}", path)),
+                                    Err(e) => app.status.error(format!("copy failed: {}", e)),
+                                }
+                            }
+                        }
+                        Some(Action::Push) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let entry = &app.entries[idx];
+                                let (name, path) = (entry.name.clone(), entry.path.clone());
+                                let progress = format!("pushing {}…", name);
+                                app.status.info(progress.clone());
+                                terminal.draw(|f| render(f, app))?;
+                                let pushed = on_push(&path);
+                                app.status.dismiss(&progress);
+                                match pushed {
+                                    Ok(remote) => {
+                                        app.status.info(format!("pushed {} to {}", name, remote))
+                                    }
+                                    Err(e) => app.status.error(format!("push failed: {}", e)),
+                                }
+                            }
+                        }
+                        Some(Action::Sync) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let entry = &app.entries[idx];
+                                let (name, path) = (entry.name.clone(), entry.path.clone());
+                                let progress = format!("rebasing {}…", name);
+                                app.status.info(progress.clone());
+                                terminal.draw(|f| render(f, app))?;
+                                let synced = on_sync(&path);
+                                app.status.dismiss(&progress);
+                                match synced {
+                                    Ok(onto) => {
+                                        if let Ok(new_entries) = list_entries()
+                                            && !new_entries.is_empty()
+                                        {
+                                            app.merge_entries(new_entries);
+                                            app.trigger_preview_fetch();
+                                        }
+                                        app.status.info(format!("rebased {} onto {}", name, onto));
+                                    }
+                                    Err(e) => app.status.error(format!("sync failed: {}", e)),
+                                }
+                            }
+                        }
+                        Some(Action::NextWaiting) => {
+                            let found = app.next_waiting();
+                            if !found {
+                                app.status.info("no agents waiting");
+                            }
+                        }
+                        Some(Action::Cleanup) => app.start_cleanup(),
+                        Some(Action::Launch) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                return Ok(Some(PickerResult::Launch(
+                                    app.entries[idx].path.to_string_lossy().to_string(),
+                                )));
                             }
                         }
+                        Some(Action::Tmux) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                return Ok(Some(PickerResult::Tmux(
+                                    app.entries[idx].path.to_string_lossy().to_string(),
+                                )));
+                            }
+                        }
+                        Some(Action::Pr) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                return Ok(Some(PickerResult::Pr(
+                                    app.entries[idx].path.to_string_lossy().to_string(),
+                                )));
+                            }
+                        }
+                        Some(Action::Ci) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let entry = &app.entries[idx];
+                                let (name, url) = (
+                                    entry.name.clone(),
+                                    entry.ci.as_ref().map(|ci| ci.url.clone()),
+                                );
+                                match url.filter(|url| !url.is_empty()) {
+                                    Some(url) => match on_browse(&url) {
+                                        Ok(()) => app.status.info(format!("opened {}", url)),
+                                        Err(e) => app
+                                            .status
+                                            .error(format!("could not open CI run: {}", e)),
+                                    },
+                                    None => app.status.info(format!("no CI run for {}", name)),
+                                }
+                            }
+                        }
+                        Some(Action::Agents) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                let entry = &app.entries[idx];
+                                let sessions = match app.repo_dir {
+                                    Some(ref dir) => agent::read_agent_sessions(dir, &entry.name),
+                                    None => Vec::new(),
+                                };
+                                app.agents = Some(AgentPopup {
+                                    title: entry.name.clone(),
+                                    sessions,
+                                });
+                            }
+                        }
+                        Some(Action::Menu) => {
+                            if let Some(idx) = app.selected_entry_index() {
+                                app.menu = Some(ActionMenu::for_entry(
+                                    &app.entries[idx],
+                                    SINGLE_REPO_MENU,
+                                ));
+                            }
+                        }
+                        Some(Action::Collapse) | None => {}
                     }
-                    _ => {}
-                },
+                }
                 Mode::InputName => match key.code {
                     KeyCode::Esc => {
                         app.mode = Mode::Browse;
@@ -761,6 +2413,11 @@ fn run_picker_inner<B: Backend>(
                         } else {
                             Some(app.input_buf.clone())
                         };
+                        // A bad name keeps the input open to fix it.
+                        if let Some(err) = name.as_deref().and_then(|n| app.invalid_name(n)) {
+                            app.status.error(err);
+                            continue;
+                        }
                         return Ok(Some(PickerResult::CreateNew(name)));
                     }
                     KeyCode::Backspace => {
@@ -797,7 +2454,19 @@ fn run_picker_inner<B: Backend>(
                     KeyCode::Char('y') => {
                         let name = name.clone();
                         app.mode = Mode::Browse;
-                        let redirected = on_delete(&name)?;
+                        // Only work the prompt warned about is deleted.
+                        let force = app
+                            .entries
+                            .iter()
+                            .find(|e| e.name == name)
+                            .is_some_and(|e| e.working_copy.is_dirty());
+                        let redirected = match on_delete(&name, force) {
+                            Ok(redirected) => redirected,
+                            Err(e) => {
+                                app.status.error(format!("delete failed: {}", e));
+                                continue;
+                            }
+                        };
                         if redirected {
                             return Ok(None);
                         }
@@ -814,13 +2483,107 @@ fn run_picker_inner<B: Backend>(
                         }
                         app.sync_table_state();
                         app.trigger_preview_fetch();
-                        app.status_message = Some(format!("workspace '{}' deleted", name));
+                        app.status.info(format!("workspace '{}' deleted", name));
                     }
                     KeyCode::Char('n') | KeyCode::Esc => {
                         app.mode = Mode::Browse;
                     }
                     _ => {}
                 },
+                Mode::Cleanup(ref mut cleanup) if cleanup.confirming => match key.code {
+                    KeyCode::Char('y') => {
+                        let targets: Vec<(String, PathBuf)> = app
+                            .cleanup_targets()
+                            .into_iter()
+                            .map(|e| (e.name.clone(), e.path.clone()))
+                            .collect();
+                        app.mode = Mode::Browse;
+                        let (redirected, level, message) =
+                            delete_all(&targets, |name, _| on_delete(name, false));
+                        if redirected {
+                            return Ok(None);
+                        }
+                        let new_entries = list_entries()?;
+                        if new_entries.is_empty() {
+                            return Ok(None);
+                        }
+                        app.merge_entries(new_entries);
+                        app.trigger_preview_fetch();
+                        app.status.push(level, message);
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => cleanup.confirming = false,
+                    _ => {}
+                },
+                Mode::Cleanup(_) => {
+                    let (action, count) = app.motion.feed(&key, app.keymap.action_for(&key));
+                    match (key.code, action) {
+                        (KeyCode::Char(' '), _) => app.toggle_cleanup_mark(),
+                        (_, Some(Action::Select)) => {
+                            if app.cleanup_targets().is_empty() {
+                                app.status.info("nothing marked");
+                            } else if let Mode::Cleanup(ref mut cleanup) = app.mode {
+                                cleanup.confirming = true;
+                            }
+                        }
+                        (_, Some(Action::Quit | Action::Cleanup)) => app.end_cleanup(),
+                        (
+                            _,
+                            Some(
+                                action @ (Action::Down
+                                | Action::Up
+                                | Action::Top
+                                | Action::Bottom
+                                | Action::HalfPageDown
+                                | Action::HalfPageUp),
+                            ),
+                        ) => app.apply_motion(action, count),
+                        _ => {}
+                    }
+                }
+                Mode::Rename(ref old_name) => match key.code {
+                    KeyCode::Esc => {
+                        app.mode = Mode::Browse;
+                        app.input_buf.clear();
+                    }
+                    KeyCode::Enter => {
+                        let old_name = old_name.clone();
+                        let new_name = app.input_buf.trim().to_string();
+                        if !new_name.is_empty()
+                            && let Some(err) = app.invalid_name(&new_name)
+                        {
+                            app.status.error(err);
+                            continue;
+                        }
+                        app.mode = Mode::Browse;
+                        app.input_buf.clear();
+                        if new_name.is_empty() || new_name == old_name {
+                            continue;
+                        }
+                        match on_rename(&old_name, &new_name) {
+                            Ok(true) => return Ok(None),
+                            Ok(false) => {
+                                let new_entries = list_entries()?;
+                                app.merge_entries(new_entries);
+                                app.select_by_name(&new_name);
+                                app.trigger_preview_fetch();
+                                app.status.info(format!(
+                                    "workspace '{}' renamed to '{}'",
+                                    old_name, new_name
+                                ));
+                            }
+                            Err(e) => {
+                                app.status.error(format!("rename failed: {}", e));
+                            }
+                        }
+                    }
+                    KeyCode::Backspace => {
+                        app.input_buf.pop();
+                    }
+                    KeyCode::Char(c) => {
+                        app.input_buf.push(c);
+                    }
+                    _ => {}
+                },
             }
 
             // Trigger preview fetch on selection change
@@ -834,18 +2597,35 @@ fn run_picker_inner<B: Backend>(
 /// Launch the interactive TUI workspace picker for a single repo.
 ///
 /// Switches the terminal to an alternate screen in raw mode, runs the event
-/// loop, then restores the terminal before returning.
+/// loop, then restores the terminal before returning. `settings` supplies the
+/// key bindings and table columns.
+///
+/// `entries` may be a quick name-only listing (see
+/// [`crate::workspace::list_workspace_names`]): the picker shows a spinner
+/// until the background refresh delivers full entries.
 ///
 /// `on_delete` is called when the user confirms deletion of a workspace.
 /// It should return `Ok(true)` if a redirect path was printed (picker exits),
-/// or `Ok(false)` to refresh and continue.
+/// or `Ok(false)` to refresh and continue. It's asked to force the deletion
+/// only when the prompt showed the workspace's uncommitted work; otherwise
+/// it should refuse to delete a dirty workspace, as the picker's view of it
+/// may be out of date.
 ///
-/// `list_entries` is called after a non-redirect deletion to get the fresh
-/// entry list.
+/// `on_rename` is called with the old and new names when the user confirms a
+/// rename. It follows the same return convention as `on_delete`.
+///
+/// `on_open` is called with the selected workspace path when the user presses
+/// `o`; the TUI is suspended while it runs.
+///
+/// `list_entries` is called after a non-redirect deletion or rename to get the
+/// fresh entry list.
 pub fn run_picker(
     entries: Vec<WorkspaceEntry>,
     repo_dir: PathBuf,
-    mut on_delete: impl FnMut(&str) -> Result<bool>,
+    settings: PickerSettings,
+    mut on_delete: impl FnMut(&str, bool) -> Result<bool>,
+    mut on_rename: impl FnMut(&str, &str) -> Result<bool>,
+    mut on_open: impl FnMut(&Path) -> Result<()>,
     mut list_entries: impl FnMut() -> Result<Vec<WorkspaceEntry>>,
 ) -> Result<Option<PickerResult>> {
     if entries.is_empty() {
@@ -856,19 +2636,33 @@ pub fn run_picker(
     enable_raw_mode()?;
     let mut stderr = io::stderr();
     crossterm::execute!(stderr, EnterAlternateScreen)?;
+    let mouse = settings.mouse;
+    if mouse {
+        crossterm::execute!(stderr, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
 
     // Set up background refresh threads
-    let app = App::new(entries);
+    let mut app = App::new(entries);
+    app.keymap = settings.keymap;
+    app.repo_dir = Some(repo_dir.clone());
+    app.columns = columns::resolve(settings.columns.as_deref(), false);
+    app.theme = settings.theme;
+    // The refresh thread polls immediately, replacing the quick listing.
+    app.loading = true;
+    app.apply_ui_state(&UiState::load(&repo_dir));
     let stop = Arc::new(StopSignal::new());
 
     let agent_sender = app.agent_refresh_mailbox.sender();
     let refresh_sender = app.refresh_mailbox.sender();
+    let refresh_error_sender = app.refresh_error_mailbox.sender();
 
-    // Agent status polling thread (~2s)
+    // Agent status thread: on each status file change, or every ~2s where
+    // that can't be watched
     let agent_repo_dir = repo_dir.clone();
-    let agent_thread = spawn_refresh_thread(
+    let agent_thread = spawn_watched_refresh_thread(
+        &[agent::agent_status_dir(&repo_dir)],
         std::time::Duration::from_secs(2),
         Arc::clone(&stop),
         agent_sender,
@@ -880,12 +2674,27 @@ pub fn run_picker(
         std::time::Duration::from_secs(10),
         Arc::clone(&stop),
         refresh_sender,
-        move || crate::workspace::list_workspace_entries().ok(),
+        posting_errors(
+            refresh_error_sender,
+            crate::workspace::list_workspace_entries,
+        ),
+    );
+
+    // Forge thread (every couple of minutes): relists as soon as new PR
+    // or CI state is in, rather than waiting for the next VCS refresh
+    let pr_thread = spawn_refresh_thread(
+        crate::forge::FORGE_TTL,
+        Arc::clone(&stop),
+        app.refresh_mailbox.sender(),
+        || {
+            crate::workspace::refresh_forge()
+                .and_then(|_| crate::workspace::list_workspace_entries().ok())
+        },
     );
 
     let result = run_picker_inner(
         &mut terminal,
-        app,
+        &mut app,
         &mut || {
             if event::poll(std::time::Duration::from_millis(100))? {
                 Ok(Some(event::read()?))
@@ -894,16 +2703,30 @@ pub fn run_picker(
             }
         },
         &mut on_delete,
+        &mut on_rename,
+        &mut |path| with_suspended_terminal(mouse, || on_open(path)),
+        &mut |text| crate::clipboard::copy(text),
+        &mut |path| crate::workspace::push_workspace_at(path),
+        &mut |path| crate::workspace::sync_workspace_at(path),
+        &mut |url| crate::browser::open(url),
         &mut list_entries,
     );
 
     // Signal background threads to stop (wakes them immediately)
+    // Remembering state is best-effort; never fail the picker over it.
+    let _ = app.ui_state().save(&repo_dir);
+
     stop.stop();
     let _ = agent_thread.join();
     let _ = refresh_thread.join();
+    let _ = pr_thread.join();
 
     disable_raw_mode()?;
-    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
     result
@@ -912,20 +2735,84 @@ pub fn run_picker(
 // ── Multi-repo picker (--all mode) ──────────────────────────────
 
 /// State for the multi-repo (`--all`) interactive picker.
+/// A step in the `--all` picker's create flow.
+#[derive(Debug, Clone, PartialEq)]
+enum CreateStep {
+    /// Choosing the target repo from `(name, main workspace path)` pairs.
+    ChooseRepo {
+        repos: Vec<(String, PathBuf)>,
+        selected: usize,
+    },
+    /// Typing the workspace name for the chosen repo.
+    Name { repo_name: String, repo: PathBuf },
+}
+
+/// A row in the `--all` picker table, which groups workspaces by repo.
+#[derive(Debug, Clone, PartialEq)]
+enum MultiRow {
+    /// Section header for the repo with this name.
+    Group(String),
+    /// A workspace; value is an index into `entries`.
+    Entry(usize),
+    /// The "+ Create new" sentinel row.
+    Create,
+}
+
+/// Name of the repo group `entry` belongs to.
+fn group_name(entry: &WorkspaceEntry) -> &str {
+    entry.repo_name.as_deref().unwrap_or("")
+}
+
 struct MultiRepoApp {
     entries: Vec<WorkspaceEntry>,
     selected: usize,
     sort_mode: SortMode,
     filter_buf: String,
     filtered_indices: Vec<usize>,
+    /// Display rows: repo headers, each followed by its filtered workspaces
+    /// unless collapsed, then the create row.
+    rows: Vec<MultiRow>,
+    /// Names of repo groups whose workspaces are hidden.
+    collapsed: HashSet<String>,
     /// Whether the user is currently typing a filter string.
     filter_mode: bool,
+    /// Workspace awaiting delete confirmation: display label and path.
+    confirm_delete: Option<(String, PathBuf)>,
+    /// Progress through the create flow, if one is underway.
+    create: Option<CreateStep>,
+    /// Cleanup mode state, while reviewing stale workspaces.
+    cleanup: Option<Cleanup>,
+    /// Text typed so far for the new workspace name.
+    input_buf: String,
     show_preview: bool,
+    preview_layout: PreviewLayout,
     preview: PreviewState,
     preview_mailbox: Arc<Mutex<Option<PreviewState>>>,
+    /// Whether a fetch into `preview_mailbox` has not yet been drained, so
+    /// periodic refreshes don't pile up behind a slow backend.
+    preview_pending: bool,
     table_state: TableState,
+    /// Status messages shown in the help bar (e.g. after an error).
+    status: StatusQueue,
+    /// Effective key bindings for browse mode.
+    keymap: KeyMap,
+    /// The `~/.dwm` directory, where each repo's agent status files live.
+    dwm_base: Option<PathBuf>,
+    /// Whether the `?` help popup is open.
+    show_help: bool,
+    /// The open action menu, if any.
+    menu: Option<ActionMenu>,
+    /// The open agent session popup, if any.
+    agents: Option<AgentPopup>,
+    motion: Motion,
+    /// Table columns in display order.
+    columns: Vec<ColumnSpec>,
+    theme: Theme,
+    mouse: MouseState,
     /// Receives full workspace entry refreshes from background thread.
     refresh_mailbox: Mailbox<Vec<WorkspaceEntry>>,
+    /// Receives the error from a failed background refresh.
+    refresh_error_mailbox: Mailbox<String>,
     /// Receives agent status updates from background thread.
     agent_refresh_mailbox: Mailbox<HashMap<String, AgentSummary>>,
 }
@@ -936,37 +2823,149 @@ impl MultiRepoApp {
         let sort_mode = SortMode::Recency;
         sort_entries(&mut entries, sort_mode);
         let filtered_indices: Vec<usize> = (0..entries.len()).collect();
-        Self {
+        let mut app = Self {
             selected: 0,
             entries,
             sort_mode,
             filter_buf: String::new(),
             filtered_indices,
+            rows: Vec::new(),
+            collapsed: HashSet::new(),
             filter_mode: false,
+            confirm_delete: None,
+            create: None,
+            cleanup: None,
+            input_buf: String::new(),
             show_preview: false,
+            preview_layout: PreviewLayout::default(),
             preview: PreviewState::Hidden,
             preview_mailbox: Arc::new(Mutex::new(None)),
+            preview_pending: false,
             table_state: TableState::default().with_selected(0),
+            status: StatusQueue::default(),
+            keymap: KeyMap::default(),
+            dwm_base: None,
+            show_help: false,
+            menu: None,
+            agents: None,
+            motion: Motion::default(),
+            columns: columns::resolve(None, true),
+            theme: Theme::default(),
+            mouse: MouseState::default(),
             refresh_mailbox: Mailbox::new(),
+            refresh_error_mailbox: Mailbox::new(),
             agent_refresh_mailbox: Mailbox::new(),
+        };
+        app.rebuild_rows();
+        app.selected = app.first_entry_row();
+        app.sync_table_state();
+        app
+    }
+
+    /// Rebuild `rows` from `filtered_indices` and `collapsed`. Groups are
+    /// ordered by repo name; workspaces keep their filtered order.
+    fn rebuild_rows(&mut self) {
+        let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+        for &i in &self.filtered_indices {
+            let name = group_name(&self.entries[i]);
+            match groups.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, indices)) => indices.push(i),
+                None => groups.push((name, vec![i])),
+            }
+        }
+        groups.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut rows = Vec::new();
+        for (name, indices) in groups {
+            rows.push(MultiRow::Group(name.to_string()));
+            if !self.collapsed.contains(name) {
+                rows.extend(indices.into_iter().map(MultiRow::Entry));
+            }
         }
+        rows.push(MultiRow::Create);
+        self.rows = rows;
     }
 
-    /// Return only the entries that pass the current filter, in display order.
-    fn visible_entries(&self) -> Vec<&WorkspaceEntry> {
-        self.filtered_indices
+    /// Index of the first workspace row, or 0 if every group is collapsed.
+    fn first_entry_row(&self) -> usize {
+        self.rows
             .iter()
-            .map(|&i| &self.entries[i])
-            .collect()
+            .position(|r| matches!(r, MultiRow::Entry(_)))
+            .unwrap_or(0)
     }
 
-    /// Total number of selectable rows.
+    /// Total number of selectable rows, including group headers and the
+    /// "+ Create new" sentinel row.
     fn total_rows(&self) -> usize {
-        self.filtered_indices.len()
+        self.rows.len()
+    }
+
+    /// Return `true` when the cursor is on the "+ Create new" row.
+    fn on_create_row(&self) -> bool {
+        self.rows.get(self.selected) == Some(&MultiRow::Create)
+    }
+
+    /// Collapse the group named `name` if expanded, or expand it if
+    /// collapsed, leaving the cursor on its header.
+    fn toggle_group(&mut self, name: &str) {
+        if !self.collapsed.remove(name) {
+            self.collapsed.insert(name.to_string());
+        }
+        self.rebuild_rows();
+        self.selected = self
+            .rows
+            .iter()
+            .position(|r| matches!(r, MultiRow::Group(n) if n == name))
+            .unwrap_or(0);
+        self.sync_table_state();
     }
 
+    /// Collapse or expand the group under the cursor: the header's own group,
+    /// or the group containing the selected workspace.
+    fn toggle_selected_group(&mut self) {
+        let name = match self.rows.get(self.selected) {
+            Some(MultiRow::Group(name)) => name.clone(),
+            Some(MultiRow::Entry(i)) => group_name(&self.entries[*i]).to_string(),
+            _ => return,
+        };
+        self.toggle_group(&name);
+    }
+
+    /// Tracked repos as `(name, main workspace path)` pairs, sorted by name.
+    fn repo_choices(&self) -> Vec<(String, PathBuf)> {
+        let mut repos: Vec<(String, PathBuf)> = Vec::new();
+        for entry in &self.entries {
+            if !repos.iter().any(|(_, path)| *path == entry.main_repo_path) {
+                let name = entry.repo_name.clone().unwrap_or_default();
+                repos.push((name, entry.main_repo_path.clone()));
+            }
+        }
+        repos.sort();
+        repos
+    }
+
+    /// Start the create flow: choose a repo first, or go straight to naming
+    /// when only one repo is tracked.
+    fn start_create(&mut self) {
+        self.input_buf.clear();
+        let mut repos = self.repo_choices();
+        self.create = match repos.len() {
+            0 => None,
+            1 => {
+                let (repo_name, repo) = repos.remove(0);
+                Some(CreateStep::Name { repo_name, repo })
+            }
+            _ => Some(CreateStep::ChooseRepo { repos, selected: 0 }),
+        };
+    }
+
+    /// Return the index into `entries` for the currently selected row, or
+    /// `None` when the cursor is on a group header or the create row.
     fn selected_entry_index(&self) -> Option<usize> {
-        self.filtered_indices.get(self.selected).copied()
+        match self.rows.get(self.selected) {
+            Some(MultiRow::Entry(i)) => Some(*i),
+            _ => None,
+        }
     }
 
     /// Move the cursor down one row (wrapping).
@@ -987,36 +2986,139 @@ impl MultiRepoApp {
         self.sync_table_state();
     }
 
+    /// Move the cursor for a navigation `action`, repeated `count` times.
+    fn apply_motion(&mut self, action: Action, count: Option<usize>) {
+        let half_page = self.mouse.half_page();
+        self.selected = motion_target(action, count, self.selected, self.total_rows(), half_page);
+        self.sync_table_state();
+    }
+
+    /// Move the cursor to the next visible workspace, after the current row
+    /// and wrapping around, whose agents are waiting for input. Returns
+    /// `false` if there is none.
+    fn next_waiting(&mut self) -> bool {
+        let total = self.total_rows();
+        let found = (1..=total)
+            .map(|step| (self.selected + step) % total)
+            .find(|&row| {
+                matches!(self.rows.get(row), Some(MultiRow::Entry(i)) if has_waiting_agent(&self.entries[*i]))
+            });
+        if let Some(row) = found {
+            self.selected = row;
+            self.sync_table_state();
+        }
+        found.is_some()
+    }
+
     fn sync_table_state(&mut self) {
         self.table_state.select(Some(self.selected));
     }
 
+    /// Switch to `mode`, re-sort, and move the cursor to the first workspace.
+    fn set_sort_mode(&mut self, mode: SortMode) {
+        self.sort_mode = mode;
+        sort_entries(&mut self.entries, self.sort_mode);
+        self.recompute_filter();
+        self.selected = self.first_entry_row();
+        self.sync_table_state();
+    }
+
+    /// Activate the current row: start the create flow on the sentinel row,
+    /// toggle a group on its header, otherwise select the workspace.
+    fn activate(&mut self) -> Option<PickerResult> {
+        match self.rows.get(self.selected) {
+            Some(MultiRow::Create) => {
+                self.start_create();
+                None
+            }
+            Some(MultiRow::Group(name)) => {
+                let name = name.clone();
+                self.toggle_group(&name);
+                None
+            }
+            Some(MultiRow::Entry(idx)) => Some(PickerResult::Selected(
+                self.entries[*idx].path.to_string_lossy().to_string(),
+            )),
+            None => None,
+        }
+    }
+
+    /// Handle a mouse event in browse mode. Returns a result when a
+    /// double-click activates a row.
+    fn handle_mouse(&mut self, event: &MouseEvent) -> Option<PickerResult> {
+        match self
+            .mouse
+            .target(event, &self.columns, self.table_state.offset())?
+        {
+            MouseTarget::ScrollDown if self.selected + 1 < self.total_rows() => self.next(),
+            MouseTarget::ScrollUp if self.selected > 0 => self.previous(),
+            MouseTarget::Header(column) => {
+                if let Some(mode) = SortMode::for_column(column) {
+                    self.set_sort_mode(mode);
+                }
+            }
+            MouseTarget::Row(row) if row < self.total_rows() => {
+                self.selected = row;
+                self.sync_table_state();
+                if self.mouse.register_click(row) {
+                    return self.activate();
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
     fn trigger_preview_fetch(&mut self) {
         if !self.show_preview {
             return;
         }
         if let Some(idx) = self.selected_entry_index() {
-            let entry = &self.entries[idx];
             self.preview = PreviewState::Loading;
-            let mailbox = Arc::new(Mutex::new(None));
-            self.preview_mailbox = Arc::clone(&mailbox);
-            fetch_preview(
-                entry.main_repo_path.clone(),
-                entry.path.clone(),
-                entry.name.clone(),
-                entry.vcs_type,
-                mailbox,
-            );
+            self.start_preview_fetch(idx);
         } else {
             self.preview = PreviewState::Hidden;
         }
     }
 
+    /// Re-fetch the visible preview in place, keeping the current contents
+    /// on screen until the new ones arrive. Skipped while a fetch is pending.
+    fn refresh_preview(&mut self) {
+        if !self.show_preview || self.preview_pending {
+            return;
+        }
+        if let Some(idx) = self.selected_entry_index() {
+            self.start_preview_fetch(idx);
+        }
+    }
+
+    /// Fetch the preview for `entries[idx]` in the background, replacing the
+    /// mailbox so results for a previous selection are discarded.
+    fn start_preview_fetch(&mut self, idx: usize) {
+        let entry = &self.entries[idx];
+        let mailbox = Arc::new(Mutex::new(None));
+        self.preview_mailbox = Arc::clone(&mailbox);
+        self.preview_pending = true;
+        let agent_repo_dir = self
+            .dwm_base
+            .as_ref()
+            .map(|base| base.join(crate::vcs::repo_dir_name(&entry.main_repo_path)));
+        fetch_preview(
+            entry.main_repo_path.clone(),
+            entry.path.clone(),
+            entry.name.clone(),
+            entry.vcs_type,
+            agent_repo_dir,
+            mailbox,
+        );
+    }
+
     fn drain_preview_mailbox(&mut self) {
         if let Ok(mut guard) = self.preview_mailbox.try_lock()
             && let Some(state) = guard.take()
         {
             self.preview = state;
+            self.preview_pending = false;
         }
     }
 
@@ -1033,53 +3135,223 @@ impl MultiRepoApp {
                 );
                 entry.agent_status = summaries.get(&key).cloned();
             }
+            // Only agent updates move workspaces in this mode.
+            if self.sort_mode == SortMode::Waiting {
+                let entries = std::mem::take(&mut self.entries);
+                self.merge_entries(entries);
+            }
         }
 
-        // Check full entry refresh (~10s interval)
+        // Check full entry refresh (~10s interval); re-fetch the preview on
+        // the same cycle so it picks up new commits.
         if let Some(new_entries) = self.refresh_mailbox.take() {
-            let selected_name = self
-                .selected_entry_index()
-                .map(|idx| self.entries[idx].name.clone());
+            self.merge_entries(new_entries);
+            self.refresh_preview();
+        }
+        if let Some(error) = self.refresh_error_mailbox.take() {
+            self.status.error(format!("refresh failed: {}", error));
+        }
+    }
 
-            self.entries = new_entries;
-            sort_entries(&mut self.entries, self.sort_mode);
-            self.recompute_filter();
+    /// Merge a fresh set of entries, preserving current selection and
+    /// sort/filter. Selection is matched by path since workspace names can
+    /// repeat across repos.
+    fn merge_entries(&mut self, new_entries: Vec<WorkspaceEntry>) {
+        let selected_path = self
+            .selected_entry_index()
+            .map(|idx| self.entries[idx].path.clone());
+
+        self.entries = new_entries;
+        sort_entries(&mut self.entries, self.sort_mode);
+        self.recompute_filter();
 
-            if let Some(ref name) = selected_name {
-                let new_selected = self
-                    .filtered_indices
+        self.selected = selected_path
+            .and_then(|path| {
+                self.rows
                     .iter()
-                    .position(|&i| self.entries[i].name == *name)
-                    .unwrap_or(0);
-                self.selected = new_selected;
-            } else {
-                self.selected = 0;
-            }
-            if self.selected >= self.total_rows() {
-                self.selected = self.total_rows().saturating_sub(1);
-            }
-            self.sync_table_state();
+                    .position(|r| matches!(r, MultiRow::Entry(i) if self.entries[*i].path == path))
+            })
+            .unwrap_or_else(|| self.first_entry_row());
+        if self.selected >= self.total_rows() {
+            self.selected = self.total_rows().saturating_sub(1);
         }
+        self.sync_table_state();
     }
 
-    /// Recompute `filtered_indices` after `filter_buf` has changed.
+    /// Recompute `filtered_indices` and `rows` after `filter_buf` has changed.
+    /// Cleanup mode further narrows them to stale workspaces.
     fn recompute_filter(&mut self) {
-        if self.filter_buf.is_empty() {
-            self.filtered_indices = (0..self.entries.len()).collect();
-        } else {
-            self.filtered_indices = self
-                .entries
-                .iter()
-                .enumerate()
-                .filter(|(_, e)| matches_filter(e, &self.filter_buf))
-                .map(|(i, _)| i)
-                .collect();
+        self.filtered_indices = filtered_indices(&self.entries, &self.filter_buf);
+        if self.cleanup.is_some() {
+            let entries = &self.entries;
+            self.filtered_indices
+                .retain(|&i| is_cleanup_candidate(&entries[i]));
         }
+        self.rebuild_rows();
         if self.selected >= self.total_rows() {
             self.selected = self.total_rows().saturating_sub(1);
         }
         self.sync_table_state();
     }
+
+    /// Whether the help bar is prompting for input, which takes precedence
+    /// over status messages.
+    fn awaiting_input(&self) -> bool {
+        self.confirm_delete.is_some()
+            || self.create.is_some()
+            || self.filter_mode
+            || self.menu.is_some()
+            || self.cleanup.as_ref().is_some_and(|c| c.confirming)
+    }
+
+    /// Enter cleanup mode with every stale workspace marked, or report that
+    /// there is nothing to clean up.
+    fn start_cleanup(&mut self) {
+        match Cleanup::start(&self.entries) {
+            Some(cleanup) => {
+                self.cleanup = Some(cleanup);
+                self.recompute_filter();
+                self.selected = self.first_entry_row();
+                self.sync_table_state();
+            }
+            None => self.status.info("no stale workspaces"),
+        }
+    }
+
+    /// Leave cleanup mode, showing every workspace that passes the filter.
+    fn end_cleanup(&mut self) {
+        self.cleanup = None;
+        self.recompute_filter();
+    }
+
+    /// Mark or unmark the selected workspace in cleanup mode.
+    fn toggle_cleanup_mark(&mut self) {
+        if let Some(idx) = self.selected_entry_index()
+            && let Some(ref mut cleanup) = self.cleanup
+        {
+            cleanup.toggle(&self.entries[idx].path);
+        }
+    }
+
+    /// The visible workspaces marked in cleanup mode, in display order, with
+    /// their `repo/name` labels.
+    fn cleanup_targets(&self) -> Vec<(String, &WorkspaceEntry)> {
+        let Some(ref cleanup) = self.cleanup else {
+            return Vec::new();
+        };
+        self.rows
+            .iter()
+            .filter_map(|row| match row {
+                MultiRow::Entry(i) => Some(&self.entries[*i]),
+                _ => None,
+            })
+            .filter(|e| cleanup.marked.contains(&e.path))
+            .map(|e| (format!("{}/{}", group_name(e), e.name), e))
+            .collect()
+    }
+
+    /// Section header text for the repo group `name`: a collapse marker, the
+    /// repo name, and its workspace and waiting-agent totals.
+    fn group_header(&self, name: &str) -> Line<'static> {
+        let in_group = self.entries.iter().filter(|e| group_name(e) == name);
+        let (count, waiting) = in_group.fold((0, 0), |(count, waiting), e| {
+            let w = e.agent_status.as_ref().map_or(0, |s| s.waiting);
+            (count + 1, waiting + w)
+        });
+        let marker = if self.collapsed.contains(name) {
+            "▸"
+        } else {
+            "▾"
+        };
+        let plural = if count == 1 { "" } else { "s" };
+        let mut spans = vec![
+            Span::styled(
+                format!("{} {}", marker, name),
+                Style::default()
+                    .fg(self.theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {} workspace{}", count, plural),
+                Style::default().fg(self.theme.muted),
+            ),
+        ];
+        if waiting > 0 {
+            spans.push(Span::styled(
+                format!(", {} waiting", waiting),
+                Style::default().fg(self.theme.warning),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    /// Agent totals across every repo for the table title, like
+    /// `3 waiting · 5 working across 4 repos`, or `None` with no agent busy.
+    fn agent_totals(&self) -> Option<String> {
+        let mut repos = HashSet::new();
+        let (mut waiting, mut working) = (0, 0);
+        for entry in &self.entries {
+            let Some(summary) = &entry.agent_status else {
+                continue;
+            };
+            if summary.waiting + summary.working > 0 {
+                repos.insert(group_name(entry));
+            }
+            waiting += summary.waiting;
+            working += summary.working;
+        }
+        let parts: Vec<String> = [(waiting, "waiting"), (working, "working")]
+            .into_iter()
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, label)| format!("{} {}", n, label))
+            .collect();
+        if parts.is_empty() {
+            return None;
+        }
+        let plural = if repos.len() == 1 { "" } else { "s" };
+        Some(format!(
+            "{} across {} repo{}",
+            parts.join(" · "),
+            repos.len(),
+            plural
+        ))
+    }
+}
+
+/// Render the repo chooser for the `--all` picker's create flow.
+fn render_repo_chooser(
+    frame: &mut Frame,
+    repos: &[(String, PathBuf)],
+    selected: usize,
+    theme: &Theme,
+) {
+    let lines: Vec<Line> = repos
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| {
+            let style = if i == selected {
+                Style::default().fg(theme.accent).bg(theme.highlight)
+            } else {
+                Style::default()
+            };
+            Line::styled(format!(" {} ", name), style)
+        })
+        .collect();
+    let width = repos
+        .iter()
+        .map(|(name, _)| width::of(name) as u16 + 4)
+        .max()
+        .unwrap_or(0)
+        .max(24);
+    let area = centered_rect(frame.area(), width, lines.len() as u16 + 2);
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Create in repo ")
+            .title_alignment(Alignment::Center),
+    );
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup, area);
 }
 
 /// Render the multi-repo workspace table and help bar into `frame`.
@@ -1094,174 +3366,259 @@ fn render_multi_repo(frame: &mut Frame, app: &mut MultiRepoApp) {
         (full_area, None)
     };
 
-    // Split horizontally if preview is visible
+    // Split off the preview pane if visible
     let (table_area, preview_area) = if app.show_preview {
-        let chunks = Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
-            .split(main_area);
-        (chunks[0], Some(chunks[1]))
+        let (table_area, preview_area) = app.preview_layout.split(main_area);
+        (table_area, Some(preview_area))
     } else {
         (main_area, None)
     };
 
-    let header_cells = [
-        "Repo",
-        "Name",
-        "Change",
-        "Description",
-        "Bookmarks",
-        "Modified",
-        "Changes",
-        "Agent",
-    ]
-    .iter()
-    .map(|h| Cell::from(*h).style(Style::default().fg(Color::White).bold()));
-    let header = Row::new(header_cells)
-        .style(Style::default().bg(Color::DarkGray))
-        .height(1);
-
-    let visible = app.visible_entries();
-    let rows: Vec<Row> = visible
-        .iter()
-        .map(|entry| {
-            let repo_text = entry.repo_name.as_deref().unwrap_or("").to_string();
-
-            let name_text = if entry.is_main {
-                format!("{} (main)", entry.name)
-            } else if entry.is_stale {
-                format!("{} [stale]", entry.name)
-            } else {
-                entry.name.clone()
-            };
-
-            let change_text = entry.change_id.clone();
-            let desc_text = entry.description.lines().next().unwrap_or("").to_string();
-            let bookmarks_text = entry.bookmarks.join(", ");
-            let time_text = format_time_ago(entry.last_modified);
-
-            let stat = &entry.diff_stat;
-            let changes_text =
-                if stat.files_changed == 0 && stat.insertions == 0 && stat.deletions == 0 {
-                    "clean".to_string()
-                } else {
-                    let mut parts = Vec::new();
-                    if stat.insertions > 0 {
-                        parts.push(format!("+{}", stat.insertions));
-                    }
-                    if stat.deletions > 0 {
-                        parts.push(format!("-{}", stat.deletions));
-                    }
-                    if parts.is_empty() {
-                        format!("{} files", stat.files_changed)
-                    } else {
-                        parts.join(" ")
-                    }
-                };
-
-            let dim = entry.is_stale;
-            let name_fg = if dim { Color::DarkGray } else { Color::Cyan };
-            let change_fg = if dim { Color::DarkGray } else { Color::Magenta };
-            let desc_fg = if dim { Color::DarkGray } else { Color::White };
-            let bookmark_fg = if dim { Color::DarkGray } else { Color::Blue };
-            let time_fg = if dim { Color::DarkGray } else { Color::Yellow };
-            let changes_fg = if dim {
-                Color::DarkGray
-            } else if stat.deletions > stat.insertions {
-                Color::Red
-            } else if stat.insertions > 0 {
-                Color::Green
-            } else {
-                Color::DarkGray
-            };
-
-            let (agent_text, agent_fg) = match &entry.agent_status {
-                Some(summary) if !summary.is_empty() => {
-                    let color = if dim {
-                        Color::DarkGray
-                    } else {
-                        match summary.most_urgent() {
-                            Some(crate::agent::AgentStatus::Waiting) => Color::Yellow,
-                            Some(crate::agent::AgentStatus::Working) => Color::Green,
-                            _ => Color::DarkGray,
-                        }
-                    };
-                    (summary.to_string(), color)
-                }
-                _ => (String::new(), Color::DarkGray),
-            };
-
-            Row::new(vec![
-                Cell::from(repo_text).style(Style::default().fg(Color::Green)),
-                Cell::from(name_text).style(Style::default().fg(name_fg)),
-                Cell::from(change_text).style(Style::default().fg(change_fg)),
-                Cell::from(desc_text).style(Style::default().fg(desc_fg)),
-                Cell::from(bookmarks_text).style(Style::default().fg(bookmark_fg)),
-                Cell::from(time_text).style(Style::default().fg(time_fg)),
-                Cell::from(changes_text).style(Style::default().fg(changes_fg)),
-                Cell::from(agent_text).style(Style::default().fg(agent_fg)),
-            ])
-        })
-        .collect();
+    let header = table_header(&app.columns, &app.theme);
+    let blank_cells = || vec![Cell::from(""); app.columns.len().max(1)];
+    let mut rows: Vec<Row> = Vec::with_capacity(app.rows.len());
+    for row in &app.rows {
+        match row {
+            // Painted over by the group header overlay below
+            MultiRow::Group(_) => rows.push(Row::new(blank_cells())),
+            MultiRow::Entry(i) => {
+                let entry = &app.entries[*i];
+                rows.push(match app.cleanup {
+                    Some(ref cleanup) => cleanup_row(
+                        entry,
+                        cleanup.marked.contains(&entry.path),
+                        &app.columns,
+                        &app.theme,
+                    ),
+                    None => entry_row(entry, &app.columns, &app.theme),
+                });
+            }
+            MultiRow::Create => {}
+        }
+    }
 
-    let widths = [
-        Constraint::Percentage(10),
-        Constraint::Percentage(11),
-        Constraint::Percentage(7),
-        Constraint::Percentage(24),
-        Constraint::Percentage(11),
-        Constraint::Percentage(10),
-        Constraint::Percentage(12),
-        Constraint::Percentage(15),
-    ];
+    // Append "+ Create new" row
+    let create_row_selected = app.on_create_row();
+    let create_style = if create_row_selected {
+        Style::default().bg(app.theme.highlight)
+    } else {
+        Style::default()
+    };
+    let naming_repo = match app.create {
+        Some(CreateStep::Name { ref repo_name, .. }) => Some(repo_name.clone()),
+        _ => None,
+    };
+    let create_name = if naming_repo.is_some() {
+        // Placeholder text that will be painted over by the overlay
+        String::new()
+    } else {
+        "+ Create new".to_string()
+    };
+    let mut create_cells =
+        vec![Cell::from(create_name).style(Style::default().fg(app.theme.accent))];
+    create_cells.resize(app.columns.len().max(1), Cell::from(""));
+    rows.push(Row::new(create_cells).style(create_style));
+
+    let widths: Vec<Constraint> = app.columns.iter().map(|c| c.constraint()).collect();
+    let title = match app.agent_totals() {
+        Some(totals) => format!(" dwm workspaces (all repos) · {} ", totals),
+        None => " dwm workspaces (all repos) ".to_string(),
+    };
 
     let table = Table::new(rows, widths)
         .header(header)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(" dwm workspaces (all repos) ")
+                .title(title)
                 .title_alignment(Alignment::Center),
         )
-        .row_highlight_style(Style::default().bg(Color::Rgb(40, 40, 60)));
+        .row_highlight_style(Style::default().bg(app.theme.highlight));
 
     frame.render_stateful_widget(table, table_area, &mut app.table_state);
-
-    // Render preview pane if visible
-    if let Some(preview_area) = preview_area {
-        render_preview(frame, preview_area, &app.preview);
+    app.mouse.table_area = table_area;
+
+    // Overlay full-width section headers on the group rows
+    let scroll_offset = app.table_state.offset();
+    let inner_bottom = table_area.bottom().saturating_sub(1);
+    for (i, row) in app.rows.iter().enumerate().skip(scroll_offset) {
+        let y = table_area.y + 2 + (i - scroll_offset) as u16;
+        if y >= inner_bottom {
+            break;
+        }
+        if let MultiRow::Group(name) = row {
+            let style = if i == app.selected {
+                Style::default().bg(app.theme.highlight)
+            } else {
+                Style::default()
+            };
+            let area = Rect::new(table_area.x + 1, y, table_area.width.saturating_sub(2), 1);
+            frame.render_widget(Paragraph::new(app.group_header(name)).style(style), area);
+        }
     }
 
-    if let Some(help_area) = help_area {
-        let help_text = if app.filter_mode {
-            format!(" filter: {}▏  Enter: apply  Esc: clear", app.filter_buf)
+    // Overlay a full-width input line on top of the create row
+    if let Some(ref repo_name) = naming_repo {
+        let scroll_offset = app.table_state.offset() as u16;
+        let create_row_index = app.rows.len().saturating_sub(1) as u16;
+        let create_row_y = table_area.y + 2 + create_row_index.saturating_sub(scroll_offset);
+        if create_row_y < table_area.bottom() {
+            let input_area = Rect::new(
+                table_area.x + 1,
+                create_row_y,
+                table_area.width.saturating_sub(2),
+                1,
+            );
+            let input_text = format!("Name (in {}): {}_", repo_name, app.input_buf);
+            let input_line = Paragraph::new(input_text).style(
+                Style::default()
+                    .fg(app.theme.accent)
+                    .bg(app.theme.highlight),
+            );
+            frame.render_widget(input_line, input_area);
+        }
+    }
+
+    // Render preview pane if visible
+    if let Some(preview_area) = preview_area {
+        render_preview(frame, preview_area, &app.preview, &app.theme);
+    }
+
+    if let Some(help_area) = help_area {
+        let status = app.status.current().filter(|_| !app.awaiting_input());
+        let (help_text, help_style) = if let Some(msg) = status {
+            status_line(msg, &app.theme)
+        } else if let Some((ref label, ref path)) = app.confirm_delete {
+            let entry = app.entries.iter().find(|e| e.path == *path);
+            (
+                format!(
+                    " Delete '{}'{}? y: confirm  n: cancel",
+                    label,
+                    dirty_warning(entry)
+                ),
+                Style::default().fg(app.theme.muted),
+            )
+        } else if let Some(ref step) = app.create {
+            let text = match step {
+                CreateStep::ChooseRepo { .. } => {
+                    " Choose a repo  j/k: move  Enter: select  Esc: cancel"
+                }
+                CreateStep::Name { .. } => " Enter: create (empty: auto-name)  Esc: cancel",
+            };
+            (text.to_string(), Style::default().fg(app.theme.muted))
+        } else if let Some(ref cleanup) = app.cleanup {
+            let marked = app.cleanup_targets().len();
+            let text = if cleanup.confirming {
+                let plural = if marked == 1 { "" } else { "s" };
+                format!(
+                    " Delete {} workspace{}? y: confirm  n: cancel",
+                    marked, plural
+                )
+            } else {
+                cleanup_help(marked, app.filtered_indices.len())
+            };
+            (text, Style::default().fg(app.theme.muted))
+        } else if app.filter_mode {
+            (
+                format!(" filter: {}▏  Enter: apply  Esc: clear", app.filter_buf),
+                Style::default().fg(app.theme.muted),
+            )
+        } else if app.menu.is_some() {
+            (MENU_HELP.to_string(), Style::default().fg(app.theme.muted))
+        } else if app.on_create_row() {
+            (
+                " Enter: create (choose repo, then name)  q: quit".to_string(),
+                Style::default().fg(app.theme.muted),
+            )
+        } else if let Some(error) =
+            selected_error(&app.entries, app.selected_entry_index(), &app.theme)
+        {
+            error
         } else {
             let filter_info = if !app.filter_buf.is_empty() {
                 format!("  [filter: \"{}\"]", app.filter_buf)
             } else {
                 String::new()
             };
-            format!(
-                " j/k: navigate  /: filter  s: sort ({})  p: preview  Enter: select  q: quit{}",
-                app.sort_mode.label(),
-                filter_info
+            (
+                format!(
+                    "{}{}",
+                    browse_help(&app.keymap, app.sort_mode, MULTI_REPO_ACTIONS),
+                    filter_info
+                ),
+                Style::default().fg(app.theme.muted),
             )
         };
-        let help = Paragraph::new(help_text).style(Style::default().fg(Color::DarkGray));
+        let help = Paragraph::new(help_text).style(help_style);
         frame.render_widget(help, help_area);
     }
+
+    if let Some(CreateStep::ChooseRepo {
+        ref repos,
+        selected,
+    }) = app.create
+    {
+        render_repo_chooser(frame, repos, selected, &app.theme);
+    }
+
+    if app.cleanup.as_ref().is_some_and(|c| c.confirming) {
+        render_cleanup_confirm(frame, &app.cleanup_targets(), &app.theme);
+    }
+
+    if let Some(ref menu) = app.menu {
+        render_action_menu(frame, menu, &app.keymap, &app.theme);
+    }
+
+    if let Some(ref agents) = app.agents {
+        render_agent_popup(frame, agents, &app.theme);
+    }
+
+    if app.show_help {
+        render_help_popup(frame, &app.keymap, MULTI_REPO_ACTIONS, &app.theme);
+    }
 }
 
 /// Event loop for the multi-repo picker. `next_event` is injectable for testing.
+///
+/// `on_delete` deletes the workspace at the given path through its own repo's
+/// backend — returns `Ok(true)` if the caller already printed a redirect path
+/// (picker should exit), `Ok(false)` if the picker should refresh and continue.
+/// Its flag forces the deletion as in [`run_picker_inner`].
+///
+/// `on_open` opens the given workspace path in the user's editor.
+///
+/// `on_copy` copies the given workspace path to the clipboard.
+///
+/// `on_push` pushes the workspace at the given path, returning the remote
+/// branch it went to.
+///
+/// `on_sync` rebases the workspace at the given path onto trunk, returning
+/// what it was rebased onto.
+///
+/// `on_browse` opens the given URL in the web browser.
+///
+/// `list_entries` is called after a non-redirect deletion to refresh the
+/// entry list.
+#[allow(clippy::too_many_arguments)]
 fn run_picker_multi_repo_inner<B: Backend>(
     terminal: &mut Terminal<B>,
     app: MultiRepoApp,
     next_event: &mut dyn FnMut() -> Result<Option<Event>>,
+    on_delete: &mut dyn FnMut(&Path, bool) -> Result<bool>,
+    on_open: &mut dyn FnMut(&Path) -> Result<()>,
+    on_copy: &mut dyn FnMut(&str) -> Result<()>,
+    on_push: &mut dyn FnMut(&Path) -> Result<String>,
+    on_sync: &mut dyn FnMut(&Path) -> Result<String>,
+    on_browse: &mut dyn FnMut(&str) -> Result<()>,
+    list_entries: &mut dyn FnMut() -> Result<Vec<WorkspaceEntry>>,
 ) -> Result<Option<PickerResult>> {
     let mut app = app;
 
     loop {
-        // Drain mailboxes before drawing
+        // Drain mailboxes and expire status messages before drawing
         app.drain_preview_mailbox();
         app.drain_refresh_mailbox();
+        app.status.expire(Instant::now());
 
         terminal.draw(|f| render_multi_repo(f, &mut app))?;
 
@@ -1270,6 +3627,26 @@ fn run_picker_multi_repo_inner<B: Backend>(
             continue;
         };
 
+        if let Event::Mouse(mouse) = event {
+            if !app.filter_mode
+                && app.confirm_delete.is_none()
+                && app.create.is_none()
+                && app.cleanup.is_none()
+                && app.menu.is_none()
+                && app.agents.is_none()
+                && !app.show_help
+            {
+                let prev_selected = app.selected;
+                if let Some(result) = app.handle_mouse(&mouse) {
+                    return Ok(Some(result));
+                }
+                if app.selected != prev_selected {
+                    app.trigger_preview_fetch();
+                }
+            }
+            continue;
+        }
+
         if let Event::Key(key) = event {
             if key.kind != KeyEventKind::Press {
                 continue;
@@ -1277,7 +3654,151 @@ fn run_picker_multi_repo_inner<B: Backend>(
 
             let prev_selected = app.selected;
 
-            if app.filter_mode {
+            // Any key dismisses the help and agent popups.
+            if app.show_help || app.agents.is_some() {
+                app.show_help = false;
+                app.agents = None;
+                continue;
+            }
+
+            if let Some((label, path)) = app.confirm_delete.clone() {
+                match key.code {
+                    KeyCode::Char('y') => {
+                        app.confirm_delete = None;
+                        // Only work the prompt warned about is deleted.
+                        let force = app
+                            .entries
+                            .iter()
+                            .find(|e| e.path == path)
+                            .is_some_and(|e| e.working_copy.is_dirty());
+                        match on_delete(&path, force) {
+                            Ok(true) => return Ok(None),
+                            Ok(false) => {
+                                let new_entries = list_entries()?;
+                                if new_entries.is_empty() {
+                                    return Ok(None);
+                                }
+                                app.merge_entries(new_entries);
+                                app.trigger_preview_fetch();
+                                app.status.info(format!("workspace '{}' deleted", label));
+                            }
+                            Err(e) => {
+                                app.status.error(format!("delete failed: {}", e));
+                            }
+                        }
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => app.confirm_delete = None,
+                    _ => {}
+                }
+            } else if let Some(step) = app.create.take() {
+                match step {
+                    CreateStep::ChooseRepo { repos, selected } => {
+                        let selected = match (key.code, app.keymap.action_for(&key)) {
+                            (KeyCode::Esc, _) | (_, Some(Action::Quit)) => continue,
+                            (_, Some(Action::Down)) => (selected + 1) % repos.len(),
+                            (_, Some(Action::Up)) => {
+                                selected.checked_sub(1).unwrap_or(repos.len() - 1)
+                            }
+                            (KeyCode::Enter, _) | (_, Some(Action::Select)) => {
+                                let (repo_name, repo) = repos[selected].clone();
+                                app.create = Some(CreateStep::Name { repo_name, repo });
+                                continue;
+                            }
+                            _ => selected,
+                        };
+                        app.create = Some(CreateStep::ChooseRepo { repos, selected });
+                    }
+                    CreateStep::Name { repo_name, repo } => match key.code {
+                        KeyCode::Esc => app.input_buf.clear(),
+                        KeyCode::Enter => {
+                            let repo_entries: Vec<WorkspaceEntry> = app
+                                .entries
+                                .iter()
+                                .filter(|e| e.main_repo_path == repo)
+                                .cloned()
+                                .collect();
+                            let invalid = Some(app.input_buf.as_str())
+                                .filter(|name| !name.is_empty())
+                                .and_then(|name| invalid_name(&repo_entries, name));
+                            if let Some(err) = invalid {
+                                app.status.error(err);
+                                app.create = Some(CreateStep::Name { repo_name, repo });
+                                continue;
+                            }
+                            let name = if app.input_buf.is_empty() {
+                                None
+                            } else {
+                                Some(std::mem::take(&mut app.input_buf))
+                            };
+                            return Ok(Some(PickerResult::CreateNewIn { repo, name }));
+                        }
+                        KeyCode::Backspace => {
+                            app.input_buf.pop();
+                            app.create = Some(CreateStep::Name { repo_name, repo });
+                        }
+                        KeyCode::Char(c) => {
+                            app.input_buf.push(c);
+                            app.create = Some(CreateStep::Name { repo_name, repo });
+                        }
+                        _ => app.create = Some(CreateStep::Name { repo_name, repo }),
+                    },
+                }
+            } else if app.cleanup.as_ref().is_some_and(|c| c.confirming) {
+                match key.code {
+                    KeyCode::Char('y') => {
+                        let targets: Vec<(String, PathBuf)> = app
+                            .cleanup_targets()
+                            .into_iter()
+                            .map(|(label, e)| (label, e.path.clone()))
+                            .collect();
+                        app.cleanup = None;
+                        let (redirected, level, message) =
+                            delete_all(&targets, |_, path| on_delete(path, false));
+                        if redirected {
+                            return Ok(None);
+                        }
+                        let new_entries = list_entries()?;
+                        if new_entries.is_empty() {
+                            return Ok(None);
+                        }
+                        app.merge_entries(new_entries);
+                        app.trigger_preview_fetch();
+                        app.status.push(level, message);
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => {
+                        if let Some(ref mut cleanup) = app.cleanup {
+                            cleanup.confirming = false;
+                        }
+                    }
+                    _ => {}
+                }
+            } else if app.cleanup.is_some() {
+                let (action, count) = app.motion.feed(&key, app.keymap.action_for(&key));
+                match (key.code, action) {
+                    (KeyCode::Char(' '), _) => app.toggle_cleanup_mark(),
+                    (_, Some(Action::Select)) => {
+                        if app.cleanup_targets().is_empty() {
+                            app.status.info("nothing marked");
+                        } else if let Some(ref mut cleanup) = app.cleanup {
+                            cleanup.confirming = true;
+                        }
+                    }
+                    (_, Some(Action::Quit | Action::Cleanup)) => app.end_cleanup(),
+                    (_, Some(Action::Collapse)) => app.toggle_selected_group(),
+                    (
+                        _,
+                        Some(
+                            action @ (Action::Down
+                            | Action::Up
+                            | Action::Top
+                            | Action::Bottom
+                            | Action::HalfPageDown
+                            | Action::HalfPageUp),
+                        ),
+                    ) => app.apply_motion(action, count),
+                    _ => {}
+                }
+            } else if app.filter_mode {
                 match key.code {
                     KeyCode::Esc => {
                         app.filter_buf.clear();
@@ -1298,21 +3819,157 @@ fn run_picker_multi_repo_inner<B: Backend>(
                     _ => {}
                 }
             } else {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
-                    KeyCode::Char('j') | KeyCode::Down => app.next(),
-                    KeyCode::Char('k') | KeyCode::Up => app.previous(),
-                    KeyCode::Char('s') => {
-                        app.sort_mode = app.sort_mode.next();
-                        sort_entries(&mut app.entries, app.sort_mode);
-                        app.recompute_filter();
-                        app.selected = 0;
-                        app.sync_table_state();
+                let (action, count) = if app.menu.is_some() {
+                    (menu_key(&mut app.menu, &app.keymap, &key), None)
+                } else {
+                    app.motion.feed(&key, app.keymap.action_for(&key))
+                };
+                match action {
+                    Some(Action::Quit) => return Ok(None),
+                    Some(Action::Help) => app.show_help = true,
+                    Some(
+                        action @ (Action::Down
+                        | Action::Up
+                        | Action::Top
+                        | Action::Bottom
+                        | Action::HalfPageDown
+                        | Action::HalfPageUp),
+                    ) => app.apply_motion(action, count),
+                    Some(Action::Sort) => app.set_sort_mode(app.sort_mode.next()),
+                    Some(Action::Collapse) => app.toggle_selected_group(),
+                    Some(Action::Copy) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let path = app.entries[idx].path.display().to_string();
+                            match on_copy(&path) {
+                                Ok(()) => app.status.info(format!("copied {}", path)),
+                                Err(e) => app.status.error(format!("copy failed: {}", e)),
+                            }
+                        }
+                    }
+                    Some(Action::Push) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let entry = &app.entries[idx];
+                            let (name, path) = (entry.name.clone(), entry.path.clone());
+                            let progress = format!("pushing {}…", name);
+                            app.status.info(progress.clone());
+                            terminal.draw(|f| render_multi_repo(f, &mut app))?;
+                            let pushed = on_push(&path);
+                            app.status.dismiss(&progress);
+                            match pushed {
+                                Ok(remote) => {
+                                    app.status.info(format!("pushed {} to {}", name, remote))
+                                }
+                                Err(e) => app.status.error(format!("push failed: {}", e)),
+                            }
+                        }
+                    }
+                    Some(Action::Sync) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let entry = &app.entries[idx];
+                            let (name, path) = (entry.name.clone(), entry.path.clone());
+                            let progress = format!("rebasing {}…", name);
+                            app.status.info(progress.clone());
+                            terminal.draw(|f| render_multi_repo(f, &mut app))?;
+                            let synced = on_sync(&path);
+                            app.status.dismiss(&progress);
+                            match synced {
+                                Ok(onto) => {
+                                    if let Ok(new_entries) = list_entries()
+                                        && !new_entries.is_empty()
+                                    {
+                                        app.merge_entries(new_entries);
+                                        app.trigger_preview_fetch();
+                                    }
+                                    app.status.info(format!("rebased {} onto {}", name, onto));
+                                }
+                                Err(e) => app.status.error(format!("sync failed: {}", e)),
+                            }
+                        }
+                    }
+                    Some(Action::Launch) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            return Ok(Some(PickerResult::Launch(
+                                app.entries[idx].path.to_string_lossy().to_string(),
+                            )));
+                        }
+                    }
+                    Some(Action::Tmux) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            return Ok(Some(PickerResult::Tmux(
+                                app.entries[idx].path.to_string_lossy().to_string(),
+                            )));
+                        }
+                    }
+                    Some(Action::Pr) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            return Ok(Some(PickerResult::Pr(
+                                app.entries[idx].path.to_string_lossy().to_string(),
+                            )));
+                        }
+                    }
+                    Some(Action::Ci) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let entry = &app.entries[idx];
+                            let (name, url) = (
+                                entry.name.clone(),
+                                entry.ci.as_ref().map(|ci| ci.url.clone()),
+                            );
+                            match url.filter(|url| !url.is_empty()) {
+                                Some(url) => match on_browse(&url) {
+                                    Ok(()) => app.status.info(format!("opened {}", url)),
+                                    Err(e) => {
+                                        app.status.error(format!("could not open CI run: {}", e))
+                                    }
+                                },
+                                None => app.status.info(format!("no CI run for {}", name)),
+                            }
+                        }
+                    }
+                    Some(Action::NextWaiting) => {
+                        let found = app.next_waiting();
+                        if !found {
+                            app.status.info("no agents waiting");
+                        }
+                    }
+                    Some(Action::Cleanup) => app.start_cleanup(),
+                    Some(Action::Agents) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let entry = &app.entries[idx];
+                            let sessions = match (&app.dwm_base, &entry.repo_name) {
+                                (Some(base), Some(repo)) => {
+                                    agent::read_agent_sessions(&base.join(repo), &entry.name)
+                                }
+                                _ => Vec::new(),
+                            };
+                            app.agents = Some(AgentPopup {
+                                title: format!("{}/{}", group_name(entry), entry.name),
+                                sessions,
+                            });
+                        }
+                    }
+                    Some(Action::Menu) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            app.menu =
+                                Some(ActionMenu::for_entry(&app.entries[idx], MULTI_REPO_MENU));
+                        }
                     }
-                    KeyCode::Char('/') => {
+                    Some(Action::Filter) => {
                         app.filter_mode = true;
                     }
-                    KeyCode::Char('p') => {
+                    Some(Action::Delete) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let entry = &app.entries[idx];
+                            if !entry.is_main {
+                                let label = format!(
+                                    "{}/{}",
+                                    entry.repo_name.as_deref().unwrap_or(""),
+                                    entry.name
+                                );
+                                app.confirm_delete = Some((label, entry.path.clone()));
+                            }
+                        }
+                    }
+                    Some(Action::Preview) => {
                         app.show_preview = !app.show_preview;
                         if app.show_preview {
                             app.trigger_preview_fetch();
@@ -1320,10 +3977,23 @@ fn run_picker_multi_repo_inner<B: Backend>(
                             app.preview = PreviewState::Hidden;
                         }
                     }
-                    KeyCode::Enter => {
-                        if let Some(&idx) = app.filtered_indices.get(app.selected) {
-                            let path = app.entries[idx].path.to_string_lossy().to_string();
-                            return Ok(Some(PickerResult::Selected(path)));
+                    Some(Action::PreviewGrow) => app.preview_layout.grow(),
+                    Some(Action::PreviewShrink) => app.preview_layout.shrink(),
+                    Some(Action::PreviewLayout) => {
+                        app.preview_layout.stacked = !app.preview_layout.stacked;
+                    }
+                    Some(Action::Open) => {
+                        if let Some(idx) = app.selected_entry_index() {
+                            let path = app.entries[idx].path.clone();
+                            if let Err(e) = on_open(&path) {
+                                app.status.error(format!("open failed: {}", e));
+                            }
+                            terminal.clear()?;
+                        }
+                    }
+                    Some(Action::Select) => {
+                        if let Some(result) = app.activate() {
+                            return Ok(Some(result));
                         }
                     }
                     _ => {}
@@ -1341,7 +4011,22 @@ fn run_picker_multi_repo_inner<B: Backend>(
 /// Launch the interactive TUI workspace picker showing all repos (`--all` mode).
 ///
 /// Returns the selected workspace path, or `None` if the user cancelled.
-pub fn run_picker_multi_repo(entries: Vec<WorkspaceEntry>) -> Result<Option<PickerResult>> {
+/// `settings` supplies the key bindings and table columns.
+///
+/// `on_delete` is called with the workspace path when the user confirms a
+/// deletion; it must use that workspace's own repo. It returns `Ok(true)` if a
+/// redirect path was printed (picker exits), or `Ok(false)` to refresh via
+/// `list_entries` and continue. As in [`run_picker`], it's asked to force
+/// the deletion only when the prompt showed uncommitted work.
+///
+/// `on_open` is called when the user presses `o`, with the TUI suspended.
+pub fn run_picker_multi_repo(
+    entries: Vec<WorkspaceEntry>,
+    settings: PickerSettings,
+    mut on_delete: impl FnMut(&Path, bool) -> Result<bool>,
+    mut on_open: impl FnMut(&Path) -> Result<()>,
+    mut list_entries: impl FnMut() -> Result<Vec<WorkspaceEntry>>,
+) -> Result<Option<PickerResult>> {
     if entries.is_empty() {
         eprintln!("{}", "no workspaces found".red());
         return Ok(None);
@@ -1350,14 +4035,23 @@ pub fn run_picker_multi_repo(entries: Vec<WorkspaceEntry>) -> Result<Option<Pick
     enable_raw_mode()?;
     let mut stderr = io::stderr();
     crossterm::execute!(stderr, EnterAlternateScreen)?;
+    let mouse = settings.mouse;
+    if mouse {
+        crossterm::execute!(stderr, EnableMouseCapture)?;
+    }
     let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
 
-    let app = MultiRepoApp::new(entries);
+    let mut app = MultiRepoApp::new(entries);
+    app.keymap = settings.keymap;
+    app.dwm_base = dirs::home_dir().map(|home| home.join(".dwm"));
+    app.columns = columns::resolve(settings.columns.as_deref(), true);
+    app.theme = settings.theme;
     let stop = Arc::new(StopSignal::new());
 
     let agent_sender = app.agent_refresh_mailbox.sender();
     let refresh_sender = app.refresh_mailbox.sender();
+    let refresh_error_sender = app.refresh_error_mailbox.sender();
 
     // Collect unique repo dirs for agent polling
     let repo_dirs: Vec<PathBuf> = {
@@ -1371,8 +4065,14 @@ pub fn run_picker_multi_repo(entries: Vec<WorkspaceEntry>) -> Result<Option<Pick
         dirs.into_iter().collect()
     };
 
-    // Agent status polling thread (~2s)
-    let agent_thread = spawn_refresh_thread(
+    // Agent status thread: on each status file change, or every ~2s where
+    // that can't be watched
+    let status_dirs: Vec<PathBuf> = repo_dirs
+        .iter()
+        .map(|d| agent::agent_status_dir(d))
+        .collect();
+    let agent_thread = spawn_watched_refresh_thread(
+        &status_dirs,
         std::time::Duration::from_secs(2),
         Arc::clone(&stop),
         agent_sender,
@@ -1397,106 +4097,397 @@ pub fn run_picker_multi_repo(entries: Vec<WorkspaceEntry>) -> Result<Option<Pick
         std::time::Duration::from_secs(10),
         Arc::clone(&stop),
         refresh_sender,
-        move || crate::workspace::list_all_workspace_entries().ok(),
+        posting_errors(
+            refresh_error_sender,
+            crate::workspace::list_all_workspace_entries,
+        ),
     );
 
-    let result = run_picker_multi_repo_inner(&mut terminal, app, &mut || {
-        if event::poll(std::time::Duration::from_millis(100))? {
-            Ok(Some(event::read()?))
-        } else {
-            Ok(None)
-        }
-    });
+    // Forge thread (every couple of minutes), as in the single-repo picker
+    let pr_thread = spawn_refresh_thread(
+        crate::forge::FORGE_TTL,
+        Arc::clone(&stop),
+        app.refresh_mailbox.sender(),
+        || {
+            crate::workspace::refresh_all_forges()
+                .then(crate::workspace::list_all_workspace_entries)
+                .and_then(Result::ok)
+        },
+    );
+
+    let result = run_picker_multi_repo_inner(
+        &mut terminal,
+        app,
+        &mut || {
+            if event::poll(std::time::Duration::from_millis(100))? {
+                Ok(Some(event::read()?))
+            } else {
+                Ok(None)
+            }
+        },
+        &mut on_delete,
+        &mut |path| with_suspended_terminal(mouse, || on_open(path)),
+        &mut |text| crate::clipboard::copy(text),
+        &mut |path| crate::workspace::push_workspace_at(path),
+        &mut |path| crate::workspace::sync_workspace_at(path),
+        &mut |url| crate::browser::open(url),
+        &mut list_entries,
+    );
 
     stop.stop();
     let _ = agent_thread.join();
     let _ = refresh_thread.join();
+    let _ = pr_thread.join();
 
     disable_raw_mode()?;
-    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        DisableMouseCapture,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
     result
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::vcs::DiffStat;
-    use crossterm::event::{KeyEvent, KeyModifiers};
-    use ratatui::backend::TestBackend;
-    use std::path::PathBuf;
-    use std::time::{Duration, SystemTime};
+// ── Agent dashboard (dwm agents) ────────────────────────────────
 
-    fn make_entry(
-        name: &str,
-        modified_secs_ago: Option<u64>,
-        insertions: u32,
-        deletions: u32,
-    ) -> WorkspaceEntry {
-        WorkspaceEntry {
-            name: name.to_string(),
-            path: PathBuf::from(format!("/tmp/{}", name)),
-            last_modified: modified_secs_ago.map(|s| SystemTime::now() - Duration::from_secs(s)),
-            diff_stat: DiffStat {
-                files_changed: 1,
-                insertions,
-                deletions,
-            },
-            is_main: false,
-            change_id: String::new(),
-            description: String::new(),
-            bookmarks: Vec::new(),
-            is_stale: false,
-            repo_name: None,
-            main_repo_path: PathBuf::from("/tmp/repo"),
-            vcs_type: crate::vcs::VcsType::Jj,
-            agent_status: None,
-        }
-    }
+/// State for the `dwm agents` dashboard: every live agent session across
+/// repos, refreshed in the background.
+struct AgentDashboard {
+    listings: Vec<AgentListing>,
+    table_state: TableState,
+    keymap: KeyMap,
+    theme: Theme,
+    motion: Motion,
+    /// Rows that fit in the table as last drawn, for half-page motions.
+    page_rows: usize,
+    refresh_mailbox: Mailbox<Vec<AgentListing>>,
+    refresh_error_mailbox: Mailbox<String>,
+    status: StatusQueue,
+}
 
-    #[test]
-    fn sort_by_name_alphabetical() {
-        let mut entries = vec![
-            make_entry("cherry", None, 0, 0),
-            make_entry("Apple", None, 0, 0),
-            make_entry("banana", None, 0, 0),
-        ];
-        sort_entries(&mut entries, SortMode::Name);
-        assert_eq!(entries[0].name, "Apple");
-        assert_eq!(entries[1].name, "banana");
-        assert_eq!(entries[2].name, "cherry");
+impl AgentDashboard {
+    fn new(listings: Vec<AgentListing>) -> Self {
+        let mut table_state = TableState::default();
+        table_state.select((!listings.is_empty()).then_some(0));
+        Self {
+            listings,
+            table_state,
+            keymap: KeyMap::default(),
+            theme: Theme::default(),
+            motion: Motion::default(),
+            page_rows: 0,
+            refresh_mailbox: Mailbox::new(),
+            refresh_error_mailbox: Mailbox::new(),
+            status: StatusQueue::default(),
+        }
     }
 
-    #[test]
-    fn sort_by_recency_most_recent_first() {
-        let mut entries = vec![
-            make_entry("old", Some(3600), 0, 0),
-            make_entry("new", Some(60), 0, 0),
-            make_entry("mid", Some(600), 0, 0),
-        ];
-        sort_entries(&mut entries, SortMode::Recency);
-        assert_eq!(entries[0].name, "new");
-        assert_eq!(entries[1].name, "mid");
-        assert_eq!(entries[2].name, "old");
+    /// Take fresh listings from the refresh thread, keeping the cursor on
+    /// the same session while it's still live.
+    fn drain_refresh_mailbox(&mut self) {
+        if let Some(err) = self.refresh_error_mailbox.take() {
+            self.status.error(format!("refresh failed: {}", err));
+        }
+        let Some(listings) = self.refresh_mailbox.take() else {
+            return;
+        };
+        let selected = self.table_state.selected().unwrap_or(0);
+        let key = self
+            .listings
+            .get(selected)
+            .map(|l| (l.repo.clone(), l.session.session_id.clone()));
+        self.listings = listings;
+        let index = key
+            .and_then(|(repo, id)| {
+                self.listings
+                    .iter()
+                    .position(|l| l.repo == repo && l.session.session_id == id)
+            })
+            .unwrap_or(selected)
+            .min(self.listings.len().saturating_sub(1));
+        self.table_state
+            .select((!self.listings.is_empty()).then_some(index));
     }
 
-    #[test]
-    fn sort_by_recency_none_sorts_last() {
-        let mut entries = vec![
-            make_entry("unknown", None, 0, 0),
-            make_entry("recent", Some(10), 0, 0),
-        ];
-        sort_entries(&mut entries, SortMode::Recency);
-        assert_eq!(entries[0].name, "recent");
-        assert_eq!(entries[1].name, "unknown");
+    /// Move the cursor for a navigation `action`, repeated `count` times.
+    fn apply_motion(&mut self, action: Action, count: Option<usize>) {
+        let total = self.listings.len();
+        if total == 0 {
+            return;
+        }
+        let selected = self.table_state.selected().unwrap_or(0);
+        let target = match (action, count) {
+            // No "+ Create new" row here, so the bottom is the last session.
+            (Action::Bottom, None) => total - 1,
+            _ => motion_target(action, count, selected, total, self.page_rows / 2),
+        };
+        self.table_state.select(Some(target));
     }
+}
 
-    #[test]
-    fn sort_by_diff_size_largest_first() {
-        let mut entries = vec![
-            make_entry("small", None, 1, 2),
-            make_entry("large", None, 50, 30),
+/// Render the agent dashboard: one row per session, waiting ones tinted as
+/// in the picker, with the help bar below.
+fn render_agent_dashboard(frame: &mut Frame, app: &mut AgentDashboard) {
+    let full_area = frame.area();
+    let (table_area, help_area) = if full_area.height > 3 {
+        let chunks = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(full_area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (full_area, None)
+    };
+    let theme = &app.theme;
+
+    let header = Row::new(
+        [
+            "REPO",
+            "WORKSPACE",
+            "AGENT",
+            "STATUS",
+            "STARTED",
+            "UPDATED",
+            "LAST EVENT",
+        ]
+        .map(|t| Cell::from(t).style(Style::default().fg(theme.text).bold())),
+    )
+    .style(Style::default().bg(theme.header))
+    .height(1);
+    let rows: Vec<Row> = app
+        .listings
+        .iter()
+        .map(|l| {
+            let status_color = match l.session.status.coarse() {
+                AgentStatus::Waiting => theme.warning,
+                AgentStatus::Idle => theme.muted,
+                _ => theme.accent,
+            };
+            let row_style = if l.session.status == AgentStatus::Waiting {
+                Style::default().bg(theme.attention)
+            } else {
+                Style::default()
+            };
+            Row::new([
+                Cell::from(l.repo.clone()).style(Style::default().fg(theme.text)),
+                Cell::from(l.workspace.clone()).style(Style::default().fg(theme.name)),
+                Cell::from(l.session.label()).style(Style::default().fg(theme.muted)),
+                Cell::from(l.session.status.label())
+                    .style(Style::default().fg(status_color).bold()),
+                Cell::from(format_time_ago(l.session.started_at))
+                    .style(Style::default().fg(theme.modified)),
+                Cell::from(format_time_ago(Some(l.session.updated_at)))
+                    .style(Style::default().fg(theme.modified)),
+                Cell::from(l.last_event()).style(Style::default().fg(theme.text)),
+            ])
+            .style(row_style)
+        })
+        .collect();
+
+    let width = |header: &str, cell: &dyn Fn(&AgentListing) -> usize| {
+        app.listings
+            .iter()
+            .map(cell)
+            .max()
+            .unwrap_or(0)
+            .max(header.len()) as u16
+    };
+    let widths = [
+        Constraint::Length(width("REPO", &|l| width::of(&l.repo))),
+        Constraint::Length(width("WORKSPACE", &|l| width::of(&l.workspace))),
+        Constraint::Length(width("AGENT", &|l| width::of(&l.session.label())).max(8)),
+        Constraint::Length(12),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Min(10),
+    ];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" dwm agents ({}) ", app.listings.len()))
+        .title_alignment(Alignment::Center);
+    if app.listings.is_empty() {
+        let empty = Paragraph::new(" no active agent sessions ")
+            .style(Style::default().fg(theme.muted))
+            .block(block);
+        frame.render_widget(empty, table_area);
+    } else {
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .row_highlight_style(Style::default().bg(theme.highlight));
+        frame.render_stateful_widget(table, table_area, &mut app.table_state);
+    }
+    // Borders and the header row take three lines.
+    app.page_rows = table_area.height.saturating_sub(3) as usize;
+
+    if let Some(help_area) = help_area {
+        let (help_text, help_style) = match app.status.current() {
+            Some(msg) => status_line(msg, &app.theme),
+            None => (
+                format!(
+                    " {}/{}: navigate  {}: quit  updates live",
+                    app.keymap.label(Action::Down),
+                    app.keymap.label(Action::Up),
+                    app.keymap.label(Action::Quit)
+                ),
+                Style::default().fg(app.theme.muted),
+            ),
+        };
+        frame.render_widget(Paragraph::new(help_text).style(help_style), help_area);
+    }
+}
+
+/// Event loop for the agent dashboard. `next_event` is injectable for
+/// testing, as in [`run_picker_inner`].
+fn run_agent_dashboard_inner<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut AgentDashboard,
+    next_event: &mut dyn FnMut() -> Result<Option<Event>>,
+) -> Result<()> {
+    loop {
+        app.drain_refresh_mailbox();
+        app.status.expire(Instant::now());
+        terminal.draw(|f| render_agent_dashboard(f, app))?;
+
+        let Some(Event::Key(key)) = next_event()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let (action, count) = app.motion.feed(&key, app.keymap.action_for(&key));
+        match action {
+            Some(Action::Quit) => return Ok(()),
+            Some(
+                action @ (Action::Down
+                | Action::Up
+                | Action::Top
+                | Action::Bottom
+                | Action::HalfPageDown
+                | Action::HalfPageUp),
+            ) => app.apply_motion(action, count),
+            _ => {}
+        }
+    }
+}
+
+/// Run the `dwm agents` dashboard until the user quits, refreshing the
+/// sessions from `list_sessions` whenever a file in `watch_dirs` changes, or
+/// every two seconds where they can't be watched.
+pub fn run_agent_dashboard(
+    listings: Vec<AgentListing>,
+    settings: PickerSettings,
+    watch_dirs: Vec<PathBuf>,
+    list_sessions: impl FnMut() -> Result<Vec<AgentListing>> + Send + 'static,
+) -> Result<()> {
+    enable_raw_mode()?;
+    let mut stderr = io::stderr();
+    crossterm::execute!(stderr, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stderr);
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut app = AgentDashboard::new(listings);
+    app.keymap = settings.keymap;
+    app.theme = settings.theme;
+    let stop = Arc::new(StopSignal::new());
+
+    let refresh_thread = spawn_watched_refresh_thread(
+        &watch_dirs,
+        std::time::Duration::from_secs(2),
+        Arc::clone(&stop),
+        app.refresh_mailbox.sender(),
+        posting_errors(app.refresh_error_mailbox.sender(), list_sessions),
+    );
+
+    let result = run_agent_dashboard_inner(&mut terminal, &mut app, &mut || {
+        if event::poll(std::time::Duration::from_millis(100))? {
+            Ok(Some(event::read()?))
+        } else {
+            Ok(None)
+        }
+    });
+
+    stop.stop();
+    let _ = refresh_thread.join();
+
+    disable_raw_mode()?;
+    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vcs::DiffStat;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    fn make_entry(
+        name: &str,
+        modified_secs_ago: Option<u64>,
+        insertions: u32,
+        deletions: u32,
+    ) -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/tmp/{}", name)),
+            last_modified: modified_secs_ago.map(|s| SystemTime::now() - Duration::from_secs(s)),
+            diff_stat: DiffStat {
+                files_changed: 1,
+                insertions,
+                deletions,
+                ..Default::default()
+            },
+            main_repo_path: PathBuf::from("/tmp/repo"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn sort_by_name_alphabetical() {
+        let mut entries = vec![
+            make_entry("cherry", None, 0, 0),
+            make_entry("Apple", None, 0, 0),
+            make_entry("banana", None, 0, 0),
+        ];
+        sort_entries(&mut entries, SortMode::Name);
+        assert_eq!(entries[0].name, "Apple");
+        assert_eq!(entries[1].name, "banana");
+        assert_eq!(entries[2].name, "cherry");
+    }
+
+    #[test]
+    fn sort_by_recency_most_recent_first() {
+        let mut entries = vec![
+            make_entry("old", Some(3600), 0, 0),
+            make_entry("new", Some(60), 0, 0),
+            make_entry("mid", Some(600), 0, 0),
+        ];
+        sort_entries(&mut entries, SortMode::Recency);
+        assert_eq!(entries[0].name, "new");
+        assert_eq!(entries[1].name, "mid");
+        assert_eq!(entries[2].name, "old");
+    }
+
+    #[test]
+    fn sort_by_recency_none_sorts_last() {
+        let mut entries = vec![
+            make_entry("unknown", None, 0, 0),
+            make_entry("recent", Some(10), 0, 0),
+        ];
+        sort_entries(&mut entries, SortMode::Recency);
+        assert_eq!(entries[0].name, "recent");
+        assert_eq!(entries[1].name, "unknown");
+    }
+
+    #[test]
+    fn sort_by_diff_size_largest_first() {
+        let mut entries = vec![
+            make_entry("small", None, 1, 2),
+            make_entry("large", None, 50, 30),
             make_entry("medium", None, 10, 5),
         ];
         sort_entries(&mut entries, SortMode::DiffSize);
@@ -1509,60 +4500,123 @@ mod tests {
     fn sort_mode_cycles() {
         assert_eq!(SortMode::Recency.next(), SortMode::Name);
         assert_eq!(SortMode::Name.next(), SortMode::DiffSize);
-        assert_eq!(SortMode::DiffSize.next(), SortMode::Recency);
+        assert_eq!(SortMode::DiffSize.next(), SortMode::Waiting);
+        assert_eq!(SortMode::Waiting.next(), SortMode::Recency);
+    }
+
+    #[test]
+    fn sort_by_waiting_longest_first() {
+        let now = SystemTime::now();
+        let waiting = |name: &str, modified: u64, waited: u64| {
+            let mut entry = make_entry(name, Some(modified), 0, 0);
+            entry.agent_status = Some(AgentSummary {
+                waiting: 1,
+                waiting_since: Some(now - Duration::from_secs(waited)),
+                ..Default::default()
+            });
+            entry
+        };
+        let mut entries = vec![
+            make_entry("old", Some(3600), 0, 0),
+            waiting("brief", 600, 30),
+            make_entry("new", Some(60), 0, 0),
+            waiting("blocked", 1200, 900),
+        ];
+        sort_entries(&mut entries, SortMode::Waiting);
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["blocked", "brief", "new", "old"]);
     }
 
     fn make_entry_with_desc(name: &str, description: &str, bookmarks: Vec<&str>) -> WorkspaceEntry {
         WorkspaceEntry {
             name: name.to_string(),
             path: PathBuf::from(format!("/tmp/{}", name)),
-            last_modified: None,
-            diff_stat: DiffStat::default(),
-            is_main: false,
-            change_id: String::new(),
             description: description.to_string(),
             bookmarks: bookmarks.into_iter().map(String::from).collect(),
-            is_stale: false,
-            repo_name: None,
             main_repo_path: PathBuf::from("/tmp/repo"),
-            vcs_type: crate::vcs::VcsType::Jj,
-            agent_status: None,
+            ..Default::default()
         }
     }
 
+    fn filter_score(entry: &WorkspaceEntry, input: &str) -> Option<i64> {
+        Query::parse(input).score(entry)
+    }
+
     #[test]
     fn filter_matches_name() {
         let entry = make_entry_with_desc("my-feature", "", vec![]);
-        assert!(matches_filter(&entry, "feat"));
-        assert!(!matches_filter(&entry, "bugfix"));
+        assert!(filter_score(&entry, "feat").is_some());
+        assert!(filter_score(&entry, "bugfix").is_none());
     }
 
     #[test]
     fn filter_matches_description() {
         let entry = make_entry_with_desc("ws1", "fix login bug", vec![]);
-        assert!(matches_filter(&entry, "login"));
-        assert!(!matches_filter(&entry, "signup"));
+        assert!(filter_score(&entry, "login").is_some());
+        assert!(filter_score(&entry, "signup").is_none());
     }
 
     #[test]
     fn filter_matches_bookmarks() {
         let entry = make_entry_with_desc("ws1", "", vec!["main", "release-v2"]);
-        assert!(matches_filter(&entry, "release"));
-        assert!(!matches_filter(&entry, "develop"));
+        assert!(filter_score(&entry, "release").is_some());
+        assert!(filter_score(&entry, "develop").is_none());
     }
 
     #[test]
     fn filter_is_case_insensitive() {
         let entry = make_entry_with_desc("MyFeature", "Fix Bug", vec!["Main"]);
-        assert!(matches_filter(&entry, "myfeature"));
-        assert!(matches_filter(&entry, "FIX"));
-        assert!(matches_filter(&entry, "main"));
+        assert!(filter_score(&entry, "myfeature").is_some());
+        assert!(filter_score(&entry, "FIX").is_some());
+        assert!(filter_score(&entry, "main").is_some());
+    }
+
+    #[test]
+    fn filter_is_fuzzy() {
+        let entry = make_entry_with_desc("live-view-widget", "", vec![]);
+        assert!(filter_score(&entry, "lvw").is_some());
+        assert!(filter_score(&entry, "wvl").is_none());
+    }
+
+    #[test]
+    fn filtered_indices_rank_by_score() {
+        let entries = vec![
+            make_entry_with_desc("lxvxw", "", vec![]),
+            make_entry_with_desc("unrelated", "", vec![]),
+            make_entry_with_desc("live-view-widget", "", vec![]),
+        ];
+        assert_eq!(filtered_indices(&entries, "lvw"), vec![2, 0]);
+        assert_eq!(filtered_indices(&entries, ""), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn filtered_indices_apply_predicates() {
+        let mut stale = make_entry_with_desc("old-login", "", vec![]);
+        stale.is_stale = true;
+        let entries = vec![
+            make_entry_with_desc("new-login", "", vec!["release"]),
+            stale,
+            make_entry_with_desc("signup", "", vec!["release"]),
+        ];
+        assert_eq!(filtered_indices(&entries, "stale:no"), vec![0, 2]);
+        assert_eq!(filtered_indices(&entries, "stale:no login"), vec![0]);
+        assert_eq!(filtered_indices(&entries, "bookmark:rel"), vec![0, 2]);
+        assert_eq!(filtered_indices(&entries, "repo:repo"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn filtered_indices_ties_keep_sort_order() {
+        let entries = vec![
+            make_entry_with_desc("ws-b", "", vec![]),
+            make_entry_with_desc("ws-a", "", vec![]),
+        ];
+        assert_eq!(filtered_indices(&entries, "ws"), vec![0, 1]);
     }
 
     #[test]
     fn filter_no_match() {
         let entry = make_entry_with_desc("ws1", "some desc", vec!["bk1"]);
-        assert!(!matches_filter(&entry, "zzz"));
+        assert!(filter_score(&entry, "zzz").is_none());
     }
 
     #[test]
@@ -1610,14 +4664,42 @@ mod tests {
         entries: Vec<WorkspaceEntry>,
         keys: Vec<KeyCode>,
     ) -> Result<Option<PickerResult>> {
-        run_picker_with_keys_and_callbacks(entries, keys, &mut |_| Ok(false), &mut || Ok(vec![]))
+        run_picker_with_keys_and_callbacks(entries, keys, &mut |_, _| Ok(false), &mut || Ok(vec![]))
+    }
+
+    /// Drive run_picker_inner with a custom rename callback.
+    fn run_picker_with_keys_and_rename(
+        entries: Vec<WorkspaceEntry>,
+        keys: Vec<KeyCode>,
+        on_rename: &mut dyn FnMut(&str, &str) -> Result<bool>,
+        list_entries: &mut dyn FnMut() -> Result<Vec<WorkspaceEntry>>,
+    ) -> Result<Option<PickerResult>> {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend)?;
+        let mut key_iter = keys.into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match key_iter.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            on_rename,
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            list_entries,
+        )
     }
 
     /// Like `run_picker_with_keys` but with custom delete/refresh callbacks.
     fn run_picker_with_keys_and_callbacks(
         entries: Vec<WorkspaceEntry>,
         keys: Vec<KeyCode>,
-        on_delete: &mut dyn FnMut(&str) -> Result<bool>,
+        on_delete: &mut dyn FnMut(&str, bool) -> Result<bool>,
         list_entries: &mut dyn FnMut() -> Result<Vec<WorkspaceEntry>>,
     ) -> Result<Option<PickerResult>> {
         let backend = TestBackend::new(120, 30);
@@ -1625,12 +4707,18 @@ mod tests {
         let mut key_iter = keys.into_iter();
         run_picker_inner(
             &mut terminal,
-            App::new(entries),
+            &mut App::new(entries),
             &mut || match key_iter.next() {
                 Some(code) => Ok(Some(key(code))),
                 None => Ok(Some(key(KeyCode::Esc))),
             },
             on_delete,
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
             list_entries,
         )
     }
@@ -1643,12 +4731,21 @@ mod tests {
         let backend = TestBackend::new(120, 30);
         let mut terminal = Terminal::new(backend)?;
         let mut key_iter = keys.into_iter();
-        run_picker_multi_repo_inner(&mut terminal, MultiRepoApp::new(entries), &mut || {
-            match key_iter.next() {
+        run_picker_multi_repo_inner(
+            &mut terminal,
+            MultiRepoApp::new(entries),
+            &mut || match key_iter.next() {
                 Some(code) => Ok(Some(key(code))),
                 None => Ok(Some(key(KeyCode::Esc))),
-            }
-        })
+            },
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
     }
 
     /// Create a named entry with a specific recency rank.
@@ -1658,16 +4755,10 @@ mod tests {
             name: name.to_string(),
             path: PathBuf::from(path),
             last_modified: Some(SystemTime::now() - Duration::from_secs(rank)),
-            diff_stat: DiffStat::default(),
-            is_main: false,
             change_id: "abc".to_string(),
             description: format!("{} description", name),
-            bookmarks: vec![],
-            is_stale: false,
-            repo_name: None,
             main_repo_path: PathBuf::from("/tmp/repo"),
-            vcs_type: crate::vcs::VcsType::Jj,
-            agent_status: None,
+            ..Default::default()
         }
     }
 
@@ -1773,6 +4864,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tui_create_new_keeps_an_invalid_name_open() {
+        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
+        // "a/b" is refused, so two backspaces and Enter create "a".
+        let result = run_picker_with_keys(
+            entries,
+            vec![
+                KeyCode::Char('j'),
+                KeyCode::Char('a'),
+                KeyCode::Char('/'),
+                KeyCode::Char('b'),
+                KeyCode::Enter,
+                KeyCode::Backspace,
+                KeyCode::Backspace,
+                KeyCode::Enter,
+            ],
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::CreateNew(Some(name))) => assert_eq!(name, "a"),
+            other => panic!("expected CreateNew(Some(\"a\")), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn main_workspace_name_is_invalid_for_new_ones() {
+        let mut main = make_named_entry("default", "/tmp/repo");
+        main.is_main = true;
+        let entries = vec![main, make_named_entry("ws1", "/tmp/ws1")];
+        assert_eq!(
+            invalid_name(&entries, "default").as_deref(),
+            Some("'default' is reserved for the main workspace in jj repos; choose another name")
+        );
+        assert_eq!(invalid_name(&entries, "ws2"), None);
+        assert!(invalid_name(&entries, ".x").is_some());
+    }
+
     #[test]
     fn tui_create_new_with_name() {
         let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
@@ -1811,7 +4939,7 @@ mod tests {
                 KeyCode::Char('y'), // confirm
                 KeyCode::Enter,     // select first entry (now ws2)
             ],
-            &mut |name| {
+            &mut |name, _| {
                 deleted_name = name.to_string();
                 Ok(false) // no redirect
             },
@@ -1831,6 +4959,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn tui_delete_forces_only_work_the_prompt_warned_about() {
+        let mut dirty = make_named_entry_ranked("ws2", "/tmp/ws2", 1);
+        dirty.working_copy.uncommitted = true;
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0), dirty];
+        let mut forced = Vec::new();
+        run_picker_with_keys_and_callbacks(
+            entries,
+            vec![
+                KeyCode::Char('d'), // ws1 looks clean, so isn't forced
+                KeyCode::Char('y'),
+                KeyCode::Char('j'),
+                KeyCode::Char('d'), // ws2's prompt warns, so is forced
+                KeyCode::Char('y'),
+            ],
+            &mut |name, force| {
+                forced.push((name.to_string(), force));
+                if force {
+                    Ok(false)
+                } else {
+                    anyhow::bail!("workspace '{}' has uncommitted changes", name)
+                }
+            },
+            &mut || Ok(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]),
+        )
+        .unwrap();
+        // The refused delete left the picker open for the second.
+        assert_eq!(
+            forced,
+            vec![("ws1".to_string(), false), ("ws2".to_string(), true)]
+        );
+    }
+
     #[test]
     fn tui_delete_redirect_exits_picker() {
         let entries = vec![
@@ -1840,7 +5001,7 @@ mod tests {
         let result = run_picker_with_keys_and_callbacks(
             entries,
             vec![KeyCode::Char('d'), KeyCode::Char('y')],
-            &mut |_| Ok(true), // redirect happened
+            &mut |_, _| Ok(true), // redirect happened
             &mut || Ok(vec![]),
         )
         .unwrap();
@@ -1854,7 +5015,7 @@ mod tests {
         let result = run_picker_with_keys_and_callbacks(
             entries,
             vec![KeyCode::Char('d'), KeyCode::Char('y')],
-            &mut |_| Ok(false),
+            &mut |_, _| Ok(false),
             &mut || Ok(vec![]), // no entries left
         )
         .unwrap();
@@ -1879,13 +5040,19 @@ mod tests {
         // then we stop and inspect the buffer.
         run_picker_inner(
             &mut terminal,
-            App::new(entries),
+            &mut App::new(entries),
             &mut || match keys.next() {
                 Some(code) => Ok(Some(key(code))),
                 // After processing keys, send Esc to exit so we can check the last frame
                 None => Ok(Some(key(KeyCode::Esc))),
             },
-            &mut |_| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
             &mut || Ok(vec![make_named_entry_ranked("ws2", "/tmp/ws2", 0)]),
         )
         .unwrap();
@@ -1962,89 +5129,1987 @@ mod tests {
         let result = run_picker_with_keys(
             entries,
             vec![
-                KeyCode::Char('/'),
-                KeyCode::Char('b'),
-                KeyCode::Char('a'),
-                KeyCode::Char('n'),
-                KeyCode::Esc,
+                KeyCode::Char('/'),
+                KeyCode::Char('b'),
+                KeyCode::Char('a'),
+                KeyCode::Char('n'),
+                KeyCode::Esc,
+                KeyCode::Enter,
+            ],
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/apple"),
+            other => panic!("expected Selected apple, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_input_name_backspace_returns_to_browse() {
+        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
+        // j to Create row, type 'a' (enters InputName), backspace (returns to Browse), q to quit
+        let result = run_picker_with_keys(
+            entries,
+            vec![
+                KeyCode::Char('j'),
+                KeyCode::Char('a'),
+                KeyCode::Backspace,
+                KeyCode::Char('q'),
+            ],
+        )
+        .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn tui_input_name_esc_cancels() {
+        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
+        // j to Create row, type 'a', Esc cancels, q quits
+        let result = run_picker_with_keys(
+            entries,
+            vec![
+                KeyCode::Char('j'),
+                KeyCode::Char('a'),
+                KeyCode::Esc,
+                KeyCode::Char('q'),
+            ],
+        )
+        .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn tui_rename_flow() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let mut renamed = None;
+        let result = run_picker_with_keys_and_rename(
+            entries,
+            vec![
+                KeyCode::Char('r'), // rename ws1 (input prefilled with "ws1")
+                KeyCode::Backspace,
+                KeyCode::Char('9'),
+                KeyCode::Enter, // confirm rename to "ws9"
+                KeyCode::Enter, // select the renamed entry
+            ],
+            &mut |old, new| {
+                renamed = Some((old.to_string(), new.to_string()));
+                Ok(false)
+            },
+            &mut || {
+                Ok(vec![
+                    make_named_entry_ranked("ws2", "/tmp/ws2", 0),
+                    make_named_entry_ranked("ws9", "/tmp/ws9", 1),
+                ])
+            },
+        )
+        .unwrap();
+        assert_eq!(renamed, Some(("ws1".to_string(), "ws9".to_string())));
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws9"),
+            other => panic!("expected Selected(ws9) after rename, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_rename_esc_cancels() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut called = false;
+        let result = run_picker_with_keys_and_rename(
+            entries,
+            vec![
+                KeyCode::Char('r'),
+                KeyCode::Char('x'),
+                KeyCode::Esc,
+                KeyCode::Enter,
+            ],
+            &mut |_, _| {
+                called = true;
+                Ok(false)
+            },
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert!(!called);
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected(ws1), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_rename_refused_on_main() {
+        let entries = vec![
+            make_main_entry("default", "/tmp/main"),
+            make_named_entry_ranked("ws1", "/tmp/ws1", 1),
+        ];
+        let mut called = false;
+        // r on main does nothing, so Enter selects the main workspace
+        let result = run_picker_with_keys_and_rename(
+            entries,
+            vec![KeyCode::Char('r'), KeyCode::Enter],
+            &mut |_, _| {
+                called = true;
+                Ok(false)
+            },
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert!(!called);
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/main"),
+            other => panic!("expected Selected(main), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_rename_redirect_exits_picker() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let result = run_picker_with_keys_and_rename(
+            entries,
+            vec![KeyCode::Char('r'), KeyCode::Char('2'), KeyCode::Enter],
+            &mut |_, _| Ok(true),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn tui_rename_error_shows_status_message() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('r'), KeyCode::Char('2'), KeyCode::Enter].into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, new| anyhow::bail!("workspace '{}' already exists", new),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("rename failed: workspace 'ws12' already exists"),
+            "expected rename error in help bar, got:\n{}",
+            all_text
+        );
+    }
+
+    fn five_entries() -> Vec<WorkspaceEntry> {
+        (0..5)
+            .map(|i| make_named_entry_ranked(&format!("ws{}", i), &format!("/tmp/ws{}", i), i))
+            .collect()
+    }
+
+    fn selected_path(result: Option<PickerResult>) -> String {
+        match result {
+            Some(PickerResult::Selected(path)) => path,
+            other => panic!("expected Selected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn motion_target_moves_and_clamps() {
+        // 5 workspaces plus the create row
+        let total = 6;
+        assert_eq!(motion_target(Action::Down, None, 5, total, 2), 0);
+        assert_eq!(motion_target(Action::Up, None, 0, total, 2), 5);
+        assert_eq!(motion_target(Action::Down, Some(3), 1, total, 2), 4);
+        assert_eq!(motion_target(Action::Down, Some(30), 1, total, 2), 5);
+        assert_eq!(motion_target(Action::Up, Some(30), 4, total, 2), 0);
+        assert_eq!(motion_target(Action::HalfPageDown, None, 0, total, 2), 2);
+        assert_eq!(motion_target(Action::HalfPageDown, Some(2), 0, total, 2), 4);
+        assert_eq!(motion_target(Action::HalfPageUp, None, 1, total, 2), 0);
+        assert_eq!(motion_target(Action::Top, None, 4, total, 2), 0);
+        assert_eq!(motion_target(Action::Bottom, None, 0, total, 2), 4);
+        assert_eq!(motion_target(Action::Top, Some(3), 0, total, 2), 2);
+        assert_eq!(motion_target(Action::Bottom, Some(99), 0, total, 2), 5);
+        assert_eq!(motion_target(Action::Down, None, 0, 0, 2), 0);
+    }
+
+    #[test]
+    fn motion_feed_collects_counts_and_gg() {
+        let keymap = KeyMap::default();
+        let mut motion = Motion::default();
+        let mut feed = |code| {
+            let key = KeyEvent::new(code, KeyModifiers::NONE);
+            motion.feed(&key, keymap.action_for(&key))
+        };
+        assert_eq!(feed(KeyCode::Char('1')), (None, None));
+        assert_eq!(feed(KeyCode::Char('0')), (None, None));
+        assert_eq!(feed(KeyCode::Char('j')), (Some(Action::Down), Some(10)));
+        // A leading zero is not a count
+        assert_eq!(feed(KeyCode::Char('0')), (None, None));
+        assert_eq!(feed(KeyCode::Char('k')), (Some(Action::Up), None));
+        assert_eq!(feed(KeyCode::Char('g')), (None, None));
+        assert_eq!(feed(KeyCode::Char('g')), (Some(Action::Top), None));
+        // A single g followed by something else is dropped
+        assert_eq!(feed(KeyCode::Char('g')), (None, None));
+        assert_eq!(feed(KeyCode::Char('j')), (Some(Action::Down), None));
+        assert_eq!(feed(KeyCode::Home), (Some(Action::Top), None));
+    }
+
+    #[test]
+    fn tui_count_prefix_moves_several_rows() {
+        let result = run_picker_with_keys(
+            five_entries(),
+            vec![KeyCode::Char('3'), KeyCode::Char('j'), KeyCode::Enter],
+        )
+        .unwrap();
+        assert_eq!(selected_path(result), "/tmp/ws3");
+    }
+
+    #[test]
+    fn tui_bottom_and_top_jumps() {
+        let result =
+            run_picker_with_keys(five_entries(), vec![KeyCode::Char('G'), KeyCode::Enter]).unwrap();
+        assert_eq!(selected_path(result), "/tmp/ws4");
+
+        let result = run_picker_with_keys(
+            five_entries(),
+            vec![
+                KeyCode::Char('G'),
+                KeyCode::Char('g'),
+                KeyCode::Char('g'),
+                KeyCode::Enter,
+            ],
+        )
+        .unwrap();
+        assert_eq!(selected_path(result), "/tmp/ws0");
+    }
+
+    #[test]
+    fn tui_half_page_down_uses_table_height() {
+        // 30 rows leave 26 for the table body, so half a page is 13 rows
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let entries = (0..40)
+            .map(|i| make_named_entry_ranked(&format!("ws{}", i), &format!("/tmp/ws{}", i), i))
+            .collect();
+        let mut keys = vec![
+            Event::Key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL)),
+            key(KeyCode::Enter),
+        ]
+        .into_iter();
+        let result = run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || Ok(keys.next()),
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(selected_path(result), "/tmp/ws13");
+    }
+
+    #[test]
+    fn tui_multi_bottom_jumps_to_last_workspace() {
+        let entries = vec![
+            make_repo_entry("alpha", "a1"),
+            make_repo_entry("alpha", "a2"),
+            make_repo_entry("beta", "b1"),
+        ];
+        let result =
+            run_multi_picker_with_keys(entries, vec![KeyCode::Char('G'), KeyCode::Enter]).unwrap();
+        assert_eq!(selected_path(result), "/tmp/beta/b1");
+    }
+
+    fn entries_with_waiting(waiting: &[usize]) -> Vec<WorkspaceEntry> {
+        let mut entries = five_entries();
+        for &i in waiting {
+            entries[i].agent_status = Some(AgentSummary {
+                waiting: 1,
+                working: 0,
+                idle: 0,
+                ..Default::default()
+            });
+        }
+        entries
+    }
+
+    #[test]
+    fn tui_next_waiting_cycles_through_waiting_rows() {
+        for (presses, expected) in [(1, "/tmp/ws1"), (2, "/tmp/ws3"), (3, "/tmp/ws1")] {
+            let mut keys = vec![KeyCode::Char('w'); presses];
+            keys.push(KeyCode::Enter);
+            let result = run_picker_with_keys(entries_with_waiting(&[1, 3]), keys).unwrap();
+            assert_eq!(selected_path(result), expected, "after {} presses", presses);
+        }
+    }
+
+    #[test]
+    fn tui_next_waiting_reports_when_none() {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('w')].into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(five_entries()),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("no agents waiting"), "{}", all_text);
+    }
+
+    #[test]
+    fn backend_errors_show_in_row_and_help_bar() {
+        let mut entries = vec![make_entry("broken", Some(60), 0, 0)];
+        entries[0].error = Some(crate::vcs::VcsError {
+            operation: "diff stat".to_string(),
+            message: "corrupt worktree".to_string(),
+        });
+        let mut app = App::new(entries);
+        let mut terminal = Terminal::new(TestBackend::new(120, 10)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("⚠ error"), "{}", all_text);
+        assert!(!all_text.contains("clean"), "{}", all_text);
+        assert!(
+            all_text.contains("⚠ diff stat: corrupt worktree"),
+            "{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn waiting_rows_get_attention_background() {
+        let mut app = App::new(entries_with_waiting(&[2]));
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let buf = terminal.backend().buffer();
+        // Rows start below the border and header; ws0 is selected.
+        assert_eq!(buf[(2, 4)].bg, Theme::DARK.attention);
+        assert_ne!(buf[(2, 3)].bg, Theme::DARK.attention);
+    }
+
+    #[test]
+    fn tui_multi_next_waiting_skips_headers() {
+        let mut entries = vec![
+            make_repo_entry("alpha", "a1"),
+            make_repo_entry("beta", "b1"),
+        ];
+        entries[1].agent_status = Some(AgentSummary {
+            waiting: 2,
+            working: 0,
+            idle: 0,
+            ..Default::default()
+        });
+        let result =
+            run_multi_picker_with_keys(entries, vec![KeyCode::Char('w'), KeyCode::Enter]).unwrap();
+        assert_eq!(selected_path(result), "/tmp/beta/b1");
+    }
+
+    fn entries_with_stale(stale: &[usize]) -> Vec<WorkspaceEntry> {
+        let mut entries = five_entries();
+        for &i in stale {
+            entries[i].is_stale = true;
+        }
+        entries
+    }
+
+    #[test]
+    fn tui_cleanup_deletes_marked_stale_workspaces() {
+        let mut deleted = Vec::new();
+        let result = run_picker_with_keys_and_callbacks(
+            entries_with_stale(&[1, 3]),
+            vec![
+                KeyCode::Char('c'),
+                KeyCode::Char('j'), // ws3
+                KeyCode::Char(' '), // unmark it
+                KeyCode::Enter,
+                KeyCode::Char('y'),
+                KeyCode::Enter,
+            ],
+            &mut |name, _| {
+                deleted.push(name.to_string());
+                Ok(false)
+            },
+            &mut || {
+                let mut entries = entries_with_stale(&[3]);
+                entries.remove(1);
+                Ok(entries)
+            },
+        )
+        .unwrap();
+        assert_eq!(deleted, vec!["ws1"]);
+        // Back to the full list, still on the unmarked workspace.
+        assert_eq!(selected_path(result), "/tmp/ws3");
+    }
+
+    #[test]
+    fn tui_cleanup_shows_only_stale_and_esc_restores() {
+        let mut app = App::new(entries_with_stale(&[1, 3]));
+        app.start_cleanup();
+        assert_eq!(app.filtered_indices, vec![1, 3]);
+        app.end_cleanup();
+        assert_eq!(app.filtered_indices, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn tui_cleanup_reports_when_nothing_stale() {
+        let mut app = App::new(five_entries());
+        app.start_cleanup();
+        assert_eq!(app.mode, Mode::Browse);
+        assert_eq!(app.status.current().unwrap().text, "no stale workspaces");
+    }
+
+    #[test]
+    fn tui_cleanup_never_offers_main_workspace() {
+        let mut entries = entries_with_stale(&[0, 2]);
+        entries[0].is_main = true;
+        let mut app = App::new(entries);
+        app.start_cleanup();
+        assert_eq!(app.filtered_indices, vec![2]);
+    }
+
+    #[test]
+    fn render_cleanup_confirm_lists_unmerged_work() {
+        let mut entries = entries_with_stale(&[1, 3]);
+        entries[3].diff_stat = crate::vcs::DiffStat {
+            files_changed: 3,
+            insertions: 10,
+            deletions: 2,
+            ..Default::default()
+        };
+        let mut app = App::new(entries);
+        app.start_cleanup();
+        if let Mode::Cleanup(ref mut cleanup) = app.mode {
+            cleanup.confirming = true;
+        }
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("Delete 2 workspaces?"), "{}", all_text);
+        assert!(all_text.contains("ws1  nothing unmerged"), "{}", all_text);
+        assert!(
+            all_text.contains("ws3  3 files unmerged (+10 -2)"),
+            "{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn delete_all_continues_past_failures() {
+        let targets = vec![
+            ("ws1".to_string(), PathBuf::from("/tmp/ws1")),
+            ("ws2".to_string(), PathBuf::from("/tmp/ws2")),
+            ("ws3".to_string(), PathBuf::from("/tmp/ws3")),
+        ];
+        let (redirected, level, message) = delete_all(&targets, |name, _| match name {
+            "ws2" => anyhow::bail!("locked"),
+            "ws3" => Ok(true),
+            _ => Ok(false),
+        });
+        assert!(redirected);
+        assert_eq!(level, Level::Warn);
+        assert_eq!(message, "deleted 2 workspaces; failed: ws2: locked");
+    }
+
+    #[test]
+    fn tui_multi_cleanup_deletes_by_path() {
+        let mut entries = vec![
+            make_repo_entry("alpha", "a1"),
+            make_repo_entry("beta", "b1"),
+            make_repo_entry("beta", "b2"),
+        ];
+        entries[0].is_stale = true;
+        entries[2].is_stale = true;
+        let mut deleted = Vec::new();
+        let (result, _) = run_multi_picker_with_delete(
+            entries,
+            vec![KeyCode::Char('c'), KeyCode::Enter, KeyCode::Char('y')],
+            &mut |path, _| {
+                deleted.push(path.to_path_buf());
+                Ok(false)
+            },
+            &mut || Ok(vec![make_repo_entry("beta", "b1")]),
+        );
+        result.unwrap();
+        assert_eq!(
+            deleted,
+            vec![
+                PathBuf::from("/tmp/alpha/a1"),
+                PathBuf::from("/tmp/beta/b2")
+            ]
+        );
+    }
+
+    #[test]
+    fn tui_menu_navigates_to_rename() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut renamed = None;
+        let result = run_picker_with_keys_and_rename(
+            entries,
+            vec![
+                KeyCode::Char('a'),
+                KeyCode::Char('j'), // open in editor
+                KeyCode::Char('j'), // copy path
+                KeyCode::Char('j'), // rename
+                KeyCode::Enter,
+                KeyCode::Char('2'),
+                KeyCode::Enter,
+                KeyCode::Char('q'),
+            ],
+            &mut |old, new| {
+                renamed = Some((old.to_string(), new.to_string()));
+                Ok(false)
+            },
+            &mut || Ok(vec![make_named_entry_ranked("ws12", "/tmp/ws12", 0)]),
+        )
+        .unwrap();
+        assert!(result.is_none());
+        assert_eq!(renamed, Some(("ws1".to_string(), "ws12".to_string())));
+    }
+
+    #[test]
+    fn tui_menu_item_key_chooses_it() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut deleted = Vec::new();
+        run_picker_with_keys_and_callbacks(
+            entries,
+            vec![KeyCode::Char('a'), KeyCode::Char('d'), KeyCode::Char('y')],
+            &mut |name, _| {
+                deleted.push(name.to_string());
+                Ok(false)
+            },
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(deleted, vec!["ws1"]);
+    }
+
+    #[test]
+    fn tui_menu_esc_closes_without_quitting() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let result = run_picker_with_keys(
+            entries,
+            vec![KeyCode::Char('a'), KeyCode::Esc, KeyCode::Enter],
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected(ws1), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn changes_column_shows_commits_ahead_and_behind() {
+        let mut entry = make_entry("ws1", Some(0), 3, 0);
+        entry.diff_stat.ahead = 2;
+        entry.diff_stat.behind = 40;
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new(vec![entry]);
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("+3 ↑2 ↓40"), "{}", all_text);
+    }
+
+    #[test]
+    fn tui_menu_renders_items_with_keys() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new(entries);
+        app.menu = Some(ActionMenu::for_entry(&app.entries[0], SINGLE_REPO_MENU));
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains(" ws1 "), "{}", all_text);
+        assert!(all_text.contains("r      rename workspace"), "{}", all_text);
+        assert!(all_text.contains(MENU_HELP.trim()), "{}", all_text);
+    }
+
+    #[test]
+    fn menu_omits_delete_and_rename_on_main() {
+        let main = make_main_entry("default", "/tmp/main");
+        let menu = ActionMenu::for_entry(&main, SINGLE_REPO_MENU);
+        assert_eq!(
+            menu.items,
+            vec![
+                Action::Select,
+                Action::Open,
+                Action::Copy,
+                Action::Agents,
+                Action::Launch,
+                Action::Tmux,
+                Action::Push,
+                Action::Sync,
+                Action::Pr,
+                Action::Ci
+            ]
+        );
+    }
+
+    #[test]
+    fn menu_key_wraps_selection() {
+        let keymap = KeyMap::default();
+        let entry = make_named_entry_ranked("ws1", "/tmp/ws1", 0);
+        let mut menu = Some(ActionMenu::for_entry(&entry, MULTI_REPO_MENU));
+        let up = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE);
+        assert_eq!(menu_key(&mut menu, &keymap, &up), None);
+        assert_eq!(menu.as_ref().unwrap().selected, 10);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(menu_key(&mut menu, &keymap, &enter), Some(Action::Ci));
+        assert!(menu.is_none());
+    }
+
+    #[test]
+    fn tui_open_calls_callback_with_selected_path() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('j'), KeyCode::Char('o'), KeyCode::Char('q')].into_iter();
+        let mut opened = Vec::new();
+        let result = run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |path| {
+                opened.push(path.to_path_buf());
+                Ok(())
+            },
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        // The picker keeps running after the editor exits
+        assert!(result.is_none());
+        assert_eq!(opened, vec![PathBuf::from("/tmp/ws2")]);
+    }
+
+    #[test]
+    fn tui_tmux_key_returns_selected_path() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('j'), KeyCode::Char('t')].into_iter();
+        let result = run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert!(matches!(result, Some(PickerResult::Tmux(p)) if p == "/tmp/ws2"));
+    }
+
+    #[test]
+    fn tui_pr_key_returns_selected_path() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('j'), KeyCode::Char('R')].into_iter();
+        let result = run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert!(matches!(result, Some(PickerResult::Pr(p)) if p == "/tmp/ws2"));
+    }
+
+    #[test]
+    fn tui_ci_key_opens_the_run_in_the_browser() {
+        let mut with_ci = make_named_entry_ranked("ws1", "/tmp/ws1", 0);
+        with_ci.ci = Some(crate::forge::CiRun {
+            checks: Checks::Failing,
+            url: "https://ci.example.com/runs/7".to_string(),
+        });
+        let entries = vec![with_ci, make_named_entry_ranked("ws2", "/tmp/ws2", 1)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![
+            KeyCode::Char('j'),
+            KeyCode::Char('C'),
+            KeyCode::Char('k'),
+            KeyCode::Char('C'),
+        ]
+        .into_iter();
+        let mut opened = Vec::new();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |url| {
+                opened.push(url.to_string());
+                Ok(())
+            },
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(opened, vec!["https://ci.example.com/runs/7"]);
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("no CI run for ws2"), "{}", all_text);
+    }
+
+    #[test]
+    fn tui_open_error_shows_status_message() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('o')].into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| anyhow::bail!("no editor configured"),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("open failed: no editor configured"),
+            "expected open error in help bar, got:\n{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn tui_push_reports_remote_branch() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('P')].into_iter();
+        let mut pushed = Vec::new();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |path| {
+                pushed.push(path.to_path_buf());
+                Ok("origin/ws1".to_string())
+            },
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(pushed, vec![PathBuf::from("/tmp/ws1")]);
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("pushed ws1 to origin/ws1"),
+            "expected push status in help bar, got:\n{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn tui_sync_conflict_shows_error() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('S')].into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| anyhow::bail!("rebasing onto main conflicts in a.txt"),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("sync failed: rebasing onto main conflicts in a.txt"),
+            "expected sync error in help bar, got:\n{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn tui_agents_popup_lists_sessions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        agent::write_agent_status(
+            dir.path(),
+            "0123456789abcdef",
+            agent::AgentKind::Claude,
+            "ws1",
+            AgentStatus::Waiting,
+            agent::AgentActivity::Prompt("refactor the parser"),
+            None,
+        )
+        .unwrap();
+        agent::write_agent_status(
+            dir.path(),
+            "0123456789abcdef",
+            agent::AgentKind::Claude,
+            "ws1",
+            AgentStatus::Waiting,
+            agent::AgentActivity::Tool("Edit", Some("src/parser.rs")),
+            None,
+        )
+        .unwrap();
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.repo_dir = Some(dir.path().to_path_buf());
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('A')].into_iter();
+        let mut done = false;
+        run_picker_inner(
+            &mut terminal,
+            &mut app,
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                // Redraw once with the popup open, then bail out before
+                // another key can dismiss it
+                None if !done => {
+                    done = true;
+                    Ok(None)
+                }
+                None => anyhow::bail!("done"),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap_err();
+        assert!(app.agents.is_some());
+        let text = buffer_lines(&terminal).join("\n");
+        assert!(text.contains("Agents in ws1"), "{}", text);
+        assert!(
+            text.contains("waiting claude   01234567 just now"),
+            "{}",
+            text
+        );
+        assert!(text.contains("prompt: refactor the parser"), "{}", text);
+        assert!(text.contains("tool: Edit"), "{}", text);
+        assert!(text.contains("last touched: src/parser.rs"), "{}", text);
+    }
+
+    #[test]
+    fn tui_agents_popup_closes_on_any_key() {
+        let result = run_picker_with_keys(
+            vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)],
+            vec![KeyCode::Char('A'), KeyCode::Char('j'), KeyCode::Enter],
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected(ws1), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn agents_popup_without_sessions_says_so() {
+        let backend = TestBackend::new(80, 20);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let popup = AgentPopup {
+            title: "ws1".to_string(),
+            sessions: Vec::new(),
+        };
+        terminal
+            .draw(|f| render_agent_popup(f, &popup, &Theme::default()))
+            .unwrap();
+        let text = buffer_lines(&terminal).join("\n");
+        assert!(text.contains("no active agent sessions"), "{}", text);
+    }
+
+    #[test]
+    fn tui_copy_copies_selected_path() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('j'), KeyCode::Char('y')].into_iter();
+        let mut copied = Vec::new();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |text| {
+                copied.push(text.to_string());
+                Ok(())
+            },
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(copied, vec!["/tmp/ws2"]);
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("copied /tmp/ws2"), "{}", all_text);
+    }
+
+    #[test]
+    fn tui_copy_error_shows_status_message() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('y')].into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| anyhow::bail!("no clipboard available"),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("copy failed: no clipboard available"),
+            "expected copy error in help bar, got:\n{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn tui_status_message_survives_keypresses() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('y'), KeyCode::Char('j'), KeyCode::Char('k')].into_iter();
+        let result = run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => anyhow::bail!("done"),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        );
+        result.unwrap_err();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("copied /tmp/ws1"), "{}", all_text);
+    }
+
+    #[test]
+    fn refresh_error_shows_in_help_bar() {
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        *app.refresh_error_mailbox.sender().lock().unwrap() = Some("jj not found".to_string());
+        app.drain_refresh_mailbox();
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let lines = buffer_lines(&terminal);
+        let help = lines.last().unwrap();
+        assert!(help.contains("refresh failed: jj not found"), "{}", help);
+        assert_eq!(terminal.backend().buffer()[(1, 29)].fg, Theme::DARK.danger);
+    }
+
+    #[test]
+    fn prompt_takes_precedence_over_status_message() {
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.status.info("copied /tmp/ws1");
+        app.mode = Mode::ConfirmDelete("ws1".to_string());
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let help = buffer_lines(&terminal).pop().unwrap();
+        assert!(help.contains("Delete 'ws1'? y: confirm"), "{}", help);
+        // The message is still queued for when the prompt closes.
+        app.mode = Mode::Browse;
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let help = buffer_lines(&terminal).pop().unwrap();
+        assert!(help.contains("copied /tmp/ws1"), "{}", help);
+    }
+
+    #[test]
+    fn posting_errors_forwards_failures() {
+        let errors = Arc::new(Mutex::new(None));
+        let mut fail = posting_errors(Arc::clone(&errors), || -> Result<u32> {
+            anyhow::bail!("boom")
+        });
+        assert_eq!(fail(), None);
+        assert_eq!(errors.lock().unwrap().take().as_deref(), Some("boom"));
+        let mut succeed = posting_errors(Arc::clone(&errors), || Ok(7));
+        assert_eq!(succeed(), Some(7));
+        assert_eq!(*errors.lock().unwrap(), None);
+    }
+
+    fn click(column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    fn scroll(kind: MouseEventKind) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column: 10,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    /// Drive the single-repo picker with arbitrary events, ending with Esc.
+    fn run_picker_with_events(
+        entries: Vec<WorkspaceEntry>,
+        events: Vec<Event>,
+    ) -> Result<Option<PickerResult>> {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend)?;
+        let mut events = events.into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut App::new(entries),
+            &mut || Ok(Some(events.next().unwrap_or_else(|| key(KeyCode::Esc)))),
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+    }
+
+    fn three_entries() -> Vec<WorkspaceEntry> {
+        vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+            make_named_entry_ranked("ws3", "/tmp/ws3", 2),
+        ]
+    }
+
+    #[test]
+    fn tui_mouse_click_selects_row() {
+        // Row 2 is the first data row (border + header above it).
+        let result =
+            run_picker_with_events(three_entries(), vec![click(5, 4), key(KeyCode::Enter)])
+                .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws3"),
+            other => panic!("expected Selected ws3, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_mouse_double_click_activates_row() {
+        let result =
+            run_picker_with_events(three_entries(), vec![click(5, 3), click(5, 3)]).unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws2"),
+            other => panic!("expected Selected ws2, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_mouse_double_click_on_create_row() {
+        // ws1..ws3 occupy rows 2-4, so the create row is at 5.
+        let result =
+            run_picker_with_events(three_entries(), vec![click(5, 5), click(5, 5)]).unwrap();
+        assert!(matches!(result, Some(PickerResult::CreateNew(None))));
+    }
+
+    #[test]
+    fn tui_mouse_click_below_rows_is_ignored() {
+        let result =
+            run_picker_with_events(three_entries(), vec![click(5, 20), key(KeyCode::Enter)])
+                .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected ws1, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_mouse_scroll_moves_without_wrapping() {
+        let result = run_picker_with_events(
+            three_entries(),
+            vec![
+                scroll(MouseEventKind::ScrollUp),
+                scroll(MouseEventKind::ScrollDown),
+                scroll(MouseEventKind::ScrollDown),
+                scroll(MouseEventKind::ScrollUp),
+                key(KeyCode::Enter),
+            ],
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws2"),
+            other => panic!("expected Selected ws2, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_mouse_header_click_sorts() {
+        let entries = vec![
+            make_named_entry_ranked("zeta", "/tmp/zeta", 0),
+            make_named_entry_ranked("alpha", "/tmp/alpha", 1),
+        ];
+        // The Name column starts just inside the left border.
+        let result =
+            run_picker_with_events(entries, vec![click(2, 1), key(KeyCode::Enter)]).unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/alpha"),
+            other => panic!("expected Selected alpha, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn mouse_state_maps_header_to_column() {
+        let mouse = MouseState {
+            table_area: Rect::new(0, 0, 100, 20),
+            last_click: None,
+        };
+        let columns = vec![
+            ColumnSpec::Sized {
+                name: Column::Name,
+                width: 10,
+            },
+            ColumnSpec::Auto(Column::Description),
+        ];
+        let event = |column| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row: 1,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(
+            mouse.target(&event(5), &columns, 0),
+            Some(MouseTarget::Header(Column::Name))
+        );
+        assert_eq!(
+            mouse.target(&event(20), &columns, 0),
+            Some(MouseTarget::Header(Column::Description))
+        );
+        assert_eq!(mouse.target(&event(0), &columns, 0), None);
+    }
+
+    #[test]
+    fn tui_multi_mouse_double_click_selects() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        // Row 2 is the repo group header, so ws2 is on row 4.
+        let mut events = vec![click(5, 4), click(5, 4)].into_iter();
+        let result = run_picker_multi_repo_inner(
+            &mut terminal,
+            MultiRepoApp::new(entries),
+            &mut || Ok(Some(events.next().unwrap_or_else(|| key(KeyCode::Esc)))),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws2"),
+            other => panic!("expected Selected ws2, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn render_preview_converts_ansi_graph() {
+        let backend = TestBackend::new(60, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let preview = PreviewState::Ready {
+            log: String::new(),
+            graph: "\x1b[33m*\x1b[m abc123 \x1b[1mfeature\x1b[0m\n|/\n".to_string(),
+            diff_stat: " 1 file changed".to_string(),
+            agents: String::new(),
+            touched: String::new(),
+            issue: None,
+        };
+        terminal
+            .draw(|f| render_preview(f, f.area(), &preview, &Theme::default()))
+            .unwrap();
+        let lines = buffer_lines(&terminal);
+        let all_text = lines.join("\n");
+        assert!(!all_text.contains('\x1b'), "escapes leaked:\n{}", all_text);
+        assert!(all_text.contains("* abc123 feature"), "got:\n{}", all_text);
+        assert!(all_text.contains("|/"), "got:\n{}", all_text);
+        // The graph marker keeps its color.
+        let row = lines.iter().position(|l| l.contains("* abc123")).unwrap() as u16;
+        let col = lines[row as usize].chars().position(|c| c == '*').unwrap() as u16;
+        let cell = &terminal.backend().buffer()[(col, row)];
+        assert_eq!(cell.fg, Color::Yellow);
+    }
+
+    #[test]
+    fn render_preview_shows_agent_time() {
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        let preview = PreviewState::Ready {
+            log: "abc123 entry".to_string(),
+            graph: String::new(),
+            diff_stat: String::new(),
+            agents: "working 42m, waiting 3m".to_string(),
+            touched: "src/tui.rs, src/agent.rs".to_string(),
+            issue: None,
+        };
+        terminal
+            .draw(|f| render_preview(f, f.area(), &preview, &Theme::default()))
+            .unwrap();
+        let lines = buffer_lines(&terminal);
+        assert!(lines[1].contains("--- agents ---"), "{:?}", lines);
+        assert!(lines[2].contains("working 42m, waiting 3m"), "{:?}", lines);
+        assert!(
+            lines[3].contains("last touched: src/tui.rs, src/agent.rs"),
+            "{:?}",
+            lines
+        );
+    }
+
+    #[test]
+    fn render_preview_shows_linked_issue() {
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        let preview = PreviewState::Ready {
+            log: "abc123 entry".to_string(),
+            graph: String::new(),
+            diff_stat: String::new(),
+            agents: String::new(),
+            touched: String::new(),
+            issue: Some(crate::forge::Issue {
+                number: 1234,
+                title: "Fix login timeout".to_string(),
+                url: "https://github.com/me/api/issues/1234".to_string(),
+            }),
+        };
+        terminal
+            .draw(|f| render_preview(f, f.area(), &preview, &Theme::default()))
+            .unwrap();
+        let lines = buffer_lines(&terminal);
+        assert!(lines[1].contains("--- issue ---"), "{:?}", lines);
+        assert!(lines[2].contains("#1234 Fix login timeout"), "{:?}", lines);
+        assert!(
+            lines[3].contains("https://github.com/me/api/issues/1234"),
+            "{:?}",
+            lines
+        );
+        assert!(lines[5].contains("--- log ---"), "{:?}", lines);
+    }
+
+    #[test]
+    fn render_preview_falls_back_to_plain_log() {
+        let backend = TestBackend::new(60, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let preview = PreviewState::Ready {
+            log: "abc123 plain entry".to_string(),
+            graph: String::new(),
+            diff_stat: String::new(),
+            agents: String::new(),
+            touched: String::new(),
+            issue: None,
+        };
+        terminal
+            .draw(|f| render_preview(f, f.area(), &preview, &Theme::default()))
+            .unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("abc123 plain entry"),
+            "got:\n{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn render_uses_configured_columns() {
+        let backend = TestBackend::new(120, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        let specs: Vec<ColumnSpec> =
+            serde_json::from_str(r#"["description", {"name": "name", "width": 12}]"#).unwrap();
+        app.columns = columns::resolve(Some(&specs), false);
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let lines = buffer_lines(&terminal);
+        let header = &lines[1];
+        assert!(header.contains("Description"), "got: {}", header);
+        assert!(!header.contains("Change"), "got: {}", header);
+        assert!(!header.contains("Bookmarks"), "got: {}", header);
+        assert!(
+            header.find("Description").unwrap() < header.find("Name").unwrap(),
+            "columns out of order: {}",
+            header
+        );
+    }
+
+    #[test]
+    fn render_uses_theme_colors() {
+        let backend = TestBackend::new(120, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.theme = Theme::LIGHT;
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let buf = terminal.backend().buffer();
+        // Header row, then the selected ws1 row.
+        assert_eq!(buf[(1, 1)].bg, Theme::LIGHT.header);
+        assert_eq!(buf[(1, 2)].bg, Theme::LIGHT.highlight);
+        assert_eq!(buf[(1, 2)].fg, Theme::LIGHT.name);
+    }
+
+    #[test]
+    fn render_multi_repo_shows_repo_column_by_default() {
+        let backend = TestBackend::new(120, 10);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = MultiRepoApp::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        terminal.draw(|f| render_multi_repo(f, &mut app)).unwrap();
+        let lines = buffer_lines(&terminal);
+        assert!(lines[1].contains("Repo"), "got: {}", lines[1]);
+    }
+
+    #[test]
+    fn tui_help_popup_lists_bindings() {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.show_help = true;
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("Help (any key to close)"),
+            "got:\n{}",
+            all_text
+        );
+        assert!(all_text.contains("delete workspace"), "got:\n{}", all_text);
+        assert!(
+            all_text.contains("recency → name → diff size"),
+            "got:\n{}",
+            all_text
+        );
+        for help in filter::HELP {
+            assert!(all_text.contains(help), "got:\n{}", all_text);
+        }
+    }
+
+    #[test]
+    fn tui_help_popup_swallows_next_key() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        // 'j' closes the popup without moving, so Enter selects ws1.
+        let result = run_picker_with_keys(
+            entries,
+            vec![KeyCode::Char('?'), KeyCode::Char('j'), KeyCode::Enter],
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected ws1, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_multi_help_popup_omits_unsupported_actions() {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = MultiRepoApp::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.show_help = true;
+        terminal.draw(|f| render_multi_repo(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("open in editor"), "got:\n{}", all_text);
+        assert!(!all_text.contains("rename workspace"), "got:\n{}", all_text);
+    }
+
+    fn custom_keymap() -> KeyMap {
+        let overrides: HashMap<String, crate::keymap::KeySpec> =
+            serde_json::from_str(r#"{"down": "n", "up": "e", "delete": "x"}"#).unwrap();
+        KeyMap::from_config(&overrides).unwrap()
+    }
+
+    #[test]
+    fn tui_custom_keymap_navigates_and_shows_effective_keys() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+            make_named_entry_ranked("ws3", "/tmp/ws3", 2),
+        ];
+        let backend = TestBackend::new(160, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = App::new(entries);
+        app.keymap = custom_keymap();
+        // 'j' is no longer bound, so only the two 'n' presses move the cursor.
+        let mut keys = vec![
+            KeyCode::Char('n'),
+            KeyCode::Char('j'),
+            KeyCode::Char('n'),
+            KeyCode::Enter,
+        ]
+        .into_iter();
+        let result = run_picker_inner(
+            &mut terminal,
+            &mut app,
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws3"),
+            other => panic!("expected Selected ws3, got {:?}", other),
+        }
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("n/e: navigate"), "got:\n{}", all_text);
+        assert!(all_text.contains("x: delete"), "got:\n{}", all_text);
+    }
+
+    // ── Multi-repo picker integration tests ─────────────────────────
+
+    #[test]
+    fn tui_multi_select_first() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let result = run_multi_picker_with_keys(entries, vec![KeyCode::Enter]).unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_multi_navigate_and_select() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let result =
+            run_multi_picker_with_keys(entries, vec![KeyCode::Char('j'), KeyCode::Enter]).unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws2"),
+            other => panic!("expected Selected ws2, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_multi_quit() {
+        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
+        let result = run_multi_picker_with_keys(entries, vec![KeyCode::Char('q')]).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn tui_multi_custom_keymap() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+        ];
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut app = MultiRepoApp::new(entries);
+        app.keymap = custom_keymap();
+        let mut keys = vec![KeyCode::Char('n'), KeyCode::Enter].into_iter();
+        let result = run_picker_multi_repo_inner(
+            &mut terminal,
+            app,
+            &mut || match keys.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        match result {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws2"),
+            other => panic!("expected Selected ws2, got {:?}", other),
+        }
+    }
+
+    /// Drive the multi-repo picker with `keys`, recording deleted paths.
+    fn run_multi_picker_with_delete(
+        entries: Vec<WorkspaceEntry>,
+        keys: Vec<KeyCode>,
+        on_delete: &mut dyn FnMut(&Path, bool) -> Result<bool>,
+        list_entries: &mut dyn FnMut() -> Result<Vec<WorkspaceEntry>>,
+    ) -> (Result<Option<PickerResult>>, Vec<String>) {
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut key_iter = keys.into_iter();
+        let result = run_picker_multi_repo_inner(
+            &mut terminal,
+            MultiRepoApp::new(entries),
+            &mut || match key_iter.next() {
+                Some(code) => Ok(Some(key(code))),
+                None => Ok(Some(key(KeyCode::Esc))),
+            },
+            on_delete,
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            list_entries,
+        );
+        (result, buffer_lines(&terminal))
+    }
+
+    #[test]
+    fn tui_multi_delete_flow() {
+        let entries = vec![
+            make_named_entry_ranked("ws1", "/tmp/repo-a/ws1", 0),
+            make_named_entry_ranked("ws2", "/tmp/repo-b/ws2", 1),
+        ];
+        let mut deleted = Vec::new();
+        let (result, _) = run_multi_picker_with_delete(
+            entries,
+            vec![
+                KeyCode::Char('j'),
+                KeyCode::Char('d'),
+                KeyCode::Char('y'),
+                KeyCode::Enter,
+            ],
+            &mut |path, _| {
+                deleted.push(path.to_path_buf());
+                Ok(false)
+            },
+            &mut || Ok(vec![make_named_entry_ranked("ws1", "/tmp/repo-a/ws1", 0)]),
+        );
+        assert_eq!(deleted, vec![PathBuf::from("/tmp/repo-b/ws2")]);
+        match result.unwrap() {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/repo-a/ws1"),
+            other => panic!("expected Selected ws1, got {:?}", other),
+        }
+    }
+
+    fn make_repo_entry(repo: &str, name: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            repo_name: Some(repo.to_string()),
+            main_repo_path: PathBuf::from(format!("/src/{}", repo)),
+            ..make_named_entry(name, &format!("/tmp/{}/{}", repo, name))
+        }
+    }
+
+    #[test]
+    fn multi_rows_group_by_repo() {
+        let entries = vec![
+            make_repo_entry("beta", "ws1"),
+            make_repo_entry("alpha", "ws2"),
+            WorkspaceEntry {
+                last_modified: None,
+                ..make_repo_entry("beta", "ws3")
+            },
+        ];
+        let app = MultiRepoApp::new(entries);
+        let labels: Vec<String> = app
+            .rows
+            .iter()
+            .map(|r| match r {
+                MultiRow::Group(name) => format!("[{}]", name),
+                MultiRow::Entry(i) => app.entries[*i].name.clone(),
+                MultiRow::Create => "+".to_string(),
+            })
+            .collect();
+        assert_eq!(labels, vec!["[alpha]", "ws2", "[beta]", "ws1", "ws3", "+"]);
+        // The cursor starts on the first workspace, not a header.
+        assert_eq!(app.selected, 1);
+    }
+
+    #[test]
+    fn render_multi_repo_shows_group_totals() {
+        let mut waiting = make_repo_entry("alpha", "ws1");
+        waiting.agent_status = Some(AgentSummary {
+            waiting: 2,
+            working: 0,
+            idle: 0,
+            ..Default::default()
+        });
+        let entries = vec![
+            waiting,
+            make_repo_entry("alpha", "ws2"),
+            make_repo_entry("beta", "ws3"),
+        ];
+        let mut app = MultiRepoApp::new(entries);
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| render_multi_repo(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("▾ alpha  2 workspaces, 2 waiting"),
+            "{}",
+            all_text
+        );
+        assert!(all_text.contains("▾ beta  1 workspace"), "{}", all_text);
+    }
+
+    #[test]
+    fn multi_repo_title_totals_agents_across_repos() {
+        let entries = vec![
+            make_repo_entry("alpha", "ws1"),
+            make_repo_entry("alpha", "ws2"),
+            make_repo_entry("beta", "ws3"),
+            make_repo_entry("gamma", "ws4"),
+        ];
+        let mut app = MultiRepoApp::new(entries);
+        assert_eq!(app.agent_totals(), None);
+
+        let agents = |waiting, working| AgentSummary {
+            waiting,
+            working,
+            ..Default::default()
+        };
+        let summaries = HashMap::from([
+            ("alpha:ws1".to_string(), agents(2, 1)),
+            ("alpha:ws2".to_string(), agents(1, 0)),
+            ("beta:ws3".to_string(), agents(0, 4)),
+        ]);
+        *app.agent_refresh_mailbox.0.lock().unwrap() = Some(summaries);
+        app.drain_refresh_mailbox();
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| render_multi_repo(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(
+            all_text.contains("3 waiting · 5 working across 2 repos"),
+            "{}",
+            all_text
+        );
+    }
+
+    #[test]
+    fn tui_multi_tab_collapses_group() {
+        let entries = vec![
+            make_repo_entry("alpha", "ws1"),
+            make_repo_entry("alpha", "ws2"),
+            make_repo_entry("beta", "ws3"),
+        ];
+        // Tab on ws1 collapses alpha and leaves the cursor on its header, so
+        // j lands on beta's header and another j on ws3.
+        let result = run_multi_picker_with_keys(
+            entries,
+            vec![
+                KeyCode::Tab,
+                KeyCode::Char('j'),
+                KeyCode::Char('j'),
+                KeyCode::Enter,
+            ],
+        );
+        match result.unwrap() {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/beta/ws3"),
+            other => panic!("expected Selected ws3, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn tui_multi_enter_on_header_toggles_group() {
+        let entries = vec![
+            make_repo_entry("alpha", "ws1"),
+            make_repo_entry("beta", "ws2"),
+        ];
+        let mut app = MultiRepoApp::new(entries);
+        app.previous();
+        assert!(app.activate().is_none());
+        assert!(app.collapsed.contains("alpha"));
+        assert_eq!(app.rows.len(), 4); // [alpha], [beta], ws2, +
+        assert!(app.activate().is_none());
+        assert!(app.collapsed.is_empty());
+        assert_eq!(app.rows.len(), 5);
+    }
+
+    #[test]
+    fn tui_multi_create_chooses_repo_then_name() {
+        let entries = vec![
+            make_repo_entry("beta", "ws1"),
+            make_repo_entry("alpha", "ws2"),
+            make_repo_entry("beta", "ws3"),
+        ];
+        // Up past the first group header wraps to the create row; repos are
+        // listed alphabetically.
+        let result = run_multi_picker_with_keys(
+            entries,
+            vec![
+                KeyCode::Up,
+                KeyCode::Up,
+                KeyCode::Enter,
+                KeyCode::Char('j'),
+                KeyCode::Enter,
+                KeyCode::Char('f'),
+                KeyCode::Char('o'),
+                KeyCode::Char('o'),
                 KeyCode::Enter,
             ],
-        )
-        .unwrap();
-        match result {
-            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/apple"),
-            other => panic!("expected Selected apple, got {:?}", other),
+        );
+        match result.unwrap() {
+            Some(PickerResult::CreateNewIn { repo, name }) => {
+                assert_eq!(repo, PathBuf::from("/src/beta"));
+                assert_eq!(name.as_deref(), Some("foo"));
+            }
+            other => panic!("expected CreateNewIn, got {:?}", other),
         }
     }
 
     #[test]
-    fn tui_input_name_backspace_returns_to_browse() {
-        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
-        // j to Create row, type 'a' (enters InputName), backspace (returns to Browse), q to quit
-        let result = run_picker_with_keys(
+    fn tui_multi_create_single_repo_skips_chooser() {
+        let entries = vec![make_repo_entry("alpha", "ws1")];
+        let result = run_multi_picker_with_keys(
             entries,
-            vec![
-                KeyCode::Char('j'),
-                KeyCode::Char('a'),
-                KeyCode::Backspace,
-                KeyCode::Char('q'),
-            ],
-        )
-        .unwrap();
-        assert!(result.is_none());
+            vec![KeyCode::Char('j'), KeyCode::Enter, KeyCode::Enter],
+        );
+        match result.unwrap() {
+            Some(PickerResult::CreateNewIn { repo, name }) => {
+                assert_eq!(repo, PathBuf::from("/src/alpha"));
+                assert_eq!(name, None);
+            }
+            other => panic!("expected CreateNewIn, got {:?}", other),
+        }
     }
 
     #[test]
-    fn tui_input_name_esc_cancels() {
-        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
-        // j to Create row, type 'a', Esc cancels, q quits
-        let result = run_picker_with_keys(
+    fn tui_multi_create_esc_cancels() {
+        let entries = vec![
+            make_repo_entry("alpha", "ws1"),
+            make_repo_entry("beta", "ws2"),
+        ];
+        // Esc leaves the chooser, then j wraps past alpha's header to ws1.
+        let result = run_multi_picker_with_keys(
             entries,
             vec![
-                KeyCode::Char('j'),
-                KeyCode::Char('a'),
+                KeyCode::Char('k'),
+                KeyCode::Char('k'),
+                KeyCode::Enter,
                 KeyCode::Esc,
-                KeyCode::Char('q'),
+                KeyCode::Char('j'),
+                KeyCode::Char('j'),
+                KeyCode::Enter,
             ],
-        )
-        .unwrap();
-        assert!(result.is_none());
+        );
+        match result.unwrap() {
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/alpha/ws1"),
+            other => panic!("expected Selected ws1, got {:?}", other),
+        }
     }
 
-    // ── Multi-repo picker integration tests ─────────────────────────
-
     #[test]
-    fn tui_multi_select_first() {
+    fn render_multi_repo_shows_repo_chooser() {
         let entries = vec![
-            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
-            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
+            make_repo_entry("alpha", "ws1"),
+            make_repo_entry("beta", "ws2"),
         ];
-        let result = run_multi_picker_with_keys(entries, vec![KeyCode::Enter]).unwrap();
-        match result {
-            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
-            other => panic!("expected Selected, got {:?}", other),
-        }
+        let mut app = MultiRepoApp::new(entries);
+        app.selected = app.rows.len() - 1;
+        app.start_create();
+        let mut terminal = Terminal::new(TestBackend::new(100, 20)).unwrap();
+        terminal.draw(|f| render_multi_repo(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("Create in repo"), "{}", all_text);
+        assert!(all_text.contains("+ Create"), "{}", all_text);
+        assert!(all_text.contains("Choose a repo"), "{}", all_text);
     }
 
     #[test]
-    fn tui_multi_navigate_and_select() {
-        let entries = vec![
-            make_named_entry_ranked("ws1", "/tmp/ws1", 0),
-            make_named_entry_ranked("ws2", "/tmp/ws2", 1),
-        ];
+    fn tui_multi_menu_selects_workspace() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
         let result =
-            run_multi_picker_with_keys(entries, vec![KeyCode::Char('j'), KeyCode::Enter]).unwrap();
+            run_multi_picker_with_keys(entries, vec![KeyCode::Char('a'), KeyCode::Enter]).unwrap();
         match result {
-            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws2"),
-            other => panic!("expected Selected ws2, got {:?}", other),
+            Some(PickerResult::Selected(path)) => assert_eq!(path, "/tmp/ws1"),
+            other => panic!("expected Selected(ws1), got {:?}", other),
         }
     }
 
     #[test]
-    fn tui_multi_quit() {
-        let entries = vec![make_named_entry("ws1", "/tmp/ws1")];
-        let result = run_multi_picker_with_keys(entries, vec![KeyCode::Char('q')]).unwrap();
-        assert!(result.is_none());
+    fn tui_multi_delete_cancel() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut called = false;
+        let (result, _) = run_multi_picker_with_delete(
+            entries,
+            vec![KeyCode::Char('d'), KeyCode::Char('n'), KeyCode::Enter],
+            &mut |_, _| {
+                called = true;
+                Ok(false)
+            },
+            &mut || Ok(vec![]),
+        );
+        assert!(!called);
+        assert!(matches!(result.unwrap(), Some(PickerResult::Selected(_))));
+    }
+
+    #[test]
+    fn tui_multi_delete_refused_on_main() {
+        let mut main = make_named_entry_ranked("default", "/tmp/repo", 0);
+        main.is_main = true;
+        let mut called = false;
+        let (_, _) = run_multi_picker_with_delete(
+            vec![main],
+            vec![KeyCode::Char('d'), KeyCode::Char('y')],
+            &mut |_, _| {
+                called = true;
+                Ok(false)
+            },
+            &mut || Ok(vec![]),
+        );
+        assert!(!called);
+    }
+
+    #[test]
+    fn tui_multi_delete_redirect_exits() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let (result, _) = run_multi_picker_with_delete(
+            entries,
+            vec![KeyCode::Char('d'), KeyCode::Char('y'), KeyCode::Enter],
+            &mut |_, _| Ok(true),
+            &mut || panic!("should not refresh after redirect"),
+        );
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn tui_multi_delete_error_shows_status() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let (result, lines) = run_multi_picker_with_delete(
+            entries,
+            vec![KeyCode::Char('d'), KeyCode::Char('y')],
+            &mut |_, _| anyhow::bail!("workspace is locked"),
+            &mut || Ok(vec![]),
+        );
+        assert!(result.unwrap().is_none());
+        let all_text = lines.join("\n");
+        assert!(
+            all_text.contains("delete failed: workspace is locked"),
+            "got:\n{}",
+            all_text
+        );
     }
 
     #[test]
@@ -2070,8 +7135,193 @@ mod tests {
         }
     }
 
+    // ── Progressive loading tests ───────────────────────────────────
+
+    #[test]
+    fn render_loading_shows_spinner_and_hides_details() {
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.loading = true;
+        let mut terminal = Terminal::new(TestBackend::new(120, 10)).unwrap();
+        terminal.draw(|f| render(f, &mut app)).unwrap();
+        let all_text = buffer_lines(&terminal).join("\n");
+        assert!(all_text.contains("⠋ loading"), "{}", all_text);
+        assert!(all_text.contains("ws1"), "{}", all_text);
+        assert!(!all_text.contains("ws1 description"), "{}", all_text);
+    }
+
+    #[test]
+    fn first_refresh_finishes_loading() {
+        let mut app = App::new(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.loading = true;
+        app.drain_refresh_mailbox();
+        assert!(app.loading);
+        *app.refresh_mailbox.sender().lock().unwrap() =
+            Some(vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)]);
+        app.drain_refresh_mailbox();
+        assert!(!app.loading);
+    }
+
+    // ── Remembered UI state tests ───────────────────────────────────
+
+    #[test]
+    fn ui_state_round_trips_through_file() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(UiState::load(dir.path()), UiState::default());
+        let state = UiState {
+            sort: Some(SortMode::DiffSize),
+            filter: "feat".to_string(),
+            preview: true,
+            preview_layout: PreviewLayout {
+                percent: 60,
+                stacked: true,
+            },
+        };
+        state.save(dir.path()).unwrap();
+        assert_eq!(UiState::load(dir.path()), state);
+        let json = std::fs::read_to_string(dir.path().join(UI_STATE_FILE)).unwrap();
+        assert!(json.contains("\"diff_size\""), "{}", json);
+    }
+
+    #[test]
+    fn ui_state_ignores_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(UI_STATE_FILE), "{not json").unwrap();
+        assert_eq!(UiState::load(dir.path()), UiState::default());
+    }
+
+    #[test]
+    fn apply_ui_state_restores_sort_and_filter() {
+        let entries = vec![
+            make_named_entry_ranked("zeta", "/tmp/zeta", 0),
+            make_named_entry_ranked("alpha", "/tmp/alpha", 1),
+            make_named_entry_ranked("beta", "/tmp/beta", 2),
+        ];
+        let mut app = App::new(entries);
+        app.apply_ui_state(&UiState {
+            sort: Some(SortMode::Name),
+            filter: "a".to_string(),
+            preview: false,
+            preview_layout: PreviewLayout::default(),
+        });
+        assert_eq!(app.sort_mode, SortMode::Name);
+        assert_eq!(app.filter_buf, "a");
+        let visible: Vec<&str> = app
+            .visible_entries()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(visible, vec!["alpha", "beta", "zeta"]);
+    }
+
+    #[test]
+    fn picker_session_updates_ui_state() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut app = App::new(entries);
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![KeyCode::Char('s'), KeyCode::Char('q')].into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut app,
+            &mut || Ok(keys.next().map(key)),
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(app.ui_state().sort, Some(SortMode::Name));
+    }
+
     // ── Preview pane tests ──────────────────────────────────────────
 
+    #[test]
+    fn preview_layout_grows_and_shrinks_within_bounds() {
+        let mut layout = PreviewLayout::default();
+        layout.grow();
+        assert_eq!(layout.percent, 50);
+        for _ in 0..20 {
+            layout.grow();
+        }
+        assert_eq!(layout.percent, PreviewLayout::MAX_PERCENT);
+        for _ in 0..20 {
+            layout.shrink();
+        }
+        assert_eq!(layout.percent, PreviewLayout::MIN_PERCENT);
+        // Out-of-range values from a hand-edited state file are pulled back.
+        let mut layout = PreviewLayout {
+            percent: 200,
+            stacked: false,
+        };
+        layout.shrink();
+        assert_eq!(
+            layout.percent,
+            PreviewLayout::MAX_PERCENT - PreviewLayout::STEP
+        );
+    }
+
+    #[test]
+    fn preview_layout_splits_side_by_side_or_stacked() {
+        let area = Rect::new(0, 0, 100, 40);
+        let (table, preview) = PreviewLayout::default().split(area);
+        assert_eq!((table.width, preview.width), (55, 45));
+        assert_eq!(preview.x, 55);
+        let (table, preview) = PreviewLayout {
+            percent: 25,
+            stacked: true,
+        }
+        .split(area);
+        assert_eq!((table.height, preview.height), (30, 10));
+        assert_eq!((preview.y, preview.width), (30, 100));
+    }
+
+    #[test]
+    fn ui_state_without_layout_uses_default() {
+        let state: UiState = serde_json::from_str(r#"{"preview": true}"#).unwrap();
+        assert_eq!(state.preview_layout, PreviewLayout::default());
+    }
+
+    #[test]
+    fn tui_preview_keys_resize_and_restack() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut app = App::new(entries);
+        let backend = TestBackend::new(120, 30);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let mut keys = vec![
+            KeyCode::Char('+'),
+            KeyCode::Char('='),
+            KeyCode::Char('-'),
+            KeyCode::Char('L'),
+            KeyCode::Char('q'),
+        ]
+        .into_iter();
+        run_picker_inner(
+            &mut terminal,
+            &mut app,
+            &mut || Ok(keys.next().map(key)),
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
+            &mut || Ok(vec![]),
+        )
+        .unwrap();
+        assert_eq!(
+            app.ui_state().preview_layout,
+            PreviewLayout {
+                percent: 50,
+                stacked: true,
+            }
+        );
+    }
+
     #[test]
     fn tui_preview_hidden_by_default() {
         let app = App::new(vec![make_named_entry("ws1", "/tmp/ws1")]);
@@ -2122,6 +7372,68 @@ mod tests {
         assert!(result.is_none());
     }
 
+    fn ready_preview(log: &str) -> PreviewState {
+        PreviewState::Ready {
+            log: log.to_string(),
+            graph: String::new(),
+            diff_stat: String::new(),
+            agents: String::new(),
+            touched: String::new(),
+            issue: None,
+        }
+    }
+
+    #[test]
+    fn refresh_cycle_refetches_visible_preview() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut app = App::new(entries);
+        app.show_preview = true;
+        app.preview = ready_preview("old");
+        let old_mailbox = Arc::clone(&app.preview_mailbox);
+
+        let refreshed = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        *app.refresh_mailbox.sender().lock().unwrap() = Some(refreshed);
+        app.drain_refresh_mailbox();
+
+        // A new fetch started, but the old contents stay up until it lands.
+        assert!(app.preview_pending);
+        assert!(!Arc::ptr_eq(&old_mailbox, &app.preview_mailbox));
+        assert!(matches!(app.preview, PreviewState::Ready { ref log, .. } if log == "old"));
+    }
+
+    #[test]
+    fn refresh_cycle_skips_preview_while_fetch_pending() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut app = App::new(entries);
+        app.show_preview = true;
+        app.preview_pending = true;
+        let pending_mailbox = Arc::clone(&app.preview_mailbox);
+
+        let refreshed = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        *app.refresh_mailbox.sender().lock().unwrap() = Some(refreshed);
+        app.drain_refresh_mailbox();
+        assert!(Arc::ptr_eq(&pending_mailbox, &app.preview_mailbox));
+
+        // Once the pending fetch is drained, the next cycle fetches again.
+        *app.preview_mailbox.lock().unwrap() = Some(ready_preview("new"));
+        app.drain_preview_mailbox();
+        assert!(!app.preview_pending);
+        assert!(matches!(app.preview, PreviewState::Ready { ref log, .. } if log == "new"));
+    }
+
+    #[test]
+    fn refresh_cycle_ignores_hidden_preview() {
+        let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        let mut app = MultiRepoApp::new(entries);
+        let mailbox = Arc::clone(&app.preview_mailbox);
+
+        let refreshed = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
+        *app.refresh_mailbox.sender().lock().unwrap() = Some(refreshed);
+        app.drain_refresh_mailbox();
+        assert!(!app.preview_pending);
+        assert!(Arc::ptr_eq(&mailbox, &app.preview_mailbox));
+    }
+
     #[test]
     fn tui_multi_preview_hidden_by_default() {
         let app = MultiRepoApp::new(vec![make_named_entry("ws1", "/tmp/ws1")]);
@@ -2193,7 +7505,8 @@ mod tests {
         for _ in 0..19 {
             app.next();
         }
-        assert_eq!(app.selected, 19);
+        let idx = app.selected_entry_index().unwrap();
+        assert_eq!(app.entries[idx].name, "ws-19");
 
         let backend = TestBackend::new(80, 10);
         let mut terminal = Terminal::new(backend).unwrap();
@@ -2329,6 +7642,7 @@ mod tests {
                 waiting: 1,
                 working: 0,
                 idle: 0,
+                ..Default::default()
             },
         );
         *app.agent_refresh_mailbox.0.lock().unwrap() = Some(summaries);
@@ -2447,6 +7761,40 @@ mod tests {
         );
     }
 
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn watched_refresh_thread_refreshes_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let stop = Arc::new(StopSignal::new());
+        let sender = Arc::new(Mutex::new(None::<usize>));
+        let watched = dir.path().join(".agent-status");
+        let counted = watched.clone();
+
+        let handle = spawn_watched_refresh_thread(
+            std::slice::from_ref(&watched),
+            Duration::from_secs(60),
+            Arc::clone(&stop),
+            Arc::clone(&sender),
+            move || Some(std::fs::read_dir(&counted).unwrap().count()),
+        );
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let wait_for = |count: usize| {
+            while *sender.lock().unwrap() != Some(count) {
+                assert!(Instant::now() < deadline, "no refresh for {} files", count);
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        };
+        wait_for(0);
+        // Long before the 60s poll, the new file is picked up.
+        std::fs::write(watched.join("s1.json"), "{}").unwrap();
+        wait_for(1);
+
+        let start = Instant::now();
+        stop.stop();
+        handle.join().unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
     #[test]
     fn agent_thread_posts_summaries() {
         let stop = Arc::new(StopSignal::new());
@@ -2465,6 +7813,7 @@ mod tests {
                         waiting: 0,
                         working: 1,
                         idle: 0,
+                        ..Default::default()
                     },
                 );
                 Some(map)
@@ -2489,7 +7838,7 @@ mod tests {
         // Start with one entry, post a refresh with two entries via mailbox,
         // then verify the picker uses the updated entries.
         let entries = vec![make_named_entry_ranked("ws1", "/tmp/ws1", 0)];
-        let app = App::new(entries);
+        let mut app = App::new(entries);
 
         // Pre-load the refresh mailbox with new entries
         let new_entries = vec![
@@ -2511,9 +7860,15 @@ mod tests {
 
         let result = run_picker_inner(
             &mut terminal,
-            app,
+            &mut app,
             &mut || Ok(events.next().unwrap_or(Some(key(KeyCode::Esc)))),
-            &mut |_| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_, _| Ok(false),
+            &mut |_| Ok(()),
+            &mut |_| Ok(()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(String::new()),
+            &mut |_| Ok(()),
             &mut || Ok(vec![]),
         )
         .unwrap();
@@ -2544,4 +7899,120 @@ mod tests {
             line.trim()
         );
     }
+
+    // --- Agent dashboard ---
+
+    fn make_listing(repo: &str, id: &str, status: AgentStatus) -> AgentListing {
+        AgentListing {
+            repo: repo.to_string(),
+            workspace: format!("{}-ws", id),
+            session: AgentSession {
+                session_id: id.to_string(),
+                agent: agent::AgentKind::Claude,
+                model: None,
+                status,
+                started_at: Some(SystemTime::now() - Duration::from_secs(300)),
+                updated_at: SystemTime::now(),
+                last_prompt: None,
+                last_tool: Some("Bash".to_string()),
+                touched: Vec::new(),
+                waiting_since: None,
+            },
+        }
+    }
+
+    fn run_dashboard_with_keys(
+        app: &mut AgentDashboard,
+        keys: Vec<KeyCode>,
+    ) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(100, 12)).unwrap();
+        let mut keys = keys.into_iter().chain([KeyCode::Esc]);
+        run_agent_dashboard_inner(&mut terminal, app, &mut || Ok(keys.next().map(key))).unwrap();
+        terminal
+    }
+
+    #[test]
+    fn tui_agent_dashboard_lists_sessions() {
+        let mut app = AgentDashboard::new(vec![
+            make_listing("api", "s1", AgentStatus::Waiting),
+            make_listing("web", "s2", AgentStatus::Working),
+        ]);
+        let terminal = run_dashboard_with_keys(&mut app, vec![]);
+        let text = buffer_lines(&terminal).join("\n");
+        assert!(text.contains("dwm agents (2)"), "{}", text);
+        assert!(text.contains("api  s1-ws"), "{}", text);
+        assert!(text.contains("waiting"), "{}", text);
+        assert!(text.contains("5m ago"), "{}", text);
+        assert!(text.contains("tool: Bash"), "{}", text);
+        assert!(text.contains("updates live"), "{}", text);
+    }
+
+    #[test]
+    fn tui_agent_dashboard_empty() {
+        let mut app = AgentDashboard::new(vec![]);
+        let terminal = run_dashboard_with_keys(&mut app, vec![KeyCode::Char('j')]);
+        let text = buffer_lines(&terminal).join("\n");
+        assert!(text.contains("no active agent sessions"), "{}", text);
+        assert_eq!(app.table_state.selected(), None);
+    }
+
+    #[test]
+    fn tui_agent_dashboard_motions() {
+        let listings = (0..4)
+            .map(|i| make_listing("api", &format!("s{}", i), AgentStatus::Idle))
+            .collect();
+        let mut app = AgentDashboard::new(listings);
+        run_dashboard_with_keys(&mut app, vec![KeyCode::Char('G')]);
+        assert_eq!(app.table_state.selected(), Some(3));
+        run_dashboard_with_keys(
+            &mut app,
+            vec![KeyCode::Char('g'), KeyCode::Char('g'), KeyCode::Char('j')],
+        );
+        assert_eq!(app.table_state.selected(), Some(1));
+    }
+
+    #[test]
+    fn tui_agent_dashboard_refresh_follows_selected_session() {
+        let mut app = AgentDashboard::new(vec![
+            make_listing("api", "s1", AgentStatus::Idle),
+            make_listing("api", "s2", AgentStatus::Idle),
+        ]);
+        app.table_state.select(Some(1));
+        // s2 starts waiting and moves to the top.
+        let _ = app.refresh_mailbox.sender().lock().map(|mut m| {
+            *m = Some(vec![
+                make_listing("api", "s2", AgentStatus::Waiting),
+                make_listing("api", "s1", AgentStatus::Idle),
+            ])
+        });
+        app.drain_refresh_mailbox();
+        assert_eq!(app.table_state.selected(), Some(0));
+
+        // When the selected session ends, the cursor stays in range.
+        let _ = app
+            .refresh_mailbox
+            .sender()
+            .lock()
+            .map(|mut m| *m = Some(vec![make_listing("api", "s1", AgentStatus::Idle)]));
+        app.table_state.select(Some(1));
+        app.drain_refresh_mailbox();
+        assert_eq!(app.table_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn tui_agent_dashboard_shows_refresh_errors() {
+        let mut app = AgentDashboard::new(vec![]);
+        let _ = app
+            .refresh_error_mailbox
+            .sender()
+            .lock()
+            .map(|mut m| *m = Some("permission denied".to_string()));
+        let terminal = run_dashboard_with_keys(&mut app, vec![]);
+        let text = buffer_lines(&terminal).join("\n");
+        assert!(
+            text.contains("refresh failed: permission denied"),
+            "{}",
+            text
+        );
+    }
 }
@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use ::notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// How long [`DirWatcher::wait`] keeps collecting events after the first,
+/// so one write (often several events) wakes its caller once.
+const SETTLE: Duration = Duration::from_millis(50);
+
+/// What a [`DirWatcher`]'s channel carries.
+enum Signal {
+    Changed,
+    Woken,
+}
+
+/// Wakes a [`DirWatcher`] blocked in [`DirWatcher::wait`] from another
+/// thread.
+#[derive(Clone)]
+pub struct Waker {
+    sender: Sender<Signal>,
+}
+
+impl Waker {
+    pub fn wake(&self) {
+        // A dropped watcher has nothing left to wake.
+        let _ = self.sender.send(Signal::Woken);
+    }
+}
+
+/// Watches directories for files being created, changed, renamed, or
+/// removed, so a refresh can follow a change instead of polling for it.
+/// Uses the platform's own notifications (inotify, FSEvents, ...) through
+/// the `notify` crate; where there are none, [`DirWatcher::new`] returns
+/// `None` and callers poll.
+pub struct DirWatcher {
+    /// Kept so notifications keep coming until the watcher is dropped.
+    _watcher: RecommendedWatcher,
+    signals: Receiver<Signal>,
+    waker: Waker,
+}
+
+impl DirWatcher {
+    /// Watch `dirs`, creating any that don't exist yet so changes to them
+    /// are seen from the start. `None` if watching isn't possible.
+    pub fn new(dirs: &[PathBuf]) -> Option<Self> {
+        for dir in dirs {
+            std::fs::create_dir_all(dir).ok()?;
+        }
+        let (sender, signals) = mpsc::channel();
+        let events = sender.clone();
+        let mut watcher =
+            ::notify::recommended_watcher(move |event: ::notify::Result<::notify::Event>| {
+                // Reads don't change anything; errors may mean missed events.
+                if !event.is_ok_and(|e| matches!(e.kind, EventKind::Access(_))) {
+                    let _ = events.send(Signal::Changed);
+                }
+            })
+            .ok()?;
+        for dir in dirs {
+            watcher.watch(dir, RecursiveMode::NonRecursive).ok()?;
+        }
+        Some(Self {
+            _watcher: watcher,
+            signals,
+            waker: Waker { sender },
+        })
+    }
+
+    /// A handle that wakes this watcher's [`wait`](Self::wait).
+    pub fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+
+    /// Block until something changes in a watched directory (`true`), or
+    /// until `timeout` passes or the waker fires (`false`).
+    pub fn wait(&self, timeout: Duration) -> bool {
+        match self.signals.recv_timeout(timeout) {
+            Ok(Signal::Changed) => {}
+            // `self.waker` keeps the channel open, so this only times out.
+            Ok(Signal::Woken) | Err(_) => return false,
+        }
+        loop {
+            match self.signals.recv_timeout(SETTLE) {
+                Ok(Signal::Changed) => {}
+                Ok(Signal::Woken) | Err(_) => return true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+
+    #[test]
+    fn creates_missing_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let watched = dir.path().join("repo").join(".agent-status");
+        let _watcher = DirWatcher::new(std::slice::from_ref(&watched)).unwrap();
+        assert!(watched.is_dir());
+    }
+
+    #[test]
+    fn times_out_without_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = DirWatcher::new(&[dir.path().to_path_buf()]).unwrap();
+        assert!(!watcher.wait(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn sees_files_written_and_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = DirWatcher::new(&[dir.path().to_path_buf()]).unwrap();
+        let file = dir.path().join("s1.json");
+        fs::write(&file, "{}").unwrap();
+        assert!(watcher.wait(Duration::from_secs(5)));
+        // Every event from the write was drained.
+        assert!(!watcher.wait(Duration::from_millis(20)));
+
+        fs::remove_file(&file).unwrap();
+        assert!(watcher.wait(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn sees_renames_into_the_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let watched = dir.path().join("watched");
+        let watcher = DirWatcher::new(std::slice::from_ref(&watched)).unwrap();
+        let tmp = dir.path().join("s1.json.tmp");
+        fs::write(&tmp, "{}").unwrap();
+        fs::rename(&tmp, watched.join("s1.json")).unwrap();
+        assert!(watcher.wait(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn waker_interrupts_wait() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = DirWatcher::new(&[dir.path().to_path_buf()]).unwrap();
+        let waker = watcher.waker();
+        let start = Instant::now();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            waker.wake();
+        });
+        assert!(!watcher.wait(Duration::from_secs(30)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,81 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// How many terminal columns `s` takes: two for most CJK characters and
+/// emoji, none for combining marks.
+pub fn of(s: &str) -> usize {
+    s.width()
+}
+
+/// `s` cut to at most `max` columns, ending in `…` if anything was cut.
+/// Never splits a wide character, so the result can be a column short;
+/// [`fit`] pads that back.
+pub fn truncate(s: &str, max: usize) -> String {
+    if of(s) <= max {
+        return s.to_string();
+    }
+    let Some(budget) = max.checked_sub(1) else {
+        return String::new();
+    };
+    let mut out = String::new();
+    let mut used = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        out.push(c);
+    }
+    out.push('…');
+    out
+}
+
+/// `s` followed by enough spaces to fill `width` columns. Like `{:<width$}`,
+/// but counting columns rather than characters.
+pub fn pad(s: &str, width: usize) -> String {
+    let fill = width.saturating_sub(of(s));
+    format!("{}{}", s, " ".repeat(fill))
+}
+
+/// `s` truncated and padded to exactly `width` columns.
+pub fn fit(s: &str, width: usize) -> String {
+    pad(&truncate(s, width), width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        assert_eq!(of("fix login"), 9);
+        assert_eq!(of("修复登录"), 8);
+        assert_eq!(of("ship it 🚀"), 10);
+        assert_eq!(of("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn truncate_counts_columns() {
+        assert_eq!(truncate("fix login", 20), "fix login");
+        assert_eq!(truncate("fix login timeout", 10), "fix login…");
+        assert_eq!(truncate("修复登录超时", 7), "修复登…");
+        // A wide character that doesn't fit whole is left out.
+        assert_eq!(truncate("修复登录超时", 6), "修复…");
+        assert_eq!(truncate("anything", 0), "");
+        assert_eq!(truncate("anything", 1), "…");
+    }
+
+    #[test]
+    fn fit_fills_exactly() {
+        for s in [
+            "fix",
+            "修复登录超时",
+            "🚀🚀🚀🚀🚀🚀",
+            "plain ascii text here",
+        ] {
+            assert_eq!(of(&fit(s, 7)), 7, "{}", s);
+        }
+        assert_eq!(pad("修复", 6), "修复  ");
+        assert_eq!(pad("too long", 3), "too long");
+    }
+}
@@ -1,87 +1,128 @@
+//! The workspace commands. What they do is in [`dwm_core::workspace`];
+//! this finds the repo to act on, asks before anything destructive, hands
+//! the shell wrapper its next directory, and prints the results.
+
 use anyhow::{Context, Result, bail};
 use owo_colors::OwoColorize;
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::SystemTime;
 
-use crate::{agent, names, vcs};
-
-/// Whether a workspace's changes have been merged into trunk.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum MergeStatus {
-    Merged,
-    Unmerged,
-}
+use crate::exit::Failure;
+use crate::format::ListFormat;
+use crate::meta::{RepoMeta, WorkspaceMeta};
+use crate::{daemon, forge, names, vcs, width};
 
-/// Controls whether progress messages are printed to stderr during deletion.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DeleteOutput {
-    /// Print progress messages to stderr.
-    Verbose,
-    /// Suppress progress messages (used by the TUI which owns the alternate screen).
-    Quiet,
-}
+pub use dwm_core::layout::base_dir as dwm_base_dir;
+use dwm_core::layout::{WorkspaceDeps, repo_dir};
+pub use dwm_core::workspace::*;
 
-/// Return `true` if `cwd` is equal to or a subdirectory of `ws_path`.
-fn is_inside(cwd: &std::path::Path, ws_path: &std::path::Path) -> bool {
-    cwd.starts_with(ws_path)
-}
+/// Main repo chosen with the global `--repo` flag, acted on instead of the
+/// repo containing the current directory.
+static REPO_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
-/// Return the path to `~/.dwm/`, the root of all dwm workspace storage.
-fn dwm_base_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().context("could not determine home directory")?;
-    Ok(home.join(".dwm"))
+/// Direct repo-inferring commands at `repo`, a tracked repo's dwm name or a
+/// path inside a repo. See [`resolve_repo`].
+pub fn set_repo_override(repo: &str) -> Result<()> {
+    let root = resolve_repo(&dwm_base_dir()?, repo)?;
+    let _ = REPO_OVERRIDE.set(root);
+    Ok(())
 }
 
-/// Return `~/.dwm/<repo_name>` — the per-repo workspace storage directory.
-fn repo_dir(dwm_base: &Path, repo_name: &str) -> PathBuf {
-    dwm_base.join(repo_name)
+/// The directory to infer the repo from: the `--repo` main repo if one was
+/// given, otherwise the current directory.
+pub fn working_dir() -> Result<PathBuf> {
+    match REPO_OVERRIDE.get() {
+        Some(root) => Ok(root.clone()),
+        None => Ok(std::env::current_dir()?),
+    }
 }
 
-/// Read the original repository root path from `~/.dwm/<repo_name>/.main-repo`.
-fn main_repo_path(dwm_base: &Path, repo_name: &str) -> Result<PathBuf> {
-    let repo_dir = repo_dir(dwm_base, repo_name);
-    let main_repo_file = repo_dir.join(".main-repo");
-    let path = fs::read_to_string(&main_repo_file)
-        .with_context(|| format!("could not read {}", main_repo_file.display()))?;
-    Ok(PathBuf::from(path.trim()))
+/// Register the repo containing the current directory with dwm, driving it
+/// with `options.vcs` if given (for colocated jj+git repos) or the detected
+/// VCS otherwise. The choice and the naming scheme are recorded in the
+/// repo's [`RepoMeta`], and the trunk in the VCS's own repo config.
+pub fn init_repo(mut options: InitOptions) -> Result<()> {
+    use std::io::IsTerminal;
+    let cwd = working_dir()?;
+    if std::io::stdin().is_terminal() && std::io::stderr().is_terminal() {
+        ask_init_options(&cwd, &mut options)?;
+    }
+    let backend = match options.vcs {
+        Some(vcs_type) => vcs_type.to_backend(),
+        None => vcs::detect(&cwd)?,
+    };
+    let deps = WorkspaceDeps {
+        backend,
+        cwd,
+        dwm_base: dwm_base_dir()?,
+    };
+    register(&deps, &options)
 }
 
-/// Create `~/.dwm/<repo_name>/` if it does not yet exist, and write the
-/// `.main-repo` and `.vcs-type` marker files on first use.
-fn ensure_repo_dir(
-    dwm_base: &Path,
-    repo_name: &str,
-    main_repo_root: &Path,
-    vcs_type: vcs::VcsType,
-) -> Result<PathBuf> {
-    let dir = repo_dir(dwm_base, repo_name);
-    fs::create_dir_all(&dir)?;
-    let main_repo_file = dir.join(".main-repo");
-    if !main_repo_file.exists() {
-        fs::write(&main_repo_file, main_repo_root.to_string_lossy().as_ref())?;
-    }
-    let vcs_file = dir.join(".vcs-type");
-    if !vcs_file.exists() {
-        fs::write(&vcs_file, vcs_type.to_string())?;
-    }
-    Ok(dir)
+/// Ask for each of `options` that's unset; an empty answer keeps the
+/// default. The VCS is only asked for in a colocated jj+git repo.
+fn ask_init_options(cwd: &Path, options: &mut InitOptions) -> Result<()> {
+    let root = vcs::detect(cwd)?.root_from(cwd)?;
+    if options.vcs.is_none() && root.join(".jj").is_dir() && root.join(".git").exists() {
+        let answer = ask("Drive this jj+git repo with jj or git? [jj]")?;
+        if !answer.is_empty() {
+            options.vcs = Some(answer.parse()?);
+        }
+    }
+    if options.trunk.is_none() {
+        let answer = ask("Trunk branch (git) or revset (jj)? [detect]")?;
+        options.trunk = Some(answer).filter(|t| !t.is_empty());
+    }
+    if options.naming.is_none() {
+        let answer = ask(
+            "Name new workspaces 'random' (calm-otter) or 'numbered' (ws-1) or 'dated' (2024-06-calm-otter)? [random]",
+        )?;
+        if !answer.is_empty() {
+            let naming = <names::NamingScheme as clap::ValueEnum>::from_str(&answer, true)
+                .map_err(|_| anyhow::anyhow!("unknown naming scheme '{}'", answer))?;
+            options.naming = Some(naming);
+        }
+    }
+    Ok(())
 }
 
-/// Common dependencies threaded through workspace operations, grouped so they
-/// can be injected in tests without touching the real filesystem or VCS.
-struct WorkspaceDeps {
-    backend: Box<dyn vcs::VcsBackend>,
-    cwd: PathBuf,
-    dwm_base: PathBuf,
+/// Merge `~/.dwm/` entries recorded under a symlinked path to their repo
+/// into the entry for the repo's real path, moving their workspaces (and
+/// agent status and history) along. Returns the outcome for each entry that
+/// needed merging: the main repo's real path, or why it couldn't be merged.
+/// Entries containing the current directory are left for a later run, as
+/// moving them would strand the shell in a deleted directory.
+pub fn merge_split_repo_dirs() -> Result<Vec<Result<PathBuf>>> {
+    let dwm_base = dwm_base_dir()?;
+    let cwd = std::env::current_dir()?;
+    Ok(merge_split_repo_dirs_in(
+        &dwm_base,
+        &cwd,
+        vcs::detect_from_dwm_dir,
+    ))
 }
 
 /// Create a new workspace, auto-detecting the VCS from the current directory.
+/// Submodules are checked out if `recurse_submodules` or the
+/// `recurse_submodules` setting is set. With `sparse`, only the directories
+/// of that profile in the `sparse_profiles` setting are checked out. With
+/// `issue`, the issue is looked up on the repo's forge, names the workspace
+/// unless `name` is given, and is linked to it.
 ///
-/// Prints the new workspace path to stdout so the shell wrapper can `cd` into it.
-pub fn new_workspace(name: Option<String>, at: Option<&str>, from: Option<&str>) -> Result<()> {
-    let cwd = std::env::current_dir()?;
+/// Then takes the shell to the new workspace; see [`go_to`].
+pub fn new_workspace(
+    name: Option<String>,
+    at: Option<&str>,
+    from: Option<&str>,
+    recurse_submodules: bool,
+    sparse: Option<&str>,
+    issue: Option<u64>,
+    name_scheme: Option<names::NamingScheme>,
+) -> Result<()> {
+    let cwd = working_dir()?;
     let backend = vcs::detect(&cwd)?;
     let dwm_base = dwm_base_dir()?;
     let deps = WorkspaceDeps {
@@ -89,72 +130,103 @@ pub fn new_workspace(name: Option<String>, at: Option<&str>, from: Option<&str>)
         cwd,
         dwm_base,
     };
-    new_workspace_inner(&deps, name, at, from)
+    let config = crate::config::Config::load()?;
+    let recurse_submodules = recurse_submodules || config.recurse_submodules;
+    let sparse = match sparse {
+        Some(profile) => config.sparse_profile(profile)?,
+        None => Vec::new(),
+    };
+    // Before creating anything, so a bad number leaves nothing behind.
+    let issue = issue.map(|number| fetch_issue(&deps, number)).transpose()?;
+    let name = name.or_else(|| {
+        issue
+            .as_ref()
+            .map(|issue| names::from_issue(issue.number, &issue.title))
+    });
+    let name = match (name, name_scheme) {
+        (None, Some(scheme)) => {
+            let repo_name = deps.backend.repo_name_from(&deps.cwd)?;
+            Some(generate_name(
+                &repo_dir(&deps.dwm_base, &repo_name),
+                Some(scheme),
+                &config.names,
+            )?)
+        }
+        (name, _) => name,
+    };
+    let ws_path = create(
+        &deps,
+        name,
+        at,
+        from,
+        recurse_submodules,
+        &sparse,
+        &config.names,
+    )?;
+    if let (Some(issue), Some(rd), Some(ws_name)) = (&issue, ws_path.parent(), ws_path.file_name())
+    {
+        let ws_name = ws_name.to_string_lossy();
+        let mut meta = WorkspaceMeta::load(rd, &ws_name)?;
+        meta.issue = Some(issue.clone());
+        meta.save(rd, &ws_name)?;
+    }
+    go_to(&ws_path)
 }
 
-/// Testable core of [`new_workspace`] that accepts injected [`WorkspaceDeps`].
-fn new_workspace_inner(
-    deps: &WorkspaceDeps,
-    name: Option<String>,
-    at: Option<&str>,
-    from: Option<&str>,
-) -> Result<()> {
-    let repo_name = deps.backend.repo_name_from(&deps.cwd)?;
+/// Look up issue `number` on the forge of the repo `deps` is in.
+fn fetch_issue(deps: &WorkspaceDeps, number: u64) -> Result<forge::Issue> {
     let root = deps.backend.root_from(&deps.cwd)?;
-    let dir = ensure_repo_dir(&deps.dwm_base, &repo_name, &root, deps.backend.vcs_type())?;
+    let forge = forge::detect(&root)
+        .context("no GitHub, GitLab or Gitea remote to look up the issue on")?;
+    log::info!("{} issue #{}...", "fetching".cyan(), number);
+    forge
+        .issue(&root, number)
+        .with_context(|| format!("could not fetch {} issue #{}", forge.name(), number))
+}
 
-    // Resolve --from to a change ID by looking up the source workspace.
-    let resolved_at;
-    let at = if let Some(ws_name) = from {
-        let workspaces = deps.backend.workspace_list(&root)?;
-        let (_name, info) = workspaces
-            .iter()
-            .find(|(n, _)| n == ws_name)
-            .with_context(|| format!("workspace '{}' not found", ws_name))?;
-        resolved_at = info.change_id.clone();
-        Some(resolved_at.as_str())
-    } else {
-        at
-    };
+/// The `recurse_submodules` and `names` settings, for workspaces made
+/// without asking for either.
+fn new_workspace_settings() -> Result<(bool, names::NameConfig)> {
+    let config = crate::config::Config::load()?;
+    Ok((config.recurse_submodules, config.names))
+}
 
-    let ws_name = match name {
-        Some(n) => {
-            if n.starts_with('.') {
-                bail!("workspace name cannot start with '.'");
-            }
-            n
-        }
-        None => names::generate_unique(&dir),
+/// Create a new workspace in the repo at `repo` rather than the one containing
+/// the current directory. Used by the `--all` picker's create flow.
+pub fn new_workspace_in(repo: &Path, name: Option<String>) -> Result<()> {
+    let deps = WorkspaceDeps {
+        backend: vcs::detect(repo)?,
+        cwd: repo.to_path_buf(),
+        dwm_base: dwm_base_dir()?,
     };
+    let (recurse_submodules, names) = new_workspace_settings()?;
+    go_to(&create(
+        &deps,
+        name,
+        None,
+        None,
+        recurse_submodules,
+        &[],
+        &names,
+    )?)
+}
 
-    let ws_path = dir.join(&ws_name);
-    if ws_path.exists() {
-        bail!(
-            "workspace '{}' already exists at {}",
-            ws_name,
-            ws_path.display()
-        );
+/// Take the shell to the workspace at `path`: with the `tmux_windows`
+/// setting inside tmux, its own tmux window, otherwise a `cd` by the shell
+/// wrapper.
+pub fn go_to(path: &Path) -> Result<()> {
+    if crate::tmux::auto() {
+        crate::tmux::open(path)
+    } else {
+        crate::shell::print_cd(path)
     }
-
-    eprintln!("{} workspace '{}'...", "creating".cyan(), ws_name.bold());
-    deps.backend.workspace_add(&root, &ws_path, &ws_name, at)?;
-    eprintln!(
-        "{} workspace '{}' created at {}",
-        "✓".green(),
-        ws_name.bold(),
-        ws_path.display().dimmed()
-    );
-
-    // stdout: path for shell wrapper to cd into
-    println!("{}", ws_path.display());
-    Ok(())
 }
 
 /// Deletes a workspace. Returns `true` if the cwd was inside the deleted
-/// workspace and a redirect path was printed to stdout.
+/// workspace and a redirect path was handed to the shell wrapper.
 /// Delete a workspace by name (or infer from cwd).
-pub fn delete_workspace(name: Option<String>, output: DeleteOutput) -> Result<bool> {
-    let cwd = std::env::current_dir()?;
+pub fn delete_workspace(name: Option<String>, force: bool, output: ProgressOutput) -> Result<bool> {
+    let cwd = working_dir()?;
     let dwm_base = dwm_base_dir()?;
 
     // We need a backend for the repo-name-from-cwd case.
@@ -180,100 +252,92 @@ pub fn delete_workspace(name: Option<String>, output: DeleteOutput) -> Result<bo
         cwd,
         dwm_base,
     };
-    if let Some(redirect) = delete_workspace_inner(&deps, name, output)? {
-        println!("{}", redirect.display());
+    if let Some(redirect) = delete(&deps, name, force, output)? {
+        crate::shell::print_cd(&redirect)?;
         Ok(true)
     } else {
         Ok(false)
     }
 }
 
-/// Returns the path the shell should cd to if cwd was inside the deleted workspace.
-fn delete_workspace_inner(
-    deps: &WorkspaceDeps,
-    name: Option<String>,
-    output: DeleteOutput,
-) -> Result<Option<PathBuf>> {
-    let verbose = output == DeleteOutput::Verbose;
-    let (repo_name_str, ws_name) = match name {
-        Some(name) => {
-            let repo_name_str = if deps.cwd.starts_with(&deps.dwm_base) {
-                let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
-                relative
-                    .components()
-                    .next()
-                    .context("could not determine repo from workspace path")?
-                    .as_os_str()
-                    .to_string_lossy()
-                    .to_string()
-            } else {
-                deps.backend.repo_name_from(&deps.cwd)?
-            };
-            (repo_name_str, name)
-        }
-        None => {
-            if !deps.cwd.starts_with(&deps.dwm_base) {
-                bail!(
-                    "not inside a dwm workspace (current dir must be under {})",
-                    deps.dwm_base.display()
-                );
-            }
-            let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
-            let components: Vec<&std::ffi::OsStr> =
-                relative.components().map(|c| c.as_os_str()).collect();
-            if components.len() < 2 {
-                bail!("could not determine workspace name from current directory");
-            }
-            (
-                components[0].to_string_lossy().to_string(),
-                components[1].to_string_lossy().to_string(),
-            )
+/// Delete every workspace of the current repo picked by `filter`, listing
+/// them and asking first unless `yes`. Workspaces with uncommitted changes
+/// are skipped unless `force`. Returns `true` if the cwd was inside a deleted
+/// workspace and a redirect path was handed to the shell wrapper.
+pub fn delete_workspaces(filter: BulkDelete, force: bool, yes: bool) -> Result<bool> {
+    let deps = list_deps()?;
+    let targets = bulk_delete_targets(&deps, filter)?;
+    if targets.is_empty() {
+        log::info!("no {} workspaces to delete", filter);
+        return Ok(false);
+    }
+    if !yes {
+        for entry in &targets {
+            let dirty = entry
+                .working_copy
+                .describe()
+                .map(|d| format!(", {}", d))
+                .unwrap_or_default();
+            eprintln!(
+                "  {}  {}{}",
+                entry.name.bold(),
+                format_time_ago(entry.last_modified).dimmed(),
+                dirty.yellow()
+            );
         }
-    };
-
-    let ws_path = deps.dwm_base.join(&repo_name_str).join(&ws_name);
-    if !ws_path.exists() {
-        bail!("workspace '{}' not found at {}", ws_name, ws_path.display());
-    }
-
-    let main_repo = main_repo_path(&deps.dwm_base, &repo_name_str)?;
-
-    if verbose {
-        eprintln!(
-            "{} workspace '{}'...",
-            "forgetting".yellow(),
-            ws_name.bold()
+        let question = format!(
+            "Delete {} {} workspace{}?",
+            targets.len(),
+            filter,
+            if targets.len() == 1 { "" } else { "s" }
         );
-    }
-    deps.backend
-        .workspace_remove(&main_repo, &ws_name, &ws_path)?;
-
-    if ws_path.exists() {
-        if verbose {
-            eprintln!("{} {}...", "removing".red(), ws_path.display().dimmed());
+        if !confirm(&question)? {
+            bail!(Failure::cancelled());
         }
-        fs::remove_dir_all(&ws_path)?;
     }
-
-    // Clean up agent status files for this workspace
-    let rd = repo_dir(&deps.dwm_base, &repo_name_str);
-    agent::remove_agent_statuses_for_workspace(&rd, &ws_name);
-
-    if verbose {
-        eprintln!("{} workspace '{}' deleted", "✓".green(), ws_name.bold());
+    let (redirect, failed) = delete_many(&deps, &targets, force)?;
+    if let Some(redirect) = &redirect {
+        crate::shell::print_cd(redirect)?;
+    }
+    if !failed.is_empty() {
+        bail!("could not delete {}", failed.join(", "));
     }
+    Ok(redirect.is_some())
+}
 
-    if is_inside(&deps.cwd, &ws_path) {
-        Ok(Some(main_repo))
+/// Delete the workspace at `ws_path` (`<dwm_base>/<repo dir>/<name>`) using
+/// that repo's own backend, whatever repo the current directory is in. Used
+/// by the `--all` picker. Returns `true` if a redirect path was printed.
+pub fn delete_workspace_at(ws_path: &Path, force: bool, output: ProgressOutput) -> Result<bool> {
+    let dwm_base = dwm_base_dir()?;
+    let (repo_name_str, _) = split_workspace_path(&dwm_base, ws_path)?;
+    let deps = WorkspaceDeps {
+        backend: vcs::detect_from_dwm_dir(&repo_dir(&dwm_base, &repo_name_str))?,
+        cwd: std::env::current_dir()?,
+        dwm_base,
+    };
+    if let Some(redirect) = delete_at(&deps, ws_path, force, output)? {
+        crate::shell::print_cd(&redirect)?;
+        Ok(true)
     } else {
-        Ok(None)
+        Ok(false)
     }
 }
 
-/// Switch to the named workspace by printing its path to stdout for the shell
-/// wrapper to `cd` into.
+/// Switch to the named workspace; see [`go_to`].
 pub fn switch_workspace(name: &str) -> Result<()> {
-    let cwd = std::env::current_dir()?;
+    go_to(&find(&switch_deps()?, name)?)
+}
+
+/// Open the named workspace in tmux; see [`crate::tmux::open`].
+pub fn tmux_workspace(name: &str) -> Result<()> {
+    crate::tmux::open(&find(&switch_deps()?, name)?)
+}
+
+/// Dependencies for looking up workspaces by name from the current
+/// directory, which may be a workspace or the main repo.
+fn switch_deps() -> Result<WorkspaceDeps> {
+    let cwd = working_dir()?;
     let dwm_base = dwm_base_dir()?;
 
     let backend: Box<dyn vcs::VcsBackend> = if cwd.starts_with(&dwm_base) {
@@ -291,49 +355,236 @@ pub fn switch_workspace(name: &str) -> Result<()> {
         vcs::detect(&cwd)?
     };
 
-    let deps = WorkspaceDeps {
+    Ok(WorkspaceDeps {
         backend,
         cwd,
         dwm_base,
-    };
-    let path = switch_workspace_inner(&deps, name)?;
-    println!("{}", path.display());
+    })
+}
+
+/// Run `command` in the named workspace with dwm's stdio, returning its exit
+/// code (or 128 plus the signal that killed it).
+pub fn exec_in_workspace(name: &str, command: &[String]) -> Result<i32> {
+    exec(&switch_deps()?, name, command)
+}
+
+/// Print the path of the `nth` most recently visited or modified workspace
+/// (1-based) other than the main one and the one the shell is in, for the
+/// wrapper to `cd` into.
+pub fn recent_workspace(nth: usize) -> Result<()> {
+    let path = recent(&list_deps()?, nth)?;
+    crate::shell::print_cd(&path)?;
     Ok(())
 }
 
-/// Resolve the path for the named workspace. Returns the path the shell should
-/// `cd` into.
-fn switch_workspace_inner(deps: &WorkspaceDeps, name: &str) -> Result<PathBuf> {
-    let repo_name_str = if deps.cwd.starts_with(&deps.dwm_base) {
-        let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
-        relative
-            .components()
-            .next()
-            .context("could not determine repo from workspace path")?
-            .as_os_str()
-            .to_string_lossy()
-            .to_string()
+/// Resolve the workspace `dwm launch` starts an agent in: the named one if it
+/// exists, otherwise a new one (auto-named when `name` is `None`).
+pub fn launch_workspace(name: Option<String>) -> Result<PathBuf> {
+    let (recurse_submodules, names) = new_workspace_settings()?;
+    find_or_create(&switch_deps()?, name, recurse_submodules, &names)
+}
+
+/// Push the named workspace (the current one if `name` is `None`) to the
+/// default remote, reporting the remote branch it went to.
+pub fn push_workspace(name: Option<String>) -> Result<()> {
+    let (ws_name, remote_branch) = push(&switch_deps()?, name)?;
+    log::info!(
+        "{} pushed '{}' to {}",
+        "✓".green(),
+        ws_name.bold(),
+        remote_branch.bold()
+    );
+    Ok(())
+}
+
+/// Push the workspace at `ws_path`, a dwm workspace or a main repo, through
+/// its own repo's backend. Used by the pickers; returns the remote branch.
+pub fn push_workspace_at(ws_path: &Path) -> Result<String> {
+    push(&deps_at(ws_path)?, None).map(|(_, remote_branch)| remote_branch)
+}
+
+/// Dependencies for acting on the workspace at `ws_path`, a dwm workspace or
+/// a main repo, as if run from inside it.
+fn deps_at(ws_path: &Path) -> Result<WorkspaceDeps> {
+    let dwm_base = dwm_base_dir()?;
+    let backend = if ws_path.starts_with(&dwm_base) {
+        let (repo_name_str, _) = split_workspace_path(&dwm_base, ws_path)?;
+        vcs::detect_from_dwm_dir(&repo_dir(&dwm_base, &repo_name_str))?
     } else {
-        deps.backend.repo_name_from(&deps.cwd)?
+        vcs::detect(ws_path)?
     };
+    Ok(WorkspaceDeps {
+        backend,
+        cwd: ws_path.to_path_buf(),
+        dwm_base,
+    })
+}
+
+/// Push the named workspace (the current one if `name` is `None`) and
+/// print the URL of its pull request, creating one from the workspace's
+/// description if it has none. With `copy`, the URL also goes to the
+/// clipboard.
+pub fn pr_workspace(name: Option<String>, copy: bool) -> Result<()> {
+    let (ws_name, url, created) = pr_workspace_inner(&switch_deps()?, name)?;
+    report_pr(&ws_name, &url, created, copy)
+}
+
+/// [`pr_workspace`] for the workspace at `ws_path`, through its own repo's
+/// backend. Used by the pickers.
+pub fn pr_workspace_at(ws_path: &Path) -> Result<()> {
+    let (ws_name, url, created) = pr_workspace_inner(&deps_at(ws_path)?, None)?;
+    report_pr(&ws_name, &url, created, false)
+}
+
+/// Testable core of [`pr_workspace`]. Returns the workspace's name, its
+/// pull request's URL, and whether the pull request was just created.
+fn pr_workspace_inner(
+    deps: &WorkspaceDeps,
+    name: Option<String>,
+) -> Result<(String, String, bool)> {
+    let (_, main_repo) = listed_repo(deps)?;
+    // Before pushing, so a repo with nowhere to open one isn't pushed for
+    // nothing.
+    let forge = forge::detect(&main_repo)
+        .context("no GitHub, GitLab or Gitea remote to open a pull request on")?;
+    let (ws_name, remote_branch) = push(deps, name)?;
+    let branch = remote_branch
+        .split_once('/')
+        .map_or(remote_branch.as_str(), |(_, branch)| branch);
+    let ws_path = find(deps, &ws_name)?;
+    let description = deps
+        .backend
+        .latest_description(&main_repo, &ws_path, &ws_name);
+    let (url, created) = forge::open_pr(&*forge, &main_repo, branch, &description)?;
+    Ok((ws_name, url, created))
+}
+
+/// Print a pull request's URL to stdout and say what happened on stderr.
+fn report_pr(ws_name: &str, url: &str, created: bool, copy: bool) -> Result<()> {
+    let verb = if created { "opened" } else { "found" };
+    log::info!(
+        "{} {} the pull request for '{}'",
+        "✓".green(),
+        verb,
+        ws_name.bold()
+    );
+    println!("{}", url);
+    if copy {
+        crate::clipboard::copy(url)?;
+        log::info!("copied {}", url);
+    }
+    Ok(())
+}
 
-    let main_ws_name = deps.backend.main_workspace_name();
-    if name == main_ws_name {
-        return main_repo_path(&deps.dwm_base, &repo_name_str);
+/// Rebase the named workspace (the current one if `name` is `None`) onto
+/// the latest trunk.
+pub fn sync_workspace(name: Option<String>) -> Result<()> {
+    let (ws_name, onto) = sync(&switch_deps()?, name)?;
+    log::info!(
+        "{} rebased '{}' onto {}",
+        "✓".green(),
+        ws_name.bold(),
+        onto.bold()
+    );
+    Ok(())
+}
+
+/// Rebase the workspace at `ws_path` onto the latest trunk through its own
+/// repo's backend. Used by the pickers; returns what it was rebased onto.
+pub fn sync_workspace_at(ws_path: &Path) -> Result<String> {
+    sync(&deps_at(ws_path)?, None).map(|(_, onto)| onto)
+}
+
+/// Build the `sh -c` script that runs the editor command `template` on the
+/// path passed as `$1`. `{path}` placeholders are replaced by the quoted path;
+/// without a placeholder the path is appended as the last argument.
+fn editor_script(template: &str) -> String {
+    if template.contains("{path}") {
+        template.replace("{path}", "\"$1\"")
+    } else {
+        format!("{} \"$1\"", template)
     }
+}
+
+/// Open the named workspace (the current one if `name` is `None`) in
+/// `with`, an editor command like `zed` or `idea {path}`, or else the
+/// configured editor. The shell's directory is left alone.
+pub fn open_workspace(name: Option<String>, with: Option<&str>) -> Result<()> {
+    let deps = switch_deps()?;
+    let ws_name = name.unwrap_or_else(|| current_name(&deps));
+    let path = find(&deps, &ws_name)?;
+    open_with(&path, with)
+}
 
-    let ws_path = deps.dwm_base.join(&repo_name_str).join(name);
-    if !ws_path.exists() {
-        bail!("workspace '{}' not found at {}", name, ws_path.display());
+/// Open `path` in the user's configured editor (see
+/// [`Config::editor_command_for`]) and wait for it to exit.
+///
+/// [`Config::editor_command_for`]: crate::config::Config::editor_command_for
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    open_with(path, None)
+}
+
+/// Run the editor command `with`, or the one configured for `path`'s repo,
+/// on `path`, and wait for it to exit.
+///
+/// The child's stdout is pointed at stderr so the editor still reaches the
+/// terminal when dwm's stdout is being captured by the shell wrapper.
+fn open_with(path: &Path, with: Option<&str>) -> Result<()> {
+    let config = crate::config::Config::load()?;
+    let template = match with {
+        Some(with) => Some(with.to_string()),
+        None => match deps_at(path).and_then(|deps| listed_repo(&deps)) {
+            Ok((dir_name, main_repo)) => config.editor_command_for(&main_repo, &dir_name),
+            Err(_) => config.editor_command(),
+        },
+    }
+    .context("no editor configured (set \"editor\" in ~/.dwm/config.json or $EDITOR)")?;
+    log::debug!("opening {} with '{}'", path.display(), template);
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(editor_script(&template))
+        .arg("dwm")
+        .arg(path)
+        .current_dir(path)
+        .stdout(std::io::stderr())
+        .status()
+        .with_context(|| format!("failed to run '{}'", template))?;
+    if !status.success() {
+        bail!("'{}' exited with {}", template, status);
     }
+    Ok(())
+}
 
-    Ok(ws_path)
+/// Open the named workspace (the current one if `name` is `None`) in VS
+/// Code through a `.code-workspace` file, written or updated first. With
+/// `with_main` the main repo is a second folder, to edit alongside trunk.
+pub fn code_workspace(name: Option<String>, with_main: bool) -> Result<()> {
+    let deps = switch_deps()?;
+    let ws_name = name.unwrap_or_else(|| current_name(&deps));
+    let file = write_code_workspace(&deps, &ws_name, with_main)?;
+    log::debug!("opening {} with code", file.display());
+    let status = std::process::Command::new("code")
+        .arg(&file)
+        .stdout(std::io::stderr())
+        .status()
+        .context("failed to run 'code'; is VS Code's command on your PATH?")?;
+    if !status.success() {
+        bail!("'code' exited with {}", status);
+    }
+    Ok(())
 }
 
 /// Rename a workspace. When `new_name` is `None` the first argument is treated
 /// as the new name and the old name is inferred from the current directory.
-pub fn rename_workspace(name: String, new_name: Option<String>) -> Result<()> {
-    let cwd = std::env::current_dir()?;
+///
+/// Returns `true` if the cwd was inside the renamed workspace and a redirect
+/// path was handed to the shell wrapper.
+pub fn rename_workspace(
+    name: String,
+    new_name: Option<String>,
+    output: ProgressOutput,
+) -> Result<bool> {
+    let cwd = working_dir()?;
     let dwm_base = dwm_base_dir()?;
 
     let backend: Box<dyn vcs::VcsBackend> = if cwd.starts_with(&dwm_base) {
@@ -361,111 +612,22 @@ pub fn rename_workspace(name: String, new_name: Option<String>) -> Result<()> {
         Some(new) => (name, new),
         None => {
             // Infer old name from cwd
-            let old = infer_workspace_name_from_cwd(&deps)?;
+            let old = name_from_cwd(&deps)?;
             (old, name)
         }
     };
 
-    if let Some(redirect) = rename_workspace_inner(&deps, &old, &new)? {
-        println!("{}", redirect.display());
-    }
-    Ok(())
-}
-
-/// Infer the current workspace name from the current directory path.
-///
-/// Expects `cwd` to be `~/.dwm/<repo>/<workspace>[/…]` and returns the
-/// `<workspace>` component.
-fn infer_workspace_name_from_cwd(deps: &WorkspaceDeps) -> Result<String> {
-    if !deps.cwd.starts_with(&deps.dwm_base) {
-        bail!(
-            "not inside a dwm workspace (current dir must be under {})",
-            deps.dwm_base.display()
-        );
-    }
-    let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
-    let components: Vec<&std::ffi::OsStr> = relative.components().map(|c| c.as_os_str()).collect();
-    if components.len() < 2 {
-        bail!("could not determine workspace name from current directory");
-    }
-    Ok(components[1].to_string_lossy().to_string())
-}
-
-/// Returns the path the shell should cd to if cwd was inside the renamed workspace.
-fn rename_workspace_inner(
-    deps: &WorkspaceDeps,
-    old_name: &str,
-    new_name: &str,
-) -> Result<Option<PathBuf>> {
-    let repo_name_str = if deps.cwd.starts_with(&deps.dwm_base) {
-        let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
-        relative
-            .components()
-            .next()
-            .context("could not determine repo from workspace path")?
-            .as_os_str()
-            .to_string_lossy()
-            .to_string()
-    } else {
-        deps.backend.repo_name_from(&deps.cwd)?
-    };
-
-    let main_ws_name = deps.backend.main_workspace_name();
-    if old_name == main_ws_name {
-        bail!("cannot rename the main workspace '{}'", old_name);
-    }
-
-    let old_path = deps.dwm_base.join(&repo_name_str).join(old_name);
-    if !old_path.exists() {
-        bail!(
-            "workspace '{}' not found at {}",
-            old_name,
-            old_path.display()
-        );
-    }
-
-    if new_name.starts_with('.') {
-        bail!("workspace name cannot start with '.'");
-    }
-
-    let new_path = deps.dwm_base.join(&repo_name_str).join(new_name);
-    if new_path.exists() {
-        bail!(
-            "workspace '{}' already exists at {}",
-            new_name,
-            new_path.display()
-        );
-    }
-
-    let main_repo = main_repo_path(&deps.dwm_base, &repo_name_str)?;
-
-    eprintln!(
-        "{} workspace '{}' -> '{}'...",
-        "renaming".cyan(),
-        old_name.bold(),
-        new_name.bold()
-    );
-    deps.backend
-        .workspace_rename(&main_repo, &old_path, &new_path, old_name, new_name)?;
-
-    eprintln!(
-        "{} workspace '{}' renamed to '{}'",
-        "✓".green(),
-        old_name.bold(),
-        new_name.bold()
-    );
-
-    if is_inside(&deps.cwd, &old_path) {
-        let relative = deps.cwd.strip_prefix(&old_path)?;
-        Ok(Some(new_path.join(relative)))
+    if let Some(redirect) = rename(&deps, &old, &new, output)? {
+        crate::shell::print_cd(&redirect)?;
+        Ok(true)
     } else {
-        Ok(None)
+        Ok(false)
     }
 }
 
 /// Return the `~/.dwm/<repo>/` directory for the current working directory.
 pub fn current_repo_dir() -> Result<PathBuf> {
-    let cwd = std::env::current_dir()?;
+    let cwd = working_dir()?;
     let dwm_base = dwm_base_dir()?;
 
     let repo_name_str = if cwd.starts_with(&dwm_base) {
@@ -486,9 +648,30 @@ pub fn current_repo_dir() -> Result<PathBuf> {
 }
 
 /// Collect [`WorkspaceEntry`] values for all workspaces belonging to the
-/// repository that contains the current directory.
+/// repository that contains the current directory, from `dwm daemon` when
+/// it has them up to date.
 pub fn list_workspace_entries() -> Result<Vec<WorkspaceEntry>> {
-    let cwd = std::env::current_dir()?;
+    let deps = list_deps()?;
+    if let Ok((repo_name, _)) = listed_repo(&deps)
+        && let Some(entries) = daemon::workspaces(&deps.dwm_base, &repo_name)
+    {
+        return Ok(entries);
+    }
+    list(&deps)
+}
+
+/// Quickly list the current repo's workspaces from the filesystem alone,
+/// without asking the VCS for descriptions or diff stats. Used to open the
+/// picker immediately while [`list_workspace_entries`] runs in the
+/// background.
+pub fn list_workspace_names() -> Result<Vec<WorkspaceEntry>> {
+    list_names(&list_deps()?)
+}
+
+/// Build [`WorkspaceDeps`] for listing the repo containing the current
+/// directory, which may be the main repo or a dwm workspace.
+fn list_deps() -> Result<WorkspaceDeps> {
+    let cwd = working_dir()?;
     let dwm_base = dwm_base_dir()?;
 
     let backend: Box<dyn vcs::VcsBackend> = if cwd.starts_with(&dwm_base) {
@@ -506,245 +689,232 @@ pub fn list_workspace_entries() -> Result<Vec<WorkspaceEntry>> {
         vcs::detect(&cwd)?
     };
 
-    let deps = WorkspaceDeps {
+    Ok(WorkspaceDeps {
         backend,
         cwd,
         dwm_base,
-    };
-    list_workspace_entries_inner(&deps)
+    })
 }
 
-/// Testable core of [`list_workspace_entries`].
-fn list_workspace_entries_inner(deps: &WorkspaceDeps) -> Result<Vec<WorkspaceEntry>> {
-    let (repo_name_str, main_repo) = if deps.cwd.starts_with(&deps.dwm_base) {
-        let relative = deps.cwd.strip_prefix(&deps.dwm_base)?;
-        let repo_name_str = relative
-            .components()
-            .next()
-            .context("could not determine repo from workspace path")?
-            .as_os_str()
-            .to_string_lossy()
-            .to_string();
-        let main_repo = main_repo_path(&deps.dwm_base, &repo_name_str)?;
-        (repo_name_str, main_repo)
-    } else {
-        let repo_name_str = deps.backend.repo_name_from(&deps.cwd)?;
-        let main_repo = deps.backend.root_from(&deps.cwd)?;
-        (repo_name_str, main_repo)
-    };
-
-    let rd = repo_dir(&deps.dwm_base, &repo_name_str);
-    if !rd.exists() {
-        return Ok(Vec::new());
+/// Names of the workspaces of the repo containing the current directory,
+/// main one first, for shell completion. Reads only `~/.dwm/`, never the
+/// VCS, so it's quick enough to run on every tab.
+pub fn completion_names() -> Vec<String> {
+    match (dwm_base_dir(), working_dir()) {
+        (Ok(dwm_base), Ok(cwd)) => completion_names_at(&dwm_base, &cwd),
+        _ => Vec::new(),
     }
+}
 
-    let mut agent_summaries = agent::read_agent_summaries(&rd);
-
-    let main_ws_name = deps.backend.main_workspace_name();
-    let vcs_workspaces = deps.backend.workspace_list(&main_repo).unwrap_or_default();
-
-    let mut entries = Vec::new();
-
-    // Find info for the main workspace
-    let main_info = vcs_workspaces
-        .iter()
-        .find(|(n, _)| n == main_ws_name)
-        .map(|(_, info)| info.clone())
-        .unwrap_or_default();
+/// Fetch trunk from the remote for the current repo, so merge status and
+/// diff stats compare against a current trunk. Unless `force`, skips the
+/// fetch if one was attempted in the last [`FETCH_INTERVAL`].
+pub fn fetch_trunk(force: bool) -> Result<()> {
+    let deps = list_deps()?;
+    let (repo_name, main_repo) = listed_repo(&deps)?;
+    let rd = repo_dir(&deps.dwm_base, &repo_name);
+    fetch_repo(&*deps.backend, &rd, &main_repo, force, SystemTime::now()).map(|_| ())
+}
 
-    let main_stat = deps
-        .backend
-        .diff_stat_vs_trunk(&main_repo, &main_repo, main_ws_name)
-        .unwrap_or_default();
-    let main_modified = fs::metadata(&main_repo).and_then(|m| m.modified()).ok();
-    let main_description = if main_info.description.trim().is_empty() {
-        deps.backend
-            .latest_description(&main_repo, &main_repo, main_ws_name)
-    } else {
-        main_info.description.clone()
+/// [`fetch_trunk`] for every repo tracked under `~/.dwm/`, carrying on past
+/// failures and reporting them all at the end.
+pub fn fetch_all_trunks(force: bool) -> Result<()> {
+    let dwm_base = dwm_base_dir()?;
+    let Ok(read_dir) = fs::read_dir(&dwm_base) else {
+        return Ok(());
     };
-    let vcs_type = deps.backend.vcs_type();
-    entries.push(WorkspaceEntry {
-        name: main_ws_name.to_string(),
-        path: main_repo.clone(),
-        last_modified: main_modified,
-        diff_stat: main_stat,
-        is_main: true,
-        change_id: main_info.change_id.clone(),
-        description: main_description,
-        bookmarks: main_info.bookmarks.clone(),
-        is_stale: false,
-        repo_name: None,
-        main_repo_path: main_repo.clone(),
-        vcs_type,
-        agent_status: agent_summaries.remove(main_ws_name),
-    });
-
-    // Scan workspace dirs
-    let read_dir = fs::read_dir(&rd)?;
-    for entry in read_dir {
-        let entry = entry?;
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-        let name = path.file_name().unwrap().to_string_lossy().to_string();
-
-        // Skip internal dot-prefixed entries (.main-repo, .vcs-type, .agent-status, etc.)
-        if name.starts_with('.') {
+    let mut failures = Vec::new();
+    for dir_entry in read_dir.flatten() {
+        let rd = dir_entry.path();
+        let Ok(Some(RepoMeta { main_repo, .. })) = RepoMeta::load(&rd) else {
             continue;
-        }
-
-        let ws_info = vcs_workspaces
-            .iter()
-            .find(|(n, _)| *n == name)
-            .map(|(_, info)| info.clone());
-
-        let has_info = ws_info.is_some();
-        let info = ws_info.unwrap_or_default();
-
-        let stat = if has_info {
-            deps.backend
-                .diff_stat_vs_trunk(&main_repo, &path, &name)
-                .unwrap_or_default()
-        } else {
-            vcs::DiffStat::default()
         };
-
-        let description = if info.description.trim().is_empty() {
-            deps.backend.latest_description(&main_repo, &path, &name)
-        } else {
-            info.description.clone()
+        let Ok(backend) = vcs::detect_from_dwm_dir(&rd) else {
+            continue;
         };
+        if let Err(e) = fetch_repo(&*backend, &rd, &main_repo, force, SystemTime::now()) {
+            failures.push(format!("{}: {}", main_repo.display(), e));
+        }
+    }
+    if !failures.is_empty() {
+        bail!("{}", failures.join("; "));
+    }
+    Ok(())
+}
 
-        let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
-
-        let merge_status =
-            if has_info && deps.backend.is_merged_into_trunk(&main_repo, &path, &name) {
-                MergeStatus::Merged
-            } else {
-                MergeStatus::Unmerged
-            };
-
-        let agent_status = agent_summaries.remove(&name);
-        entries.push(WorkspaceEntry {
-            is_stale: compute_is_stale(merge_status, modified),
-            repo_name: None,
-            name,
-            path,
-            last_modified: modified,
-            diff_stat: stat,
-            is_main: false,
-            change_id: info.change_id,
-            description,
-            bookmarks: info.bookmarks,
-            main_repo_path: main_repo.clone(),
-            vcs_type,
-            agent_status,
-        });
+/// Which forge state is fetched for a repo using `vcs_type`: what the
+/// `pr_status` and `ci_status` settings turn on, and only for git, whose
+/// branches are what pull requests are opened from.
+fn forge_shown(vcs_type: vcs::VcsType) -> forge::Shown {
+    match crate::config::Config::load() {
+        Ok(config) if vcs_type == vcs::VcsType::Git => forge::Shown::from_config(&config),
+        _ => forge::Shown::default(),
     }
+}
 
-    Ok(entries)
+/// Fetch the current repo's open pull requests and CI state if they're
+/// shown and the cached ones are due a refresh; see [`forge::refresh`].
+/// Returns the new state if it fetched. Failures are only logged, since
+/// forge state is an extra and this runs in the background.
+pub fn refresh_forge() -> Option<forge::ForgeCache> {
+    let deps = list_deps().ok()?;
+    let shown = forge_shown(deps.backend.vcs_type());
+    if !shown.any() {
+        return None;
+    }
+    let (repo_name, main_repo) = listed_repo(&deps).ok()?;
+    let rd = repo_dir(&deps.dwm_base, &repo_name);
+    forge::refresh(&rd, &main_repo, shown, SystemTime::now())
+        .inspect_err(|e| log::debug!("could not fetch forge state: {:#}", e))
+        .ok()
+        .flatten()
 }
 
-/// Number of days of inactivity after which a workspace is considered stale.
-const STALE_DAYS: u64 = 30;
-
-/// All data needed to display a single row in the workspace picker or status output.
-#[derive(Debug)]
-pub struct WorkspaceEntry {
-    pub name: String,
-    pub path: PathBuf,
-    pub last_modified: Option<std::time::SystemTime>,
-    pub diff_stat: vcs::DiffStat,
-    pub is_main: bool,
-    pub change_id: String,
-    pub description: String,
-    pub bookmarks: Vec<String>,
-    pub is_stale: bool,
-    pub repo_name: Option<String>,
-    pub main_repo_path: PathBuf,
-    pub vcs_type: vcs::VcsType,
-    pub agent_status: Option<agent::AgentSummary>,
+/// [`refresh_forge`] for every repo tracked under `~/.dwm/`, concurrently.
+/// Returns whether any repo's forge state was fetched.
+pub fn refresh_all_forges() -> bool {
+    let Ok(dwm_base) = dwm_base_dir() else {
+        return false;
+    };
+    let repos: Vec<(PathBuf, PathBuf, forge::Shown)> = fs::read_dir(&dwm_base)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter_map(|rd| {
+            let meta = RepoMeta::load(&rd).ok()??;
+            let shown = forge_shown(meta.vcs_type());
+            shown.any().then_some((rd, meta.main_repo, shown))
+        })
+        .collect();
+    let fetched = vcs::parallel_map(&repos, |(rd, main_repo, shown)| {
+        forge::refresh(rd, main_repo, *shown, SystemTime::now())
+            .inspect_err(|e| log::debug!("could not fetch forge state: {:#}", e))
+            .is_ok_and(|cache| cache.is_some())
+    });
+    fetched.contains(&true)
 }
 
-/// Determine whether a non-main workspace should be shown as stale.
-///
-/// A workspace is stale if it has been merged into trunk, or if its last
-/// modification time is more than [`STALE_DAYS`] days in the past.
-fn compute_is_stale(merged: MergeStatus, last_modified: Option<SystemTime>) -> bool {
-    if merged == MergeStatus::Merged {
-        return true;
-    }
-    if let Some(time) = last_modified
-        && let Ok(duration) = time.elapsed()
-    {
-        return duration.as_secs() > STALE_DAYS * 86400;
+/// Format a byte count compactly: `512 B`, `3.4 MB`, `1.2 GB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
     }
-    false
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
 }
 
-/// Collect [`WorkspaceEntry`] values for every workspace across all repos
-/// tracked under `~/.dwm/`.
-pub fn list_all_workspace_entries() -> Result<Vec<WorkspaceEntry>> {
-    let dwm_base = dwm_base_dir()?;
-    list_all_workspace_entries_inner(&dwm_base)
+/// Print a table of the repos tracked under `~/.dwm/` to stderr.
+pub fn list_repos() -> Result<()> {
+    print_repos_to(&tracked_repos(&dwm_base_dir()?), std::io::stderr().lock())
 }
 
-/// Testable core of [`list_all_workspace_entries`].
-fn list_all_workspace_entries_inner(dwm_base: &Path) -> Result<Vec<WorkspaceEntry>> {
-    if !dwm_base.exists() {
-        return Ok(Vec::new());
+/// Write the `dwm repos list` table for `repos` to `out`. Repos whose main
+/// checkout is gone are marked, since they're candidates for forgetting.
+fn print_repos_to<W: Write>(repos: &[TrackedRepo], mut out: W) -> Result<()> {
+    if repos.is_empty() {
+        writeln!(out, "no repos tracked yet (run dwm init in one)")?;
+        return Ok(());
     }
+    let name_w = repos
+        .iter()
+        .map(|r| r.label.len())
+        .max()
+        .unwrap_or(4)
+        .max(4);
+    writeln!(
+        out,
+        "{}",
+        format!(
+            "{:<name_w$}  {:<8}  {:>10}  {:>9}  PATH",
+            "NAME", "VCS", "WORKSPACES", "SIZE"
+        )
+        .bold()
+        .dimmed()
+    )?;
+    for repo in repos {
+        let missing = if repo.main_repo.exists() {
+            String::new()
+        } else {
+            format!(" {}", "(missing)".red())
+        };
+        writeln!(
+            out,
+            "{:<name_w$}  {:<8}  {:>10}  {:>9}  {}{}",
+            repo.label,
+            repo.vcs,
+            repo.workspaces,
+            format_size(repo.size),
+            repo.main_repo.display(),
+            missing
+        )?;
+    }
+    Ok(())
+}
 
-    let mut all_entries = Vec::new();
-
-    for dir_entry in fs::read_dir(dwm_base)? {
-        let dir_entry = dir_entry?;
-        let repo_path = dir_entry.path();
-        if !repo_path.is_dir() {
-            continue;
-        }
-
-        let main_repo_file = repo_path.join(".main-repo");
-        if !main_repo_file.exists() {
-            continue;
+/// Stop tracking the repo called `name`, deleting its workspaces, after
+/// asking unless `yes` is set. The main repo itself is left alone.
+pub fn forget_repo(name: &str, yes: bool) -> Result<()> {
+    let dwm_base = dwm_base_dir()?;
+    let repo = find_tracked_repo(&dwm_base, name)?;
+    if !yes {
+        let question = format!(
+            "Forget {} and delete its {} workspace{} ({})? Uncommitted work in them is lost.",
+            repo.label.bold(),
+            repo.workspaces,
+            if repo.workspaces == 1 { "" } else { "s" },
+            format_size(repo.size)
+        );
+        if !confirm(&question)? {
+            bail!(Failure::cancelled());
         }
+    }
+    forget(&dwm_base, &repo, &vcs::detect_from_dwm_dir)?;
+    log::info!("{} forgot {}", "✓".green(), repo.label.bold());
+    Ok(())
+}
 
-        let main_repo_content = match fs::read_to_string(&main_repo_file) {
-            Ok(s) => s,
-            Err(_) => continue,
-        };
-        let repo_name = Path::new(main_repo_content.trim())
-            .file_name()
-            .map(|n| n.to_string_lossy().into_owned())
-            .unwrap_or_else(|| dir_entry.file_name().to_string_lossy().into_owned());
-
-        let backend = match vcs::detect_from_dwm_dir(&repo_path) {
-            Ok(b) => b,
-            Err(_) => continue,
-        };
+/// Change the name shown for the tracked repo called `name` to `label`,
+/// or back to its directory name when `label` is that name. Its `~/.dwm/`
+/// entry keeps its name, since that's derived from the repo's path.
+pub fn rename_repo(name: &str, label: &str) -> Result<()> {
+    let dwm_base = dwm_base_dir()?;
+    relabel(&dwm_base, name, label)?;
+    log::info!("{} renamed {} to {}", "✓".green(), name, label.bold());
+    Ok(())
+}
 
-        let deps = WorkspaceDeps {
-            backend,
-            cwd: repo_path.clone(),
-            dwm_base: dwm_base.to_path_buf(),
-        };
+/// Ask `question` on the terminal and return whether the answer was yes.
+fn confirm(question: &str) -> Result<bool> {
+    Ok(ask(&format!("{} [y/N]", question))?.eq_ignore_ascii_case("y"))
+}
 
-        match list_workspace_entries_inner(&deps) {
-            Ok(entries) => {
-                for mut entry in entries {
-                    entry.repo_name = Some(repo_name.clone());
-                    all_entries.push(entry);
-                }
-            }
-            Err(e) => {
-                eprintln!("warning: skipping repo '{}': {}", repo_name, e);
-            }
-        }
-    }
+/// Ask `question` on the terminal and return the trimmed answer. Reads the
+/// terminal rather than stdin so it works with stdin redirected; without a
+/// terminal the answer is empty.
+fn ask(question: &str) -> Result<String> {
+    eprint!("{} {} ", "?".bold().cyan(), question);
+    let Some(line) = crate::platform::read_tty_line()? else {
+        eprintln!();
+        return Ok(String::new());
+    };
+    Ok(line.trim().to_string())
+}
 
-    Ok(all_entries)
+/// Collect [`WorkspaceEntry`] values for every workspace across all repos
+/// tracked under `~/.dwm/`, from `dwm daemon` when it has them up to date.
+pub fn list_all_workspace_entries() -> Result<Vec<WorkspaceEntry>> {
+    let dwm_base = dwm_base_dir()?;
+    if let Some(entries) = daemon::all_workspaces(&dwm_base) {
+        return Ok(entries);
+    }
+    list_all(&dwm_base)
 }
 
 /// Format a [`SystemTime`] as a human-readable relative age string such as
@@ -777,32 +947,125 @@ pub fn format_time_ago(time: Option<SystemTime>) -> String {
     format!("{}mo ago", months)
 }
 
-/// Print a non-interactive tabular workspace summary to stderr.
+/// Print one tab-separated `name, path, description` line per workspace to
+/// stdout, for piping into external pickers. Names are qualified as
+/// `repo/name` when entries span several repos. A `format` template
+/// replaces the default columns.
+pub fn print_plain(entries: &[WorkspaceEntry], format: Option<&ListFormat>) {
+    let mut out = std::io::stdout().lock();
+    for entry in entries {
+        let line = match format {
+            Some(format) => format.render(entry),
+            None => plain_line(entry),
+        };
+        if writeln!(out, "{}", line).is_err() {
+            // The reader went away (e.g. `| head`); stop quietly.
+            return;
+        }
+    }
+}
+
+/// Format `entry` for [`print_plain`]. Tabs and newlines in the description
+/// would break the columns, so only its first line is kept, tabs replaced.
+fn plain_line(entry: &WorkspaceEntry) -> String {
+    let name = match &entry.repo_name {
+        Some(repo) => format!("{}/{}", repo, entry.name),
+        None => entry.name.clone(),
+    };
+    let description = entry
+        .description
+        .lines()
+        .next()
+        .unwrap_or("")
+        .replace('\t', " ");
+    format!("{}\t{}\t{}", name, entry.path.display(), description)
+}
+
+/// `"*"` after the name of a workspace with uncommitted changes or
+/// untracked files, else empty.
+pub fn dirty_marker(entry: &WorkspaceEntry) -> &'static str {
+    if entry.working_copy.is_dirty() {
+        "*"
+    } else {
+        ""
+    }
+}
+
+/// Remember the order `dwm status` numbered `entries` in, so `dwm switch 3`
+/// and friends can name a workspace by its row.
+pub fn record_listing(entries: &[WorkspaceEntry]) -> Result<()> {
+    record_listing_in(&current_repo_dir()?, entries)
+}
+
+/// Called by the shell wrappers after each `cd`: stamp the workspace at
+/// `path` as visited and, with `tmux_rename_window` set, name the tmux window
+/// after it. Outside a dwm workspace this returns before touching the disk,
+/// so it's cheap to run on every `cd`. Never fails, so a wrapper never
+/// prints an error after a successful `cd`.
+pub fn on_cd(path: &Path) {
+    let Ok(dwm_base) = dwm_base_dir() else {
+        return;
+    };
+    let Some((rd, ws_name)) = workspace_at(&dwm_base, path) else {
+        return;
+    };
+    if let Err(e) = record_visit(&rd, &ws_name, SystemTime::now()) {
+        log::debug!("could not record visit to {}: {:#}", ws_name, e);
+    }
+    if std::env::var_os("TMUX").is_some()
+        && crate::config::Config::load().is_ok_and(|c| c.tmux_rename_window)
+    {
+        let _ = std::process::Command::new("tmux")
+            .args(["rename-window", &ws_name])
+            .status();
+    }
+}
+
+/// Width of `dwm status`'s description column when stderr isn't a terminal.
+const STATUS_DESC_WIDTH: usize = 40;
+
+/// Narrowest the description column gets to fit a narrow terminal.
+const STATUS_DESC_MIN: usize = 20;
+
+/// Print a non-interactive tabular workspace summary to stderr, fitting the
+/// description column to the terminal.
 pub fn print_status(entries: &[WorkspaceEntry]) {
-    let out = std::io::stderr().lock();
-    let _ = print_status_to(entries, out);
+    use std::io::IsTerminal;
+    let stderr = std::io::stderr();
+    let columns = stderr
+        .is_terminal()
+        .then(crossterm::terminal::size)
+        .and_then(Result::ok)
+        .map(|(columns, _)| usize::from(columns));
+    let _ = print_status_to(entries, columns, stderr.lock());
 }
 
-/// Core logic for printing the status table to any Write implementation.
-fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<()> {
-    // Column widths
+/// Core logic for printing the status table to any Write implementation,
+/// `columns` wide if known.
+fn print_status_to<W: Write>(
+    entries: &[WorkspaceEntry],
+    columns: Option<usize>,
+    mut out: W,
+) -> Result<()> {
+    // Column widths, in terminal columns rather than bytes or characters.
     let name_w = entries
         .iter()
         .map(|e| {
             let display = if e.is_main {
-                format!("{} (main)", e.name)
+                format!("{}{} (main)", e.name, dirty_marker(e))
             } else {
-                e.name.clone()
+                format!("{}{}", e.name, dirty_marker(e))
             };
-            display.len()
+            width::of(&display)
         })
         .max()
         .unwrap_or(4)
         .max(4);
+    let index_w = entries.len().to_string().len();
     let change_w = 8;
     let bookmark_w = entries
         .iter()
-        .map(|e| e.bookmarks.join(", ").len())
+        .map(|e| width::of(&e.bookmarks.join(", ")))
         .max()
         .unwrap_or(9)
         .max(9);
@@ -815,7 +1078,7 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
             .map(|e| {
                 e.agent_status
                     .as_ref()
-                    .map(|s| s.to_string().len())
+                    .map(|s| width::of(&s.to_string()))
                     .unwrap_or(0)
             })
             .max()
@@ -825,43 +1088,95 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
         0
     };
 
+    let has_prs = entries.iter().any(|e| e.pr.is_some());
+    let pr_w = entries
+        .iter()
+        .filter_map(|e| e.pr.as_ref())
+        .map(|pr| width::of(&pr.to_string()))
+        .max()
+        .unwrap_or(2)
+        .max(2);
+    let has_ci = entries.iter().any(|e| e.ci.is_some());
+    let ci_w = entries
+        .iter()
+        .filter_map(|e| e.ci.as_ref())
+        .map(|ci| width::of(&ci.to_string()))
+        .max()
+        .unwrap_or(2)
+        .max(2);
+    let changes: Vec<String> = entries.iter().map(status_changes).collect();
+
+    // The description takes whatever the other columns leave of the
+    // terminal, but no more than the longest one needs.
+    let desc_w = match columns {
+        Some(columns) => {
+            let optional = |shown: bool, w: usize| if shown { 2 + w } else { 0 };
+            let others = index_w
+                + 2
+                + name_w
+                + 2
+                + change_w
+                + 2
+                + 2
+                + bookmark_w
+                + 2
+                + 9
+                + optional(has_prs, pr_w)
+                + optional(has_ci, ci_w)
+                + optional(has_agents, agent_w)
+                + 2
+                + changes
+                    .iter()
+                    .map(|c| width::of(c))
+                    .max()
+                    .unwrap_or(0)
+                    .max("CHANGES".len());
+            let longest = entries
+                .iter()
+                .map(|e| width::of(e.description.lines().next().unwrap_or("")))
+                .max()
+                .unwrap_or(0)
+                .max("DESCRIPTION".len());
+            columns
+                .saturating_sub(others)
+                .max(STATUS_DESC_MIN)
+                .min(longest)
+        }
+        None => STATUS_DESC_WIDTH,
+    };
+
     // Header
+    let mut header = format!(
+        "{:>index_w$}  {:<name_w$}  {:<change_w$}  {:<desc_w$}  {:<bookmark_w$}  {:<9}",
+        "#", "NAME", "CHANGE", "DESCRIPTION", "BOOKMARKS", "MODIFIED",
+    );
+    if has_prs {
+        header.push_str(&format!("  {:<pr_w$}", "PR"));
+    }
+    if has_ci {
+        header.push_str(&format!("  {:<ci_w$}", "CI"));
+    }
     if has_agents {
-        let _ = writeln!(
-            out,
-            "{}",
-            format!(
-                "{:<name_w$}  {:<change_w$}  {:<40}  {:<bookmark_w$}  {:<9}  {:<agent_w$}  CHANGES",
-                "NAME", "CHANGE", "DESCRIPTION", "BOOKMARKS", "MODIFIED", "AGENTS",
-            )
-            .bold()
-            .dimmed()
-        );
-    } else {
-        let _ = writeln!(
-            out,
-            "{}",
-            format!(
-                "{:<name_w$}  {:<change_w$}  {:<40}  {:<bookmark_w$}  {:<9}  CHANGES",
-                "NAME", "CHANGE", "DESCRIPTION", "BOOKMARKS", "MODIFIED",
-            )
-            .bold()
-            .dimmed()
-        );
+        header.push_str(&format!("  {:<agent_w$}", "AGENTS"));
     }
+    header.push_str("  CHANGES");
+    let _ = writeln!(out, "{}", header.bold().dimmed());
 
-    for entry in entries {
+    for (i, (entry, changes_text)) in entries.iter().zip(changes).enumerate() {
+        // Numbers `dwm switch 3` and friends accept; see [`record_listing`].
+        let index = format!("{:>index_w$}", i + 1).dimmed().to_string();
+        let marker = dirty_marker(entry);
         let name_text = if entry.is_main {
-            format!("{} (main)", entry.name)
+            format!("{}{} (main)", entry.name, marker)
         } else if entry.is_stale {
-            format!("{} [stale]", entry.name)
+            format!("{}{} [stale]", entry.name, marker)
         } else {
-            entry.name.clone()
+            format!("{}{}", entry.name, marker)
         };
 
         let dim = entry.is_stale;
         let name_colored = {
-            let s = format!("{:<name_w$}", name_text);
+            let s = width::pad(&name_text, name_w);
             if dim {
                 s.dimmed().to_string()
             } else {
@@ -879,9 +1194,8 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
         };
 
         let desc = entry.description.lines().next().unwrap_or("");
-        let desc_text: String = desc.chars().take(40).collect();
         let desc_colored = {
-            let s = format!("{:<40}", desc_text);
+            let s = width::fit(desc, desc_w);
             if dim {
                 s.dimmed().to_string()
             } else {
@@ -891,7 +1205,7 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
 
         let bookmarks_text = entry.bookmarks.join(", ");
         let bookmarks_colored = {
-            let s = format!("{:<bookmark_w$}", bookmarks_text);
+            let s = width::pad(&bookmarks_text, bookmark_w);
             if dim {
                 s.dimmed().to_string()
             } else {
@@ -910,25 +1224,10 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
         };
 
         let stat = &entry.diff_stat;
-        let changes_text = if stat.files_changed == 0 && stat.insertions == 0 && stat.deletions == 0
-        {
-            "clean".to_string()
-        } else {
-            let mut parts = Vec::new();
-            if stat.insertions > 0 {
-                parts.push(format!("+{}", stat.insertions));
-            }
-            if stat.deletions > 0 {
-                parts.push(format!("-{}", stat.deletions));
-            }
-            if parts.is_empty() {
-                format!("{} files", stat.files_changed)
-            } else {
-                parts.join(" ")
-            }
-        };
-
-        let changes_colored = if dim {
+        // A failed backend call leaves the stat empty; don't call that clean.
+        let changes_colored = if entry.error.is_some() {
+            "error".red().to_string()
+        } else if dim {
             changes_text.dimmed().to_string()
         } else if stat.deletions > stat.insertions {
             changes_text.red().to_string()
@@ -938,10 +1237,45 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
             changes_text.dimmed().to_string()
         };
 
+        let mut row = format!(
+            "{}  {}  {}  {}  {}  {}",
+            index, name_colored, change_colored, desc_colored, bookmarks_colored, time_colored,
+        );
+        if has_prs {
+            let text = entry
+                .pr
+                .as_ref()
+                .map(|pr| pr.to_string())
+                .unwrap_or_default();
+            let text = width::pad(&text, pr_w);
+            let pr_colored = match &entry.pr {
+                _ if dim => text.dimmed().to_string(),
+                Some(pr) if pr.is_blocked() => text.red().to_string(),
+                Some(pr) if pr.is_ready() => text.green().to_string(),
+                _ => text.dimmed().to_string(),
+            };
+            row.push_str(&format!("  {}", pr_colored));
+        }
+        if has_ci {
+            let text = entry
+                .ci
+                .as_ref()
+                .map(|ci| ci.to_string())
+                .unwrap_or_default();
+            let text = width::pad(&text, ci_w);
+            let ci_colored = match entry.ci.as_ref().map(|ci| ci.checks) {
+                _ if dim => text.dimmed().to_string(),
+                Some(forge::Checks::Passing) => text.green().to_string(),
+                Some(forge::Checks::Failing) => text.red().to_string(),
+                Some(forge::Checks::Pending) => text.yellow().to_string(),
+                None => text,
+            };
+            row.push_str(&format!("  {}", ci_colored));
+        }
         if has_agents {
             let agent_colored = match &entry.agent_status {
                 Some(summary) if !summary.is_empty() => {
-                    let text = format!("{:<agent_w$}", summary);
+                    let text = width::pad(&summary.to_string(), agent_w);
                     if dim {
                         text.dimmed().to_string()
                     } else {
@@ -954,1194 +1288,351 @@ fn print_status_to<W: Write>(entries: &[WorkspaceEntry], mut out: W) -> Result<(
                 }
                 _ => format!("{:<agent_w$}", ""),
             };
+            row.push_str(&format!("  {}", agent_colored));
+        }
+        let _ = writeln!(out, "{}  {}", row, changes_colored);
+    }
 
-            let _ = writeln!(
-                out,
-                "{}  {}  {}  {}  {}  {}  {}",
-                name_colored,
-                change_colored,
-                desc_colored,
-                bookmarks_colored,
-                time_colored,
-                agent_colored,
-                changes_colored,
-            );
-        } else {
-            let _ = writeln!(
-                out,
-                "{}  {}  {}  {}  {}  {}",
-                name_colored,
-                change_colored,
-                desc_colored,
-                bookmarks_colored,
-                time_colored,
-                changes_colored,
-            );
+    for entry in entries {
+        if let Some(error) = &entry.error {
+            let _ = writeln!(out, "{} {}: {}", "warning:".yellow(), entry.name, error);
         }
     }
     Ok(())
 }
 
+/// The changes column of `dwm status` for `entry`: `+insertions -deletions`
+/// or `clean`, then commits ahead of and behind trunk.
+fn status_changes(entry: &WorkspaceEntry) -> String {
+    let stat = &entry.diff_stat;
+    let changes = if stat.files_changed == 0 && stat.insertions == 0 && stat.deletions == 0 {
+        "clean".to_string()
+    } else {
+        let mut parts = Vec::new();
+        if stat.insertions > 0 {
+            parts.push(format!("+{}", stat.insertions));
+        }
+        if stat.deletions > 0 {
+            parts.push(format!("-{}", stat.deletions));
+        }
+        if parts.is_empty() {
+            format!("{} files", stat.files_changed)
+        } else {
+            parts.join(" ")
+        }
+    };
+    match stat.commit_counts() {
+        Some(counts) => format!("{} {}", changes, counts),
+        None => changes,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::Path;
-    use std::sync::{Arc, Mutex};
 
     fn print_status_to_string(entries: &[WorkspaceEntry]) -> String {
         owo_colors::set_override(true);
         let mut buf = Vec::new();
-        print_status_to(entries, &mut buf).unwrap();
+        print_status_to(entries, None, &mut buf).unwrap();
         String::from_utf8(buf).unwrap()
     }
 
+    /// The status table for `entries`, `columns` wide, without colors.
+    fn print_status_plain(entries: &[WorkspaceEntry], columns: Option<usize>) -> String {
+        owo_colors::set_override(true);
+        let mut buf = Vec::new();
+        print_status_to(entries, columns, &mut buf).unwrap();
+        // Drop color escapes, which all end in `m`.
+        let mut plain = String::new();
+        let mut chars = String::from_utf8(buf)
+            .unwrap()
+            .chars()
+            .collect::<Vec<_>>()
+            .into_iter();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                plain.push(c);
+            }
+        }
+        plain
+    }
+
     #[test]
-    fn is_inside_detects_cwd_within_workspace() {
-        let ws = Path::new("/home/user/.dwm/myrepo/my-workspace");
-        assert!(is_inside(ws, ws));
-        assert!(is_inside(
-            Path::new("/home/user/.dwm/myrepo/my-workspace/src"),
-            ws,
-        ));
+    fn editor_script_appends_path() {
+        assert_eq!(editor_script("vim"), "vim \"$1\"");
+        assert_eq!(editor_script("code -n"), "code -n \"$1\"");
     }
 
     #[test]
-    fn is_inside_false_for_sibling_workspace() {
-        let ws = Path::new("/home/user/.dwm/myrepo/my-workspace");
-        assert!(!is_inside(
-            Path::new("/home/user/.dwm/myrepo/other-workspace"),
-            ws,
-        ));
+    fn editor_script_substitutes_placeholder() {
+        assert_eq!(
+            editor_script("tmux new-window -c {path} nvim"),
+            "tmux new-window -c \"$1\" nvim"
+        );
     }
 
     #[test]
-    fn is_inside_false_for_main_repo() {
-        let ws = Path::new("/home/user/.dwm/myrepo/my-workspace");
-        assert!(!is_inside(Path::new("/home/user/code/myrepo"), ws));
+    fn format_size_picks_a_unit() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 300 * 1024), "5.3 MB");
     }
 
-    // ── MockBackend ──────────────────────────────────────────────────
+    // ── format_time_ago tests ───────────────────────────────────────
 
-    #[derive(Debug, Clone)]
-    enum MockCall {
-        WorkspaceAdd {
-            repo_dir: PathBuf,
-            ws_path: PathBuf,
-            name: String,
-            at: Option<String>,
-        },
-        WorkspaceRemove {
-            repo_dir: PathBuf,
-            name: String,
-            ws_path: PathBuf,
-        },
-        WorkspaceRename {
-            old_name: String,
-            new_name: String,
-        },
+    #[test]
+    fn format_time_ago_none_returns_unknown() {
+        assert_eq!(format_time_ago(None), "unknown");
     }
 
-    struct MockBackend {
-        /// The root path returned by root_from / repo_name_from.
-        root: PathBuf,
-        /// Workspaces returned by workspace_list.
-        workspaces: Vec<(String, vcs::WorkspaceInfo)>,
-        /// Records every mutating call for assertions.
-        calls: Arc<Mutex<Vec<MockCall>>>,
-    }
-
-    impl MockBackend {
-        fn new(
-            root: PathBuf,
-            workspaces: Vec<(String, vcs::WorkspaceInfo)>,
-        ) -> (Self, Arc<Mutex<Vec<MockCall>>>) {
-            let calls = Arc::new(Mutex::new(Vec::new()));
-            (
-                Self {
-                    root,
-                    workspaces,
-                    calls: Arc::clone(&calls),
-                },
-                calls,
-            )
-        }
+    #[test]
+    fn format_time_ago_just_now() {
+        let time = SystemTime::now() - std::time::Duration::from_secs(30);
+        assert_eq!(format_time_ago(Some(time)), "just now");
     }
 
-    impl vcs::VcsBackend for MockBackend {
-        fn root_from(&self, _dir: &Path) -> Result<PathBuf> {
-            Ok(self.root.clone())
-        }
-
-        fn workspace_list(&self, _repo_dir: &Path) -> Result<Vec<(String, vcs::WorkspaceInfo)>> {
-            Ok(self.workspaces.clone())
-        }
-
-        fn workspace_add(
-            &self,
-            repo_dir: &Path,
-            ws_path: &Path,
-            name: &str,
-            at: Option<&str>,
-        ) -> Result<()> {
-            self.calls.lock().unwrap().push(MockCall::WorkspaceAdd {
-                repo_dir: repo_dir.to_path_buf(),
-                ws_path: ws_path.to_path_buf(),
-                name: name.to_string(),
-                at: at.map(|s| s.to_string()),
-            });
-            // Create the directory so the workspace "exists" after add
-            fs::create_dir_all(ws_path)?;
-            Ok(())
-        }
-
-        fn workspace_remove(&self, repo_dir: &Path, name: &str, ws_path: &Path) -> Result<()> {
-            self.calls.lock().unwrap().push(MockCall::WorkspaceRemove {
-                repo_dir: repo_dir.to_path_buf(),
-                name: name.to_string(),
-                ws_path: ws_path.to_path_buf(),
-            });
-            Ok(())
-        }
-
-        fn workspace_rename(
-            &self,
-            _repo_dir: &Path,
-            old_path: &Path,
-            new_path: &Path,
-            old_name: &str,
-            new_name: &str,
-        ) -> Result<()> {
-            self.calls.lock().unwrap().push(MockCall::WorkspaceRename {
-                old_name: old_name.to_string(),
-                new_name: new_name.to_string(),
-            });
-            fs::rename(old_path, new_path)?;
-            Ok(())
-        }
-
-        fn diff_stat_vs_trunk(
-            &self,
-            _repo_dir: &Path,
-            _worktree_dir: &Path,
-            _ws_name: &str,
-        ) -> Result<vcs::DiffStat> {
-            Ok(vcs::DiffStat {
-                files_changed: 1,
-                insertions: 10,
-                deletions: 2,
-            })
-        }
-
-        fn latest_description(
-            &self,
-            _repo_dir: &Path,
-            _worktree_dir: &Path,
-            _ws_name: &str,
-        ) -> String {
-            "mock description".to_string()
-        }
-
-        fn is_merged_into_trunk(
-            &self,
-            _repo_dir: &Path,
-            _worktree_dir: &Path,
-            _ws_name: &str,
-        ) -> bool {
-            false
-        }
-
-        fn vcs_type(&self) -> vcs::VcsType {
-            vcs::VcsType::Jj
-        }
-
-        fn main_workspace_name(&self) -> &'static str {
-            "default"
-        }
+    #[test]
+    fn format_time_ago_minutes() {
+        let time = SystemTime::now() - std::time::Duration::from_secs(300);
+        assert_eq!(format_time_ago(Some(time)), "5m ago");
     }
 
-    // ── Helper to set up a dwm repo dir on disk ─────────────────────
-
-    /// Creates a dwm repo dir with `.main-repo` pointing at `main_repo`.
-    /// Returns the dwm_base path.
-    fn setup_dwm_dir(tmp: &Path, repo_name: &str, main_repo: &Path) -> PathBuf {
-        let dwm_base = tmp.join("dwm");
-        let rd = dwm_base.join(repo_name);
-        fs::create_dir_all(&rd).unwrap();
-        fs::write(rd.join(".main-repo"), main_repo.to_string_lossy().as_ref()).unwrap();
-        fs::write(rd.join(".vcs-type"), "mock").unwrap();
-        dwm_base
+    #[test]
+    fn format_time_ago_hours() {
+        let time = SystemTime::now() - std::time::Duration::from_secs(7200);
+        assert_eq!(format_time_ago(Some(time)), "2h ago");
     }
 
-    // ── list_workspace_entries_inner tests ────────────────────────────
-
     #[test]
-    fn list_entries_from_inside_dwm() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        // Create a workspace subdir
-        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let workspaces = vec![
-            (
-                "default".to_string(),
-                vcs::WorkspaceInfo {
-                    change_id: "aaa".to_string(),
-                    description: "main desc".to_string(),
-                    bookmarks: vec!["main".to_string()],
-                },
-            ),
-            (
-                "feat-x".to_string(),
-                vcs::WorkspaceInfo {
-                    change_id: "bbb".to_string(),
-                    description: "feature".to_string(),
-                    bookmarks: vec![],
-                },
-            ),
-        ];
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: ws_dir.clone(),
-            dwm_base,
-        };
-
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        // Should have main + feat-x
-        assert!(entries.len() >= 2);
+    fn format_time_ago_days() {
+        let time = SystemTime::now() - std::time::Duration::from_secs(86400 * 5);
+        assert_eq!(format_time_ago(Some(time)), "5d ago");
+    }
 
-        let main_entry = entries.iter().find(|e| e.is_main).unwrap();
-        assert_eq!(main_entry.name, "default");
-        assert_eq!(main_entry.change_id, "aaa");
-        assert_eq!(main_entry.description, "main desc");
-        assert_eq!(main_entry.path, main_repo);
+    #[test]
+    fn format_time_ago_months() {
+        let time = SystemTime::now() - std::time::Duration::from_secs(86400 * 60);
+        assert_eq!(format_time_ago(Some(time)), "2mo ago");
+    }
 
-        let feat_entry = entries.iter().find(|e| e.name == "feat-x").unwrap();
-        assert_eq!(feat_entry.change_id, "bbb");
-        assert_eq!(feat_entry.description, "feature");
-        assert!(!feat_entry.is_main);
+    fn plain_entry(name: &str, repo_name: Option<&str>, description: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            name: name.to_string(),
+            path: PathBuf::from(format!("/tmp/{}", name)),
+            description: description.to_string(),
+            repo_name: repo_name.map(str::to_string),
+            main_repo_path: PathBuf::from("/tmp/repo"),
+            ..Default::default()
+        }
     }
 
     #[test]
-    fn list_entries_skips_dot_prefixed_dirs() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        // Create a workspace and an internal dot-prefixed directory
-        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-        let agent_dir = dwm_base.join(format!("{}/.agent-status", dir_name));
-        fs::create_dir_all(&agent_dir).unwrap();
-
-        let workspaces = vec![
-            (
-                "default".to_string(),
-                vcs::WorkspaceInfo {
-                    change_id: "aaa".to_string(),
-                    description: "".to_string(),
-                    bookmarks: vec![],
-                },
-            ),
-            (
-                "feat-x".to_string(),
-                vcs::WorkspaceInfo {
-                    change_id: "bbb".to_string(),
-                    description: "".to_string(),
-                    bookmarks: vec![],
-                },
-            ),
+    fn print_status_numbers_rows() {
+        let entries = vec![
+            plain_entry("feat-a", None, ""),
+            plain_entry("feat-b", None, ""),
         ];
+        let plain = print_status_plain(&entries, None);
+        let rows: Vec<&str> = plain.lines().collect();
+        assert!(rows[0].starts_with("#  NAME"), "{}", plain);
+        assert!(rows[1].starts_with("1  feat-a"), "{}", plain);
+        assert!(rows[2].starts_with("2  feat-b"), "{}", plain);
+    }
 
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: ws_dir,
-            dwm_base,
-        };
-
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    #[test]
+    fn print_status_shows_backend_errors() {
+        let error = vcs::VcsError::new("workspace list", &anyhow::anyhow!("jj not found"));
+        let entries = vec![WorkspaceEntry {
+            error: Some(error),
+            ..plain_entry("feat-x", None, "")
+        }];
+        let output = print_status_to_string(&entries);
+        assert!(output.contains("error"), "{}", output);
+        assert!(!output.contains("clean"), "{}", output);
         assert!(
-            !names.contains(&".agent-status"),
-            "dot-prefixed dirs should be excluded, got: {:?}",
-            names
+            output.contains("feat-x: workspace list: jj not found"),
+            "{}",
+            output
         );
-        assert!(names.contains(&"feat-x"));
     }
 
     #[test]
-    fn list_entries_from_repo_dir() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let workspaces = vec![(
-            "default".to_string(),
-            vcs::WorkspaceInfo {
-                change_id: "abc".to_string(),
-                description: "".to_string(),
-                bookmarks: vec![],
-            },
-        )];
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), workspaces);
-        // cwd is the repo itself (outside dwm)
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
+    fn print_repos_marks_missing_main_repos() {
+        let repos = vec![TrackedRepo {
+            dir_name: "api-1234".to_string(),
+            label: "api".to_string(),
+            main_repo: PathBuf::from("/nonexistent/repos/api"),
+            vcs: "git".to_string(),
+            workspaces: 2,
+            size: 2048,
+        }];
+        owo_colors::set_override(false);
+        let mut buf = Vec::new();
+        print_repos_to(&repos, &mut buf).unwrap();
+        let table = String::from_utf8(buf).unwrap();
+        assert!(table.contains("(missing)"), "{}", table);
+        assert!(table.lines().nth(1).unwrap().starts_with("api   git"));
+    }
 
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        assert_eq!(entries.len(), 1);
-        assert!(entries[0].is_main);
-        // Empty description should fall through to latest_description
-        assert_eq!(entries[0].description, "mock description");
+    #[test]
+    fn plain_line_is_tab_separated() {
+        let entry = plain_entry("feat-x", None, "add the thing");
+        assert_eq!(plain_line(&entry), "feat-x\t/tmp/feat-x\tadd the thing");
     }
 
     #[test]
-    fn list_entries_empty_repo_dir() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        // Don't create dwm dir — repo_dir won't exist
-        let dwm_base = tmp.path().join("dwm");
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
+    fn plain_line_qualifies_name_with_repo() {
+        let entry = plain_entry("feat-x", Some("myrepo"), "");
+        assert_eq!(plain_line(&entry), "myrepo/feat-x\t/tmp/feat-x\t");
+    }
 
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        assert!(entries.is_empty());
+    #[test]
+    fn plain_line_keeps_description_on_one_line() {
+        let entry = plain_entry("feat-x", None, "first\tline\n\nbody");
+        assert_eq!(plain_line(&entry), "feat-x\t/tmp/feat-x\tfirst line");
     }
 
-    // ── new_workspace_inner tests ────────────────────────────────────
+    // ── print_status tests ──────────────────────────────────────────
 
     #[test]
-    fn new_workspace_calls_add() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dwm_base = tmp.path().join("dwm");
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        new_workspace_inner(&deps, Some("my-ws".to_string()), None, None).unwrap();
-
-        let calls = calls.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        match &calls[0] {
-            MockCall::WorkspaceAdd {
-                repo_dir,
-                ws_path,
-                name,
-                at,
-            } => {
-                assert_eq!(repo_dir, &main_repo);
-                assert_eq!(ws_path, &dwm_base.join(format!("{}/my-ws", dir_name)));
-                assert_eq!(name, "my-ws");
-                assert!(at.is_none());
-            }
-            other => panic!("expected WorkspaceAdd, got {:?}", other),
-        }
+    fn print_status_does_not_panic() {
+        let entries = vec![
+            WorkspaceEntry {
+                name: "default".to_string(),
+                path: PathBuf::from("/tmp/repo"),
+                last_modified: Some(SystemTime::now()),
+                diff_stat: vcs::DiffStat {
+                    files_changed: 1,
+                    insertions: 10,
+                    deletions: 2,
+                    ..Default::default()
+                },
+                is_main: true,
+                change_id: "abc12345".to_string(),
+                description: "main workspace".to_string(),
+                bookmarks: vec!["main".to_string()],
+                main_repo_path: PathBuf::from("/tmp/repo"),
+                ..Default::default()
+            },
+            WorkspaceEntry {
+                name: "feat-x".to_string(),
+                path: PathBuf::from("/tmp/feat-x"),
+                change_id: "def67890".to_string(),
+                description: "feature work".to_string(),
+                main_repo_path: PathBuf::from("/tmp/repo"),
+                ..Default::default()
+            },
+        ];
+        // Should not panic; output goes to stderr
+        print_status(&entries);
     }
 
     #[test]
-    fn new_workspace_auto_names() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dwm_base = tmp.path().join("dwm");
-
-        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        new_workspace_inner(&deps, None, None, None).unwrap();
-
-        let calls = calls.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        match &calls[0] {
-            MockCall::WorkspaceAdd { name, .. } => {
-                // Auto-generated name should be non-empty and contain a hyphen (adjective-noun)
-                assert!(!name.is_empty());
-                assert!(
-                    name.contains('-'),
-                    "auto name should be adjective-noun: {}",
-                    name
-                );
-            }
-            other => panic!("expected WorkspaceAdd, got {:?}", other),
+    fn print_status_aligns_wide_characters() {
+        let mut entries = vec![
+            plain_entry("修复", None, "修复登录超时问题 🚀"),
+            plain_entry("feat-b", None, "fix login"),
+        ];
+        for entry in &mut entries {
+            entry.bookmarks = vec!["bm".to_string()];
         }
+        let plain = print_status_plain(&entries, None);
+        let rows: Vec<&str> = plain.lines().skip(1).collect();
+        let bookmark_at = |row: &str| width::of(&row[..row.find("bm").unwrap()]);
+        assert_eq!(bookmark_at(rows[0]), bookmark_at(rows[1]), "{}", plain);
     }
 
     #[test]
-    fn new_workspace_duplicate_errors() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dwm_base = tmp.path().join("dwm");
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace once
-        new_workspace_inner(&deps, Some("dup-ws".to_string()), None, None).unwrap();
+    fn print_status_fits_description_to_terminal() {
+        let long = "make the workspace picker stay responsive with thousands of branches";
+        let entries = vec![plain_entry("feat-a", None, long)];
+
+        let narrow = print_status_plain(&entries, Some(80));
+        assert!(narrow.contains("make the workspace"), "{}", narrow);
+        assert!(narrow.contains('…'), "{}", narrow);
+        assert!(narrow.lines().all(|row| width::of(row) <= 80), "{}", narrow);
+
+        let wide = print_status_plain(&entries, Some(200));
+        assert!(wide.contains(long), "{}", wide);
+        // No wider than the longest description needs.
+        let mut lines = wide.lines();
+        let header = lines.next().unwrap();
+        let row = lines.next().unwrap();
+        assert_eq!(
+            header.find("BOOKMARKS").unwrap(),
+            row.find(long).unwrap() + long.len() + 2,
+            "{}",
+            wide
+        );
 
-        // Second attempt should fail
-        let err = new_workspace_inner(&deps, Some("dup-ws".to_string()), None, None).unwrap_err();
-        assert!(err.to_string().contains("already exists"), "error: {}", err);
+        // Too narrow for everything: the description keeps a minimum.
+        let tiny = print_status_plain(&entries, Some(30));
+        let desc = tiny.lines().nth(1).unwrap();
+        assert!(desc.contains("make the workspace …"), "{}", tiny);
     }
 
     #[test]
-    fn new_workspace_dot_prefix_rejected() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base: tmp.path().join("dwm"),
-        };
-
-        let err =
-            new_workspace_inner(&deps, Some(".agent-status".to_string()), None, None).unwrap_err();
-        assert!(
-            err.to_string().contains("cannot start with '.'"),
-            "error: {}",
-            err
-        );
+    fn print_status_marks_dirty_workspaces() {
+        let mut dirty = plain_entry("feat-x", None, "feature");
+        dirty.working_copy.untracked = true;
+        let clean = plain_entry("feat-y", None, "other");
+        let out = print_status_to_string(&[dirty, clean]);
+        assert!(out.contains("feat-x*"), "{}", out);
+        assert!(!out.contains("feat-y*"), "{}", out);
     }
 
     #[test]
-    fn new_workspace_from_existing() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dwm_base = tmp.path().join("dwm");
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let workspaces = vec![(
-            "source-ws".to_string(),
-            vcs::WorkspaceInfo {
-                change_id: "abc12345".to_string(),
-                description: "some work".to_string(),
-                bookmarks: vec![],
-            },
-        )];
-
-        let (mock, calls) = MockBackend::new(main_repo.clone(), workspaces);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        new_workspace_inner(&deps, Some("forked".to_string()), None, Some("source-ws")).unwrap();
-
-        let calls = calls.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        match &calls[0] {
-            MockCall::WorkspaceAdd {
-                ws_path, name, at, ..
-            } => {
-                assert_eq!(ws_path, &dwm_base.join(format!("{}/forked", dir_name)));
-                assert_eq!(name, "forked");
-                assert_eq!(at.as_deref(), Some("abc12345"));
-            }
-            other => panic!("expected WorkspaceAdd, got {:?}", other),
-        }
+    fn print_status_adds_pr_column_only_with_prs() {
+        let out = print_status_to_string(&[plain_entry("feat-x", None, "feature")]);
+        assert!(!out.contains("PR"), "{}", out);
+
+        let mut with_pr = plain_entry("feat-x", None, "feature");
+        with_pr.pr = Some(forge::PullRequest {
+            number: 42,
+            url: String::new(),
+            draft: false,
+            review: Some(forge::ReviewDecision::Approved),
+            checks: Some(forge::Checks::Passing),
+        });
+        let out = print_status_to_string(&[with_pr, plain_entry("feat-y", None, "other")]);
+        let header = out.lines().next().unwrap();
+        assert!(header.contains("MODIFIED   PR "), "{}", out);
+        assert!(out.contains("#42 ✓ approved"), "{}", out);
+        assert!(!out.contains("CI"), "{}", out);
     }
 
     #[test]
-    fn new_workspace_from_nonexistent() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dwm_base = tmp.path().join("dwm");
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = new_workspace_inner(&deps, Some("forked".to_string()), None, Some("no-such-ws"))
-            .unwrap_err();
-        assert!(
-            err.to_string().contains("not found"),
-            "error should mention not found: {}",
-            err
-        );
+    fn print_status_adds_ci_column_only_with_ci() {
+        let mut failing = plain_entry("feat-x", None, "feature");
+        failing.ci = Some(forge::CiRun {
+            checks: forge::Checks::Failing,
+            url: String::new(),
+        });
+        let out = print_status_to_string(&[failing, plain_entry("feat-y", None, "other")]);
+        let header = out.lines().next().unwrap();
+        assert!(header.contains("MODIFIED   CI "), "{}", out);
+        assert!(out.contains("✗ failed"), "{}", out);
     }
 
-    // ── delete_workspace_inner tests ─────────────────────────────────
-
     #[test]
-    fn delete_workspace_by_name() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        // Create the workspace dir to be deleted
-        let ws_dir = dwm_base.join(format!("{}/my-ws", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
-        // cwd is outside the workspace being deleted
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        let redirect =
-            delete_workspace_inner(&deps, Some("my-ws".to_string()), DeleteOutput::Verbose)
-                .unwrap();
-        assert!(
-            redirect.is_none(),
-            "should not redirect when cwd is outside workspace"
-        );
-
-        let calls = calls.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        match &calls[0] {
-            MockCall::WorkspaceRemove {
-                repo_dir,
-                name,
-                ws_path,
-            } => {
-                assert_eq!(repo_dir, &main_repo);
-                assert_eq!(name, "my-ws");
-                assert_eq!(ws_path, &ws_dir);
-            }
-            other => panic!("expected WorkspaceRemove, got {:?}", other),
-        }
-
-        // Dir should be removed
-        assert!(!ws_dir.exists());
-    }
-
-    #[test]
-    fn delete_workspace_redirects_when_inside() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let ws_dir = dwm_base.join(format!("{}/my-ws", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        // cwd is inside the workspace being deleted
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: ws_dir.join("src"),
-            dwm_base,
-        };
-
-        let redirect =
-            delete_workspace_inner(&deps, Some("my-ws".to_string()), DeleteOutput::Verbose)
-                .unwrap();
-        let redirect = redirect.expect("should redirect when cwd is inside workspace");
-        assert_eq!(redirect, main_repo);
-    }
-
-    #[test]
-    fn delete_workspace_infers_from_cwd() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let ws_dir = dwm_base.join(format!("{}/inferred-ws", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: ws_dir.clone(),
-            dwm_base,
-        };
-
-        // No name given — should infer repo=myrepo, ws=inferred-ws from cwd
-        let _redirected = delete_workspace_inner(&deps, None, DeleteOutput::Verbose).unwrap();
-
-        let calls = calls.lock().unwrap();
-        match &calls[0] {
-            MockCall::WorkspaceRemove { name, .. } => {
-                assert_eq!(name, "inferred-ws");
-            }
-            other => panic!("expected WorkspaceRemove, got {:?}", other),
-        }
-    }
-
-    #[test]
-    fn delete_workspace_not_found_errors() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = delete_workspace_inner(
-            &deps,
-            Some("nonexistent".to_string()),
-            DeleteOutput::Verbose,
-        )
-        .unwrap_err();
-        assert!(err.to_string().contains("not found"), "error: {}", err);
-    }
-
-    // ── rename_workspace_inner tests ──────────────────────────────
-
-    #[test]
-    fn rename_workspace_success() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let (mock, calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        let redirect = rename_workspace_inner(&deps, "old-name", "new-name").unwrap();
-        assert!(
-            redirect.is_none(),
-            "should not redirect when cwd is outside workspace"
-        );
-
-        // Old dir gone, new dir exists
-        assert!(!ws_dir.exists());
-        assert!(dwm_base.join(format!("{}/new-name", dir_name)).exists());
-
-        let calls = calls.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        match &calls[0] {
-            MockCall::WorkspaceRename {
-                old_name, new_name, ..
-            } => {
-                assert_eq!(old_name, "old-name");
-                assert_eq!(new_name, "new-name");
-            }
-            other => panic!("expected WorkspaceRename, got {:?}", other),
-        }
-    }
-
-    #[test]
-    fn rename_workspace_redirects_when_inside() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
-        fs::create_dir_all(ws_dir.join("src")).unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo, vec![]);
-        // cwd is inside the workspace being renamed
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: ws_dir.join("src"),
-            dwm_base: dwm_base.clone(),
-        };
-
-        let redirect = rename_workspace_inner(&deps, "old-name", "new-name").unwrap();
-        let redirect = redirect.expect("should redirect when cwd is inside workspace");
-        // cwd was old-name/src, so redirect should be new-name/src
-        assert_eq!(
-            redirect,
-            dwm_base.join(format!("{}/new-name/src", dir_name))
-        );
-    }
-
-    #[test]
-    fn rename_workspace_preserves_files() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
-        fs::create_dir_all(ws_dir.join("src")).unwrap();
-        fs::write(ws_dir.join("src/main.rs"), "fn main() {}").unwrap();
-        fs::write(ws_dir.join("README.md"), "# hello").unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base: dwm_base.clone(),
-        };
-
-        rename_workspace_inner(&deps, "old-name", "new-name").unwrap();
-
-        let new_dir = dwm_base.join(format!("{}/new-name", dir_name));
-        assert!(new_dir.join("src/main.rs").exists());
-        assert_eq!(
-            fs::read_to_string(new_dir.join("src/main.rs")).unwrap(),
-            "fn main() {}"
-        );
-        assert_eq!(
-            fs::read_to_string(new_dir.join("README.md")).unwrap(),
-            "# hello"
-        );
-    }
-
-    #[test]
-    fn rename_workspace_old_not_found() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = rename_workspace_inner(&deps, "nonexistent", "new-name").unwrap_err();
-        assert!(err.to_string().contains("not found"), "error: {}", err);
-    }
-
-    #[test]
-    fn rename_workspace_new_already_exists() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        fs::create_dir_all(dwm_base.join(format!("{}/old-name", dir_name))).unwrap();
-        fs::create_dir_all(dwm_base.join(format!("{}/new-name", dir_name))).unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = rename_workspace_inner(&deps, "old-name", "new-name").unwrap_err();
-        assert!(err.to_string().contains("already exists"), "error: {}", err);
-    }
-
-    #[test]
-    fn rename_workspace_refuses_main() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = rename_workspace_inner(&deps, "default", "new-name").unwrap_err();
-        assert!(err.to_string().contains("cannot rename"), "error: {}", err);
-    }
-
-    #[test]
-    fn rename_workspace_dot_prefix_rejected() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        fs::create_dir_all(dwm_base.join(format!("{}/old-name", dir_name))).unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = rename_workspace_inner(&deps, "old-name", ".hidden").unwrap_err();
-        assert!(
-            err.to_string().contains("cannot start with '.'"),
-            "error: {}",
-            err
-        );
-    }
-
-    // ── switch_workspace_inner tests ──────────────────────────────
-
-    #[test]
-    fn switch_workspace_by_name() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        // Create a workspace dir
-        let ws_dir = dwm_base.join(format!("{}/feat-x", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let path = switch_workspace_inner(&deps, "feat-x").unwrap();
-        assert_eq!(path, ws_dir);
-    }
-
-    #[test]
-    fn switch_workspace_to_main() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-
-        // "default" is the mock's main_workspace_name
-        let path = switch_workspace_inner(&deps, "default").unwrap();
-        assert_eq!(path, main_repo);
-    }
-
-    #[test]
-    fn switch_workspace_not_found() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = switch_workspace_inner(&deps, "nonexistent").unwrap_err();
-        assert!(err.to_string().contains("not found"), "error: {}", err);
-    }
-
-    // ── rename with cwd inference tests ─────────────────────────────
-
-    #[test]
-    fn rename_infers_from_cwd() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let ws_dir = dwm_base.join(format!("{}/old-name", dir_name));
-        fs::create_dir_all(&ws_dir).unwrap();
-
-        let (mock, calls) = MockBackend::new(main_repo, vec![]);
-        // cwd is inside the workspace
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: ws_dir.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Infer old name from cwd
-        let old = infer_workspace_name_from_cwd(&deps).unwrap();
-        assert_eq!(old, "old-name");
-
-        // Now do the rename
-        let redirect = rename_workspace_inner(&deps, &old, "new-name").unwrap();
-        let redirect = redirect.expect("should redirect when cwd is inside workspace");
-        assert_eq!(redirect, dwm_base.join(format!("{}/new-name", dir_name)));
-
-        let calls = calls.lock().unwrap();
-        assert_eq!(calls.len(), 1);
-        match &calls[0] {
-            MockCall::WorkspaceRename { old_name, new_name } => {
-                assert_eq!(old_name, "old-name");
-                assert_eq!(new_name, "new-name");
-            }
-            other => panic!("expected WorkspaceRename, got {:?}", other),
-        }
-    }
-
-    #[test]
-    fn rename_refuses_outside_workspace() {
-        let tmp = tempfile::tempdir().unwrap();
-        let main_repo = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&main_repo).unwrap();
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir(tmp.path(), &dir_name, &main_repo);
-
-        let (mock, _calls) = MockBackend::new(main_repo.clone(), vec![]);
-        // cwd is outside dwm
-        let deps = WorkspaceDeps {
-            backend: Box::new(mock),
-            cwd: main_repo,
-            dwm_base,
-        };
-
-        let err = infer_workspace_name_from_cwd(&deps).unwrap_err();
-        assert!(
-            err.to_string().contains("not inside a dwm workspace"),
-            "error: {}",
-            err
-        );
-    }
-
-    // ── regression: same basename, different paths get distinct dwm dirs ──
-
-    #[test]
-    fn same_basename_different_paths_get_distinct_dir_names() {
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_a = tmp.path().join("team-a/myrepo");
-        let repo_b = tmp.path().join("team-b/myrepo");
-        let dir_a = vcs::repo_dir_name(&repo_a);
-        let dir_b = vcs::repo_dir_name(&repo_b);
-        assert_ne!(
-            dir_a, dir_b,
-            "two repos with same basename but different paths must not collide"
-        );
-        assert!(dir_a.starts_with("myrepo-"), "dir_a: {}", dir_a);
-        assert!(dir_b.starts_with("myrepo-"), "dir_b: {}", dir_b);
-    }
-
-    // ── list_all_workspace_entries_inner tests ─────────────────────
-
-    #[test]
-    fn list_all_entries_multiple_repos() {
-        let tmp = tempfile::tempdir().unwrap();
-        let dwm_base = tmp.path().join("dwm");
-
-        // Set up two repos
-        let repo1 = tmp.path().join("repos/repo1");
-        let repo2 = tmp.path().join("repos/repo2");
-        fs::create_dir_all(&repo1).unwrap();
-        fs::create_dir_all(&repo2).unwrap();
-
-        // Create dwm dirs with .main-repo and .vcs-type
-        let rd1 = dwm_base.join("repo1");
-        fs::create_dir_all(&rd1).unwrap();
-        fs::write(rd1.join(".main-repo"), repo1.to_string_lossy().as_ref()).unwrap();
-        fs::write(rd1.join(".vcs-type"), "mock").unwrap();
-
-        let rd2 = dwm_base.join("repo2");
-        fs::create_dir_all(&rd2).unwrap();
-        fs::write(rd2.join(".main-repo"), repo2.to_string_lossy().as_ref()).unwrap();
-        fs::write(rd2.join(".vcs-type"), "mock").unwrap();
-
-        // list_all_workspace_entries_inner won't work with MockBackend since it
-        // uses detect_from_dwm_dir internally. The detect_from_dwm_dir will
-        // try to instantiate JjBackend or GitBackend. So we test the scanning
-        // logic by checking it doesn't panic on dirs without .main-repo.
-        let rd3 = dwm_base.join("not-a-repo");
-        fs::create_dir_all(&rd3).unwrap();
-        // No .main-repo — should be skipped
-
-        // We can't fully test this without real VCS backends, but we verify
-        // the function doesn't panic and correctly skips dirs without .main-repo
-        // We need to accept that entries for mock VCS type will fail at workspace_list
-        let result = list_all_workspace_entries_inner(&dwm_base);
-        // Should not panic; may return Ok or Err depending on mock backend availability
-        assert!(result.is_ok() || result.is_err());
-    }
-
-    #[test]
-    fn list_all_entries_empty_dwm() {
-        let tmp = tempfile::tempdir().unwrap();
-        let dwm_base = tmp.path().join("dwm");
-        // Don't even create it
-        let entries = list_all_workspace_entries_inner(&dwm_base).unwrap();
-        assert!(entries.is_empty());
-    }
-
-    #[test]
-    fn list_all_entries_no_repos() {
-        let tmp = tempfile::tempdir().unwrap();
-        let dwm_base = tmp.path().join("dwm");
-        fs::create_dir_all(&dwm_base).unwrap();
-        // Create a file (not a dir)
-        fs::write(dwm_base.join("some-file"), "").unwrap();
-        let entries = list_all_workspace_entries_inner(&dwm_base).unwrap();
-        assert!(entries.is_empty());
-    }
-
-    // ── compute_is_stale tests ────────────────────────────────────
-
-    #[test]
-    fn stale_merged_workspace_is_stale() {
-        assert!(compute_is_stale(
-            MergeStatus::Merged,
-            Some(SystemTime::now())
-        ));
-    }
-
-    #[test]
-    fn stale_merged_workspace_without_time_is_stale() {
-        assert!(compute_is_stale(MergeStatus::Merged, None));
-    }
-
-    #[test]
-    fn stale_old_workspace_is_stale() {
-        let old_time = SystemTime::now() - std::time::Duration::from_secs(86400 * 31);
-        assert!(compute_is_stale(MergeStatus::Unmerged, Some(old_time)));
-    }
-
-    #[test]
-    fn stale_recent_workspace_is_not_stale() {
-        let recent = SystemTime::now() - std::time::Duration::from_secs(86400 * 5);
-        assert!(!compute_is_stale(MergeStatus::Unmerged, Some(recent)));
-    }
-
-    #[test]
-    fn stale_unknown_time_not_merged_is_not_stale() {
-        assert!(!compute_is_stale(MergeStatus::Unmerged, None));
-    }
-
-    // ── format_time_ago tests ───────────────────────────────────────
-
-    #[test]
-    fn format_time_ago_none_returns_unknown() {
-        assert_eq!(format_time_ago(None), "unknown");
-    }
-
-    #[test]
-    fn format_time_ago_just_now() {
-        let time = SystemTime::now() - std::time::Duration::from_secs(30);
-        assert_eq!(format_time_ago(Some(time)), "just now");
-    }
-
-    #[test]
-    fn format_time_ago_minutes() {
-        let time = SystemTime::now() - std::time::Duration::from_secs(300);
-        assert_eq!(format_time_ago(Some(time)), "5m ago");
-    }
-
-    #[test]
-    fn format_time_ago_hours() {
-        let time = SystemTime::now() - std::time::Duration::from_secs(7200);
-        assert_eq!(format_time_ago(Some(time)), "2h ago");
-    }
-
-    #[test]
-    fn format_time_ago_days() {
-        let time = SystemTime::now() - std::time::Duration::from_secs(86400 * 5);
-        assert_eq!(format_time_ago(Some(time)), "5d ago");
-    }
-
-    #[test]
-    fn format_time_ago_months() {
-        let time = SystemTime::now() - std::time::Duration::from_secs(86400 * 60);
-        assert_eq!(format_time_ago(Some(time)), "2mo ago");
-    }
-
-    // ── print_status tests ──────────────────────────────────────────
-
-    #[test]
-    fn print_status_does_not_panic() {
-        let entries = vec![
-            WorkspaceEntry {
-                name: "default".to_string(),
-                path: PathBuf::from("/tmp/repo"),
-                last_modified: Some(SystemTime::now()),
-                diff_stat: vcs::DiffStat {
-                    files_changed: 1,
-                    insertions: 10,
-                    deletions: 2,
-                },
-                is_main: true,
-                change_id: "abc12345".to_string(),
-                description: "main workspace".to_string(),
-                bookmarks: vec!["main".to_string()],
-                is_stale: false,
-                repo_name: None,
-                main_repo_path: PathBuf::from("/tmp/repo"),
-                vcs_type: vcs::VcsType::Jj,
-                agent_status: None,
-            },
-            WorkspaceEntry {
-                name: "feat-x".to_string(),
-                path: PathBuf::from("/tmp/feat-x"),
-                last_modified: None,
-                diff_stat: vcs::DiffStat::default(),
-                is_main: false,
-                change_id: "def67890".to_string(),
-                description: "feature work".to_string(),
-                bookmarks: vec![],
-                is_stale: false,
-                repo_name: None,
-                main_repo_path: PathBuf::from("/tmp/repo"),
-                vcs_type: vcs::VcsType::Jj,
-                agent_status: None,
-            },
-        ];
-        // Should not panic; output goes to stderr
-        print_status(&entries);
-    }
+    fn print_status_shows_commits_ahead_and_behind() {
+        let mut long_runner = plain_entry("feat-x", None, "feature");
+        long_runner.diff_stat = vcs::DiffStat {
+            files_changed: 1,
+            insertions: 3,
+            ahead: 2,
+            behind: 40,
+            ..Default::default()
+        };
+        let mut behind_only = plain_entry("feat-y", None, "other");
+        behind_only.diff_stat.behind = 5;
+        let out = print_status_to_string(&[long_runner, behind_only]);
+        assert!(out.contains("+3 ↑2 ↓40"), "{}", out);
+        assert!(out.contains("clean ↓5"), "{}", out);
+    }
 
     #[test]
     fn status_table_snapshot() {
@@ -2159,16 +1650,14 @@ mod tests {
                     files_changed: 1,
                     insertions: 10,
                     deletions: 2,
+                    ..Default::default()
                 },
                 is_main: true,
                 change_id: "abc12345".to_string(),
                 description: "refactor help system".to_string(),
                 bookmarks: vec!["main".to_string()],
-                is_stale: false,
-                repo_name: None,
                 main_repo_path: PathBuf::from("/tmp/repo"),
-                vcs_type: vcs::VcsType::Jj,
-                agent_status: None,
+                ..Default::default()
             },
             WorkspaceEntry {
                 name: "hazy-quail".to_string(),
@@ -2178,20 +1667,18 @@ mod tests {
                     files_changed: 5,
                     insertions: 100,
                     deletions: 50,
+                    ..Default::default()
                 },
-                is_main: false,
                 change_id: "tqqorvwl".to_string(),
                 description: "Live-updating list view".to_string(),
-                bookmarks: vec![],
-                is_stale: false,
-                repo_name: None,
                 main_repo_path: PathBuf::from("/tmp/repo"),
-                vcs_type: vcs::VcsType::Jj,
                 agent_status: Some(crate::agent::AgentSummary {
                     waiting: 1,
                     working: 0,
                     idle: 0,
+                    ..Default::default()
                 }),
+                ..Default::default()
             },
         ];
 
@@ -2216,850 +1703,4 @@ mod tests {
         // Verify ANSI codes are present (cyan for names)
         assert!(out.contains("\x1b[36m"));
     }
-
-    // ── E2E tests with real git repos ───────────────────────────────
-
-    fn git_available() -> bool {
-        std::process::Command::new("git")
-            .arg("--version")
-            .output()
-            .is_ok()
-    }
-
-    /// Initialize a git repo with an initial commit.
-    /// Returns the canonicalized repo path.
-    fn init_git_repo(dir: &Path) -> PathBuf {
-        let dir_str = dir.to_str().unwrap();
-        std::process::Command::new("git")
-            .args(["init", "-b", "main", dir_str])
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args([
-                "-C",
-                dir_str,
-                "commit",
-                "--allow-empty",
-                "-m",
-                "initial commit",
-            ])
-            .output()
-            .unwrap();
-        dir.canonicalize().unwrap()
-    }
-
-    /// Set up a dwm directory for a real git repo.
-    fn setup_dwm_dir_git(tmp: &Path, repo_name: &str, main_repo: &Path) -> PathBuf {
-        let dwm_base = tmp.join("dwm");
-        let rd = dwm_base.join(repo_name);
-        fs::create_dir_all(&rd).unwrap();
-        fs::write(rd.join(".main-repo"), main_repo.to_string_lossy().as_ref()).unwrap();
-        fs::write(rd.join(".vcs-type"), "git").unwrap();
-        dwm_base
-    }
-
-    #[test]
-    fn e2e_git_list_entries_main_only() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_git(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        assert_eq!(entries.len(), 1, "should have main worktree entry");
-        assert!(entries[0].is_main);
-        assert_eq!(entries[0].name, "main-worktree");
-        assert_eq!(entries[0].path, main_repo);
-        assert_eq!(entries[0].description, "initial commit");
-    }
-
-    #[test]
-    fn e2e_git_list_entries_with_worktree() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_git(tmp.path(), &dir_name, &main_repo);
-
-        // Create a git worktree in the dwm directory
-        let ws_path = dwm_base.join(format!("{}/feat-branch", dir_name));
-        std::process::Command::new("git")
-            .args([
-                "-C",
-                main_repo.to_str().unwrap(),
-                "worktree",
-                "add",
-                ws_path.to_str().unwrap(),
-                "-b",
-                "feat-branch",
-            ])
-            .output()
-            .unwrap();
-
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        assert!(
-            entries.len() >= 2,
-            "should have main + worktree, got {}",
-            entries.len()
-        );
-
-        let main_entry = entries.iter().find(|e| e.is_main).unwrap();
-        assert_eq!(main_entry.name, "main-worktree");
-        assert_eq!(main_entry.path, main_repo);
-
-        let feat_entry = entries.iter().find(|e| e.name == "feat-branch").unwrap();
-        assert!(!feat_entry.is_main);
-        assert!(feat_entry.path.ends_with("feat-branch"));
-        assert!(feat_entry.bookmarks.contains(&"feat-branch".to_string()));
-    }
-
-    #[test]
-    fn e2e_git_new_and_delete_workspace() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let dwm_base = tmp.path().join("dwm");
-
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create a workspace
-        new_workspace_inner(&deps, Some("test-ws".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/test-ws", dir_name));
-        assert!(ws_dir.exists(), "workspace dir should exist after creation");
-
-        // List and verify it shows up
-        let backend2 = crate::git::GitBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        let entries = list_workspace_entries_inner(&deps2).unwrap();
-        assert!(
-            entries.iter().any(|e| e.name == "test-ws"),
-            "test-ws should appear in listing"
-        );
-
-        // Delete the workspace
-        let backend3 = crate::git::GitBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        delete_workspace_inner(&deps3, Some("test-ws".to_string()), DeleteOutput::Verbose).unwrap();
-        assert!(
-            !ws_dir.exists(),
-            "workspace dir should be removed after deletion"
-        );
-
-        // Verify it's gone from listing
-        let backend4 = crate::git::GitBackend;
-        let deps4 = WorkspaceDeps {
-            backend: Box::new(backend4),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps4).unwrap();
-        assert!(
-            !entries.iter().any(|e| e.name == "test-ws"),
-            "test-ws should not appear after deletion"
-        );
-    }
-
-    #[test]
-    fn e2e_git_worktree_with_changes() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let dwm_base = tmp.path().join("dwm");
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace and make a commit in it
-        new_workspace_inner(&deps, Some("feature".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/feature", dir_name));
-
-        // Add a file and commit in the worktree
-        fs::write(ws_dir.join("hello.txt"), "hello world\n").unwrap();
-        let ws_str = ws_dir.to_str().unwrap();
-        std::process::Command::new("git")
-            .args(["-C", ws_str, "add", "hello.txt"])
-            .output()
-            .unwrap();
-        std::process::Command::new("git")
-            .args(["-C", ws_str, "commit", "-m", "add hello"])
-            .output()
-            .unwrap();
-
-        // List and check that the feature workspace has diff stats
-        let backend2 = crate::git::GitBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps2).unwrap();
-        let feat = entries.iter().find(|e| e.name == "feature").unwrap();
-        assert_eq!(feat.description, "add hello");
-        assert!(
-            feat.diff_stat.insertions > 0 || feat.diff_stat.files_changed > 0,
-            "feature workspace should show changes vs trunk"
-        );
-    }
-
-    #[test]
-    fn e2e_git_rename_workspace() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let dwm_base = tmp.path().join("dwm");
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace
-        new_workspace_inner(&deps, Some("old-name".to_string()), None, None).unwrap();
-        let old_path = dwm_base.join(format!("{}/old-name", dir_name));
-        assert!(old_path.exists());
-
-        // Rename it
-        let backend2 = crate::git::GitBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        rename_workspace_inner(&deps2, "old-name", "new-name").unwrap();
-
-        assert!(!old_path.exists(), "old dir should be gone");
-        assert!(
-            dwm_base.join(format!("{}/new-name", dir_name)).exists(),
-            "new dir should exist"
-        );
-
-        // Verify listing shows the new name
-        let backend3 = crate::git::GitBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps3).unwrap();
-        assert!(entries.iter().any(|e| e.name == "new-name"));
-        assert!(!entries.iter().any(|e| e.name == "old-name"));
-    }
-
-    #[test]
-    fn e2e_git_rename_redirects_when_inside() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let dwm_base = tmp.path().join("dwm");
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace with a subdirectory
-        new_workspace_inner(&deps, Some("my-ws".to_string()), None, None).unwrap();
-        let ws_path = dwm_base.join(format!("{}/my-ws", dir_name));
-        let subdir = ws_path.join("src");
-        fs::create_dir_all(&subdir).unwrap();
-
-        // Rename while cwd is inside the workspace
-        let backend2 = crate::git::GitBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: subdir,
-            dwm_base: dwm_base.clone(),
-        };
-        let redirect = rename_workspace_inner(&deps2, "my-ws", "renamed-ws").unwrap();
-        let redirect = redirect.expect("should redirect when cwd is inside renamed workspace");
-        assert_eq!(
-            redirect,
-            dwm_base.join(format!("{}/renamed-ws/src", dir_name))
-        );
-
-        // The new path should exist and contain the subdirectory
-        let new_ws = dwm_base.join(format!("{}/renamed-ws", dir_name));
-        assert!(new_ws.exists());
-        assert!(new_ws.join("src").exists());
-    }
-
-    fn jj_available() -> bool {
-        std::process::Command::new("jj")
-            .arg("--version")
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
-    }
-
-    fn init_jj_repo(dir: &Path) -> PathBuf {
-        let dir_str = dir.to_str().unwrap();
-        std::process::Command::new("jj")
-            .args(["git", "init", dir_str])
-            .output()
-            .unwrap();
-        // Create a "main" bookmark so trunk() resolves
-        std::process::Command::new("jj")
-            .args([
-                "--repository",
-                dir_str,
-                "bookmark",
-                "create",
-                "main",
-                "-r",
-                "@-",
-            ])
-            .output()
-            .unwrap();
-        dir.canonicalize().unwrap()
-    }
-
-    fn setup_dwm_dir_jj(tmp: &Path, repo_name: &str, main_repo: &Path) -> PathBuf {
-        let dwm_base = tmp.join("dwm");
-        let rd = dwm_base.join(repo_name);
-        fs::create_dir_all(&rd).unwrap();
-        fs::write(rd.join(".main-repo"), main_repo.to_string_lossy().as_ref()).unwrap();
-        fs::write(rd.join(".vcs-type"), "jj").unwrap();
-        dwm_base
-    }
-
-    #[test]
-    fn e2e_jj_list_entries_main_only() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        assert_eq!(entries.len(), 1, "should have default workspace entry");
-        assert!(entries[0].is_main);
-        assert_eq!(entries[0].name, "default");
-        assert_eq!(entries[0].path, main_repo);
-    }
-
-    #[test]
-    fn e2e_jj_list_entries_with_workspace() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        // Create a jj workspace in the dwm directory
-        let ws_path = dwm_base.join(format!("{}/feat-ws", dir_name));
-        std::process::Command::new("jj")
-            .args([
-                "--repository",
-                main_repo.to_str().unwrap(),
-                "workspace",
-                "add",
-                "--name",
-                "feat-ws",
-                ws_path.to_str().unwrap(),
-            ])
-            .output()
-            .unwrap();
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-
-        let entries = list_workspace_entries_inner(&deps).unwrap();
-        assert!(
-            entries.len() >= 2,
-            "should have default + workspace, got {}",
-            entries.len()
-        );
-
-        let main_entry = entries.iter().find(|e| e.is_main).unwrap();
-        assert_eq!(main_entry.name, "default");
-        assert_eq!(main_entry.path, main_repo);
-
-        let feat_entry = entries.iter().find(|e| e.name == "feat-ws").unwrap();
-        assert!(!feat_entry.is_main);
-        assert!(feat_entry.path.ends_with("feat-ws"));
-    }
-
-    #[test]
-    fn e2e_jj_new_and_delete_workspace() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create a workspace
-        new_workspace_inner(&deps, Some("test-ws".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/test-ws", dir_name));
-        assert!(ws_dir.exists(), "workspace dir should exist after creation");
-
-        // List and verify it shows up
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        let entries = list_workspace_entries_inner(&deps2).unwrap();
-        assert!(
-            entries.iter().any(|e| e.name == "test-ws"),
-            "test-ws should appear in listing"
-        );
-
-        // Delete the workspace
-        let backend3 = crate::jj::JjBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        delete_workspace_inner(&deps3, Some("test-ws".to_string()), DeleteOutput::Verbose).unwrap();
-        assert!(
-            !ws_dir.exists(),
-            "workspace dir should be removed after deletion"
-        );
-
-        // Verify it's gone from listing
-        let backend4 = crate::jj::JjBackend;
-        let deps4 = WorkspaceDeps {
-            backend: Box::new(backend4),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps4).unwrap();
-        assert!(
-            !entries.iter().any(|e| e.name == "test-ws"),
-            "test-ws should not appear after deletion"
-        );
-    }
-
-    #[test]
-    fn e2e_jj_workspace_with_spaces_in_name() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create a workspace with spaces in its name
-        new_workspace_inner(&deps, Some("my cool feature".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/my cool feature", dir_name));
-        assert!(ws_dir.exists(), "workspace dir should exist after creation");
-
-        // List and verify it shows up
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        let entries = list_workspace_entries_inner(&deps2).unwrap();
-        assert!(
-            entries.iter().any(|e| e.name == "my cool feature"),
-            "workspace with spaces should appear in listing, got: {:?}",
-            entries.iter().map(|e| &e.name).collect::<Vec<_>>()
-        );
-
-        // Switch to the workspace
-        let backend3 = crate::jj::JjBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        let switch_path = switch_workspace_inner(&deps3, "my cool feature").unwrap();
-        assert_eq!(switch_path, ws_dir);
-
-        // Delete the workspace
-        let backend4 = crate::jj::JjBackend;
-        let deps4 = WorkspaceDeps {
-            backend: Box::new(backend4),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        delete_workspace_inner(
-            &deps4,
-            Some("my cool feature".to_string()),
-            DeleteOutput::Verbose,
-        )
-        .unwrap();
-        assert!(
-            !ws_dir.exists(),
-            "workspace dir should be removed after deletion"
-        );
-
-        // Verify it's gone from listing
-        let backend5 = crate::jj::JjBackend;
-        let deps5 = WorkspaceDeps {
-            backend: Box::new(backend5),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps5).unwrap();
-        assert!(
-            !entries.iter().any(|e| e.name == "my cool feature"),
-            "workspace with spaces should not appear after deletion"
-        );
-    }
-
-    #[test]
-    fn e2e_jj_workspace_with_changes() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace and make changes in it
-        new_workspace_inner(&deps, Some("feature".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/feature", dir_name));
-
-        // Add a file (jj auto-tracks new files)
-        fs::write(ws_dir.join("hello.txt"), "hello world\n").unwrap();
-        // Set a description on the workspace's working copy
-        let ws_str = ws_dir.to_str().unwrap();
-        std::process::Command::new("jj")
-            .args(["--repository", ws_str, "describe", "-m", "add hello"])
-            .output()
-            .unwrap();
-
-        // List and check that the feature workspace has diff stats
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps2).unwrap();
-        let feat = entries.iter().find(|e| e.name == "feature").unwrap();
-        assert_eq!(feat.description.trim(), "add hello");
-        assert!(
-            feat.diff_stat.insertions > 0 || feat.diff_stat.files_changed > 0,
-            "feature workspace should show changes vs trunk: {:?}",
-            feat.diff_stat
-        );
-    }
-
-    #[test]
-    fn e2e_jj_rename_workspace() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace
-        new_workspace_inner(&deps, Some("old-name".to_string()), None, None).unwrap();
-        let old_path = dwm_base.join(format!("{}/old-name", dir_name));
-        assert!(old_path.exists());
-
-        // Rename it
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        rename_workspace_inner(&deps2, "old-name", "new-name").unwrap();
-
-        assert!(!old_path.exists(), "old dir should be gone");
-        assert!(
-            dwm_base.join(format!("{}/new-name", dir_name)).exists(),
-            "new dir should exist"
-        );
-
-        // Verify listing shows the new name
-        let backend3 = crate::jj::JjBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps3).unwrap();
-        assert!(entries.iter().any(|e| e.name == "new-name"));
-        assert!(!entries.iter().any(|e| e.name == "old-name"));
-    }
-
-    #[test]
-    fn e2e_jj_rename_stale_workspace() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace
-        new_workspace_inner(&deps, Some("my-ws".to_string()), None, None).unwrap();
-
-        // Make the workspace stale by committing in the default workspace,
-        // which advances the operation log past what my-ws has seen.
-        let main_str = main_repo.to_str().unwrap();
-        fs::write(main_repo.join("file.txt"), "content\n").unwrap();
-        std::process::Command::new("jj")
-            .args(["--repository", main_str, "describe", "-m", "advance op log"])
-            .output()
-            .unwrap();
-
-        // Rename should succeed despite stale working copy
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        rename_workspace_inner(&deps2, "my-ws", "renamed-ws").unwrap();
-
-        assert!(!dwm_base.join(format!("{}/my-ws", dir_name)).exists());
-        assert!(dwm_base.join(format!("{}/renamed-ws", dir_name)).exists());
-
-        // Verify listing shows the new name
-        let backend3 = crate::jj::JjBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo,
-            dwm_base,
-        };
-        let entries = list_workspace_entries_inner(&deps3).unwrap();
-        assert!(entries.iter().any(|e| e.name == "renamed-ws"));
-        assert!(!entries.iter().any(|e| e.name == "my-ws"));
-    }
-
-    #[test]
-    fn e2e_git_switch_workspace() {
-        assert!(git_available(), "git must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_git_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-
-        let dwm_base = tmp.path().join("dwm");
-        let backend = crate::git::GitBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create a workspace
-        new_workspace_inner(&deps, Some("switch-target".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/switch-target", dir_name));
-
-        // Switch to it
-        let backend2 = crate::git::GitBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        let path = switch_workspace_inner(&deps2, "switch-target").unwrap();
-        assert_eq!(path, ws_dir);
-
-        // Switch to main
-        let backend3 = crate::git::GitBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-        let path = switch_workspace_inner(&deps3, "main-worktree").unwrap();
-        assert_eq!(path, main_repo);
-    }
-
-    #[test]
-    fn e2e_jj_switch_workspace() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create a workspace
-        new_workspace_inner(&deps, Some("switch-target".to_string()), None, None).unwrap();
-        let ws_dir = dwm_base.join(format!("{}/switch-target", dir_name));
-
-        // Switch to it
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-        let path = switch_workspace_inner(&deps2, "switch-target").unwrap();
-        assert_eq!(path, ws_dir);
-
-        // Switch to main (default)
-        let backend3 = crate::jj::JjBackend;
-        let deps3 = WorkspaceDeps {
-            backend: Box::new(backend3),
-            cwd: main_repo.clone(),
-            dwm_base,
-        };
-        let path = switch_workspace_inner(&deps3, "default").unwrap();
-        assert_eq!(path, main_repo);
-    }
-
-    #[test]
-    fn e2e_jj_rename_redirects_when_inside() {
-        assert!(jj_available(), "jj must be installed to run this test");
-        let tmp = tempfile::tempdir().unwrap();
-        let repo_path = tmp.path().join("repos/myrepo");
-        fs::create_dir_all(&repo_path).unwrap();
-        let main_repo = init_jj_repo(&repo_path);
-        let dir_name = vcs::repo_dir_name(&main_repo);
-        let dwm_base = setup_dwm_dir_jj(tmp.path(), &dir_name, &main_repo);
-
-        let backend = crate::jj::JjBackend;
-        let deps = WorkspaceDeps {
-            backend: Box::new(backend),
-            cwd: main_repo.clone(),
-            dwm_base: dwm_base.clone(),
-        };
-
-        // Create workspace with a subdirectory
-        new_workspace_inner(&deps, Some("my-ws".to_string()), None, None).unwrap();
-        let ws_path = dwm_base.join(format!("{}/my-ws", dir_name));
-        let subdir = ws_path.join("src");
-        fs::create_dir_all(&subdir).unwrap();
-
-        // Rename while cwd is inside the workspace
-        let backend2 = crate::jj::JjBackend;
-        let deps2 = WorkspaceDeps {
-            backend: Box::new(backend2),
-            cwd: subdir,
-            dwm_base: dwm_base.clone(),
-        };
-        let redirect = rename_workspace_inner(&deps2, "my-ws", "renamed-ws").unwrap();
-        let redirect = redirect.expect("should redirect when cwd is inside renamed workspace");
-        assert_eq!(
-            redirect,
-            dwm_base.join(format!("{}/renamed-ws/src", dir_name))
-        );
-
-        // The new path should exist and contain the subdirectory
-        let new_ws = dwm_base.join(format!("{}/renamed-ws", dir_name));
-        assert!(new_ws.exists());
-        assert!(new_ws.join("src").exists());
-    }
 }